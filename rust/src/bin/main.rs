@@ -4,30 +4,63 @@
 //! Optionally serves a web dashboard for viewing and analyzing crashes.
 
 use bugstr::{
-    decompress_payload, parse_crash_content, AppState, CrashReport, CrashStorage, create_router,
-    MappingStore, Platform, Symbolicator, SymbolicationContext,
+    config, decode_payload, decompress_payload, ConfigFile, Profile, AppState, CrashReport, CrashStorage, create_router,
+    ExecHook, format_issue, ingest, ingest_file, IngestOutcome, issue_title, GroupStatus, MappingChange, MappingStore, MappingWatcher, Platform, PrunePolicy, ResultCache, Debuginfod, InAppRules, SymbolServer, Symbolicator, SymbolicationContext, SymbolicationError, SymbolicationMetrics, SignedNostrEvent, UnsignedNostrEvent, RumorKind, SCHEMA_VERSION,
+    notify_all, publish_event, rewrap, SendError, Hooks, run_export, ExportSummary, S3Client, S3Config, route_alerts, spike_alert, Alert, AnomalyDetector, Integration, AlertKind, Dsn, IntegrationConfig, Rule, RuleCondition, SentryIngest,
+    SmtpSecurity, WebhookConfig, Manifest, PendingManifest, ChunkReader,
 };
+use std::io::Read;
 use tokio::sync::Mutex;
 use chrono::{DateTime, Utc};
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use colored::Colorize;
 use futures_util::{SinkExt, StreamExt};
+use rayon::prelude::*;
 use nostr::nips::nip44;
 use nostr::prelude::*;
-use std::collections::HashSet;
-use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 const DEFAULT_RELAYS: &[&str] = &["wss://relay.damus.io", "wss://nos.lol"];
 const DEFAULT_DB_PATH: &str = "bugstr.db";
+const DEFAULT_MAPPINGS_DIR: &str = "mappings";
+const DEFAULT_PORT: u16 = 3000;
+const DEFAULT_BIND: IpAddr = IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
+const CI_DEFAULT_TIMEOUT_SECS: u64 = 60;
+/// Exit code for `--timeout` expiry, matching coreutils `timeout`.
+const TIMEOUT_EXIT_CODE: i32 = 124;
 
 #[derive(Parser)]
 #[command(name = "bugstr")]
 #[command(about = "Zero-infrastructure crash reporting — no server to run, no SaaS to pay for")]
 struct Cli {
+    /// Configuration file [default: ~/.config/bugstr/bugstr.toml]
+    #[arg(long, global = true, env = "BUGSTR_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Named profile from the configuration file [default: "default" if present]
+    #[arg(long, global = true, env = "BUGSTR_PROFILE")]
+    profile: Option<String>,
+
+    /// Abort the command after this many seconds (exit code 124)
+    #[arg(long, global = true, value_name = "SECS")]
+    timeout: Option<u64>,
+
+    /// Disable colored output (also honors $NO_COLOR)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Non-interactive mode for CI: no color, no TUI, and a default
+    /// timeout of 60 seconds for commands that finish on their own
+    /// unless --timeout is given
+    #[arg(long, global = true)]
+    ci: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -36,52 +69,148 @@ struct Cli {
 enum Commands {
     /// Listen for incoming crash reports (terminal output only)
     Listen {
-        /// Your private key (hex or nsec)
-        #[arg(short, long, env = "BUGSTR_PRIVKEY")]
-        privkey: String,
+        /// Your private key (hex or nsec) [default: profile key, then $BUGSTR_PRIVKEY]
+        #[arg(short, long)]
+        privkey: Option<String>,
 
-        /// Relay URLs to connect to
-        #[arg(short, long, default_values_t = DEFAULT_RELAYS.iter().map(|s| s.to_string()).collect::<Vec<_>>())]
+        /// Relay URLs to connect to [default: profile relays, then built-in relays]
+        #[arg(short, long, num_args = 1..)]
         relays: Vec<String>,
 
         /// Output format: pretty, json, or raw
         #[arg(short, long, default_value = "pretty")]
         format: OutputFormat,
+
+        /// Only show crashes created at or after this time (unix seconds,
+        /// RFC 3339, YYYY-MM-DD[ HH:MM], or a relative age like 12h, 2d)
+        #[arg(long, value_parser = parse_time_arg)]
+        since: Option<u64>,
+
+        /// Only show crashes created at or before this time; exits once
+        /// relays have sent their stored events
+        #[arg(long, value_parser = parse_time_arg)]
+        until: Option<u64>,
     },
 
     /// Run the web dashboard with crash collection
     Serve {
-        /// Your private key (hex or nsec)
-        #[arg(short, long, env = "BUGSTR_PRIVKEY")]
-        privkey: String,
+        /// Your private key (hex or nsec) [default: profile key, then $BUGSTR_PRIVKEY]
+        #[arg(short, long)]
+        privkey: Option<String>,
 
-        /// Relay URLs to connect to
-        #[arg(short, long, default_values_t = DEFAULT_RELAYS.iter().map(|s| s.to_string()).collect::<Vec<_>>())]
+        /// Relay URLs to connect to [default: profile relays, then built-in relays]
+        #[arg(short, long, num_args = 1..)]
         relays: Vec<String>,
 
-        /// Web server port
-        #[arg(long, default_value = "3000")]
-        port: u16,
+        /// Web server port [default: profile port, then 3000]
+        #[arg(long, env = "BUGSTR_PORT")]
+        port: Option<u16>,
+
+        /// Address to bind the web server to. The dashboard has no
+        /// authentication; use 0.0.0.0 only behind a trusted network or proxy.
+        /// [default: profile bind, then 127.0.0.1]
+        #[arg(long, env = "BUGSTR_BIND")]
+        bind: Option<IpAddr>,
 
-        /// Database file path
-        #[arg(long, default_value = DEFAULT_DB_PATH)]
-        db: PathBuf,
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
 
-        /// Directory containing mapping files for symbolication
+        /// Directory containing mapping files for symbolication [default: profile mappings]
         #[arg(long)]
         mappings: Option<PathBuf>,
+
+        /// URL template for downloading missing mappings, with {platform},
+        /// {app}, {version} and {file} placeholders [default: profile symbol_server]
+        #[arg(long)]
+        symbol_server: Option<String>,
+
+        /// debuginfod server URLs for native debug info by build ID
+        /// [default: profile debuginfod]
+        #[arg(long, env = "DEBUGINFOD_URLS", value_delimiter = ' ', num_args = 1..)]
+        debuginfod: Vec<String>,
+
+        /// Disable endpoints that modify data, for sharing the dashboard safely
+        #[arg(long)]
+        read_only: bool,
+
+        /// Command to run for each new crash, via `sh -c`. The crash JSON is
+        /// passed on stdin; placeholders {id}, {event_id}, {sender}, {app},
+        /// {version}, {exception}, {message}, {environment} and {release}
+        /// are substituted safely.
+        #[arg(long)]
+        exec: Option<String>,
+
+        /// Accept crashes from Sentry SDKs at /api/ingest/sentry, authenticated
+        /// with this DSN public key
+        #[arg(long, env = "BUGSTR_SENTRY_KEY")]
+        sentry_key: Option<String>,
+
+        /// POST alerts for new crashes, new groups, regressions, thresholds
+        /// and spikes as JSON to this URL (repeatable); registered as a
+        /// webhook integration [default: profile webhooks]
+        #[arg(long = "webhook-url", value_name = "URL")]
+        webhook_urls: Vec<String>,
+
+        /// Shared secret for the X-Bugstr-Signature HMAC of --webhook-url
+        #[arg(long, env = "BUGSTR_WEBHOOK_SECRET", hide_env_values = true)]
+        webhook_secret: Option<String>,
+
+        /// Delete crash reports older than this many days, checked hourly
+        /// [default: profile retention_days, else keep forever]
+        #[arg(long, value_name = "DAYS", value_parser = clap::value_parser!(u32).range(1..))]
+        retention_days: Option<u32>,
+
+        /// Largest chunked report to fetch and reassemble, in bytes
+        /// [default: profile max_chunked_size, then 16 MiB]
+        #[arg(long, value_name = "BYTES")]
+        max_chunked_size: Option<usize>,
+
+        /// Log every relay asked for chunks, not just a summary per report
+        #[arg(short, long, conflicts_with = "quiet")]
+        verbose: bool,
+
+        /// Don't log chunk-fetch progress; failed reports are still reported
+        #[arg(short, long)]
+        quiet: bool,
+    },
+
+    /// Re-gift-wrap incoming crash reports to other receivers, without
+    /// storing them
+    Forward {
+        /// Your private key (hex or nsec) [default: profile key, then $BUGSTR_PRIVKEY]
+        #[arg(short, long)]
+        privkey: Option<String>,
+
+        /// Relay URLs to receive from [default: profile relays, then built-in relays]
+        #[arg(short, long, num_args = 1..)]
+        relays: Vec<String>,
+
+        /// Downstream receiver pubkey (npub or hex, repeatable)
+        #[arg(long, required = true)]
+        to: Vec<String>,
+
+        /// Relay to publish forwarded reports to (repeatable) [default: the
+        /// relays received from]
+        #[arg(long = "publish-relay")]
+        publish_relays: Vec<String>,
+
+        /// Database remembering which reports were forwarded, so restarts
+        /// resume without resending [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
     },
 
     /// Show your receiver pubkey (npub)
     Pubkey {
-        /// Your private key (hex or nsec)
-        #[arg(short, long, env = "BUGSTR_PRIVKEY")]
-        privkey: String,
+        /// Your private key (hex or nsec) [default: profile key, then $BUGSTR_PRIVKEY]
+        #[arg(short, long)]
+        privkey: Option<String>,
     },
 
     /// Symbolicate a stack trace using mapping files
     Symbolicate {
-        /// Platform: android, electron, flutter, rust, go, python, react-native
+        /// Platform: android, electron, flutter, rust, go, python, react-native, ios, windows, unity
         #[arg(short = 'P', long)]
         platform: String,
 
@@ -89,9 +218,19 @@ enum Commands {
         #[arg(short, long, default_value = "-")]
         input: String,
 
-        /// Directory containing mapping files
-        #[arg(short, long, default_value = "mappings")]
-        mappings: PathBuf,
+        /// Directory containing mapping files [default: profile mappings, then ./mappings]
+        #[arg(short, long)]
+        mappings: Option<PathBuf>,
+
+        /// URL template for downloading missing mappings, with {platform},
+        /// {app}, {version} and {file} placeholders [default: profile symbol_server]
+        #[arg(long)]
+        symbol_server: Option<String>,
+
+        /// debuginfod server URLs for native debug info by build ID
+        /// [default: profile debuginfod]
+        #[arg(long, env = "DEBUGINFOD_URLS", value_delimiter = ' ', num_args = 1..)]
+        debuginfod: Vec<String>,
 
         /// Application ID (package name, bundle id, etc.)
         #[arg(short, long)]
@@ -105,88 +244,2200 @@ enum Commands {
         #[arg(short, long, default_value = "pretty")]
         format: SymbolicateFormat,
     },
+
+    /// Re-run symbolication over stored crashes (e.g. after uploading new mappings)
+    Resymbolicate {
+        /// Platform: android, electron, flutter, rust, go, python, react-native, ios, windows, unity
+        #[arg(short = 'P', long)]
+        platform: String,
+
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+
+        /// Directory containing mapping files [default: profile mappings, then ./mappings]
+        #[arg(short, long)]
+        mappings: Option<PathBuf>,
+
+        /// URL template for downloading missing mappings, with {platform},
+        /// {app}, {version} and {file} placeholders [default: profile symbol_server]
+        #[arg(long)]
+        symbol_server: Option<String>,
+
+        /// debuginfod server URLs for native debug info by build ID
+        /// [default: profile debuginfod]
+        #[arg(long, env = "DEBUGINFOD_URLS", value_delimiter = ' ', num_args = 1..)]
+        debuginfod: Vec<String>,
+
+        /// Only crashes from this app (matches the stored app name)
+        #[arg(short, long)]
+        app_id: Option<String>,
+
+        /// Only crashes from this app version
+        #[arg(short, long)]
+        version: Option<String>,
+    },
+
+    /// Decrypt gift wrap events from a file, reassembling chunked reports
+    /// from the chunk events beside them
+    Decrypt {
+        /// Your private key (hex or nsec) [default: profile key, then $BUGSTR_PRIVKEY]
+        #[arg(short, long)]
+        privkey: Option<String>,
+
+        /// File containing kind 1059 event JSON, and kind 10422 chunk events
+        /// of chunked reports (or - for stdin)
+        #[arg(short, long, default_value = "-")]
+        input: String,
+
+        /// Relays to fetch chunks missing from the input from, besides those
+        /// the manifest lists
+        #[arg(short, long, num_args = 1..)]
+        relays: Vec<String>,
+
+        /// Output format: pretty, json, or raw
+        #[arg(short, long, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Mark a crash group as resolved
+    Resolve {
+        /// Group key (fingerprint, as shown in the dashboard)
+        #[arg(short, long)]
+        group: String,
+
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+
+        /// Release that contains the fix
+        #[arg(long)]
+        in_release: Option<String>,
+    },
+
+    /// Mark a crash group as ignored
+    Ignore {
+        /// Group key (fingerprint, as shown in the dashboard)
+        #[arg(short, long)]
+        group: String,
+
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+
+    /// Reopen a resolved or ignored crash group
+    Reopen {
+        /// Group key (fingerprint, as shown in the dashboard)
+        #[arg(short, long)]
+        group: String,
+
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+
+    /// Insert crash payload files from a directory into the database (no relays)
+    Ingest {
+        /// Directory containing crash payload files
+        dir: PathBuf,
+
+        /// Keep running and ingest new files as they appear
+        #[arg(short, long)]
+        watch: bool,
+
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+
+    /// Browse and triage crashes in an interactive terminal UI
+    Tui {
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+
+    /// Format a stored crash as a markdown issue report
+    Issue {
+        /// Crash ID (as shown in the dashboard)
+        id: i64,
+
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+
+        /// Print only the issue title
+        #[arg(long)]
+        title: bool,
+    },
+
+    /// Measure connect time, NIP-11 limits and round-trip latency for relays
+    RelayTest {
+        /// Relay URLs to test [default: profile relays, then built-in relays]
+        #[arg(short, long, num_args = 1..)]
+        relays: Vec<String>,
+
+        /// Per-relay timeout in seconds
+        #[arg(long, default_value = "10")]
+        relay_timeout: u64,
+    },
+
+    /// Show crash totals and symbolication metrics of a running dashboard
+    Stats {
+        /// Dashboard URL
+        #[arg(long, default_value = "http://127.0.0.1:3000")]
+        url: String,
+
+        /// Print the raw JSON from /api/stats
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Generate a shell completion script
+    Completions {
+        /// Shell to generate completions for
+        shell: clap_complete::Shell,
+    },
+
+    /// Database maintenance
+    Db {
+        #[command(subcommand)]
+        command: DbCommands,
+    },
+
+    /// Mapping file maintenance
+    Mappings {
+        #[command(subcommand)]
+        command: MappingsCommands,
+    },
+
+    /// Manage integrations notified of crash alerts
+    Integrations {
+        #[command(subcommand)]
+        command: IntegrationsCommands,
+    },
+
+    /// Manage alert rules deciding when integrations are notified
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommands,
+    },
+
+    /// Chunked crash reports still being fetched
+    Chunks {
+        #[command(subcommand)]
+        command: ChunksCommands,
+    },
 }
 
-#[derive(Clone, Debug, clap::ValueEnum)]
-enum OutputFormat {
-    Pretty,
-    Json,
-    Raw,
+#[derive(Subcommand)]
+enum ChunksCommands {
+    /// List chunked reports whose chunks haven't all been fetched
+    Status {
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
 }
 
-#[derive(Clone, Debug, clap::ValueEnum)]
-enum SymbolicateFormat {
-    Pretty,
-    Json,
+#[derive(Subcommand)]
+enum RulesCommands {
+    /// Add a rule
+    Add {
+        /// Rule name, shown in listings
+        #[arg(long, global = true)]
+        name: Option<String>,
+
+        /// Only watch crashes from this app [default: every app]
+        #[arg(long, global = true)]
+        app: Option<String>,
+
+        /// Integration to notify (repeatable) [default: all of the app's integrations]
+        #[arg(long = "integration", global = true)]
+        integrations: Vec<i64>,
+
+        /// Minutes before the rule can fire again for the same group
+        #[arg(long, global = true, default_value_t = bugstr::notify::rules::DEFAULT_COOLDOWN_MINUTES)]
+        cooldown: i64,
+
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long, global = true)]
+        db: Option<PathBuf>,
+
+        #[command(subcommand)]
+        condition: NewRuleCondition,
+    },
+
+    /// List rules
+    List {
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+
+    /// Remove a rule
+    Remove {
+        /// Rule ID (as shown by `bugstr rules list`)
+        id: i64,
+
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
 }
 
-/// A received crash report ready for storage.
-struct ReceivedCrash {
-    event_id: String,
-    sender_pubkey: String,
-    created_at: i64,
-    content: String,
+#[derive(Subcommand)]
+enum NewRuleCondition {
+    /// Fire when a crash starts a new group
+    NewGroup,
+
+    /// Fire when a crash arrives in a group marked resolved
+    Regression,
+
+    /// Fire when a group has more than COUNT crashes in WINDOW minutes
+    Rate {
+        /// Crash count to exceed
+        #[arg(long)]
+        count: i64,
+
+        /// Window in minutes
+        #[arg(long, value_parser = clap::value_parser!(i64).range(1..))]
+        window: i64,
+    },
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+#[derive(Subcommand)]
+enum DbCommands {
+    /// Apply pending schema migrations
+    Migrate {
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
 
-    match cli.command {
-        Commands::Listen {
-            privkey,
-            relays,
-            format,
-        } => {
-            listen(&privkey, &relays, format).await?;
-        }
-        Commands::Serve {
-            privkey,
-            relays,
-            port,
-            db,
-            mappings,
-        } => {
-            serve(&privkey, &relays, port, db, mappings).await?;
-        }
-        Commands::Pubkey { privkey } => {
-            show_pubkey(&privkey)?;
-        }
-        Commands::Symbolicate {
-            platform,
-            input,
-            mappings,
-            app_id,
-            version,
-            format,
-        } => {
-            symbolicate_stack(&platform, &input, &mappings, app_id, version, format)?;
-        }
-    }
+    /// Write a consistent copy of the database (safe while serving)
+    Backup {
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
 
-    Ok(())
+        /// Destination file (must not exist)
+        output: PathBuf,
+    },
+
+    /// Upload a JSONL export and a backup to the profile's S3 bucket
+    /// (`serve` does this every `interval_hours`)
+    Export {
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MappingsCommands {
+    /// Check mapping files for format problems (exits 1 on errors)
+    Verify {
+        /// Mapping files to check [default: every mapping in the mappings directory]
+        files: Vec<PathBuf>,
+
+        /// Directory containing mapping files [default: profile mappings, then ./mappings]
+        #[arg(short, long)]
+        mappings: Option<PathBuf>,
+
+        /// Platform of the given files [default: from <platform>/<app>/<version>/<file>]
+        #[arg(short = 'P', long)]
+        platform: Option<String>,
+    },
+
+    /// Save a mapping file for an app version, locally or on a running server
+    Upload {
+        /// Mapping file (mapping.txt, *.map, symbols, dSYM archive, ...)
+        file: PathBuf,
+
+        /// Platform: android, electron, flutter, rust, go, python, react-native, ios, windows, unity
+        #[arg(short = 'P', long)]
+        platform: String,
+
+        /// Application ID (package name, bundle id, etc.)
+        #[arg(short, long)]
+        app_id: String,
+
+        /// Application version
+        #[arg(short, long)]
+        version: String,
+
+        /// File name to store it under [default: the file's own name]
+        #[arg(long)]
+        filename: Option<String>,
+
+        /// Upload to the `bugstr serve` dashboard at this URL instead of
+        /// writing to the mappings directory
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Directory containing mapping files [default: profile mappings, then ./mappings]
+        #[arg(short, long)]
+        mappings: Option<PathBuf>,
+    },
+
+    /// List the app versions that have mappings
+    List {
+        /// Directory containing mapping files [default: profile mappings, then ./mappings]
+        #[arg(short, long)]
+        mappings: Option<PathBuf>,
+    },
+
+    /// Delete the mappings of an app version
+    Delete {
+        /// Platform: android, electron, flutter, rust, go, python, react-native, ios, windows, unity
+        #[arg(short = 'P', long)]
+        platform: String,
+
+        /// Application ID (package name, bundle id, etc.)
+        #[arg(short, long)]
+        app_id: String,
+
+        /// Application version
+        #[arg(short, long)]
+        version: String,
+
+        /// Directory containing mapping files [default: profile mappings, then ./mappings]
+        #[arg(short, long)]
+        mappings: Option<PathBuf>,
+    },
+
+    /// Replace identical mapping files with hard links to a single copy
+    Dedup {
+        /// Directory containing mapping files [default: profile mappings, then ./mappings]
+        #[arg(short, long)]
+        mappings: Option<PathBuf>,
+    },
+
+    /// Remove mappings of old versions (needs --keep and/or --older-than)
+    Prune {
+        /// Keep the newest N versions of each app
+        #[arg(long, value_name = "N")]
+        keep: Option<usize>,
+
+        /// Only remove versions last changed before this time (unix seconds,
+        /// RFC 3339, YYYY-MM-DD[ HH:MM], or an age like 90d)
+        #[arg(long, value_parser = parse_time_arg)]
+        older_than: Option<u64>,
+
+        /// List the versions that would be removed without removing them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Directory containing mapping files [default: profile mappings, then ./mappings]
+        #[arg(short, long)]
+        mappings: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum IntegrationsCommands {
+    /// Add an integration
+    Add {
+        /// Only handle crashes from this app [default: every app]
+        #[arg(long, global = true)]
+        app: Option<String>,
+
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long, global = true)]
+        db: Option<PathBuf>,
+
+        #[command(subcommand)]
+        integration: NewIntegration,
+    },
+
+    /// List integrations (secrets are not shown)
+    List {
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+
+    /// Remove an integration
+    Remove {
+        /// Integration ID (as shown by `bugstr integrations list`)
+        id: i64,
+
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+
+    /// Show recent alert deliveries and failures
+    Deliveries {
+        /// Maximum number of deliveries to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+
+    /// Resolve crash groups whose Jira issue was closed (`serve` does this
+    /// every five minutes)
+    Sync {
+        /// Database file path [default: profile db, then bugstr.db]
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum NewIntegration {
+    /// Open a GitLab issue for each new crash group
+    Gitlab {
+        /// Project ID or path, e.g. group/app
+        #[arg(long)]
+        project: String,
+
+        /// Access token with the api scope
+        #[arg(long, env = "GITLAB_TOKEN", hide_env_values = true)]
+        token: String,
+
+        /// GitLab instance URL, for self-hosted GitLab
+        #[arg(long, default_value = "https://gitlab.com")]
+        url: String,
+
+        /// Label for created issues (repeatable)
+        #[arg(long = "label")]
+        labels: Vec<String>,
+    },
+
+    /// Open a Jira issue for each new crash group
+    Jira {
+        /// Site URL, e.g. https://example.atlassian.net
+        #[arg(long)]
+        url: String,
+
+        /// Project key, e.g. APP
+        #[arg(long)]
+        project: String,
+
+        /// Account email, to use an API token on Jira Cloud
+        #[arg(long)]
+        email: Option<String>,
+
+        /// API token (with --email) or personal access token
+        #[arg(long, env = "JIRA_API_TOKEN", hide_env_values = true)]
+        token: String,
+
+        /// Issue type for created issues
+        #[arg(long, default_value = bugstr::notify::jira::DEFAULT_ISSUE_TYPE)]
+        issue_type: String,
+
+        /// Label for created issues (repeatable)
+        #[arg(long = "label")]
+        labels: Vec<String>,
+
+        /// Resolve a crash group once its issue is closed
+        #[arg(long)]
+        sync_status: bool,
+    },
+
+    /// POST alerts as JSON to a URL
+    Webhook {
+        /// URL to POST to
+        #[arg(long)]
+        url: String,
+
+        /// Shared secret for the X-Bugstr-Signature HMAC
+        #[arg(long, env = "BUGSTR_WEBHOOK_SECRET", hide_env_values = true)]
+        secret: Option<String>,
+
+        /// Alert to send: new_crash, new_group, regression, threshold or spike
+        /// (repeatable) [default: all]
+        #[arg(long = "event", value_parser = parse_alert_kind)]
+        events: Vec<AlertKind>,
+    },
+
+    /// Email new crash groups, threshold alerts and spikes over SMTP
+    Email {
+        /// SMTP server host name
+        #[arg(long)]
+        host: String,
+
+        /// SMTP port [default: 587, 465 with --security tls, 25 with none]
+        #[arg(long)]
+        port: Option<u16>,
+
+        /// Connection security: starttls, tls or none
+        #[arg(long, default_value = "starttls", value_parser = parse_smtp_security)]
+        security: SmtpSecurity,
+
+        /// SMTP user name
+        #[arg(long)]
+        username: Option<String>,
+
+        /// SMTP password
+        #[arg(long, env = "BUGSTR_SMTP_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+
+        /// Sender address, e.g. "Bugstr <bugstr@example.com>"
+        #[arg(long)]
+        from: String,
+
+        /// Recipient address (repeatable)
+        #[arg(long, required = true)]
+        to: Vec<String>,
+    },
+
+    /// Push alerts to an ntfy topic
+    Ntfy {
+        /// Topic to publish to (anyone who knows the name can subscribe)
+        #[arg(long)]
+        topic: String,
+
+        /// ntfy server, for self-hosted ntfy
+        #[arg(long, default_value = bugstr::notify::ntfy::DEFAULT_SERVER)]
+        server: String,
+
+        /// Access token for protected topics
+        #[arg(long, env = "NTFY_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+
+        /// Alert to send: new_crash, new_group, regression, threshold or spike
+        /// (repeatable) [default: new_group]
+        #[arg(long = "event", value_parser = parse_alert_kind)]
+        events: Vec<AlertKind>,
+    },
+
+    /// Message a Telegram chat about new crash groups and regressions
+    Telegram {
+        /// Bot token from @BotFather
+        #[arg(long, env = "TELEGRAM_BOT_TOKEN", hide_env_values = true)]
+        token: String,
+
+        /// Chat ID (negative for groups), or @channel for public channels
+        #[arg(long, allow_hyphen_values = true)]
+        chat_id: String,
+
+        /// Message template with placeholders such as {summary}, {exception},
+        /// {count}, {app}, {version} and {message} [default: summary and message]
+        #[arg(long)]
+        template: Option<String>,
+    },
+
+    /// Send maintainers a NIP-17 DM for new groups, regressions and thresholds
+    Nostr {
+        /// Maintainer public key (npub or hex, repeatable)
+        #[arg(long, required = true)]
+        to: Vec<String>,
+
+        /// Relay to publish to (repeatable) [default: built-in relays]
+        #[arg(long = "relay")]
+        relays: Vec<String>,
+
+        /// Key to send from (hex or nsec) [default: a new key]
+        #[arg(long, env = "BUGSTR_ALERT_PRIVKEY", hide_env_values = true)]
+        privkey: Option<String>,
+    },
+
+    /// Post new groups, regressions and thresholds to a Matrix room
+    Matrix {
+        /// Room ID, e.g. !abc:matrix.org (the token's user must have joined it)
+        #[arg(long)]
+        room_id: String,
+
+        /// Access token
+        #[arg(long, env = "MATRIX_TOKEN", hide_env_values = true)]
+        token: String,
+
+        /// Homeserver URL
+        #[arg(long, default_value = "https://matrix.org")]
+        homeserver: String,
+    },
+
+    /// Post every alert except new crashes to a Slack incoming webhook
+    Slack {
+        /// Incoming webhook URL
+        #[arg(long, env = "SLACK_WEBHOOK_URL", hide_env_values = true)]
+        webhook_url: String,
+
+        /// Dashboard URL to link from messages, e.g. https://bugstr.example.com
+        #[arg(long)]
+        dashboard_url: Option<String>,
+    },
+
+    /// Post new crash groups and regressions to a Discord channel webhook
+    Discord {
+        /// Channel webhook URL
+        #[arg(long, env = "DISCORD_WEBHOOK_URL", hide_env_values = true)]
+        webhook_url: String,
+
+        /// Dashboard URL to link from embeds, e.g. https://bugstr.example.com
+        #[arg(long)]
+        dashboard_url: Option<String>,
+    },
+
+    /// Forward every stored crash to a Sentry project, e.g. while evaluating
+    /// bugstr alongside it
+    Sentry {
+        /// Project DSN, e.g. https://<key>@o1.ingest.sentry.io/42
+        #[arg(long, env = "SENTRY_DSN", value_parser = parse_sentry_dsn)]
+        dsn: String,
+    },
+}
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+enum OutputFormat {
+    Pretty,
+    Json,
+    Raw,
+}
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+enum SymbolicateFormat {
+    Pretty,
+    Json,
+}
+
+/// A received crash report ready for storage.
+struct ReceivedCrash {
+    event_id: String,
+    sender_pubkey: String,
+    created_at: i64,
+    content: String,
+    /// Randomized `created_at` of the gift wrap, for the relay cursor.
+    wrap_created_at: i64,
+    /// Set for chunked reports, whose `content` is fetched separately.
+    manifest: Option<Manifest>,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    if cli.no_color || cli.ci {
+        colored::control::set_override(false);
+    }
+
+    // Runs on a worker thread, so it also fires while a command blocks the main thread
+    let ci_timeout = (cli.ci && !cli.command.runs_until_stopped()).then_some(CI_DEFAULT_TIMEOUT_SECS);
+    if let Some(secs) = cli.timeout.or(ci_timeout) {
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(secs)).await;
+            eprintln!("{} Timed out after {}s", "error".red(), secs);
+            std::process::exit(TIMEOUT_EXIT_CODE);
+        });
+    }
+
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{} {}", "error".red(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+impl Commands {
+    /// Whether the command keeps running until stopped, so `--ci` must not
+    /// give it a default timeout.
+    fn runs_until_stopped(&self) -> bool {
+        matches!(
+            self,
+            Commands::Serve { .. }
+                | Commands::Forward { .. }
+                | Commands::Listen { until: None, .. }
+                | Commands::Ingest { watch: true, .. }
+        )
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let settings = Settings::load(cli.config.as_deref(), cli.profile.as_deref())?;
+
+    match cli.command {
+        Commands::Listen {
+            privkey,
+            relays,
+            format,
+            since,
+            until,
+        } => {
+            if let (Some(since), Some(until)) = (since, until) {
+                if since > until {
+                    return Err("--since must be before --until".into());
+                }
+            }
+            let window = TimeWindow { since, until };
+            listen(&settings.privkey(privkey)?, &settings.relays(relays), format, window).await?;
+        }
+        Commands::Serve {
+            privkey,
+            relays,
+            port,
+            bind,
+            db,
+            mappings,
+            symbol_server,
+            debuginfod,
+            read_only,
+            exec,
+            sentry_key,
+            webhook_urls,
+            webhook_secret,
+            retention_days,
+            max_chunked_size,
+            verbose,
+            quiet,
+        } => {
+            let options = ServeOptions {
+                addr: settings.addr(bind, port),
+                db_path: settings.db(db),
+                mappings_dir: mappings.or_else(|| settings.profile.mappings.clone()),
+                remote: settings.remote_symbols(symbol_server, debuginfod),
+                read_only,
+                exec_hook: exec.map(ExecHook::new),
+                sentry_key,
+                s3: settings.profile.s3.clone(),
+                webhooks: settings.webhooks(webhook_urls, webhook_secret),
+                retention_days: retention_days
+                    .or(settings.profile.retention_days)
+                    .filter(|days| *days > 0),
+                max_chunked_size: max_chunked_size
+                    .or(settings.profile.max_chunked_size)
+                    .unwrap_or(DEFAULT_MAX_CHUNKED_SIZE),
+                chunk_progress: ChunkProgress::from_flags(verbose, quiet),
+            };
+            serve(&settings.privkey(privkey)?, &settings.relays(relays), options).await?;
+        }
+        Commands::Forward {
+            privkey,
+            relays,
+            to,
+            publish_relays,
+            db,
+        } => {
+            let relays = settings.relays(relays);
+            let publish_relays = if publish_relays.is_empty() {
+                relays.clone()
+            } else {
+                publish_relays
+            };
+            let db_path = settings.db(db);
+            forward(&settings.privkey(privkey)?, &relays, &to, &publish_relays, &db_path).await?;
+        }
+        Commands::Pubkey { privkey } => {
+            show_pubkey(&settings.privkey(privkey)?)?;
+        }
+        Commands::Symbolicate {
+            platform,
+            input,
+            mappings,
+            symbol_server,
+            debuginfod,
+            app_id,
+            version,
+            format,
+        } => {
+            let mappings = settings.mappings(mappings);
+            let remote = settings.remote_symbols(symbol_server, debuginfod);
+            symbolicate_stack(&platform, &input, &mappings, remote, app_id, version, format)?;
+        }
+        Commands::Resymbolicate {
+            platform,
+            db,
+            mappings,
+            symbol_server,
+            debuginfod,
+            app_id,
+            version,
+        } => {
+            resymbolicate(
+                &platform,
+                &settings.db(db),
+                &settings.mappings(mappings),
+                settings.remote_symbols(symbol_server, debuginfod),
+                app_id.as_deref(),
+                version.as_deref(),
+            )?;
+        }
+        Commands::Decrypt {
+            privkey,
+            input,
+            relays,
+            format,
+        } => {
+            decrypt_events(&settings.privkey(privkey)?, &input, &relays, format).await?;
+        }
+        Commands::Resolve {
+            group,
+            db,
+            in_release,
+        } => {
+            set_group_status(
+                &settings.db(db),
+                &group,
+                GroupStatus::Resolved,
+                in_release.as_deref(),
+            )?;
+        }
+        Commands::Ignore { group, db } => {
+            set_group_status(&settings.db(db), &group, GroupStatus::Ignored, None)?;
+        }
+        Commands::Reopen { group, db } => {
+            set_group_status(&settings.db(db), &group, GroupStatus::Open, None)?;
+        }
+        Commands::Ingest { dir, watch, db } => {
+            ingest_dir(&settings.db(db), &dir, watch)?;
+        }
+        Commands::Tui { db } => {
+            if cli.ci {
+                return Err("bugstr tui is interactive and unavailable in CI mode".into());
+            }
+            let storage = CrashStorage::open(settings.db(db))?;
+            bugstr::tui::run(&storage)?;
+        }
+        Commands::Issue { id, db, title } => {
+            print_issue(&settings.db(db), id, title)?;
+        }
+        Commands::RelayTest {
+            relays,
+            relay_timeout,
+        } => {
+            relay_test(&settings.relays(relays), relay_timeout).await?;
+        }
+        Commands::Stats { url, json } => {
+            show_stats(&url, json).await?;
+        }
+        Commands::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "bugstr", &mut std::io::stdout());
+        }
+        Commands::Db { command } => match command {
+            DbCommands::Migrate { db } => migrate_db(&settings.db(db))?,
+            DbCommands::Backup { db, output } => backup_db(&settings.db(db), &output)?,
+            DbCommands::Export { db } => {
+                let Some(s3) = settings.profile.s3.clone() else {
+                    return Err(format!(
+                        "Profile '{}' has no [s3] settings",
+                        settings.profile_name
+                    )
+                    .into());
+                };
+                let client = S3Client::new(s3)?;
+                let db_path = settings.db(db);
+                let summary =
+                    tokio::task::spawn_blocking(move || run_export(&db_path, &client)).await??;
+                print_export(&summary);
+            }
+        },
+        Commands::Mappings { command } => match command {
+            MappingsCommands::Verify {
+                files,
+                mappings,
+                platform,
+            } => verify_mappings(&settings.mappings(mappings), &files, platform.as_deref())?,
+            MappingsCommands::Upload {
+                file,
+                platform,
+                app_id,
+                version,
+                filename,
+                url,
+                mappings,
+            } => {
+                let filename = match filename {
+                    Some(filename) => filename,
+                    None => file
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .ok_or_else(|| format!("Not a file: {}", file.display()))?,
+                };
+                let target = UploadTarget {
+                    platform: Platform::from_str(&platform),
+                    app_id,
+                    version,
+                    filename,
+                };
+                match url {
+                    Some(url) => upload_mapping_remote(&url, &file, &target).await?,
+                    None => upload_mapping(&settings.mappings(mappings), &file, &target)?,
+                }
+            }
+            MappingsCommands::List { mappings } => list_mappings(&settings.mappings(mappings))?,
+            MappingsCommands::Delete {
+                platform,
+                app_id,
+                version,
+                mappings,
+            } => delete_mapping(
+                &settings.mappings(mappings),
+                &Platform::from_str(&platform),
+                &app_id,
+                &version,
+            )?,
+            MappingsCommands::Dedup { mappings } => dedup_mappings(&settings.mappings(mappings))?,
+            MappingsCommands::Prune {
+                keep,
+                older_than,
+                dry_run,
+                mappings,
+            } => {
+                let policy = PrunePolicy {
+                    keep_latest: keep,
+                    older_than: older_than
+                        .map(|secs| std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs)),
+                };
+                prune_mappings(&settings.mappings(mappings), &policy, dry_run)?
+            }
+        },
+        Commands::Integrations { command } => match command {
+            IntegrationsCommands::Add {
+                app,
+                db,
+                integration,
+            } => {
+                let config = match integration {
+                    NewIntegration::Gitlab {
+                        project,
+                        token,
+                        url,
+                        labels,
+                    } => IntegrationConfig::Gitlab {
+                        url,
+                        project,
+                        token,
+                        labels,
+                    },
+                    NewIntegration::Jira {
+                        url,
+                        project,
+                        email,
+                        token,
+                        issue_type,
+                        labels,
+                        sync_status,
+                    } => IntegrationConfig::Jira {
+                        url,
+                        project,
+                        email,
+                        token,
+                        issue_type: Some(issue_type),
+                        labels,
+                        sync_status,
+                    },
+                    NewIntegration::Webhook {
+                        url,
+                        secret,
+                        events,
+                    } => IntegrationConfig::Webhook {
+                        url,
+                        secret,
+                        events,
+                    },
+                    NewIntegration::Email {
+                        host,
+                        port,
+                        security,
+                        username,
+                        password,
+                        from,
+                        to,
+                    } => IntegrationConfig::Email {
+                        host,
+                        port,
+                        security,
+                        username,
+                        password,
+                        from,
+                        to,
+                    },
+                    NewIntegration::Ntfy {
+                        topic,
+                        server,
+                        token,
+                        events,
+                    } => IntegrationConfig::Ntfy {
+                        server,
+                        topic,
+                        token,
+                        events,
+                    },
+                    NewIntegration::Telegram {
+                        token,
+                        chat_id,
+                        template,
+                    } => IntegrationConfig::Telegram {
+                        token,
+                        chat_id,
+                        template,
+                    },
+                    NewIntegration::Nostr {
+                        to,
+                        relays,
+                        privkey,
+                    } => nostr_integration(&to, relays, privkey.as_deref())?,
+                    NewIntegration::Matrix {
+                        room_id,
+                        token,
+                        homeserver,
+                    } => IntegrationConfig::Matrix {
+                        homeserver,
+                        token,
+                        room_id,
+                    },
+                    NewIntegration::Slack {
+                        webhook_url,
+                        dashboard_url,
+                    } => IntegrationConfig::Slack {
+                        webhook_url,
+                        dashboard_url,
+                    },
+                    NewIntegration::Discord {
+                        webhook_url,
+                        dashboard_url,
+                    } => IntegrationConfig::Discord {
+                        webhook_url,
+                        dashboard_url,
+                    },
+                    NewIntegration::Sentry { dsn } => IntegrationConfig::Sentry { dsn },
+                };
+                add_integration(&settings.db(db), app.as_deref(), &config)?
+            }
+            IntegrationsCommands::List { db } => list_integrations(&settings.db(db))?,
+            IntegrationsCommands::Remove { id, db } => {
+                let storage = CrashStorage::open(settings.db(db))?;
+                if !storage.delete_integration(id)? {
+                    return Err(format!("No integration {}", id).into());
+                }
+                println!("{} Removed integration {}", "✓".green(), id);
+            }
+            IntegrationsCommands::Deliveries { limit, db } => {
+                list_deliveries(&settings.db(db), limit)?
+            }
+            IntegrationsCommands::Sync { db } => {
+                let resolved = sync_issue_status(&settings.db(db))?;
+                println!("{} Resolved {} crash groups", "✓".green(), resolved);
+            }
+        },
+        Commands::Rules { command } => match command {
+            RulesCommands::Add {
+                name,
+                app,
+                integrations,
+                cooldown,
+                db,
+                condition,
+            } => {
+                let condition = match condition {
+                    NewRuleCondition::NewGroup => RuleCondition::NewGroup,
+                    NewRuleCondition::Regression => RuleCondition::Regression,
+                    NewRuleCondition::Rate { count, window } => RuleCondition::GroupRate {
+                        count,
+                        window_minutes: window,
+                    },
+                };
+                let rule = Rule {
+                    id: 0,
+                    name: name.unwrap_or_else(|| condition.describe()),
+                    app_name: app,
+                    condition,
+                    integration_ids: integrations,
+                    cooldown_minutes: cooldown,
+                    created_at: Utc::now().timestamp(),
+                };
+                add_rule(&settings.db(db), &rule)?
+            }
+            RulesCommands::List { db } => list_rules(&settings.db(db))?,
+            RulesCommands::Remove { id, db } => {
+                let storage = CrashStorage::open(settings.db(db))?;
+                if !storage.delete_rule(id)? {
+                    return Err(format!("No rule {}", id).into());
+                }
+                println!("{} Removed rule {}", "✓".green(), id);
+            }
+        },
+        Commands::Chunks { command } => match command {
+            ChunksCommands::Status { db } => chunks_status(&settings.db(db))?,
+        },
+    }
+
+    Ok(())
+}
+
+/// Values from the selected configuration profile, used where a flag is omitted.
+struct Settings {
+    profile_name: String,
+    profile: Profile,
+}
+
+impl Settings {
+    /// Load the configuration file and select a profile.
+    ///
+    /// A missing file is only an error when `--config` was given explicitly.
+    fn load(config: Option<&Path>, profile: Option<&str>) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = match config {
+            Some(path) => ConfigFile::load(path)?,
+            None => match ConfigFile::default_path().filter(|path| path.exists()) {
+                Some(path) => ConfigFile::load(path)?,
+                None => ConfigFile::default(),
+            },
+        };
+
+        Ok(Self {
+            profile_name: profile.unwrap_or(config::DEFAULT_PROFILE).to_string(),
+            profile: file.profile(profile)?,
+        })
+    }
+
+    /// Private key from the flag, the profile, or `$BUGSTR_PRIVKEY`.
+    fn privkey(&self, flag: Option<String>) -> Result<String, Box<dyn std::error::Error>> {
+        if let Some(key) = flag {
+            return Ok(key);
+        }
+        if let Some(key) = self.profile.load_privkey(&self.profile_name)? {
+            return Ok(key);
+        }
+        std::env::var("BUGSTR_PRIVKEY").map_err(|_| {
+            "No private key: pass --privkey, set one in the profile, or set BUGSTR_PRIVKEY".into()
+        })
+    }
+
+    /// Relays from the flag, the profile, or the built-in defaults.
+    fn relays(&self, flag: Vec<String>) -> Vec<String> {
+        if !flag.is_empty() {
+            return flag;
+        }
+        self.profile
+            .relays
+            .clone()
+            .unwrap_or_else(|| DEFAULT_RELAYS.iter().map(|s| s.to_string()).collect())
+    }
+
+    /// Database path from the flag, the profile, or `bugstr.db`.
+    fn db(&self, flag: Option<PathBuf>) -> PathBuf {
+        flag.or_else(|| self.profile.db.clone())
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_DB_PATH))
+    }
+
+    /// Mappings directory from the flag, the profile, or `./mappings`.
+    fn mappings(&self, flag: Option<PathBuf>) -> PathBuf {
+        flag.or_else(|| self.profile.mappings.clone())
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_MAPPINGS_DIR))
+    }
+
+    /// Dashboard address from the flags, the profile, or 127.0.0.1:3000.
+    fn addr(&self, bind: Option<IpAddr>, port: Option<u16>) -> SocketAddr {
+        SocketAddr::new(
+            bind.or(self.profile.bind).unwrap_or(DEFAULT_BIND),
+            port.or(self.profile.port).unwrap_or(DEFAULT_PORT),
+        )
+    }
+
+    /// Webhooks from the flags, or the profile's.
+    fn webhooks(&self, urls: Vec<String>, secret: Option<String>) -> Vec<WebhookConfig> {
+        if urls.is_empty() {
+            return self.profile.webhooks.clone();
+        }
+        urls.into_iter()
+            .map(|url| WebhookConfig {
+                url,
+                secret: secret.clone(),
+                events: Vec::new(),
+            })
+            .collect()
+    }
+
+    /// Symbol server and debuginfod servers from the flags or the profile.
+    fn remote_symbols(&self, symbol_server: Option<String>, debuginfod: Vec<String>) -> RemoteSymbols {
+        let debuginfod = if debuginfod.is_empty() {
+            self.profile.debuginfod.clone().unwrap_or_default()
+        } else {
+            debuginfod
+        };
+        RemoteSymbols {
+            in_app_rules: self.profile.in_app.clone(),
+            symbol_server: symbol_server
+                .or_else(|| self.profile.symbol_server.clone())
+                .map(SymbolServer::new),
+            debuginfod: (!debuginfod.is_empty()).then(|| {
+                let cache_dir = Debuginfod::default_cache_dir()
+                    .unwrap_or_else(|| std::env::temp_dir().join("debuginfod_client"));
+                Debuginfod::new(debuginfod, cache_dir)
+            }),
+        }
+    }
+}
+
+/// Remote sources for mappings and debug info missing from the mappings
+/// directory, along with the profile's in-app rules.
+struct RemoteSymbols {
+    symbol_server: Option<SymbolServer>,
+    debuginfod: Option<Debuginfod>,
+    in_app_rules: HashMap<String, InAppRules>,
+}
+
+impl RemoteSymbols {
+    fn is_empty(&self) -> bool {
+        self.symbol_server.is_none() && self.debuginfod.is_none()
+    }
+
+    /// Attach the configured sources to `symbolicator`.
+    fn apply(self, mut symbolicator: Symbolicator) -> Symbolicator {
+        if let Some(server) = self.symbol_server {
+            symbolicator = symbolicator.with_symbol_server(server);
+        }
+        if let Some(debuginfod) = self.debuginfod {
+            symbolicator = symbolicator.with_debuginfod(debuginfod);
+        }
+        symbolicator.with_in_app_rules(self.in_app_rules)
+    }
+}
+
+fn parse_privkey(input: &str) -> Result<SecretKey, Box<dyn std::error::Error>> {
+    if input.starts_with("nsec") {
+        let secret = SecretKey::from_bech32(input)?;
+        Ok(secret)
+    } else {
+        let secret = SecretKey::from_hex(input)?;
+        Ok(secret)
+    }
+}
+
+fn show_pubkey(privkey: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let secret = parse_privkey(privkey)?;
+    let keys = Keys::new(secret);
+    let pubkey = keys.public_key();
+
+    println!("Receiver pubkey:");
+    println!("  npub: {}", pubkey.to_bech32()?);
+    println!("  hex:  {}", pubkey.to_hex());
+    println!();
+    println!("Add this pubkey to your app's bugstr configuration.");
+
+    Ok(())
+}
+
+/// Read the full contents of a file, or stdin if `input` is `"-"`.
+fn read_input(input: &str) -> std::io::Result<String> {
+    if input == "-" {
+        use std::io::Read;
+        let mut buf = String::new();
+        std::io::stdin().read_to_string(&mut buf)?;
+        Ok(buf)
+    } else {
+        std::fs::read_to_string(input)
+    }
+}
+
+/// Parse gift wrap events from exported JSON.
+///
+/// Accepts a single event object, a JSON array of events, a relay
+/// `["EVENT", <sub_id>, <event>]` message, or one of those per line
+/// (as produced by most relay export tools).
+fn parse_exported_events(text: &str) -> Result<Vec<Event>, Box<dyn std::error::Error>> {
+    fn collect(value: serde_json::Value, out: &mut Vec<Event>) -> Result<(), Box<dyn std::error::Error>> {
+        match value {
+            serde_json::Value::Array(items) => {
+                if items.first().and_then(|v| v.as_str()) == Some("EVENT") {
+                    if let Some(event) = items.into_iter().nth(2) {
+                        out.push(serde_json::from_value(event)?);
+                    }
+                } else {
+                    for item in items {
+                        collect(item, out)?;
+                    }
+                }
+            }
+            other => out.push(serde_json::from_value(other)?),
+        }
+        Ok(())
+    }
+
+    let mut events = Vec::new();
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(value) => collect(value, &mut events)?,
+        Err(_) => {
+            for line in text.lines().filter(|l| !l.trim().is_empty()) {
+                collect(serde_json::from_str(line)?, &mut events)?;
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+/// Decrypt exported gift wrap events offline.
+///
+/// Reads kind 1059 events from a file or stdin (see [`parse_exported_events`]
+/// for accepted shapes), unwraps gift wrap → seal → rumor with the receiver's
+/// private key, decompresses the payload, and prints each crash report.
+/// Chunked reports are reassembled from the chunk events in the input (see
+/// [`decrypt_reports`]); relays are only contacted for chunks it lacks.
+/// Useful for debugging transport issues without a relay connection.
+///
+/// Events that fail to unwrap are reported on stderr; the command fails only
+/// if no event could be decrypted.
+async fn decrypt_events(
+    privkey: &str,
+    input: &str,
+    relays: &[String],
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let secret = parse_privkey(privkey)?;
+    let keys = Keys::new(secret);
+
+    let events = parse_exported_events(&read_input(input)?)?;
+    if events.is_empty() {
+        return Err("No events found in input".into());
+    }
+
+    let reports = decrypt_reports(&keys, &events, relays).await;
+    if reports.is_empty() {
+        return Err("No gift wraps could be decrypted with this key".into());
+    }
+    for (event, mut rumor) in reports {
+        match format {
+            OutputFormat::Pretty => print_pretty(&rumor, event),
+            OutputFormat::Json => {
+                rumor.content = decompress_payload(&rumor.content).unwrap_or(rumor.content);
+                println!("{}", serde_json::to_string_pretty(&rumor)?);
+            }
+            OutputFormat::Raw => {
+                println!("{}", decompress_payload(&rumor.content).unwrap_or(rumor.content));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Unwraps the gift wraps among `events`, each with its rumor.
+///
+/// A chunked report's manifest is replaced by the payload reassembled from
+/// the kind 10422 chunk events among `events`, fetching any missing from the
+/// manifest's relays and `relays`. Events that fail are reported on stderr
+/// and skipped.
+async fn decrypt_reports<'a>(keys: &Keys, events: &'a [Event], relays: &[String]) -> Vec<(&'a Event, Rumor)> {
+    let chunks: HashMap<String, &Event> = events
+        .iter()
+        .filter(|event| event.kind.as_u16() == bugstr::kinds::KIND_CHUNK)
+        .map(|event| (event.id.to_hex(), event))
+        .collect();
+
+    let mut reports = Vec::new();
+    for event in events {
+        if event.kind.as_u16() == bugstr::kinds::KIND_CHUNK {
+            continue;
+        }
+        if event.kind != Kind::GiftWrap {
+            eprintln!(
+                "{} Skipping event {}: kind {} is not a gift wrap (1059)",
+                "warning".yellow(),
+                &event.id.to_hex()[..16],
+                event.kind.as_u16()
+            );
+            continue;
+        }
+
+        let mut rumor = match open_gift_wrap(keys, event) {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("{} Failed to unwrap gift wrap {}: {}", "✗".red(), &event.id.to_hex()[..16], e);
+                continue;
+            }
+        };
+        if rumor.kind == u64::from(RumorKind::Manifest.kind()) {
+            let assembled = match serde_json::from_str::<Manifest>(&rumor.content) {
+                Ok(manifest) => assemble_chunks(keys, &manifest, &chunks, relays).await,
+                Err(e) => Err(format!("invalid manifest: {}", e).into()),
+            };
+            match assembled {
+                Ok(content) => rumor.content = content,
+                Err(e) => {
+                    eprintln!(
+                        "{} Failed to reassemble chunked report {}: {}",
+                        "✗".red(),
+                        &event.id.to_hex()[..16],
+                        e
+                    );
+                    continue;
+                }
+            }
+        }
+        reports.push((event, rumor));
+    }
+    reports
+}
+
+/// Reassembles and decodes the payload `manifest` describes from `chunks`,
+/// fetching chunks missing from them from the manifest's relays, then
+/// `relays`. Every chunk is checked against the manifest.
+async fn assemble_chunks(
+    keys: &Keys,
+    manifest: &Manifest,
+    chunks: &HashMap<String, &Event>,
+    relays: &[String],
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut found: Vec<Option<String>> = vec![None; manifest.chunk_ids.len()];
+    let fill = |found: &mut Vec<Option<String>>, events: Vec<Event>| {
+        let mut ids = HashSet::new();
+        let wanted = events
+            .into_iter()
+            .filter(|event| ids.insert(event.id))
+            .filter_map(|event| {
+                let indices: Vec<usize> =
+                    manifest.chunk_indices(&event.id.to_hex()).filter(|index| found[*index].is_none()).collect();
+                (!indices.is_empty()).then_some((event, indices))
+            })
+            .collect();
+        for (indices, content) in decrypt_chunks(keys.secret_key(), wanted) {
+            for index in indices {
+                manifest.check_chunk(index, content.as_bytes())?;
+                found[index] = Some(content.clone());
+            }
+        }
+        Ok::<_, Box<dyn std::error::Error>>(())
+    };
+
+    let local = manifest.chunk_ids.iter().filter_map(|id| chunks.get(id)).map(|event| (*event).clone());
+    fill(&mut found, local.collect())?;
+
+    let mut candidates: Vec<&String> = manifest.relays.iter().collect();
+    for relay in manifest.chunk_relays.values().flatten().chain(relays) {
+        if !candidates.contains(&relay) {
+            candidates.push(relay);
+        }
+    }
+    for relay in candidates {
+        let missing: Vec<EventId> = manifest
+            .chunk_ids
+            .iter()
+            .zip(&found)
+            .filter(|(_, found)| found.is_none())
+            .filter_map(|(id, _)| EventId::from_hex(id).ok())
+            .collect();
+        if missing.is_empty() {
+            break;
+        }
+        match fetch_events(relay, missing).await {
+            Ok(events) => fill(&mut found, events)?,
+            Err(e) => eprintln!("{} Fetching chunks from {}: {}", "warning".yellow(), relay, e),
+        }
+    }
+
+    let missing = found.iter().filter(|chunk| chunk.is_none()).count();
+    if missing > 0 {
+        return Err(format!(
+            "{} of {} chunks missing; add the chunk events to the input or pass --relays",
+            missing,
+            found.len()
+        )
+        .into());
+    }
+    let chunks = found.into_iter().flatten().map(|chunk| Ok(chunk.into_bytes()));
+    let mut content = String::new();
+    ChunkReader::for_manifest(chunks, manifest).read_to_string(&mut content)?;
+    Ok(decode_payload(content, manifest.encoding.as_deref())?)
+}
+
+/// Update the triage status of a crash group in the database.
+fn set_group_status(
+    db_path: &PathBuf,
+    group: &str,
+    status: GroupStatus,
+    in_release: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = CrashStorage::open(db_path)?;
+
+    if !storage.set_group_status(group, status, in_release, Utc::now().timestamp())? {
+        return Err(format!("No crash group '{}' in {}", group, db_path.display()).into());
+    }
+
+    match (status, in_release) {
+        (GroupStatus::Resolved, Some(release)) => {
+            println!("{} {} marked resolved in {}", "✓".green(), group.bold(), release);
+        }
+        _ => println!("{} {} marked {}", "✓".green(), group.bold(), status.as_str()),
+    }
+
+    Ok(())
+}
+
+/// Ingest crash payload files from a directory, optionally watching for new ones.
+fn ingest_dir(db_path: &PathBuf, dir: &PathBuf, watch: bool) -> Result<(), Box<dyn std::error::Error>> {
+    use notify::{EventKind, RecursiveMode, Watcher};
+    use std::sync::mpsc as std_mpsc;
+    use std::time::Duration;
+
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", dir.display()).into());
+    }
+    let storage = CrashStorage::open(db_path)?;
+
+    let ingest = |path: &std::path::Path| match ingest_file(&storage, path, Utc::now().timestamp()) {
+        Ok(IngestOutcome::Inserted(id)) => {
+            println!("{} {} → crash {}", "✓".green(), path.display(), id);
+            true
+        }
+        Ok(IngestOutcome::Duplicate) => false,
+        Err(e) => {
+            eprintln!("{} {}", "warning".yellow(), e);
+            false
+        }
+    };
+
+    // Start watching before the initial pass so no file slips between the two
+    let (tx, rx) = std_mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    if watch {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    }
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| ingest::is_candidate(path))
+        .collect();
+    paths.sort();
+    let inserted = paths.iter().filter(|path| ingest(path)).count();
+    println!(
+        "{} Ingested {} new crashes from {} files in {}",
+        "✓".green(),
+        inserted,
+        paths.len(),
+        dir.display()
+    );
+
+    if !watch {
+        return Ok(());
+    }
+
+    println!("{} Watching {} for new crash files...", "→".blue(), dir.display());
+    let mut pending: HashSet<PathBuf> = HashSet::new();
+    loop {
+        // Wait for writers to go quiet before reading, to avoid partial files
+        let timeout = if pending.is_empty() {
+            Duration::from_secs(3600)
+        } else {
+            Duration::from_millis(500)
+        };
+        match rx.recv_timeout(timeout) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    pending.extend(event.paths);
+                }
+            }
+            Ok(Err(e)) => eprintln!("{} Watch error: {}", "error".red(), e),
+            Err(std_mpsc::RecvTimeoutError::Timeout) => {
+                let mut ready: Vec<PathBuf> = pending.drain().collect();
+                ready.sort();
+                for path in ready.iter().filter(|path| ingest::is_candidate(path)) {
+                    ingest(path);
+                }
+            }
+            Err(std_mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate mapping files, printing each problem found.
+fn verify_mappings(
+    mappings_dir: &Path,
+    files: &[PathBuf],
+    platform: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut targets: Vec<(Platform, PathBuf)> = Vec::new();
+    if files.is_empty() {
+        if !mappings_dir.is_dir() {
+            return Err(format!("Not a directory: {}", mappings_dir.display()).into());
+        }
+        let store = MappingStore::new(mappings_dir);
+        store.scan()?;
+        targets.extend(store.list().into_iter().map(|info| (info.platform, info.path)));
+        targets.sort_by(|a, b| a.1.cmp(&b.1));
+    } else {
+        for file in files {
+            // <platform>/<app>/<version>/<file>
+            let platform = platform
+                .map(String::from)
+                .or_else(|| {
+                    file.ancestors()
+                        .nth(3)
+                        .and_then(|dir| dir.file_name())
+                        .map(|name| name.to_string_lossy().to_string())
+                })
+                .ok_or_else(|| format!("Can't tell the platform of {}; pass --platform", file.display()))?;
+            targets.push((Platform::from_str(&platform), file.clone()));
+        }
+    }
+
+    let (mut errors, mut warnings) = (0, 0);
+    for (platform, path) in &targets {
+        let problems = MappingStore::validate(platform, path)?;
+        if problems.is_empty() {
+            println!("{} {}", "✓".green(), path.display());
+            continue;
+        }
+
+        let marker = if problems.iter().any(|p| p.is_error()) {
+            "✗".red()
+        } else {
+            "!".yellow()
+        };
+        println!("{} {}", marker, path.display());
+        for problem in &problems {
+            let label = if problem.is_error() {
+                errors += 1;
+                "error".red()
+            } else {
+                warnings += 1;
+                "warning".yellow()
+            };
+            match problem.line {
+                Some(line) => println!("  {} line {}: {}", label, line, problem.message),
+                None => println!("  {} {}", label, problem.message),
+            }
+        }
+    }
+
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    println!(
+        "{} file{} checked, {} error{}, {} warning{}",
+        targets.len(),
+        plural(targets.len()),
+        errors,
+        plural(errors),
+        warnings,
+        plural(warnings)
+    );
+    if errors > 0 {
+        return Err(format!("Mapping files have {} error{}", errors, plural(errors)).into());
+    }
+    Ok(())
+}
+
+/// Where `bugstr mappings upload` stores a mapping file.
+struct UploadTarget {
+    platform: Platform,
+    app_id: String,
+    version: String,
+    filename: String,
+}
+
+/// Save a mapping file in a mapping directory.
+fn upload_mapping(
+    mappings_dir: &Path,
+    file: &Path,
+    target: &UploadTarget,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let content = std::fs::read(file)?;
+    let path = MappingStore::new(mappings_dir).save_mapping(
+        target.platform.clone(),
+        &target.app_id,
+        &target.version,
+        &target.filename,
+        &content,
+    )?;
+    println!("{} Saved {}", "✓".green(), path.display());
+    Ok(())
+}
+
+/// Upload a mapping file to a running dashboard's `/api/mappings`.
+async fn upload_mapping_remote(
+    url: &str,
+    file: &Path,
+    target: &UploadTarget,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let content = tokio::fs::read(file).await?;
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/mappings", url.trim_end_matches('/')))
+        .query(&[
+            ("platform", target.platform.as_str()),
+            ("app_id", target.app_id.as_str()),
+            ("version", target.version.as_str()),
+            ("filename", target.filename.as_str()),
+        ])
+        .body(content)
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        let message = body["error"].as_str().unwrap_or(status.as_str());
+        return Err(format!("Upload failed ({}): {}", status, message).into());
+    }
+    println!(
+        "{} Uploaded {}/{}/{} to {}",
+        "✓".green(),
+        target.platform.as_str(),
+        target.app_id,
+        target.version,
+        url
+    );
+    Ok(())
+}
+
+/// List the versions in a mapping directory.
+fn list_mappings(mappings_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if !mappings_dir.is_dir() {
+        return Err(format!("Not a directory: {}", mappings_dir.display()).into());
+    }
+    let store = MappingStore::new(mappings_dir);
+    store.scan()?;
+
+    let mut mappings = store.list();
+    mappings.sort_by(|a, b| {
+        (a.platform.as_str(), &a.app_id)
+            .cmp(&(b.platform.as_str(), &b.app_id))
+            .then_with(|| {
+                match (semver::Version::parse(&a.version), semver::Version::parse(&b.version)) {
+                    (Ok(va), Ok(vb)) => va.cmp(&vb),
+                    _ => a.version.cmp(&b.version),
+                }
+            })
+    });
+    for info in &mappings {
+        println!(
+            "{}/{}/{}  {}",
+            info.platform.as_str(),
+            info.app_id.bold(),
+            info.version,
+            info.path.display().to_string().dimmed()
+        );
+    }
+    println!(
+        "{} {} version{}",
+        "✓".green(),
+        mappings.len(),
+        if mappings.len() == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+/// Delete the mappings of one version from a mapping directory.
+fn delete_mapping(
+    mappings_dir: &Path,
+    platform: &Platform,
+    app_id: &str,
+    version: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let store = MappingStore::new(mappings_dir);
+    store.scan()?;
+    if store.remove(platform, app_id, version)?.is_none() {
+        return Err(format!(
+            "No mappings for {}/{}/{} in {}",
+            platform.as_str(),
+            app_id,
+            version,
+            mappings_dir.display()
+        )
+        .into());
+    }
+    println!("{} Deleted {}/{}/{}", "✗".red(), platform.as_str(), app_id, version);
+    Ok(())
+}
+
+/// Deduplicate the files in a mapping directory.
+fn dedup_mappings(mappings_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if !mappings_dir.is_dir() {
+        return Err(format!("Not a directory: {}", mappings_dir.display()).into());
+    }
+    let stats = MappingStore::new(mappings_dir).dedup()?;
+    println!(
+        "{} Linked {} of {} files, freeing {:.1} MB",
+        "✓".green(),
+        stats.linked,
+        stats.files,
+        stats.bytes_saved as f64 / 1_000_000.0
+    );
+    Ok(())
+}
+
+/// Remove the mappings of old versions from a mapping directory.
+fn prune_mappings(
+    mappings_dir: &Path,
+    policy: &PrunePolicy,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if policy.keep_latest.is_none() && policy.older_than.is_none() {
+        return Err("Specify --keep and/or --older-than".into());
+    }
+    if !mappings_dir.is_dir() {
+        return Err(format!("Not a directory: {}", mappings_dir.display()).into());
+    }
+
+    let store = MappingStore::new(mappings_dir);
+    store.scan()?;
+    let removed = store.prune(policy, dry_run)?;
+    for info in &removed {
+        println!(
+            "{} {}/{}/{}",
+            if dry_run { "→".blue() } else { "✗".red() },
+            info.platform.as_str(),
+            info.app_id,
+            info.version
+        );
+    }
+    println!(
+        "{} {} {} version{}",
+        "✓".green(),
+        if dry_run { "Would remove" } else { "Removed" },
+        removed.len(),
+        if removed.len() == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+/// Report a mapping directory change seen by the `serve` watcher.
+fn print_mapping_change(change: Result<MappingChange, SymbolicationError>) {
+    match change {
+        Ok(MappingChange::Updated(key)) => println!(
+            "{} Mapping loaded: {}/{}/{}",
+            "✓".green(),
+            key.platform.as_str(),
+            key.app_id,
+            key.version
+        ),
+        Ok(MappingChange::Removed(key)) => println!(
+            "{} Mapping removed: {}/{}/{}",
+            "→".blue(),
+            key.platform.as_str(),
+            key.app_id,
+            key.version
+        ),
+        Ok(MappingChange::Rescanned(count)) => {
+            println!("{} Mappings rescanned: {} mapping files", "✓".green(), count)
+        }
+        Err(e) => eprintln!("{} Failed to update mappings: {}", "error".red(), e),
+    }
+}
+
+/// Print a crash as a markdown issue report (or just its title).
+fn print_issue(db_path: &PathBuf, id: i64, title_only: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = CrashStorage::open(db_path)?;
+    let report = storage
+        .get_by_id(id)?
+        .ok_or_else(|| format!("No crash {} in {}", id, db_path.display()))?;
+
+    if title_only {
+        println!("{}", issue_title(&report));
+    } else {
+        let symbolicated = storage.get_symbolicated_stack(id)?;
+        print!("{}", format_issue(&report, symbolicated.as_deref()));
+    }
+
+    Ok(())
+}
+
+/// Store a new integration.
+fn add_integration(
+    db_path: &PathBuf,
+    app: Option<&str>,
+    config: &IntegrationConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = CrashStorage::open(db_path)?;
+    let id = storage.add_integration(app, config, Utc::now().timestamp())?;
+    println!(
+        "{} Added {} integration {} for {}: {}",
+        "✓".green(),
+        config.kind(),
+        id,
+        app.unwrap_or("every app"),
+        config.target()
+    );
+    Ok(())
+}
+
+/// Settings for a Nostr DM integration, generating the sending key if
+/// none is given.
+fn nostr_integration(
+    to: &[String],
+    relays: Vec<String>,
+    privkey: Option<&str>,
+) -> Result<IntegrationConfig, Box<dyn std::error::Error>> {
+    let keys = match privkey {
+        Some(privkey) => Keys::new(parse_privkey(privkey)?),
+        None => {
+            let keys = Keys::generate();
+            println!(
+                "Alerts will be sent from {}; follow it to see them outside message requests.",
+                keys.public_key().to_bech32()?
+            );
+            keys
+        }
+    };
+    let recipients = to
+        .iter()
+        .map(|pubkey| PublicKey::parse(pubkey).map(|pk| pk.to_hex()))
+        .collect::<Result<_, _>>()?;
+    let relays = if relays.is_empty() {
+        DEFAULT_RELAYS.iter().map(|r| r.to_string()).collect()
+    } else {
+        relays
+    };
+    Ok(IntegrationConfig::Nostr {
+        secret_key: keys.secret_key().to_secret_hex(),
+        recipients,
+        relays,
+    })
+}
+
+/// Print stored integrations.
+fn list_integrations(db_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = CrashStorage::open(db_path)?;
+    let integrations = storage.get_integrations()?;
+    if integrations.is_empty() {
+        println!("No integrations in {}", db_path.display());
+    }
+    for integration in integrations {
+        println!(
+            "{:>4}  {:<8} {:<20} {}",
+            integration.id,
+            integration.config.kind(),
+            integration.app_name.as_deref().unwrap_or("*"),
+            integration.config.target()
+        );
+    }
+    Ok(())
+}
+
+/// Store a new alert rule.
+fn add_rule(db_path: &PathBuf, rule: &Rule) -> Result<(), Box<dyn std::error::Error>> {
+    rule.condition.validate()?;
+    let storage = CrashStorage::open(db_path)?;
+    let known: HashSet<i64> = storage.get_integrations()?.iter().map(|i| i.id).collect();
+    if let Some(id) = rule.integration_ids.iter().find(|id| !known.contains(id)) {
+        return Err(format!("No integration {}", id).into());
+    }
+
+    let id = storage.add_rule(rule)?;
+    println!(
+        "{} Added rule {} for {}: {}",
+        "✓".green(),
+        id,
+        rule.app_name.as_deref().unwrap_or("every app"),
+        rule.condition.describe()
+    );
+    Ok(())
+}
+
+/// Print stored alert rules.
+fn list_rules(db_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = CrashStorage::open(db_path)?;
+    let rules = storage.get_rules()?;
+    if rules.is_empty() {
+        println!("No rules in {}; every integration gets the built-in alerts", db_path.display());
+    }
+    for rule in rules {
+        let targets = if rule.integration_ids.is_empty() {
+            "all integrations".to_string()
+        } else {
+            let ids: Vec<_> = rule.integration_ids.iter().map(i64::to_string).collect();
+            format!("integrations {}", ids.join(", "))
+        };
+        let condition = rule.condition.describe();
+        let name = if rule.name == condition {
+            String::new()
+        } else {
+            format!(" [{}]", rule.name)
+        };
+        println!(
+            "{:>4}  {:<24} {}{} → {} (cooldown {} min)",
+            rule.id,
+            rule.app_name.as_deref().unwrap_or("*"),
+            condition,
+            name,
+            targets,
+            rule.cooldown_minutes
+        );
+    }
+    Ok(())
+}
+
+/// Print the chunked reports still waiting for chunks.
+fn chunks_status(db_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = CrashStorage::open(db_path)?;
+    let pending = storage.get_pending_manifests()?;
+    if pending.is_empty() {
+        println!("No incomplete chunked reports in {}", db_path.display());
+    }
+    for pending in pending {
+        let time = DateTime::from_timestamp(pending.received_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let manifest = &pending.manifest;
+        let attempts = format!(
+            "{} failed attempt{}",
+            pending.attempts,
+            if pending.attempts == 1 { "" } else { "s" }
+        );
+        let retry = match (pending.next_retry_at, &pending.last_error) {
+            (_, None) => String::new(),
+            (Some(at), Some(error)) => {
+                let at = DateTime::from_timestamp(at, 0)
+                    .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default();
+                format!("  {}, next {} ({})", attempts, at, error)
+            }
+            (None, Some(error)) => format!("  {}, retrying ({})", attempts, error),
+        };
+        println!(
+            "{}  {}  {}/{} chunks  {} bytes  from {}{}",
+            &manifest.root_hash[..16.min(manifest.root_hash.len())],
+            time,
+            pending.chunks_fetched,
+            manifest.chunk_ids.len(),
+            manifest.total_size,
+            &pending.sender_pubkey[..16.min(pending.sender_pubkey.len())],
+            retry
+        );
+    }
+    Ok(())
+}
+
+/// Print recent alert deliveries, newest first.
+fn list_deliveries(db_path: &PathBuf, limit: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = CrashStorage::open(db_path)?;
+    let deliveries = storage.get_deliveries(limit)?;
+    if deliveries.is_empty() {
+        println!("No deliveries in {}", db_path.display());
+    }
+    for delivery in deliveries {
+        let time = DateTime::from_timestamp(delivery.created_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let outcome = match (&delivery.error, &delivery.reference) {
+            (None, Some(reference)) => format!("{} {}", "ok".green(), reference),
+            (None, None) => "ok".green().to_string(),
+            (Some(e), _) => format!("{} {}", "failed".red(), e),
+        };
+        println!(
+            "{}  integration {:<4} {:<10} {}  {}",
+            time,
+            delivery.integration_id,
+            delivery.alert_kind.as_str(),
+            delivery.group_key,
+            outcome
+        );
+    }
+    Ok(())
+}
+
+/// Resolve open crash groups whose issue was closed in an integration with
+/// status sync. Returns how many groups were resolved.
+fn sync_issue_status(db_path: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+    let storage = CrashStorage::open(db_path)?;
+    let mut resolved = 0;
+    for integration in storage.get_integrations()? {
+        let Some(jira) = integration.config.status_sync() else {
+            continue;
+        };
+        let links = storage.issue_links(integration.id)?;
+        for link in jira.closed_groups(&links)? {
+            storage.set_group_status(
+                &link.group_key,
+                GroupStatus::Resolved,
+                None,
+                Utc::now().timestamp(),
+            )?;
+            println!(
+                "{} Resolved {} ({} closed)",
+                "✓".green(),
+                link.group_key,
+                link.reference
+            );
+            resolved += 1;
+        }
+    }
+    Ok(resolved)
+}
+
+fn parse_alert_kind(s: &str) -> Result<AlertKind, String> {
+    AlertKind::parse(s).ok_or_else(|| {
+        let kinds: Vec<_> = AlertKind::ALL.iter().map(AlertKind::as_str).collect();
+        format!("expected one of {}", kinds.join(", "))
+    })
+}
+
+fn parse_sentry_dsn(s: &str) -> Result<String, String> {
+    Dsn::parse(s)
+        .map(|_| s.trim().to_string())
+        .map_err(|e| e.to_string())
+}
+
+fn parse_smtp_security(s: &str) -> Result<SmtpSecurity, String> {
+    SmtpSecurity::parse(s).ok_or_else(|| "expected starttls, tls or none".to_string())
+}
+
+/// Apply pending schema migrations to the database.
+fn migrate_db(db_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = CrashStorage::open_unmigrated(db_path)?;
+    let before = storage.schema_version()?;
+
+    if before > SCHEMA_VERSION {
+        return Err(format!(
+            "{} is at schema version {}, newer than this build supports ({})",
+            db_path.display(),
+            before,
+            SCHEMA_VERSION
+        )
+        .into());
+    }
+
+    let applied = storage.migrate()?;
+    if applied == 0 {
+        println!(
+            "{} {} is up to date (schema version {})",
+            "✓".green(),
+            db_path.display(),
+            before
+        );
+    } else {
+        println!(
+            "{} Migrated {} from schema version {} to {} ({} migration{})",
+            "✓".green(),
+            db_path.display(),
+            before,
+            storage.schema_version()?,
+            applied,
+            if applied == 1 { "" } else { "s" }
+        );
+    }
+
+    Ok(())
+}
+
+/// Print what an S3 export uploaded.
+fn print_export(summary: &ExportSummary) {
+    println!(
+        "{} Exported {} crashes to {} and a backup to {}",
+        "✓".green(),
+        summary.crashes,
+        summary.jsonl_key,
+        summary.backup_key
+    );
 }
 
-fn parse_privkey(input: &str) -> Result<SecretKey, Box<dyn std::error::Error>> {
-    if input.starts_with("nsec") {
-        let secret = SecretKey::from_bech32(input)?;
-        Ok(secret)
-    } else {
-        let secret = SecretKey::from_hex(input)?;
-        Ok(secret)
+/// Write a consistent copy of the database using SQLite's backup API.
+fn backup_db(db_path: &PathBuf, output: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    if !db_path.exists() {
+        return Err(format!("Database not found: {}", db_path.display()).into());
+    }
+    if output.exists() {
+        return Err(format!("Refusing to overwrite existing file: {}", output.display()).into());
     }
-}
 
-fn show_pubkey(privkey: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let secret = parse_privkey(privkey)?;
-    let keys = Keys::new(secret);
-    let pubkey = keys.public_key();
+    let storage = CrashStorage::open_unmigrated(db_path)?;
+    storage.backup_to(output)?;
 
-    println!("Receiver pubkey:");
-    println!("  npub: {}", pubkey.to_bech32()?);
-    println!("  hex:  {}", pubkey.to_hex());
-    println!();
-    println!("Add this pubkey to your app's bugstr configuration.");
+    println!(
+        "{} Backed up {} to {} (schema version {})",
+        "✓".green(),
+        db_path.display(),
+        output.display(),
+        storage.schema_version()?
+    );
 
     Ok(())
 }
@@ -206,6 +2457,10 @@ fn show_pubkey(privkey: &str) -> Result<(), Box<dyn std::error::Error>> {
 ///   - `"go"` or `"golang"` - Go (goroutine stacks)
 ///   - `"python"` - Python (traceback parsing)
 ///   - `"react-native"` or `"reactnative"` or `"rn"` - React Native (Hermes + source maps)
+///   - `"ios"` or `"apple"` or `"macos"` - iOS/macOS (dSYM debug info)
+///   - `"windows"` or `"win32"` or `"win"` - Windows (PDB files)
+///   - `"unity"` or `"il2cpp"` - Unity (IL2CPP method and line mappings)
+///
 ///   Unknown platforms trigger a warning but still attempt symbolication.
 ///
 /// * `input` - Path to file containing the stack trace, or `"-"` to read from stdin.
@@ -215,6 +2470,9 @@ fn show_pubkey(privkey: &str) -> Result<(), Box<dyn std::error::Error>> {
 ///   Expected structure: `<root>/<platform>/<app_id>/<version>/<mapping_file>`.
 ///   See [`MappingStore`] for detailed directory layout.
 ///
+/// * `remote` - Optional symbol server and debuginfod servers to download
+///   mappings and debug info from when they are missing from `mappings_dir`.
+///
 /// * `app_id` - Optional application identifier (e.g., package name, bundle ID).
 ///   Used to locate the correct mapping file. If `None`, defaults to `"unknown"`.
 ///
@@ -252,6 +2510,8 @@ fn show_pubkey(privkey: &str) -> Result<(), Box<dyn std::error::Error>> {
 /// **JSON format**:
 /// ```json
 /// {
+///   "schema_version": 1,
+///   "raw": "...",
 ///   "symbolicated_count": 5,
 ///   "total_count": 6,
 ///   "percentage": 83.33,
@@ -262,7 +2522,9 @@ fn show_pubkey(privkey: &str) -> Result<(), Box<dyn std::error::Error>> {
 ///       "file": "MyClass.java",
 ///       "line": 42,
 ///       "column": null,
-///       "symbolicated": true
+///       "symbolicated": true,
+///       "in_app": null,
+///       "confidence": "exact"
 ///     }
 ///   ]
 /// }
@@ -289,6 +2551,7 @@ fn show_pubkey(privkey: &str) -> Result<(), Box<dyn std::error::Error>> {
 ///     "android",
 ///     "crash.txt",
 ///     &PathBuf::from("./mappings"),
+///     remote,
 ///     Some("com.myapp".to_string()),
 ///     Some("1.0.0".to_string()),
 ///     SymbolicateFormat::Pretty,
@@ -299,6 +2562,7 @@ fn show_pubkey(privkey: &str) -> Result<(), Box<dyn std::error::Error>> {
 ///     "python",
 ///     "-",
 ///     &PathBuf::from("./mappings"),
+///     remote,
 ///     None,
 ///     None,
 ///     SymbolicateFormat::Json,
@@ -308,41 +2572,35 @@ fn symbolicate_stack(
     platform_str: &str,
     input: &str,
     mappings_dir: &PathBuf,
+    remote: RemoteSymbols,
     app_id: Option<String>,
     version: Option<String>,
     format: SymbolicateFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Read stack trace
-    let stack_trace = if input == "-" {
-        use std::io::Read;
-        let mut buf = String::new();
-        std::io::stdin().read_to_string(&mut buf)?;
-        buf
-    } else {
-        std::fs::read_to_string(input)?
-    };
+    let stack_trace = read_input(input)?;
 
     // Parse platform
     let platform = Platform::from_str(platform_str);
     if matches!(platform, Platform::Unknown(_)) {
         eprintln!(
-            "{} Unknown platform '{}'. Supported: android, electron, flutter, rust, go, python, react-native",
+            "{} Unknown platform '{}'. Supported: android, electron, flutter, rust, go, python, react-native, ios, windows, unity",
             "warning".yellow(),
             platform_str
         );
     }
 
     // Create symbolicator with scanned mapping store
-    let mut store = MappingStore::new(mappings_dir);
+    let store = MappingStore::new(mappings_dir);
     let count = store.scan()?;
-    if count == 0 {
+    if count == 0 && remote.is_empty() {
         eprintln!(
             "{} No mapping files found in {}",
             "warning".yellow(),
             mappings_dir.display()
         );
     }
-    let symbolicator = Symbolicator::new(store);
+    let symbolicator = remote.apply(Symbolicator::new(store));
 
     // Create context
     let context = SymbolicationContext {
@@ -350,6 +2608,7 @@ fn symbolicate_stack(
         app_id,
         version,
         build_id: None,
+        process_type: None,
     };
 
     // Symbolicate
@@ -375,12 +2634,14 @@ fn symbolicate_stack(
                         (Some(f), None) => format!(" ({})", f.dimmed()),
                         _ => String::new(),
                     };
-                    println!(
-                        "  {} {}{}",
-                        format!("#{}", i).cyan(),
-                        frame.function.as_deref().unwrap_or("<unknown>").green(),
-                        location
-                    );
+                    // Library frames are dimmed so the app's own code stands out
+                    let function = frame.function.as_deref().unwrap_or("<unknown>");
+                    let function = if frame.in_app == Some(false) {
+                        function.dimmed()
+                    } else {
+                        function.green()
+                    };
+                    println!("  {} {}{}", format!("#{}", i).cyan(), function, location);
                 } else {
                     println!("  {} {}", format!("#{}", i).cyan(), frame.raw.dimmed());
                 }
@@ -388,21 +2649,8 @@ fn symbolicate_stack(
             println!();
         }
         SymbolicateFormat::Json => {
-            let output = serde_json::json!({
-                "symbolicated_count": result.symbolicated_count,
-                "total_count": result.total_count,
-                "percentage": result.percentage(),
-                "frames": result.frames.iter().map(|f| {
-                    serde_json::json!({
-                        "raw": f.raw,
-                        "function": f.function,
-                        "file": f.file,
-                        "line": f.line,
-                        "column": f.column,
-                        "symbolicated": f.symbolicated,
-                    })
-                }).collect::<Vec<_>>()
-            });
+            let mut output = serde_json::to_value(&result)?;
+            output["percentage"] = result.percentage().into();
             println!("{}", serde_json::to_string_pretty(&output)?);
         }
     }
@@ -410,24 +2658,423 @@ fn symbolicate_stack(
     Ok(())
 }
 
+/// Re-run symbolication over stored crashes and update their symbolicated stacks.
+fn resymbolicate(
+    platform_str: &str,
+    db_path: &PathBuf,
+    mappings_dir: &PathBuf,
+    remote: RemoteSymbols,
+    app_id: Option<&str>,
+    version: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let platform = Platform::from_str(platform_str);
+    if matches!(platform, Platform::Unknown(_)) {
+        return Err(format!(
+            "Unknown platform '{}'. Supported: android, electron, flutter, rust, go, python, react-native, ios, windows, unity",
+            platform_str
+        )
+        .into());
+    }
+
+    let store = MappingStore::new(mappings_dir);
+    if store.scan()? == 0 && remote.is_empty() {
+        return Err(format!("No mapping files found in {}", mappings_dir.display()).into());
+    }
+    let storage = CrashStorage::open(db_path)?;
+    let symbolicator = remote
+        .apply(Symbolicator::new(store))
+        .with_result_cache(ResultCache::open(db_path)?);
+    let crashes = storage.get_with_stack_trace(app_id, version)?;
+
+    let mut updated = 0;
+    let mut failed = 0;
+    let mut symbolicated_frames = 0;
+    let mut total_frames = 0;
+
+    for crash in &crashes {
+        let Some(stack_trace) = crash.stack_trace.as_deref() else {
+            continue;
+        };
+        let context = SymbolicationContext {
+            platform: platform.clone(),
+            app_id: crash.app_name.clone(),
+            version: crash.app_version.clone(),
+            build_id: None,
+            process_type: crash.process_type.clone(),
+        };
+
+        match symbolicator.symbolicate(stack_trace, &context) {
+            Ok(result) => {
+                symbolicated_frames += result.symbolicated_count;
+                total_frames += result.total_count;
+                if result.symbolicated_count > 0 {
+                    storage.set_symbolicated_stack(crash.id, Some(&result.display()))?;
+                    updated += 1;
+                }
+            }
+            Err(e) => {
+                eprintln!("{} crash {}: {}", "warning".yellow(), crash.id, e);
+                failed += 1;
+            }
+        }
+    }
+
+    let coverage = if total_frames == 0 {
+        0.0
+    } else {
+        symbolicated_frames as f64 / total_frames as f64 * 100.0
+    };
+
+    println!(
+        "{} Resymbolicated {} of {} crashes",
+        "✓".green(),
+        updated,
+        crashes.len()
+    );
+    println!(
+        "  {} frames symbolicated: {}/{} ({:.1}%)",
+        "→".blue(),
+        symbolicated_frames,
+        total_frames,
+        coverage
+    );
+    if failed > 0 {
+        println!("  {} {} crashes failed", "→".blue(), failed);
+    }
+
+    Ok(())
+}
+
+/// Response of a dashboard's `GET /api/stats`.
+#[derive(serde::Deserialize)]
+struct DashboardStats {
+    total_crashes: i64,
+    symbolication: Option<SymbolicationMetrics>,
+}
+
+/// Print a running dashboard's crash totals and per-platform symbolication
+/// timing, error, frame resolution and mapping cache hit rates.
+async fn show_stats(url: &str, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let stats: serde_json::Value = reqwest::Client::new()
+        .get(format!("{}/api/stats", url.trim_end_matches('/')))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    let stats: DashboardStats = serde_json::from_value(stats)?;
+
+    println!("{} {} crashes stored", "bugstr".green().bold(), stats.total_crashes);
+    let Some(metrics) = stats.symbolication else {
+        println!("  {} Symbolication not configured", "→".blue());
+        return Ok(());
+    };
+    if metrics.platforms.is_empty() {
+        println!("  {} No stack traces symbolicated yet", "→".blue());
+        return Ok(());
+    }
+
+    let rate = |rate: Option<f64>| {
+        rate.map(|r| format!("{:.1}%", r))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    println!();
+    println!(
+        "{:<14}  {:>8}  {:>6}  {:>9}  {:>15}  {:>10}",
+        "Platform", "Requests", "Errors", "Mean time", "Frames resolved", "Cache hits"
+    );
+    println!("{}", "─".repeat(72).dimmed());
+    for (platform, m) in &metrics.platforms {
+        // Pad before coloring; escape codes would count towards the width
+        let errors = format!("{:>6}", m.errors);
+        let errors = if m.errors > 0 { errors.red().to_string() } else { errors };
+        let mean = m
+            .mean_duration()
+            .map(|d| format!("{}ms", d.as_millis()))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<14}  {:>8}  {}  {:>9}  {:>15}  {:>10}",
+            platform,
+            m.requests,
+            errors,
+            mean,
+            rate(m.resolution_rate()),
+            rate(m.cache_hit_rate())
+        );
+    }
+    Ok(())
+}
+
+/// Ephemeral kind used for relay round-trip probes (not stored by relays).
+const RELAY_TEST_KIND: u16 = 21420;
+
+/// Results of probing one relay.
+#[derive(Default)]
+struct RelayTestResult {
+    connect: Option<std::time::Duration>,
+    info: Option<RelayInfo>,
+    publish: Option<std::time::Duration>,
+    round_trip: Option<std::time::Duration>,
+    error: Option<String>,
+}
+
+/// Fields of interest from a NIP-11 relay information document.
+#[derive(Default, serde::Deserialize)]
+struct RelayInfo {
+    name: Option<String>,
+    software: Option<String>,
+    #[serde(default)]
+    limitation: RelayLimitation,
+}
+
+#[derive(Default, serde::Deserialize)]
+struct RelayLimitation {
+    max_message_length: Option<u64>,
+    max_content_length: Option<u64>,
+    #[serde(default)]
+    auth_required: bool,
+    #[serde(default)]
+    payment_required: bool,
+}
+
+/// Probe each relay and print a comparison table.
+async fn relay_test(relays: &[String], timeout_secs: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let timeout = std::time::Duration::from_secs(timeout_secs);
+    let http = reqwest::Client::builder().timeout(timeout).build()?;
+
+    println!("{} Testing {} relays...", "bugstr".green().bold(), relays.len());
+    println!();
+
+    let probes = relays.iter().map(|relay| {
+        let http = http.clone();
+        async move {
+            let mut result = RelayTestResult {
+                info: fetch_relay_info(&http, relay).await.ok(),
+                ..Default::default()
+            };
+            match tokio::time::timeout(timeout, probe_relay(relay, &mut result)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => result.error = Some(e.to_string()),
+                Err(_) => result.error = Some(format!("timed out after {}s", timeout_secs)),
+            }
+            (relay, result)
+        }
+    });
+    let results = futures_util::future::join_all(probes).await;
+
+    let ms = |d: Option<std::time::Duration>| {
+        d.map(|d| format!("{}ms", d.as_millis()))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let width = relays.iter().map(|r| r.len()).max().unwrap_or(5).max(5);
+    println!(
+        "{:<width$}  {:>8}  {:>8}  {:>10}  {:>10}  {:<6}  Notes",
+        "Relay", "Connect", "Publish", "Round trip", "Max msg", "Auth",
+        width = width
+    );
+    println!("{}", "─".repeat(width + 62).dimmed());
+
+    let mut failures = 0;
+    for (relay, result) in &results {
+        let limitation = result.info.as_ref().map(|i| &i.limitation);
+        let max_message = limitation
+            .and_then(|l| l.max_message_length.or(l.max_content_length))
+            .map(|n| format!("{}KB", n / 1024))
+            .unwrap_or_else(|| "-".to_string());
+        let auth = match limitation {
+            Some(l) if l.auth_required => "yes".yellow().to_string(),
+            Some(_) => "no".to_string(),
+            None => "?".dimmed().to_string(),
+        };
+
+        let mut notes = Vec::new();
+        if let Some(info) = &result.info {
+            if let Some(name) = info.name.as_deref().or(info.software.as_deref()) {
+                notes.push(name.to_string());
+            }
+            if info.limitation.payment_required {
+                notes.push("paid".yellow().to_string());
+            }
+        } else {
+            notes.push("no NIP-11".dimmed().to_string());
+        }
+        if let Some(error) = &result.error {
+            notes.push(error.red().to_string());
+            failures += 1;
+        }
+
+        println!(
+            "{:<width$}  {:>8}  {:>8}  {:>10}  {:>10}  {:<6}  {}",
+            relay,
+            ms(result.connect),
+            ms(result.publish),
+            ms(result.round_trip),
+            max_message,
+            auth,
+            notes.join(", "),
+            width = width
+        );
+    }
+
+    println!();
+    if failures > 0 {
+        return Err(format!("{} of {} relays failed", failures, relays.len()).into());
+    }
+    println!("{} All relays passed", "✓".green());
+    Ok(())
+}
+
+/// Fetch a relay's NIP-11 information document.
+async fn fetch_relay_info(http: &reqwest::Client, relay_url: &str) -> Result<RelayInfo, Box<dyn std::error::Error>> {
+    let url = relay_url
+        .replacen("wss://", "https://", 1)
+        .replacen("ws://", "http://", 1);
+    let info = http
+        .get(url)
+        .header("Accept", "application/nostr+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(info)
+}
+
+/// Connect to a relay and time an ephemeral publish/subscribe round trip.
+async fn probe_relay(relay_url: &str, result: &mut RelayTestResult) -> Result<(), Box<dyn std::error::Error>> {
+    use std::time::Instant;
+
+    let start = Instant::now();
+    let (ws_stream, _) = connect_async(relay_url).await?;
+    result.connect = Some(start.elapsed());
+    let (mut write, mut read) = ws_stream.split();
+
+    // Throwaway identity so probes never touch the receiver key
+    let keys = Keys::generate();
+    let kind = Kind::Custom(RELAY_TEST_KIND);
+    let filter = Filter::new().kind(kind).author(keys.public_key());
+    let subscription_id = "bugstr-relay-test";
+    write
+        .send(Message::Text(
+            format!(r#"["REQ","{}",{}]"#, subscription_id, serde_json::to_string(&filter)?).into(),
+        ))
+        .await?;
+
+    let event = EventBuilder::new(kind, "bugstr relay test").sign_with_keys(&keys)?;
+    let event_id = event.id.to_hex();
+    let sent = Instant::now();
+    write
+        .send(Message::Text(format!(r#"["EVENT",{}]"#, serde_json::to_string(&event)?).into()))
+        .await?;
+
+    while let Some(msg) = read.next().await {
+        let Message::Text(text) = msg? else {
+            continue;
+        };
+        let Ok(msg) = serde_json::from_str::<Vec<serde_json::Value>>(&text) else {
+            continue;
+        };
+        match msg.first().and_then(|v| v.as_str()) {
+            Some("OK") if msg.get(1).and_then(|v| v.as_str()) == Some(event_id.as_str()) => {
+                if msg.get(2).and_then(|v| v.as_bool()) != Some(true) {
+                    let reason = msg.get(3).and_then(|v| v.as_str()).unwrap_or("rejected");
+                    return Err(format!("publish rejected: {}", reason).into());
+                }
+                result.publish = Some(sent.elapsed());
+            }
+            Some("EVENT") if msg.get(2).and_then(|e| e.get("id")).and_then(|v| v.as_str()) == Some(event_id.as_str()) => {
+                result.round_trip = Some(sent.elapsed());
+            }
+            Some("CLOSED") => {
+                let reason = msg.get(2).and_then(|v| v.as_str()).unwrap_or("closed");
+                return Err(format!("subscription closed: {}", reason).into());
+            }
+            _ => {}
+        }
+        if result.publish.is_some() && result.round_trip.is_some() {
+            break;
+        }
+    }
+
+    let _ = write.send(Message::Close(None)).await;
+    if result.round_trip.is_none() {
+        return Err("connection closed before round trip completed".into());
+    }
+    Ok(())
+}
+
+/// Settings for `bugstr serve`.
+struct ServeOptions {
+    addr: SocketAddr,
+    db_path: PathBuf,
+    mappings_dir: Option<PathBuf>,
+    remote: RemoteSymbols,
+    read_only: bool,
+    exec_hook: Option<ExecHook>,
+    sentry_key: Option<String>,
+    s3: Option<S3Config>,
+    webhooks: Vec<WebhookConfig>,
+    retention_days: Option<u32>,
+    max_chunked_size: usize,
+    chunk_progress: ChunkProgress,
+}
+
+/// Default for `serve --max-chunked-size`. Anyone who knows the receiver's
+/// public key can send it a manifest, so keep what one makes `serve` fetch
+/// and store small.
+const DEFAULT_MAX_CHUNKED_SIZE: usize = 16 * 1024 * 1024;
+
+/// How often `serve` checks for crash-rate spikes.
+const ANOMALY_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// How often `serve` deletes crash reports past their retention.
+const RETENTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// How often `serve` checks for closed issues.
+const ISSUE_SYNC_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
 /// Run web dashboard with crash collection.
 async fn serve(
     privkey: &str,
     relays: &[String],
-    port: u16,
-    db_path: PathBuf,
-    mappings_dir: Option<PathBuf>,
+    options: ServeOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let ServeOptions {
+        addr,
+        db_path,
+        mappings_dir,
+        remote,
+        read_only,
+        exec_hook,
+        sentry_key,
+        s3,
+        webhooks,
+        retention_days,
+        max_chunked_size,
+        chunk_progress,
+    } = options;
     let secret = parse_privkey(privkey)?;
     let keys = Keys::new(secret);
     let pubkey = keys.public_key();
 
     // Open/create database
     let storage = CrashStorage::open(&db_path)?;
+    for webhook in &webhooks {
+        storage.ensure_webhook(
+            &webhook.url,
+            webhook.secret.as_deref(),
+            &webhook.events,
+            Utc::now().timestamp(),
+        )?;
+    }
 
     // Create symbolicator if mappings directory is provided
     let symbolicator = if let Some(ref dir) = mappings_dir {
-        let mut store = MappingStore::new(dir);
+        let store = MappingStore::new(dir);
         match store.scan() {
             Ok(count) => {
                 if count == 0 {
@@ -439,7 +3086,17 @@ async fn serve(
                 } else {
                     println!("  {} {} mapping files loaded", "Loaded:".cyan(), count);
                 }
-                Some(Arc::new(Symbolicator::new(store)))
+                let mut symbolicator = remote.apply(Symbolicator::new(store));
+                // Repeated stacks are served from the crash database
+                match ResultCache::open(&db_path) {
+                    Ok(cache) => symbolicator = symbolicator.with_result_cache(cache),
+                    Err(e) => eprintln!(
+                        "{} Symbolication result cache unavailable: {}",
+                        "warning".yellow(),
+                        e
+                    ),
+                }
+                Some(Arc::new(symbolicator))
             }
             Err(e) => {
                 eprintln!("{} Failed to scan mappings: {}", "error".red(), e);
@@ -450,9 +3107,32 @@ async fn serve(
         None
     };
 
+    // Pick up mappings added to the directory while serving
+    let _mapping_watcher = symbolicator.as_ref().and_then(|sym| {
+        match MappingWatcher::start(Arc::clone(sym), print_mapping_change) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                eprintln!("{} Not watching mappings for changes: {}", "warning".yellow(), e);
+                None
+            }
+        }
+    });
+
+    // Crashes to store, from relays and the Sentry endpoint
+    let (crash_tx, mut crash_rx) = mpsc::channel::<CrashReport>(100);
+    // Wakes the chunk retry task early
+    let (retry_tx, mut retry_rx) = mpsc::channel::<()>(1);
+
     let state = Arc::new(AppState {
         storage: Mutex::new(storage),
         symbolicator,
+        read_only,
+        sentry: sentry_key.clone().map(|key| SentryIngest {
+            key,
+            crashes: crash_tx.clone(),
+        }),
+        hooks: Hooks::default(),
+        chunk_retries: Some(retry_tx),
     });
 
     println!("{}", "━".repeat(60).dimmed());
@@ -463,11 +3143,35 @@ async fn serve(
     println!("{}", "━".repeat(60).dimmed());
     println!("  {} {}", "Pubkey:".cyan(), pubkey.to_bech32()?);
     println!("  {} {}", "Database:".cyan(), db_path.display());
-    println!("  {} http://localhost:{}", "Dashboard:".cyan(), port);
+    println!("  {} http://{}", "Dashboard:".cyan(), addr);
     println!("  {} {}", "Relays:".cyan(), relays.join(", "));
     if let Some(ref dir) = mappings_dir {
         println!("  {} {}", "Mappings:".cyan(), dir.display());
     }
+    if let Some(ref hook) = exec_hook {
+        println!("  {} {}", "Exec:".cyan(), hook.command());
+    }
+    for webhook in &webhooks {
+        println!("  {} {}", "Webhook:".cyan(), webhook.url);
+    }
+    if let Some(days) = retention_days {
+        println!("  {} {} days", "Retention:".cyan(), days);
+    }
+    if read_only {
+        println!("  {} read-only", "Mode:".cyan());
+    }
+    if let Some(ref s3) = s3 {
+        println!(
+            "  {} s3://{}/{} every {}h",
+            "Archive:".cyan(),
+            s3.bucket,
+            s3.prefix,
+            s3.interval_hours
+        );
+    }
+    if let Some(ref key) = sentry_key {
+        println!("  {} http://{}@{}/api/ingest/sentry/1", "Sentry DSN:".cyan(), key, addr);
+    }
     println!("{}", "━".repeat(60).dimmed());
     println!();
 
@@ -479,10 +3183,11 @@ async fn serve(
         let relay = relay_url.clone();
         let keys = keys.clone();
         let tx = tx.clone();
+        let state = state.clone();
 
         tokio::spawn(async move {
             loop {
-                match subscribe_relay_with_storage(&relay, &keys, &tx).await {
+                match subscribe_relay_with_storage(&relay, &keys, &tx, &state).await {
                     Ok(()) => {}
                     Err(e) => {
                         let err_msg = e.to_string();
@@ -494,75 +3199,299 @@ async fn serve(
         });
     }
 
-    // Spawn crash storage worker
-    let storage_state = state.clone();
+    // Retry chunked reports whose chunks couldn't be fetched, starting with
+    // those interrupted by the last shutdown
+    {
+        let storage = state.storage.lock().await;
+        storage.schedule_all_manifest_retries(Utc::now().timestamp())?;
+    }
+    let assembler = ChunkAssembler {
+        state: state.clone(),
+        keys: keys.clone(),
+        relays: relays.to_vec(),
+        crash_tx: crash_tx.clone(),
+        fetches: ChunkFetches::default(),
+        max_size: max_chunked_size,
+        progress: chunk_progress,
+    };
+    let retry_assembler = assembler.clone();
+    tokio::spawn(async move {
+        loop {
+            let due = retry_assembler
+                .state
+                .storage
+                .lock()
+                .await
+                .take_due_manifests(Utc::now().timestamp());
+            match due {
+                Ok(due) => {
+                    for pending in due {
+                        tokio::spawn(retry_assembler.clone().assemble(pending));
+                    }
+                }
+                Err(e) => eprintln!("{} Failed to load chunk retries: {}", "error".red(), e),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(CHUNK_RETRY_INTERVAL) => {}
+                _ = retry_rx.recv() => {}
+            }
+        }
+    });
+
+    // Parse crashes received from relays
     tokio::spawn(async move {
         while let Some(crash) = rx.recv().await {
-            let parsed = parse_crash_content(&crash.content);
-            let now = Utc::now().timestamp();
-
-            let report = CrashReport {
-                id: 0, // Will be set by insert
-                event_id: crash.event_id.clone(),
-                sender_pubkey: crash.sender_pubkey,
-                received_at: now,
-                created_at: crash.created_at,
-                app_name: parsed.app_name,
-                app_version: parsed.app_version,
-                exception_type: parsed.exception_type,
-                message: parsed.message,
-                stack_trace: parsed.stack_trace,
-                raw_content: crash.content,
-                environment: parsed.environment,
-                release: parsed.release,
-            };
+            if let Some(manifest) = crash.manifest {
+                let pending = PendingManifest {
+                    event_id: crash.event_id,
+                    sender_pubkey: crash.sender_pubkey,
+                    created_at: crash.created_at,
+                    received_at: Utc::now().timestamp(),
+                    manifest,
+                    chunks_fetched: 0,
+                    attempts: 0,
+                    next_retry_at: None,
+                    last_error: None,
+                };
+                tokio::spawn(assembler.clone().assemble(pending));
+                continue;
+            }
+            let report = CrashReport::from_content(
+                crash.event_id,
+                crash.sender_pubkey,
+                crash.created_at,
+                Utc::now().timestamp(),
+                crash.content,
+            );
+            if crash_tx.send(report).await.is_err() {
+                break;
+            }
+        }
+    });
 
-            let storage = storage_state.storage.lock().await;
-            match storage.insert(&report) {
-                Ok(Some(_id)) => {
+    // Spawn crash storage worker
+    let storage_state = state.clone();
+    tokio::spawn(async move {
+        while let Some(report) = crash_rx.recv().await {
+            match storage_state.store_crash(report).await {
+                Ok(Some(report)) => {
                     println!(
                         "{} Stored crash: {} - {}",
                         "✓".green(),
                         report.exception_type.as_deref().unwrap_or("Unknown"),
                         report.message.as_deref().unwrap_or("No message").chars().take(50).collect::<String>()
                     );
+
+                    let storage = storage_state.storage.lock().await;
+                    notify_integrations(&storage_state, &storage, &report);
+                    drop(storage);
+
+                    if let Some(hook) = exec_hook.clone() {
+                        tokio::spawn(async move {
+                            match hook.run(&report).await {
+                                Ok(status) if !status.success() => {
+                                    eprintln!("{} Exec hook exited with {}", "warning".yellow(), status);
+                                }
+                                Ok(_) => {}
+                                Err(e) => {
+                                    eprintln!("{} Failed to run exec hook: {}", "error".red(), e);
+                                }
+                            }
+                        });
+                    }
                 }
                 Ok(None) => {
-                    // Duplicate, ignore
+                    // Duplicate or dropped by a hook, ignore
+                }
+                Err(e) => {
+                    eprintln!("{} Failed to store crash: {}", "error".red(), e);
+                }
+            }
+        }
+    });
+
+    // Flag crash-rate spikes
+    let anomaly_state = state.clone();
+    tokio::spawn(async move {
+        let mut detector = AnomalyDetector::new();
+        loop {
+            tokio::time::sleep(ANOMALY_CHECK_INTERVAL).await;
+            notify_spikes(&anomaly_state, &mut detector).await;
+        }
+    });
+
+    // Archive to object storage
+    if let Some(s3) = s3 {
+        let interval = std::time::Duration::from_secs(s3.interval_hours.max(1) * 3600);
+        let client = Arc::new(S3Client::new(s3)?);
+        let db_path = db_path.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let (db_path, client) = (db_path.clone(), client.clone());
+                match tokio::task::spawn_blocking(move || run_export(&db_path, &client)).await {
+                    Ok(Ok(summary)) => print_export(&summary),
+                    Ok(Err(e)) => eprintln!("{} S3 export failed: {}", "error".red(), e),
+                    Err(_) => {}
+                }
+            }
+        });
+    }
+
+    // Delete expired crash reports
+    if let Some(days) = retention_days {
+        let retention_state = state.clone();
+        tokio::spawn(async move {
+            loop {
+                // Vacuuming rewrites the whole database
+                let state = retention_state.clone();
+                let expire = tokio::task::spawn_blocking(move || {
+                    state.storage.blocking_lock().expire(days, Utc::now().timestamp())
+                });
+                match expire.await {
+                    Ok(Ok(0)) | Err(_) => {}
+                    Ok(Ok(deleted)) => println!(
+                        "{} Deleted {} crash report{} older than {} days",
+                        "✗".red(),
+                        deleted,
+                        if deleted == 1 { "" } else { "s" },
+                        days
+                    ),
+                    Ok(Err(e)) => eprintln!("{} Retention cleanup failed: {}", "error".red(), e),
+                }
+                tokio::time::sleep(RETENTION_INTERVAL).await;
+            }
+        });
+    }
+
+    // Resolve groups whose issue was closed
+    tokio::spawn(async move {
+        loop {
+            let db_path = db_path.clone();
+            let sync = tokio::task::spawn_blocking(move || {
+                sync_issue_status(&db_path).map_err(|e| e.to_string())
+            });
+            if let Ok(Err(e)) = sync.await {
+                eprintln!("{} Issue status sync failed: {}", "error".red(), e);
+            }
+            tokio::time::sleep(ISSUE_SYNC_INTERVAL).await;
+        }
+    });
+
+    // Start web server
+    let router = create_router(state);
+    if !addr.ip().is_loopback() {
+        eprintln!(
+            "{} Dashboard is reachable from other hosts on {} and has no authentication",
+            "warning".yellow(),
+            addr
+        );
+    }
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("{} Web server listening on http://{}", "✓".green(), addr);
+
+    axum::serve(listener, router).await?;
+
+    Ok(())
+}
+
+/// Send the alerts `report` raises to the app's integrations and record
+/// each delivery.
+fn notify_integrations(state: &Arc<AppState>, storage: &CrashStorage, report: &CrashReport) {
+    let routes = match route_alerts(storage, report, Utc::now().timestamp()) {
+        Ok(routes) if routes.is_empty() => return,
+        Ok(routes) => routes,
+        Err(e) => {
+            eprintln!("{} Failed to evaluate alerts: {}", "error".red(), e);
+            return;
+        }
+    };
+
+    deliver_alerts(state, routes);
+}
+
+/// Send an alert for each crash-rate spike `detector` finds to the app's
+/// integrations.
+async fn notify_spikes(state: &Arc<AppState>, detector: &mut AnomalyDetector) {
+    let now = Utc::now().timestamp();
+    let storage = state.storage.lock().await;
+    let spikes = match detector.detect(&storage, now) {
+        Ok(spikes) => spikes,
+        Err(e) => {
+            eprintln!("{} Failed to check crash rates: {}", "error".red(), e);
+            return;
+        }
+    };
+
+    let mut routes = Vec::new();
+    for spike in spikes {
+        let app_name = spike.app_name.clone();
+        let route = spike_alert(&storage, spike, now - 3600).and_then(|alert| {
+            let integrations = storage.integrations_for_app(app_name.as_deref())?;
+            Ok(alert.map(|alert| (alert, integrations)))
+        });
+        match route {
+            Ok(Some((alert, integrations))) => {
+                println!("{} {}", "⚠".yellow(), alert.summary());
+                routes.push((alert, integrations));
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("{} Failed to build spike alert: {}", "error".red(), e),
+        }
+    }
+    if !routes.is_empty() {
+        deliver_alerts(state, routes);
+    }
+}
+
+/// Send each alert to its integrations on a blocking thread and record
+/// the deliveries.
+fn deliver_alerts(state: &Arc<AppState>, routes: Vec<(Alert, Vec<Integration>)>) {
+    // Notifiers make blocking network requests
+    let state = state.clone();
+    tokio::task::spawn_blocking(move || {
+        for (alert, integrations) in &routes {
+            state.hooks.alert(alert);
+            for delivery in notify_all(integrations, alert) {
+                match &delivery.error {
+                    None => println!(
+                        "{} Sent {} to integration {}",
+                        "✓".green(),
+                        delivery.alert_kind.as_str(),
+                        delivery.integration_id
+                    ),
+                    Some(e) => eprintln!(
+                        "{} Integration {} failed: {}",
+                        "error".red(),
+                        delivery.integration_id,
+                        e
+                    ),
                 }
-                Err(e) => {
-                    eprintln!("{} Failed to store crash: {}", "error".red(), e);
+                if let Err(e) = state.storage.blocking_lock().record_delivery(&delivery) {
+                    eprintln!("{} Failed to record delivery: {}", "error".red(), e);
                 }
             }
         }
     });
-
-    // Start web server
-    let router = create_router(state);
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    println!("{} Web server listening on http://localhost:{}", "✓".green(), port);
-
-    axum::serve(listener, router).await?;
-
-    Ok(())
 }
 
 /// Subscribe to relay and send crashes to storage channel.
+///
+/// Resumes from the newest gift wrap previously processed from this relay,
+/// so crashes sent while the server was offline are backfilled.
 async fn subscribe_relay_with_storage(
     relay_url: &str,
     keys: &Keys,
     tx: &mpsc::Sender<ReceivedCrash>,
+    state: &AppState,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut seen: HashSet<EventId> = HashSet::new();
+    let last_seen = state.storage.lock().await.relay_last_seen(relay_url)?;
     let (ws_stream, _) = connect_async(relay_url).await?;
     let (mut write, mut read) = ws_stream.split();
 
     // Subscribe to gift wraps (kind 1059) addressed to us
-    let filter = Filter::new()
-        .kind(Kind::GiftWrap)
-        .pubkey(keys.public_key())
-        .limit(100);
+    let filter = gift_wrap_filter(keys.public_key(), last_seen);
 
     let subscription_id = "bugstr-listen";
     let req = format!(
@@ -578,9 +3507,13 @@ async fn subscribe_relay_with_storage(
         match msg {
             Ok(Message::Text(text)) => {
                 if let Some(crash) = handle_message_for_storage(&text, keys, &mut seen) {
+                    let wrap_created_at = crash.wrap_created_at;
                     if tx.send(crash).await.is_err() {
                         break;
                     }
+                    if let Err(e) = state.storage.lock().await.set_relay_last_seen(relay_url, wrap_created_at) {
+                        eprintln!("{} Failed to save cursor for {}: {}", "error".red(), relay_url, e);
+                    }
                 }
             }
             Ok(Message::Close(_)) => {
@@ -631,7 +3564,7 @@ fn handle_message_for_storage(
     );
 
     // Unwrap gift wrap
-    let rumor = match unwrap_gift_wrap(keys, &event) {
+    let rumor = match open_gift_wrap(keys, &event) {
         Ok(r) => r,
         Err(e) => {
             eprintln!("{} Failed to unwrap gift wrap {}: {}", "✗".red(), &event.id.to_hex()[..16], e);
@@ -639,25 +3572,702 @@ fn handle_message_for_storage(
         }
     };
 
-    // Decompress if needed
-    let content = decompress_payload(&rumor.content).unwrap_or_else(|_| rumor.content.clone());
+    // Chunked reports are assembled later; decompress direct ones if needed
+    let (content, manifest) = if rumor.kind == u64::from(RumorKind::Manifest.kind()) {
+        match serde_json::from_str::<Manifest>(&rumor.content) {
+            Ok(manifest) => (String::new(), Some(manifest)),
+            Err(e) => {
+                eprintln!("{} Invalid manifest in {}: {}", "✗".red(), &event.id.to_hex()[..16], e);
+                return None;
+            }
+        }
+    } else {
+        let content = decompress_payload(&rumor.content).unwrap_or_else(|_| rumor.content.clone());
+        (content, None)
+    };
 
     Some(ReceivedCrash {
         event_id: event.id.to_hex(),
         sender_pubkey: rumor.pubkey.clone(),
         created_at: rumor.created_at as i64,
         content,
+        wrap_created_at: event.created_at.as_u64() as i64,
+        manifest,
     })
 }
 
+/// Root hashes of the chunked reports being fetched, so a manifest that
+/// arrives from several relays, or comes due while it's fetched, is only
+/// fetched once at a time.
+#[derive(Clone, Default)]
+struct ChunkFetches(Arc<std::sync::Mutex<HashSet<String>>>);
+
+impl ChunkFetches {
+    /// Claims `root_hash` until the returned guard drops, or `None` if it is
+    /// already being fetched.
+    fn claim(&self, root_hash: &str) -> Option<ChunkFetch> {
+        let mut fetching = self.0.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        fetching.insert(root_hash.to_string()).then(|| ChunkFetch {
+            fetches: self.clone(),
+            root_hash: root_hash.to_string(),
+        })
+    }
+}
+
+struct ChunkFetch {
+    fetches: ChunkFetches,
+    root_hash: String,
+}
+
+impl Drop for ChunkFetch {
+    fn drop(&mut self) {
+        let mut fetching = self.fetches.0.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        fetching.remove(&self.root_hash);
+    }
+}
+
+/// Fetches and reassembles chunked reports for `serve`.
+#[derive(Clone)]
+struct ChunkAssembler {
+    state: Arc<AppState>,
+    keys: Keys,
+    /// Relays to ask for chunks when the manifest lists none.
+    relays: Vec<String>,
+    crash_tx: mpsc::Sender<CrashReport>,
+    fetches: ChunkFetches,
+    /// Largest payload accepted, in bytes.
+    max_size: usize,
+    progress: ChunkProgress,
+}
+
+/// How much `serve` logs while fetching chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChunkProgress {
+    /// Only reports that fail.
+    Quiet,
+    /// One line per fetch attempt.
+    Summary,
+    /// A line per relay asked, plus the summary.
+    Verbose,
+}
+
+impl ChunkProgress {
+    fn from_flags(verbose: bool, quiet: bool) -> Self {
+        match (verbose, quiet) {
+            (true, _) => Self::Verbose,
+            (false, true) => Self::Quiet,
+            (false, false) => Self::Summary,
+        }
+    }
+}
+
+/// Summary of one attempt at fetching a chunked report's missing chunks.
+fn chunk_fetch_summary(
+    event_id: &str,
+    fetched: usize,
+    missing: usize,
+    relays: usize,
+    failed: usize,
+) -> String {
+    let mut summary = format!(
+        "Chunked report {}: fetched {}/{} chunks from {} {}",
+        &event_id[..16.min(event_id.len())],
+        fetched,
+        missing,
+        relays,
+        if relays == 1 { "relay" } else { "relays" }
+    );
+    if failed > 0 {
+        summary.push_str(&format!(" ({} failed)", failed));
+    }
+    summary
+}
+
+impl ChunkAssembler {
+    /// Fetches the chunks of a chunked report and queues the assembled crash.
+    ///
+    /// Each chunk is stored as it arrives, so a report interrupted by a failed
+    /// relay or a restart is completed later without fetching them again.
+    async fn assemble(self, pending: PendingManifest) {
+        let root_hash = pending.manifest.root_hash.clone();
+        let Some(_fetch) = self.fetches.claim(&root_hash) else {
+            return;
+        };
+        let fetched = self.fetch_chunks(&pending).await.map_err(|e| e.to_string());
+        let content = match fetched {
+            Ok(content) => content,
+            Err(error) => {
+                let retry = self
+                    .state
+                    .storage
+                    .lock()
+                    .await
+                    .record_chunk_failure(&root_hash, &error, Utc::now().timestamp());
+                let next = match retry {
+                    Ok(Some(at)) => format!("retrying in {}s", at - Utc::now().timestamp()),
+                    Ok(None) => "giving up".to_string(),
+                    Err(e) => format!("failed to schedule a retry: {}", e),
+                };
+                eprintln!(
+                    "{} Chunked report {}: {} - {}",
+                    "warning".yellow(),
+                    &pending.event_id[..16.min(pending.event_id.len())],
+                    error,
+                    next
+                );
+                return;
+            }
+        };
+
+        let report = CrashReport::from_content(
+            pending.event_id,
+            pending.sender_pubkey,
+            pending.created_at,
+            pending.received_at,
+            content,
+        );
+        if self.crash_tx.send(report).await.is_ok() {
+            if let Err(e) = self.state.storage.lock().await.delete_manifest(&root_hash) {
+                eprintln!("{} Failed to clear chunks of {}: {}", "error".red(), root_hash, e);
+            }
+        }
+    }
+
+    /// Fetches the chunks of `pending` not already stored, from the relays
+    /// the manifest lists for them (or [`relays`](Self::relays) if it lists
+    /// none), and returns the reassembled, decompressed payload.
+    ///
+    /// Chunks go to the database as they arrive and are read back one at a
+    /// time, so at most the payload itself is held in memory, and no more
+    /// than the manifest's size, capped at [`max_size`](Self::max_size), is
+    /// stored.
+    async fn fetch_chunks(&self, pending: &PendingManifest) -> Result<String, Box<dyn std::error::Error>> {
+        let manifest = &pending.manifest;
+        let root_hash = &manifest.root_hash;
+        if manifest.total_size > self.max_size {
+            self.state.storage.lock().await.delete_manifest(root_hash)?;
+            return Err(format!(
+                "payload of {} bytes exceeds the {} byte limit",
+                manifest.total_size, self.max_size
+            )
+            .into());
+        }
+        let mut stored = vec![false; manifest.chunk_ids.len()];
+        let mut stored_size = 0;
+        {
+            let storage = self.state.storage.lock().await;
+            storage.save_manifest(pending)?;
+            for (index, size) in storage.get_chunk_sizes(root_hash)? {
+                if let Some(stored) = stored.get_mut(index) {
+                    *stored = true;
+                    stored_size += size;
+                }
+            }
+        }
+
+        // Ask each relay only for the chunks it accepted, when the sender says
+        let mut candidates: Vec<&String> = manifest.relays.iter().collect();
+        for relay in manifest.chunk_relays.values().flatten() {
+            if !candidates.contains(&relay) {
+                candidates.push(relay);
+            }
+        }
+        if candidates.is_empty() {
+            candidates = self.relays.iter().collect();
+        }
+        let missing_before = stored.iter().filter(|stored| !**stored).count();
+        let mut asked = 0;
+        let mut failed = 0;
+        let mut relay_error = None;
+        for relay in candidates {
+            if stored.iter().all(|stored| *stored) {
+                break;
+            }
+            let missing: Vec<EventId> = manifest
+                .chunk_ids
+                .iter()
+                .zip(&stored)
+                .filter(|(id, stored)| {
+                    !**stored
+                        && manifest.chunk_relays.get(*id).is_none_or(|hints| hints.contains(relay))
+                })
+                .filter_map(|(id, _)| EventId::from_hex(id).ok())
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+            let requested = missing.len();
+            asked += 1;
+            let events = match fetch_events(relay, missing).await {
+                Ok(events) => events,
+                Err(e) => {
+                    failed += 1;
+                    if self.progress == ChunkProgress::Verbose {
+                        eprintln!("{} Fetching chunks from {}: {}", "warning".yellow(), relay, e);
+                    }
+                    relay_error = Some(format!("{}: {}", relay, e));
+                    continue;
+                }
+            };
+            let missing_now = stored.iter().filter(|stored| !**stored).count();
+            // Relays may send a chunk twice or out of order; each is stored
+            // once at every position the manifest lists it
+            let mut ids = HashSet::new();
+            let wanted: Vec<(Event, Vec<usize>)> = events
+                .into_iter()
+                .filter(|event| ids.insert(event.id))
+                .filter_map(|event| {
+                    let indices: Vec<usize> = manifest
+                        .chunk_indices(&event.id.to_hex())
+                        .filter(|index| !stored[*index])
+                        .collect();
+                    (!indices.is_empty()).then_some((event, indices))
+                })
+                .collect();
+            let secret_key = self.keys.secret_key().clone();
+            let decrypted =
+                tokio::task::spawn_blocking(move || decrypt_chunks(&secret_key, wanted)).await?;
+            for (indices, content) in decrypted {
+                for index in indices {
+                    // Every copy of a signed chunk is the same, so a bad one
+                    // won't be fixed by retrying
+                    if let Err(e) = manifest.check_chunk(index, content.as_bytes()) {
+                        self.state.storage.lock().await.delete_manifest(root_hash)?;
+                        return Err(e.into());
+                    }
+                    stored_size += content.len();
+                    if stored_size > manifest.total_size {
+                        self.state.storage.lock().await.delete_manifest(root_hash)?;
+                        return Err(format!(
+                            "chunks hold more than the expected {} bytes",
+                            manifest.total_size
+                        )
+                        .into());
+                    }
+                    if !self.state.storage.lock().await.save_chunk(root_hash, index, &content)? {
+                        self.state.storage.lock().await.delete_manifest(root_hash)?;
+                        return Err(format!("conflicting copies of chunk {}", index).into());
+                    }
+                    stored[index] = true;
+                }
+            }
+            if self.progress == ChunkProgress::Verbose {
+                let got = missing_now - stored.iter().filter(|stored| !**stored).count();
+                println!("  {} {}: {}/{} chunks", "→".blue(), relay, got, requested);
+            }
+        }
+
+        let fetched = stored.iter().filter(|stored| **stored).count();
+        if self.progress != ChunkProgress::Quiet && asked > 0 {
+            let summary = chunk_fetch_summary(
+                &pending.event_id,
+                missing_before - (stored.len() - fetched),
+                missing_before,
+                asked,
+                failed,
+            );
+            println!("{} {}", "→".blue(), summary);
+        }
+        if fetched < stored.len() {
+            let mut error = format!("{} of {} chunks fetched", fetched, stored.len());
+            if let Some(relay_error) = relay_error {
+                error.push_str(&format!(" (last relay error: {})", relay_error));
+            }
+            return Err(error.into());
+        }
+        let mut content = String::with_capacity(manifest.total_size);
+        let read = {
+            let storage = self.state.storage.lock().await;
+            let chunks = (0..stored.len()).map(|index| {
+                storage
+                    .get_chunk(root_hash, index)
+                    .map_err(std::io::Error::other)?
+                    .map(String::into_bytes)
+                    .ok_or_else(|| std::io::Error::other(format!("chunk {} is missing", index)))
+            });
+            ChunkReader::for_manifest(chunks, manifest)
+                .with_max_size(self.max_size)
+                .read_to_string(&mut content)
+        };
+        if let Err(e) = read {
+            // Drop the stored chunks so the next attempt fetches them afresh
+            self.state.storage.lock().await.delete_manifest(root_hash)?;
+            return Err(format!("reassembling chunks: {}", e).into());
+        }
+        // Decompress once, as the manifest says; the chunks were verified,
+        // so a payload that fails to decode won't on a retry either
+        match decode_payload(content, manifest.encoding.as_deref()) {
+            Ok(content) => Ok(content),
+            Err(e) => {
+                self.state.storage.lock().await.delete_manifest(root_hash)?;
+                Err(format!("decoding payload: {}", e).into())
+            }
+        }
+    }
+}
+
+/// Verifies and decrypts chunk events in parallel, dropping any that fail.
+/// Each chunk's plaintext is returned with the manifest positions it fills,
+/// in the order given.
+fn decrypt_chunks(secret_key: &SecretKey, chunks: Vec<(Event, Vec<usize>)>) -> Vec<(Vec<usize>, String)> {
+    chunks
+        .into_par_iter()
+        .filter_map(|(event, indices)| {
+            verify_event(&event).ok()?;
+            let content = nip44::decrypt(secret_key, &event.pubkey, &event.content).ok()?;
+            Some((indices, content))
+        })
+        .collect()
+}
+
+/// How often `serve` looks for chunked reports due for another attempt.
+const CHUNK_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How long to wait for a relay to send the next chunk.
+const CHUNK_FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Fetches the stored events with `ids` from `relay_url`.
+async fn fetch_events(
+    relay_url: &str,
+    ids: Vec<EventId>,
+) -> Result<Vec<Event>, Box<dyn std::error::Error>> {
+    let (ws_stream, _) = connect_async(relay_url).await?;
+    let (mut write, mut read) = ws_stream.split();
+
+    let filter = Filter::new().ids(ids);
+    let req = format!(r#"["REQ","bugstr-chunks",{}]"#, serde_json::to_string(&filter)?);
+    write.send(Message::Text(req.into())).await?;
+
+    let mut events = Vec::new();
+    while let Some(msg) = tokio::time::timeout(CHUNK_FETCH_TIMEOUT, read.next()).await? {
+        let Message::Text(text) = msg? else {
+            continue;
+        };
+        let Ok(msg) = serde_json::from_str::<Vec<serde_json::Value>>(&text) else {
+            continue;
+        };
+        match msg.first().and_then(|v| v.as_str()) {
+            Some("EVENT") if msg.len() >= 3 => {
+                if let Ok(event) = serde_json::from_value::<Event>(msg[2].clone()) {
+                    events.push(event);
+                }
+            }
+            Some("EOSE") | Some("CLOSED") => break,
+            _ => {}
+        }
+    }
+    let _ = write.send(Message::Close(None)).await;
+    Ok(events)
+}
+
+// ============================================================================
+// Forward command (re-gift-wrap to downstream receivers, no report storage)
+// ============================================================================
+
+/// How long to wait for a relay to accept a forwarded report.
+const PUBLISH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Unwrap crash reports received on `relays` and gift wrap each again for
+/// every pubkey in `to`, publishing to `publish_relays`. Reports only exist
+/// decrypted in memory while they are rewrapped.
+///
+/// `db_path` keeps only which gift wraps were forwarded and the newest seen
+/// on each relay, so a restart resumes where it left off without sending
+/// any report downstream twice.
+async fn forward(
+    privkey: &str,
+    relays: &[String],
+    to: &[String],
+    publish_relays: &[String],
+    db_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let secret = parse_privkey(privkey)?;
+    let keys = Keys::new(secret);
+    let secret_key = zeroize::Zeroizing::new(keys.secret_key().to_secret_bytes());
+    let recipients = to
+        .iter()
+        .map(|pubkey| PublicKey::parse(pubkey))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let storage = CrashStorage::open(db_path)?;
+    // Relays resend gift wraps back to a cursor less the timestamp jitter;
+    // older ones won't be seen again
+    let mut oldest_cursor = Some(i64::MAX);
+    for relay in relays {
+        oldest_cursor = oldest_cursor.zip(storage.forward_last_seen(relay)?).map(|(a, b)| a.min(b));
+    }
+    if let Some(oldest) = oldest_cursor {
+        storage.prune_forwarded(oldest.saturating_sub(GIFT_WRAP_TIMESTAMP_JITTER as i64))?;
+    }
+    let storage = Arc::new(Mutex::new(storage));
+
+    println!("{} Forwarding crash reports...", "bugstr".green().bold());
+    println!("  Pubkey: {}", keys.public_key().to_bech32()?);
+    println!("  Relays: {}", relays.join(", "));
+    for recipient in &recipients {
+        println!("  To: {}", recipient.to_bech32()?);
+    }
+    println!("  Publishing to: {}", publish_relays.join(", "));
+    println!("  Database: {}", db_path.display());
+    println!();
+
+    let (tx, mut rx) = mpsc::channel::<(String, Event)>(100);
+    for relay_url in relays {
+        let relay = relay_url.clone();
+        let keys = keys.clone();
+        let tx = tx.clone();
+        let storage = storage.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = subscribe_gift_wraps(&relay, &keys, &tx, &storage).await {
+                    eprintln!("{} Relay {} error: {} - reconnecting...", "error".red(), relay, e);
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            }
+        });
+    }
+    drop(tx);
+
+    // Relays deliver the same gift wrap independently; forward it once, and
+    // never again after a restart
+    let mut seen: HashSet<EventId> = HashSet::new();
+    while let Some((relay, gift_wrap)) = rx.recv().await {
+        let wrap_created_at = gift_wrap.created_at.as_u64() as i64;
+        if seen.insert(gift_wrap.id) {
+            let id = gift_wrap.id.to_hex();
+            let forwarded = storage.lock().await.is_forwarded(&id);
+            match forwarded {
+                Ok(true) => {}
+                Ok(false) => {
+                    if forward_gift_wrap(&keys, &secret_key, &recipients, publish_relays, &gift_wrap).await {
+                        if let Err(e) = storage.lock().await.set_forwarded(&id, wrap_created_at) {
+                            eprintln!("{} Failed to record forward of {}: {}", "error".red(), &id[..16], e);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("{} Failed to check forward of {}: {}", "error".red(), &id[..16], e),
+            }
+        }
+        if let Err(e) = storage.lock().await.set_forward_last_seen(&relay, wrap_created_at) {
+            eprintln!("{} Failed to save cursor for {}: {}", "error".red(), relay, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Gift wraps `gift_wrap`'s report again for each of `recipients` and
+/// publishes it. Returns `false` if it should be retried: some recipient
+/// didn't get it. Reports that can't be forwarded at all return `true`.
+async fn forward_gift_wrap(
+    keys: &Keys,
+    secret_key: &[u8; 32],
+    recipients: &[PublicKey],
+    publish_relays: &[String],
+    gift_wrap: &Event,
+) -> bool {
+    let short_id = &gift_wrap.id.to_hex()[..16];
+    let rumor = match unwrap_gift_wrap(keys, gift_wrap) {
+        Ok(rumor) => rumor,
+        Err(e) => {
+            eprintln!("{} Not forwarding {}: {}", "✗".red(), short_id, e);
+            return true;
+        }
+    };
+    let rumor = UnsignedNostrEvent::new(
+        rumor.pubkey,
+        rumor.created_at,
+        rumor.kind as u16,
+        rumor.tags,
+        rumor.content,
+    );
+
+    let mut delivered = true;
+    for recipient in recipients {
+        let wrapped = match rewrap(&rumor, secret_key, &recipient.to_hex()) {
+            Ok(wrapped) => wrapped,
+            Err(e) => {
+                eprintln!("{} Failed to rewrap {}: {}", "✗".red(), short_id, e);
+                delivered = false;
+                continue;
+            }
+        };
+        let wrapped = &wrapped;
+        let publishes = publish_relays.iter().map(|relay| async move {
+            let result = tokio::time::timeout(PUBLISH_TIMEOUT, publish_event(relay, wrapped))
+                .await
+                .unwrap_or_else(|_| Err(SendError::Relay("timed out".into())));
+            (relay, result)
+        });
+        let mut accepted = 0;
+        for (relay, result) in futures_util::future::join_all(publishes).await {
+            match result {
+                Ok(()) => accepted += 1,
+                Err(e) => eprintln!("{} {} rejected forward of {}: {}", "warn".yellow(), relay, short_id, e),
+            }
+        }
+        if accepted > 0 {
+            println!(
+                "{} Forwarded {} to {} ({}/{} relays)",
+                "✓".green(),
+                short_id,
+                &recipient.to_hex()[..16],
+                accepted,
+                publish_relays.len()
+            );
+        } else {
+            eprintln!("{} Could not forward {} to {}", "✗".red(), short_id, &recipient.to_hex()[..16]);
+            delivered = false;
+        }
+    }
+    delivered
+}
+
+/// Subscribe to gift wraps addressed to us, from the newest `forward` has
+/// handled from `relay_url`, and send each to `tx` with the relay.
+async fn subscribe_gift_wraps(
+    relay_url: &str,
+    keys: &Keys,
+    tx: &mpsc::Sender<(String, Event)>,
+    storage: &Mutex<CrashStorage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let last_seen = storage.lock().await.forward_last_seen(relay_url)?;
+    let (ws_stream, _) = connect_async(relay_url).await?;
+    let (mut write, mut read) = ws_stream.split();
+
+    let filter = gift_wrap_filter(keys.public_key(), last_seen);
+    let req = format!(r#"["REQ","bugstr-forward",{}]"#, serde_json::to_string(&filter)?);
+    write.send(Message::Text(req.into())).await?;
+    println!("{} Connected to {}", "✓".green(), relay_url.cyan());
+
+    while let Some(msg) = read.next().await {
+        match msg? {
+            Message::Text(text) => {
+                let Ok(msg) = serde_json::from_str::<Vec<serde_json::Value>>(&text) else {
+                    continue;
+                };
+                if msg.first().and_then(|v| v.as_str()) != Some("EVENT") || msg.len() < 3 {
+                    continue;
+                }
+                if let Ok(event) = serde_json::from_value::<Event>(msg[2].clone()) {
+                    if tx.send((relay_url.to_string(), event)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Relay filter for gift wraps to `pubkey` newer than `last_seen`, the
+/// `created_at` of the newest already processed, or the latest 100 without
+/// one. Covers the gift wrap timestamp jitter (see [`TimeWindow::apply`]).
+fn gift_wrap_filter(pubkey: PublicKey, last_seen: Option<i64>) -> Filter {
+    let window = TimeWindow {
+        since: last_seen.map(|t| t.max(0) as u64),
+        until: None,
+    };
+    window.apply(Filter::new().kind(Kind::GiftWrap).pubkey(pubkey))
+}
+
 // ============================================================================
 // Original listen command (terminal-only, no storage)
 // ============================================================================
 
+/// NIP-59 gift wraps carry a randomized `created_at` up to two days in the
+/// past, so relay-side `since` filters must reach back this far.
+const GIFT_WRAP_TIMESTAMP_JITTER: u64 = 2 * 24 * 60 * 60;
+
+/// Time bounds (unix seconds) applied to the crash creation time.
+#[derive(Clone, Copy, Debug, Default)]
+struct TimeWindow {
+    since: Option<u64>,
+    until: Option<u64>,
+}
+
+impl TimeWindow {
+    fn is_bounded(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    fn contains(&self, created_at: u64) -> bool {
+        self.since.is_none_or(|since| created_at >= since)
+            && self.until.is_none_or(|until| created_at <= until)
+    }
+
+    /// Relay filter for gift wraps that may contain crashes in this window.
+    ///
+    /// Gift wrap timestamps are randomized backwards, so `since` is widened
+    /// and the exact window is applied to the decrypted rumor instead.
+    fn apply(&self, filter: Filter) -> Filter {
+        if !self.is_bounded() {
+            return filter.limit(100);
+        }
+        let mut filter = filter;
+        if let Some(since) = self.since {
+            filter = filter.since(Timestamp::from(since.saturating_sub(GIFT_WRAP_TIMESTAMP_JITTER)));
+        }
+        if let Some(until) = self.until {
+            filter = filter.until(Timestamp::from(until));
+        }
+        filter
+    }
+}
+
+/// Parse a `--since`/`--until` value into unix seconds.
+fn parse_time_arg(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Ok(secs);
+    }
+
+    // Relative age: 30m, 12h, 2d, 1w
+    if let Some(unit) = value.chars().last() {
+        let multiplier: Option<u64> = match unit {
+            's' => Some(1),
+            'm' => Some(60),
+            'h' => Some(60 * 60),
+            'd' => Some(24 * 60 * 60),
+            'w' => Some(7 * 24 * 60 * 60),
+            _ => None,
+        };
+        // The unit is ASCII, so slicing it off stays on a char boundary
+        if let Some(multiplier) = multiplier {
+            if let Ok(amount) = value[..value.len() - 1].parse::<u64>() {
+                let age = amount
+                    .checked_mul(multiplier)
+                    .ok_or_else(|| format!("time '{}' is too far in the past", value))?;
+                return Ok((Utc::now().timestamp() as u64).saturating_sub(age));
+            }
+        }
+    }
+
+    if let Ok(t) = DateTime::parse_from_rfc3339(value) {
+        return Ok(t.timestamp().max(0) as u64);
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"] {
+        if let Ok(t) = chrono::NaiveDateTime::parse_from_str(value, format) {
+            return Ok(t.and_utc().timestamp().max(0) as u64);
+        }
+    }
+    if let Ok(d) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(d.and_time(chrono::NaiveTime::MIN).and_utc().timestamp().max(0) as u64);
+    }
+
+    Err(format!(
+        "invalid time '{}' (expected unix seconds, RFC 3339, YYYY-MM-DD[ HH:MM], or an age like 12h)",
+        value
+    ))
+}
+
 async fn listen(
     privkey: &str,
     relays: &[String],
     format: OutputFormat,
+    window: TimeWindow,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let secret = parse_privkey(privkey)?;
     let keys = Keys::new(secret);
@@ -669,6 +4279,14 @@ async fn listen(
     );
     println!("  Pubkey: {}", pubkey.to_bech32()?);
     println!("  Relays: {}", relays.join(", "));
+    if window.is_bounded() {
+        let show = |t: Option<u64>| {
+            t.and_then(|t| DateTime::from_timestamp(t as i64, 0))
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                .unwrap_or_else(|| "…".to_string())
+        };
+        println!("  Window: {} → {}", show(window.since), show(window.until));
+    }
     println!();
 
     // Connect to all relays concurrently
@@ -679,7 +4297,7 @@ async fn listen(
         let format = format.clone();
 
         let handle = tokio::spawn(async move {
-            if let Err(e) = subscribe_relay(&relay, &keys, &format).await {
+            if let Err(e) = subscribe_relay(&relay, &keys, &format, window).await {
                 eprintln!("{} Relay {} error: {}", "error".red(), relay, e);
             }
         });
@@ -698,16 +4316,14 @@ async fn subscribe_relay(
     relay_url: &str,
     keys: &Keys,
     format: &OutputFormat,
+    window: TimeWindow,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut seen: HashSet<EventId> = HashSet::new();
     let (ws_stream, _) = connect_async(relay_url).await?;
     let (mut write, mut read) = ws_stream.split();
 
     // Subscribe to gift wraps (kind 1059) addressed to us
-    let filter = Filter::new()
-        .kind(Kind::GiftWrap)
-        .pubkey(keys.public_key())
-        .limit(100);
+    let filter = window.apply(Filter::new().kind(Kind::GiftWrap).pubkey(keys.public_key()));
 
     let subscription_id = "bugstr-listen";
     let req = format!(
@@ -727,7 +4343,12 @@ async fn subscribe_relay(
     while let Some(msg) = read.next().await {
         match msg {
             Ok(Message::Text(text)) => {
-                if let Err(e) = handle_message(&text, keys, format, &mut seen) {
+                // A bounded window has no live tail: stop once stored events are sent
+                if window.until.is_some() && text.starts_with(r#"["EOSE""#) {
+                    let _ = write.send(Message::Close(None)).await;
+                    break;
+                }
+                if let Err(e) = handle_message(&text, keys, format, window, &mut seen) {
                     eprintln!("{} Parse error: {}", "warn".yellow(), e);
                 }
             }
@@ -750,6 +4371,7 @@ fn handle_message(
     text: &str,
     keys: &Keys,
     format: &OutputFormat,
+    window: TimeWindow,
     seen: &mut HashSet<EventId>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let msg: Vec<serde_json::Value> = serde_json::from_str(text)?;
@@ -773,6 +4395,9 @@ fn handle_message(
 
     // Unwrap gift wrap
     let unwrapped = unwrap_gift_wrap(keys, &event)?;
+    if !window.contains(unwrapped.created_at) {
+        return Ok(());
+    }
 
     // Output based on format
     match format {
@@ -799,15 +4424,38 @@ struct Rumor {
 }
 
 fn unwrap_gift_wrap(keys: &Keys, gift_wrap: &Event) -> Result<Rumor, Box<dyn std::error::Error>> {
+    let rumor = open_gift_wrap(keys, gift_wrap)?;
+    if rumor.kind == u64::from(RumorKind::Manifest.kind()) {
+        return Err("chunked crash reports (manifest rumors) are only assembled by `bugstr serve`".into());
+    }
+    Ok(rumor)
+}
+
+/// Unwraps a gift wrap holding any bugstr rumor, chunk manifests included.
+fn open_gift_wrap(keys: &Keys, gift_wrap: &Event) -> Result<Rumor, Box<dyn std::error::Error>> {
+    // Reject forged or corrupted events before decrypting anything
+    verify_event(gift_wrap)?;
+
     // Decrypt gift wrap to get seal
     let seal_json = nip44::decrypt(keys.secret_key(), &gift_wrap.pubkey, &gift_wrap.content)?;
     let seal: Event = serde_json::from_str(&seal_json)?;
+    verify_event(&seal)?;
 
     // Decrypt seal to get rumor (unsigned, so parse as Rumor not Event)
     let rumor_json = nip44::decrypt(keys.secret_key(), &seal.pubkey, &seal.content)?;
     let rumor: Rumor = serde_json::from_str(&rumor_json)?;
 
-    Ok(rumor)
+    match u16::try_from(rumor.kind).ok().and_then(RumorKind::from_kind) {
+        Some(_) => Ok(rumor),
+        None => Err(format!("rumor kind {} is not a crash report", rumor.kind).into()),
+    }
+}
+
+/// Checks the event's ID and signature.
+fn verify_event(event: &Event) -> Result<(), Box<dyn std::error::Error>> {
+    let signed: SignedNostrEvent = serde_json::from_value(serde_json::to_value(event)?)?;
+    signed.verify()?;
+    Ok(())
 }
 
 fn print_pretty(rumor: &Rumor, gift_wrap: &Event) {
@@ -861,3 +4509,157 @@ fn print_pretty(rumor: &Rumor, gift_wrap: &Event) {
 
     println!();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_arg() {
+        assert_eq!(parse_time_arg("1700000000").unwrap(), 1_700_000_000);
+        assert_eq!(parse_time_arg("2024-01-02").unwrap(), 1_704_153_600);
+        let now = Utc::now().timestamp() as u64;
+        assert!(parse_time_arg("2h").unwrap().abs_diff(now - 7200) <= 1);
+    }
+
+    #[test]
+    fn test_parse_time_arg_rejects_bad_input() {
+        // Non-ASCII last character must not be sliced mid-char
+        assert!(parse_time_arg("é").is_err());
+        assert!(parse_time_arg("5é").is_err());
+        assert!(parse_time_arg("99999999999999999w").is_err());
+    }
+
+    #[test]
+    fn test_chunk_fetches_claim_once() {
+        let fetches = ChunkFetches::default();
+        let fetch = fetches.claim("root").unwrap();
+        assert!(fetches.clone().claim("root").is_none());
+        assert!(fetches.claim("other").is_some());
+        drop(fetch);
+        assert!(fetches.claim("root").is_some());
+    }
+
+    #[test]
+    fn test_ci_timeout_skips_long_running_commands() {
+        let command = |args: &[&str]| Cli::try_parse_from(args).unwrap().command;
+        assert!(command(&["bugstr", "--ci", "serve"]).runs_until_stopped());
+        assert!(command(&["bugstr", "--ci", "listen"]).runs_until_stopped());
+        assert!(!command(&["bugstr", "--ci", "listen", "--until", "0h"]).runs_until_stopped());
+        assert!(!command(&["bugstr", "--ci", "relay-test"]).runs_until_stopped());
+    }
+
+    /// A payload too large to send directly, and the chunk events and gift
+    /// wrap carrying it to `keys`.
+    fn chunked_report(keys: &Keys) -> (String, Vec<SignedNostrEvent>, SignedNostrEvent) {
+        use sha2::Digest;
+        let mut stack = String::new();
+        let mut hash = sha2::Sha256::digest(b"seed");
+        while stack.len() < 300_000 {
+            hash = sha2::Sha256::digest(hash);
+            stack.push_str(&hex::encode(hash));
+        }
+        let sender = bugstr::CrashSender::new(bugstr::BugstrConfig {
+            recipient_pubkey: keys.public_key().to_hex(),
+            ..Default::default()
+        });
+        let payload = sender.crash_payload("boom", &stack);
+        let outgoing = sender.build(&payload).unwrap();
+        (payload, outgoing.chunks, outgoing.gift_wrap)
+    }
+
+    fn to_event(event: &SignedNostrEvent) -> Event {
+        serde_json::from_value(serde_json::to_value(event).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_decrypt_chunks_in_parallel_keeps_order() {
+        let keys = Keys::generate();
+        let (payload, chunk_events, _) = chunked_report(&keys);
+        assert!(chunk_events.len() > 4);
+        let mut chunks: Vec<(Event, Vec<usize>)> = chunk_events
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| (to_event(chunk), vec![index]))
+            .collect();
+        // A chunk that fails verification is dropped, not stored
+        chunks[1].0.content.push('x');
+
+        let decrypted = decrypt_chunks(keys.secret_key(), chunks);
+        let indices: Vec<usize> = decrypted.iter().flat_map(|(indices, _)| indices.clone()).collect();
+        let expected: Vec<usize> = (0..chunk_events.len()).filter(|index| *index != 1).collect();
+        assert_eq!(indices, expected);
+
+        let chunks = chunk_events
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| (to_event(chunk), vec![index]))
+            .collect();
+        let content: String =
+            decrypt_chunks(keys.secret_key(), chunks).into_iter().map(|(_, content)| content).collect();
+        assert_eq!(decode_payload(content, None).unwrap(), payload);
+    }
+
+    #[tokio::test]
+    async fn test_decrypts_chunked_report_from_file() {
+        let keys = Keys::generate();
+        let (payload, chunks, gift_wrap) = chunked_report(&keys);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let lines: Vec<String> = std::iter::once(&gift_wrap)
+            .chain(chunks.iter().rev())
+            .map(|event| serde_json::to_string(event).unwrap())
+            .collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let events = parse_exported_events(&read_input(path.to_str().unwrap()).unwrap()).unwrap();
+        let reports = decrypt_reports(&keys, &events, &[]).await;
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].1.content, payload);
+        let privkey = keys.secret_key().to_secret_hex();
+        assert!(decrypt_events(&privkey, path.to_str().unwrap(), &[], OutputFormat::Raw).await.is_ok());
+
+        // Without every chunk, and no relays to ask, there is nothing to print
+        let partial: Vec<Event> = events.into_iter().take(chunks.len()).collect();
+        assert!(decrypt_reports(&keys, &partial, &[]).await.is_empty());
+    }
+
+    #[test]
+    fn test_gift_wrap_filter_resumes_from_cursor() {
+        let pubkey = Keys::generate().public_key();
+        let first_run = serde_json::to_value(gift_wrap_filter(pubkey, None)).unwrap();
+        assert_eq!(first_run["limit"], 100);
+        assert!(first_run.get("since").is_none());
+
+        // Resuming reaches back over the gift wrap jitter, without a limit
+        let resumed = serde_json::to_value(gift_wrap_filter(pubkey, Some(1_700_000_000))).unwrap();
+        assert_eq!(resumed["since"], 1_700_000_000 - GIFT_WRAP_TIMESTAMP_JITTER);
+        assert!(resumed.get("limit").is_none());
+        assert_eq!(resumed["kinds"][0], 1059);
+    }
+
+    #[test]
+    fn test_chunk_progress_flags() {
+        let progress = |args: &[&str]| match Cli::try_parse_from(args).unwrap().command {
+            Commands::Serve { verbose, quiet, .. } => ChunkProgress::from_flags(verbose, quiet),
+            _ => unreachable!(),
+        };
+        assert_eq!(progress(&["bugstr", "serve"]), ChunkProgress::Summary);
+        assert_eq!(progress(&["bugstr", "serve", "-v"]), ChunkProgress::Verbose);
+        assert_eq!(progress(&["bugstr", "serve", "--quiet"]), ChunkProgress::Quiet);
+        assert!(Cli::try_parse_from(["bugstr", "serve", "-v", "-q"]).is_err());
+    }
+
+    #[test]
+    fn test_chunk_fetch_summary() {
+        let id = "0123456789abcdef0123";
+        assert_eq!(
+            chunk_fetch_summary(id, 50, 50, 1, 0),
+            "Chunked report 0123456789abcdef: fetched 50/50 chunks from 1 relay"
+        );
+        assert_eq!(
+            chunk_fetch_summary(id, 10, 50, 3, 2),
+            "Chunked report 0123456789abcdef: fetched 10/50 chunks from 3 relays (2 failed)"
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Breadcrumbs: the trail of events leading up to a crash.
+//!
+//! [`Breadcrumbs`] keeps the most recent events an app records (navigation,
+//! network requests, user actions, ...) in a bounded ring buffer shared
+//! across threads. A [`CrashSender`](crate::CrashSender) given the buffer
+//! with [`with_breadcrumbs`](crate::CrashSender::with_breadcrumbs) adds its
+//! contents to every crash payload as a `breadcrumbs` list, which the
+//! receiver stores alongside the crash.
+//!
+//! ```
+//! use bugstr::breadcrumbs::{Breadcrumbs, Level};
+//!
+//! let breadcrumbs = Breadcrumbs::new(100);
+//! breadcrumbs.record("navigation", "opened settings", Level::Info, Default::default());
+//! assert_eq!(breadcrumbs.snapshot()[0].message, "opened settings");
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Breadcrumbs kept by [`Breadcrumbs::default`].
+pub const DEFAULT_CAPACITY: usize = 100;
+
+/// Severity of a breadcrumb.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Debug,
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+/// One recorded event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Breadcrumb {
+    /// Unix time in milliseconds, like the payload's `timestamp`.
+    pub timestamp: u64,
+    pub category: String,
+    pub message: String,
+    #[serde(default)]
+    pub level: Level,
+    /// Extra structured context, e.g. a request's URL and status.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub data: BTreeMap<String, serde_json::Value>,
+}
+
+/// A bounded, thread-safe ring buffer of breadcrumbs. Clones share the
+/// same buffer; once full, recording drops the oldest breadcrumb.
+#[derive(Debug, Clone)]
+pub struct Breadcrumbs {
+    buffer: Arc<Mutex<VecDeque<Breadcrumb>>>,
+    capacity: usize,
+}
+
+impl Default for Breadcrumbs {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl Breadcrumbs {
+    /// A buffer keeping the last `capacity` breadcrumbs.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffer: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Records an event that happened now.
+    pub fn record(
+        &self,
+        category: impl Into<String>,
+        message: impl Into<String>,
+        level: Level,
+        data: BTreeMap<String, serde_json::Value>,
+    ) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        self.push(Breadcrumb {
+            timestamp,
+            category: category.into(),
+            message: message.into(),
+            level,
+            data,
+        });
+    }
+
+    /// Adds an already built breadcrumb.
+    pub fn push(&self, breadcrumb: Breadcrumb) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut buffer = self.lock();
+        if buffer.len() == self.capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(breadcrumb);
+    }
+
+    /// The recorded breadcrumbs, oldest first.
+    pub fn snapshot(&self) -> Vec<Breadcrumb> {
+        self.lock().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Locks the buffer, even if a thread panicked while holding it: the
+    /// breadcrumbs matter most when reporting that very panic.
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<Breadcrumb>> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The breadcrumbs in a JSON crash payload's `breadcrumbs` list, skipping
+/// malformed entries. Empty for other content.
+pub fn parse_breadcrumbs(content: &str) -> Vec<Breadcrumb> {
+    if !content.contains("\"breadcrumbs\"") {
+        return Vec::new();
+    }
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    let Some(items) = json.get("breadcrumbs").and_then(|b| b.as_array()) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| serde_json::from_value(item.clone()).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_drops_oldest() {
+        let breadcrumbs = Breadcrumbs::new(2);
+        let shared = breadcrumbs.clone();
+        for message in ["one", "two", "three"] {
+            shared.record("test", message, Level::Info, BTreeMap::new());
+        }
+        let messages: Vec<_> = breadcrumbs.snapshot().into_iter().map(|b| b.message).collect();
+        assert_eq!(messages, ["two", "three"]);
+
+        breadcrumbs.clear();
+        assert!(shared.is_empty());
+        Breadcrumbs::new(0).record("test", "dropped", Level::Info, BTreeMap::new());
+    }
+
+    #[test]
+    fn test_records_across_threads() {
+        let breadcrumbs = Breadcrumbs::new(1000);
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let breadcrumbs = breadcrumbs.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        breadcrumbs.record("thread", format!("{}", i), Level::Debug, BTreeMap::new());
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(breadcrumbs.len(), 400);
+    }
+
+    #[test]
+    fn test_parse_breadcrumbs() {
+        let content = serde_json::json!({
+            "message": "boom",
+            "breadcrumbs": [
+                {"timestamp": 1, "category": "http", "message": "GET /", "level": "warning",
+                 "data": {"status": 500}},
+                {"timestamp": 2, "category": "ui", "message": "tap"},
+                {"message": "no timestamp"},
+            ],
+        })
+        .to_string();
+        let breadcrumbs = parse_breadcrumbs(&content);
+        assert_eq!(breadcrumbs.len(), 2);
+        assert_eq!(breadcrumbs[0].level, Level::Warning);
+        assert_eq!(breadcrumbs[0].data["status"], 500);
+        assert_eq!(breadcrumbs[1].level, Level::Info);
+        assert!(parse_breadcrumbs("java.lang.NullPointerException").is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+//! Streaming chunking of large payloads.
+//!
+//! [`CrashSender`](crate::CrashSender) splits payloads held in memory. For
+//! payloads too large for that, such as ones carrying minidumps or logs,
+//! [`ChunkWriter`] splits whatever is written to it into chunks as it goes,
+//! and [`ChunkReader`] reads chunks back in order, checking each against
+//! the [`Manifest`]'s per-chunk size and checksum as it is read and the
+//! total size and root hash once the last one is. Neither holds more than
+//! one chunk in memory.
+//!
+//! ```
+//! use std::io::{Read, Write};
+//! use bugstr::chunking::{ChunkReader, ChunkWriter};
+//!
+//! let mut chunks = Vec::new();
+//! let mut writer = ChunkWriter::new(|chunk| {
+//!     chunks.push(chunk);
+//!     Ok(())
+//! })
+//! .with_chunk_size(4);
+//! writer.write_all(b"hello, world")?;
+//! let payload = writer.finish()?;
+//! assert_eq!(payload.chunks, 3);
+//!
+//! let mut reader = ChunkReader::new(chunks.into_iter().map(Ok), payload.root_hash, payload.total_size);
+//! let mut content = String::new();
+//! reader.read_to_string(&mut content)?;
+//! assert_eq!(content, "hello, world");
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::io::{self, Read, Write};
+
+use sha2::{Digest, Sha256};
+
+use crate::sender::{Manifest, CHUNK_SIZE};
+
+/// Largest payload [`ChunkWriter`] and [`ChunkReader`] accept by default.
+pub const DEFAULT_MAX_PAYLOAD_SIZE: usize = 512 * 1024 * 1024;
+
+/// What a [`ChunkWriter`] wrote, for the payload's [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkedPayload {
+    /// SHA256 of the whole payload (lowercase hex).
+    pub root_hash: String,
+    /// Size of the payload in bytes.
+    pub total_size: usize,
+    /// Number of chunks passed to the sink.
+    pub chunks: usize,
+}
+
+/// Splits everything written to it into chunks of at most
+/// [`CHUNK_SIZE`] bytes, passing each full chunk to `sink`.
+///
+/// The last, partial chunk is only passed on by [`finish`](Self::finish).
+/// Writes that would take the payload past the size cap fail.
+pub struct ChunkWriter<F> {
+    sink: F,
+    chunk_size: usize,
+    max_size: usize,
+    buffer: Vec<u8>,
+    hasher: Sha256,
+    total_size: usize,
+    chunks: usize,
+}
+
+impl<F: FnMut(Vec<u8>) -> io::Result<()>> ChunkWriter<F> {
+    pub fn new(sink: F) -> Self {
+        Self {
+            sink,
+            chunk_size: CHUNK_SIZE,
+            max_size: DEFAULT_MAX_PAYLOAD_SIZE,
+            buffer: Vec::new(),
+            hasher: Sha256::new(),
+            total_size: 0,
+            chunks: 0,
+        }
+    }
+
+    /// Largest chunk, in bytes.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Largest payload accepted, in bytes.
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Passes on the last chunk and returns the payload's size and hash.
+    pub fn finish(mut self) -> io::Result<ChunkedPayload> {
+        if !self.buffer.is_empty() {
+            self.emit()?;
+        }
+        Ok(ChunkedPayload {
+            root_hash: hex::encode(self.hasher.finalize()),
+            total_size: self.total_size,
+            chunks: self.chunks,
+        })
+    }
+
+    fn emit(&mut self) -> io::Result<()> {
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(self.chunk_size));
+        (self.sink)(chunk)?;
+        self.chunks += 1;
+        Ok(())
+    }
+}
+
+impl<F: FnMut(Vec<u8>) -> io::Result<()>> Write for ChunkWriter<F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.total_size.saturating_add(buf.len()) > self.max_size {
+            return Err(too_large(self.max_size));
+        }
+        let mut rest = buf;
+        while !rest.is_empty() {
+            let take = (self.chunk_size - self.buffer.len()).min(rest.len());
+            let (head, tail) = rest.split_at(take);
+            self.buffer.extend_from_slice(head);
+            self.hasher.update(head);
+            self.total_size += take;
+            rest = tail;
+            if self.buffer.len() == self.chunk_size {
+                self.emit()?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads a chunked payload back from its chunks, in order.
+///
+/// Reading fails with [`io::ErrorKind::InvalidData`] if the chunks add up
+/// to more than the expected size or the size cap, if a chunk doesn't match
+/// its manifest entry, and at the end if the size or root hash don't match.
+pub struct ChunkReader<I> {
+    chunks: I,
+    /// The manifest whose per-chunk sizes and checksums are checked.
+    manifest: Option<Manifest>,
+    /// Index of the next chunk.
+    index: usize,
+    current: Vec<u8>,
+    position: usize,
+    hasher: Sha256,
+    read: usize,
+    root_hash: String,
+    total_size: usize,
+    max_size: usize,
+    verified: bool,
+}
+
+impl<I: Iterator<Item = io::Result<Vec<u8>>>> ChunkReader<I> {
+    /// Reads `chunks`, expecting `total_size` bytes hashing to `root_hash`.
+    pub fn new(chunks: I, root_hash: impl Into<String>, total_size: usize) -> Self {
+        Self {
+            chunks,
+            manifest: None,
+            index: 0,
+            current: Vec::new(),
+            position: 0,
+            hasher: Sha256::new(),
+            read: 0,
+            root_hash: root_hash.into(),
+            total_size,
+            max_size: DEFAULT_MAX_PAYLOAD_SIZE,
+            verified: false,
+        }
+    }
+
+    /// Reads `chunks` of the payload described by `manifest`, checking
+    /// each against its [`Manifest::check_chunk`] entry.
+    pub fn for_manifest(chunks: I, manifest: &Manifest) -> Self {
+        Self {
+            manifest: Some(manifest.clone()),
+            ..Self::new(chunks, manifest.root_hash.clone(), manifest.total_size)
+        }
+    }
+
+    /// Largest payload accepted, in bytes.
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Checks the size and hash of everything read.
+    fn verify(&mut self) -> io::Result<()> {
+        if self.verified {
+            return Ok(());
+        }
+        if self.read != self.total_size {
+            return Err(invalid(format!(
+                "chunks hold {} bytes, expected {}",
+                self.read, self.total_size
+            )));
+        }
+        let root_hash = hex::encode(std::mem::take(&mut self.hasher).finalize());
+        if !root_hash.eq_ignore_ascii_case(&self.root_hash) {
+            return Err(invalid("chunks don't match the root hash".to_string()));
+        }
+        self.verified = true;
+        Ok(())
+    }
+}
+
+impl<I: Iterator<Item = io::Result<Vec<u8>>>> Read for ChunkReader<I> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.total_size > self.max_size {
+            return Err(too_large(self.max_size));
+        }
+        while self.position == self.current.len() {
+            let Some(chunk) = self.chunks.next() else {
+                self.verify()?;
+                return Ok(0);
+            };
+            let chunk = chunk?;
+            if let Some(manifest) = &self.manifest {
+                manifest
+                    .check_chunk(self.index, &chunk)
+                    .map_err(|e| invalid(e.to_string()))?;
+            }
+            self.index += 1;
+            if self.read + chunk.len() > self.total_size {
+                return Err(invalid(format!(
+                    "chunks hold more than the expected {} bytes",
+                    self.total_size
+                )));
+            }
+            self.hasher.update(&chunk);
+            self.read += chunk.len();
+            self.current = chunk;
+            self.position = 0;
+        }
+
+        let n = buf.len().min(self.current.len() - self.position);
+        buf[..n].copy_from_slice(&self.current[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+fn too_large(max_size: usize) -> io::Error {
+    invalid(format!("payload is larger than the {} byte limit", max_size))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(data: &[u8], chunk_size: usize) -> (Vec<Vec<u8>>, ChunkedPayload) {
+        let mut chunks = Vec::new();
+        let mut writer = ChunkWriter::new(|chunk| {
+            chunks.push(chunk);
+            Ok(())
+        })
+        .with_chunk_size(chunk_size);
+        // Uneven writes must still produce full chunks
+        for piece in data.chunks(7) {
+            writer.write_all(piece).unwrap();
+        }
+        let payload = writer.finish().unwrap();
+        (chunks, payload)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data: Vec<u8> = (0..100u8).collect();
+        let (chunks, payload) = chunk(&data, 16);
+        assert_eq!(chunks.len(), 7);
+        assert!(chunks[..6].iter().all(|chunk| chunk.len() == 16));
+        assert_eq!(payload.chunks, 7);
+        assert_eq!(payload.total_size, 100);
+        assert_eq!(payload.root_hash, hex::encode(Sha256::digest(&data)));
+
+        let mut reader = ChunkReader::new(chunks.into_iter().map(Ok), payload.root_hash, 100);
+        let mut read = Vec::new();
+        reader.read_to_end(&mut read).unwrap();
+        assert_eq!(read, data);
+    }
+
+    #[test]
+    fn test_empty_payload() {
+        let (chunks, payload) = chunk(b"", 16);
+        assert!(chunks.is_empty());
+        assert_eq!(payload.chunks, 0);
+        let mut reader = ChunkReader::new(chunks.into_iter().map(Ok), payload.root_hash, 0);
+        assert_eq!(reader.read(&mut [0; 8]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_writer_enforces_max_size() {
+        let mut writer = ChunkWriter::new(|_| Ok(())).with_chunk_size(4).with_max_size(10);
+        writer.write_all(b"0123456789").unwrap();
+        assert!(writer.write_all(b"x").is_err());
+    }
+
+    #[test]
+    fn test_reader_rejects_bad_chunks() {
+        let (chunks, payload) = chunk(b"hello, world", 4);
+        let read = |chunks: Vec<Vec<u8>>, total_size: usize| {
+            let mut reader =
+                ChunkReader::new(chunks.into_iter().map(Ok), payload.root_hash.clone(), total_size);
+            reader.read_to_end(&mut Vec::new())
+        };
+
+        let mut tampered = chunks.clone();
+        tampered[1][0] ^= 1;
+        assert!(read(tampered, 12).is_err());
+        assert!(read(chunks[..2].to_vec(), 12).is_err());
+        assert!(read(chunks.clone(), 11).is_err());
+        assert!(read(chunks.clone(), 12).is_ok());
+
+        let mut reader = ChunkReader::new(chunks.into_iter().map(Ok), payload.root_hash, 12)
+            .with_max_size(8);
+        assert!(reader.read_to_end(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_reader_names_bad_chunk() {
+        let (mut chunks, payload) = chunk(b"hello, world", 4);
+        let manifest = Manifest {
+            v: 1,
+            root_hash: payload.root_hash,
+            total_size: payload.total_size,
+            chunk_ids: vec![String::new(); chunks.len()],
+            relays: vec![],
+            chunk_relays: Default::default(),
+            chunk_sizes: chunks.iter().map(Vec::len).collect(),
+            chunk_hashes: chunks.iter().map(|chunk| hex::encode(Sha256::digest(chunk))).collect(),
+            encoding: None,
+        };
+        chunks[1][0] ^= 1;
+        let mut reader = ChunkReader::for_manifest(chunks.into_iter().map(Ok), &manifest);
+        let error = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(error.to_string(), "Chunk 1 doesn't match its checksum");
+    }
+}
@@ -2,18 +2,37 @@
 //!
 //! Provides gzip compression with a versioned envelope format
 //! for efficient transmission of crash reports.
+//!
+//! [`compress_payload_dict`] compresses with zstd and a dictionary of
+//! strings common to crash payloads (exception names, frame prefixes, SDK
+//! JSON fields), which shrinks typical reports well below gzip, keeping
+//! more of them under the direct-send size limit.
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use serde::{Deserialize, Serialize};
-use std::io::{Read, Write};
+use std::io::{self, BufRead, BufReader, Read, Write};
 use thiserror::Error;
 
 const COMPRESSION_VERSION: u8 = 1;
 const COMPRESSION_TYPE: &str = "gzip";
+const ZSTD_COMPRESSION_TYPE: &str = "zstd";
+/// Payloads are small, so favor size over speed.
+const ZSTD_LEVEL: i32 = 19;
+
+/// ID of [`CRASH_DICTIONARY`] in envelopes. The dictionary must never change
+/// under an ID; add a new one instead, keeping the old for decompression.
+pub const CRASH_DICTIONARY_ID: u32 = 1;
+
+/// Raw-content zstd dictionary: substrings common to crash payloads, with
+/// the most frequent last, where they are cheapest to reference.
+const CRASH_DICTIONARY: &[u8] = include_bytes!("crash_payload_v1.dict");
 /// Default compression threshold in bytes (1KB).
 pub const DEFAULT_THRESHOLD: usize = 1024;
 
+/// [`payload_encoding`] of a payload that isn't compressed.
+pub const IDENTITY_ENCODING: &str = "identity";
+
 /// Compressed payload envelope.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CompressedEnvelope {
@@ -21,7 +40,11 @@ pub struct CompressedEnvelope {
     pub v: u8,
     /// Compression algorithm
     pub compression: String,
-    /// Base64-encoded compressed payload
+    /// Dictionary the payload was compressed with, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dict: Option<u32>,
+    /// Base64-encoded compressed payload. Last, so that streaming readers
+    /// know the other fields before it starts.
     pub payload: String,
 }
 
@@ -39,6 +62,15 @@ pub enum CompressionError {
 
     #[error("UTF-8 decode failed: {0}")]
     Utf8Failed(#[from] std::string::FromUtf8Error),
+
+    #[error("Unsupported compression: {0}")]
+    UnsupportedCompression(String),
+
+    #[error("Unknown compression dictionary: {0}")]
+    UnknownDictionary(u32),
+
+    #[error("Payload is {found}, expected {expected}")]
+    EncodingMismatch { expected: String, found: String },
 }
 
 /// Compresses a plaintext string using gzip and wraps it in a versioned envelope.
@@ -62,6 +94,39 @@ pub fn compress_payload(plaintext: &str) -> Result<String, CompressionError> {
         v: COMPRESSION_VERSION,
         compression: COMPRESSION_TYPE.into(),
         payload: BASE64.encode(&compressed),
+        dict: None,
+    };
+
+    Ok(serde_json::to_string(&envelope)?)
+}
+
+/// Compresses a plaintext string using zstd with the built-in crash payload
+/// dictionary and wraps it in a versioned envelope.
+///
+/// Output format: `{"v":1,"compression":"zstd","dict":1,"payload":"<base64>"}`
+///
+/// The receiver must understand zstd envelopes; use [`compress_payload`] for
+/// receivers that only handle gzip.
+///
+/// # Example
+///
+/// ```
+/// use bugstr::{compress_payload_dict, decompress_payload};
+///
+/// let report = r#"{"message":"boom","stack":"Error: boom\n    at main (app.js:1:1)"}"#;
+/// let envelope = compress_payload_dict(report).unwrap();
+/// assert!(envelope.contains("\"compression\":\"zstd\""));
+/// assert_eq!(decompress_payload(&envelope).unwrap(), report);
+/// ```
+pub fn compress_payload_dict(plaintext: &str) -> Result<String, CompressionError> {
+    let mut compressor = zstd::bulk::Compressor::with_dictionary(ZSTD_LEVEL, CRASH_DICTIONARY)?;
+    let compressed = compressor.compress(plaintext.as_bytes())?;
+
+    let envelope = CompressedEnvelope {
+        v: COMPRESSION_VERSION,
+        compression: ZSTD_COMPRESSION_TYPE.into(),
+        payload: BASE64.encode(&compressed),
+        dict: Some(CRASH_DICTIONARY_ID),
     };
 
     Ok(serde_json::to_string(&envelope)?)
@@ -94,12 +159,215 @@ pub fn decompress_payload(envelope: &str) -> Result<String, CompressionError> {
         Err(_) => return Ok(envelope.to_string()), // not a valid envelope
     };
 
-    let compressed = BASE64.decode(&parsed.payload)?;
-    let mut decoder = GzDecoder::new(&compressed[..]);
-    let mut decompressed = Vec::new();
-    decoder.read_to_end(&mut decompressed)?;
+    parsed.decompress()
+}
+
+impl CompressedEnvelope {
+    /// Decompresses the envelope's payload to plaintext.
+    pub fn decompress(&self) -> Result<String, CompressionError> {
+        let compressed = BASE64.decode(&self.payload)?;
+        let mut decompressed = Vec::new();
+        match self.compression.as_str() {
+            COMPRESSION_TYPE => {
+                GzDecoder::new(&compressed[..]).read_to_end(&mut decompressed)?;
+            }
+            ZSTD_COMPRESSION_TYPE => {
+                let dictionary: &[u8] = match self.dict {
+                    None => &[],
+                    Some(CRASH_DICTIONARY_ID) => CRASH_DICTIONARY,
+                    Some(id) => return Err(CompressionError::UnknownDictionary(id)),
+                };
+                zstd::stream::read::Decoder::with_dictionary(&compressed[..], dictionary)?
+                    .read_to_end(&mut decompressed)?;
+            }
+            other => return Err(CompressionError::UnsupportedCompression(other.to_string())),
+        }
+
+        Ok(String::from_utf8(decompressed)?)
+    }
+}
+
+/// Decodes `content` as `encoding` (see [`payload_encoding`]) says,
+/// decompressing it exactly once. Without an encoding, as from senders that
+/// predate it, falls back to [`decompress_payload`]'s sniffing: `content`
+/// is returned unchanged if it isn't an envelope, and an envelope that fails
+/// to decompress is an error.
+pub fn decode_payload(content: String, encoding: Option<&str>) -> Result<String, CompressionError> {
+    match encoding {
+        None => decompress_payload(&content),
+        Some(IDENTITY_ENCODING) => Ok(content),
+        Some(expected) => {
+            let envelope: CompressedEnvelope = serde_json::from_str(&content)?;
+            if envelope.compression != expected {
+                return Err(CompressionError::EncodingMismatch {
+                    expected: expected.to_string(),
+                    found: envelope.compression,
+                });
+            }
+            envelope.decompress()
+        }
+    }
+}
+
+/// How `content`, as [`maybe_compress_payload`] returns it, is encoded: the
+/// envelope's compression (`gzip`, `zstd`) or [`IDENTITY_ENCODING`] for
+/// plaintext.
+///
+/// ```
+/// use bugstr::compression::{payload_encoding, IDENTITY_ENCODING};
+/// use bugstr::compress_payload;
+///
+/// assert_eq!(payload_encoding(&compress_payload("boom").unwrap()), "gzip");
+/// assert_eq!(payload_encoding(r#"{"message":"boom"}"#), IDENTITY_ENCODING);
+/// ```
+pub fn payload_encoding(content: &str) -> String {
+    #[derive(Deserialize)]
+    struct Header {
+        compression: String,
+        #[serde(rename = "payload")]
+        _payload: serde::de::IgnoredAny,
+    }
 
-    Ok(String::from_utf8(decompressed)?)
+    let trimmed = content.trim();
+    if !trimmed.starts_with('{') || !trimmed.contains("\"compression\"") {
+        return IDENTITY_ENCODING.to_string();
+    }
+    serde_json::from_str::<Header>(trimmed)
+        .map(|header| header.compression)
+        .unwrap_or_else(|_| IDENTITY_ENCODING.to_string())
+}
+
+/// Largest envelope header (the JSON before the payload) that
+/// [`decompress_stream`] looks for before falling back to buffering.
+const MAX_STREAM_HEADER: usize = 1024;
+
+/// Compresses everything `reader` yields with gzip into a versioned envelope
+/// written to `writer`, without buffering the whole payload.
+///
+/// The output is the same envelope as [`compress_payload`] produces.
+/// Returns the number of plaintext bytes read.
+///
+/// # Example
+///
+/// ```
+/// use bugstr::{compress_stream, decompress_stream};
+///
+/// let attachment = vec![b'x'; 100_000];
+/// let mut envelope = Vec::new();
+/// compress_stream(&attachment[..], &mut envelope).unwrap();
+///
+/// let mut restored = Vec::new();
+/// decompress_stream(&envelope[..], &mut restored).unwrap();
+/// assert_eq!(restored, attachment);
+/// ```
+pub fn compress_stream<R: Read, W: Write>(mut reader: R, mut writer: W) -> Result<u64, CompressionError> {
+    write!(
+        writer,
+        "{{\"v\":{},\"compression\":\"{}\",\"payload\":\"",
+        COMPRESSION_VERSION, COMPRESSION_TYPE
+    )?;
+
+    let base64 = base64::write::EncoderWriter::new(&mut writer, &BASE64);
+    let mut encoder = GzEncoder::new(base64, Compression::default());
+    let read = io::copy(&mut reader, &mut encoder)?;
+    encoder.finish()?.finish()?;
+
+    writer.write_all(b"\"}")?;
+    writer.flush()?;
+    Ok(read)
+}
+
+/// Decompresses an envelope read from `reader` into `writer`, without
+/// buffering the whole payload.
+///
+/// Accepts everything [`decompress_payload`] does: gzip and zstd envelopes,
+/// and raw plaintext, which is copied through. Envelopes whose payload
+/// doesn't follow a compact header (`"payload":"` within the first 1KB) are
+/// buffered and decompressed with [`decompress_payload`]. Returns the number
+/// of bytes written.
+pub fn decompress_stream<R: Read, W: Write>(reader: R, mut writer: W) -> Result<u64, CompressionError> {
+    const PAYLOAD_START: &[u8] = b"\"payload\":\"";
+
+    let mut reader = BufReader::new(reader);
+    let mut header = Vec::new();
+    let mut byte = [0u8; 1];
+    while header.len() < MAX_STREAM_HEADER && !header.ends_with(PAYLOAD_START) {
+        if reader.read(&mut byte)? == 0 {
+            break;
+        }
+        header.push(byte[0]);
+    }
+
+    let looks_like_envelope = header.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{');
+    if !looks_like_envelope {
+        writer.write_all(&header)?;
+        return Ok(header.len() as u64 + io::copy(&mut reader, &mut writer)?);
+    }
+
+    // The header with an empty payload is a complete envelope
+    let envelope = header
+        .ends_with(PAYLOAD_START)
+        .then(|| [&header[..], b"\"}"].concat())
+        .and_then(|json| serde_json::from_slice::<CompressedEnvelope>(&json).ok());
+    let Some(envelope) = envelope else {
+        let mut content = header;
+        reader.read_to_end(&mut content)?;
+        let plaintext = decompress_payload(&String::from_utf8(content)?)?;
+        writer.write_all(plaintext.as_bytes())?;
+        return Ok(plaintext.len() as u64);
+    };
+
+    let payload = base64::read::DecoderReader::new(UntilQuote::new(&mut reader), &BASE64);
+    let written = match envelope.compression.as_str() {
+        COMPRESSION_TYPE => io::copy(&mut GzDecoder::new(payload), &mut writer)?,
+        ZSTD_COMPRESSION_TYPE => {
+            let dictionary: &[u8] = match envelope.dict {
+                None => &[],
+                Some(CRASH_DICTIONARY_ID) => CRASH_DICTIONARY,
+                Some(id) => return Err(CompressionError::UnknownDictionary(id)),
+            };
+            let mut decoder =
+                zstd::stream::read::Decoder::with_dictionary(BufReader::new(payload), dictionary)?;
+            io::copy(&mut decoder, &mut writer)?
+        }
+        other => return Err(CompressionError::UnsupportedCompression(other.to_string())),
+    };
+    writer.flush()?;
+    Ok(written)
+}
+
+/// Reads up to (not including) the next `"`, i.e. the rest of a JSON string
+/// without escapes such as base64.
+struct UntilQuote<R> {
+    inner: R,
+    done: bool,
+}
+
+impl<R: BufRead> UntilQuote<R> {
+    fn new(inner: R) -> Self {
+        Self { inner, done: false }
+    }
+}
+
+impl<R: BufRead> Read for UntilQuote<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        let available = self.inner.fill_buf()?;
+        if available.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "unterminated payload"));
+        }
+        let end = available.iter().position(|&b| b == b'"');
+        let len = end.unwrap_or(available.len()).min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.inner.consume(len);
+        if end == Some(len) {
+            self.inner.consume(1);
+            self.done = true;
+        }
+        Ok(len)
+    }
 }
 
 /// Checks if a payload should be compressed based on size.
@@ -190,6 +458,67 @@ mod tests {
         assert_eq!(decompress_payload(&result).unwrap(), large);
     }
 
+    #[test]
+    fn dictionary_compression_beats_gzip() {
+        let payload = serde_json::json!({
+            "message": "Cannot read properties of undefined (reading 'id')",
+            "stack": "TypeError: Cannot read properties of undefined (reading 'id')\n    \
+                      at render (file:///app/resources/app.asar/renderer.js:1:2345)\n    \
+                      at async load (file:///app/resources/app.asar/renderer.js:10:5)\n    \
+                      at processTicksAndRejections (node:internal/process/task_queues:95:5)",
+            "timestamp": 1_700_000_000_000u64,
+            "environment": "production",
+            "release": "1.4.2",
+            "processType": "renderer",
+        })
+        .to_string();
+
+        let dict = compress_payload_dict(&payload).unwrap();
+        let gzip = compress_payload(&payload).unwrap();
+        assert!(dict.len() < gzip.len(), "{} >= {}", dict.len(), gzip.len());
+        assert_eq!(decompress_payload(&dict).unwrap(), payload);
+
+        // Envelopes naming a dictionary this build doesn't have are rejected
+        let unknown = dict.replace("\"dict\":1", "\"dict\":99");
+        assert!(matches!(
+            decompress_payload(&unknown),
+            Err(CompressionError::UnknownDictionary(99))
+        ));
+    }
+
+    #[test]
+    fn stream_round_trips_and_reads_buffered_envelopes() {
+        let attachment: Vec<u8> = (0..200_000u32).flat_map(|i| (i % 251).to_le_bytes()).collect();
+        let mut envelope = Vec::new();
+        assert_eq!(
+            compress_stream(&attachment[..], &mut envelope).unwrap(),
+            attachment.len() as u64
+        );
+        let parsed: CompressedEnvelope = serde_json::from_slice(&envelope).unwrap();
+        assert_eq!(parsed.compression, "gzip");
+
+        let mut restored = Vec::new();
+        decompress_stream(&envelope[..], &mut restored).unwrap();
+        assert_eq!(restored, attachment);
+
+        let plaintext = "Error: boom\n    at main (app.js:1:1)";
+        for envelope in [
+            compress_payload(plaintext).unwrap(),
+            compress_payload_dict(plaintext).unwrap(),
+            // Not compact: buffered
+            serde_json::to_string_pretty(
+                &serde_json::from_str::<CompressedEnvelope>(&compress_payload(plaintext).unwrap())
+                    .unwrap(),
+            )
+            .unwrap(),
+            plaintext.to_string(),
+        ] {
+            let mut restored = Vec::new();
+            decompress_stream(envelope.as_bytes(), &mut restored).unwrap();
+            assert_eq!(String::from_utf8(restored).unwrap(), plaintext);
+        }
+    }
+
     #[test]
     fn compression_achieves_significant_reduction() {
         let stack_trace: String = (0..100)
@@ -206,4 +535,24 @@ mod tests {
             compressed.len() as f64 / stack_trace.len() as f64
         );
     }
+
+    #[test]
+    fn decode_payload_follows_encoding() {
+        let envelope = compress_payload("boom").unwrap();
+        assert_eq!(payload_encoding(&envelope), "gzip");
+        assert_eq!(decode_payload(envelope.clone(), Some("gzip")).unwrap(), "boom");
+        assert_eq!(decode_payload(envelope.clone(), None).unwrap(), "boom");
+        // Identity payloads are never decompressed, even if they look compressed
+        assert_eq!(decode_payload(envelope.clone(), Some(IDENTITY_ENCODING)).unwrap(), envelope);
+        assert!(matches!(
+            decode_payload(envelope, Some("zstd")),
+            Err(CompressionError::EncodingMismatch { .. })
+        ));
+        assert!(decode_payload("boom".to_string(), Some("gzip")).is_err());
+        assert_eq!(decode_payload("boom".to_string(), None).unwrap(), "boom");
+        // A corrupt envelope is never passed off as the payload
+        let corrupt = r#"{"v":1,"compression":"gzip","payload":"not base64!"}"#.to_string();
+        assert!(decode_payload(corrupt.clone(), None).is_err());
+        assert!(decode_payload(corrupt, Some("gzip")).is_err());
+    }
 }
@@ -0,0 +1,283 @@
+//! Receiver configuration file.
+//!
+//! `bugstr.toml` holds named profiles so one machine can run or inspect
+//! several receiver identities:
+//!
+//! ```toml
+//! [profiles.default]
+//! privkey_file = "~/.config/bugstr/default.key"
+//!
+//! [profiles.work]
+//! privkey = "nsec1..."
+//! relays = ["wss://relay.work.example"]
+//! db = "/var/lib/bugstr/work.db"
+//! mappings = "/var/lib/bugstr/work-mappings"
+//! symbol_server = "https://symbols.work.example/{platform}/{app}/{version}/{file}"
+//! debuginfod = ["https://debuginfod.work.example"]
+//! port = 3001
+//! retention_days = 30
+//! max_chunked_size = 8388608
+//!
+//! [profiles.work.in_app."com.example.app"]
+//! include = ["com.example."]
+//! paths = ["src/**"]
+//!
+//! [[profiles.work.webhooks]]
+//! url = "https://hooks.work.example/bugstr"
+//! secret = "..."
+//! events = ["new_crash", "new_group"]
+//! ```
+//!
+//! Command-line flags always take precedence over profile values.
+
+use crate::export::S3Config;
+use crate::notify::AlertKind;
+use crate::symbolication::InAppRules;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// File name of the configuration file.
+pub const CONFIG_FILE_NAME: &str = "bugstr.toml";
+
+/// Profile used when none is selected explicitly.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Errors from loading the configuration file.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid config {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    #[error("Unknown profile '{0}'")]
+    UnknownProfile(String),
+
+    #[error("Profile '{0}' sets both privkey and privkey_file")]
+    PrivkeyConflict(String),
+}
+
+/// Parsed `bugstr.toml`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Named profiles, keyed by name.
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+/// A named receiver identity: key, relays, database and mappings.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Private key (hex or nsec).
+    pub privkey: Option<String>,
+    /// File containing the private key. Preferred over `privkey`.
+    pub privkey_file: Option<PathBuf>,
+    /// Relay URLs.
+    pub relays: Option<Vec<String>>,
+    /// Database file path.
+    pub db: Option<PathBuf>,
+    /// Directory containing mapping files.
+    pub mappings: Option<PathBuf>,
+    /// URL template for downloading missing mappings.
+    pub symbol_server: Option<String>,
+    /// debuginfod server URLs.
+    pub debuginfod: Option<Vec<String>>,
+    /// Rules classifying frames as application or library code, keyed by
+    /// app ID.
+    #[serde(default)]
+    pub in_app: HashMap<String, InAppRules>,
+    /// Bucket for scheduled exports.
+    pub s3: Option<S3Config>,
+    /// Webhooks `serve` sends alerts to.
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    /// Port of the `serve` dashboard.
+    pub port: Option<u16>,
+    /// Address the `serve` dashboard binds to.
+    pub bind: Option<IpAddr>,
+    /// Days `serve` keeps crash reports before deleting them.
+    pub retention_days: Option<u32>,
+    /// Largest chunked report `serve` fetches, in bytes.
+    pub max_chunked_size: Option<usize>,
+}
+
+/// A webhook registered by `serve` on startup, like `--webhook-url`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Shared secret for the `X-Bugstr-Signature` HMAC.
+    pub secret: Option<String>,
+    /// Alert kinds to send; empty sends all.
+    #[serde(default)]
+    pub events: Vec<AlertKind>,
+}
+
+impl ConfigFile {
+    /// Parses a configuration file from TOML text.
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// Loads and parses the configuration file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&text).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Default location: `$XDG_CONFIG_HOME/bugstr/bugstr.toml`, falling back
+    /// to `~/.config/bugstr/bugstr.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("bugstr").join(CONFIG_FILE_NAME))
+    }
+
+    /// Selects a profile.
+    ///
+    /// An explicitly named profile must exist. Without a name, the
+    /// `default` profile is used if present, otherwise an empty profile.
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, ConfigError> {
+        match name {
+            Some(name) => self
+                .profiles
+                .get(name)
+                .cloned()
+                .ok_or_else(|| ConfigError::UnknownProfile(name.to_string())),
+            None => Ok(self.profiles.get(DEFAULT_PROFILE).cloned().unwrap_or_default()),
+        }
+    }
+}
+
+impl Profile {
+    /// Returns the profile's private key, reading `privkey_file` if set.
+    ///
+    /// `name` is only used in error messages.
+    pub fn load_privkey(&self, name: &str) -> Result<Option<String>, ConfigError> {
+        match (&self.privkey, &self.privkey_file) {
+            (Some(_), Some(_)) => Err(ConfigError::PrivkeyConflict(name.to_string())),
+            (Some(key), None) => Ok(Some(key.clone())),
+            (None, Some(path)) => {
+                let path = expand_home(path);
+                let key = std::fs::read_to_string(&path)
+                    .map_err(|source| ConfigError::Io { path, source })?;
+                Ok(Some(key.trim().to_string()))
+            }
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+/// Expands a leading `~/` to the home directory.
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_profiles() {
+        let config = ConfigFile::parse(
+            r#"
+            [profiles.default]
+            privkey = "abc"
+
+            [profiles.work]
+            relays = ["wss://relay.work.example"]
+            db = "work.db"
+            port = 3001
+            bind = "0.0.0.0"
+            retention_days = 30
+            max_chunked_size = 1048576
+
+            [profiles.work.in_app."com.example.app"]
+            include = ["com.example."]
+
+            [[profiles.work.webhooks]]
+            url = "https://hooks.example.com"
+            events = ["new_group"]
+            "#,
+        )
+        .unwrap();
+
+        let default = config.profile(None).unwrap();
+        assert_eq!(default.load_privkey("default").unwrap().as_deref(), Some("abc"));
+
+        let work = config.profile(Some("work")).unwrap();
+        assert_eq!(work.relays.unwrap(), vec!["wss://relay.work.example"]);
+        assert_eq!(work.db.unwrap(), PathBuf::from("work.db"));
+        assert_eq!(work.port, Some(3001));
+        assert_eq!(work.bind, Some(IpAddr::from([0, 0, 0, 0])));
+        assert_eq!(work.retention_days, Some(30));
+        assert_eq!(work.max_chunked_size, Some(1024 * 1024));
+        assert_eq!(work.in_app["com.example.app"].include, ["com.example."]);
+        assert_eq!(work.webhooks[0].url, "https://hooks.example.com");
+        assert_eq!(work.webhooks[0].events, [AlertKind::NewGroup]);
+
+        assert!(matches!(
+            config.profile(Some("missing")),
+            Err(ConfigError::UnknownProfile(_))
+        ));
+    }
+
+    #[test]
+    fn test_empty_config_has_empty_default() {
+        let config = ConfigFile::parse("").unwrap();
+        let profile = config.profile(None).unwrap();
+        assert!(profile.relays.is_none());
+        assert_eq!(profile.load_privkey("default").unwrap(), None);
+    }
+
+    #[test]
+    fn test_privkey_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = dir.path().join("work.key");
+        std::fs::write(&key_path, "deadbeef\n").unwrap();
+
+        let profile = Profile {
+            privkey_file: Some(key_path),
+            ..Default::default()
+        };
+        assert_eq!(profile.load_privkey("work").unwrap().as_deref(), Some("deadbeef"));
+
+        let conflicting = Profile {
+            privkey: Some("abc".into()),
+            ..profile
+        };
+        assert!(matches!(
+            conflicting.load_privkey("work"),
+            Err(ConfigError::PrivkeyConflict(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_unknown_keys() {
+        assert!(ConfigFile::parse("[profiles.work]\nprivkye = \"abc\"").is_err());
+    }
+}
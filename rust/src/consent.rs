@@ -0,0 +1,173 @@
+//! User consent for sending crash reports.
+//!
+//! Crash reports are cached locally with [`CrashReportCache::store`] and
+//! only leave the device once the user agrees. [`ConsentQueue`] drives that
+//! flow: the host application lists the [`pending`](ConsentQueue::pending)
+//! reports, shows them to the user, and then approves or discards them.
+//! Approved reports are sent with a [`CrashSender`] and removed from the
+//! cache; a report whose sending fails stays queued for a later attempt.
+//!
+//! ```no_run
+//! use bugstr::{BugstrConfig, ConsentQueue, CrashReportCache, CrashSender};
+//!
+//! # async fn run() -> Result<(), bugstr::ConsentError> {
+//! let cache = CrashReportCache::new("/tmp/crashes")?;
+//! let sender = CrashSender::new(BugstrConfig {
+//!     recipient_pubkey: "ab".repeat(32),
+//!     ..Default::default()
+//! });
+//! let queue = ConsentQueue::new(cache, sender);
+//!
+//! for report in queue.pending()? {
+//!     println!("{}: {}", report.id, report.payload);
+//! }
+//! // The user agreed to send everything
+//! for (id, result) in queue.approve_all().await? {
+//!     if let Err(e) = result {
+//!         eprintln!("{} not sent: {}", id, e);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use thiserror::Error;
+
+use crate::sender::{CrashSender, SendError, SendReport};
+use crate::{CrashReportCache, PendingReport};
+
+/// Consent queue errors.
+#[derive(Debug, Error)]
+pub enum ConsentError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("No pending crash report {0}")]
+    NotFound(String),
+
+    #[error("Send failed: {0}")]
+    Send(#[from] SendError),
+}
+
+/// Crash reports waiting for the user's consent.
+#[derive(Debug)]
+pub struct ConsentQueue {
+    cache: CrashReportCache,
+    sender: CrashSender,
+}
+
+impl ConsentQueue {
+    /// Creates a queue over the reports in `cache`, sent with `sender`.
+    pub fn new(cache: CrashReportCache, sender: CrashSender) -> Self {
+        Self { cache, sender }
+    }
+
+    pub fn cache(&self) -> &CrashReportCache {
+        &self.cache
+    }
+
+    /// Reports not yet sent or discarded, oldest first.
+    pub fn pending(&self) -> Result<Vec<PendingReport>, ConsentError> {
+        Ok(self.cache.list()?)
+    }
+
+    /// Sends the report `id` and removes it from the cache.
+    pub async fn approve(&self, id: &str) -> Result<SendReport, ConsentError> {
+        let report = self
+            .cache
+            .get(id)?
+            .ok_or_else(|| ConsentError::NotFound(id.to_string()))?;
+        self.send(&report).await
+    }
+
+    /// Removes the report `id` from the cache without sending it.
+    pub fn discard(&self, id: &str) -> Result<(), ConsentError> {
+        if !self.cache.remove(id)? {
+            return Err(ConsentError::NotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Sends every pending report, oldest first. Returns each report's ID
+    /// with its result; reports that fail stay queued.
+    pub async fn approve_all(
+        &self,
+    ) -> Result<Vec<(String, Result<SendReport, ConsentError>)>, ConsentError> {
+        let mut results = Vec::new();
+        for report in self.pending()? {
+            let result = self.send(&report).await;
+            results.push((report.id, result));
+        }
+        Ok(results)
+    }
+
+    async fn send(&self, report: &PendingReport) -> Result<SendReport, ConsentError> {
+        let sent = self.sender.send(&report.payload).await?;
+        self.cache.remove(&report.id)?;
+        Ok(sent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BugstrConfig;
+
+    fn queue(dir: &std::path::Path) -> ConsentQueue {
+        let sender = CrashSender::new(BugstrConfig {
+            recipient_pubkey: "ab".repeat(32),
+            relays: vec![],
+            ..Default::default()
+        });
+        ConsentQueue::new(CrashReportCache::new(dir).unwrap(), sender)
+    }
+
+    #[test]
+    fn test_cache_stores_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CrashReportCache::new(dir.path()).unwrap();
+        let first = cache.store(r#"{"message": "first"}"#).unwrap();
+        let second = cache.store(r#"{"message": "second"}"#).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a report").unwrap();
+
+        let reports = cache.list().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].id, first);
+        assert_eq!(reports[0].payload, r#"{"message": "first"}"#);
+        assert_eq!(reports[1].id, second);
+
+        assert!(cache.get("../notes").unwrap().is_none());
+        assert!(!cache.remove("../notes").unwrap());
+        assert!(cache.remove(&first).unwrap());
+        assert!(!cache.remove(&first).unwrap());
+        assert_eq!(cache.list().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_discard_and_failed_approve() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = queue(dir.path());
+        let kept = queue.cache().store("{}").unwrap();
+        let discarded = queue.cache().store("{}").unwrap();
+
+        queue.discard(&discarded).unwrap();
+        assert!(matches!(
+            queue.discard(&discarded),
+            Err(ConsentError::NotFound(_))
+        ));
+        assert!(matches!(
+            queue.approve(&discarded).await,
+            Err(ConsentError::NotFound(_))
+        ));
+
+        // Without relays sending fails, and the report stays queued
+        let results = queue.approve_all().await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, kept);
+        assert!(matches!(
+            results[0].1,
+            Err(ConsentError::Send(SendError::NoRelays))
+        ));
+        assert_eq!(queue.pending().unwrap()[0].id, kept);
+    }
+}
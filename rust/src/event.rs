@@ -1,9 +1,100 @@
 //! Nostr event types and utilities.
 //!
-//! Implements NIP-01 event structure and ID computation.
+//! Implements NIP-01 event structure, ID computation and BIP-340 Schnorr
+//! signing and verification, so chunk events, gift wraps and auth events can
+//! be produced and checked without the full `nostr` crate.
 
+use secp256k1::rand::{thread_rng, Rng};
+use secp256k1::schnorr::Signature;
+use secp256k1::{Keypair, Message, Secp256k1, SecretKey, XOnlyPublicKey};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// How far in the past [`randomized_created_at`] may move a timestamp: 2 days.
+pub const MAX_CREATED_AT_OFFSET: u64 = 60 * 60 * 24 * 2;
+
+/// Event signing and verification errors.
+#[derive(Debug, Error)]
+pub enum EventError {
+    #[error("Invalid secret key")]
+    InvalidSecretKey,
+
+    #[error("Invalid public key: {0}")]
+    InvalidPublicKey(String),
+
+    #[error("Event ID mismatch: expected {expected}, got {actual}")]
+    IdMismatch { expected: String, actual: String },
+
+    #[error("Invalid signature for event {0}")]
+    InvalidSignature(String),
+}
+
+/// A single event tag, built with the typed constructors below.
+///
+/// Converts into the `Vec<String>` form used by event `tags`:
+///
+/// ```
+/// use bugstr::{Tag, UnsignedNostrEvent};
+///
+/// let tags = vec![
+///     Tag::pubkey("ab".repeat(32), Some("wss://relay.damus.io")).into(),
+///     Tag::expiration(1234567890).into(),
+///     Tag::client("bugstr").into(),
+/// ];
+/// let event = UnsignedNostrEvent::new("cd".repeat(32), 1234567890, 14, tags, "crash");
+/// assert_eq!(event.tags[0], ["p", &"ab".repeat(32), "wss://relay.damus.io"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag(Vec<String>);
+
+impl Tag {
+    /// `["p", <pubkey>, <relay hint>]` referencing a recipient (NIP-01). The
+    /// pubkey is lowercased; the relay hint is omitted when `None`.
+    pub fn pubkey(pubkey: impl Into<String>, relay_hint: Option<&str>) -> Self {
+        let mut tag = vec!["p".to_string(), pubkey.into().to_lowercase()];
+        tag.extend(relay_hint.map(str::to_string));
+        Self(tag)
+    }
+
+    /// `["expiration", <unix timestamp>]` asking relays to drop the event
+    /// after `timestamp` (NIP-40).
+    pub fn expiration(timestamp: u64) -> Self {
+        Self(vec!["expiration".to_string(), timestamp.to_string()])
+    }
+
+    /// `["relay", <url>]` naming the relay an event is meant for, as in
+    /// NIP-42 auth events.
+    pub fn relay(url: impl Into<String>) -> Self {
+        Self(vec!["relay".to_string(), url.into()])
+    }
+
+    /// `["client", <name>]` identifying the sending software (NIP-89).
+    pub fn client(name: impl Into<String>) -> Self {
+        Self(vec!["client".to_string(), name.into()])
+    }
+
+    /// `["content-type", <type>]` describing the event content; see
+    /// [`crate::kinds`].
+    pub fn content_type(content_type: impl Into<String>) -> Self {
+        Self(vec![
+            crate::kinds::CONTENT_TYPE_TAG.to_string(),
+            content_type.into(),
+        ])
+    }
+
+    /// The tag's fields, name first.
+    pub fn as_slice(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl From<Tag> for Vec<String> {
+    fn from(tag: Tag) -> Self {
+        tag.0
+    }
+}
 
 /// Minimal unsigned Nostr event representation.
 ///
@@ -59,18 +150,13 @@ impl UnsignedNostrEvent {
     ///
     /// ID = SHA256([0, pubkey, created_at, kind, tags, content])
     pub fn compute_id(&self) -> String {
-        let serialized = serde_json::json!([
-            0,
-            self.pubkey.to_lowercase(),
+        event_id(
+            &self.pubkey.to_lowercase(),
             self.created_at,
             self.kind,
-            self.tags,
-            self.content
-        ]);
-
-        let json = serde_json::to_string(&serialized).expect("JSON serialization failed");
-        let hash = Sha256::digest(json.as_bytes());
-        hex::encode(hash)
+            &self.tags,
+            &self.content,
+        )
     }
 
     /// Returns a copy with the computed ID field set.
@@ -92,6 +178,210 @@ impl UnsignedNostrEvent {
         };
         serde_json::to_string(&event).expect("JSON serialization failed")
     }
+
+    /// Signs the event with a BIP-340 Schnorr signature.
+    ///
+    /// The event's `pubkey` is replaced by the x-only public key of
+    /// `secret_key`, so the ID and signature always match the signer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bugstr::UnsignedNostrEvent;
+    ///
+    /// let secret_key = [1u8; 32];
+    /// let event = UnsignedNostrEvent::new("", 1234567890, 1, vec![], "hello");
+    /// let signed = event.sign(&secret_key).unwrap();
+    /// assert_eq!(signed.sig.len(), 128);
+    /// ```
+    pub fn sign(&self, secret_key: &[u8; 32]) -> Result<SignedNostrEvent, EventError> {
+        let secp = Secp256k1::signing_only();
+        let mut secret_key =
+            SecretKey::from_slice(secret_key).map_err(|_| EventError::InvalidSecretKey)?;
+        let mut keypair = Keypair::from_secret_key(&secp, &secret_key);
+
+        let mut event = self.clone();
+        event.pubkey = keypair.x_only_public_key().0.to_string();
+        let id = event.compute_id();
+
+        let sig = secp.sign_schnorr(&id_message(&id), &keypair);
+        secret_key.non_secure_erase();
+        keypair.non_secure_erase();
+
+        Ok(SignedNostrEvent {
+            id,
+            pubkey: event.pubkey,
+            created_at: event.created_at,
+            kind: event.kind,
+            tags: event.tags,
+            content: event.content,
+            sig: sig.to_string(),
+        })
+    }
+}
+
+/// A one-time key pair, as used for NIP-59 gift wraps.
+///
+/// The secret key is zeroed when the keys are dropped, so wrap keys don't
+/// outlive the event they sign. The type is deliberately not `Clone`.
+///
+/// # Example
+///
+/// ```
+/// use bugstr::{EphemeralKeys, UnsignedNostrEvent};
+///
+/// let keys = EphemeralKeys::generate();
+/// let event = UnsignedNostrEvent::new("", 1234567890, 1059, vec![], "wrapped");
+/// let signed = keys.sign(&event).unwrap();
+/// assert_eq!(signed.pubkey, keys.public_key());
+/// ```
+pub struct EphemeralKeys {
+    secret_key: Zeroizing<[u8; 32]>,
+    public_key: String,
+}
+
+impl EphemeralKeys {
+    /// Generates a random key pair.
+    pub fn generate() -> Self {
+        let secp = Secp256k1::signing_only();
+        let mut keypair = Keypair::new(&secp, &mut thread_rng());
+        let keys = Self {
+            secret_key: Zeroizing::new(keypair.secret_bytes()),
+            public_key: keypair.x_only_public_key().0.to_string(),
+        };
+        keypair.non_secure_erase();
+        keys
+    }
+
+    /// The x-only public key (lowercase hex, 64 chars).
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// The secret key bytes, for deriving encryption keys.
+    pub fn secret_key(&self) -> &[u8; 32] {
+        &self.secret_key
+    }
+
+    /// Signs `event` with these keys; see [`UnsignedNostrEvent::sign`].
+    pub fn sign(&self, event: &UnsignedNostrEvent) -> Result<SignedNostrEvent, EventError> {
+        event.sign(&self.secret_key)
+    }
+}
+
+impl std::fmt::Debug for EphemeralKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EphemeralKeys")
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A signed Nostr event, ready to publish.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedNostrEvent {
+    /// Event ID (lowercase hex SHA256, 64 chars)
+    pub id: String,
+
+    /// Public key of event author (lowercase hex, 64 chars)
+    pub pubkey: String,
+
+    /// Unix timestamp in seconds
+    pub created_at: u64,
+
+    /// Event kind
+    pub kind: u16,
+
+    /// List of tag arrays
+    pub tags: Vec<Vec<String>>,
+
+    /// Event content
+    pub content: String,
+
+    /// BIP-340 Schnorr signature of the ID (lowercase hex, 128 chars)
+    pub sig: String,
+}
+
+impl SignedNostrEvent {
+    /// Serializes to JSON for publishing.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("JSON serialization failed")
+    }
+
+    /// Checks that `id` is the NIP-01 hash of the event and `sig` is a valid
+    /// BIP-340 signature of it by `pubkey`.
+    ///
+    /// Receivers call this on gift wraps and seals before decrypting them,
+    /// so forged or corrupted events are rejected early.
+    pub fn verify(&self) -> Result<(), EventError> {
+        let expected = event_id(
+            &self.pubkey,
+            self.created_at,
+            self.kind,
+            &self.tags,
+            &self.content,
+        );
+        if expected != self.id {
+            return Err(EventError::IdMismatch {
+                expected,
+                actual: self.id.clone(),
+            });
+        }
+
+        let pubkey = hex::decode(&self.pubkey)
+            .ok()
+            .and_then(|bytes| XOnlyPublicKey::from_slice(&bytes).ok())
+            .ok_or_else(|| EventError::InvalidPublicKey(self.pubkey.clone()))?;
+        let invalid = || EventError::InvalidSignature(self.id.clone());
+        let sig = hex::decode(&self.sig)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or_else(invalid)?;
+
+        Secp256k1::verification_only()
+            .verify_schnorr(&sig, &id_message(&self.id), &pubkey)
+            .map_err(|_| invalid())
+    }
+}
+
+/// The x-only public key (lowercase hex) of `secret_key`.
+pub(crate) fn public_key(secret_key: &[u8; 32]) -> Result<String, EventError> {
+    let secp = Secp256k1::signing_only();
+    let mut secret_key =
+        SecretKey::from_slice(secret_key).map_err(|_| EventError::InvalidSecretKey)?;
+    let pubkey = secret_key.x_only_public_key(&secp).0.to_string();
+    secret_key.non_secure_erase();
+    Ok(pubkey)
+}
+
+/// A random timestamp up to [`MAX_CREATED_AT_OFFSET`] seconds before `now`.
+///
+/// NIP-59 gift wraps and seals use this instead of the real time so relays
+/// can't correlate them with the crash or with each other.
+pub fn randomized_created_at(now: u64) -> u64 {
+    now.saturating_sub(thread_rng().gen_range(0..MAX_CREATED_AT_OFFSET))
+}
+
+/// NIP-01 event ID: SHA256 of `[0, pubkey, created_at, kind, tags, content]`.
+fn event_id(
+    pubkey: &str,
+    created_at: u64,
+    kind: u16,
+    tags: &[Vec<String>],
+    content: &str,
+) -> String {
+    let serialized = serde_json::json!([0, pubkey, created_at, kind, tags, content]);
+    let json = serde_json::to_string(&serialized).expect("JSON serialization failed");
+    hex::encode(Sha256::digest(json.as_bytes()))
+}
+
+/// The signed message for a computed event ID.
+fn id_message(id: &str) -> Message {
+    let digest: [u8; 32] = hex::decode(id)
+        .expect("event ID is hex")
+        .try_into()
+        .expect("event ID is 32 bytes");
+    Message::from_digest(digest)
 }
 
 #[cfg(test)]
@@ -157,6 +447,116 @@ mod tests {
         assert_eq!(parsed["kind"], 14);
     }
 
+    #[test]
+    fn sign_produces_valid_schnorr_signature() {
+        // Secret key 1: the public key is the generator's x coordinate
+        let mut secret_key = [0u8; 32];
+        secret_key[31] = 1;
+        let event = UnsignedNostrEvent::new("a".repeat(64), 1234567890, 1, vec![], "hello");
+
+        let signed = event.sign(&secret_key).unwrap();
+
+        assert_eq!(
+            signed.pubkey,
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        );
+        let unsigned = UnsignedNostrEvent::new(&signed.pubkey, 1234567890, 1, vec![], "hello");
+        assert_eq!(signed.id, unsigned.compute_id());
+
+        let secp = Secp256k1::verification_only();
+        let sig = Signature::from_slice(&hex::decode(&signed.sig).unwrap()).unwrap();
+        let pubkey = XOnlyPublicKey::from_slice(&hex::decode(&signed.pubkey).unwrap()).unwrap();
+        secp.verify_schnorr(&sig, &id_message(&signed.id), &pubkey)
+            .unwrap();
+
+        assert!(matches!(
+            event.sign(&[0u8; 32]),
+            Err(EventError::InvalidSecretKey)
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_tampered_events() {
+        let signed = UnsignedNostrEvent::new("", 1234567890, 1059, vec![], "hello")
+            .sign(&[7u8; 32])
+            .unwrap();
+        signed.verify().unwrap();
+
+        let tampered = SignedNostrEvent {
+            content: "goodbye".to_string(),
+            ..signed.clone()
+        };
+        assert!(matches!(
+            tampered.verify(),
+            Err(EventError::IdMismatch { .. })
+        ));
+
+        let forged = UnsignedNostrEvent::new("", 1234567890, 1059, vec![], "hello")
+            .sign(&[8u8; 32])
+            .unwrap();
+        let forged = SignedNostrEvent {
+            sig: forged.sig,
+            ..signed.clone()
+        };
+        assert!(matches!(
+            forged.verify(),
+            Err(EventError::InvalidSignature(_))
+        ));
+
+        let bad_key = SignedNostrEvent {
+            id: event_id("zz", 1234567890, 1059, &[], "hello"),
+            pubkey: "zz".to_string(),
+            ..signed
+        };
+        assert!(matches!(
+            bad_key.verify(),
+            Err(EventError::InvalidPublicKey(_))
+        ));
+    }
+
+    #[test]
+    fn randomized_created_at_stays_within_two_days() {
+        let now = 1_700_000_000;
+        for _ in 0..100 {
+            let created_at = randomized_created_at(now);
+            assert!(created_at <= now);
+            assert!(created_at > now - MAX_CREATED_AT_OFFSET);
+        }
+        assert_eq!(randomized_created_at(0), 0);
+    }
+
+    #[test]
+    fn tag_constructors() {
+        assert_eq!(Tag::pubkey("AB", None).as_slice(), ["p", "ab"]);
+        assert_eq!(
+            Tag::pubkey("ab", Some("wss://relay")).as_slice(),
+            ["p", "ab", "wss://relay"]
+        );
+        assert_eq!(Tag::expiration(42).as_slice(), ["expiration", "42"]);
+        assert_eq!(
+            Tag::relay("wss://relay").as_slice(),
+            ["relay", "wss://relay"]
+        );
+        assert_eq!(Vec::from(Tag::client("bugstr")), ["client", "bugstr"]);
+    }
+
+    #[test]
+    fn ephemeral_keys_are_unique_and_hide_secret() {
+        let keys = EphemeralKeys::generate();
+        let other = EphemeralKeys::generate();
+        assert_ne!(keys.public_key(), other.public_key());
+        assert_eq!(keys.public_key().len(), 64);
+
+        let signed = keys
+            .sign(&UnsignedNostrEvent::new("", 1234567890, 1059, vec![], "x"))
+            .unwrap();
+        assert_eq!(signed.pubkey, keys.public_key());
+        signed.verify().unwrap();
+
+        let debug = format!("{:?}", keys);
+        assert!(!debug.contains(&hex::encode(keys.secret_key())));
+    }
+
     #[test]
     fn sig_defaults_to_empty() {
         let event = UnsignedNostrEvent::new("a".repeat(64), 1234567890, 14, vec![], "test");
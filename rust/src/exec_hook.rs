@@ -0,0 +1,151 @@
+//! Run a local command for each new crash.
+//!
+//! `bugstr serve --exec '<command>'` runs the command through `sh -c` with the
+//! crash report as JSON on stdin. Placeholders such as `{exception}` are
+//! replaced with references to environment variables (`${BUGSTR_EXCEPTION}`)
+//! rather than the raw values, so crash content can never inject shell syntax:
+//!
+//! ```text
+//! notify-send "Crash: {exception}"
+//! # runs: notify-send "Crash: ${BUGSTR_EXCEPTION}"  with BUGSTR_EXCEPTION=NullPointerException
+//! ```
+
+use crate::storage::CrashReport;
+use std::process::{ExitStatus, Stdio};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Supported placeholders and the environment variable each maps to.
+pub const PLACEHOLDERS: &[(&str, &str)] = &[
+    ("id", "BUGSTR_ID"),
+    ("event_id", "BUGSTR_EVENT_ID"),
+    ("sender", "BUGSTR_SENDER"),
+    ("app", "BUGSTR_APP"),
+    ("version", "BUGSTR_VERSION"),
+    ("exception", "BUGSTR_EXCEPTION"),
+    ("message", "BUGSTR_MESSAGE"),
+    ("environment", "BUGSTR_ENVIRONMENT"),
+    ("release", "BUGSTR_RELEASE"),
+];
+
+/// A command template run for each new crash.
+#[derive(Debug, Clone)]
+pub struct ExecHook {
+    template: String,
+}
+
+impl ExecHook {
+    /// Creates a hook from a command template.
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+        }
+    }
+
+    /// Returns the shell command with placeholders rewritten to variable references.
+    ///
+    /// Unknown placeholders are left untouched.
+    pub fn command(&self) -> String {
+        let mut command = self.template.clone();
+        for (name, var) in PLACEHOLDERS {
+            command = command.replace(&format!("{{{}}}", name), &format!("${{{}}}", var));
+        }
+        command
+    }
+
+    /// Returns the environment variables for a crash, one per placeholder.
+    ///
+    /// Missing optional fields are exported as empty strings.
+    pub fn env(report: &CrashReport) -> Vec<(&'static str, String)> {
+        let opt = |v: &Option<String>| v.clone().unwrap_or_default();
+        vec![
+            ("BUGSTR_ID", report.id.to_string()),
+            ("BUGSTR_EVENT_ID", report.event_id.clone()),
+            ("BUGSTR_SENDER", report.sender_pubkey.clone()),
+            ("BUGSTR_APP", opt(&report.app_name)),
+            ("BUGSTR_VERSION", opt(&report.app_version)),
+            ("BUGSTR_EXCEPTION", opt(&report.exception_type)),
+            ("BUGSTR_MESSAGE", opt(&report.message)),
+            ("BUGSTR_ENVIRONMENT", opt(&report.environment)),
+            ("BUGSTR_RELEASE", opt(&report.release)),
+        ]
+    }
+
+    /// Runs the command for a crash and waits for it to exit.
+    ///
+    /// The crash report is written to the command's stdin as JSON. A command
+    /// that doesn't read stdin is not an error.
+    pub async fn run(&self, report: &CrashReport) -> std::io::Result<ExitStatus> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(self.command())
+            .envs(Self::env(report))
+            .stdin(Stdio::piped())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            let json = serde_json::to_vec(report)?;
+            if let Err(e) = stdin.write_all(&json).await {
+                if e.kind() != std::io::ErrorKind::BrokenPipe {
+                    return Err(e);
+                }
+            }
+        }
+
+        child.wait().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> CrashReport {
+        CrashReport {
+            id: 7,
+            event_id: "abc".to_string(),
+            sender_pubkey: "pubkey".to_string(),
+            received_at: 1000,
+            created_at: 999,
+            app_name: Some("com.example.app".to_string()),
+            app_version: Some("1.0.0".to_string()),
+            exception_type: Some("$(rm -rf ~)".to_string()),
+            message: None,
+            stack_trace: None,
+            raw_content: "raw".to_string(),
+            environment: None,
+            release: None,
+            process_type: None,
+            route: None,
+            fingerprint: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_command_rewrites_placeholders() {
+        let hook = ExecHook::new(r#"notify-send "Crash: {exception} in {app}" {unknown}"#);
+        assert_eq!(
+            hook.command(),
+            r#"notify-send "Crash: ${BUGSTR_EXCEPTION} in ${BUGSTR_APP}" {unknown}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_passes_values_without_shell_injection() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let hook = ExecHook::new(format!(
+            r#"printf '%s|' "{{exception}}" {{id}} > {out}; cat >> {out}"#,
+            out = out.display()
+        ));
+
+        let status = hook.run(&report()).await.unwrap();
+        assert!(status.success());
+
+        let output = std::fs::read_to_string(&out).unwrap();
+        let (args, json) = output.split_at(output.find('{').unwrap());
+        assert_eq!(args, "$(rm -rf ~)|7|");
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(value["event_id"], "abc");
+    }
+}
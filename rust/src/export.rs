@@ -0,0 +1,370 @@
+//! Export to S3-compatible object storage.
+//!
+//! Uploads a gzipped JSONL export of every stored crash and a consistent
+//! copy of the database to a bucket, so long-term archival doesn't depend
+//! on the receiver host's disk. Works with AWS S3 and compatible services
+//! (MinIO, Cloudflare R2, Backblaze B2, Garage, ...). Requests are signed
+//! with AWS Signature Version 4.
+//!
+//! The bucket is configured per profile:
+//!
+//! ```toml
+//! [profiles.default.s3]
+//! endpoint = "https://s3.eu-central-1.amazonaws.com"
+//! bucket = "crash-archive"
+//! region = "eu-central-1"
+//! prefix = "bugstr/"
+//! interval_hours = 24
+//! ```
+//!
+//! Credentials come from `access_key_id` and `secret_access_key`, or from
+//! `$AWS_ACCESS_KEY_ID` and `$AWS_SECRET_ACCESS_KEY`.
+
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use flate2::{write::GzEncoder, Compression};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::storage::CrashStorage;
+
+/// Errors from exporting to object storage.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Invalid S3 config: {0}")]
+    Config(String),
+
+    #[error("Storage error: {0}")]
+    Storage(#[from] rusqlite::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("S3 returned {status}: {body}")]
+    Status { status: u16, body: String },
+}
+
+/// Bucket settings from the `s3` table of a profile.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Config {
+    /// Service URL, e.g. `https://s3.us-east-1.amazonaws.com`.
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default = "default_region")]
+    pub region: String,
+    /// Prepended to object keys, e.g. `bugstr/`.
+    #[serde(default)]
+    pub prefix: String,
+    /// Access key ID, falling back to `$AWS_ACCESS_KEY_ID`.
+    pub access_key_id: Option<String>,
+    /// Secret access key, falling back to `$AWS_SECRET_ACCESS_KEY`.
+    pub secret_access_key: Option<String>,
+    /// Address the bucket as `<bucket>.<endpoint host>` instead of
+    /// `<endpoint>/<bucket>`.
+    #[serde(default)]
+    pub virtual_hosted: bool,
+    /// Hours between exports while serving.
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u64,
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_interval_hours() -> u64 {
+    24
+}
+
+/// Keys of the objects one export uploaded.
+#[derive(Debug, Clone)]
+pub struct ExportSummary {
+    /// Crashes in the JSONL export.
+    pub crashes: usize,
+    pub jsonl_key: String,
+    pub backup_key: String,
+}
+
+/// Uploads objects to an S3-compatible bucket.
+pub struct S3Client {
+    config: S3Config,
+    access_key_id: String,
+    secret_access_key: String,
+    client: reqwest::blocking::Client,
+}
+
+impl S3Client {
+    /// Creates a client, reading credentials missing from `config` from
+    /// the environment.
+    pub fn new(config: S3Config) -> Result<Self, ExportError> {
+        let credential = |value: &Option<String>, var: &str| {
+            value
+                .clone()
+                .or_else(|| std::env::var(var).ok())
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| ExportError::Config(format!("no credentials (set {})", var)))
+        };
+        let access_key_id = credential(&config.access_key_id, "AWS_ACCESS_KEY_ID")?;
+        let secret_access_key = credential(&config.secret_access_key, "AWS_SECRET_ACCESS_KEY")?;
+        reqwest::Url::parse(&config.endpoint)
+            .map_err(|e| ExportError::Config(format!("endpoint: {}", e)))?;
+        Ok(Self {
+            config,
+            access_key_id,
+            secret_access_key,
+            client: reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(300))
+                .build()
+                .expect("HTTP client"),
+        })
+    }
+
+    /// Uploads `body` as the object `key` (below the configured prefix)
+    /// and returns the full key.
+    pub fn put_object(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        content_type: &str,
+    ) -> Result<String, ExportError> {
+        let key = format!("{}{}", self.config.prefix, key);
+        let url = self.object_url(&key)?;
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let now = Utc::now();
+        let authorization = self.authorization(&url, &payload_hash, now);
+        let response = self
+            .client
+            .put(url)
+            .header("x-amz-content-sha256", &payload_hash)
+            .header("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string())
+            .header("Authorization", authorization)
+            .header("Content-Type", content_type)
+            .body(body)
+            .send()?;
+        if !response.status().is_success() {
+            return Err(ExportError::Status {
+                status: response.status().as_u16(),
+                body: response.text().unwrap_or_default(),
+            });
+        }
+        Ok(key)
+    }
+
+    fn object_url(&self, key: &str) -> Result<reqwest::Url, ExportError> {
+        let endpoint = self.config.endpoint.trim_end_matches('/');
+        let url = if self.config.virtual_hosted {
+            let mut url = reqwest::Url::parse(endpoint)
+                .map_err(|e| ExportError::Config(format!("endpoint: {}", e)))?;
+            let host = format!(
+                "{}.{}",
+                self.config.bucket,
+                url.host_str().unwrap_or_default()
+            );
+            url.set_host(Some(&host))
+                .map_err(|e| ExportError::Config(format!("bucket host: {}", e)))?;
+            format!("{}/{}", url.as_str().trim_end_matches('/'), uri_encode(key))
+        } else {
+            format!("{}/{}/{}", endpoint, self.config.bucket, uri_encode(key))
+        };
+        reqwest::Url::parse(&url).map_err(|e| ExportError::Config(format!("object URL: {}", e)))
+    }
+
+    /// The SigV4 `Authorization` header of a PUT to `url` at `now`.
+    fn authorization(&self, url: &reqwest::Url, payload_hash: &str, now: DateTime<Utc>) -> String {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            url.path(),
+            host,
+            payload_hash,
+            amz_date,
+            signed_headers,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(&self.secret_access_key, &date, &self.config.region, "s3");
+        let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        )
+    }
+}
+
+/// Writes every stored crash as one JSON object per line, gzipped. Returns
+/// the compressed export and the number of crashes.
+pub fn export_jsonl(storage: &CrashStorage) -> Result<(Vec<u8>, usize), ExportError> {
+    let crashes = storage.get_received_since(0)?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    for crash in &crashes {
+        serde_json::to_writer(&mut encoder, crash).map_err(std::io::Error::from)?;
+        encoder.write_all(b"\n")?;
+    }
+    Ok((encoder.finish()?, crashes.len()))
+}
+
+/// Uploads a JSONL export and a backup of the database at `db_path`, named
+/// by the time of the export.
+pub fn run_export(db_path: &Path, client: &S3Client) -> Result<ExportSummary, ExportError> {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ");
+    let storage = CrashStorage::open(db_path)?;
+
+    let (jsonl, crashes) = export_jsonl(&storage)?;
+    let jsonl_key = client.put_object(
+        &format!("exports/crashes-{}.jsonl.gz", stamp),
+        jsonl,
+        "application/gzip",
+    )?;
+
+    let dir = tempfile::tempdir()?;
+    let backup = dir.path().join("bugstr.db");
+    storage.backup_to(&backup)?;
+    let backup_key = client.put_object(
+        &format!("backups/bugstr-{}.db", stamp),
+        std::fs::read(&backup)?,
+        "application/vnd.sqlite3",
+    )?;
+
+    Ok(ExportSummary {
+        crashes,
+        jsonl_key,
+        backup_key,
+    })
+}
+
+/// Derives the SigV4 signing key for a date (`YYYYMMDD`), region and service.
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+    let key = hmac(&key, region.as_bytes());
+    let key = hmac(&key, service.as_bytes());
+    hmac(&key, b"aws4_request")
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encodes an object key for the request path, keeping `/`.
+fn uri_encode(key: &str) -> String {
+    key.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::test_server::serve_once;
+    use crate::storage::CrashReport;
+    use std::io::Read;
+
+    fn config(endpoint: String) -> S3Config {
+        S3Config {
+            endpoint,
+            bucket: "archive".to_string(),
+            region: "eu-central-1".to_string(),
+            prefix: "bugstr/".to_string(),
+            access_key_id: Some("AKIDEXAMPLE".to_string()),
+            secret_access_key: Some("secret".to_string()),
+            virtual_hosted: false,
+            interval_hours: 24,
+        }
+    }
+
+    #[test]
+    fn test_signing_key() {
+        // Example from the AWS Signature Version 4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_put_object() {
+        let (url, server) = serve_once(200, "");
+        let client = S3Client::new(config(url)).unwrap();
+        let key = client
+            .put_object("exports/a b.jsonl.gz", b"data".to_vec(), "application/gzip")
+            .unwrap();
+        assert_eq!(key, "bugstr/exports/a b.jsonl.gz");
+
+        let request = server.join().unwrap();
+        assert_eq!(
+            request.request_line,
+            "PUT /archive/bugstr/exports/a%20b.jsonl.gz HTTP/1.1"
+        );
+        assert_eq!(
+            request.header("x-amz-content-sha256"),
+            Some(hex::encode(Sha256::digest(b"data")).as_str())
+        );
+        let authorization = request.header("authorization").unwrap();
+        assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        assert!(authorization.contains("/eu-central-1/s3/aws4_request, "));
+        assert_eq!(request.body, "data");
+    }
+
+    #[test]
+    fn test_export_jsonl() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        for n in 0..2 {
+            storage
+                .insert(&CrashReport::from_content(
+                    format!("event{}", n),
+                    "sender".to_string(),
+                    1000,
+                    1001,
+                    r#"{"message": "boom"}"#.to_string(),
+                ))
+                .unwrap();
+        }
+
+        let (gz, count) = export_jsonl(&storage).unwrap();
+        assert_eq!(count, 2);
+        let mut jsonl = String::new();
+        flate2::read::GzDecoder::new(gz.as_slice())
+            .read_to_string(&mut jsonl)
+            .unwrap();
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["event_id"], "event1");
+        assert_eq!(lines[0]["message"], "boom");
+    }
+}
@@ -0,0 +1,96 @@
+//! Crash fingerprinting.
+//!
+//! Crashes are grouped by a fingerprint: a hash of the exception type and
+//! the top [`FINGERPRINT_FRAMES`] frames of the stack, normalized with
+//! [`normalize_stack`] so line numbers, addresses and lambda indices don't
+//! split a group between releases. Two `NullPointerException`s thrown from
+//! different code land in different groups; the same crash in the next
+//! release stays in its group.
+//!
+//! Crashes without a recognizable frame fall back to their exception type
+//! alone, which is how every crash was grouped before fingerprints.
+//!
+//! ```
+//! use bugstr::grouping::fingerprint;
+//!
+//! let a = fingerprint(Some("NullPointerException"), Some("at com.example.Main.run(Main.java:12)"));
+//! let b = fingerprint(Some("NullPointerException"), Some("at com.example.Main.run(Main.java:40)"));
+//! let c = fingerprint(Some("NullPointerException"), Some("at com.example.Repo.load(Repo.java:12)"));
+//! assert_eq!(a, b);
+//! assert_ne!(a, c);
+//! ```
+
+use sha2::{Digest, Sha256};
+
+use crate::symbolication::{normalize_stack, Platform};
+
+/// Stack frames that count towards a fingerprint.
+pub const FINGERPRINT_FRAMES: usize = 5;
+
+/// Fingerprint of a crash with `exception_type` and `stack_trace`, as 16
+/// lowercase hex characters.
+pub fn fingerprint(exception_type: Option<&str>, stack_trace: Option<&str>) -> String {
+    let exception_type = exception_type.unwrap_or("Unknown");
+    let frames = stack_trace
+        .map(|stack| normalize_stack(&guess_platform(stack), stack))
+        .unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    hasher.update(exception_type.as_bytes());
+    for frame in frames.iter().take(FINGERPRINT_FRAMES) {
+        hasher.update(b"\n");
+        hasher.update(frame.function.as_bytes());
+        if let Some(module) = &frame.module {
+            hasher.update(b" ");
+            hasher.update(module.as_bytes());
+        }
+    }
+    hex::encode(&hasher.finalize()[..8])
+}
+
+/// The platform whose frame formats `stack` needs beyond the common ones:
+/// Rust and Go print frame locations on their own lines.
+fn guess_platform(stack: &str) -> Platform {
+    if stack.contains(".rs:") {
+        Platform::Rust
+    } else if stack.contains(".go:") {
+        Platform::Go
+    } else {
+        Platform::Unknown(String::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_stable_across_builds() {
+        let stack = |line: u32, address: &str| {
+            format!(
+                "thread 'main' panicked at src/db.rs:{line}:5\n\
+                 stack backtrace:\n\
+                 \x20  0: {address} - std::panicking::begin_panic\n\
+                 \x20  1: {address} - myapp::db::connect::h0123456789abcdef\n\
+                 \x20            at ./src/db.rs:{line}:5\n\
+                 \x20  2: {address} - myapp::main\n\
+                 \x20            at ./src/main.rs:3:5"
+            )
+        };
+        let a = fingerprint(Some("panic"), Some(&stack(10, "0x55d1c2a3b4c5")));
+        let b = fingerprint(Some("panic"), Some(&stack(42, "0x7f00aa001234")));
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 16);
+        assert_ne!(a, fingerprint(Some("panic"), None));
+        assert_ne!(a, fingerprint(Some("Error"), Some(&stack(10, "0x1"))));
+    }
+
+    #[test]
+    fn test_fingerprint_without_frames() {
+        assert_eq!(
+            fingerprint(Some("Error"), Some("Error: boom")),
+            fingerprint(Some("Error"), None)
+        );
+        assert_eq!(fingerprint(None, None), fingerprint(Some("Unknown"), None));
+    }
+}
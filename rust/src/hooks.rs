@@ -0,0 +1,197 @@
+//! Plugin hooks.
+//!
+//! Downstream users extend the receiver without forking the crate by
+//! registering [`Hook`] implementations on
+//! [`AppState::hooks`](crate::web::AppState::hooks). Hooks run at fixed
+//! points of the ingest and alert pipeline:
+//!
+//! | Method | Called |
+//! |--------|--------|
+//! | [`on_report_received`](Hook::on_report_received) | For every crash received, before anything else |
+//! | [`before_store`](Hook::before_store) | Before storing; may enrich the report or drop it |
+//! | [`on_group_created`](Hook::on_group_created) | After storing the first crash of a new group |
+//! | [`on_alert`](Hook::on_alert) | Before an alert is sent to integrations |
+//!
+//! Every method has a no-op default, so a hook implements only the points
+//! it needs. Hooks run in registration order on the receiver's workers and
+//! should return quickly; spawn a thread or task for slow work such as
+//! calls to an external tracker.
+//!
+//! ```
+//! use bugstr::{CrashReport, Hook, Hooks};
+//!
+//! /// Tags every crash with the deployment it was received by.
+//! struct Region(&'static str);
+//!
+//! impl Hook for Region {
+//!     fn name(&self) -> &str {
+//!         "region"
+//!     }
+//!
+//!     fn before_store(&self, report: &mut CrashReport) -> bool {
+//!         report.environment.get_or_insert_with(|| self.0.to_string());
+//!         true
+//!     }
+//! }
+//!
+//! let hooks = Hooks::new().with_hook(Region("eu-west"));
+//! ```
+
+use std::sync::Arc;
+
+use crate::notify::Alert;
+use crate::storage::CrashReport;
+
+/// Extension points of the receiver pipeline. See the [module
+/// documentation](self).
+pub trait Hook: Send + Sync {
+    /// Short name, for logs.
+    fn name(&self) -> &str;
+
+    /// A crash was received, from a relay or the Sentry endpoint. Called
+    /// before [`before_store`](Self::before_store) and for duplicates too.
+    fn on_report_received(&self, _report: &CrashReport) {}
+
+    /// Called before `report` is stored. Hooks may change it, e.g. to add
+    /// fields, and return `false` to drop it; later hooks then don't run.
+    fn before_store(&self, _report: &mut CrashReport) -> bool {
+        true
+    }
+
+    /// `report`, now stored with its ID, started the group `group_key`.
+    fn on_group_created(&self, _group_key: &str, _report: &CrashReport) {}
+
+    /// `alert` is about to be sent to integrations.
+    fn on_alert(&self, _alert: &Alert) {}
+}
+
+/// The registered hooks, called in registration order.
+#[derive(Clone, Default)]
+pub struct Hooks {
+    hooks: Vec<Arc<dyn Hook>>,
+}
+
+impl Hooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `hook` after the ones already registered.
+    pub fn with_hook(mut self, hook: impl Hook + 'static) -> Self {
+        self.register(Arc::new(hook));
+        self
+    }
+
+    /// Adds `hook` after the ones already registered.
+    pub fn register(&mut self, hook: Arc<dyn Hook>) {
+        self.hooks.push(hook);
+    }
+
+    /// Names of the registered hooks, in order.
+    pub fn names(&self) -> Vec<&str> {
+        self.hooks.iter().map(|hook| hook.name()).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Calls [`Hook::on_report_received`] on every hook.
+    pub fn report_received(&self, report: &CrashReport) {
+        for hook in &self.hooks {
+            hook.on_report_received(report);
+        }
+    }
+
+    /// Calls [`Hook::before_store`] on every hook until one drops the
+    /// report. Returns whether to store it.
+    pub fn before_store(&self, report: &mut CrashReport) -> bool {
+        self.hooks.iter().all(|hook| hook.before_store(report))
+    }
+
+    /// Calls [`Hook::on_group_created`] on every hook.
+    pub fn group_created(&self, group_key: &str, report: &CrashReport) {
+        for hook in &self.hooks {
+            hook.on_group_created(group_key, report);
+        }
+    }
+
+    /// Calls [`Hook::on_alert`] on every hook.
+    pub fn alert(&self, alert: &Alert) {
+        for hook in &self.hooks {
+            hook.on_alert(alert);
+        }
+    }
+}
+
+impl std::fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records the calls it gets and drops crashes without a message.
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Hook for Arc<Recorder> {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        fn on_report_received(&self, report: &CrashReport) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("received {}", report.event_id));
+        }
+
+        fn before_store(&self, report: &mut CrashReport) -> bool {
+            report.environment = Some("staging".to_string());
+            report.message.is_some()
+        }
+
+        fn on_group_created(&self, group_key: &str, _report: &CrashReport) {
+            self.0.lock().unwrap().push(format!("group {}", group_key));
+        }
+    }
+
+    struct Never;
+
+    impl Hook for Never {
+        fn name(&self) -> &str {
+            "never"
+        }
+
+        fn before_store(&self, _report: &mut CrashReport) -> bool {
+            panic!("runs after a hook dropped the report")
+        }
+    }
+
+    fn report(content: &str) -> CrashReport {
+        CrashReport::from_content("event".into(), "sender".into(), 1000, 1001, content.into())
+    }
+
+    #[test]
+    fn test_hooks_run_in_order() {
+        let recorder = Arc::new(Recorder::default());
+        let hooks = Hooks::new().with_hook(recorder.clone()).with_hook(Never);
+        assert_eq!(hooks.names(), ["recorder", "never"]);
+
+        let mut dropped = report(r#"{"stack": "java.lang.IllegalStateException: boom"}"#);
+        hooks.report_received(&dropped);
+        assert!(!hooks.before_store(&mut dropped));
+        assert_eq!(dropped.environment.as_deref(), Some("staging"));
+
+        hooks.group_created("IllegalStateException", &dropped);
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            ["received event", "group IllegalStateException"]
+        );
+    }
+}
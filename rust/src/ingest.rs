@@ -0,0 +1,158 @@
+//! File-based crash ingestion.
+//!
+//! Inserts crash payload files (written by a client's local cache, CI, or
+//! copied off a device) directly into storage, bypassing relays. Each file
+//! holds one crash payload, plain or in the compression envelope.
+//!
+//! Files are identified by the SHA-256 of their content (`file:<hex>`), so
+//! ingesting the same payload twice is a no-op.
+
+use crate::compression::decompress_payload;
+use crate::storage::{CrashReport, CrashStorage};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use thiserror::Error;
+
+/// Sender recorded for crashes ingested from files.
+pub const FILE_SENDER: &str = "file";
+
+/// Errors from ingesting a crash file.
+#[derive(Debug, Error)]
+pub enum IngestError {
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("{0} is empty")]
+    Empty(PathBuf),
+
+    #[error("Database error: {0}")]
+    Storage(#[from] rusqlite::Error),
+}
+
+/// Outcome of ingesting one file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngestOutcome {
+    /// Stored as a new crash with this id.
+    Inserted(i64),
+    /// Same payload was already ingested.
+    Duplicate,
+}
+
+/// Returns whether a path looks like a finished crash payload file.
+///
+/// Skips hidden files and common in-progress suffixes (`.tmp`, `.part`,
+/// `.partial`, `.swp`) so writers can drop files atomically by renaming.
+pub fn is_candidate(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    if name.starts_with('.') || name.ends_with('~') {
+        return false;
+    }
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    !matches!(ext, "tmp" | "part" | "partial" | "swp") && path.is_file()
+}
+
+/// Ingests a single crash payload file.
+///
+/// The crash's `created_at` is the file's modification time.
+pub fn ingest_file(
+    storage: &CrashStorage,
+    path: &Path,
+    received_at: i64,
+) -> Result<IngestOutcome, IngestError> {
+    let io_err = |source| IngestError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let raw = std::fs::read_to_string(path).map_err(io_err)?;
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err(IngestError::Empty(path.to_path_buf()));
+    }
+
+    let created_at = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(received_at);
+
+    let content = decompress_payload(raw).unwrap_or_else(|_| raw.to_string());
+    let event_id = format!("file:{}", hex::encode(Sha256::digest(content.as_bytes())));
+    let report = CrashReport::from_content(
+        event_id,
+        FILE_SENDER.to_string(),
+        created_at,
+        received_at,
+        content,
+    );
+
+    Ok(match storage.insert(&report)? {
+        Some(id) => IngestOutcome::Inserted(id),
+        None => IngestOutcome::Duplicate,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::compress_payload;
+
+    #[test]
+    fn test_ingest_file_dedups_by_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = CrashStorage::open_in_memory().unwrap();
+
+        let plain = dir.path().join("crash.json");
+        std::fs::write(&plain, r#"{"message":"boom","stack":"Error: boom\n  at main"}"#).unwrap();
+        let outcome = ingest_file(&storage, &plain, 1000).unwrap();
+        let IngestOutcome::Inserted(id) = outcome else {
+            panic!("expected insert, got {:?}", outcome);
+        };
+        let stored = storage.get_by_id(id).unwrap().unwrap();
+        assert!(stored.event_id.starts_with("file:"));
+        assert_eq!(stored.sender_pubkey, FILE_SENDER);
+        assert_eq!(stored.message.as_deref(), Some("boom"));
+
+        // Same payload, compressed, in another file
+        let compressed = dir.path().join("crash-2.json");
+        let envelope =
+            compress_payload(r#"{"message":"boom","stack":"Error: boom\n  at main"}"#).unwrap();
+        std::fs::write(&compressed, envelope).unwrap();
+        assert_eq!(
+            ingest_file(&storage, &compressed, 1000).unwrap(),
+            IngestOutcome::Duplicate
+        );
+        assert_eq!(storage.count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_ingest_empty_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let path = dir.path().join("empty.txt");
+        std::fs::write(&path, "\n").unwrap();
+        assert!(matches!(
+            ingest_file(&storage, &path, 1000),
+            Err(IngestError::Empty(_))
+        ));
+    }
+
+    #[test]
+    fn test_is_candidate() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["crash.json", ".hidden", "crash.json.tmp", "crash.part"] {
+            std::fs::write(dir.path().join(name), "x").unwrap();
+        }
+        assert!(is_candidate(&dir.path().join("crash.json")));
+        assert!(!is_candidate(&dir.path().join(".hidden")));
+        assert!(!is_candidate(&dir.path().join("crash.json.tmp")));
+        assert!(!is_candidate(&dir.path().join("crash.part")));
+        assert!(!is_candidate(dir.path()));
+    }
+}
@@ -0,0 +1,191 @@
+//! Markdown issue reports.
+//!
+//! Formats a stored crash as a GitHub-flavored markdown document: a
+//! metadata table, the stack trace (symbolicated when available) and any
+//! breadcrumbs recorded by the SDK. Used by `bugstr issue`.
+
+use crate::storage::CrashReport;
+use chrono::DateTime;
+
+/// Returns a one-line issue title for a crash.
+pub fn issue_title(report: &CrashReport) -> String {
+    let exception = report.exception_type.as_deref().unwrap_or("Crash");
+    match report.message.as_deref().and_then(|m| m.lines().next()) {
+        Some(message) if !message.contains(exception) => {
+            format!("{}: {}", exception, truncate(message, 100))
+        }
+        Some(message) => truncate(message, 120),
+        None => exception.to_string(),
+    }
+}
+
+/// Formats a crash as a markdown issue body.
+///
+/// `symbolicated_stack` replaces the raw stack trace when provided.
+pub fn format_issue(report: &CrashReport, symbolicated_stack: Option<&str>) -> String {
+    let mut out = format!("## {}\n\n", issue_title(report));
+
+    out.push_str("| Field | Value |\n|---|---|\n");
+    let mut row = |field: &str, value: Option<&str>| {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            out.push_str(&format!("| {} | {} |\n", field, table_cell(value)));
+        }
+    };
+    row("Crash ID", Some(&report.id.to_string()));
+    row("App", report.app_name.as_deref());
+    row("Version", report.app_version.as_deref());
+    row("Release", report.release.as_deref());
+    row("Environment", report.environment.as_deref());
+    row("Process", report.process_type.as_deref());
+    row("Route", report.route.as_deref());
+    row("Exception", report.exception_type.as_deref());
+    row("Occurred", format_timestamp(report.created_at).as_deref());
+    row("Received", format_timestamp(report.received_at).as_deref());
+    row("Event ID", Some(&format!("`{}`", report.event_id)));
+
+    if let Some(message) = report.message.as_deref().filter(|m| !m.is_empty()) {
+        out.push_str("\n### Message\n\n");
+        out.push_str(&code_block(message));
+    }
+
+    let stack = symbolicated_stack.or(report.stack_trace.as_deref());
+    if let Some(stack) = stack.filter(|s| !s.trim().is_empty()) {
+        if symbolicated_stack.is_some() {
+            out.push_str("\n### Stack trace (symbolicated)\n\n");
+        } else {
+            out.push_str("\n### Stack trace\n\n");
+        }
+        out.push_str(&code_block(stack));
+    }
+
+    let breadcrumbs = extract_breadcrumbs(&report.raw_content);
+    if !breadcrumbs.is_empty() {
+        out.push_str("\n### Breadcrumbs\n\n");
+        for crumb in breadcrumbs {
+            out.push_str(&format!("- {}\n", crumb));
+        }
+    }
+
+    out
+}
+
+/// Extracts breadcrumbs from a JSON crash payload as display lines.
+///
+/// Accepts a `breadcrumbs` array of strings or objects with optional
+/// `timestamp`, `category` and `message` fields.
+fn extract_breadcrumbs(raw_content: &str) -> Vec<String> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(raw_content) else {
+        return Vec::new();
+    };
+    let Some(items) = json.get("breadcrumbs").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+
+    items
+        .iter()
+        .filter_map(|item| {
+            if let Some(s) = item.as_str() {
+                return Some(s.to_string());
+            }
+            let message = item.get("message").and_then(|v| v.as_str())?;
+            let mut line = String::new();
+            if let Some(ts) = item.get("timestamp").and_then(|v| v.as_i64()) {
+                // SDKs report milliseconds; tolerate seconds too
+                let secs = if ts > 10_000_000_000 { ts / 1000 } else { ts };
+                if let Some(t) = format_timestamp(secs) {
+                    line.push_str(&format!("`{}` ", t));
+                }
+            }
+            if let Some(category) = item.get("category").and_then(|v| v.as_str()) {
+                line.push_str(&format!("**{}** ", category));
+            }
+            line.push_str(message);
+            Some(line)
+        })
+        .collect()
+}
+
+fn format_timestamp(secs: i64) -> Option<String> {
+    DateTime::from_timestamp(secs, 0).map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+}
+
+/// Escapes a value for a single markdown table cell.
+fn table_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Wraps text in a fenced code block, lengthening the fence if the text
+/// contains backtick runs.
+fn code_block(text: &str) -> String {
+    let mut fence = "```".to_string();
+    while text.contains(&fence) {
+        fence.push('`');
+    }
+    format!("{}\n{}\n{}\n", fence, text.trim_end(), fence)
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        format!("{}…", s.chars().take(max).collect::<String>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(raw_content: &str) -> CrashReport {
+        CrashReport {
+            id: 42,
+            event_id: "abc123".to_string(),
+            sender_pubkey: "pubkey".to_string(),
+            received_at: 1_700_000_100,
+            created_at: 1_700_000_000,
+            app_name: Some("com.example.app".to_string()),
+            app_version: Some("1.2.0".to_string()),
+            exception_type: Some("IllegalStateException".to_string()),
+            message: Some("state | invalid".to_string()),
+            stack_trace: Some("at a.b.c(Unknown Source)".to_string()),
+            raw_content: raw_content.to_string(),
+            environment: None,
+            release: None,
+            process_type: None,
+            route: None,
+            fingerprint: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_format_issue() {
+        let raw = r#"{"breadcrumbs":[
+            {"timestamp":1700000000000,"category":"nav","message":"Opened settings"},
+            "Tapped save"
+        ]}"#;
+        let md = format_issue(&report(raw), None);
+
+        assert!(md.starts_with("## IllegalStateException: state | invalid\n"));
+        assert!(md.contains("| Crash ID | 42 |"));
+        assert!(md.contains("| Version | 1.2.0 |"));
+        assert!(!md.contains("| Release |"));
+        assert!(md.contains("### Stack trace\n\n```\nat a.b.c(Unknown Source)\n```"));
+        assert!(md.contains("- `2023-11-14 22:13:20 UTC` **nav** Opened settings\n"));
+        assert!(md.contains("- Tapped save\n"));
+    }
+
+    #[test]
+    fn test_format_issue_prefers_symbolicated_stack() {
+        let md = format_issue(&report("plain text"), Some("at com.example.Foo.bar(Foo.kt:10)"));
+        assert!(md.contains("### Stack trace (symbolicated)"));
+        assert!(md.contains("Foo.kt:10"));
+        assert!(!md.contains("Unknown Source"));
+        assert!(!md.contains("### Breadcrumbs"));
+    }
+
+    #[test]
+    fn test_code_block_fence() {
+        assert_eq!(code_block("a ``` b"), "````\na ``` b\n````\n");
+        assert_eq!(table_cell("a|b\nc"), "a\\|b c");
+    }
+}
@@ -0,0 +1,170 @@
+//! Bugstr rumor kinds and tag conventions.
+//!
+//! Senders and receivers both build and classify crash report rumors
+//! through this module, so the two sides agree on kinds and tags:
+//!
+//! | Kind | Use |
+//! |------|-----|
+//! | 14 | Legacy NIP-17 chat message carrying a crash payload |
+//! | 10420 | Crash payload delivered directly in the rumor |
+//! | 10421 | Manifest of a payload too large for one event, sent as chunks |
+//! | 10422 | One chunk of a large payload (public, encrypted) |
+//!
+//! Rumors also carry a `["content-type", <type>]` tag describing their
+//! content, so receivers don't have to guess from the payload.
+
+use crate::event::{Tag, UnsignedNostrEvent};
+
+/// Legacy NIP-17 chat message kind, still accepted from older senders.
+pub const KIND_LEGACY_DM: u16 = 14;
+
+/// Crash payload delivered directly in the rumor.
+pub const KIND_DIRECT: u16 = 10420;
+
+/// Manifest describing a chunked crash payload.
+pub const KIND_MANIFEST: u16 = 10421;
+
+/// One chunk of a chunked crash payload.
+pub const KIND_CHUNK: u16 = 10422;
+
+/// Name of the content-type tag.
+pub const CONTENT_TYPE_TAG: &str = "content-type";
+
+/// Content type of a crash payload: JSON, possibly inside a compression
+/// envelope (see [`crate::compression`]).
+pub const CONTENT_TYPE_CRASH: &str = "application/vnd.bugstr.crash+json";
+
+/// Content type of a chunk manifest.
+pub const CONTENT_TYPE_MANIFEST: &str = "application/vnd.bugstr.manifest+json";
+
+/// A bugstr rumor kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RumorKind {
+    /// Kind 14: legacy chat message carrying the payload.
+    LegacyDm,
+    /// Kind 10420: payload carried directly.
+    Direct,
+    /// Kind 10421: chunk manifest.
+    Manifest,
+}
+
+impl RumorKind {
+    /// Classifies an event kind, or `None` for kinds bugstr doesn't send.
+    pub fn from_kind(kind: u16) -> Option<Self> {
+        match kind {
+            KIND_LEGACY_DM => Some(Self::LegacyDm),
+            KIND_DIRECT => Some(Self::Direct),
+            KIND_MANIFEST => Some(Self::Manifest),
+            _ => None,
+        }
+    }
+
+    /// The event kind.
+    pub fn kind(self) -> u16 {
+        match self {
+            Self::LegacyDm => KIND_LEGACY_DM,
+            Self::Direct => KIND_DIRECT,
+            Self::Manifest => KIND_MANIFEST,
+        }
+    }
+
+    /// Whether the rumor's content is the crash payload itself.
+    pub fn carries_payload(self) -> bool {
+        matches!(self, Self::LegacyDm | Self::Direct)
+    }
+}
+
+/// The value of the first content-type tag in `tags`, if any.
+pub fn content_type(tags: &[Vec<String>]) -> Option<&str> {
+    tags.iter()
+        .find(|tag| tag.first().map(String::as_str) == Some(CONTENT_TYPE_TAG))
+        .and_then(|tag| tag.get(1))
+        .map(String::as_str)
+}
+
+/// Builds a kind 10420 rumor carrying `payload` directly, addressed to
+/// `recipient_pubkey`. Seal it with [`crate::build_seal`].
+///
+/// # Example
+///
+/// ```
+/// use bugstr::kinds::{build_direct_rumor, content_type, CONTENT_TYPE_CRASH, KIND_DIRECT};
+///
+/// let rumor = build_direct_rumor("ab".repeat(32), "cd".repeat(32), 1234567890, "{}");
+/// assert_eq!(rumor.kind, KIND_DIRECT);
+/// assert_eq!(content_type(&rumor.tags), Some(CONTENT_TYPE_CRASH));
+/// ```
+pub fn build_direct_rumor(
+    sender_pubkey: impl Into<String>,
+    recipient_pubkey: impl Into<String>,
+    created_at: u64,
+    payload: impl Into<String>,
+) -> UnsignedNostrEvent {
+    build_rumor(
+        sender_pubkey,
+        recipient_pubkey,
+        created_at,
+        KIND_DIRECT,
+        CONTENT_TYPE_CRASH,
+        payload,
+    )
+}
+
+/// Builds a kind 10421 rumor carrying a chunk `manifest` (JSON), addressed
+/// to `recipient_pubkey`.
+pub fn build_manifest_rumor(
+    sender_pubkey: impl Into<String>,
+    recipient_pubkey: impl Into<String>,
+    created_at: u64,
+    manifest: impl Into<String>,
+) -> UnsignedNostrEvent {
+    build_rumor(
+        sender_pubkey,
+        recipient_pubkey,
+        created_at,
+        KIND_MANIFEST,
+        CONTENT_TYPE_MANIFEST,
+        manifest,
+    )
+}
+
+fn build_rumor(
+    sender_pubkey: impl Into<String>,
+    recipient_pubkey: impl Into<String>,
+    created_at: u64,
+    kind: u16,
+    content_type: &str,
+    content: impl Into<String>,
+) -> UnsignedNostrEvent {
+    let tags = vec![
+        Tag::pubkey(recipient_pubkey, None).into(),
+        Tag::content_type(content_type).into(),
+    ];
+    UnsignedNostrEvent::new(sender_pubkey, created_at, kind, tags, content).with_id()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rumor_builders_and_classification() {
+        let recipient = "cd".repeat(32);
+        let direct = build_direct_rumor("ab".repeat(32), &recipient, 1234567890, "{}");
+        assert_eq!(direct.tags[0], ["p", recipient.as_str()]);
+        assert_eq!(content_type(&direct.tags), Some(CONTENT_TYPE_CRASH));
+        assert_eq!(direct.id, Some(direct.compute_id()));
+
+        let manifest = build_manifest_rumor("ab".repeat(32), &recipient, 1234567890, "{}");
+        assert_eq!(manifest.kind, KIND_MANIFEST);
+        assert_eq!(content_type(&manifest.tags), Some(CONTENT_TYPE_MANIFEST));
+
+        for kind in [RumorKind::LegacyDm, RumorKind::Direct, RumorKind::Manifest] {
+            assert_eq!(RumorKind::from_kind(kind.kind()), Some(kind));
+        }
+        assert!(RumorKind::Direct.carries_payload());
+        assert!(!RumorKind::Manifest.carries_payload());
+        assert_eq!(RumorKind::from_kind(KIND_CHUNK), None);
+        assert_eq!(content_type(&[]), None);
+    }
+}
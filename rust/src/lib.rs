@@ -7,8 +7,12 @@
 //!
 //! - Panic hook for capturing crashes
 //! - Local file-based caching
-//! - Gzip compression for large payloads
+//! - Gzip compression for large payloads, or zstd with a crash payload dictionary
 //! - NIP-17/44/59 gift wrap building
+//! - Sending reports to relays, directly or in chunks ([`CrashSender`])
+//! - Streaming chunking of very large payloads ([`chunking`])
+//! - Breadcrumbs recorded before a crash ([`breadcrumbs`])
+//! - A versioned crash payload schema ([`schema::CrashEvent`])
 //!
 //! # Example
 //!
@@ -23,20 +27,65 @@
 //! }
 //! ```
 
+pub mod breadcrumbs;
+pub mod chunking;
 pub mod compression;
+pub mod config;
+pub mod consent;
 pub mod event;
+pub mod exec_hook;
+pub mod export;
+pub mod grouping;
+pub mod hooks;
+pub mod ingest;
+pub mod issue;
+pub mod kinds;
+pub mod nip19;
+pub mod nip59;
+pub mod notify;
+pub mod schema;
+pub mod sender;
+pub mod sentry;
 pub mod storage;
 pub mod symbolication;
+pub mod tui;
 pub mod web;
 
-pub use compression::{compress_payload, decompress_payload, maybe_compress_payload, DEFAULT_THRESHOLD};
-pub use event::UnsignedNostrEvent;
-pub use storage::{CrashReport, CrashGroup, CrashStorage, parse_crash_content};
+pub use breadcrumbs::{Breadcrumb, Breadcrumbs};
+pub use chunking::{ChunkReader, ChunkWriter, ChunkedPayload};
+pub use compression::{
+    compress_payload, compress_payload_dict, compress_stream, decode_payload, decompress_payload,
+    decompress_stream, maybe_compress_payload, payload_encoding, DEFAULT_THRESHOLD,
+};
+pub use config::{ConfigError, ConfigFile, Profile, WebhookConfig};
+pub use consent::{ConsentError, ConsentQueue};
+pub use event::{
+    randomized_created_at, EphemeralKeys, EventError, SignedNostrEvent, Tag, UnsignedNostrEvent,
+    MAX_CREATED_AT_OFFSET,
+};
+pub use exec_hook::ExecHook;
+pub use export::{export_jsonl, run_export, ExportError, ExportSummary, S3Client, S3Config};
+pub use hooks::{Hook, Hooks};
+pub use ingest::{ingest_file, IngestError, IngestOutcome};
+pub use issue::{format_issue, issue_title};
+pub use kinds::{build_direct_rumor, build_manifest_rumor, RumorKind};
+pub use nip19::{EventPointer, Nip19, Nip19Error};
+pub use nip59::{build_gift_wrap, build_seal, rewrap, Nip59Error};
+pub use notify::rules::{Rule, RuleCondition};
+pub use notify::{
+    alerts_for_crash, notify_all, route_alerts, Alert, AlertKind, AnomalyDetector, Delivery,
+    Integration, IntegrationConfig, Notifier, NotifyError, SmtpSecurity, Spike,
+};
+pub use notify::anomaly::spike_alert;
+pub use schema::{CrashEvent, SchemaError};
+pub use sender::{publish_event, Attachment, ChunkError, CrashSender, Manifest, SendError, SendReport, Transport};
+pub use sentry::{crash_from_event, event_from_crash, Dsn, SentryError, SENTRY_SENDER};
+pub use storage::{AttachmentInfo, CrashReport, CrashGroup, CrashStorage, GroupStatus, IssueLink, parse_crash_content, PendingManifest, SCHEMA_VERSION};
 pub use symbolication::{
-    MappingStore, Platform, Symbolicator, SymbolicatedFrame, SymbolicatedStack,
-    SymbolicationContext, SymbolicationError,
+    Confidence, Debuginfod, ExceptionCause, InAppRules, normalize_stack, NormalizedFrame, MappingCache, MappingChange, MappingStore, MappingWatcher, Platform, PlatformMetrics, PlatformSymbolicator, PrunePolicy, ResultCache, SymbolServer, Symbolicator,
+    SymbolicatedFrame, SymbolicatedStack, SymbolicationContext, SymbolicationError, SymbolicationMetrics,
 };
-pub use web::{create_router, AppState};
+pub use web::{create_router, AppState, SentryIngest};
 
 /// Configuration for the crash report handler.
 #[derive(Debug, Clone)]
@@ -51,6 +100,8 @@ pub struct BugstrConfig {
     pub app_version: String,
     /// Maximum stack trace characters
     pub max_stack_chars: usize,
+    /// Largest payload, after compression, that will be sent
+    pub max_payload_size: usize,
 }
 
 impl Default for BugstrConfig {
@@ -64,6 +115,7 @@ impl Default for BugstrConfig {
             app_name: "Unknown".into(),
             app_version: "0.0.0".into(),
             max_stack_chars: 200_000,
+            max_payload_size: chunking::DEFAULT_MAX_PAYLOAD_SIZE,
         }
     }
 }
@@ -103,4 +155,89 @@ impl CrashReportCache {
     pub fn path(&self) -> &std::path::Path {
         &self.path
     }
+
+    /// Saves a crash `payload` until it is sent or discarded. Returns its ID.
+    pub fn store(&self, payload: &str) -> std::io::Result<String> {
+        use secp256k1::rand::{thread_rng, Rng};
+
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let id = format!("{}-{:08x}", nanos, thread_rng().gen::<u32>());
+        std::fs::write(self.file(&id), payload)?;
+        Ok(id)
+    }
+
+    /// Cached reports, oldest first.
+    pub fn list(&self) -> std::io::Result<Vec<PendingReport>> {
+        let mut reports = Vec::new();
+        for entry in std::fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            let Some(id) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".json"))
+            else {
+                continue;
+            };
+            if let Some(report) = self.get(id)? {
+                reports.push(report);
+            }
+        }
+        reports.sort_by_key(|report| parse_cache_id(&report.id));
+        Ok(reports)
+    }
+
+    /// The cached report `id`, if any.
+    pub fn get(&self, id: &str) -> std::io::Result<Option<PendingReport>> {
+        let Some(nanos) = parse_cache_id(id) else {
+            return Ok(None);
+        };
+        match std::fs::read_to_string(self.file(id)) {
+            Ok(payload) => Ok(Some(PendingReport {
+                id: id.to_string(),
+                created_at: (nanos / 1_000_000) as u64,
+                payload,
+            })),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Deletes the cached report `id`. Returns whether it existed.
+    pub fn remove(&self, id: &str) -> std::io::Result<bool> {
+        if parse_cache_id(id).is_none() {
+            return Ok(false);
+        }
+        match std::fs::remove_file(self.file(id)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn file(&self, id: &str) -> std::path::PathBuf {
+        self.path.join(format!("{}.json", id))
+    }
+}
+
+/// A crash report waiting in a [`CrashReportCache`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingReport {
+    pub id: String,
+    /// When the report was cached (Unix milliseconds).
+    pub created_at: u64,
+    /// The crash payload, as passed to [`CrashReportCache::store`].
+    pub payload: String,
+}
+
+/// The creation time in a cache ID (`<nanos>-<random hex>`), or `None` if
+/// `id` isn't one, so IDs can't name files outside the cache.
+fn parse_cache_id(id: &str) -> Option<u128> {
+    let (nanos, random) = id.split_once('-')?;
+    if random.is_empty() || !random.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    nanos.parse().ok()
 }
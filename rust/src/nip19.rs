@@ -0,0 +1,253 @@
+//! NIP-19 bech32 identifiers.
+//!
+//! Encodes and decodes `npub`, `nsec`, `note` and `nevent` strings so
+//! library users can render and parse keys and event IDs without the
+//! `nostr` crate. Keys and IDs are lowercase hex, as elsewhere in bugstr.
+
+use bech32::{Bech32, Hrp};
+use thiserror::Error;
+
+/// TLV type of the event ID (`nevent`).
+const TLV_SPECIAL: u8 = 0;
+/// TLV type of a relay hint.
+const TLV_RELAY: u8 = 1;
+/// TLV type of the author's public key.
+const TLV_AUTHOR: u8 = 2;
+/// TLV type of the event kind (32-bit big-endian).
+const TLV_KIND: u8 = 3;
+
+/// NIP-19 encoding and decoding errors.
+#[derive(Debug, Error)]
+pub enum Nip19Error {
+    #[error("Invalid bech32: {0}")]
+    Bech32(String),
+
+    #[error("Unsupported prefix: {0}")]
+    UnsupportedPrefix(String),
+
+    #[error("Invalid hex: {0}")]
+    InvalidHex(#[from] hex::FromHexError),
+
+    #[error("Expected 32 bytes, got {0}")]
+    InvalidLength(usize),
+
+    #[error("Malformed TLV data")]
+    InvalidTlv,
+
+    #[error("Missing event ID")]
+    MissingEventId,
+}
+
+/// A reference to an event with optional hints (the `nevent` payload).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventPointer {
+    /// Event ID (lowercase hex, 64 chars)
+    pub id: String,
+    /// Relays where the event may be found
+    pub relays: Vec<String>,
+    /// Public key of the event author (lowercase hex, 64 chars)
+    pub author: Option<String>,
+    /// Event kind
+    pub kind: Option<u32>,
+}
+
+/// A decoded NIP-19 identifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Nip19 {
+    /// `npub`: a public key.
+    Pubkey(String),
+    /// `nsec`: a secret key.
+    SecretKey(String),
+    /// `note`: an event ID.
+    Note(String),
+    /// `nevent`: an event ID with hints.
+    Event(EventPointer),
+}
+
+/// Encodes a hex public key as `npub1...`.
+pub fn encode_npub(pubkey: &str) -> Result<String, Nip19Error> {
+    encode("npub", &bytes32(pubkey)?)
+}
+
+/// Encodes a hex secret key as `nsec1...`.
+pub fn encode_nsec(secret_key: &str) -> Result<String, Nip19Error> {
+    encode("nsec", &bytes32(secret_key)?)
+}
+
+/// Encodes a hex event ID as `note1...`.
+pub fn encode_note(event_id: &str) -> Result<String, Nip19Error> {
+    encode("note", &bytes32(event_id)?)
+}
+
+/// Encodes an event pointer as `nevent1...`, including its relay hints,
+/// author and kind when present.
+///
+/// # Example
+///
+/// ```
+/// use bugstr::nip19::{decode, encode_nevent, EventPointer, Nip19};
+///
+/// let pointer = EventPointer {
+///     id: "ab".repeat(32),
+///     relays: vec!["wss://relay.damus.io".to_string()],
+///     ..Default::default()
+/// };
+/// let nevent = encode_nevent(&pointer).unwrap();
+/// assert_eq!(decode(&nevent).unwrap(), Nip19::Event(pointer));
+/// ```
+pub fn encode_nevent(pointer: &EventPointer) -> Result<String, Nip19Error> {
+    let mut data = Vec::new();
+    push_tlv(&mut data, TLV_SPECIAL, &bytes32(&pointer.id)?)?;
+    for relay in &pointer.relays {
+        push_tlv(&mut data, TLV_RELAY, relay.as_bytes())?;
+    }
+    if let Some(author) = &pointer.author {
+        push_tlv(&mut data, TLV_AUTHOR, &bytes32(author)?)?;
+    }
+    if let Some(kind) = pointer.kind {
+        push_tlv(&mut data, TLV_KIND, &kind.to_be_bytes())?;
+    }
+    encode("nevent", &data)
+}
+
+/// Decodes an `npub`, `nsec`, `note` or `nevent` string. A leading
+/// `nostr:` URI scheme is accepted.
+pub fn decode(s: &str) -> Result<Nip19, Nip19Error> {
+    let s = s.strip_prefix("nostr:").unwrap_or(s);
+    let (hrp, data) = bech32::decode(s).map_err(|e| Nip19Error::Bech32(e.to_string()))?;
+
+    match hrp.as_str() {
+        "npub" => Ok(Nip19::Pubkey(hex32(&data)?)),
+        "nsec" => Ok(Nip19::SecretKey(hex32(&data)?)),
+        "note" => Ok(Nip19::Note(hex32(&data)?)),
+        "nevent" => decode_nevent(&data).map(Nip19::Event),
+        other => Err(Nip19Error::UnsupportedPrefix(other.to_string())),
+    }
+}
+
+fn decode_nevent(mut data: &[u8]) -> Result<EventPointer, Nip19Error> {
+    let mut id = None;
+    let mut pointer = EventPointer::default();
+
+    while !data.is_empty() {
+        let [kind, len, rest @ ..] = data else {
+            return Err(Nip19Error::InvalidTlv);
+        };
+        let len = *len as usize;
+        if rest.len() < len {
+            return Err(Nip19Error::InvalidTlv);
+        }
+        let (value, rest) = rest.split_at(len);
+
+        // Unknown TLV types are skipped, per NIP-19
+        match *kind {
+            TLV_SPECIAL => id = Some(hex32(value)?),
+            TLV_RELAY => {
+                let relay =
+                    String::from_utf8(value.to_vec()).map_err(|_| Nip19Error::InvalidTlv)?;
+                pointer.relays.push(relay);
+            }
+            TLV_AUTHOR => pointer.author = Some(hex32(value)?),
+            TLV_KIND => {
+                let bytes: [u8; 4] = value.try_into().map_err(|_| Nip19Error::InvalidTlv)?;
+                pointer.kind = Some(u32::from_be_bytes(bytes));
+            }
+            _ => {}
+        }
+        data = rest;
+    }
+
+    pointer.id = id.ok_or(Nip19Error::MissingEventId)?;
+    Ok(pointer)
+}
+
+fn encode(hrp: &str, data: &[u8]) -> Result<String, Nip19Error> {
+    let hrp = Hrp::parse(hrp).expect("valid NIP-19 prefix");
+    bech32::encode::<Bech32>(hrp, data).map_err(|e| Nip19Error::Bech32(e.to_string()))
+}
+
+fn push_tlv(data: &mut Vec<u8>, kind: u8, value: &[u8]) -> Result<(), Nip19Error> {
+    let len = u8::try_from(value.len()).map_err(|_| Nip19Error::InvalidTlv)?;
+    data.push(kind);
+    data.push(len);
+    data.extend_from_slice(value);
+    Ok(())
+}
+
+fn bytes32(hex_str: &str) -> Result<Vec<u8>, Nip19Error> {
+    let bytes = hex::decode(hex_str)?;
+    if bytes.len() != 32 {
+        return Err(Nip19Error::InvalidLength(bytes.len()));
+    }
+    Ok(bytes)
+}
+
+fn hex32(bytes: &[u8]) -> Result<String, Nip19Error> {
+    if bytes.len() != 32 {
+        return Err(Nip19Error::InvalidLength(bytes.len()));
+    }
+    Ok(hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors from NIP-19
+    const PUBKEY: &str = "7e7e9c42a91bfef19fa929e5fda1b72e0ebc1a4c1141673e2794234d86addf4e";
+    const NPUB: &str = "npub10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjptg";
+    const SECRET_KEY: &str = "67dea2ed018072d675f5415ecfaed7d2597555e202d85b3d65ea4e58d2d92ffa";
+    const NSEC: &str = "nsec1vl029mgpspedva04g90vltkh6fvh240zqtv9k0t9af8935ke9laqsnlfe5";
+
+    #[test]
+    fn test_keys_match_nip19_vectors() {
+        assert_eq!(encode_npub(PUBKEY).unwrap(), NPUB);
+        assert_eq!(encode_nsec(SECRET_KEY).unwrap(), NSEC);
+        assert_eq!(decode(NPUB).unwrap(), Nip19::Pubkey(PUBKEY.to_string()));
+        assert_eq!(
+            decode(&format!("nostr:{}", NSEC)).unwrap(),
+            Nip19::SecretKey(SECRET_KEY.to_string())
+        );
+
+        let note = encode_note(PUBKEY).unwrap();
+        assert!(note.starts_with("note1"));
+        assert_eq!(decode(&note).unwrap(), Nip19::Note(PUBKEY.to_string()));
+
+        assert!(matches!(
+            encode_npub("abcd"),
+            Err(Nip19Error::InvalidLength(2))
+        ));
+        assert!(matches!(decode("npub1invalid"), Err(Nip19Error::Bech32(_))));
+    }
+
+    #[test]
+    fn test_nevent_round_trip() {
+        let pointer = EventPointer {
+            id: SECRET_KEY.to_string(),
+            relays: vec![
+                "wss://relay.damus.io".to_string(),
+                "wss://nos.lol".to_string(),
+            ],
+            author: Some(PUBKEY.to_string()),
+            kind: Some(1059),
+        };
+        let nevent = encode_nevent(&pointer).unwrap();
+        assert!(nevent.starts_with("nevent1"));
+        assert_eq!(decode(&nevent).unwrap(), Nip19::Event(pointer));
+
+        // An unknown TLV type is ignored, a missing ID is an error
+        let mut data = vec![9, 1, 0];
+        assert!(matches!(
+            decode(&encode("nevent", &data).unwrap()),
+            Err(Nip19Error::MissingEventId)
+        ));
+        push_tlv(&mut data, TLV_SPECIAL, &[1; 32]).unwrap();
+        assert_eq!(
+            decode(&encode("nevent", &data).unwrap()).unwrap(),
+            Nip19::Event(EventPointer {
+                id: "01".repeat(32),
+                ..Default::default()
+            })
+        );
+    }
+}
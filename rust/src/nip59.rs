@@ -0,0 +1,236 @@
+//! NIP-59 seals and gift wraps.
+//!
+//! A crash report rumor (unsigned kind 14 event) is encrypted to the
+//! recipient inside a seal (kind 13) signed by the sender, and the seal is
+//! encrypted again inside a gift wrap (kind 1059) signed by a one-time key.
+//! Both layers use NIP-44 v2 encryption and randomized timestamps.
+//!
+//! [`build_gift_wrap`] composes the two layers; [`build_seal`] is exposed
+//! for callers that customize the outer wrapping. [`rewrap`] wraps a
+//! received rumor again for another recipient, for forwarding.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use nostr::nips::nip44;
+use thiserror::Error;
+
+use crate::event::{
+    public_key, randomized_created_at, EphemeralKeys, EventError, SignedNostrEvent, Tag,
+    UnsignedNostrEvent,
+};
+
+/// Seal event kind.
+pub const KIND_SEAL: u16 = 13;
+
+/// Gift wrap event kind.
+pub const KIND_GIFT_WRAP: u16 = 1059;
+
+/// Seal and gift wrap building errors.
+#[derive(Debug, Error)]
+pub enum Nip59Error {
+    #[error("Event error: {0}")]
+    Event(#[from] EventError),
+
+    #[error("Invalid recipient public key: {0}")]
+    InvalidRecipient(String),
+
+    #[error("Encryption failed: {0}")]
+    Encryption(String),
+}
+
+/// Builds a signed kind 13 seal holding `rumor`, NIP-44 encrypted from
+/// `sender_secret_key` to `recipient_pubkey` (hex).
+///
+/// The rumor's `pubkey` is set to the sender's, as NIP-17 requires, and its
+/// ID is computed; its signature stays empty.
+pub fn build_seal(
+    rumor: &UnsignedNostrEvent,
+    sender_secret_key: &[u8; 32],
+    recipient_pubkey: &str,
+) -> Result<SignedNostrEvent, Nip59Error> {
+    let mut rumor = rumor.clone();
+    rumor.pubkey = public_key(sender_secret_key)?;
+    rumor.sig = String::new();
+
+    let content = encrypt(sender_secret_key, recipient_pubkey, &rumor.to_json())?;
+    let seal =
+        UnsignedNostrEvent::new("", randomized_created_at(now()), KIND_SEAL, vec![], content);
+    Ok(seal.sign(sender_secret_key)?)
+}
+
+/// Wraps `seal` in a kind 1059 gift wrap for `recipient_pubkey` (hex),
+/// encrypted and signed with a freshly generated [`EphemeralKeys`].
+///
+/// With `expiration`, the gift wrap carries a NIP-40 expiration tag so
+/// relays drop it after that time.
+///
+/// # Example
+///
+/// ```
+/// use bugstr::nip59::{build_gift_wrap, build_seal, KIND_GIFT_WRAP};
+/// use bugstr::{EphemeralKeys, UnsignedNostrEvent};
+///
+/// let sender = EphemeralKeys::generate();
+/// let recipient = EphemeralKeys::generate();
+/// let rumor = UnsignedNostrEvent::new("", 1234567890, 14, vec![], "crash report");
+///
+/// let seal = build_seal(&rumor, sender.secret_key(), recipient.public_key()).unwrap();
+/// let gift_wrap = build_gift_wrap(&seal, recipient.public_key(), None).unwrap();
+/// assert_eq!(gift_wrap.kind, KIND_GIFT_WRAP);
+/// ```
+pub fn build_gift_wrap(
+    seal: &SignedNostrEvent,
+    recipient_pubkey: &str,
+    expiration: Option<u64>,
+) -> Result<SignedNostrEvent, Nip59Error> {
+    let keys = EphemeralKeys::generate();
+    let content = encrypt(keys.secret_key(), recipient_pubkey, &seal.to_json())?;
+
+    let mut tags = vec![Tag::pubkey(recipient_pubkey, None).into()];
+    tags.extend(expiration.map(|ts| Tag::expiration(ts).into()));
+
+    let gift_wrap = UnsignedNostrEvent::new(
+        "",
+        randomized_created_at(now()),
+        KIND_GIFT_WRAP,
+        tags,
+        content,
+    );
+    Ok(keys.sign(&gift_wrap)?)
+}
+
+/// Seals and gift wraps a received `rumor` again, from `sender_secret_key`
+/// to `recipient_pubkey` (hex).
+///
+/// The rumor's `p` tags are replaced with the new recipient; its kind, tags,
+/// content and timestamp are otherwise kept. Like every seal, the new one
+/// makes the forwarder the rumor's author.
+pub fn rewrap(
+    rumor: &UnsignedNostrEvent,
+    sender_secret_key: &[u8; 32],
+    recipient_pubkey: &str,
+) -> Result<SignedNostrEvent, Nip59Error> {
+    let mut rumor = rumor.clone();
+    rumor
+        .tags
+        .retain(|tag| tag.first().map(String::as_str) != Some("p"));
+    rumor
+        .tags
+        .insert(0, Tag::pubkey(recipient_pubkey, None).into());
+
+    let seal = build_seal(&rumor, sender_secret_key, recipient_pubkey)?;
+    build_gift_wrap(&seal, recipient_pubkey, None)
+}
+
+/// NIP-44 v2 encrypts `plaintext` from `secret_key` to `recipient_pubkey`.
+pub(crate) fn encrypt(
+    secret_key: &[u8; 32],
+    recipient_pubkey: &str,
+    plaintext: &str,
+) -> Result<String, Nip59Error> {
+    let secret_key =
+        nostr::SecretKey::from_slice(secret_key).map_err(|_| EventError::InvalidSecretKey)?;
+    let recipient = nostr::PublicKey::from_hex(recipient_pubkey)
+        .map_err(|_| Nip59Error::InvalidRecipient(recipient_pubkey.to_string()))?;
+    nip44::encrypt(&secret_key, &recipient, plaintext, nip44::Version::V2)
+        .map_err(|e| Nip59Error::Encryption(e.to_string()))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decrypt(recipient: &EphemeralKeys, event: &SignedNostrEvent) -> String {
+        let secret_key = nostr::SecretKey::from_slice(recipient.secret_key()).unwrap();
+        let sender = nostr::PublicKey::from_hex(&event.pubkey).unwrap();
+        nip44::decrypt(&secret_key, &sender, &event.content).unwrap()
+    }
+
+    #[test]
+    fn test_gift_wrap_round_trip() {
+        let sender = EphemeralKeys::generate();
+        let recipient = EphemeralKeys::generate();
+        let rumor = UnsignedNostrEvent::new(
+            "",
+            1234567890,
+            14,
+            vec![Tag::pubkey(recipient.public_key(), None).into()],
+            "crash report",
+        );
+
+        let seal = build_seal(&rumor, sender.secret_key(), recipient.public_key()).unwrap();
+        assert_eq!(seal.kind, KIND_SEAL);
+        assert_eq!(seal.pubkey, sender.public_key());
+        assert!(seal.tags.is_empty());
+        seal.verify().unwrap();
+
+        let gift_wrap = build_gift_wrap(&seal, recipient.public_key(), Some(42)).unwrap();
+        assert_eq!(gift_wrap.kind, KIND_GIFT_WRAP);
+        assert_ne!(gift_wrap.pubkey, sender.public_key());
+        assert_eq!(
+            gift_wrap.tags,
+            [
+                vec!["p".to_string(), recipient.public_key().to_string()],
+                vec!["expiration".to_string(), "42".to_string()],
+            ]
+        );
+        assert!(gift_wrap.created_at <= now());
+        gift_wrap.verify().unwrap();
+
+        // The recipient unwraps both layers back to the rumor
+        let unwrapped: SignedNostrEvent =
+            serde_json::from_str(&decrypt(&recipient, &gift_wrap)).unwrap();
+        assert_eq!(unwrapped, seal);
+        let unsealed: UnsignedNostrEvent =
+            serde_json::from_str(&decrypt(&recipient, &unwrapped)).unwrap();
+        assert_eq!(unsealed.pubkey, sender.public_key());
+        assert_eq!(unsealed.content, "crash report");
+        assert_eq!(unsealed.sig, "");
+        assert_eq!(unsealed.id, Some(unsealed.compute_id()));
+
+        assert!(matches!(
+            build_seal(&rumor, sender.secret_key(), "not hex"),
+            Err(Nip59Error::InvalidRecipient(_))
+        ));
+    }
+
+    #[test]
+    fn test_rewrap_for_downstream_recipient() {
+        let forwarder = EphemeralKeys::generate();
+        let downstream = EphemeralKeys::generate();
+        let rumor = UnsignedNostrEvent::new(
+            "ab".repeat(32),
+            1234567890,
+            10420,
+            vec![
+                Tag::pubkey(forwarder.public_key(), None).into(),
+                vec!["content-type".to_string(), "application/json".to_string()],
+            ],
+            "crash report",
+        );
+
+        let gift_wrap = rewrap(&rumor, forwarder.secret_key(), downstream.public_key()).unwrap();
+        let seal: SignedNostrEvent =
+            serde_json::from_str(&decrypt(&downstream, &gift_wrap)).unwrap();
+        assert_eq!(seal.pubkey, forwarder.public_key());
+        let unsealed: UnsignedNostrEvent =
+            serde_json::from_str(&decrypt(&downstream, &seal)).unwrap();
+        assert_eq!(unsealed.kind, 10420);
+        assert_eq!(unsealed.created_at, 1234567890);
+        assert_eq!(unsealed.content, "crash report");
+        assert_eq!(
+            unsealed.tags,
+            [
+                vec!["p".to_string(), downstream.public_key().to_string()],
+                vec!["content-type".to_string(), "application/json".to_string()],
+            ]
+        );
+    }
+}
@@ -0,0 +1,312 @@
+//! Crash-rate anomaly detection.
+//!
+//! [`AnomalyDetector`] compares each app's crashes in the last hour with a
+//! rolling baseline of its hourly crash counts (the previous week by
+//! default). An hour well above the baseline, e.g. after a bad deploy,
+//! raises an [`AlertKind::Spike`] alert naming the groups behind it.
+//!
+//! An hour is a spike when it has at least [`min_crashes`] crashes and
+//! exceeds the baseline mean by more than [`z_threshold`] standard
+//! deviations. The deviation is at least the square root of the mean, as
+//! for Poisson-distributed counts, so quiet apps with a flat baseline don't
+//! alert on a handful of crashes.
+//!
+//! [`min_crashes`]: AnomalyDetector::with_min_crashes
+//! [`z_threshold`]: AnomalyDetector::with_z_threshold
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{Alert, AlertKind};
+use crate::storage::CrashStorage;
+
+/// Hours of history the baseline covers by default (one week).
+pub const DEFAULT_BASELINE_HOURS: i64 = 7 * 24;
+
+/// Groups listed in a spike alert.
+const MAX_SPIKE_GROUPS: usize = 5;
+
+/// A crash-rate spike of one app.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Spike {
+    /// App that spiked, or `None` for crashes without an app name.
+    pub app_name: Option<String>,
+    /// Crashes in the last hour.
+    pub crashes: i64,
+    /// Mean crashes per hour in the baseline.
+    pub baseline: f64,
+    /// Standard deviation of the baseline's hourly counts.
+    pub stddev: f64,
+    /// Groups with crashes in the last hour, most crashes first.
+    pub groups: Vec<SpikeGroup>,
+}
+
+/// A crash group's share of a [`Spike`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SpikeGroup {
+    pub group_key: String,
+    pub exception_type: String,
+    /// Crashes in the last hour.
+    pub count: i64,
+}
+
+/// Finds crash-rate spikes per app.
+#[derive(Debug, Clone)]
+pub struct AnomalyDetector {
+    baseline_hours: i64,
+    min_baseline_hours: i64,
+    min_crashes: i64,
+    z_threshold: f64,
+    cooldown_minutes: i64,
+    /// When each app last spiked
+    last_spike: HashMap<Option<String>, i64>,
+}
+
+impl Default for AnomalyDetector {
+    fn default() -> Self {
+        Self {
+            baseline_hours: DEFAULT_BASELINE_HOURS,
+            min_baseline_hours: 24,
+            min_crashes: 10,
+            z_threshold: 3.0,
+            cooldown_minutes: 60,
+            last_spike: HashMap::new(),
+        }
+    }
+}
+
+impl AnomalyDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hours of history the baseline covers.
+    pub fn with_baseline_hours(mut self, hours: i64) -> Self {
+        self.baseline_hours = hours.max(1);
+        self
+    }
+
+    /// Hours of history an app needs before it can spike, so a new app's
+    /// first crashes don't alert.
+    pub fn with_min_baseline_hours(mut self, hours: i64) -> Self {
+        self.min_baseline_hours = hours.max(1);
+        self
+    }
+
+    /// Fewest crashes in an hour that count as a spike.
+    pub fn with_min_crashes(mut self, crashes: i64) -> Self {
+        self.min_crashes = crashes;
+        self
+    }
+
+    /// Standard deviations above the baseline mean that count as a spike.
+    pub fn with_z_threshold(mut self, z: f64) -> Self {
+        self.z_threshold = z;
+        self
+    }
+
+    /// Minimum time between spikes of the same app.
+    pub fn with_cooldown_minutes(mut self, minutes: i64) -> Self {
+        self.cooldown_minutes = minutes;
+        self
+    }
+
+    /// Finds apps whose last hour before `now` is a spike, leaving out
+    /// apps still cooling down from an earlier one.
+    pub fn detect(&mut self, storage: &CrashStorage, now: i64) -> rusqlite::Result<Vec<Spike>> {
+        let hour_start = now - 3600;
+        let since = hour_start - self.baseline_hours * 3600;
+
+        // Hourly counts per app; the last hour is the one being checked
+        let mut apps: HashMap<Option<String>, Vec<(i64, i64)>> = HashMap::new();
+        for (app_name, hour, count) in storage.hourly_app_counts(since, now)? {
+            apps.entry(app_name).or_default().push((hour, count));
+        }
+
+        let mut spikes = Vec::new();
+        for (app_name, hours) in apps {
+            let groups = storage.app_group_counts_since(app_name.as_deref(), hour_start)?;
+            let crashes = groups.iter().map(|(_, _, count, _)| count).sum::<i64>();
+            if crashes < self.min_crashes {
+                continue;
+            }
+
+            // The baseline starts at the app's first crash in the window
+            let Some(&(first_hour, _)) = hours.first() else {
+                continue;
+            };
+            if self.baseline_hours - first_hour < self.min_baseline_hours {
+                continue;
+            }
+            let mut baseline = vec![0; (self.baseline_hours - first_hour) as usize];
+            for &(hour, count) in hours.iter().filter(|(hour, _)| *hour < self.baseline_hours) {
+                baseline[(hour - first_hour) as usize] = count;
+            }
+            let (mean, stddev) = mean_stddev(&baseline);
+            if (crashes as f64) <= mean + self.z_threshold * stddev.max(mean.sqrt()).max(1.0) {
+                continue;
+            }
+
+            let cooling_down = self
+                .last_spike
+                .get(&app_name)
+                .is_some_and(|at| now - at < self.cooldown_minutes * 60);
+            if cooling_down {
+                continue;
+            }
+            self.last_spike.insert(app_name.clone(), now);
+
+            let groups = groups
+                .into_iter()
+                .take(MAX_SPIKE_GROUPS)
+                .map(|(group_key, exception_type, count, _)| SpikeGroup {
+                    group_key,
+                    exception_type,
+                    count,
+                })
+                .collect();
+            spikes.push(Spike {
+                app_name,
+                crashes,
+                baseline: mean,
+                stddev,
+                groups,
+            });
+        }
+        spikes.sort_by(|a, b| a.app_name.cmp(&b.app_name));
+        Ok(spikes)
+    }
+}
+
+/// The alert for `spike`, about its latest crash in the group with the most
+/// crashes. `since` is the start of the spike's hour.
+pub fn spike_alert(
+    storage: &CrashStorage,
+    spike: Spike,
+    since: i64,
+) -> rusqlite::Result<Option<Alert>> {
+    let Some((group_key, _, _, crash_id)) = storage
+        .app_group_counts_since(spike.app_name.as_deref(), since)?
+        .into_iter()
+        .next()
+    else {
+        return Ok(None);
+    };
+    let Some(crash) = storage.get_by_id(crash_id)? else {
+        return Ok(None);
+    };
+    Ok(Some(Alert {
+        kind: AlertKind::Spike,
+        group_key,
+        count: spike.crashes,
+        symbolicated_stack: storage.get_symbolicated_stack(crash.id)?,
+        crash,
+        spike: Some(spike),
+    }))
+}
+
+fn mean_stddev(counts: &[i64]) -> (f64, f64) {
+    let n = counts.len() as f64;
+    let mean = counts.iter().sum::<i64>() as f64 / n;
+    let variance = counts
+        .iter()
+        .map(|&c| (c as f64 - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    (mean, variance.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grouping::fingerprint;
+    use crate::storage::CrashReport;
+
+    const NOW: i64 = 1_000 * 3600;
+
+    fn insert(storage: &CrashStorage, n: i64, received_at: i64, exception: &str) {
+        let mut report = CrashReport::from_content(
+            format!("event{}-{}", received_at, n),
+            "sender".to_string(),
+            received_at,
+            received_at,
+            serde_json::json!({ "stack": format!("{}: boom", exception) }).to_string(),
+        );
+        report.app_name = Some("com.example".to_string());
+        storage.insert(&report).unwrap();
+    }
+
+    #[test]
+    fn test_detects_spike_over_baseline() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        // Two crashes an hour for two days
+        for hour in 2..50 {
+            for n in 0..2 {
+                insert(
+                    &storage,
+                    n,
+                    NOW - hour * 3600,
+                    "java.lang.IllegalStateException",
+                );
+            }
+        }
+        let mut detector = AnomalyDetector::new();
+        assert!(detector.detect(&storage, NOW).unwrap().is_empty());
+
+        for n in 0..15 {
+            insert(&storage, n, NOW - 600, "java.lang.NullPointerException");
+        }
+        insert(&storage, 15, NOW - 600, "java.lang.IllegalStateException");
+        let spikes = detector.detect(&storage, NOW).unwrap();
+        assert_eq!(spikes.len(), 1);
+        assert_eq!(spikes[0].app_name.as_deref(), Some("com.example"));
+        assert_eq!(spikes[0].crashes, 16);
+        assert_eq!(spikes[0].baseline, 2.0);
+        assert_eq!(
+            spikes[0].groups[0],
+            SpikeGroup {
+                group_key: fingerprint(Some("NullPointerException"), None),
+                exception_type: "NullPointerException".to_string(),
+                count: 15
+            }
+        );
+
+        // Cooling down
+        assert!(detector.detect(&storage, NOW + 60).unwrap().is_empty());
+
+        let alert = spike_alert(&storage, spikes[0].clone(), NOW - 3600)
+            .unwrap()
+            .unwrap();
+        assert_eq!(alert.kind, AlertKind::Spike);
+        assert_eq!(alert.group_key, spikes[0].groups[0].group_key);
+        assert_eq!(alert.exception_type(), "NullPointerException");
+        assert_eq!(alert.count, 16);
+    }
+
+    #[test]
+    fn test_new_app_needs_history() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        insert(
+            &storage,
+            0,
+            NOW - 5 * 3600,
+            "java.lang.IllegalStateException",
+        );
+        for n in 0..50 {
+            insert(&storage, n, NOW - 600, "java.lang.NullPointerException");
+        }
+        assert!(AnomalyDetector::new()
+            .detect(&storage, NOW)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            AnomalyDetector::new()
+                .with_min_baseline_hours(4)
+                .detect(&storage, NOW)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
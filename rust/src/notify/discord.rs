@@ -0,0 +1,171 @@
+//! Discord notifications.
+//!
+//! Posts an embed to a Discord channel webhook for new crash groups and
+//! regressions, with the exception, the top in-app frame, version and
+//! crash count. With a dashboard URL, the embed title links to it.
+
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use super::{send_with_retry, Alert, AlertKind, Notifier, NotifyError};
+
+/// Embed colour of new crash group alerts (orange).
+const NEW_GROUP_COLOR: u32 = 0xE67E22;
+
+/// Embed colour of regression alerts (red).
+const REGRESSION_COLOR: u32 = 0xE74C3C;
+
+/// Posts alerts to a Discord webhook.
+pub struct Discord {
+    webhook_url: String,
+    dashboard_url: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+impl Discord {
+    /// Creates a notifier posting to the channel webhook `webhook_url`.
+    pub fn new(webhook_url: impl Into<String>) -> Self {
+        Self {
+            webhook_url: webhook_url.into(),
+            dashboard_url: None,
+            client: reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("HTTP client"),
+        }
+    }
+
+    /// Links embeds to the dashboard at `url`.
+    pub fn with_dashboard_url(mut self, url: impl Into<String>) -> Self {
+        self.dashboard_url = Some(url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// The webhook message for `alert`, one embed.
+    pub fn message(&self, alert: &Alert) -> Value {
+        let crash = &alert.crash;
+        let mut description = String::new();
+        if let Some(message) = crash.message.as_deref().filter(|m| !m.is_empty()) {
+            description.push_str(message);
+        }
+        if let Some(frame) = alert.top_in_app_frame() {
+            if !description.is_empty() {
+                description.push('\n');
+            }
+            description.push_str(&format!("`{}`", frame.replace('`', "'")));
+        }
+
+        let mut fields = vec![
+            ("App", crash.app_name.clone()),
+            ("Version", crash.app_version.clone()),
+            ("Crashes", Some(alert.count.to_string())),
+            ("Environment", crash.environment.clone()),
+        ];
+        fields.retain(|(_, value)| value.as_deref().is_some_and(|v| !v.is_empty()));
+
+        let mut embed = json!({
+            "title": truncate(&alert.summary(), 256),
+            "description": truncate(&description, 4096),
+            "color": if alert.kind == AlertKind::Regression {
+                REGRESSION_COLOR
+            } else {
+                NEW_GROUP_COLOR
+            },
+            "fields": fields
+                .into_iter()
+                .map(|(name, value)| json!({
+                    "name": name,
+                    "value": truncate(&value.unwrap_or_default(), 1024),
+                    "inline": true,
+                }))
+                .collect::<Vec<_>>(),
+            "footer": { "text": format!("Crash {}", crash.id) },
+        });
+        if let Some(timestamp) = chrono::DateTime::from_timestamp(crash.created_at, 0) {
+            embed["timestamp"] = json!(timestamp.to_rfc3339());
+        }
+        if let Some(dashboard) = &self.dashboard_url {
+            embed["url"] = json!(dashboard);
+        }
+        json!({
+            "embeds": [embed],
+            // Crash text must not ping anyone
+            "allowed_mentions": { "parse": [] },
+        })
+    }
+
+    fn send(&self, message: &Value) -> Result<(), NotifyError> {
+        let response = self.client.post(&self.webhook_url).json(message).send()?;
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                status: response.status().as_u16(),
+                body: response.text().unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Notifier for Discord {
+    fn name(&self) -> &str {
+        "discord"
+    }
+
+    fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        if !matches!(alert.kind, AlertKind::NewGroup | AlertKind::Regression) {
+            return Ok(());
+        }
+        let message = self.message(alert);
+        send_with_retry(3, Duration::from_secs(1), || self.send(&message))
+    }
+}
+
+/// Shortens `s` to at most `max` characters, as Discord requires.
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let mut out: String = s.chars().take(max - 1).collect();
+    out.push('…');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::test_server::serve_once;
+    use crate::notify::{test_alert, test_crash};
+
+    #[test]
+    fn test_posts_embed() {
+        let mut alert = test_alert(AlertKind::Regression, 4);
+        alert.crash = test_crash(
+            r#"{"message": "boom @everyone", "stack": "java.lang.IllegalStateException: boom\n\tat com.example.app.Cart.total(Cart.kt:12)"}"#,
+        );
+        alert.crash.id = 9;
+        alert.crash.app_version = Some("1.2.0".to_string());
+
+        let (url, server) = serve_once(204, "");
+        Discord::new(format!("{}/api/webhooks/1/token", url))
+            .with_dashboard_url("https://bugstr.example.com")
+            .notify(&alert)
+            .unwrap();
+
+        let request = server.join().unwrap();
+        assert_eq!(request.request_line, "POST /api/webhooks/1/token HTTP/1.1");
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        let embed = &body["embeds"][0];
+        assert_eq!(embed["title"], alert.summary());
+        assert_eq!(
+            embed["description"],
+            "boom @everyone\n`com.example.app.Cart.total(Cart.kt:12)`"
+        );
+        assert_eq!(embed["color"], REGRESSION_COLOR);
+        assert_eq!(embed["fields"][0]["name"], "Version");
+        assert_eq!(embed["fields"][1]["value"], "4");
+        assert_eq!(embed["url"], "https://bugstr.example.com");
+        assert_eq!(embed["footer"]["text"], "Crash 9");
+        assert_eq!(body["allowed_mentions"]["parse"], json!([]));
+    }
+}
@@ -0,0 +1,287 @@
+//! Email alerts over SMTP.
+//!
+//! Sends a summary email for new crash groups, groups reaching a
+//! threshold and crash-rate spikes, as plain text with an HTML alternative. The body lists the
+//! crash details and the top frames of the stack, symbolicated when
+//! available.
+
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use serde::{Deserialize, Serialize};
+
+use super::{Alert, AlertKind, Notifier, NotifyError};
+
+/// Number of stack frames included in an email.
+pub const TOP_FRAMES: usize = 10;
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Upgrade a plain connection with STARTTLS (port 587).
+    #[default]
+    Starttls,
+    /// TLS from the start (port 465).
+    Tls,
+    /// No encryption (port 25). Only for local relays.
+    None,
+}
+
+impl SmtpSecurity {
+    /// Name as used in the CLI and stored settings.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SmtpSecurity::Starttls => "starttls",
+            SmtpSecurity::Tls => "tls",
+            SmtpSecurity::None => "none",
+        }
+    }
+
+    /// Parse a security name. Returns `None` for unknown values.
+    pub fn parse(s: &str) -> Option<Self> {
+        [
+            SmtpSecurity::Starttls,
+            SmtpSecurity::Tls,
+            SmtpSecurity::None,
+        ]
+        .into_iter()
+        .find(|security| security.as_str() == s)
+    }
+}
+
+/// Emails alerts to a list of recipients.
+pub struct Email {
+    host: String,
+    port: Option<u16>,
+    security: SmtpSecurity,
+    credentials: Option<(String, String)>,
+    from: String,
+    to: Vec<String>,
+}
+
+impl Email {
+    /// Creates a notifier sending from `from` to each address in `to`
+    /// through the SMTP server at `host`, using STARTTLS on its default port.
+    pub fn new(host: impl Into<String>, from: impl Into<String>, to: Vec<String>) -> Self {
+        Self {
+            host: host.into(),
+            port: None,
+            security: SmtpSecurity::default(),
+            credentials: None,
+            from: from.into(),
+            to,
+        }
+    }
+
+    /// Connects on `port` instead of the security mode's default.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Secures the connection with `security`.
+    pub fn with_security(mut self, security: SmtpSecurity) -> Self {
+        self.security = security;
+        self
+    }
+
+    /// Authenticates with `username` and `password`.
+    pub fn with_credentials(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.credentials = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Builds the email for `alert`.
+    pub fn message(&self, alert: &Alert) -> Result<Message, NotifyError> {
+        let mut builder = Message::builder()
+            .from(mailbox(&self.from)?)
+            .subject(alert.summary());
+        for to in &self.to {
+            builder = builder.to(mailbox(to)?);
+        }
+        builder
+            .multipart(MultiPart::alternative_plain_html(
+                plain_body(alert),
+                html_body(alert),
+            ))
+            .map_err(|e| NotifyError::Email(e.to_string()))
+    }
+
+    fn transport(&self) -> Result<SmtpTransport, NotifyError> {
+        let builder = match self.security {
+            SmtpSecurity::Starttls => SmtpTransport::starttls_relay(&self.host),
+            SmtpSecurity::Tls => SmtpTransport::relay(&self.host),
+            SmtpSecurity::None => Ok(SmtpTransport::builder_dangerous(&self.host)),
+        };
+        let mut builder = builder.map_err(|e| NotifyError::Email(e.to_string()))?;
+        if let Some(port) = self.port {
+            builder = builder.port(port);
+        }
+        if let Some((username, password)) = &self.credentials {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        Ok(builder.build())
+    }
+}
+
+impl Notifier for Email {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        if !matches!(
+            alert.kind,
+            AlertKind::NewGroup | AlertKind::Threshold | AlertKind::Spike
+        ) {
+            return Ok(());
+        }
+        let message = self.message(alert)?;
+        self.transport()?
+            .send(&message)
+            .map_err(|e| NotifyError::Email(e.to_string()))?;
+        Ok(())
+    }
+}
+
+fn mailbox(address: &str) -> Result<Mailbox, NotifyError> {
+    address
+        .parse()
+        .map_err(|e| NotifyError::Email(format!("invalid address {}: {}", address, e)))
+}
+
+/// Crash details shown in both bodies, as label/value pairs.
+fn details(alert: &Alert) -> Vec<(&'static str, String)> {
+    let crash = &alert.crash;
+    let mut details = vec![
+        ("Exception", alert.exception_type().to_string()),
+        ("Group", alert.group_key.clone()),
+        ("Crashes", alert.count.to_string()),
+        ("Crash ID", crash.id.to_string()),
+    ];
+    let optional = [
+        ("App", &crash.app_name),
+        ("Version", &crash.app_version),
+        ("Environment", &crash.environment),
+        ("Message", &crash.message),
+    ];
+    for (label, value) in optional {
+        if let Some(value) = value.as_ref().filter(|v| !v.is_empty()) {
+            details.push((label, value.clone()));
+        }
+    }
+    if let Some(spike) = &alert.spike {
+        let groups: Vec<_> = spike
+            .groups
+            .iter()
+            .map(|group| format!("{} ({})", group.exception_type, group.count))
+            .collect();
+        details.push(("Groups", groups.join(", ")));
+    }
+    details
+}
+
+/// The first [`TOP_FRAMES`] non-empty lines of the stack, symbolicated when
+/// available.
+fn top_frames(alert: &Alert) -> Vec<&str> {
+    alert
+        .symbolicated_stack
+        .as_deref()
+        .or(alert.crash.stack_trace.as_deref())
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .take(TOP_FRAMES)
+        .collect()
+}
+
+fn plain_body(alert: &Alert) -> String {
+    let mut out = format!("{}\n\n", alert.summary());
+    for (label, value) in details(alert) {
+        out.push_str(&format!("{}: {}\n", label, value));
+    }
+    let frames = top_frames(alert);
+    if !frames.is_empty() {
+        out.push_str("\nTop frames:\n");
+        for frame in frames {
+            out.push_str(&format!("  {}\n", frame));
+        }
+    }
+    out
+}
+
+fn html_body(alert: &Alert) -> String {
+    let mut out = format!("<h2>{}</h2>\n<table>\n", escape(&alert.summary()));
+    for (label, value) in details(alert) {
+        out.push_str(&format!(
+            "<tr><th align=\"left\">{}</th><td>{}</td></tr>\n",
+            label,
+            escape(&value)
+        ));
+    }
+    out.push_str("</table>\n");
+    let frames = top_frames(alert);
+    if !frames.is_empty() {
+        out.push_str("<h3>Top frames</h3>\n<pre>");
+        out.push_str(&escape(&frames.join("\n")));
+        out.push_str("</pre>\n");
+    }
+    out
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::{test_alert, test_crash};
+
+    #[test]
+    fn test_message() {
+        let stack = (0..15)
+            .map(|i| format!("    at com.example.Foo.bar{}(Foo.kt:{})", i, i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut alert = test_alert(AlertKind::Threshold, 100);
+        alert.crash = test_crash(
+            r#"{"message": "boom <here>", "stack": "java.lang.IllegalStateException: boom"}"#,
+        );
+        alert.crash.id = 7;
+        alert.symbolicated_stack = Some(stack);
+
+        let email = Email::new(
+            "smtp.example.com",
+            "bugstr@example.com",
+            vec![
+                "a@example.com".to_string(),
+                "Dev <b@example.com>".to_string(),
+            ],
+        );
+        let message = String::from_utf8(email.message(&alert).unwrap().formatted()).unwrap();
+        assert!(message.contains("To: a@example.com, Dev <b@example.com>"));
+        assert!(message.contains("Subject: Crash group threshold reached"));
+        assert!(message.contains("Content-Type: text/plain"));
+        assert!(message.contains("Content-Type: text/html"));
+        assert!(message.contains("Message: boom <here>"));
+        assert!(message.contains("<td>boom &lt;here&gt;</td>"));
+        assert!(message.contains("Foo.bar9(Foo.kt:9)"));
+        assert!(!message.contains("Foo.bar10("));
+
+        let invalid = Email::new("smtp.example.com", "not an address", vec![]);
+        assert!(matches!(
+            invalid.message(&alert),
+            Err(NotifyError::Email(_))
+        ));
+    }
+}
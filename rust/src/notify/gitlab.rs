@@ -0,0 +1,162 @@
+//! GitLab issue integration.
+//!
+//! Opens an issue for each new crash group through the GitLab REST API
+//! (`POST /api/v4/projects/:id/issues`), on gitlab.com or a self-hosted
+//! instance. The issue body is the markdown report of `bugstr issue`.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::{Alert, AlertKind, Notifier, NotifyError};
+use crate::issue::{format_issue, issue_title};
+
+/// Creates GitLab issues for new crash groups.
+pub struct GitLab {
+    url: String,
+    project: String,
+    token: String,
+    labels: Vec<String>,
+    client: reqwest::blocking::Client,
+}
+
+/// The fields of a created issue that bugstr reports.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreatedIssue {
+    /// Issue number within the project.
+    pub iid: u64,
+    /// Link to the issue.
+    pub web_url: String,
+}
+
+impl GitLab {
+    /// Creates an integration for `project` (numeric ID or `group/name`
+    /// path) on the instance at `url`, authenticating with `token`.
+    pub fn new(
+        url: impl Into<String>,
+        project: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            project: project.into(),
+            token: token.into(),
+            labels: Vec::new(),
+            client: reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("HTTP client"),
+        }
+    }
+
+    /// Adds these labels to every created issue.
+    pub fn with_labels(mut self, labels: Vec<String>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Opens an issue and returns its number and URL.
+    pub fn create_issue(
+        &self,
+        title: &str,
+        description: &str,
+    ) -> Result<CreatedIssue, NotifyError> {
+        let mut body = serde_json::json!({
+            "title": title,
+            "description": description,
+        });
+        if !self.labels.is_empty() {
+            body["labels"] = self.labels.join(",").into();
+        }
+
+        let response = self
+            .client
+            .post(self.issues_url())
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&body)
+            .send()?;
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                status: response.status().as_u16(),
+                body: response.text().unwrap_or_default(),
+            });
+        }
+        Ok(response.json()?)
+    }
+
+    /// Issues endpoint of the project. Paths are URL-encoded as GitLab
+    /// requires (`group/name` → `group%2Fname`).
+    fn issues_url(&self) -> String {
+        format!(
+            "{}/api/v4/projects/{}/issues",
+            self.url,
+            self.project.replace('/', "%2F")
+        )
+    }
+}
+
+impl Notifier for GitLab {
+    fn name(&self) -> &str {
+        "gitlab"
+    }
+
+    fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        self.deliver(alert).map(|_| ())
+    }
+
+    fn deliver(&self, alert: &Alert) -> Result<Option<String>, NotifyError> {
+        if alert.kind != AlertKind::NewGroup {
+            return Ok(None);
+        }
+        let description = format_issue(&alert.crash, alert.symbolicated_stack.as_deref());
+        let issue = self.create_issue(&issue_title(&alert.crash), &description)?;
+        Ok(Some(issue.web_url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::test_alert;
+    use crate::notify::test_server::serve_once;
+
+    fn alert() -> Alert {
+        let mut alert = test_alert(AlertKind::NewGroup, 1);
+        alert.crash.id = 7;
+        alert
+    }
+
+    #[test]
+    fn test_creates_issue_on_self_hosted_instance() {
+        let (url, server) = serve_once(201, r#"{"iid": 12, "web_url": "https://x/12"}"#);
+        let gitlab = GitLab::new(format!("{}/", url), "team/app", "glpat-secret")
+            .with_labels(vec!["crash".to_string(), "bugstr".to_string()]);
+
+        gitlab.notify(&alert()).unwrap();
+
+        let request = server.join().unwrap();
+        assert_eq!(
+            request.request_line,
+            "POST /api/v4/projects/team%2Fapp/issues HTTP/1.1"
+        );
+        assert_eq!(request.header("private-token"), Some("glpat-secret"));
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["title"], issue_title(&alert().crash));
+        assert!(body["description"]
+            .as_str()
+            .unwrap()
+            .contains("| Crash ID | 7 |"));
+        assert_eq!(body["labels"], "crash,bugstr");
+    }
+
+    #[test]
+    fn test_error_status() {
+        let (url, server) = serve_once(401, r#"{"message": "401 Unauthorized"}"#);
+        let result = GitLab::new(url, "42", "bad").create_issue("title", "body");
+        server.join().unwrap();
+        assert!(matches!(
+            result,
+            Err(NotifyError::Status { status: 401, .. })
+        ));
+    }
+}
@@ -0,0 +1,293 @@
+//! Jira issue integration.
+//!
+//! Opens an issue for each new crash group through the Jira REST API
+//! (`POST /rest/api/2/issue`), on Jira Cloud or Data Center. The
+//! description is the `bugstr issue` report converted to wiki markup.
+//!
+//! With status sync enabled, [`Jira::closed_groups`] checks the issues of
+//! groups that are still open, so the receiver can resolve a group once
+//! its issue is closed. The issue key is kept as the
+//! [`Delivery::reference`](super::Delivery::reference) of the delivery
+//! that created it.
+
+use std::time::Duration;
+
+use chrono::DateTime;
+use serde::Deserialize;
+
+use super::{Alert, AlertKind, Notifier, NotifyError};
+use crate::issue::{format_issue, issue_title};
+use crate::storage::IssueLink;
+
+/// Issue type used when none is configured.
+pub const DEFAULT_ISSUE_TYPE: &str = "Bug";
+
+/// Creates Jira issues for new crash groups.
+pub struct Jira {
+    url: String,
+    project: String,
+    token: String,
+    email: Option<String>,
+    issue_type: String,
+    labels: Vec<String>,
+    client: reqwest::blocking::Client,
+}
+
+/// The fields of a created issue that bugstr keeps.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreatedJiraIssue {
+    pub id: String,
+    /// Issue key, e.g. `APP-12`.
+    pub key: String,
+}
+
+impl Jira {
+    /// Creates an integration for the project with key `project` on the
+    /// site at `url`. Without [`with_email`](Self::with_email), `token` is
+    /// sent as a personal access token (Jira Data Center).
+    pub fn new(
+        url: impl Into<String>,
+        project: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            project: project.into(),
+            token: token.into(),
+            email: None,
+            issue_type: DEFAULT_ISSUE_TYPE.to_string(),
+            labels: Vec::new(),
+            client: reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("HTTP client"),
+        }
+    }
+
+    /// Authenticates as `email` with an API token, as Jira Cloud requires.
+    pub fn with_email(mut self, email: impl Into<String>) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    /// Creates issues of this type instead of [`DEFAULT_ISSUE_TYPE`].
+    pub fn with_issue_type(mut self, issue_type: impl Into<String>) -> Self {
+        self.issue_type = issue_type.into();
+        self
+    }
+
+    /// Adds these labels to every created issue.
+    pub fn with_labels(mut self, labels: Vec<String>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Opens an issue and returns its key.
+    pub fn create_issue(
+        &self,
+        summary: &str,
+        description: &str,
+    ) -> Result<CreatedJiraIssue, NotifyError> {
+        let body = serde_json::json!({
+            "fields": {
+                "project": { "key": self.project },
+                "summary": summary,
+                "description": description,
+                "issuetype": { "name": self.issue_type },
+                "labels": self.labels,
+            }
+        });
+        let response = self
+            .request(reqwest::Method::POST, "/rest/api/2/issue")
+            .json(&body)
+            .send()?;
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                status: response.status().as_u16(),
+                body: response.text().unwrap_or_default(),
+            });
+        }
+        Ok(response.json()?)
+    }
+
+    /// When the issue was closed, or `None` while it is open. Closed means
+    /// its status is in the Done category; the time is its resolution date,
+    /// or 0 when it has none.
+    pub fn closed_at(&self, key: &str) -> Result<Option<i64>, NotifyError> {
+        let path = format!("/rest/api/2/issue/{}?fields=status,resolutiondate", key);
+        let response = self.request(reqwest::Method::GET, &path).send()?;
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                status: response.status().as_u16(),
+                body: response.text().unwrap_or_default(),
+            });
+        }
+        let issue: serde_json::Value = response.json()?;
+        let category = issue.pointer("/fields/status/statusCategory/key");
+        if category.and_then(|v| v.as_str()) != Some("done") {
+            return Ok(None);
+        }
+        let resolved_at = issue
+            .pointer("/fields/resolutiondate")
+            .and_then(|v| v.as_str())
+            .and_then(|s| DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f%z").ok())
+            .map(|dt| dt.timestamp());
+        Ok(Some(resolved_at.unwrap_or(0)))
+    }
+
+    /// The links whose issue was closed after the group's status last
+    /// changed, so a group reopened by hand isn't resolved again.
+    pub fn closed_groups<'a>(
+        &self,
+        links: &'a [IssueLink],
+    ) -> Result<Vec<&'a IssueLink>, NotifyError> {
+        let mut closed = Vec::new();
+        for link in links {
+            let Some(closed_at) = self.closed_at(&link.reference)? else {
+                continue;
+            };
+            if link.status_updated_at.is_none_or(|at| closed_at > at) {
+                closed.push(link);
+            }
+        }
+        Ok(closed)
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::blocking::RequestBuilder {
+        let request = self.client.request(method, format!("{}{}", self.url, path));
+        match &self.email {
+            Some(email) => request.basic_auth(email, Some(&self.token)),
+            None => request.bearer_auth(&self.token),
+        }
+    }
+}
+
+impl Notifier for Jira {
+    fn name(&self) -> &str {
+        "jira"
+    }
+
+    fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        self.deliver(alert).map(|_| ())
+    }
+
+    fn deliver(&self, alert: &Alert) -> Result<Option<String>, NotifyError> {
+        if alert.kind != AlertKind::NewGroup {
+            return Ok(None);
+        }
+        let report = format_issue(&alert.crash, alert.symbolicated_stack.as_deref());
+        let issue = self.create_issue(&issue_title(&alert.crash), &to_wiki(&report))?;
+        Ok(Some(issue.key))
+    }
+}
+
+/// Converts the markdown of [`format_issue`] to Jira wiki markup: headings,
+/// tables, lists and code blocks.
+fn to_wiki(markdown: &str) -> String {
+    let mut lines = Vec::new();
+    // Closing fence of the open code block
+    let mut fence: Option<&str> = None;
+    let mut table_header = true;
+    for line in markdown.lines() {
+        if let Some(open) = fence {
+            if line == open {
+                fence = None;
+                lines.push("{noformat}".to_string());
+            } else {
+                lines.push(line.to_string());
+            }
+        } else if line.starts_with("```") {
+            fence = Some(line.trim_end_matches(|c| c != '`'));
+            lines.push("{noformat}".to_string());
+        } else if let Some(heading) = line.strip_prefix("### ") {
+            lines.push(format!("h3. {}", heading));
+        } else if let Some(heading) = line.strip_prefix("## ") {
+            lines.push(format!("h2. {}", heading));
+        } else if line.starts_with("|---") {
+            continue;
+        } else if line.starts_with('|') {
+            // The first row of a table is its header
+            lines.push(if table_header {
+                line.replace('|', "||")
+            } else {
+                line.to_string()
+            });
+            table_header = false;
+        } else if let Some(item) = line.strip_prefix("- ") {
+            lines.push(format!("* {}", item));
+        } else {
+            table_header = true;
+            lines.push(line.to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::test_alert;
+    use crate::notify::test_server::serve_once;
+
+    #[test]
+    fn test_creates_issue() {
+        let alert = test_alert(AlertKind::NewGroup, 1);
+
+        let (url, server) = serve_once(201, r#"{"id": "10001", "key": "APP-12"}"#);
+        let reference = Jira::new(url, "APP", "api-token")
+            .with_email("dev@example.com")
+            .with_labels(vec!["crash".to_string()])
+            .deliver(&alert)
+            .unwrap();
+        assert_eq!(reference.as_deref(), Some("APP-12"));
+
+        let request = server.join().unwrap();
+        assert_eq!(request.request_line, "POST /rest/api/2/issue HTTP/1.1");
+        assert!(request
+            .header("authorization")
+            .unwrap()
+            .starts_with("Basic "));
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["fields"]["project"]["key"], "APP");
+        assert_eq!(body["fields"]["issuetype"]["name"], "Bug");
+        assert_eq!(body["fields"]["labels"][0], "crash");
+        let description = body["fields"]["description"].as_str().unwrap();
+        assert!(description.starts_with("h2. "));
+        assert!(description.contains("|| Field || Value ||"));
+        assert!(description.contains("{noformat}\nboom\n{noformat}"));
+    }
+
+    #[test]
+    fn test_closed_groups_respects_reopening() {
+        let closed = |status_updated_at| {
+            let (url, server) = serve_once(
+                200,
+                r#"{"fields": {"status": {"statusCategory": {"key": "done"}},
+                    "resolutiondate": "2024-01-02T03:04:05.000+0000"}}"#,
+            );
+            let links = [IssueLink {
+                integration_id: 1,
+                group_key: "IllegalStateException".to_string(),
+                reference: "APP-12".to_string(),
+                status_updated_at,
+            }];
+            let closed = Jira::new(url, "APP", "pat")
+                .closed_groups(&links)
+                .unwrap()
+                .len();
+            (closed, server.join().unwrap())
+        };
+
+        let (count, request) = closed(None);
+        assert_eq!(count, 1);
+        assert_eq!(
+            request.request_line,
+            "GET /rest/api/2/issue/APP-12?fields=status,resolutiondate HTTP/1.1"
+        );
+        assert_eq!(request.header("authorization"), Some("Bearer pat"));
+
+        // Reopened by hand after the issue was closed
+        assert_eq!(closed(Some(1704164645 + 60)).0, 0);
+        assert_eq!(closed(Some(1704164645 - 60)).0, 1);
+    }
+}
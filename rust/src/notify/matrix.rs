@@ -0,0 +1,159 @@
+//! Matrix room notifications.
+//!
+//! Posts an `m.room.message` to a room through the client-server API
+//! (`PUT /_matrix/client/v3/rooms/:room/send/m.room.message/:txn`) for new
+//! crash groups, regressions and thresholds. The access token's user must
+//! have joined the room. Retries reuse the transaction ID, so the
+//! homeserver drops duplicates.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{send_with_retry, Alert, AlertKind, Notifier, NotifyError};
+
+/// Posts alerts to a Matrix room.
+pub struct Matrix {
+    homeserver: String,
+    token: String,
+    room_id: String,
+    client: reqwest::blocking::Client,
+}
+
+impl Matrix {
+    /// Creates a notifier posting to `room_id` (e.g. `!abc:example.org`) on
+    /// `homeserver`, authenticating with an access token.
+    pub fn new(
+        homeserver: impl Into<String>,
+        token: impl Into<String>,
+        room_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            homeserver: homeserver.into().trim_end_matches('/').to_string(),
+            token: token.into(),
+            room_id: room_id.into(),
+            client: reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("HTTP client"),
+        }
+    }
+
+    /// Sends a text message with an HTML rendering.
+    pub fn send_message(&self, txn_id: &str, body: &str, html: &str) -> Result<(), NotifyError> {
+        let url = format!(
+            "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+            self.homeserver,
+            encode_path_segment(&self.room_id),
+            encode_path_segment(txn_id)
+        );
+        let content = serde_json::json!({
+            "msgtype": "m.text",
+            "body": body,
+            "format": "org.matrix.custom.html",
+            "formatted_body": html,
+        });
+        let response = self
+            .client
+            .put(url)
+            .bearer_auth(&self.token)
+            .json(&content)
+            .send()?;
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                status: response.status().as_u16(),
+                body: response.text().unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Notifier for Matrix {
+    fn name(&self) -> &str {
+        "matrix"
+    }
+
+    fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        if alert.kind == AlertKind::NewCrash {
+            return Ok(());
+        }
+        let summary = alert.summary();
+        let (body, html) = match alert.crash.message.as_deref().filter(|m| !m.is_empty()) {
+            Some(message) => (
+                format!("{}\n{}", summary, message),
+                format!(
+                    "<strong>{}</strong><br><code>{}</code>",
+                    escape(&summary),
+                    escape(message)
+                ),
+            ),
+            None => (
+                summary.clone(),
+                format!("<strong>{}</strong>", escape(&summary)),
+            ),
+        };
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let txn_id = format!(
+            "bugstr-{}-{}-{}",
+            alert.crash.id,
+            alert.kind.as_str(),
+            nanos
+        );
+        send_with_retry(3, Duration::from_secs(1), || {
+            self.send_message(&txn_id, &body, &html)
+        })
+    }
+}
+
+/// Percent-encodes everything but unreserved characters (RFC 3986).
+fn encode_path_segment(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::test_server::serve_once;
+    use crate::notify::{test_alert, test_crash};
+
+    #[test]
+    fn test_posts_to_room() {
+        let mut alert = test_alert(AlertKind::NewGroup, 1);
+        alert.crash =
+            test_crash(r#"{"message": "a < b", "stack": "java.lang.IllegalStateException: boom"}"#);
+        alert.crash.id = 3;
+
+        let (url, server) = serve_once(200, r#"{"event_id": "$x"}"#);
+        Matrix::new(url, "syt_token", "!room:example.org")
+            .notify(&alert)
+            .unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.request_line.starts_with(
+            "PUT /_matrix/client/v3/rooms/%21room%3Aexample.org/send/m.room.message/bugstr-3-new_group-"
+        ));
+        assert_eq!(request.header("authorization"), Some("Bearer syt_token"));
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["msgtype"], "m.text");
+        assert_eq!(body["body"], format!("{}\na < b", alert.summary()));
+        assert!(body["formatted_body"]
+            .as_str()
+            .unwrap()
+            .ends_with("<code>a &lt; b</code>"));
+    }
+}
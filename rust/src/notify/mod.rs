@@ -0,0 +1,1067 @@
+//! Outbound notifications and issue tracker integrations.
+//!
+//! When the receiver stores a crash, [`alerts_for_crash`] decides which
+//! [`Alert`]s it raises (new crash, new group, regression, threshold) and
+//! each is handed to the [`Integration`]s configured for the crash's app.
+//! Integrations are stored in the crash database (see
+//! [`CrashStorage::add_integration`](crate::CrashStorage::add_integration))
+//! and managed with `bugstr integrations`; every attempt is logged as a
+//! [`Delivery`].
+//!
+//! Each integration becomes a [`Notifier`]. Notifiers make blocking network
+//! requests, so async callers run [`notify_all`] on a blocking thread.
+//!
+//! | Kind | Notifier | Action |
+//! |------|----------|--------|
+//! | `gitlab` | [`GitLab`] | Opens an issue for each new crash group |
+//! | `jira` | [`Jira`] | Opens an issue for each new crash group, optionally syncing its status back |
+//! | `webhook` | [`Webhook`] | POSTs signed JSON for the alerts it subscribes to |
+//! | `email` | [`Email`] | Emails new crash groups, threshold alerts and spikes over SMTP |
+//! | `ntfy` | [`Ntfy`] | Pushes the alerts it subscribes to (new groups by default) |
+//! | `telegram` | [`Telegram`] | Messages a chat about new crash groups and regressions |
+//! | `nostr` | [`NostrDm`] | Sends maintainers a NIP-17 DM for every alert except new crashes |
+//! | `matrix` | [`Matrix`] | Posts every alert except new crashes to a room |
+//! | `slack` | [`Slack`] | Posts every alert except new crashes to an incoming webhook |
+//! | `discord` | [`Discord`] | Posts new crash groups and regressions to a channel webhook |
+//! | `sentry` | [`Sentry`] | Forwards every stored crash to a Sentry project |
+
+pub mod anomaly;
+pub mod discord;
+pub mod email;
+pub mod gitlab;
+pub mod jira;
+pub mod matrix;
+pub mod nostr_dm;
+pub mod ntfy;
+pub mod rules;
+pub mod sentry;
+pub mod slack;
+pub mod telegram;
+pub mod webhook;
+
+pub use anomaly::{AnomalyDetector, Spike, SpikeGroup};
+pub use discord::Discord;
+pub use email::{Email, SmtpSecurity};
+pub use gitlab::GitLab;
+pub use jira::Jira;
+pub use matrix::Matrix;
+pub use nostr_dm::NostrDm;
+pub use ntfy::Ntfy;
+pub use sentry::Sentry;
+pub use slack::Slack;
+pub use telegram::Telegram;
+pub use webhook::Webhook;
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::storage::{CrashReport, CrashStorage};
+
+/// Group sizes that raise a [`AlertKind::Threshold`] alert.
+pub const THRESHOLDS: &[i64] = &[10, 100, 1000, 10000];
+
+/// Frames from these prefixes are runtime or library code, for
+/// [`Alert::top_in_app_frame`].
+const LIBRARY_PREFIXES: &[&str] = &[
+    "java.",
+    "javax.",
+    "kotlin.",
+    "kotlinx.",
+    "android.",
+    "androidx.",
+    "dalvik.",
+    "com.android.",
+    "sun.",
+    "dart:",
+    "package:flutter/",
+    "node:",
+    "std::",
+    "core::",
+    "alloc::",
+    "tokio::",
+    "runtime.",
+];
+
+/// Notification errors.
+#[derive(Debug, Error)]
+pub enum NotifyError {
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Server returned {status}: {body}")]
+    Status { status: u16, body: String },
+
+    #[error("Email error: {0}")]
+    Email(String),
+
+    #[error("Nostr error: {0}")]
+    Nostr(String),
+
+    #[error("Sentry error: {0}")]
+    Sentry(String),
+}
+
+/// What an [`Alert`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// A crash was stored.
+    NewCrash,
+    /// The first crash of a new group arrived.
+    NewGroup,
+    /// A crash regressed a group marked resolved.
+    Regression,
+    /// A group reached one of the [`THRESHOLDS`].
+    Threshold,
+    /// An app's crash rate spiked (see [`anomaly`]).
+    Spike,
+}
+
+impl AlertKind {
+    /// Every alert kind.
+    pub const ALL: [AlertKind; 5] = [
+        AlertKind::NewCrash,
+        AlertKind::NewGroup,
+        AlertKind::Regression,
+        AlertKind::Threshold,
+        AlertKind::Spike,
+    ];
+
+    /// Name as used in payloads and the database, e.g. `new_group`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::NewCrash => "new_crash",
+            AlertKind::NewGroup => "new_group",
+            AlertKind::Regression => "regression",
+            AlertKind::Threshold => "threshold",
+            AlertKind::Spike => "spike",
+        }
+    }
+
+    /// Parse a kind name. Returns `None` for unknown values.
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == s)
+    }
+}
+
+/// A crash event worth telling maintainers about.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub kind: AlertKind,
+    /// Key of the crash's group (its fingerprint).
+    pub group_key: String,
+    /// Crashes in the group so far.
+    pub count: i64,
+    /// The crash that raised the alert.
+    pub crash: CrashReport,
+    /// Symbolicated stack trace of the crash, when available.
+    pub symbolicated_stack: Option<String>,
+    /// The spike behind a [`AlertKind::Spike`] alert. `crash` is then the
+    /// latest crash of its largest group and `count` the crashes in the hour.
+    pub spike: Option<Spike>,
+}
+
+impl Alert {
+    /// One-line summary, e.g. `New crash group: NullPointerException in
+    /// com.example 1.2.0 (1 crash)`.
+    pub fn summary(&self) -> String {
+        if let Some(spike) = &self.spike {
+            let app = spike
+                .app_name
+                .as_ref()
+                .map(|name| format!(" in {}", name))
+                .unwrap_or_default();
+            return format!(
+                "Crash rate spike{}: {} crashes in the last hour (usually {:.1}), mostly {}",
+                app,
+                spike.crashes,
+                spike.baseline,
+                self.exception_type()
+            );
+        }
+        let what = match self.kind {
+            AlertKind::NewCrash => "New crash",
+            AlertKind::NewGroup => "New crash group",
+            AlertKind::Regression => "Regression",
+            AlertKind::Threshold => "Crash group threshold reached",
+            AlertKind::Spike => "Crash rate spike",
+        };
+        let app = match (&self.crash.app_name, &self.crash.app_version) {
+            (Some(name), Some(version)) => format!(" in {} {}", name, version),
+            (Some(name), None) => format!(" in {}", name),
+            (None, Some(version)) => format!(" in {}", version),
+            (None, None) => String::new(),
+        };
+        let crashes = if self.count == 1 { "crash" } else { "crashes" };
+        format!(
+            "{}: {}{} ({} {})",
+            what,
+            self.exception_type(),
+            app,
+            self.count,
+            crashes
+        )
+    }
+
+    /// Exception type of the crash, or `Unknown`.
+    pub fn exception_type(&self) -> &str {
+        self.crash.exception_type.as_deref().unwrap_or("Unknown")
+    }
+
+    /// Fills a message template. Placeholders:
+    ///
+    /// | Placeholder | Value |
+    /// |-------------|-------|
+    /// | `{summary}` | [`summary`](Self::summary) |
+    /// | `{event}` | Alert kind, e.g. `new_group` |
+    /// | `{group}` | Group key (fingerprint) |
+    /// | `{exception}` | Exception type, `Unknown` when unknown |
+    /// | `{count}` | Crashes in the group |
+    /// | `{crash_id}` | Stored crash ID |
+    /// | `{app}`, `{version}`, `{environment}`, `{message}` | Crash fields, empty when unknown |
+    ///
+    /// Unknown placeholders are left as they are.
+    ///
+    /// ```
+    /// # use bugstr::{Alert, AlertKind, CrashReport};
+    /// # let crash = CrashReport::from_content(String::new(), String::new(), 0, 0,
+    /// #     r#"{"message": "boom", "stack": "java.lang.IllegalStateException: boom"}"#.into());
+    /// # let alert = Alert { kind: AlertKind::NewGroup, group_key: crash.fingerprint.clone(),
+    /// #     count: 3, crash, symbolicated_stack: None, spike: None };
+    /// assert_eq!(
+    ///     alert.render("{exception} x{count}: {message}"),
+    ///     "IllegalStateException x3: boom"
+    /// );
+    /// ```
+    pub fn render(&self, template: &str) -> String {
+        let crash = &self.crash;
+        let field = |value: &Option<String>| value.clone().unwrap_or_default();
+        let values = [
+            ("{summary}", self.summary()),
+            ("{event}", self.kind.as_str().to_string()),
+            ("{group}", self.group_key.clone()),
+            ("{exception}", self.exception_type().to_string()),
+            ("{count}", self.count.to_string()),
+            ("{crash_id}", crash.id.to_string()),
+            ("{app}", field(&crash.app_name)),
+            ("{version}", field(&crash.app_version)),
+            ("{environment}", field(&crash.environment)),
+            ("{message}", field(&crash.message)),
+        ];
+
+        // Scan once so values containing placeholders aren't expanded
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            match values.iter().find(|(name, _)| rest.starts_with(name)) {
+                Some((name, value)) => {
+                    out.push_str(value);
+                    rest = &rest[name.len()..];
+                }
+                None => {
+                    out.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// The first stack frame that isn't runtime or library code, symbolicated
+    /// when available. Frames in the crash's app package win; otherwise the
+    /// first frame outside well-known runtime packages.
+    pub fn top_in_app_frame(&self) -> Option<&str> {
+        let stack = self
+            .symbolicated_stack
+            .as_deref()
+            .or(self.crash.stack_trace.as_deref())?;
+        let frames: Vec<&str> = stack
+            .lines()
+            .map(|line| line.trim().trim_start_matches("at ").trim_start())
+            .filter(|line| is_frame(line))
+            .collect();
+        let app_package = self
+            .crash
+            .app_name
+            .as_deref()
+            .filter(|name| name.contains('.'));
+        app_package
+            .and_then(|package| frames.iter().find(|frame| frame.starts_with(package)))
+            .or_else(|| {
+                frames
+                    .iter()
+                    .find(|frame| !LIBRARY_PREFIXES.iter().any(|p| frame.starts_with(p)))
+            })
+            .copied()
+    }
+
+    /// JSON body describing the alert, as sent by [`Webhook`].
+    pub fn payload(&self) -> serde_json::Value {
+        let crash = &self.crash;
+        let mut payload = serde_json::json!({
+            "event": self.kind.as_str(),
+            "summary": self.summary(),
+            "group": {
+                "key": self.group_key,
+                "count": self.count,
+            },
+            "crash": {
+                "id": crash.id,
+                "event_id": crash.event_id,
+                "received_at": crash.received_at,
+                "created_at": crash.created_at,
+                "app_name": crash.app_name,
+                "app_version": crash.app_version,
+                "exception_type": crash.exception_type,
+                "message": crash.message,
+                "environment": crash.environment,
+                "release": crash.release,
+                "stack_trace": self.symbolicated_stack.as_ref().or(crash.stack_trace.as_ref()),
+            },
+        });
+        if let Some(spike) = &self.spike {
+            payload["spike"] = serde_json::json!(spike);
+        }
+        payload
+    }
+}
+
+/// Whether a stack line is a frame rather than an exception or `Caused by:`
+/// line.
+fn is_frame(line: &str) -> bool {
+    !line.is_empty()
+        && !line.starts_with("Caused by")
+        && !line.starts_with("...")
+        && (line.contains('(') || line.contains(':'))
+        && !line.contains(": ")
+}
+
+/// The alerts a newly stored crash raises, given the state of its group.
+///
+/// Call right after [`CrashStorage::insert`]; `report.id` should be set.
+pub fn alerts_for_crash(
+    storage: &CrashStorage,
+    report: &CrashReport,
+) -> rusqlite::Result<Vec<Alert>> {
+    let group_key = report.group_key();
+    let count = storage.group_count(&group_key)?;
+    let symbolicated_stack = storage.get_symbolicated_stack(report.id)?;
+
+    let mut kinds = vec![AlertKind::NewCrash];
+    if count == 1 {
+        kinds.push(AlertKind::NewGroup);
+    }
+    if storage.regressed_by(&group_key)? == Some(report.id) {
+        kinds.push(AlertKind::Regression);
+    }
+    if THRESHOLDS.contains(&count) {
+        kinds.push(AlertKind::Threshold);
+    }
+
+    Ok(kinds
+        .into_iter()
+        .map(|kind| Alert {
+            kind,
+            group_key: group_key.clone(),
+            count,
+            crash: report.clone(),
+            symbolicated_stack: symbolicated_stack.clone(),
+            spike: None,
+        })
+        .collect())
+}
+
+/// The alerts a newly stored crash raises at time `now`, each with the
+/// integrations to send it to.
+///
+/// Without [`rules`] for the crash's app, these are the
+/// [`alerts_for_crash`], each for every integration of the app. With rules,
+/// the new crash alert still goes to every integration, and group alerts
+/// come from the rules that fire, for the integrations they target.
+pub fn route_alerts(
+    storage: &CrashStorage,
+    report: &CrashReport,
+    now: i64,
+) -> rusqlite::Result<Vec<(Alert, Vec<Integration>)>> {
+    let app_name = report.app_name.as_deref();
+    let integrations = storage.integrations_for_app(app_name)?;
+    if integrations.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rules = storage.rules_for_app(app_name)?;
+    if rules.is_empty() {
+        return Ok(alerts_for_crash(storage, report)?
+            .into_iter()
+            .map(|alert| (alert, integrations.clone()))
+            .collect());
+    }
+
+    let mut routes: Vec<_> = alerts_for_crash(storage, report)?
+        .into_iter()
+        .filter(|alert| alert.kind == AlertKind::NewCrash)
+        .map(|alert| (alert, integrations.clone()))
+        .collect();
+    for (rule, alert) in rules::evaluate_rules(storage, rules, report, now)? {
+        let targets = integrations
+            .iter()
+            .filter(|integration| rule.targets(integration.id))
+            .cloned()
+            .collect();
+        routes.push((alert, targets));
+    }
+    Ok(routes)
+}
+
+/// Delivers alerts to one destination.
+///
+/// Implementations may block; see the [module docs](self).
+pub trait Notifier: Send + Sync {
+    /// Short name used in logs, e.g. `gitlab`.
+    fn name(&self) -> &str;
+
+    /// Delivers `alert`. Notifiers ignore alert kinds they don't act on.
+    fn notify(&self, alert: &Alert) -> Result<(), NotifyError>;
+
+    /// Like [`notify`](Self::notify), also returning a reference to what the
+    /// delivery created, such as an issue key, for [`Delivery::reference`].
+    fn deliver(&self, alert: &Alert) -> Result<Option<String>, NotifyError> {
+        self.notify(alert).map(|()| None)
+    }
+}
+
+/// Retries `send` up to `attempts` times, doubling `backoff` between tries,
+/// while it fails with a network error, a 5xx or a 429 response.
+pub(crate) fn send_with_retry<T>(
+    attempts: u32,
+    mut backoff: Duration,
+    mut send: impl FnMut() -> Result<T, NotifyError>,
+) -> Result<T, NotifyError> {
+    let mut attempt = 1;
+    loop {
+        match send() {
+            Err(e) if attempt < attempts && is_transient(&e) => {
+                std::thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_transient(error: &NotifyError) -> bool {
+    match error {
+        NotifyError::Http(_) => true,
+        NotifyError::Status { status, .. } => *status >= 500 || *status == 429,
+        NotifyError::Email(_) | NotifyError::Nostr(_) | NotifyError::Sentry(_) => false,
+    }
+}
+
+/// Settings of one integration, stored as JSON tagged by `kind`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IntegrationConfig {
+    /// Open Jira issues (Cloud or Data Center).
+    Jira {
+        /// Site URL, e.g. `https://example.atlassian.net`.
+        url: String,
+        /// Project key, e.g. `APP`.
+        project: String,
+        /// Account email for an API token (Jira Cloud); without it the
+        /// token is a personal access token.
+        #[serde(default)]
+        email: Option<String>,
+        token: String,
+        /// Issue type name; defaults to [`jira::DEFAULT_ISSUE_TYPE`].
+        #[serde(default)]
+        issue_type: Option<String>,
+        /// Labels added to created issues.
+        #[serde(default)]
+        labels: Vec<String>,
+        /// Resolve a crash group once its issue is closed.
+        #[serde(default)]
+        sync_status: bool,
+    },
+    /// Open GitLab issues (gitlab.com or self-hosted).
+    Gitlab {
+        /// Base URL of the GitLab instance, e.g. `https://gitlab.com`.
+        url: String,
+        /// Project ID or path, e.g. `group/app`.
+        project: String,
+        /// Access token with the `api` scope.
+        token: String,
+        /// Labels added to created issues.
+        #[serde(default)]
+        labels: Vec<String>,
+    },
+    /// POST alerts as JSON to a URL.
+    Webhook {
+        url: String,
+        /// Shared secret for the `X-Bugstr-Signature` HMAC.
+        #[serde(default)]
+        secret: Option<String>,
+        /// Alert kinds to send; empty sends all.
+        #[serde(default)]
+        events: Vec<AlertKind>,
+    },
+    /// Email new crash groups and thresholds over SMTP.
+    Email {
+        /// SMTP server host name.
+        host: String,
+        /// SMTP port; defaults to the one for `security`.
+        #[serde(default)]
+        port: Option<u16>,
+        #[serde(default)]
+        security: SmtpSecurity,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        /// Sender address, e.g. `Bugstr <bugstr@example.com>`.
+        from: String,
+        /// Recipient addresses.
+        to: Vec<String>,
+    },
+    /// Publish push notifications to an ntfy topic.
+    Ntfy {
+        /// ntfy server, e.g. `https://ntfy.sh`.
+        server: String,
+        topic: String,
+        /// Access token for protected topics.
+        #[serde(default)]
+        token: Option<String>,
+        /// Alert kinds to send; empty sends new groups only.
+        #[serde(default)]
+        events: Vec<AlertKind>,
+    },
+    /// Message a Telegram chat through a bot.
+    Telegram {
+        /// Bot token from @BotFather.
+        token: String,
+        /// Chat ID, or `@channel` for public channels.
+        chat_id: String,
+        /// Message template (see [`Alert::render`]).
+        #[serde(default)]
+        template: Option<String>,
+    },
+    /// Send NIP-17 DMs to maintainers.
+    Nostr {
+        /// Hex secret key the DMs are sent from.
+        secret_key: String,
+        /// Hex public keys of the maintainers.
+        recipients: Vec<String>,
+        /// Relays to publish to.
+        relays: Vec<String>,
+    },
+    /// Post to a Matrix room.
+    Matrix {
+        /// Homeserver URL, e.g. `https://matrix.org`.
+        homeserver: String,
+        /// Access token of a user in the room.
+        token: String,
+        /// Room ID, e.g. `!abc:matrix.org`.
+        room_id: String,
+    },
+    /// Post to a Slack incoming webhook.
+    Slack {
+        /// Incoming webhook URL, e.g. `https://hooks.slack.com/services/...`.
+        webhook_url: String,
+        /// Dashboard URL linked from messages.
+        #[serde(default)]
+        dashboard_url: Option<String>,
+    },
+    /// Post to a Discord channel webhook.
+    Discord {
+        /// Webhook URL, e.g. `https://discord.com/api/webhooks/...`.
+        webhook_url: String,
+        /// Dashboard URL linked from embeds.
+        #[serde(default)]
+        dashboard_url: Option<String>,
+    },
+    /// Forward crashes to a Sentry project.
+    Sentry {
+        /// Project DSN, e.g. `https://<key>@o1.ingest.sentry.io/42`.
+        dsn: String,
+    },
+}
+
+impl IntegrationConfig {
+    /// The `kind` tag, e.g. `gitlab`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IntegrationConfig::Gitlab { .. } => "gitlab",
+            IntegrationConfig::Jira { .. } => "jira",
+            IntegrationConfig::Webhook { .. } => "webhook",
+            IntegrationConfig::Email { .. } => "email",
+            IntegrationConfig::Ntfy { .. } => "ntfy",
+            IntegrationConfig::Telegram { .. } => "telegram",
+            IntegrationConfig::Nostr { .. } => "nostr",
+            IntegrationConfig::Matrix { .. } => "matrix",
+            IntegrationConfig::Slack { .. } => "slack",
+            IntegrationConfig::Discord { .. } => "discord",
+            IntegrationConfig::Sentry { .. } => "sentry",
+        }
+    }
+
+    /// Whether this integration acts on alerts of `kind`.
+    pub fn handles(&self, kind: AlertKind) -> bool {
+        match self {
+            IntegrationConfig::Gitlab { .. } | IntegrationConfig::Jira { .. } => {
+                kind == AlertKind::NewGroup
+            }
+            IntegrationConfig::Webhook { events, .. } => {
+                events.is_empty() || events.contains(&kind)
+            }
+            IntegrationConfig::Email { .. } => {
+                matches!(
+                    kind,
+                    AlertKind::NewGroup | AlertKind::Threshold | AlertKind::Spike
+                )
+            }
+            IntegrationConfig::Ntfy { events, .. } => {
+                if events.is_empty() {
+                    kind == AlertKind::NewGroup
+                } else {
+                    events.contains(&kind)
+                }
+            }
+            IntegrationConfig::Telegram { .. } | IntegrationConfig::Discord { .. } => {
+                matches!(kind, AlertKind::NewGroup | AlertKind::Regression)
+            }
+            IntegrationConfig::Nostr { .. }
+            | IntegrationConfig::Matrix { .. }
+            | IntegrationConfig::Slack { .. } => kind != AlertKind::NewCrash,
+            IntegrationConfig::Sentry { .. } => kind == AlertKind::NewCrash,
+        }
+    }
+
+    /// Human-readable target without secrets, for listings.
+    pub fn target(&self) -> String {
+        match self {
+            IntegrationConfig::Gitlab { url, project, .. } => {
+                format!("{}/{}", url.trim_end_matches('/'), project)
+            }
+            IntegrationConfig::Jira { url, project, .. } => {
+                format!("{} project {}", url.trim_end_matches('/'), project)
+            }
+            IntegrationConfig::Webhook { url, .. } => url.clone(),
+            IntegrationConfig::Email { host, to, .. } => format!("{} via {}", to.join(", "), host),
+            IntegrationConfig::Ntfy { server, topic, .. } => {
+                format!("{}/{}", server.trim_end_matches('/'), topic)
+            }
+            IntegrationConfig::Telegram { chat_id, .. } => format!("chat {}", chat_id),
+            IntegrationConfig::Nostr { recipients, .. } => recipients
+                .iter()
+                .map(|pubkey| crate::nip19::encode_npub(pubkey).unwrap_or_else(|_| pubkey.clone()))
+                .collect::<Vec<_>>()
+                .join(", "),
+            IntegrationConfig::Matrix {
+                homeserver,
+                room_id,
+                ..
+            } => format!("{} on {}", room_id, homeserver),
+            // The webhook URL is the secret
+            IntegrationConfig::Slack { webhook_url, .. }
+            | IntegrationConfig::Discord { webhook_url, .. } => {
+                match reqwest::Url::parse(webhook_url) {
+                    Ok(url) => format!("webhook on {}", url.host_str().unwrap_or_default()),
+                    Err(_) => "invalid webhook URL".to_string(),
+                }
+            }
+            IntegrationConfig::Sentry { dsn } => match crate::sentry::Dsn::parse(dsn) {
+                Ok(dsn) => format!("project {} on {}", dsn.project_id, dsn.base_url),
+                Err(_) => "invalid DSN".to_string(),
+            },
+        }
+    }
+
+    /// Builds the Jira client of a Jira integration.
+    fn jira(&self) -> Option<Jira> {
+        let IntegrationConfig::Jira {
+            url,
+            project,
+            email,
+            token,
+            issue_type,
+            labels,
+            ..
+        } = self
+        else {
+            return None;
+        };
+        let mut jira = Jira::new(url, project, token).with_labels(labels.clone());
+        if let Some(email) = email {
+            jira = jira.with_email(email);
+        }
+        if let Some(issue_type) = issue_type {
+            jira = jira.with_issue_type(issue_type);
+        }
+        Some(jira)
+    }
+
+    /// The Jira client of a Jira integration with status sync enabled.
+    pub fn status_sync(&self) -> Option<Jira> {
+        match self {
+            IntegrationConfig::Jira {
+                sync_status: true, ..
+            } => self.jira(),
+            _ => None,
+        }
+    }
+
+    /// Builds the notifier for this integration.
+    pub fn notifier(&self) -> Box<dyn Notifier> {
+        match self {
+            IntegrationConfig::Gitlab {
+                url,
+                project,
+                token,
+                labels,
+            } => Box::new(GitLab::new(url, project, token).with_labels(labels.clone())),
+            IntegrationConfig::Jira { .. } => Box::new(self.jira().expect("Jira integration")),
+            IntegrationConfig::Webhook { url, secret, .. } => {
+                let webhook = Webhook::new(url);
+                Box::new(match secret {
+                    Some(secret) => webhook.with_secret(secret),
+                    None => webhook,
+                })
+            }
+            IntegrationConfig::Email {
+                host,
+                port,
+                security,
+                username,
+                password,
+                from,
+                to,
+            } => {
+                let mut email = Email::new(host, from, to.clone()).with_security(*security);
+                if let Some(port) = port {
+                    email = email.with_port(*port);
+                }
+                if let Some(username) = username {
+                    email = email.with_credentials(username, password.clone().unwrap_or_default());
+                }
+                Box::new(email)
+            }
+            IntegrationConfig::Ntfy {
+                server,
+                topic,
+                token,
+                ..
+            } => {
+                let ntfy = Ntfy::new(server, topic);
+                Box::new(match token {
+                    Some(token) => ntfy.with_token(token),
+                    None => ntfy,
+                })
+            }
+            IntegrationConfig::Telegram {
+                token,
+                chat_id,
+                template,
+            } => {
+                let telegram = Telegram::new(token, chat_id);
+                Box::new(match template {
+                    Some(template) => telegram.with_template(template),
+                    None => telegram,
+                })
+            }
+            IntegrationConfig::Nostr {
+                secret_key,
+                recipients,
+                relays,
+            } => {
+                // An all-zero key is invalid, so a malformed one fails on send
+                let secret_key = hex::decode(secret_key)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .unwrap_or([0; 32]);
+                Box::new(NostrDm::new(secret_key, recipients.clone(), relays.clone()))
+            }
+            IntegrationConfig::Matrix {
+                homeserver,
+                token,
+                room_id,
+            } => Box::new(Matrix::new(homeserver, token, room_id)),
+            IntegrationConfig::Slack {
+                webhook_url,
+                dashboard_url,
+            } => {
+                let slack = Slack::new(webhook_url);
+                Box::new(match dashboard_url {
+                    Some(url) => slack.with_dashboard_url(url),
+                    None => slack,
+                })
+            }
+            IntegrationConfig::Discord {
+                webhook_url,
+                dashboard_url,
+            } => {
+                let discord = Discord::new(webhook_url);
+                Box::new(match dashboard_url {
+                    Some(url) => discord.with_dashboard_url(url),
+                    None => discord,
+                })
+            }
+            IntegrationConfig::Sentry { dsn } => Box::new(Sentry::new(dsn)),
+        }
+    }
+}
+
+/// A stored integration.
+#[derive(Debug, Clone)]
+pub struct Integration {
+    pub id: i64,
+    /// App whose crashes it handles, or `None` for every app.
+    pub app_name: Option<String>,
+    pub config: IntegrationConfig,
+    pub created_at: i64,
+}
+
+/// One attempt to send an alert through an integration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    pub integration_id: i64,
+    pub alert_kind: AlertKind,
+    pub group_key: String,
+    pub crash_id: Option<i64>,
+    /// Why delivery failed (after retries), or `None` on success.
+    pub error: Option<String>,
+    /// What the delivery created, e.g. an issue key or link.
+    pub reference: Option<String>,
+    pub created_at: i64,
+}
+
+/// Sends `alert` through each integration that handles its kind, in turn.
+/// One failing integration doesn't stop the others. Returns a [`Delivery`]
+/// per integration tried, to record with
+/// [`CrashStorage::record_delivery`].
+pub fn notify_all(integrations: &[Integration], alert: &Alert) -> Vec<Delivery> {
+    integrations
+        .iter()
+        .filter(|integration| integration.config.handles(alert.kind))
+        .map(|integration| {
+            let (reference, error) = match integration.config.notifier().deliver(alert) {
+                Ok(reference) => (reference, None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            Delivery {
+                integration_id: integration.id,
+                alert_kind: alert.kind,
+                group_key: alert.group_key.clone(),
+                crash_id: Some(alert.crash.id),
+                error,
+                reference,
+                created_at: chrono::Utc::now().timestamp(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::GroupStatus;
+
+    fn kinds(storage: &CrashStorage, report: &CrashReport) -> Vec<AlertKind> {
+        alerts_for_crash(storage, report)
+            .unwrap()
+            .iter()
+            .map(|alert| alert.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_alerts_for_crash() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let report = |n: i64| {
+            CrashReport::from_content(
+                format!("event{}", n),
+                "sender".to_string(),
+                1000 + n,
+                1000 + n,
+                r#"{"stack": "java.lang.IllegalStateException: boom"}"#.to_string(),
+            )
+        };
+
+        let mut first = report(1);
+        first.id = storage.insert(&first).unwrap().unwrap();
+        assert_eq!(
+            kinds(&storage, &first),
+            [AlertKind::NewCrash, AlertKind::NewGroup]
+        );
+
+        storage
+            .set_group_status(&first.fingerprint, GroupStatus::Resolved, None, 2000)
+            .unwrap();
+        let mut regressed = report(2);
+        regressed.id = storage.insert(&regressed).unwrap().unwrap();
+        assert_eq!(
+            kinds(&storage, &regressed),
+            [AlertKind::NewCrash, AlertKind::Regression]
+        );
+
+        // Only the crash that regressed the group raises a regression
+        let mut last = report(10);
+        for n in 3..=10 {
+            last = report(n);
+            last.id = storage.insert(&last).unwrap().unwrap();
+        }
+        assert_eq!(
+            kinds(&storage, &last),
+            [AlertKind::NewCrash, AlertKind::Threshold]
+        );
+    }
+
+    #[test]
+    fn test_top_in_app_frame_skips_library_code() {
+        let mut alert = test_alert(AlertKind::NewGroup, 1);
+        alert.crash = test_crash(
+            &serde_json::json!({
+                "stack": "java.lang.IllegalStateException: boom\n\
+                          \tat java.util.ArrayList.get(ArrayList.java:437)\n\
+                          \tat com.acme.sdk.Client.send(Client.java:3)\n\
+                          \tat com.example.app.Cart.total(Cart.kt:12)",
+            })
+            .to_string(),
+        );
+        alert.crash.app_name = Some("com.example.app".to_string());
+        assert_eq!(
+            alert.top_in_app_frame(),
+            Some("com.example.app.Cart.total(Cart.kt:12)")
+        );
+
+        alert.crash.app_name = Some("shop".to_string());
+        assert_eq!(
+            alert.top_in_app_frame(),
+            Some("com.acme.sdk.Client.send(Client.java:3)")
+        );
+    }
+
+    #[test]
+    fn test_handles() {
+        let webhook = IntegrationConfig::Webhook {
+            url: "https://example.com/hook".to_string(),
+            secret: None,
+            events: vec![],
+        };
+        assert!(AlertKind::ALL.iter().all(|kind| webhook.handles(*kind)));
+
+        let webhook: IntegrationConfig = serde_json::from_str(
+            r#"{"kind": "webhook", "url": "https://example.com/hook", "events": ["regression"]}"#,
+        )
+        .unwrap();
+        assert!(webhook.handles(AlertKind::Regression));
+        assert!(!webhook.handles(AlertKind::NewCrash));
+
+        let ntfy: IntegrationConfig = serde_json::from_str(
+            r#"{"kind": "ntfy", "server": "https://ntfy.sh", "topic": "alerts"}"#,
+        )
+        .unwrap();
+        assert!(ntfy.handles(AlertKind::NewGroup));
+        assert!(!ntfy.handles(AlertKind::NewCrash));
+        assert_eq!(AlertKind::parse("regression"), Some(AlertKind::Regression));
+        assert_eq!(AlertKind::parse("nope"), None);
+    }
+}
+
+/// A crash received at 1001 with `content`, as notifier tests alert about.
+#[cfg(test)]
+pub(crate) fn test_crash(content: &str) -> CrashReport {
+    CrashReport::from_content(
+        "event".to_string(),
+        "sender".to_string(),
+        1000,
+        1001,
+        content.to_string(),
+    )
+}
+
+/// An alert of `kind` about a `java.lang.IllegalStateException: boom` crash
+/// whose group has `count` crashes.
+#[cfg(test)]
+pub(crate) fn test_alert(kind: AlertKind, count: i64) -> Alert {
+    Alert {
+        kind,
+        group_key: "IllegalStateException".to_string(),
+        count,
+        crash: test_crash(r#"{"message": "boom", "stack": "java.lang.IllegalStateException: boom"}"#),
+        symbolicated_stack: None,
+        spike: None,
+    }
+}
+
+/// Test helpers: a one-shot HTTP server that records the request it gets.
+#[cfg(test)]
+pub(crate) mod test_server {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    /// A received HTTP request.
+    #[derive(Debug)]
+    pub(crate) struct Request {
+        pub request_line: String,
+        pub headers: Vec<(String, String)>,
+        pub body: String,
+    }
+
+    impl Request {
+        pub(crate) fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        }
+    }
+
+    /// Serves one request with `status` and `response` as the body. Returns
+    /// the server's base URL and a handle yielding the request.
+    pub(crate) fn serve_once(status: u16, response: &'static str) -> (String, JoinHandle<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut headers = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                match line.trim_end().split_once(": ") {
+                    Some((name, value)) => headers.push((name.to_string(), value.to_string())),
+                    None => break,
+                }
+            }
+            let len = headers
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                .map_or(0, |(_, value)| value.parse().unwrap());
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body).unwrap();
+
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {} OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                status,
+                response.len(),
+                response
+            )
+            .unwrap();
+            Request {
+                request_line: request_line.trim_end().to_string(),
+                headers,
+                body: String::from_utf8(body).unwrap(),
+            }
+        });
+        (url, handle)
+    }
+}
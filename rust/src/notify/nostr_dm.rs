@@ -0,0 +1,199 @@
+//! Nostr DM alerts.
+//!
+//! Sends the alert summary to maintainers as a NIP-17 private message: a
+//! kind 14 chat message, sealed and gift wrapped with
+//! [`build_seal`] and [`build_gift_wrap`], published to each relay. Any
+//! Nostr client with NIP-17 support shows it as a DM from the alert key.
+
+use std::net::TcpStream;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use super::{Alert, AlertKind, Notifier, NotifyError};
+use crate::event::{public_key, SignedNostrEvent, Tag, UnsignedNostrEvent};
+use crate::kinds::KIND_LEGACY_DM;
+use crate::nip59::{build_gift_wrap, build_seal};
+
+/// How long to wait for a relay to acknowledge an event.
+const RELAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sends alerts as gift-wrapped DMs to maintainer public keys.
+pub struct NostrDm {
+    secret_key: zeroize::Zeroizing<[u8; 32]>,
+    recipients: Vec<String>,
+    relays: Vec<String>,
+}
+
+impl NostrDm {
+    /// Creates a notifier sending from `secret_key` to each hex public key
+    /// in `recipients`, through `relays`.
+    pub fn new(secret_key: [u8; 32], recipients: Vec<String>, relays: Vec<String>) -> Self {
+        Self {
+            secret_key: zeroize::Zeroizing::new(secret_key),
+            recipients,
+            relays,
+        }
+    }
+
+    /// Builds the gift wrap carrying `text` to `recipient`.
+    pub fn gift_wrap(&self, recipient: &str, text: &str) -> Result<SignedNostrEvent, NotifyError> {
+        let sender = public_key(&self.secret_key).map_err(|e| NotifyError::Nostr(e.to_string()))?;
+        let rumor = UnsignedNostrEvent::new(
+            sender,
+            now(),
+            KIND_LEGACY_DM,
+            vec![Tag::pubkey(recipient, None).into()],
+            text,
+        );
+        build_seal(&rumor, &self.secret_key, recipient)
+            .and_then(|seal| build_gift_wrap(&seal, recipient, None))
+            .map_err(|e| NotifyError::Nostr(e.to_string()))
+    }
+
+    /// Publishes `event` to every relay. Succeeds if at least one accepted it.
+    fn publish(&self, event: &SignedNostrEvent) -> Result<(), NotifyError> {
+        let mut errors = Vec::new();
+        for relay in &self.relays {
+            match publish(relay, event) {
+                Ok(()) => return Ok(()),
+                Err(e) => errors.push(format!("{}: {}", relay, e)),
+            }
+        }
+        Err(NotifyError::Nostr(format!(
+            "no relay accepted the message ({})",
+            errors.join("; ")
+        )))
+    }
+}
+
+impl Notifier for NostrDm {
+    fn name(&self) -> &str {
+        "nostr"
+    }
+
+    fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        if alert.kind == AlertKind::NewCrash {
+            return Ok(());
+        }
+        let text = match alert.crash.message.as_deref().filter(|m| !m.is_empty()) {
+            Some(message) => format!("{}\n{}", alert.summary(), message),
+            None => alert.summary(),
+        };
+        for recipient in &self.recipients {
+            self.publish(&self.gift_wrap(recipient, &text)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends `event` to one relay and waits for its `OK`.
+fn publish(relay: &str, event: &SignedNostrEvent) -> Result<(), String> {
+    let (mut socket, _) = tungstenite::connect(relay).map_err(|e| e.to_string())?;
+    set_read_timeout(&socket).map_err(|e| e.to_string())?;
+
+    let request = serde_json::json!(["EVENT", event]).to_string();
+    socket
+        .send(Message::text(request))
+        .map_err(|e| e.to_string())?;
+
+    let result = loop {
+        let message = match socket.read() {
+            Ok(message) => message,
+            Err(e) => break Err(e.to_string()),
+        };
+        let Ok(text) = message.to_text() else {
+            continue;
+        };
+        // ["OK", <event id>, <accepted>, <message>]
+        let Ok(serde_json::Value::Array(reply)) = serde_json::from_str(text) else {
+            continue;
+        };
+        if reply.first().and_then(|v| v.as_str()) != Some("OK")
+            || reply.get(1).and_then(|v| v.as_str()) != Some(event.id.as_str())
+        {
+            continue;
+        }
+        break match reply.get(2).and_then(|v| v.as_bool()) {
+            Some(true) => Ok(()),
+            _ => Err(format!(
+                "rejected: {}",
+                reply.get(3).and_then(|v| v.as_str()).unwrap_or_default()
+            )),
+        };
+    };
+    let _ = socket.close(None);
+    result
+}
+
+fn set_read_timeout(socket: &WebSocket<MaybeTlsStream<TcpStream>>) -> std::io::Result<()> {
+    match socket.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream.set_read_timeout(Some(RELAY_TIMEOUT)),
+        MaybeTlsStream::NativeTls(stream) => stream.get_ref().set_read_timeout(Some(RELAY_TIMEOUT)),
+        _ => Ok(()),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EphemeralKeys;
+    use crate::notify::test_alert;
+    use nostr::nips::nip44;
+    use std::net::TcpListener;
+
+    fn decrypt(recipient: &EphemeralKeys, event: &SignedNostrEvent) -> String {
+        let secret_key = nostr::SecretKey::from_slice(recipient.secret_key()).unwrap();
+        let sender = nostr::PublicKey::from_hex(&event.pubkey).unwrap();
+        nip44::decrypt(&secret_key, &sender, &event.content).unwrap()
+    }
+
+    /// Accepts one EVENT and replies `OK` for it.
+    fn relay() -> (String, std::thread::JoinHandle<SignedNostrEvent>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            let message = socket.read().unwrap();
+            let (_, event): (String, SignedNostrEvent) =
+                serde_json::from_str(message.to_text().unwrap()).unwrap();
+            let reply = serde_json::json!(["OK", event.id, true, ""]).to_string();
+            socket.send(Message::text(reply)).unwrap();
+            event
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_sends_gift_wrapped_dm() {
+        let sender = EphemeralKeys::generate();
+        let maintainer = EphemeralKeys::generate();
+        let alert = test_alert(AlertKind::NewGroup, 1);
+
+        let (url, relay) = relay();
+        let notifier = NostrDm::new(
+            *sender.secret_key(),
+            vec![maintainer.public_key().to_string()],
+            vec!["ws://127.0.0.1:1".to_string(), url],
+        );
+        notifier.notify(&alert).unwrap();
+
+        let gift_wrap = relay.join().unwrap();
+        gift_wrap.verify().unwrap();
+        let seal: SignedNostrEvent =
+            serde_json::from_str(&decrypt(&maintainer, &gift_wrap)).unwrap();
+        assert_eq!(seal.pubkey, sender.public_key());
+        let rumor: UnsignedNostrEvent = serde_json::from_str(&decrypt(&maintainer, &seal)).unwrap();
+        assert_eq!(rumor.kind, 14);
+        assert_eq!(rumor.content, format!("{}\nboom", alert.summary()));
+    }
+}
@@ -0,0 +1,122 @@
+//! ntfy push notifications.
+//!
+//! Publishes alerts to a topic on [ntfy.sh](https://ntfy.sh) or a
+//! self-hosted ntfy server, so subscribers get a push notification on
+//! their phone or desktop. The message is the alert summary plus the crash
+//! message; new groups and regressions are sent with high priority.
+
+use std::time::Duration;
+
+use super::{send_with_retry, Alert, AlertKind, Notifier, NotifyError};
+
+/// The public ntfy server.
+pub const DEFAULT_SERVER: &str = "https://ntfy.sh";
+
+/// Publishes alerts to an ntfy topic.
+pub struct Ntfy {
+    server: String,
+    topic: String,
+    token: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+impl Ntfy {
+    /// Creates a notifier publishing to `topic` on `server`.
+    pub fn new(server: impl Into<String>, topic: impl Into<String>) -> Self {
+        Self {
+            server: server.into().trim_end_matches('/').to_string(),
+            topic: topic.into(),
+            token: None,
+            client: reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("HTTP client"),
+        }
+    }
+
+    /// Authenticates with an access token, for protected topics.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    fn publish(&self, alert: &Alert) -> Result<(), NotifyError> {
+        let priority = match alert.kind {
+            AlertKind::NewGroup | AlertKind::Regression | AlertKind::Spike => "high",
+            AlertKind::NewCrash | AlertKind::Threshold => "default",
+        };
+        let mut request = self
+            .client
+            .post(format!("{}/{}", self.server, self.topic))
+            .header("Title", alert.summary())
+            .header("Priority", priority)
+            .header("Tags", "warning")
+            .body(message(alert));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send()?;
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                status: response.status().as_u16(),
+                body: response.text().unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Notifier for Ntfy {
+    fn name(&self) -> &str {
+        "ntfy"
+    }
+
+    fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        send_with_retry(3, Duration::from_secs(1), || self.publish(alert))
+    }
+}
+
+/// Notification body: the crash message and app version, when known.
+fn message(alert: &Alert) -> String {
+    let crash = &alert.crash;
+    let mut lines = Vec::new();
+    if let Some(message) = crash.message.as_deref().filter(|m| !m.is_empty()) {
+        lines.push(message.to_string());
+    }
+    match (&crash.app_name, &crash.app_version) {
+        (Some(app), Some(version)) => lines.push(format!("{} {}", app, version)),
+        (Some(app), None) => lines.push(app.clone()),
+        (None, Some(version)) => lines.push(format!("Version {}", version)),
+        (None, None) => {}
+    }
+    if lines.is_empty() {
+        lines.push(alert.exception_type().to_string());
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::test_alert;
+    use crate::notify::test_server::serve_once;
+
+    #[test]
+    fn test_publishes_to_topic() {
+        let alert = test_alert(AlertKind::NewGroup, 1);
+
+        let (url, server) = serve_once(200, "{}");
+        Ntfy::new(format!("{}/", url), "bugstr-alerts")
+            .with_token("tk_secret")
+            .notify(&alert)
+            .unwrap();
+
+        let request = server.join().unwrap();
+        assert_eq!(request.request_line, "POST /bugstr-alerts HTTP/1.1");
+        assert_eq!(request.header("title"), Some(alert.summary().as_str()));
+        assert_eq!(request.header("priority"), Some("high"));
+        assert_eq!(request.header("authorization"), Some("Bearer tk_secret"));
+        assert_eq!(request.body, "boom");
+    }
+}
@@ -0,0 +1,229 @@
+//! Alert rules.
+//!
+//! By default every crash raises the built-in alerts of
+//! [`alerts_for_crash`](super::alerts_for_crash) and each goes to every
+//! integration. Once an app has rules, its group alerts come from the rules
+//! instead: each rule has a condition, the integrations it notifies, and a
+//! cooldown that keeps it from firing again for the same group too soon.
+//!
+//! | Condition | Fires when |
+//! |-----------|------------|
+//! | `new_group` | A crash starts a new group |
+//! | `regression` | A crash arrives in a group marked resolved |
+//! | `group_rate` | A group has more than `count` crashes in `window_minutes` |
+//!
+//! Rules are stored in the crash database (see
+//! [`CrashStorage::add_rule`]) and managed with `bugstr rules` or the
+//! `/api/rules` endpoints.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Alert, AlertKind};
+use crate::storage::{CrashReport, CrashStorage};
+
+/// Default cooldown between firings of a rule for one group.
+pub const DEFAULT_COOLDOWN_MINUTES: i64 = 60;
+
+/// When a rule fires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleCondition {
+    /// A crash starts a new group.
+    NewGroup,
+    /// A crash regresses a group marked resolved.
+    Regression,
+    /// A group has more than `count` crashes in the last `window_minutes`.
+    GroupRate { count: i64, window_minutes: i64 },
+}
+
+impl RuleCondition {
+    /// Checks the parameters, returning a message for the first bad one.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            RuleCondition::GroupRate {
+                count,
+                window_minutes,
+            } => {
+                if *count < 0 {
+                    return Err("count must not be negative".to_string());
+                }
+                if *window_minutes < 1 {
+                    return Err("window_minutes must be at least 1".to_string());
+                }
+                Ok(())
+            }
+            RuleCondition::NewGroup | RuleCondition::Regression => Ok(()),
+        }
+    }
+
+    /// The kind of alert the rule raises.
+    pub fn alert_kind(&self) -> AlertKind {
+        match self {
+            RuleCondition::NewGroup => AlertKind::NewGroup,
+            RuleCondition::Regression => AlertKind::Regression,
+            RuleCondition::GroupRate { .. } => AlertKind::Threshold,
+        }
+    }
+
+    /// Human-readable description, for listings.
+    pub fn describe(&self) -> String {
+        match self {
+            RuleCondition::NewGroup => "new group".to_string(),
+            RuleCondition::Regression => "regression".to_string(),
+            RuleCondition::GroupRate {
+                count,
+                window_minutes,
+            } => format!("more than {} crashes in {} min", count, window_minutes),
+        }
+    }
+
+    fn matches(
+        &self,
+        storage: &CrashStorage,
+        report: &CrashReport,
+        group_key: &str,
+        group_count: i64,
+        now: i64,
+    ) -> rusqlite::Result<bool> {
+        match self {
+            RuleCondition::NewGroup => Ok(group_count == 1),
+            RuleCondition::Regression => Ok(storage.regressed_by(group_key)? == Some(report.id)),
+            RuleCondition::GroupRate {
+                count,
+                window_minutes,
+            } => {
+                let since = now - window_minutes * 60;
+                Ok(storage.group_count_since(group_key, since)? > *count)
+            }
+        }
+    }
+}
+
+/// A stored alert rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rule {
+    pub id: i64,
+    pub name: String,
+    /// App whose crashes it watches, or `None` for every app.
+    pub app_name: Option<String>,
+    pub condition: RuleCondition,
+    /// Integrations to notify; empty notifies all of the app's integrations.
+    pub integration_ids: Vec<i64>,
+    /// Minimum time between firings for the same group.
+    pub cooldown_minutes: i64,
+    pub created_at: i64,
+}
+
+impl Rule {
+    /// Whether the rule sends to the integration with `id`.
+    pub fn targets(&self, integration_id: i64) -> bool {
+        self.integration_ids.is_empty() || self.integration_ids.contains(&integration_id)
+    }
+}
+
+/// Evaluates `rules` for a newly stored crash at time `now`. Returns each
+/// rule that fires with its alert, and records the firing so the rule
+/// cools down.
+pub fn evaluate_rules(
+    storage: &CrashStorage,
+    rules: Vec<Rule>,
+    report: &CrashReport,
+    now: i64,
+) -> rusqlite::Result<Vec<(Rule, Alert)>> {
+    let group_key = report.group_key();
+    let count = storage.group_count(&group_key)?;
+
+    let mut fired = Vec::new();
+    for rule in rules {
+        if !rule.condition.matches(storage, report, &group_key, count, now)? {
+            continue;
+        }
+        let cooling_down = storage
+            .rule_last_fired(rule.id, &group_key)?
+            .is_some_and(|at| now - at < rule.cooldown_minutes * 60);
+        if cooling_down {
+            continue;
+        }
+        storage.set_rule_fired(rule.id, &group_key, now)?;
+
+        let alert = Alert {
+            kind: rule.condition.alert_kind(),
+            group_key: group_key.clone(),
+            count,
+            crash: report.clone(),
+            symbolicated_stack: storage.get_symbolicated_stack(report.id)?,
+            spike: None,
+        };
+        fired.push((rule, alert));
+    }
+    Ok(fired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(condition: RuleCondition, cooldown_minutes: i64) -> Rule {
+        Rule {
+            id: 0,
+            name: "rule".to_string(),
+            app_name: None,
+            condition,
+            integration_ids: vec![],
+            cooldown_minutes,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_rate_rule_with_cooldown() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let rate = RuleCondition::GroupRate {
+            count: 2,
+            window_minutes: 10,
+        };
+        let id = storage.add_rule(&rule(rate, 30)).unwrap();
+        let new_group = storage.add_rule(&rule(RuleCondition::NewGroup, 0)).unwrap();
+
+        let fired_at = |n: i64, now: i64| {
+            let mut report = CrashReport::from_content(
+                format!("event{}", n),
+                "sender".to_string(),
+                now,
+                now,
+                r#"{"stack": "java.lang.IllegalStateException: boom"}"#.to_string(),
+            );
+            report.id = storage.insert(&report).unwrap().unwrap();
+            let rules = storage.rules_for_app(None).unwrap();
+            evaluate_rules(&storage, rules, &report, now)
+                .unwrap()
+                .into_iter()
+                .map(|(rule, alert)| (rule.id, alert.kind))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(fired_at(1, 1000), [(new_group, AlertKind::NewGroup)]);
+        assert_eq!(fired_at(2, 1060), []);
+        // Third crash within 10 minutes
+        assert_eq!(fired_at(3, 1120), [(id, AlertKind::Threshold)]);
+        // Still over the rate, but cooling down
+        assert_eq!(fired_at(4, 1180), []);
+        // Cooldown over, rate high again
+        for n in 5..8 {
+            fired_at(n, 1000 + 31 * 60);
+        }
+        assert_eq!(fired_at(8, 1000 + 32 * 60), [(id, AlertKind::Threshold)]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let rate = |count, window_minutes| RuleCondition::GroupRate {
+            count,
+            window_minutes,
+        };
+        assert!(rate(10, 5).validate().is_ok());
+        assert!(rate(-1, 5).validate().is_err());
+        assert!(rate(10, 0).validate().is_err());
+        assert_eq!(rate(10, 5).describe(), "more than 10 crashes in 5 min");
+    }
+}
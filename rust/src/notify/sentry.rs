@@ -0,0 +1,94 @@
+//! Forwarding to Sentry.
+//!
+//! Sends every stored crash to a Sentry project as an error event (see
+//! [`event_from_crash`]), so bugstr can run alongside an existing Sentry
+//! pipeline while a team evaluates it. Crashes keep a stable event ID, and
+//! Sentry drops duplicates of an ID it has already seen.
+
+use std::time::Duration;
+
+use super::{send_with_retry, Alert, AlertKind, Notifier, NotifyError};
+use crate::sentry::{envelope, event_from_crash, Dsn};
+
+/// Forwards crashes to a Sentry DSN.
+pub struct Sentry {
+    dsn: String,
+    client: reqwest::blocking::Client,
+}
+
+impl Sentry {
+    /// Creates a notifier forwarding to `dsn`. An invalid DSN fails on send.
+    pub fn new(dsn: impl Into<String>) -> Self {
+        Self {
+            dsn: dsn.into(),
+            client: reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("HTTP client"),
+        }
+    }
+
+    fn send(&self, dsn: &Dsn, body: &[u8]) -> Result<(), NotifyError> {
+        let response = self
+            .client
+            .post(dsn.envelope_url())
+            .header("X-Sentry-Auth", dsn.auth_header())
+            .header("Content-Type", "application/x-sentry-envelope")
+            .body(body.to_vec())
+            .send()?;
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                status: response.status().as_u16(),
+                body: response.text().unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Notifier for Sentry {
+    fn name(&self) -> &str {
+        "sentry"
+    }
+
+    fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        if alert.kind != AlertKind::NewCrash {
+            return Ok(());
+        }
+        let dsn = Dsn::parse(&self.dsn).map_err(|e| NotifyError::Sentry(e.to_string()))?;
+        let event = event_from_crash(&alert.crash, alert.symbolicated_stack.as_deref());
+        let body = envelope(&event);
+        send_with_retry(3, Duration::from_secs(1), || self.send(&dsn, &body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::test_server::serve_once;
+    use crate::notify::{test_alert, test_crash};
+
+    #[test]
+    fn test_forwards_crash_envelope() {
+        let mut alert = test_alert(AlertKind::NewCrash, 1);
+        alert.crash = test_crash(
+            r#"{"message": "boom", "stack": "java.lang.IllegalStateException: boom\n    at com.example.Main.run(Main.java:12)"}"#,
+        );
+        alert.crash.id = 4;
+
+        let (url, server) = serve_once(200, r#"{"id": "x"}"#);
+        let dsn = url.replacen("http://", "http://pubkey@", 1) + "/42";
+        Sentry::new(dsn).notify(&alert).unwrap();
+
+        let request = server.join().unwrap();
+        assert_eq!(request.request_line, "POST /api/42/envelope/ HTTP/1.1");
+        assert!(request
+            .header("x-sentry-auth")
+            .unwrap()
+            .ends_with("sentry_key=pubkey"));
+        let events = crate::sentry::parse_events(request.body.as_bytes()).unwrap();
+        let frames = &events[0]["exception"]["values"][0]["stacktrace"]["frames"];
+        assert_eq!(events[0]["tags"]["bugstr.crash_id"], "4");
+        assert_eq!(frames[0]["function"], "com.example.Main.run(Main.java:12)");
+    }
+}
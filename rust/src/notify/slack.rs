@@ -0,0 +1,186 @@
+//! Slack notifications.
+//!
+//! Posts a Block Kit message to a Slack incoming webhook for new crash
+//! groups, regressions and thresholds: the summary, the exception and its
+//! message, the top in-app frame, version and crash count, and a link to
+//! the dashboard when one is configured.
+
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use super::{send_with_retry, Alert, AlertKind, Notifier, NotifyError};
+
+/// Posts alerts to a Slack incoming webhook.
+pub struct Slack {
+    webhook_url: String,
+    dashboard_url: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+impl Slack {
+    /// Creates a notifier posting to the incoming webhook `webhook_url`.
+    pub fn new(webhook_url: impl Into<String>) -> Self {
+        Self {
+            webhook_url: webhook_url.into(),
+            dashboard_url: None,
+            client: reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("HTTP client"),
+        }
+    }
+
+    /// Links messages to the dashboard at `url`, e.g.
+    /// `https://bugstr.example.com`.
+    pub fn with_dashboard_url(mut self, url: impl Into<String>) -> Self {
+        self.dashboard_url = Some(url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// The Block Kit message for `alert`.
+    pub fn message(&self, alert: &Alert) -> Value {
+        let crash = &alert.crash;
+        let summary = alert.summary();
+        let mut blocks = vec![json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": truncate(&summary, 150), "emoji": false },
+        })];
+
+        let mut exception = format!("*{}*", escape(alert.exception_type()));
+        if let Some(message) = crash.message.as_deref().filter(|m| !m.is_empty()) {
+            exception.push_str(&format!("\n{}", escape(message)));
+        }
+        if let Some(frame) = alert.top_in_app_frame() {
+            exception.push_str(&format!("\n`{}`", frame.replace('`', "'")));
+        }
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": truncate(&exception, 3000) },
+        }));
+
+        let mut fields = vec![
+            ("App", crash.app_name.clone()),
+            ("Version", crash.app_version.clone()),
+            ("Crashes", Some(alert.count.to_string())),
+            ("Environment", crash.environment.clone()),
+        ];
+        fields.retain(|(_, value)| value.as_deref().is_some_and(|v| !v.is_empty()));
+        blocks.push(json!({
+            "type": "section",
+            "fields": fields
+                .into_iter()
+                .map(|(label, value)| json!({
+                    "type": "mrkdwn",
+                    "text": format!("*{}*\n{}", label, escape(&value.unwrap_or_default())),
+                }))
+                .collect::<Vec<_>>(),
+        }));
+
+        if let Some(dashboard) = &self.dashboard_url {
+            blocks.push(json!({
+                "type": "actions",
+                "elements": [{
+                    "type": "button",
+                    "text": { "type": "plain_text", "text": "Open dashboard" },
+                    "url": dashboard,
+                }],
+            }));
+        }
+
+        json!({
+            // Shown in notifications, which don't render blocks
+            "text": summary,
+            "blocks": blocks,
+        })
+    }
+
+    fn send(&self, message: &Value) -> Result<(), NotifyError> {
+        let response = self.client.post(&self.webhook_url).json(message).send()?;
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                status: response.status().as_u16(),
+                body: response.text().unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Notifier for Slack {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        if alert.kind == AlertKind::NewCrash {
+            return Ok(());
+        }
+        let message = self.message(alert);
+        send_with_retry(3, Duration::from_secs(1), || self.send(&message))
+    }
+}
+
+/// Escapes the characters Slack's mrkdwn treats as control characters.
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Shortens `s` to at most `max` characters, as Block Kit requires.
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let mut out: String = s.chars().take(max - 1).collect();
+    out.push('…');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::test_server::serve_once;
+    use crate::notify::{test_alert, test_crash};
+
+    fn alert(stack: &str) -> Alert {
+        let mut alert = test_alert(AlertKind::Regression, 7);
+        alert.crash = test_crash(&json!({ "message": "a < b", "stack": stack }).to_string());
+        alert.crash.app_name = Some("com.example.app".to_string());
+        alert.crash.app_version = Some("1.2.0".to_string());
+        alert
+    }
+
+    #[test]
+    fn test_posts_blocks() {
+        let alert = alert(
+            "java.lang.IllegalStateException: a < b\n\
+             \tat java.util.ArrayList.get(ArrayList.java:437)\n\
+             \tat com.example.app.Cart.total(Cart.kt:12)",
+        );
+
+        let (url, server) = serve_once(200, "ok");
+        Slack::new(format!("{}/services/T0/B0/secret", url))
+            .with_dashboard_url("https://bugstr.example.com/")
+            .notify(&alert)
+            .unwrap();
+
+        let request = server.join().unwrap();
+        assert_eq!(request.request_line, "POST /services/T0/B0/secret HTTP/1.1");
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["text"], alert.summary());
+        let blocks = body["blocks"].as_array().unwrap();
+        assert_eq!(blocks[0]["type"], "header");
+        assert_eq!(
+            blocks[1]["text"]["text"],
+            "*IllegalStateException*\na &lt; b\n`com.example.app.Cart.total(Cart.kt:12)`"
+        );
+        assert_eq!(blocks[2]["fields"][1]["text"], "*Version*\n1.2.0");
+        assert_eq!(blocks[2]["fields"][2]["text"], "*Crashes*\n7");
+        assert_eq!(
+            blocks[3]["elements"][0]["url"],
+            "https://bugstr.example.com"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Telegram bot notifications.
+//!
+//! Sends a message to a chat through the Bot API (`sendMessage`) for new
+//! crash groups and regressions. The text comes from a template (see
+//! [`Alert::render`]); route apps to different chats by adding one
+//! integration per app.
+
+use std::time::Duration;
+
+use super::{send_with_retry, Alert, AlertKind, Notifier, NotifyError};
+
+/// Message template used when none is configured.
+pub const DEFAULT_TEMPLATE: &str = "{summary}\n{message}";
+
+const API_URL: &str = "https://api.telegram.org";
+
+/// Sends alerts to a Telegram chat.
+pub struct Telegram {
+    api_url: String,
+    token: String,
+    chat_id: String,
+    template: String,
+    client: reqwest::blocking::Client,
+}
+
+impl Telegram {
+    /// Creates a notifier posting to `chat_id` (numeric ID or `@channel`)
+    /// as the bot with `token`.
+    pub fn new(token: impl Into<String>, chat_id: impl Into<String>) -> Self {
+        Self {
+            api_url: API_URL.to_string(),
+            token: token.into(),
+            chat_id: chat_id.into(),
+            template: DEFAULT_TEMPLATE.to_string(),
+            client: reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("HTTP client"),
+        }
+    }
+
+    /// Formats messages with `template` instead of [`DEFAULT_TEMPLATE`].
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = template.into();
+        self
+    }
+
+    /// Uses another Bot API server, e.g. a local `telegram-bot-api`.
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sends `text` to the chat.
+    pub fn send_message(&self, text: &str) -> Result<(), NotifyError> {
+        let body = serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": text,
+            "disable_web_page_preview": true,
+        });
+        let response = self
+            .client
+            .post(format!("{}/bot{}/sendMessage", self.api_url, self.token))
+            .json(&body)
+            .send()?;
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                status: response.status().as_u16(),
+                body: response.text().unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Notifier for Telegram {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        if !matches!(alert.kind, AlertKind::NewGroup | AlertKind::Regression) {
+            return Ok(());
+        }
+        let text = alert.render(&self.template);
+        send_with_retry(3, Duration::from_secs(1), || self.send_message(text.trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::test_alert;
+    use crate::notify::test_server::serve_once;
+
+    #[test]
+    fn test_sends_rendered_message() {
+        let alert = test_alert(AlertKind::Regression, 5);
+
+        let (url, server) = serve_once(200, r#"{"ok": true}"#);
+        Telegram::new("123:abc", "-100200")
+            .with_api_url(url)
+            .with_template("{event}: {group} ({count}) {unknown}")
+            .notify(&alert)
+            .unwrap();
+
+        let request = server.join().unwrap();
+        assert_eq!(
+            request.request_line,
+            "POST /bot123:abc/sendMessage HTTP/1.1"
+        );
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["chat_id"], "-100200");
+        assert_eq!(
+            body["text"],
+            "regression: IllegalStateException (5) {unknown}"
+        );
+    }
+}
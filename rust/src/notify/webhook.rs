@@ -0,0 +1,176 @@
+//! Generic outbound webhooks.
+//!
+//! POSTs [`Alert::payload`] as JSON to a URL. The request carries the alert
+//! kind in `X-Bugstr-Event` and, when a shared secret is configured, an
+//! HMAC-SHA256 of the body in `X-Bugstr-Signature` (`sha256=<hex>`), so
+//! receivers can check that it came from this bugstr instance:
+//!
+//! ```
+//! use bugstr::notify::webhook::signature;
+//!
+//! let body = br#"{"event":"new_group"}"#;
+//! let header = signature("secret", body);
+//! assert!(header.starts_with("sha256="));
+//! ```
+//!
+//! Network errors, 5xx and 429 responses are retried with exponential
+//! backoff.
+
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::{send_with_retry, Alert, Notifier, NotifyError};
+
+/// Header carrying the alert kind.
+pub const EVENT_HEADER: &str = "X-Bugstr-Event";
+
+/// Header carrying the body signature.
+pub const SIGNATURE_HEADER: &str = "X-Bugstr-Signature";
+
+/// POSTs alerts as JSON to a URL.
+pub struct Webhook {
+    url: String,
+    secret: Option<String>,
+    attempts: u32,
+    backoff: Duration,
+    client: reqwest::blocking::Client,
+}
+
+impl Webhook {
+    /// Creates a webhook for `url`, unsigned, trying each alert up to 3
+    /// times starting with a 1 second backoff.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            secret: None,
+            attempts: 3,
+            backoff: Duration::from_secs(1),
+            client: reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("HTTP client"),
+        }
+    }
+
+    /// Signs each body with `secret`.
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Tries each alert up to `attempts` times, waiting `backoff` after the
+    /// first failure and doubling it after each further one.
+    pub fn with_retries(mut self, attempts: u32, backoff: Duration) -> Self {
+        self.attempts = attempts.max(1);
+        self.backoff = backoff;
+        self
+    }
+
+    fn send(&self, kind: &str, body: &[u8]) -> Result<(), NotifyError> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, kind)
+            .body(body.to_vec());
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret, body));
+        }
+
+        let response = request.send()?;
+        if !response.status().is_success() {
+            return Err(NotifyError::Status {
+                status: response.status().as_u16(),
+                body: response.text().unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Notifier for Webhook {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        let body = alert.payload().to_string();
+        send_with_retry(self.attempts, self.backoff, || {
+            self.send(alert.kind.as_str(), body.as_bytes())
+        })
+    }
+}
+
+/// The `X-Bugstr-Signature` value for `body`: `sha256=` followed by the
+/// hex HMAC-SHA256 of the body keyed with `secret`.
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::test_server::serve_once;
+    use crate::notify::{test_alert, AlertKind};
+
+    #[test]
+    fn test_signature() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            signature("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_posts_signed_payload() {
+        let (url, server) = serve_once(204, "");
+        Webhook::new(url)
+            .with_secret("s3cret")
+            .notify(&test_alert(AlertKind::Regression, 4))
+            .unwrap();
+
+        let request = server.join().unwrap();
+        assert_eq!(request.request_line, "POST / HTTP/1.1");
+        assert_eq!(request.header("x-bugstr-event"), Some("regression"));
+        assert_eq!(
+            request.header("x-bugstr-signature"),
+            Some(signature("s3cret", request.body.as_bytes()).as_str())
+        );
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["event"], "regression");
+        assert_eq!(body["group"]["count"], 4);
+        assert_eq!(body["crash"]["message"], "boom");
+    }
+
+    #[test]
+    fn test_retries_server_errors_only() {
+        let (url, first) = serve_once(503, "");
+        let webhook = Webhook::new(&url).with_retries(1, Duration::ZERO);
+        let result = webhook.notify(&test_alert(AlertKind::Regression, 4));
+        first.join().unwrap();
+        assert!(matches!(
+            result,
+            Err(NotifyError::Status { status: 503, .. })
+        ));
+
+        let mut calls = 0;
+        let result = send_with_retry(3, Duration::ZERO, || {
+            calls += 1;
+            Err::<(), _>(NotifyError::Status {
+                status: if calls < 2 { 502 } else { 400 },
+                body: String::new(),
+            })
+        });
+        assert_eq!(calls, 2);
+        assert!(matches!(
+            result,
+            Err(NotifyError::Status { status: 400, .. })
+        ));
+    }
+}
@@ -0,0 +1,282 @@
+//! Versioned crash payload schema shared by senders and receivers.
+//!
+//! [`CrashEvent`] is the structured payload [`CrashSender`](crate::CrashSender)
+//! sends. It keeps the field names of the legacy SDK payload (`message`,
+//! `stack`, `timestamp`, `release`, `environment`), so older receivers still
+//! read it, and adds a `schema_version` that tells newer receivers to
+//! deserialize it natively instead of guessing fields (see
+//! [`parse_crash_content`](crate::parse_crash_content)).
+//!
+//! # Versioning
+//!
+//! Within a schema version fields are only ever added, and every field but
+//! `schema_version` and `message` is optional, so a receiver reads events
+//! of versions [`MIN_CRASH_EVENT_VERSION`] to [`CRASH_EVENT_VERSION`],
+//! ignoring fields it doesn't know. A change that old receivers can't read
+//! safely bumps [`CRASH_EVENT_VERSION`] past what they
+//! [`support`](supports), and they fall back to reading the legacy fields.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::breadcrumbs::Breadcrumb;
+use crate::sender::Attachment;
+
+/// Schema version of the [`CrashEvent`]s this build sends.
+pub const CRASH_EVENT_VERSION: u32 = 1;
+
+/// Oldest schema version this build reads.
+pub const MIN_CRASH_EVENT_VERSION: u32 = 1;
+
+/// Name of the schema version field.
+const VERSION_FIELD: &str = "schema_version";
+
+/// Crash event parsing errors.
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    #[error("No schema_version field; not a structured crash event")]
+    Unversioned,
+
+    #[error("Unsupported crash event schema version {0}")]
+    UnsupportedVersion(u64),
+
+    #[error("Invalid crash event: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Whether this build reads crash events of schema `version`.
+pub fn supports(version: u64) -> bool {
+    (u64::from(MIN_CRASH_EVENT_VERSION)..=u64::from(CRASH_EVENT_VERSION)).contains(&version)
+}
+
+/// A structured crash report payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashEvent {
+    pub schema_version: u32,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exception_type: Option<String>,
+    /// The stack trace as text, as legacy receivers expect it.
+    #[serde(default)]
+    pub stack: String,
+    /// The stack trace as frames, innermost first, when the SDK has them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frames: Vec<Frame>,
+    /// Unix time of the crash in milliseconds.
+    #[serde(default)]
+    pub timestamp: u64,
+    /// `<app name>@<app version>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<DeviceInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breadcrumbs: Vec<Breadcrumb>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+    /// SHA-256 of an app-chosen user ID (lowercase hex), to count affected
+    /// users without learning who they are.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sdk: Option<SdkInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+}
+
+/// One stack frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Frame {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+}
+
+/// The device or host that crashed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl DeviceInfo {
+    /// The OS and architecture this build runs on.
+    pub fn current() -> Self {
+        Self {
+            os: Some(std::env::consts::OS.to_string()),
+            arch: Some(std::env::consts::ARCH.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// The SDK that sent a crash event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SdkInfo {
+    pub name: String,
+    pub version: String,
+}
+
+impl SdkInfo {
+    /// This crate.
+    pub fn bugstr() -> Self {
+        Self {
+            name: "bugstr-rust".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+impl CrashEvent {
+    /// A crash event of the current schema version.
+    pub fn new(message: impl Into<String>, stack: impl Into<String>) -> Self {
+        Self {
+            schema_version: CRASH_EVENT_VERSION,
+            message: message.into(),
+            exception_type: None,
+            stack: stack.into(),
+            frames: Vec::new(),
+            timestamp: 0,
+            release: None,
+            environment: None,
+            device: None,
+            breadcrumbs: Vec::new(),
+            tags: BTreeMap::new(),
+            user_hash: None,
+            sdk: None,
+            attachments: Vec::new(),
+        }
+    }
+
+    /// Reads a crash event from a JSON payload. Fails with
+    /// [`SchemaError::Unversioned`] for legacy payloads.
+    pub fn parse(content: &str) -> Result<Self, SchemaError> {
+        Self::from_value(serde_json::from_str(content)?)
+    }
+
+    /// Reads a crash event from a parsed JSON payload.
+    pub fn from_value(json: serde_json::Value) -> Result<Self, SchemaError> {
+        let version = json
+            .get(VERSION_FIELD)
+            .and_then(|v| v.as_u64())
+            .ok_or(SchemaError::Unversioned)?;
+        if !supports(version) {
+            return Err(SchemaError::UnsupportedVersion(version));
+        }
+        Ok(serde_json::from_value(json)?)
+    }
+
+    /// The stack trace as text: [`stack`](Self::stack), or the frames
+    /// rendered one per line when it's empty.
+    pub fn stack_trace(&self) -> Option<String> {
+        if !self.stack.is_empty() {
+            return Some(self.stack.clone());
+        }
+        if self.frames.is_empty() {
+            return None;
+        }
+        let lines: Vec<String> = self.frames.iter().map(Frame::to_string).collect();
+        Some(lines.join("\n"))
+    }
+}
+
+impl std::fmt::Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let function = match (&self.module, &self.function) {
+            (Some(module), Some(function)) => format!("{}.{}", module, function),
+            (None, Some(function)) => function.clone(),
+            (Some(module), None) => module.clone(),
+            (None, None) => "<unknown>".to_string(),
+        };
+        write!(f, "    at {}", function)?;
+        if let Some(file) = &self.file {
+            write!(f, " ({}", file)?;
+            if let Some(line) = self.line {
+                write!(f, ":{}", line)?;
+                if let Some(column) = self.column {
+                    write!(f, ":{}", column)?;
+                }
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_keeps_legacy_fields() {
+        let mut event = CrashEvent::new("boom", "at main");
+        event.timestamp = 1_700_000_000_000;
+        event.release = Some("app@1.0".to_string());
+        event.tags.insert("flavor".to_string(), "beta".to_string());
+
+        let json: serde_json::Value = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["schema_version"], CRASH_EVENT_VERSION);
+        assert_eq!(json["stack"], "at main");
+        assert_eq!(json["release"], "app@1.0");
+        assert!(json.get("frames").is_none());
+        assert_eq!(CrashEvent::parse(&json.to_string()).unwrap(), event);
+    }
+
+    #[test]
+    fn test_version_negotiation() {
+        assert!(matches!(
+            CrashEvent::parse(r#"{"message": "boom", "stack": ""}"#),
+            Err(SchemaError::Unversioned)
+        ));
+        assert!(matches!(
+            CrashEvent::parse(r#"{"schema_version": 0, "message": "boom"}"#),
+            Err(SchemaError::UnsupportedVersion(0))
+        ));
+        let newer = format!(r#"{{"schema_version": {}, "message": "boom"}}"#, CRASH_EVENT_VERSION + 1);
+        assert!(matches!(
+            CrashEvent::parse(&newer),
+            Err(SchemaError::UnsupportedVersion(v)) if v == u64::from(CRASH_EVENT_VERSION) + 1
+        ));
+        // Fields this build doesn't know are ignored
+        let event = CrashEvent::parse(r#"{"schema_version": 1, "message": "boom", "new_field": 1}"#)
+            .unwrap();
+        assert_eq!(event.message, "boom");
+    }
+
+    #[test]
+    fn test_stack_trace_from_frames() {
+        let mut event = CrashEvent::new("boom", "");
+        assert_eq!(event.stack_trace(), None);
+        event.frames = vec![
+            Frame {
+                function: Some("crash".to_string()),
+                module: Some("app::main".to_string()),
+                file: Some("src/main.rs".to_string()),
+                line: Some(3),
+                column: Some(5),
+            },
+            Frame::default(),
+        ];
+        assert_eq!(
+            event.stack_trace().unwrap(),
+            "    at app::main.crash (src/main.rs:3:5)\n    at <unknown>"
+        );
+    }
+}
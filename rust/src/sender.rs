@@ -0,0 +1,948 @@
+//! Sending crash reports.
+//!
+//! [`CrashSender`] delivers a crash payload to the receiver configured in
+//! [`BugstrConfig`]: it compresses the payload, seals it in a NIP-17 rumor
+//! and gift wraps it (NIP-44/59), then publishes the gift wrap to every
+//! configured relay, retrying relays that fail.
+//!
+//! Payloads up to [`DIRECT_SIZE_LIMIT`] bytes travel directly in a kind
+//! 10420 rumor. Larger ones are split into kind 10422 chunk events of at
+//! most [`CHUNK_SIZE`] bytes, each NIP-44 encrypted to the recipient and
+//! signed by a one-time key, and the gift wrap carries a kind 10421
+//! manifest listing them:
+//!
+//! ```json
+//! {
+//!   "v": 1,
+//!   "root_hash": "<sha256 of the reassembled chunks, hex>",
+//!   "total_size": 180000,
+//!   "chunk_ids": ["<chunk event id>", "..."],
+//!   "relays": ["wss://relay.damus.io"],
+//!   "chunk_relays": {"<chunk event id>": ["wss://relay.damus.io"]},
+//!   "chunk_sizes": [32768, "..."],
+//!   "chunk_hashes": ["<sha256 of the decrypted chunk, hex>", "..."],
+//!   "encoding": "gzip"
+//! }
+//! ```
+//!
+//! Files such as logs, screenshots or minidumps can travel with a report as
+//! [`Attachment`]s, base64 encoded in the payload's `attachments` list (see
+//! [`CrashSender::crash_payload_with_attachments`]). A payload made large by
+//! its attachments is chunked like any other.
+//!
+//! Chunks are published before the manifest, so a receiver never sees a
+//! manifest whose chunks aren't on the relays yet, and the manifest records
+//! which relays accepted each chunk.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_util::{SinkExt, StreamExt};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use zeroize::Zeroizing;
+
+use crate::breadcrumbs::Breadcrumbs;
+use crate::schema::{CrashEvent, DeviceInfo, SdkInfo};
+use crate::compression::{maybe_compress_payload, payload_encoding, CompressionError, DEFAULT_THRESHOLD};
+use crate::event::{EphemeralKeys, EventError, SignedNostrEvent, UnsignedNostrEvent};
+use crate::kinds::{build_direct_rumor, build_manifest_rumor, KIND_CHUNK};
+use crate::nip59::{build_gift_wrap, build_seal, encrypt, Nip59Error};
+use crate::BugstrConfig;
+
+/// Largest payload, after compression, sent directly in the rumor. The
+/// rumor is encrypted twice, so larger payloads would push the gift wrap
+/// past NIP-44's plaintext limit and common relay event size limits.
+pub const DIRECT_SIZE_LIMIT: usize = 32 * 1024;
+
+/// Largest chunk of a chunked payload, in bytes.
+pub const CHUNK_SIZE: usize = 32 * 1024;
+
+/// Crash sending errors.
+#[derive(Debug, Error)]
+pub enum SendError {
+    #[error("No relays configured")]
+    NoRelays,
+
+    #[error("Compression failed: {0}")]
+    Compression(#[from] CompressionError),
+
+    #[error("Event error: {0}")]
+    Event(#[from] EventError),
+
+    #[error("Gift wrap failed: {0}")]
+    Nip59(#[from] Nip59Error),
+
+    #[error("Relay error: {0}")]
+    Relay(String),
+
+    #[error("No relay accepted event {id}: {errors}")]
+    NotAccepted { id: String, errors: String },
+
+    #[error("Payload of {size} bytes exceeds the {max} byte limit")]
+    TooLarge { size: usize, max: usize },
+}
+
+/// How a payload was sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// In the rumor itself.
+    Direct,
+    /// As this many chunk events, listed in a manifest rumor.
+    Chunked { chunks: usize },
+}
+
+/// Chunk manifest carried by a kind 10421 rumor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub v: u8,
+    /// SHA256 of the reassembled chunks (lowercase hex).
+    pub root_hash: String,
+    /// Size of the reassembled chunks in bytes.
+    pub total_size: usize,
+    /// Chunk event IDs, in payload order.
+    pub chunk_ids: Vec<String>,
+    /// Relays the chunks were published to.
+    pub relays: Vec<String>,
+    /// Relays that accepted each chunk, by chunk event ID. Empty when the
+    /// manifest was built before publishing (see [`CrashSender::build`]).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub chunk_relays: BTreeMap<String, Vec<String>>,
+    /// Size in bytes of each decrypted chunk, in payload order. Empty in
+    /// manifests from older senders.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunk_sizes: Vec<usize>,
+    /// SHA256 of each decrypted chunk (lowercase hex), in payload order.
+    /// Empty in manifests from older senders.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunk_hashes: Vec<String>,
+    /// How the reassembled chunks are encoded (see
+    /// [`payload_encoding`]): the compression of the envelope they hold,
+    /// or `identity`. `None` in manifests from older senders, whose
+    /// payloads receivers must sniff.
+    ///
+    /// The chunks hold the JSON envelope, with its compressed bytes base64
+    /// encoded, rather than the raw compressed bytes: NIP-44 only encrypts
+    /// UTF-8 strings, and receivers of every SDK reassemble the envelope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+/// A decrypted chunk that doesn't match its [`Manifest`] entry.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ChunkError {
+    #[error("Chunk {index} holds {size} bytes, expected {expected}")]
+    Size {
+        index: usize,
+        size: usize,
+        expected: usize,
+    },
+
+    #[error("Chunk {index} doesn't match its checksum")]
+    Checksum { index: usize },
+}
+
+impl Manifest {
+    /// Positions of chunk event `id` in the payload. A chunk listed more
+    /// than once fills every position it is listed at.
+    pub fn chunk_indices<'a>(&'a self, id: &'a str) -> impl Iterator<Item = usize> + 'a {
+        self.chunk_ids
+            .iter()
+            .enumerate()
+            .filter(move |(_, chunk_id)| chunk_id.as_str() == id)
+            .map(|(index, _)| index)
+    }
+
+    /// Checks the decrypted chunk at `index` against its size and checksum,
+    /// if the manifest lists them.
+    pub fn check_chunk(&self, index: usize, chunk: &[u8]) -> Result<(), ChunkError> {
+        if let Some(&expected) = self.chunk_sizes.get(index) {
+            if chunk.len() != expected {
+                return Err(ChunkError::Size {
+                    index,
+                    size: chunk.len(),
+                    expected,
+                });
+            }
+        }
+        if let Some(expected) = self.chunk_hashes.get(index) {
+            if !hex::encode(Sha256::digest(chunk)).eq_ignore_ascii_case(expected) {
+                return Err(ChunkError::Checksum { index });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A file sent with a crash report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// File name, unique within the report.
+    pub name: String,
+    pub mime_type: String,
+    /// File contents, base64 encoded in the payload.
+    #[serde(with = "base64_data")]
+    pub data: Vec<u8>,
+}
+
+impl Attachment {
+    pub fn new(name: impl Into<String>, mime_type: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            name: name.into(),
+            mime_type: mime_type.into(),
+            data,
+        }
+    }
+
+    /// Reads the file at `path`, named after the file, with the MIME type
+    /// guessed from its extension.
+    pub fn from_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mime_type = mime_guess::from_path(path).first_or_octet_stream();
+        Ok(Self::new(name, mime_type.essence_str(), std::fs::read(path)?))
+    }
+}
+
+mod base64_data {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64.encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        BASE64.decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Events to publish for one crash report, built by [`CrashSender::build`].
+#[derive(Debug, Clone)]
+pub struct Outgoing {
+    pub transport: Transport,
+    /// Chunk events, empty for direct transport. Published first.
+    pub chunks: Vec<SignedNostrEvent>,
+    /// The gift wrap holding the rumor.
+    pub gift_wrap: SignedNostrEvent,
+}
+
+/// Result of a successful [`CrashSender::send`].
+#[derive(Debug, Clone)]
+pub struct SendReport {
+    pub transport: Transport,
+    /// ID of the published gift wrap.
+    pub gift_wrap_id: String,
+    /// Relays that accepted the gift wrap.
+    pub relays: Vec<String>,
+}
+
+/// Sends crash reports to the receiver in a [`BugstrConfig`].
+///
+/// ```no_run
+/// use bugstr::{BugstrConfig, CrashSender};
+///
+/// # async fn run() -> Result<(), bugstr::SendError> {
+/// let sender = CrashSender::new(BugstrConfig {
+///     recipient_pubkey: "ab".repeat(32),
+///     app_name: "com.example".into(),
+///     app_version: "1.2.0".into(),
+///     ..Default::default()
+/// });
+/// let payload = sender.crash_payload("boom", "thread 'main' panicked at src/main.rs:3:5");
+/// let report = sender.send(&payload).await?;
+/// println!("Sent {} to {} relays", report.gift_wrap_id, report.relays.len());
+/// # Ok(())
+/// # }
+/// ```
+pub struct CrashSender {
+    config: BugstrConfig,
+    secret_key: Option<Zeroizing<[u8; 32]>>,
+    retries: u32,
+    timeout: Duration,
+    breadcrumbs: Option<Breadcrumbs>,
+    tags: BTreeMap<String, String>,
+    user_hash: Option<String>,
+}
+
+impl CrashSender {
+    /// Creates a sender for `config`. Each report is sealed by a fresh key
+    /// unless [`with_secret_key`](Self::with_secret_key) sets one.
+    pub fn new(config: BugstrConfig) -> Self {
+        Self {
+            config,
+            secret_key: None,
+            retries: 3,
+            timeout: Duration::from_secs(10),
+            breadcrumbs: None,
+            tags: BTreeMap::new(),
+            user_hash: None,
+        }
+    }
+
+    /// Adds a `key: value` tag to every crash payload.
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    /// Marks crash payloads as coming from `user_id`. Only its SHA-256 is
+    /// sent, so the receiver can count affected users without learning who
+    /// they are.
+    pub fn with_user_id(mut self, user_id: &str) -> Self {
+        self.user_hash = Some(hex::encode(Sha256::digest(user_id.as_bytes())));
+        self
+    }
+
+    /// Adds the breadcrumbs recorded in `breadcrumbs` to every crash
+    /// payload.
+    pub fn with_breadcrumbs(mut self, breadcrumbs: Breadcrumbs) -> Self {
+        self.breadcrumbs = Some(breadcrumbs);
+        self
+    }
+
+    /// Seals reports with `secret_key`, so the receiver sees the same
+    /// sender for every report.
+    pub fn with_secret_key(mut self, secret_key: [u8; 32]) -> Self {
+        self.secret_key = Some(Zeroizing::new(secret_key));
+        self
+    }
+
+    /// Attempts per relay before giving up on it.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries.max(1);
+        self
+    }
+
+    /// How long to wait for a relay to accept an event.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn config(&self) -> &BugstrConfig {
+        &self.config
+    }
+
+    /// A JSON [`CrashEvent`] payload for `message` and `stack`, readable by
+    /// receivers that expect the other bugstr SDKs' payload. The stack is
+    /// cut to the configured `max_stack_chars` and the release is
+    /// `<app_name>@<app_version>`.
+    pub fn crash_payload(&self, message: &str, stack: &str) -> String {
+        self.crash_payload_with_attachments(message, stack, &[])
+    }
+
+    /// [`crash_payload`](Self::crash_payload) with `attachments`, such as
+    /// logs or a minidump, in its `attachments` list.
+    pub fn crash_payload_with_attachments(
+        &self,
+        message: &str,
+        stack: &str,
+        attachments: &[Attachment],
+    ) -> String {
+        let stack: String = stack.chars().take(self.config.max_stack_chars).collect();
+        let mut event = CrashEvent::new(message, stack);
+        event.timestamp = now() * 1000;
+        event.release = Some(format!("{}@{}", self.config.app_name, self.config.app_version));
+        event.device = Some(DeviceInfo::current());
+        event.breadcrumbs = self.breadcrumbs.as_ref().map(Breadcrumbs::snapshot).unwrap_or_default();
+        event.tags = self.tags.clone();
+        event.user_hash = self.user_hash.clone();
+        event.sdk = Some(SdkInfo::bugstr());
+        event.attachments = attachments.to_vec();
+        serde_json::to_string(&event).expect("JSON serialization failed")
+    }
+
+    /// Builds the events that deliver `payload`, choosing direct or chunked
+    /// transport by its compressed size.
+    pub fn build(&self, payload: &str) -> Result<Outgoing, SendError> {
+        self.build_compressed(maybe_compress_payload(payload, DEFAULT_THRESHOLD)?)
+    }
+
+    /// [`build`](Self::build) for an already compressed payload.
+    fn build_compressed(&self, content: String) -> Result<Outgoing, SendError> {
+        let recipient = &self.config.recipient_pubkey;
+        self.check_size(&content)?;
+        let (transport, chunks, rumor) = if content.len() <= DIRECT_SIZE_LIMIT {
+            let rumor = build_direct_rumor("", recipient.as_str(), now(), content);
+            (Transport::Direct, vec![], rumor)
+        } else {
+            let chunks = build_chunks(&content, recipient)?;
+            let manifest = manifest(&content, &chunks, self.config.relays.clone(), BTreeMap::new());
+            let transport = Transport::Chunked {
+                chunks: chunks.len(),
+            };
+            (transport, chunks, self.manifest_rumor(&manifest))
+        };
+
+        Ok(Outgoing {
+            transport,
+            chunks,
+            gift_wrap: self.gift_wrap(&rumor)?,
+        })
+    }
+
+    /// Builds and publishes `payload` to the configured relays, as chunks
+    /// (see [`publish_chunked_report`](Self::publish_chunked_report)) if it
+    /// is too large to send directly.
+    ///
+    /// Every chunk and the gift wrap must be accepted by at least one relay;
+    /// otherwise the report is not delivered and an error is returned.
+    pub async fn send(&self, payload: &str) -> Result<SendReport, SendError> {
+        if self.config.relays.is_empty() {
+            return Err(SendError::NoRelays);
+        }
+        let content = maybe_compress_payload(payload, DEFAULT_THRESHOLD)?;
+        if content.len() > DIRECT_SIZE_LIMIT {
+            return self.publish_chunked_report(&content).await;
+        }
+        let outgoing = self.build_compressed(content)?;
+        let relays = self.publish_all(&outgoing.gift_wrap).await?;
+        Ok(SendReport {
+            transport: outgoing.transport,
+            gift_wrap_id: outgoing.gift_wrap.id,
+            relays,
+        })
+    }
+
+    /// Publishes `content`, an already compressed payload, as kind 10422
+    /// chunk events, then gift wraps and publishes a manifest listing each
+    /// chunk with the relays that accepted it, so receivers know where to
+    /// fetch it.
+    pub async fn publish_chunked_report(&self, content: &str) -> Result<SendReport, SendError> {
+        if self.config.relays.is_empty() {
+            return Err(SendError::NoRelays);
+        }
+        self.check_size(content)?;
+        let chunks = build_chunks(content, &self.config.recipient_pubkey)?;
+        let mut chunk_relays = BTreeMap::new();
+        for chunk in &chunks {
+            chunk_relays.insert(chunk.id.clone(), self.publish_all(chunk).await?);
+        }
+        let relays = self
+            .config
+            .relays
+            .iter()
+            .filter(|relay| chunk_relays.values().any(|accepted| accepted.contains(relay)))
+            .cloned()
+            .collect();
+
+        let manifest = manifest(content, &chunks, relays, chunk_relays);
+        let gift_wrap = self.gift_wrap(&self.manifest_rumor(&manifest))?;
+        let relays = self.publish_all(&gift_wrap).await?;
+        Ok(SendReport {
+            transport: Transport::Chunked {
+                chunks: chunks.len(),
+            },
+            gift_wrap_id: gift_wrap.id,
+            relays,
+        })
+    }
+
+    fn check_size(&self, content: &str) -> Result<(), SendError> {
+        if content.len() > self.config.max_payload_size {
+            return Err(SendError::TooLarge {
+                size: content.len(),
+                max: self.config.max_payload_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// A kind 10421 rumor carrying `manifest`.
+    fn manifest_rumor(&self, manifest: &Manifest) -> UnsignedNostrEvent {
+        let manifest = serde_json::to_string(manifest).expect("JSON serialization failed");
+        build_manifest_rumor("", self.config.recipient_pubkey.as_str(), now(), manifest)
+    }
+
+    /// Seals `rumor` and gift wraps it to the recipient.
+    fn gift_wrap(&self, rumor: &UnsignedNostrEvent) -> Result<SignedNostrEvent, SendError> {
+        let recipient = &self.config.recipient_pubkey;
+        let keys;
+        let secret_key = match &self.secret_key {
+            Some(secret_key) => secret_key,
+            None => {
+                keys = EphemeralKeys::generate();
+                keys.secret_key()
+            }
+        };
+        let seal = build_seal(rumor, secret_key, recipient)?;
+        Ok(build_gift_wrap(&seal, recipient, None)?)
+    }
+
+    /// Publishes `event` to every relay, returning those that accepted it.
+    async fn publish_all(&self, event: &SignedNostrEvent) -> Result<Vec<String>, SendError> {
+        let publishes = self
+            .config
+            .relays
+            .iter()
+            .map(|relay| async move { (relay, self.publish_with_retry(relay, event).await) });
+        let mut accepted = Vec::new();
+        let mut errors = Vec::new();
+        for (relay, result) in futures_util::future::join_all(publishes).await {
+            match result {
+                Ok(()) => accepted.push(relay.clone()),
+                Err(e) => errors.push(format!("{}: {}", relay, e)),
+            }
+        }
+        if accepted.is_empty() {
+            return Err(SendError::NotAccepted {
+                id: event.id.clone(),
+                errors: errors.join("; "),
+            });
+        }
+        Ok(accepted)
+    }
+
+    async fn publish_with_retry(
+        &self,
+        relay: &str,
+        event: &SignedNostrEvent,
+    ) -> Result<(), SendError> {
+        let mut delay = Duration::from_secs(1);
+        let mut attempt = 1;
+        loop {
+            let result = tokio::time::timeout(self.timeout, publish_event(relay, event))
+                .await
+                .unwrap_or_else(|_| Err(SendError::Relay("timed out".into())));
+            match result {
+                Err(_) if attempt < self.retries => {
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for CrashSender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CrashSender")
+            .field("config", &self.config)
+            .field("retries", &self.retries)
+            .field("timeout", &self.timeout)
+            .field("breadcrumbs", &self.breadcrumbs.as_ref().map(Breadcrumbs::len))
+            .field("tags", &self.tags)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Publishes `event` to `relay_url` and waits for the relay's `OK`.
+pub async fn publish_event(relay_url: &str, event: &SignedNostrEvent) -> Result<(), SendError> {
+    let relay_error = |e: tokio_tungstenite::tungstenite::Error| SendError::Relay(e.to_string());
+    let (ws_stream, _) = connect_async(relay_url).await.map_err(relay_error)?;
+    let (mut write, mut read) = ws_stream.split();
+    write
+        .send(Message::Text(
+            format!(r#"["EVENT",{}]"#, event.to_json()).into(),
+        ))
+        .await
+        .map_err(relay_error)?;
+
+    let mut result = Err(SendError::Relay(
+        "connection closed before the relay replied".into(),
+    ));
+    while let Some(msg) = read.next().await {
+        let Message::Text(text) = msg.map_err(relay_error)? else {
+            continue;
+        };
+        let Ok(msg) = serde_json::from_str::<Vec<serde_json::Value>>(&text) else {
+            continue;
+        };
+        if msg.first().and_then(|v| v.as_str()) == Some("OK")
+            && msg.get(1).and_then(|v| v.as_str()) == Some(event.id.as_str())
+        {
+            result = match msg.get(2).and_then(|v| v.as_bool()) {
+                Some(true) => Ok(()),
+                _ => Err(SendError::Relay(
+                    msg.get(3)
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("rejected")
+                        .to_string(),
+                )),
+            };
+            break;
+        }
+    }
+    let _ = write.send(Message::Close(None)).await;
+    result
+}
+
+/// The manifest of `chunks`, which together hold `content`.
+///
+/// `content` is the payload as sent directly: plaintext or a compressed
+/// JSON envelope, never raw compressed bytes, which NIP-44 can't encrypt.
+/// Its [`encoding`](Manifest::encoding) names the envelope's compression.
+fn manifest(
+    content: &str,
+    chunks: &[SignedNostrEvent],
+    relays: Vec<String>,
+    chunk_relays: BTreeMap<String, Vec<String>>,
+) -> Manifest {
+    let pieces = split_chunks(content, CHUNK_SIZE);
+    Manifest {
+        v: 1,
+        root_hash: hex::encode(Sha256::digest(content.as_bytes())),
+        total_size: content.len(),
+        chunk_ids: chunks.iter().map(|chunk| chunk.id.clone()).collect(),
+        relays,
+        chunk_relays,
+        chunk_sizes: pieces.iter().map(|piece| piece.len()).collect(),
+        chunk_hashes: pieces.iter().map(|piece| hex::encode(Sha256::digest(piece))).collect(),
+        encoding: Some(payload_encoding(content)),
+    }
+}
+
+/// Splits `content` into chunk events of at most [`CHUNK_SIZE`] bytes,
+/// encrypted to `recipient` and signed by one one-time key.
+///
+/// Chunks are encrypted and signed in parallel; the events stay in chunk
+/// order.
+fn build_chunks(content: &str, recipient: &str) -> Result<Vec<SignedNostrEvent>, SendError> {
+    let keys = EphemeralKeys::generate();
+    let created_at = now();
+    split_chunks(content, CHUNK_SIZE)
+        .into_par_iter()
+        .map(|chunk| {
+            let content = encrypt(keys.secret_key(), recipient, chunk)?;
+            let event = UnsignedNostrEvent::new("", created_at, KIND_CHUNK, vec![], content);
+            Ok(keys.sign(&event)?)
+        })
+        .collect()
+}
+
+/// Splits `s` into pieces of at most `size` bytes, on char boundaries.
+fn split_chunks(s: &str, size: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let mut end = size.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time went backwards")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kinds::{KIND_DIRECT, KIND_MANIFEST};
+    use nostr::nips::nip44;
+
+    fn decrypt(recipient: &EphemeralKeys, sender: &str, content: &str) -> String {
+        let secret_key = nostr::SecretKey::from_slice(recipient.secret_key()).unwrap();
+        let sender = nostr::PublicKey::from_hex(sender).unwrap();
+        nip44::decrypt(&secret_key, &sender, content).unwrap()
+    }
+
+    /// The rumor inside `gift_wrap`.
+    fn unwrap(recipient: &EphemeralKeys, gift_wrap: &SignedNostrEvent) -> UnsignedNostrEvent {
+        gift_wrap.verify().unwrap();
+        let seal: SignedNostrEvent =
+            serde_json::from_str(&decrypt(recipient, &gift_wrap.pubkey, &gift_wrap.content))
+                .unwrap();
+        seal.verify().unwrap();
+        serde_json::from_str(&decrypt(recipient, &seal.pubkey, &seal.content)).unwrap()
+    }
+
+    fn sender(recipient: &EphemeralKeys) -> CrashSender {
+        CrashSender::new(BugstrConfig {
+            recipient_pubkey: recipient.public_key().to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_builds_direct_report() {
+        let recipient = EphemeralKeys::generate();
+        let sender = sender(&recipient);
+        let payload = sender.crash_payload("boom", "at main");
+
+        let outgoing = sender.build(&payload).unwrap();
+        assert_eq!(outgoing.transport, Transport::Direct);
+        assert!(outgoing.chunks.is_empty());
+        let rumor = unwrap(&recipient, &outgoing.gift_wrap);
+        assert_eq!(rumor.kind, KIND_DIRECT);
+        assert_eq!(rumor.content, payload);
+        assert_eq!(rumor.tags[0], ["p", recipient.public_key()]);
+        assert!(payload.contains(r#""release":"Unknown@0.0.0""#));
+    }
+
+    /// A stack too large to send directly: hex of a hash chain doesn't
+    /// compress well.
+    fn large_stack() -> String {
+        let mut stack = String::new();
+        let mut hash = Sha256::digest(b"seed");
+        while stack.len() < 150_000 {
+            hash = Sha256::digest(hash);
+            stack.push_str(&hex::encode(hash));
+        }
+        stack
+    }
+
+    #[test]
+    fn test_builds_chunked_report() {
+        let recipient = EphemeralKeys::generate();
+        let sender = sender(&recipient).with_secret_key(*EphemeralKeys::generate().secret_key());
+        let payload = sender.crash_payload("boom", &large_stack());
+
+        let outgoing = sender.build(&payload).unwrap();
+        let rumor = unwrap(&recipient, &outgoing.gift_wrap);
+        assert_eq!(rumor.kind, KIND_MANIFEST);
+        let manifest: Manifest = serde_json::from_str(&rumor.content).unwrap();
+        assert_eq!(
+            outgoing.transport,
+            Transport::Chunked {
+                chunks: manifest.chunk_ids.len()
+            }
+        );
+        assert!(manifest.chunk_ids.len() > 1);
+
+        let mut content = String::new();
+        for (chunk, id) in outgoing.chunks.iter().zip(&manifest.chunk_ids) {
+            chunk.verify().unwrap();
+            assert_eq!(chunk.kind, KIND_CHUNK);
+            assert_eq!(&chunk.id, id);
+            content.push_str(&decrypt(&recipient, &chunk.pubkey, &chunk.content));
+        }
+        assert_eq!(content.len(), manifest.total_size);
+        assert_eq!(
+            hex::encode(Sha256::digest(content.as_bytes())),
+            manifest.root_hash
+        );
+        assert_eq!(manifest.encoding.as_deref(), Some("gzip"));
+        assert_eq!(
+            crate::compression::decode_payload(content, manifest.encoding.as_deref()).unwrap(),
+            payload
+        );
+    }
+
+    #[test]
+    fn test_manifest_chunk_indices() {
+        let manifest = Manifest {
+            v: 1,
+            root_hash: String::new(),
+            total_size: 0,
+            chunk_ids: vec!["a".to_string(), "b".to_string(), "a".to_string()],
+            relays: vec![],
+            chunk_relays: BTreeMap::new(),
+            chunk_sizes: vec![],
+            chunk_hashes: vec![],
+            encoding: None,
+        };
+        assert_eq!(manifest.chunk_indices("a").collect::<Vec<_>>(), [0, 2]);
+        assert_eq!(manifest.chunk_indices("b").collect::<Vec<_>>(), [1]);
+        assert_eq!(manifest.chunk_indices("c").count(), 0);
+    }
+
+    #[test]
+    fn test_many_chunks_round_trip_in_order() {
+        // Distinct chunks, so any reordering shows up in the reassembled text
+        let content: String = (0..40u8)
+            .map(|i| char::from(b'A' + i % 26).to_string().repeat(CHUNK_SIZE))
+            .collect();
+        let recipient = EphemeralKeys::generate();
+        let chunks = build_chunks(&content, recipient.public_key()).unwrap();
+        assert_eq!(chunks.len(), 40);
+        let reassembled: String = chunks
+            .iter()
+            .map(|chunk| decrypt(&recipient, &chunk.pubkey, &chunk.content))
+            .collect();
+        assert_eq!(reassembled, content);
+        let manifest = manifest(&content, &chunks, vec![], BTreeMap::new());
+        for (index, chunk) in chunks.iter().enumerate() {
+            let plaintext = decrypt(&recipient, &chunk.pubkey, &chunk.content);
+            assert!(manifest.check_chunk(index, plaintext.as_bytes()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_manifest_checks_chunks() {
+        let content = "x".repeat(CHUNK_SIZE + 10);
+        let recipient = EphemeralKeys::generate();
+        let chunks = build_chunks(&content, recipient.public_key()).unwrap();
+        let manifest = manifest(&content, &chunks, vec![], BTreeMap::new());
+        assert_eq!(manifest.chunk_sizes, [CHUNK_SIZE, 10]);
+        assert!(manifest.check_chunk(1, &[b'x'; 10]).is_ok());
+        assert_eq!(
+            manifest.check_chunk(1, &[b'x'; 9]),
+            Err(ChunkError::Size { index: 1, size: 9, expected: 10 })
+        );
+        assert_eq!(manifest.check_chunk(1, &[b'y'; 10]), Err(ChunkError::Checksum { index: 1 }));
+
+        // Older manifests have nothing to check against
+        let legacy = Manifest {
+            chunk_sizes: vec![],
+            chunk_hashes: vec![],
+            encoding: None,
+            ..manifest
+        };
+        assert!(legacy.check_chunk(1, b"anything").is_ok());
+    }
+
+    #[test]
+    fn test_payload_attachments() {
+        let recipient = EphemeralKeys::generate();
+        let sender = sender(&recipient);
+        assert!(!sender.crash_payload("boom", "at main").contains("attachments"));
+
+        let log = Attachment::new("app.log", "text/plain", b"started\n".to_vec());
+        let payload =
+            sender.crash_payload_with_attachments("boom", "at main", std::slice::from_ref(&log));
+        let json: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(json["attachments"][0]["data"], "c3RhcnRlZAo=");
+        let attachments: Vec<Attachment> =
+            serde_json::from_value(json["attachments"].clone()).unwrap();
+        assert_eq!(attachments, [log]);
+    }
+
+    #[test]
+    fn test_payload_is_crash_event() {
+        let recipient = EphemeralKeys::generate();
+        let sender = sender(&recipient)
+            .with_tag("flavor", "beta")
+            .with_user_id("alice");
+        let event = CrashEvent::parse(&sender.crash_payload("boom", "at main")).unwrap();
+        assert_eq!(event.schema_version, crate::schema::CRASH_EVENT_VERSION);
+        assert_eq!(event.message, "boom");
+        assert_eq!(event.stack, "at main");
+        assert_eq!(event.tags["flavor"], "beta");
+        assert_eq!(event.user_hash.unwrap(), hex::encode(Sha256::digest(b"alice")));
+        assert_eq!(event.sdk.unwrap().name, "bugstr-rust");
+        assert_eq!(event.device.unwrap().os.as_deref(), Some(std::env::consts::OS));
+    }
+
+    #[test]
+    fn test_payload_breadcrumbs() {
+        let recipient = EphemeralKeys::generate();
+        let breadcrumbs = Breadcrumbs::default();
+        let sender = sender(&recipient).with_breadcrumbs(breadcrumbs.clone());
+        assert!(!sender.crash_payload("boom", "at main").contains("breadcrumbs"));
+
+        breadcrumbs.record("ui", "tapped save", crate::breadcrumbs::Level::Info, Default::default());
+        let payload = sender.crash_payload("boom", "at main");
+        let parsed = crate::breadcrumbs::parse_breadcrumbs(&payload);
+        assert_eq!(parsed, breadcrumbs.snapshot());
+    }
+
+    #[test]
+    fn test_rejects_payloads_over_max_size() {
+        let recipient = EphemeralKeys::generate();
+        let sender = CrashSender::new(BugstrConfig {
+            recipient_pubkey: recipient.public_key().to_string(),
+            max_payload_size: 10,
+            ..Default::default()
+        });
+        assert!(matches!(
+            sender.build(r#"{"message":"boom"}"#),
+            Err(SendError::TooLarge { size: 18, max: 10 })
+        ));
+    }
+
+    #[test]
+    fn test_split_chunks_on_char_boundaries() {
+        assert_eq!(split_chunks("aé b", 2), ["a", "é", " b"]);
+        assert!(split_chunks("", 2).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_publishes_to_relay() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let relay = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let Some(Ok(Message::Text(text))) = ws.next().await else {
+                panic!("expected an EVENT message");
+            };
+            let msg: serde_json::Value = serde_json::from_str(&text).unwrap();
+            let ok = serde_json::json!(["OK", msg[1]["id"], true, ""]);
+            ws.send(Message::Text(ok.to_string().into())).await.unwrap();
+            msg[1]["kind"].as_u64().unwrap()
+        });
+
+        let recipient = EphemeralKeys::generate();
+        let sender = CrashSender::new(BugstrConfig {
+            recipient_pubkey: recipient.public_key().to_string(),
+            relays: vec![relay.clone()],
+            ..Default::default()
+        });
+        let report = sender.send("{}").await.unwrap();
+        assert_eq!(report.transport, Transport::Direct);
+        assert_eq!(report.relays, [relay]);
+        assert_eq!(server.await.unwrap(), 1059);
+    }
+
+    #[tokio::test]
+    async fn test_publishes_chunked_report_with_relay_hints() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let relay = format!("ws://{}", listener.local_addr().unwrap());
+        let (events_tx, mut events_rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            // One connection per published event
+            while let Ok((stream, _)) = listener.accept().await {
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let Some(Ok(Message::Text(text))) = ws.next().await else {
+                    continue;
+                };
+                let msg: serde_json::Value = serde_json::from_str(&text).unwrap();
+                let ok = serde_json::json!(["OK", msg[1]["id"], true, ""]);
+                ws.send(Message::Text(ok.to_string().into())).await.unwrap();
+                events_tx.send(msg[1].clone()).unwrap();
+            }
+        });
+
+        let recipient = EphemeralKeys::generate();
+        let sender = CrashSender::new(BugstrConfig {
+            recipient_pubkey: recipient.public_key().to_string(),
+            relays: vec![relay.clone()],
+            ..Default::default()
+        });
+        let report = sender
+            .send(&sender.crash_payload("boom", &large_stack()))
+            .await
+            .unwrap();
+        let Transport::Chunked { chunks } = report.transport else {
+            panic!("expected chunked transport");
+        };
+
+        let mut published = Vec::new();
+        while let Ok(event) = events_rx.try_recv() {
+            published.push(event);
+        }
+        assert_eq!(published.len(), chunks + 1);
+        // Chunks first, then the manifest
+        assert!(published[..chunks].iter().all(|event| event["kind"] == KIND_CHUNK));
+        let gift_wrap: SignedNostrEvent = serde_json::from_value(published[chunks].clone()).unwrap();
+        assert_eq!(gift_wrap.id, report.gift_wrap_id);
+
+        let manifest: Manifest = serde_json::from_str(&unwrap(&recipient, &gift_wrap).content).unwrap();
+        assert_eq!(manifest.relays, [relay.as_str()]);
+        assert_eq!(manifest.chunk_ids.len(), chunks);
+        for (event, id) in published.iter().zip(&manifest.chunk_ids) {
+            assert_eq!(event["id"], *id);
+            assert_eq!(manifest.chunk_relays[id], [relay.as_str()]);
+        }
+    }
+}
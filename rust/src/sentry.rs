@@ -0,0 +1,532 @@
+//! Sentry compatibility.
+//!
+//! Converts events sent by Sentry SDKs into crash reports, so apps already
+//! instrumented with Sentry can report to a self-hosted bugstr receiver by
+//! changing only their DSN. Both upload formats are accepted: envelopes
+//! (`/envelope/`, used by current SDKs) and single event JSON (`/store/`,
+//! used by older ones), optionally gzip or deflate compressed.
+//!
+//! Only `event` items are stored; sessions, transactions and attachments in
+//! an envelope are skipped. Crashes are identified by the Sentry event ID
+//! (`sentry:<id>`), so SDK retries are no-ops.
+//!
+//! The other direction, [`event_from_crash`] and [`Dsn`], lets the `sentry`
+//! integration forward stored crashes to an existing Sentry project.
+
+use std::io::Read;
+
+use chrono::DateTime;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::storage::CrashReport;
+
+/// Sender recorded for crashes received from Sentry SDKs.
+pub const SENTRY_SENDER: &str = "sentry";
+
+/// Errors from decoding a Sentry upload.
+#[derive(Debug, Error)]
+pub enum SentryError {
+    #[error("Invalid envelope: {0}")]
+    Envelope(String),
+
+    #[error("Invalid event JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Unsupported content encoding: {0}")]
+    Encoding(String),
+
+    #[error("Failed to decompress body: {0}")]
+    Decompress(#[from] std::io::Error),
+
+    #[error("Invalid DSN: {0}")]
+    Dsn(String),
+}
+
+/// Undoes the request's `Content-Encoding` (`gzip`, `deflate` or none).
+pub fn decode_body(encoding: Option<&str>, body: &[u8]) -> Result<Vec<u8>, SentryError> {
+    let mut decoded = Vec::new();
+    match encoding.map(str::trim) {
+        None | Some("") | Some("identity") => decoded.extend_from_slice(body),
+        Some("gzip") => {
+            flate2::read::GzDecoder::new(body).read_to_end(&mut decoded)?;
+        }
+        Some("deflate") => {
+            flate2::read::ZlibDecoder::new(body).read_to_end(&mut decoded)?;
+        }
+        Some(other) => return Err(SentryError::Encoding(other.to_string())),
+    }
+    Ok(decoded)
+}
+
+/// Returns the events in an upload: the event itself for a `/store/` body,
+/// or every `event` item of an envelope.
+pub fn parse_events(body: &[u8]) -> Result<Vec<Value>, SentryError> {
+    // A store body is one JSON document; an envelope is several
+    if let Ok(event) = serde_json::from_slice::<Value>(body) {
+        return Ok(vec![event]);
+    }
+    parse_envelope(body)
+}
+
+/// Returns the payloads of the `event` items in an envelope.
+///
+/// An envelope is a JSON header line followed by items, each a JSON header
+/// line and a payload. The payload is `length` bytes when the header gives
+/// one, else it runs to the end of the line.
+pub fn parse_envelope(body: &[u8]) -> Result<Vec<Value>, SentryError> {
+    let (_header, mut rest) = split_line(body);
+    let mut events = Vec::new();
+    while !rest.is_empty() {
+        let (line, after) = split_line(rest);
+        if line.iter().all(u8::is_ascii_whitespace) {
+            rest = after;
+            continue;
+        }
+        let item: Value = serde_json::from_slice(line)?;
+        let (payload, after) = match item.get("length").and_then(Value::as_u64) {
+            Some(length) => {
+                let length = length as usize;
+                if after.len() < length {
+                    return Err(SentryError::Envelope(format!(
+                        "item claims {} bytes, {} left",
+                        length,
+                        after.len()
+                    )));
+                }
+                let (payload, after) = after.split_at(length);
+                (payload, after.strip_prefix(b"\n").unwrap_or(after))
+            }
+            None => split_line(after),
+        };
+        if item.get("type").and_then(Value::as_str) == Some("event") {
+            events.push(serde_json::from_slice(payload)?);
+        }
+        rest = after;
+    }
+    Ok(events)
+}
+
+fn split_line(data: &[u8]) -> (&[u8], &[u8]) {
+    match data.iter().position(|&b| b == b'\n') {
+        Some(i) => (&data[..i], &data[i + 1..]),
+        None => (data, &[]),
+    }
+}
+
+/// Converts a Sentry event into a crash report, or `None` if it has
+/// neither an exception nor a message.
+///
+/// The stored content is a bugstr crash payload (`message`, `stack`,
+/// `environment`, `release`, ...), with the stack rendered from the
+/// event's exceptions, outermost first. A release of the form
+/// `app@version` fills in the app name and version.
+pub fn crash_from_event(event: &Value, received_at: i64) -> Option<CrashReport> {
+    let exceptions = event
+        .pointer("/exception/values")
+        .or_else(|| event.get("exception"))
+        .and_then(Value::as_array)
+        .map(|values| values.as_slice())
+        .unwrap_or_default();
+    let outermost = exceptions.last();
+
+    let message = outermost
+        .and_then(|e| e.get("value"))
+        .and_then(Value::as_str)
+        .or_else(|| event.pointer("/logentry/formatted").and_then(Value::as_str))
+        .or_else(|| event.pointer("/logentry/message").and_then(Value::as_str))
+        .or_else(|| event.get("message").and_then(Value::as_str))
+        .or_else(|| event.pointer("/message/formatted").and_then(Value::as_str));
+    if exceptions.is_empty() && message.is_none() {
+        return None;
+    }
+
+    let string = |key: &str| event.get(key).and_then(Value::as_str);
+    let mut payload = serde_json::Map::new();
+    let mut insert = |key: &str, value: Option<&str>| {
+        if let Some(value) = value {
+            payload.insert(key.to_string(), Value::from(value));
+        }
+    };
+    insert("message", message);
+    let stack = (!exceptions.is_empty()).then(|| render_stack(exceptions));
+    insert("stack", stack.as_deref());
+    insert("environment", string("environment"));
+    insert("release", string("release"));
+    insert("platform", string("platform"));
+    insert("level", string("level"));
+    insert("sdk", event.pointer("/sdk/name").and_then(Value::as_str));
+    let content = Value::Object(payload).to_string();
+
+    let event_id = match string("event_id") {
+        Some(id) => format!("sentry:{}", id),
+        None => format!("sentry:{}", hex::encode(Sha256::digest(content.as_bytes()))),
+    };
+    let created_at = event
+        .get("timestamp")
+        .and_then(parse_timestamp)
+        .unwrap_or(received_at);
+
+    let mut report = CrashReport::from_content(
+        event_id,
+        SENTRY_SENDER.to_string(),
+        created_at,
+        received_at,
+        content,
+    );
+    if let Some(kind) = outermost
+        .and_then(|e| e.get("type"))
+        .and_then(Value::as_str)
+    {
+        report.exception_type = Some(kind.to_string());
+    }
+    if let Some((app, version)) = report.release.as_deref().and_then(|r| r.split_once('@')) {
+        report.app_name = Some(app.to_string());
+        report.app_version = Some(version.to_string());
+    }
+    Some(report)
+}
+
+/// Renders exceptions (innermost first, as Sentry orders them) as a stack
+/// trace with the outermost exception first and its causes below.
+fn render_stack(exceptions: &[Value]) -> String {
+    let mut lines = Vec::new();
+    for (i, exception) in exceptions.iter().rev().enumerate() {
+        let field = |key: &str| exception.get(key).and_then(Value::as_str);
+        let kind = match (field("module"), field("type")) {
+            (Some(module), Some(kind)) => format!("{}.{}", module, kind),
+            (None, Some(kind)) => kind.to_string(),
+            (_, None) => "Error".to_string(),
+        };
+        let header = match field("value") {
+            Some(value) => format!("{}: {}", kind, value),
+            None => kind,
+        };
+        lines.push(if i == 0 {
+            header
+        } else {
+            format!("Caused by: {}", header)
+        });
+
+        let frames = exception
+            .pointer("/stacktrace/frames")
+            .and_then(Value::as_array)
+            .map(|frames| frames.as_slice())
+            .unwrap_or_default();
+        // Sentry lists frames oldest first
+        for frame in frames.iter().rev() {
+            lines.push(format!("    at {}", render_frame(frame)));
+        }
+    }
+    lines.join("\n")
+}
+
+fn render_frame(frame: &Value) -> String {
+    let field = |key: &str| frame.get(key).and_then(Value::as_str);
+    let number = |key: &str| frame.get(key).and_then(Value::as_u64);
+
+    let function = match (field("module"), field("function")) {
+        (Some(module), Some(function)) => format!("{}.{}", module, function),
+        (None, Some(function)) => function.to_string(),
+        (Some(module), None) => module.to_string(),
+        (None, None) => "?".to_string(),
+    };
+    let Some(file) = field("filename").or_else(|| field("abs_path")) else {
+        return function;
+    };
+    let location = match (number("lineno"), number("colno")) {
+        (Some(line), Some(column)) => format!("{}:{}:{}", file, line, column),
+        (Some(line), None) => format!("{}:{}", file, line),
+        _ => file.to_string(),
+    };
+    format!("{} ({})", function, location)
+}
+
+/// Sentry timestamps are RFC 3339 strings or Unix seconds.
+fn parse_timestamp(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_f64().map(|secs| secs as i64),
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.timestamp()),
+        _ => None,
+    }
+}
+
+/// Extracts the DSN public key from an `X-Sentry-Auth` header value, e.g.
+/// `Sentry sentry_version=7, sentry_key=abc, sentry_client=sentry.python/2.0`.
+pub fn auth_key(header: &str) -> Option<&str> {
+    header
+        .trim()
+        .strip_prefix("Sentry")?
+        .split(',')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == "sentry_key")
+        .map(|(_, value)| value.trim())
+}
+
+/// A parsed Sentry DSN, `https://<key>@<host>/<project>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dsn {
+    /// Public key SDKs authenticate with.
+    pub key: String,
+    /// Scheme, host, port and any path prefix, without a trailing slash.
+    pub base_url: String,
+    pub project_id: String,
+}
+
+impl Dsn {
+    /// Parses a DSN. Legacy DSNs with a secret (`key:secret@`) are accepted;
+    /// the secret is not needed.
+    pub fn parse(dsn: &str) -> Result<Self, SentryError> {
+        let url = reqwest::Url::parse(dsn.trim()).map_err(|e| SentryError::Dsn(e.to_string()))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(SentryError::Dsn(format!(
+                "unsupported scheme {}",
+                url.scheme()
+            )));
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| SentryError::Dsn("missing host".to_string()))?;
+        if url.username().is_empty() {
+            return Err(SentryError::Dsn("missing public key".to_string()));
+        }
+        let path = url.path().trim_end_matches('/');
+        let (prefix, project_id) = path.rsplit_once('/').unwrap_or(("", path));
+        if project_id.is_empty() {
+            return Err(SentryError::Dsn("missing project ID".to_string()));
+        }
+
+        let port = url.port().map(|p| format!(":{}", p)).unwrap_or_default();
+        Ok(Self {
+            key: url.username().to_string(),
+            base_url: format!("{}://{}{}{}", url.scheme(), host, port, prefix),
+            project_id: project_id.to_string(),
+        })
+    }
+
+    /// Where envelopes for this project are posted.
+    pub fn envelope_url(&self) -> String {
+        format!("{}/api/{}/envelope/", self.base_url, self.project_id)
+    }
+
+    /// `X-Sentry-Auth` header value.
+    pub fn auth_header(&self) -> String {
+        format!(
+            "Sentry sentry_version=7, sentry_client=bugstr/{}, sentry_key={}",
+            env!("CARGO_PKG_VERSION"),
+            self.key
+        )
+    }
+}
+
+/// Converts a stored crash into a Sentry error event.
+///
+/// The event ID is the original one for crashes that came from a Sentry SDK
+/// and derived from the bugstr event ID otherwise, so forwarding a crash
+/// twice doesn't duplicate it. Frames are the lines of the symbolicated
+/// stack when there is one, else of the raw stack, which is also sent as
+/// `extra.stack_trace`.
+pub fn event_from_crash(report: &CrashReport, symbolicated: Option<&str>) -> Value {
+    let event_id = report
+        .event_id
+        .strip_prefix("sentry:")
+        .filter(|id| id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(str::to_string)
+        .unwrap_or_else(|| hex::encode(&Sha256::digest(report.event_id.as_bytes())[..16]));
+
+    // Sentry lists frames oldest first
+    let mut frames: Vec<Value> = symbolicated
+        .or(report.stack_trace.as_deref())
+        .unwrap_or_default()
+        .lines()
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .take_while(|line| !line.starts_with("Caused by"))
+        .map(|line| serde_json::json!({ "function": line.strip_prefix("at ").unwrap_or(line) }))
+        .collect();
+    frames.reverse();
+
+    let mut exception = serde_json::json!({
+        "type": report.exception_type.as_deref().unwrap_or("Error"),
+        "value": report.message.as_deref().unwrap_or_default(),
+    });
+    if !frames.is_empty() {
+        exception["stacktrace"] = serde_json::json!({ "frames": frames });
+    }
+
+    let platform = serde_json::from_str::<Value>(&report.raw_content)
+        .ok()
+        .and_then(|content| content.get("platform")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| "other".to_string());
+    let release =
+        report
+            .release
+            .clone()
+            .or_else(|| match (&report.app_name, &report.app_version) {
+                (Some(app), Some(version)) => Some(format!("{}@{}", app, version)),
+                _ => None,
+            });
+
+    let mut event = serde_json::json!({
+        "event_id": event_id,
+        "timestamp": report.created_at,
+        "platform": platform,
+        "level": "fatal",
+        "logger": "bugstr",
+        "exception": { "values": [exception] },
+        "tags": { "bugstr.crash_id": report.id.to_string() },
+        "extra": { "stack_trace": report.stack_trace },
+    });
+    if let Some(release) = release {
+        event["release"] = Value::from(release);
+    }
+    if let Some(environment) = &report.environment {
+        event["environment"] = Value::from(environment.as_str());
+    }
+    event
+}
+
+/// Wraps an event in an envelope for [`Dsn::envelope_url`].
+pub fn envelope(event: &Value) -> Vec<u8> {
+    let payload = event.to_string();
+    let header = serde_json::json!({ "event_id": event["event_id"] });
+    let item = serde_json::json!({
+        "type": "event",
+        "length": payload.len(),
+        "content_type": "application/json",
+    });
+    format!("{}\n{}\n{}\n", header, item, payload).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const EVENT: &str = r#"{
+        "event_id": "9ec79c33ec9942ab8353589fcb2e04dc",
+        "timestamp": "2024-01-02T03:04:05Z",
+        "platform": "java",
+        "release": "com.example.app@2.1.0+45",
+        "environment": "production",
+        "exception": {"values": [
+            {"type": "IOException", "module": "java.io", "value": "disk full"},
+            {"type": "IllegalStateException", "module": "java.lang", "value": "save failed",
+             "stacktrace": {"frames": [
+                {"module": "com.example.Main", "function": "main", "filename": "Main.java", "lineno": 10},
+                {"module": "com.example.Store", "function": "save", "filename": "Store.java", "lineno": 42}
+             ]}}
+        ]}
+    }"#;
+
+    #[test]
+    fn test_crash_from_event() {
+        let event: Value = serde_json::from_str(EVENT).unwrap();
+        let report = crash_from_event(&event, 2000).unwrap();
+
+        assert_eq!(report.event_id, "sentry:9ec79c33ec9942ab8353589fcb2e04dc");
+        assert_eq!(report.sender_pubkey, SENTRY_SENDER);
+        assert_eq!(report.created_at, 1704164645);
+        assert_eq!(
+            report.exception_type.as_deref(),
+            Some("IllegalStateException")
+        );
+        assert_eq!(report.message.as_deref(), Some("save failed"));
+        assert_eq!(report.app_name.as_deref(), Some("com.example.app"));
+        assert_eq!(report.app_version.as_deref(), Some("2.1.0+45"));
+        assert_eq!(report.environment.as_deref(), Some("production"));
+        assert_eq!(
+            report.stack_trace.as_deref(),
+            Some(
+                "java.lang.IllegalStateException: save failed\n\
+                 \x20   at com.example.Store.save (Store.java:42)\n\
+                 \x20   at com.example.Main.main (Main.java:10)\n\
+                 Caused by: java.io.IOException: disk full"
+            )
+        );
+
+        let transaction: Value = serde_json::json!({"type": "transaction", "spans": []});
+        assert!(crash_from_event(&transaction, 2000).is_none());
+    }
+
+    #[test]
+    fn test_parse_envelope_and_store_bodies() {
+        let event = serde_json::to_string(&serde_json::from_str::<Value>(EVENT).unwrap()).unwrap();
+        let session = r#"{"sid":"abc","status":"ok"}"#;
+        let envelope = format!(
+            "{{\"event_id\":\"9ec79c33ec9942ab8353589fcb2e04dc\"}}\n\
+             {{\"type\":\"session\"}}\n{}\n\
+             {{\"type\":\"attachment\",\"length\":5}}\nab\ncd\n\
+             {{\"type\":\"event\",\"length\":{}}}\n{}\n",
+            session,
+            event.len(),
+            event
+        );
+        let events = parse_events(envelope.as_bytes()).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["platform"], "java");
+
+        let events = parse_events(EVENT.as_bytes()).unwrap();
+        assert_eq!(events[0]["platform"], "java");
+
+        let truncated = "{}\n{\"type\":\"event\",\"length\":100}\n{}";
+        assert!(matches!(
+            parse_events(truncated.as_bytes()),
+            Err(SentryError::Envelope(_))
+        ));
+    }
+
+    #[test]
+    fn test_dsn_and_event_round_trip() {
+        let dsn = Dsn::parse("https://abc123@o1.ingest.sentry.io:8443/prefix/42").unwrap();
+        assert_eq!(dsn.key, "abc123");
+        assert_eq!(
+            dsn.envelope_url(),
+            "https://o1.ingest.sentry.io:8443/prefix/api/42/envelope/"
+        );
+        assert!(Dsn::parse("https://o1.ingest.sentry.io/42").is_err());
+        assert!(Dsn::parse("https://abc123@o1.ingest.sentry.io/").is_err());
+
+        let event: Value = serde_json::from_str(EVENT).unwrap();
+        let mut report = crash_from_event(&event, 2000).unwrap();
+        report.id = 7;
+        let forwarded = event_from_crash(&report, None);
+        assert_eq!(forwarded["event_id"], "9ec79c33ec9942ab8353589fcb2e04dc");
+        assert_eq!(forwarded["platform"], "java");
+        assert_eq!(forwarded["release"], "com.example.app@2.1.0+45");
+        assert_eq!(forwarded["tags"]["bugstr.crash_id"], "7");
+
+        // Converting back yields the same crash
+        let events = parse_events(&envelope(&forwarded)).unwrap();
+        let again = crash_from_event(&events[0], 2000).unwrap();
+        assert_eq!(again.exception_type, report.exception_type);
+        assert_eq!(again.message, report.message);
+        assert_eq!(again.created_at, report.created_at);
+        assert!(again
+            .stack_trace
+            .unwrap()
+            .ends_with("at com.example.Store.save (Store.java:42)\n    at com.example.Main.main (Main.java:10)"));
+    }
+
+    #[test]
+    fn test_decode_body_and_auth() {
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(EVENT.as_bytes()).unwrap();
+        let decoded = decode_body(Some("gzip"), &gz.finish().unwrap()).unwrap();
+        assert_eq!(decoded, EVENT.as_bytes());
+        assert!(matches!(
+            decode_body(Some("br"), b""),
+            Err(SentryError::Encoding(_))
+        ));
+
+        assert_eq!(
+            auth_key("Sentry sentry_version=7, sentry_key=abc123, sentry_client=x/1"),
+            Some("abc123")
+        );
+        assert_eq!(auth_key("Bearer abc123"), None);
+    }
+}
@@ -1,13 +1,21 @@
 //! SQLite storage for crash reports.
 //!
 //! Stores decrypted crash reports with indexing for efficient querying
-//! and grouping by exception type, app version, etc.
+//! and grouping by fingerprint (see [`crate::grouping`]), app version, etc.
 
-use rusqlite::{params, Connection, Result};
+use crate::breadcrumbs::{parse_breadcrumbs, Breadcrumb};
+use crate::grouping::fingerprint;
+use crate::notify::rules::Rule;
+use crate::notify::{AlertKind, Delivery, Integration, IntegrationConfig};
+use crate::schema::CrashEvent;
+use crate::sender::{Attachment, Manifest};
+use crate::symbolication::compare_versions;
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::cmp::Ordering;
 use std::path::Path;
 
 /// A stored crash report.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct CrashReport {
     pub id: i64,
     pub event_id: String,
@@ -22,18 +30,304 @@ pub struct CrashReport {
     pub raw_content: String,
     pub environment: Option<String>,
     pub release: Option<String>,
+    /// Electron process that crashed (`main`, `renderer`, `gpu`, ...).
+    pub process_type: Option<String>,
+    /// Window route or URL the renderer was showing.
+    pub route: Option<String>,
+    /// Key of the crash's group; see [`crate::grouping`]. Computed on
+    /// insert when empty.
+    pub fingerprint: String,
+}
+
+impl CrashReport {
+    /// Builds a report from decrypted crash content, extracting known fields
+    /// with [`parse_crash_content`]. The id is assigned on insert.
+    pub fn from_content(
+        event_id: String,
+        sender_pubkey: String,
+        created_at: i64,
+        received_at: i64,
+        content: String,
+    ) -> Self {
+        let parsed = parse_crash_content(&content);
+        let fingerprint = fingerprint(
+            parsed.exception_type.as_deref(),
+            parsed.stack_trace.as_deref(),
+        );
+        Self {
+            id: 0,
+            event_id,
+            sender_pubkey,
+            received_at,
+            created_at,
+            app_name: parsed.app_name,
+            app_version: parsed.app_version,
+            exception_type: parsed.exception_type,
+            message: parsed.message,
+            stack_trace: parsed.stack_trace,
+            raw_content: content,
+            environment: parsed.environment,
+            release: parsed.release,
+            process_type: parsed.process_type,
+            route: parsed.route,
+            fingerprint,
+        }
+    }
+
+    /// [`fingerprint`](Self::fingerprint), or the one computed from the
+    /// exception type and stack trace when it's empty.
+    pub fn group_key(&self) -> String {
+        if self.fingerprint.is_empty() {
+            fingerprint(self.exception_type.as_deref(), self.stack_trace.as_deref())
+        } else {
+            self.fingerprint.clone()
+        }
+    }
 }
 
-/// A group of crashes by exception type.
+/// A group of crashes with the same fingerprint.
 #[derive(Debug, Clone)]
 pub struct CrashGroup {
+    pub fingerprint: String,
     pub exception_type: String,
     pub count: i64,
     pub first_seen: i64,
     pub last_seen: i64,
     pub app_versions: Vec<String>,
+    pub status: GroupStatus,
+    /// Release in which the group was marked resolved, if any.
+    pub resolved_in_release: Option<String>,
+}
+
+/// Triage status of a crash group.
+///
+/// Groups start out `Open`. Maintainers mark them `Resolved` once a fix
+/// ships or `Ignored` when the crash is not actionable. A resolved group
+/// that crashes again in a release that should contain the fix becomes
+/// `Regressed` (see [`CrashStorage::insert`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupStatus {
+    #[default]
+    Open,
+    Resolved,
+    Ignored,
+    Regressed,
+}
+
+impl GroupStatus {
+    /// Get status name as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupStatus::Open => "open",
+            GroupStatus::Resolved => "resolved",
+            GroupStatus::Ignored => "ignored",
+            GroupStatus::Regressed => "regressed",
+        }
+    }
+
+    /// Parse a status name. Returns `None` for unknown values.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "open" => Some(GroupStatus::Open),
+            "resolved" => Some(GroupStatus::Resolved),
+            "ignored" => Some(GroupStatus::Ignored),
+            "regressed" => Some(GroupStatus::Regressed),
+            _ => None,
+        }
+    }
+}
+
+/// An issue opened for a crash group that is still open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueLink {
+    pub integration_id: i64,
+    pub group_key: String,
+    /// Issue key or link, from [`Delivery::reference`].
+    pub reference: String,
+    /// When the group's status last changed, if ever.
+    pub status_updated_at: Option<i64>,
 }
 
+/// A file received with a crash, without its contents.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct AttachmentInfo {
+    pub name: String,
+    pub mime_type: String,
+    /// Size in bytes.
+    pub size: usize,
+}
+
+/// A chunked crash report whose chunks haven't all been fetched yet.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PendingManifest {
+    /// Gift wrap that carried the manifest; becomes the crash's event ID.
+    pub event_id: String,
+    pub sender_pubkey: String,
+    pub created_at: i64,
+    pub received_at: i64,
+    pub manifest: Manifest,
+    /// Chunks stored so far (see [`CrashStorage::save_chunk`]).
+    pub chunks_fetched: usize,
+    /// Failed attempts to fetch the remaining chunks.
+    pub attempts: u32,
+    /// When the next attempt is due, or `None` while one is running.
+    pub next_retry_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Delay before retrying a chunked report after its first failure; doubled
+/// after each further failure.
+const CHUNK_RETRY_BASE_SECS: i64 = 60;
+
+/// Longest delay between retries of a chunked report.
+const CHUNK_RETRY_MAX_SECS: i64 = 6 * 60 * 60;
+
+/// How long after a chunked report is received its missing chunks are
+/// retried; the chunks fetched so far are then dropped.
+const CHUNK_EXPIRY_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Schema migrations, applied in order. Migration `n` (1-based) brings the
+/// database to `user_version = n`. Never edit a released migration; append
+/// a new one instead.
+const MIGRATIONS: &[&str] = &[
+    // 1: crash reports
+    "CREATE TABLE IF NOT EXISTS crashes (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        event_id TEXT UNIQUE NOT NULL,
+        sender_pubkey TEXT NOT NULL,
+        received_at INTEGER NOT NULL,
+        created_at INTEGER NOT NULL,
+        app_name TEXT,
+        app_version TEXT,
+        exception_type TEXT,
+        message TEXT,
+        stack_trace TEXT,
+        raw_content TEXT NOT NULL,
+        environment TEXT,
+        release TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_crashes_received_at ON crashes(received_at DESC);
+    CREATE INDEX IF NOT EXISTS idx_crashes_exception_type ON crashes(exception_type);
+    CREATE INDEX IF NOT EXISTS idx_crashes_app_version ON crashes(app_version);
+    CREATE INDEX IF NOT EXISTS idx_crashes_sender ON crashes(sender_pubkey);",
+    // 2: group triage status
+    "CREATE TABLE IF NOT EXISTS group_status (
+        group_key TEXT PRIMARY KEY,
+        status TEXT NOT NULL DEFAULT 'open',
+        resolved_in_release TEXT,
+        updated_at INTEGER NOT NULL
+    );",
+    // 3: symbolicated stack traces
+    "ALTER TABLE crashes ADD COLUMN symbolicated_stack TEXT;",
+    // 4: Electron process and window context
+    "ALTER TABLE crashes ADD COLUMN process_type TEXT;
+    ALTER TABLE crashes ADD COLUMN route TEXT;",
+    // 5: issue tracker and notification integrations
+    "CREATE TABLE IF NOT EXISTS integrations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        app_name TEXT,
+        config TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );",
+    // 6: notification delivery log
+    "CREATE TABLE IF NOT EXISTS notification_deliveries (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        integration_id INTEGER NOT NULL,
+        alert_kind TEXT NOT NULL,
+        group_key TEXT NOT NULL,
+        crash_id INTEGER,
+        error TEXT,
+        created_at INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_notification_deliveries_created_at
+        ON notification_deliveries(created_at DESC);",
+    // 7: alert rules and their last firing per group
+    "CREATE TABLE IF NOT EXISTS alert_rules (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        app_name TEXT,
+        condition TEXT NOT NULL,
+        integration_ids TEXT NOT NULL,
+        cooldown_minutes INTEGER NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS rule_firings (
+        rule_id INTEGER NOT NULL,
+        group_key TEXT NOT NULL,
+        fired_at INTEGER NOT NULL,
+        PRIMARY KEY (rule_id, group_key)
+    );",
+    // 8: what each delivery created, e.g. an issue key
+    "ALTER TABLE notification_deliveries ADD COLUMN reference TEXT;",
+    // 9: group by fingerprint; existing crashes are fingerprinted in
+    // `backfill_fingerprints`
+    "ALTER TABLE crashes ADD COLUMN fingerprint TEXT;
+
+    CREATE INDEX IF NOT EXISTS idx_crashes_fingerprint ON crashes(fingerprint);",
+    // 10: the crash that regressed a resolved group
+    "ALTER TABLE group_status ADD COLUMN regressed_by INTEGER;",
+    // 11: newest gift wrap processed from each relay
+    "CREATE TABLE IF NOT EXISTS relay_cursors (
+        relay_url TEXT PRIMARY KEY,
+        last_seen INTEGER NOT NULL
+    );",
+    // 12: chunked reports still being fetched, and the chunks fetched so far
+    "CREATE TABLE IF NOT EXISTS chunk_manifests (
+        root_hash TEXT PRIMARY KEY,
+        event_id TEXT NOT NULL,
+        sender_pubkey TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        received_at INTEGER NOT NULL,
+        manifest TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS chunks (
+        root_hash TEXT NOT NULL,
+        chunk_index INTEGER NOT NULL,
+        content TEXT NOT NULL,
+        PRIMARY KEY (root_hash, chunk_index)
+    );",
+    // 13: retry schedule of chunked reports whose chunks couldn't be fetched
+    "ALTER TABLE chunk_manifests ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE chunk_manifests ADD COLUMN next_retry_at INTEGER;
+    ALTER TABLE chunk_manifests ADD COLUMN last_error TEXT;",
+    // 14: files sent with crashes, removed from their raw content
+    "CREATE TABLE IF NOT EXISTS attachments (
+        crash_id INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        mime_type TEXT NOT NULL,
+        data BLOB NOT NULL,
+        PRIMARY KEY (crash_id, name)
+    );
+
+    CREATE TRIGGER IF NOT EXISTS delete_crash_attachments AFTER DELETE ON crashes
+    BEGIN
+        DELETE FROM attachments WHERE crash_id = OLD.id;
+    END;",
+    // 15: breadcrumbs sent with crashes, as a JSON list
+    "ALTER TABLE crashes ADD COLUMN breadcrumbs TEXT;",
+    // 16: gift wraps `bugstr forward` has handled, and the newest it has
+    // handled from each relay
+    "CREATE TABLE IF NOT EXISTS forwarded_wraps (
+        event_id TEXT PRIMARY KEY,
+        created_at INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS forward_cursors (
+        relay_url TEXT PRIMARY KEY,
+        last_seen INTEGER NOT NULL
+    );",
+];
+
+/// Migration that added fingerprints.
+const FINGERPRINT_MIGRATION: usize = 9;
+
+/// Latest database schema version known to this build.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
 /// SQLite-backed crash report storage.
 pub struct CrashStorage {
     conn: Connection,
@@ -41,57 +335,142 @@ pub struct CrashStorage {
 
 impl CrashStorage {
     /// Opens or creates a crash storage database at the given path.
+    ///
+    /// Applies any pending schema migrations before returning.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let conn = Connection::open(path)?;
-        let storage = Self { conn };
-        storage.init_schema()?;
+        let storage = Self::open_unmigrated(path)?;
+        storage.migrate()?;
         Ok(storage)
     }
 
+    /// Opens a database without applying schema migrations.
+    ///
+    /// Useful for inspecting [`schema_version`](Self::schema_version) or
+    /// migrating explicitly with [`migrate`](Self::migrate). Queries may fail
+    /// until the schema is up to date.
+    pub fn open_unmigrated<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        Ok(Self { conn })
+    }
+
     /// Opens an in-memory database (useful for testing).
     pub fn open_in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
         let storage = Self { conn };
-        storage.init_schema()?;
+        storage.migrate()?;
         Ok(storage)
     }
 
-    fn init_schema(&self) -> Result<()> {
-        self.conn.execute_batch(
-            "
-            CREATE TABLE IF NOT EXISTS crashes (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                event_id TEXT UNIQUE NOT NULL,
-                sender_pubkey TEXT NOT NULL,
-                received_at INTEGER NOT NULL,
-                created_at INTEGER NOT NULL,
-                app_name TEXT,
-                app_version TEXT,
-                exception_type TEXT,
-                message TEXT,
-                stack_trace TEXT,
-                raw_content TEXT NOT NULL,
-                environment TEXT,
-                release TEXT
-            );
+    /// Returns the schema version of the open database.
+    ///
+    /// Version `0` means no migrations have been applied. The latest
+    /// version is [`SCHEMA_VERSION`].
+    pub fn schema_version(&self) -> Result<u32> {
+        self.conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+    }
 
-            CREATE INDEX IF NOT EXISTS idx_crashes_received_at ON crashes(received_at DESC);
-            CREATE INDEX IF NOT EXISTS idx_crashes_exception_type ON crashes(exception_type);
-            CREATE INDEX IF NOT EXISTS idx_crashes_app_version ON crashes(app_version);
-            CREATE INDEX IF NOT EXISTS idx_crashes_sender ON crashes(sender_pubkey);
-            ",
-        )
+    /// Applies pending schema migrations.
+    ///
+    /// Each migration runs in its own transaction and bumps the database's
+    /// `user_version`. Returns the number of migrations applied.
+    pub fn migrate(&self) -> Result<usize> {
+        let current = self.schema_version()? as usize;
+        let mut applied = 0;
+
+        for (index, sql) in MIGRATIONS.iter().enumerate().skip(current) {
+            self.conn.execute_batch(&format!(
+                "BEGIN;\n{}\nPRAGMA user_version = {};\nCOMMIT;",
+                sql,
+                index + 1
+            ))?;
+            applied += 1;
+        }
+        if current < FINGERPRINT_MIGRATION && applied > 0 {
+            self.backfill_fingerprints()?;
+        }
+
+        Ok(applied)
+    }
+
+    /// Fingerprints crashes stored before fingerprints existed, and moves
+    /// group statuses, delivery records and rule cooldowns from exception
+    /// types to the fingerprints of the crashes they covered.
+    fn backfill_fingerprints(&self) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut select = tx.prepare(
+                "SELECT id, exception_type, stack_trace FROM crashes WHERE fingerprint IS NULL",
+            )?;
+            let mut update = tx.prepare("UPDATE crashes SET fingerprint = ?2 WHERE id = ?1")?;
+            let rows = select.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })?;
+            for row in rows {
+                let (id, exception_type, stack_trace) = row?;
+                update.execute(params![
+                    id,
+                    fingerprint(exception_type.as_deref(), stack_trace.as_deref())
+                ])?;
+            }
+        }
+        tx.execute_batch(
+            "INSERT OR IGNORE INTO group_status (group_key, status, resolved_in_release, updated_at)
+                SELECT DISTINCT c.fingerprint, s.status, s.resolved_in_release, s.updated_at
+                FROM group_status s
+                JOIN crashes c ON COALESCE(c.exception_type, 'Unknown') = s.group_key;
+             DELETE FROM group_status
+                WHERE group_key NOT IN (SELECT fingerprint FROM crashes);
+             UPDATE notification_deliveries
+                SET group_key = (SELECT fingerprint FROM crashes WHERE id = crash_id)
+                WHERE crash_id IN (SELECT id FROM crashes);
+             INSERT INTO rule_firings (rule_id, group_key, fired_at)
+                SELECT f.rule_id, c.fingerprint, MAX(f.fired_at)
+                FROM rule_firings f
+                JOIN crashes c ON COALESCE(c.exception_type, 'Unknown') = f.group_key
+                WHERE true
+                GROUP BY f.rule_id, c.fingerprint
+                ON CONFLICT(rule_id, group_key)
+                DO UPDATE SET fired_at = MAX(fired_at, excluded.fired_at);
+             DELETE FROM rule_firings
+                WHERE group_key NOT IN (SELECT fingerprint FROM crashes);",
+        )?;
+        tx.commit()
+    }
+
+    /// Writes a consistent copy of the database to `path`.
+    ///
+    /// Uses SQLite's online backup API, so it is safe to call while a
+    /// running receiver is writing to the database.
+    pub fn backup_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut dest = Connection::open(path)?;
+        let backup = rusqlite::backup::Backup::new(&self.conn, &mut dest)?;
+        backup.run_to_completion(256, std::time::Duration::from_millis(10), None)
     }
 
     /// Inserts a new crash report. Returns the inserted row ID.
     /// If the event_id already exists, returns None (duplicate).
+    ///
+    /// A crash in a `Resolved` group from the group's `resolved_in_release`
+    /// or later moves the group to `Regressed`. Without a release, only
+    /// versions newer than any that crashed before the group was resolved
+    /// count; crashes without a version always do.
     pub fn insert(&self, report: &CrashReport) -> Result<Option<i64>> {
+        let (raw_content, attachments) = split_attachments(&report.raw_content);
+        let breadcrumbs = parse_breadcrumbs(&raw_content);
+        let breadcrumbs = (!breadcrumbs.is_empty())
+            .then(|| serde_json::to_string(&breadcrumbs).expect("JSON serialization failed"));
         let result = self.conn.execute(
             "INSERT OR IGNORE INTO crashes (
                 event_id, sender_pubkey, received_at, created_at,
                 app_name, app_version, exception_type, message,
-                stack_trace, raw_content, environment, release
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                stack_trace, raw_content, environment, release,
+                process_type, route, fingerprint, breadcrumbs
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
             params![
                 report.event_id,
                 report.sender_pubkey,
@@ -102,31 +481,184 @@ impl CrashStorage {
                 report.exception_type,
                 report.message,
                 report.stack_trace,
-                report.raw_content,
+                raw_content,
                 report.environment,
                 report.release,
+                report.process_type,
+                report.route,
+                report.group_key(),
+                breadcrumbs,
             ],
         )?;
 
         if result == 0 {
-            Ok(None) // Duplicate
-        } else {
-            Ok(Some(self.conn.last_insert_rowid()))
+            return Ok(None); // Duplicate
         }
+        let id = self.conn.last_insert_rowid();
+        for attachment in attachments {
+            self.conn.execute(
+                "INSERT OR REPLACE INTO attachments (crash_id, name, mime_type, data)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![id, attachment.name, attachment.mime_type, attachment.data],
+            )?;
+        }
+        self.detect_regression(id, report)?;
+        Ok(Some(id))
+    }
+
+    /// Moves the group of crash `id` from `Resolved` to `Regressed` if the
+    /// crash's version should contain the fix (see [`insert`](Self::insert)).
+    fn detect_regression(&self, id: i64, report: &CrashReport) -> Result<()> {
+        let group_key = report.group_key();
+        let state: Option<(String, Option<String>, i64)> = self
+            .conn
+            .query_row(
+                "SELECT status, resolved_in_release, updated_at FROM group_status
+                 WHERE group_key = ?1",
+                [&group_key],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        let Some((status, fixed_in, resolved_at)) = state else {
+            return Ok(());
+        };
+        if GroupStatus::parse(&status) != Some(GroupStatus::Resolved) {
+            return Ok(());
+        }
+
+        let regressed = match (report.app_version.as_deref(), fixed_in) {
+            (None, _) => true,
+            (Some(version), Some(fixed_in)) => {
+                compare_versions(version, &fixed_in) != Ordering::Less
+            }
+            (Some(version), None) => {
+                let mut stmt = self.conn.prepare(
+                    "SELECT DISTINCT app_version FROM crashes
+                     WHERE fingerprint = ?1 AND received_at <= ?2 AND id != ?3
+                        AND app_version IS NOT NULL",
+                )?;
+                let versions = stmt
+                    .query_map(params![group_key, resolved_at, id], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>>>()?;
+                versions
+                    .iter()
+                    .all(|seen| compare_versions(version, seen) == Ordering::Greater)
+            }
+        };
+        if regressed {
+            self.conn.execute(
+                "UPDATE group_status SET status = ?2, regressed_by = ?3, updated_at = ?4
+                 WHERE group_key = ?1",
+                params![group_key, GroupStatus::Regressed.as_str(), id, report.received_at],
+            )?;
+        }
+        Ok(())
     }
 
     /// Gets recent crash reports, ordered by received_at descending.
     pub fn get_recent(&self, limit: usize) -> Result<Vec<CrashReport>> {
+        self.get_recent_with_status(limit, None)
+    }
+
+    /// Gets recent crash reports, only those whose group has `status` if
+    /// given, ordered by received_at descending.
+    pub fn get_recent_with_status(
+        &self,
+        limit: usize,
+        status: Option<GroupStatus>,
+    ) -> Result<Vec<CrashReport>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.id, c.event_id, c.sender_pubkey, c.received_at, c.created_at,
+                    c.app_name, c.app_version, c.exception_type, c.message,
+                    c.stack_trace, c.raw_content, c.environment, c.release,
+                    c.process_type, c.route, COALESCE(c.fingerprint, '')
+             FROM crashes c
+             LEFT JOIN group_status s ON s.group_key = c.fingerprint
+             WHERE ?2 IS NULL OR COALESCE(s.status, 'open') = ?2
+             ORDER BY c.received_at DESC
+             LIMIT ?1",
+        )?;
+
+        let rows = stmt.query_map(params![limit, status.map(|s| s.as_str())], |row| {
+            Ok(CrashReport {
+                id: row.get(0)?,
+                event_id: row.get(1)?,
+                sender_pubkey: row.get(2)?,
+                received_at: row.get(3)?,
+                created_at: row.get(4)?,
+                app_name: row.get(5)?,
+                app_version: row.get(6)?,
+                exception_type: row.get(7)?,
+                message: row.get(8)?,
+                stack_trace: row.get(9)?,
+                raw_content: row.get(10)?,
+                environment: row.get(11)?,
+                release: row.get(12)?,
+                process_type: row.get(13)?,
+                route: row.get(14)?,
+                fingerprint: row.get(15)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    /// Gets crash reports received at or after `since`, ordered by id.
+    pub fn get_received_since(&self, since: i64) -> Result<Vec<CrashReport>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, event_id, sender_pubkey, received_at, created_at,
                     app_name, app_version, exception_type, message,
-                    stack_trace, raw_content, environment, release
+                    stack_trace, raw_content, environment, release,
+                    process_type, route, COALESCE(fingerprint, '')
              FROM crashes
-             ORDER BY received_at DESC
-             LIMIT ?1",
+             WHERE received_at >= ?1
+             ORDER BY id",
         )?;
 
-        let rows = stmt.query_map([limit], |row| {
+        let rows = stmt.query_map([since], |row| {
+            Ok(CrashReport {
+                id: row.get(0)?,
+                event_id: row.get(1)?,
+                sender_pubkey: row.get(2)?,
+                received_at: row.get(3)?,
+                created_at: row.get(4)?,
+                app_name: row.get(5)?,
+                app_version: row.get(6)?,
+                exception_type: row.get(7)?,
+                message: row.get(8)?,
+                stack_trace: row.get(9)?,
+                raw_content: row.get(10)?,
+                environment: row.get(11)?,
+                release: row.get(12)?,
+                process_type: row.get(13)?,
+                route: row.get(14)?,
+                fingerprint: row.get(15)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    /// Gets crashes with a stack trace, optionally filtered by app name and
+    /// version, ordered by id.
+    pub fn get_with_stack_trace(
+        &self,
+        app_name: Option<&str>,
+        app_version: Option<&str>,
+    ) -> Result<Vec<CrashReport>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, event_id, sender_pubkey, received_at, created_at,
+                    app_name, app_version, exception_type, message,
+                    stack_trace, raw_content, environment, release,
+                    process_type, route, COALESCE(fingerprint, '')
+             FROM crashes
+             WHERE stack_trace IS NOT NULL
+               AND (?1 IS NULL OR app_name = ?1)
+               AND (?2 IS NULL OR app_version = ?2)
+             ORDER BY id",
+        )?;
+
+        let rows = stmt.query_map(params![app_name, app_version], |row| {
             Ok(CrashReport {
                 id: row.get(0)?,
                 event_id: row.get(1)?,
@@ -141,57 +673,843 @@ impl CrashStorage {
                 raw_content: row.get(10)?,
                 environment: row.get(11)?,
                 release: row.get(12)?,
+                process_type: row.get(13)?,
+                route: row.get(14)?,
+                fingerprint: row.get(15)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    /// Gets the stored symbolicated stack trace for a crash, if any.
+    pub fn get_symbolicated_stack(&self, id: i64) -> Result<Option<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT symbolicated_stack FROM crashes WHERE id = ?1")?;
+        let mut rows = stmt.query_map([id], |row| row.get(0))?;
+        Ok(rows.next().transpose()?.flatten())
+    }
+
+    /// Stores (or clears) the symbolicated stack trace for a crash.
+    ///
+    /// Returns `false` if no crash has the given id.
+    pub fn set_symbolicated_stack(&self, id: i64, stack: Option<&str>) -> Result<bool> {
+        let updated = self.conn.execute(
+            "UPDATE crashes SET symbolicated_stack = ?2 WHERE id = ?1",
+            params![id, stack],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Gets crash groups aggregated by fingerprint.
+    pub fn get_groups(&self, limit: usize) -> Result<Vec<CrashGroup>> {
+        self.get_groups_with_status(limit, None)
+    }
+
+    /// Gets crash groups aggregated by fingerprint, only those with
+    /// `status` if given.
+    pub fn get_groups_with_status(
+        &self,
+        limit: usize,
+        status: Option<GroupStatus>,
+    ) -> Result<Vec<CrashGroup>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT
+                g.exc_type, g.count, g.first_seen, g.last_seen, g.versions,
+                s.status, s.resolved_in_release, COALESCE(g.fingerprint, '')
+             FROM (
+                SELECT
+                    fingerprint,
+                    COALESCE(MAX(exception_type), 'Unknown') as exc_type,
+                    COUNT(*) as count,
+                    MIN(received_at) as first_seen,
+                    MAX(received_at) as last_seen,
+                    GROUP_CONCAT(DISTINCT app_version) as versions
+                FROM crashes
+                GROUP BY fingerprint
+             ) g
+             LEFT JOIN group_status s ON s.group_key = g.fingerprint
+             WHERE ?2 IS NULL OR COALESCE(s.status, 'open') = ?2
+             ORDER BY g.count DESC
+             LIMIT ?1",
+        )?;
+
+        let rows = stmt.query_map(params![limit, status.map(|s| s.as_str())], |row| {
+            let versions_str: Option<String> = row.get(4)?;
+            let app_versions = versions_str
+                .map(|s| s.split(',').map(String::from).collect())
+                .unwrap_or_default();
+            let status: Option<String> = row.get(5)?;
+
+            Ok(CrashGroup {
+                fingerprint: row.get(7)?,
+                exception_type: row.get(0)?,
+                count: row.get(1)?,
+                first_seen: row.get(2)?,
+                last_seen: row.get(3)?,
+                app_versions,
+                status: status.as_deref().and_then(GroupStatus::parse).unwrap_or_default(),
+                resolved_in_release: row.get(6)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    /// Sets the triage status of a crash group.
+    ///
+    /// `group_key` is the group's fingerprint as returned by
+    /// [`get_groups`](Self::get_groups). `resolved_in_release` records the
+    /// release containing the fix and is only kept for `Resolved`.
+    ///
+    /// Returns `false` if no crashes belong to the group.
+    pub fn set_group_status(
+        &self,
+        group_key: &str,
+        status: GroupStatus,
+        resolved_in_release: Option<&str>,
+        updated_at: i64,
+    ) -> Result<bool> {
+        let exists: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM crashes WHERE fingerprint = ?1)",
+            [group_key],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Ok(false);
+        }
+
+        let resolved_in_release = match status {
+            GroupStatus::Resolved => resolved_in_release,
+            _ => None,
+        };
+        self.conn.execute(
+            "INSERT INTO group_status (group_key, status, resolved_in_release, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(group_key) DO UPDATE SET
+                status = excluded.status,
+                resolved_in_release = excluded.resolved_in_release,
+                regressed_by = NULL,
+                updated_at = excluded.updated_at",
+            params![group_key, status.as_str(), resolved_in_release, updated_at],
+        )?;
+
+        Ok(true)
+    }
+
+    /// Gets the triage status of a crash group. Groups without a stored
+    /// status are `Open`.
+    pub fn get_group_status(&self, group_key: &str) -> Result<GroupStatus> {
+        let status: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT status FROM group_status WHERE group_key = ?1",
+                [group_key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(status.as_deref().and_then(GroupStatus::parse).unwrap_or_default())
+    }
+
+    /// The crash that moved a group to `Regressed`, if it is regressed.
+    pub fn regressed_by(&self, group_key: &str) -> Result<Option<i64>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT regressed_by FROM group_status WHERE group_key = ?1 AND status = ?2",
+                params![group_key, GroupStatus::Regressed.as_str()],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten())
+    }
+
+    /// Number of crashes in a group (see [`get_groups`](Self::get_groups)).
+    ///
+    /// A count of 1 right after [`insert`](Self::insert) means the crash
+    /// started a new group.
+    pub fn group_count(&self, group_key: &str) -> Result<i64> {
+        self.conn.query_row(
+            "SELECT COUNT(*) FROM crashes WHERE fingerprint = ?1",
+            [group_key],
+            |row| row.get(0),
+        )
+    }
+
+    /// Number of crashes in a group received at or after `since`.
+    pub fn group_count_since(&self, group_key: &str, since: i64) -> Result<i64> {
+        self.conn.query_row(
+            "SELECT COUNT(*) FROM crashes
+             WHERE fingerprint = ?1 AND received_at >= ?2",
+            params![group_key, since],
+            |row| row.get(0),
+        )
+    }
+
+    /// Crashes per app and hour received in `[since, until)`, as
+    /// `(app_name, hour, count)` with hours numbered from `since`. Hours
+    /// without crashes are left out.
+    pub fn hourly_app_counts(
+        &self,
+        since: i64,
+        until: i64,
+    ) -> Result<Vec<(Option<String>, i64, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT app_name, (received_at - ?1) / 3600 AS hour, COUNT(*)
+             FROM crashes
+             WHERE received_at >= ?1 AND received_at < ?2
+             GROUP BY app_name, hour
+             ORDER BY app_name, hour",
+        )?;
+        let rows = stmt.query_map([since, until], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?;
+
+        rows.collect()
+    }
+
+    /// Groups of an app's crashes received at or after `since`, most
+    /// crashes first, as `(group_key, exception_type, count,
+    /// latest_crash_id)`.
+    pub fn app_group_counts_since(
+        &self,
+        app_name: Option<&str>,
+        since: i64,
+    ) -> Result<Vec<(String, String, i64, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT fingerprint, COALESCE(MAX(exception_type), 'Unknown'), COUNT(*), MAX(id)
+             FROM crashes
+             WHERE app_name IS ?1 AND received_at >= ?2
+             GROUP BY fingerprint
+             ORDER BY COUNT(*) DESC, fingerprint",
+        )?;
+        let rows = stmt.query_map(params![app_name, since], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?;
+
+        rows.collect()
+    }
+
+    /// Adds an integration for one app, or for every app when `app_name`
+    /// is `None`. Returns its ID.
+    pub fn add_integration(
+        &self,
+        app_name: Option<&str>,
+        config: &IntegrationConfig,
+        created_at: i64,
+    ) -> Result<i64> {
+        let config = serde_json::to_string(config)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.conn.execute(
+            "INSERT INTO integrations (app_name, config, created_at) VALUES (?1, ?2, ?3)",
+            params![app_name, config, created_at],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Gets all integrations, oldest first.
+    ///
+    /// Integrations this build doesn't understand (added by a newer bugstr)
+    /// are skipped.
+    pub fn get_integrations(&self) -> Result<Vec<Integration>> {
+        self.query_integrations(
+            "SELECT id, app_name, config, created_at FROM integrations ORDER BY id",
+            [],
+        )
+    }
+
+    /// Gets the integrations that apply to crashes from `app_name`: those
+    /// configured for that app and those configured for every app.
+    pub fn integrations_for_app(&self, app_name: Option<&str>) -> Result<Vec<Integration>> {
+        self.query_integrations(
+            "SELECT id, app_name, config, created_at FROM integrations
+             WHERE app_name IS NULL OR app_name = ?1
+             ORDER BY id",
+            [app_name],
+        )
+    }
+
+    /// Adds a webhook integration for every app, or updates the secret and
+    /// events of the one already sending to `url`. Returns its ID.
+    ///
+    /// Lets `bugstr serve --webhook-url` register its webhook on every start
+    /// without piling up copies.
+    pub fn ensure_webhook(
+        &self,
+        url: &str,
+        secret: Option<&str>,
+        events: &[AlertKind],
+        created_at: i64,
+    ) -> Result<i64> {
+        let config = IntegrationConfig::Webhook {
+            url: url.to_string(),
+            secret: secret.map(String::from),
+            events: events.to_vec(),
+        };
+        let existing = self.get_integrations()?.into_iter().find(|integration| {
+            integration.app_name.is_none()
+                && matches!(&integration.config, IntegrationConfig::Webhook { url: u, .. } if u == url)
+        });
+        let Some(existing) = existing else {
+            return self.add_integration(None, &config, created_at);
+        };
+
+        if existing.config != config {
+            let config = serde_json::to_string(&config)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+            self.conn.execute(
+                "UPDATE integrations SET config = ?2 WHERE id = ?1",
+                params![existing.id, config],
+            )?;
+        }
+        Ok(existing.id)
+    }
+
+    /// Deletes an integration. Returns `false` if it didn't exist.
+    pub fn delete_integration(&self, id: i64) -> Result<bool> {
+        let deleted = self.conn.execute("DELETE FROM integrations WHERE id = ?1", [id])?;
+        Ok(deleted > 0)
+    }
+
+    /// Adds an alert rule. Its `id` is ignored; returns the new ID.
+    pub fn add_rule(&self, rule: &Rule) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO alert_rules
+                (name, app_name, condition, integration_ids, cooldown_minutes, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                rule.name,
+                rule.app_name,
+                to_json(&rule.condition)?,
+                to_json(&rule.integration_ids)?,
+                rule.cooldown_minutes,
+                rule.created_at,
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Gets all alert rules, oldest first. Rules this build doesn't
+    /// understand are skipped.
+    pub fn get_rules(&self) -> Result<Vec<Rule>> {
+        self.query_rules(
+            "SELECT id, name, app_name, condition, integration_ids, cooldown_minutes, created_at
+             FROM alert_rules ORDER BY id",
+            [],
+        )
+    }
+
+    /// Gets the rules that watch crashes from `app_name`: those for that
+    /// app and those for every app.
+    pub fn rules_for_app(&self, app_name: Option<&str>) -> Result<Vec<Rule>> {
+        self.query_rules(
+            "SELECT id, name, app_name, condition, integration_ids, cooldown_minutes, created_at
+             FROM alert_rules
+             WHERE app_name IS NULL OR app_name = ?1
+             ORDER BY id",
+            [app_name],
+        )
+    }
+
+    /// Deletes an alert rule. Returns `false` if it didn't exist.
+    pub fn delete_rule(&self, id: i64) -> Result<bool> {
+        self.conn.execute("DELETE FROM rule_firings WHERE rule_id = ?1", [id])?;
+        let deleted = self.conn.execute("DELETE FROM alert_rules WHERE id = ?1", [id])?;
+        Ok(deleted > 0)
+    }
+
+    /// When a rule last fired for a group, if ever.
+    pub fn rule_last_fired(&self, rule_id: i64, group_key: &str) -> Result<Option<i64>> {
+        self.conn
+            .query_row(
+                "SELECT fired_at FROM rule_firings WHERE rule_id = ?1 AND group_key = ?2",
+                params![rule_id, group_key],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Records that a rule fired for a group at `fired_at`.
+    pub fn set_rule_fired(&self, rule_id: i64, group_key: &str, fired_at: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO rule_firings (rule_id, group_key, fired_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(rule_id, group_key) DO UPDATE SET fired_at = excluded.fired_at",
+            params![rule_id, group_key, fired_at],
+        )?;
+        Ok(())
+    }
+
+    fn query_rules<P: rusqlite::Params>(&self, sql: &str, params: P) -> Result<Vec<Rule>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map(params, |row| {
+            let condition: String = row.get(3)?;
+            let integration_ids: String = row.get(4)?;
+            let (Ok(condition), Ok(integration_ids)) = (
+                serde_json::from_str(&condition),
+                serde_json::from_str(&integration_ids),
+            ) else {
+                return Ok(None);
+            };
+            Ok(Some(Rule {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                app_name: row.get(2)?,
+                condition,
+                integration_ids,
+                cooldown_minutes: row.get(5)?,
+                created_at: row.get(6)?,
+            }))
+        })?;
+
+        rows.filter_map(Result::transpose).collect()
+    }
+
+    pub fn record_delivery(&self, delivery: &Delivery) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO notification_deliveries
+                (integration_id, alert_kind, group_key, crash_id, error, reference,
+                 created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                delivery.integration_id,
+                delivery.alert_kind.as_str(),
+                delivery.group_key,
+                delivery.crash_id,
+                delivery.error,
+                delivery.reference,
+                delivery.created_at,
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Gets recent deliveries, newest first.
+    pub fn get_deliveries(&self, limit: usize) -> Result<Vec<Delivery>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT integration_id, alert_kind, group_key, crash_id, error, created_at,
+                    reference
+             FROM notification_deliveries
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?1",
+        )?;
+        let rows = stmt.query_map([limit], |row| {
+            // Skip kinds added by a newer bugstr
+            let alert_kind: String = row.get(1)?;
+            let Some(alert_kind) = AlertKind::parse(&alert_kind) else {
+                return Ok(None);
+            };
+            Ok(Some(Delivery {
+                integration_id: row.get(0)?,
+                alert_kind,
+                group_key: row.get(2)?,
+                crash_id: row.get(3)?,
+                error: row.get(4)?,
+                reference: row.get(6)?,
+                created_at: row.get(5)?,
+            }))
+        })?;
+
+        rows.filter_map(Result::transpose).collect()
+    }
+
+    /// Gets the issues an integration opened for groups that are still open.
+    pub fn issue_links(&self, integration_id: i64) -> Result<Vec<IssueLink>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT d.group_key, d.reference, s.updated_at
+             FROM notification_deliveries d
+             LEFT JOIN group_status s ON s.group_key = d.group_key
+             WHERE d.integration_id = ?1 AND d.reference IS NOT NULL
+               AND COALESCE(s.status, 'open') = 'open'
+             GROUP BY d.group_key, d.reference
+             ORDER BY MIN(d.created_at)",
+        )?;
+        let rows = stmt.query_map([integration_id], |row| {
+            Ok(IssueLink {
+                integration_id,
+                group_key: row.get(0)?,
+                reference: row.get(1)?,
+                status_updated_at: row.get(2)?,
             })
         })?;
 
-        rows.collect()
+        rows.collect()
+    }
+
+    fn query_integrations<P: rusqlite::Params>(
+        &self,
+        sql: &str,
+        params: P,
+    ) -> Result<Vec<Integration>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map(params, |row| {
+            let config: String = row.get(2)?;
+            Ok((row.get(0)?, row.get(1)?, config, row.get(3)?))
+        })?;
+
+        let mut integrations = Vec::new();
+        for row in rows {
+            let (id, app_name, config, created_at) = row?;
+            if let Ok(config) = serde_json::from_str(&config) {
+                integrations.push(Integration {
+                    id,
+                    app_name,
+                    config,
+                    created_at,
+                });
+            }
+        }
+        Ok(integrations)
+    }
+
+    /// Gets total crash count.
+    pub fn count(&self) -> Result<i64> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM crashes", [], |row| row.get(0))
+    }
+
+    /// Deletes crashes older than the given timestamp.
+    pub fn delete_older_than(&self, timestamp: i64) -> Result<usize> {
+        self.conn.execute(
+            "DELETE FROM crashes WHERE received_at < ?1",
+            [timestamp],
+        )
+    }
+
+    /// Deletes crashes received more than `retention_days` before `now`,
+    /// and vacuums the database so their content doesn't linger in free
+    /// pages. Returns the number of crashes deleted.
+    pub fn expire(&self, retention_days: u32, now: i64) -> Result<usize> {
+        let deleted = self.delete_older_than(now - i64::from(retention_days) * 24 * 60 * 60)?;
+        if deleted > 0 {
+            self.conn.execute_batch("VACUUM")?;
+        }
+        Ok(deleted)
+    }
+
+    /// Gets the `created_at` of the newest gift wrap processed from `relay_url`.
+    pub fn relay_last_seen(&self, relay_url: &str) -> Result<Option<i64>> {
+        self.conn
+            .query_row(
+                "SELECT last_seen FROM relay_cursors WHERE relay_url = ?1",
+                [relay_url],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Records a gift wrap processed from `relay_url`. The cursor only moves
+    /// forward, since relays don't deliver events in `created_at` order.
+    pub fn set_relay_last_seen(&self, relay_url: &str, created_at: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO relay_cursors (relay_url, last_seen) VALUES (?1, ?2)
+             ON CONFLICT(relay_url) DO UPDATE SET last_seen = MAX(last_seen, excluded.last_seen)",
+            params![relay_url, created_at],
+        )?;
+        Ok(())
+    }
+
+    /// Gets the `created_at` of the newest gift wrap `bugstr forward` handled
+    /// from `relay_url`. Kept apart from [`relay_last_seen`](Self::relay_last_seen),
+    /// so `serve` and `forward` can share a database.
+    pub fn forward_last_seen(&self, relay_url: &str) -> Result<Option<i64>> {
+        self.conn
+            .query_row(
+                "SELECT last_seen FROM forward_cursors WHERE relay_url = ?1",
+                [relay_url],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Records a gift wrap `bugstr forward` handled from `relay_url`. The
+    /// cursor only moves forward.
+    pub fn set_forward_last_seen(&self, relay_url: &str, created_at: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO forward_cursors (relay_url, last_seen) VALUES (?1, ?2)
+             ON CONFLICT(relay_url) DO UPDATE SET last_seen = MAX(last_seen, excluded.last_seen)",
+            params![relay_url, created_at],
+        )?;
+        Ok(())
+    }
+
+    /// Whether the gift wrap `event_id` was already forwarded (or can't be).
+    pub fn is_forwarded(&self, event_id: &str) -> Result<bool> {
+        self.conn
+            .query_row("SELECT 1 FROM forwarded_wraps WHERE event_id = ?1", [event_id], |_| Ok(()))
+            .optional()
+            .map(|row| row.is_some())
+    }
+
+    /// Records that the gift wrap `event_id`, created at `created_at`, needs
+    /// no forwarding again.
+    pub fn set_forwarded(&self, event_id: &str, created_at: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO forwarded_wraps (event_id, created_at) VALUES (?1, ?2)",
+            params![event_id, created_at],
+        )?;
+        Ok(())
+    }
+
+    /// Forgets forwarded gift wraps created before `before`, which relays
+    /// won't deliver again. Returns how many were forgotten.
+    pub fn prune_forwarded(&self, before: i64) -> Result<usize> {
+        self.conn
+            .execute("DELETE FROM forwarded_wraps WHERE created_at < ?1", [before])
+    }
+
+    /// Remembers a chunked report until its chunks are fetched. Does nothing
+    /// if its manifest is already pending.
+    pub fn save_manifest(&self, pending: &PendingManifest) -> Result<()> {
+        let manifest = serde_json::to_string(&pending.manifest)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO chunk_manifests
+             (root_hash, event_id, sender_pubkey, created_at, received_at, manifest)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                pending.manifest.root_hash,
+                pending.event_id,
+                pending.sender_pubkey,
+                pending.created_at,
+                pending.received_at,
+                manifest
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Stores the decrypted chunk at `index` of the manifest with `root_hash`.
+    ///
+    /// Saving the same chunk again does nothing. Returns `false`, keeping
+    /// the stored chunk, if a different one is already stored at `index`.
+    pub fn save_chunk(&self, root_hash: &str, index: usize, content: &str) -> Result<bool> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO chunks (root_hash, chunk_index, content) VALUES (?1, ?2, ?3)",
+            params![root_hash, index as i64, content],
+        )?;
+        Ok(self.get_chunk(root_hash, index)?.as_deref() == Some(content))
+    }
+
+    /// Gets the chunks stored for `root_hash` as `(index, content)`, in order.
+    pub fn get_chunks(&self, root_hash: &str) -> Result<Vec<(usize, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT chunk_index, content FROM chunks WHERE root_hash = ?1 ORDER BY chunk_index",
+        )?;
+        let rows = stmt.query_map([root_hash], |row| {
+            Ok((row.get::<_, i64>(0)? as usize, row.get(1)?))
+        })?;
+        rows.collect()
+    }
+
+    /// Gets the chunk stored at `index` of `root_hash`, if any.
+    pub fn get_chunk(&self, root_hash: &str, index: usize) -> Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT content FROM chunks WHERE root_hash = ?1 AND chunk_index = ?2",
+                params![root_hash, index as i64],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Gets the sizes in bytes of the chunks stored for `root_hash` as
+    /// `(index, size)`, without loading them.
+    pub fn get_chunk_sizes(&self, root_hash: &str) -> Result<Vec<(usize, usize)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT chunk_index, LENGTH(CAST(content AS BLOB)) FROM chunks
+             WHERE root_hash = ?1 ORDER BY chunk_index",
+        )?;
+        let rows = stmt.query_map([root_hash], |row| {
+            Ok((row.get::<_, i64>(0)? as usize, row.get::<_, i64>(1)? as usize))
+        })?;
+        rows.collect()
+    }
+
+    /// Gets the manifests whose chunks are still being fetched, oldest first.
+    pub fn get_pending_manifests(&self) -> Result<Vec<PendingManifest>> {
+        self.query_manifests("1", [])
+    }
+
+    /// Gets the pending manifest with `root_hash`, if any.
+    pub fn get_pending_manifest(&self, root_hash: &str) -> Result<Option<PendingManifest>> {
+        Ok(self.query_manifests("m.root_hash = ?1", [root_hash])?.pop())
+    }
+
+    /// Records a failed attempt to fetch the chunks of `root_hash` and
+    /// schedules the next one with exponential backoff. Returns when it is
+    /// due, or `None` if the manifest isn't pending.
+    ///
+    /// A report whose next attempt would come more than a week after it was
+    /// received is given up on: its manifest and chunks are deleted and
+    /// `None` is returned.
+    pub fn record_chunk_failure(&self, root_hash: &str, error: &str, now: i64) -> Result<Option<i64>> {
+        let pending: Option<(u32, i64)> = self
+            .conn
+            .query_row(
+                "SELECT attempts, received_at FROM chunk_manifests WHERE root_hash = ?1",
+                [root_hash],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((attempts, received_at)) = pending else {
+            return Ok(None);
+        };
+        let attempts = attempts + 1;
+
+        let delay = CHUNK_RETRY_BASE_SECS
+            .saturating_mul(1 << (attempts - 1).min(20))
+            .min(CHUNK_RETRY_MAX_SECS);
+        if now + delay - received_at > CHUNK_EXPIRY_SECS {
+            self.delete_manifest(root_hash)?;
+            return Ok(None);
+        }
+        self.conn.execute(
+            "UPDATE chunk_manifests SET attempts = ?2, next_retry_at = ?3, last_error = ?4
+             WHERE root_hash = ?1",
+            params![root_hash, attempts, now + delay, error],
+        )?;
+        Ok(Some(now + delay))
+    }
+
+    /// Makes the manifest with `root_hash` due for a retry at `at`. Returns
+    /// `false` if it isn't pending or its chunks are being fetched (no
+    /// retry is scheduled), so it is never fetched twice at once.
+    pub fn schedule_manifest_retry(&self, root_hash: &str, at: i64) -> Result<bool> {
+        let updated = self.conn.execute(
+            "UPDATE chunk_manifests SET next_retry_at = ?2
+             WHERE root_hash = ?1 AND next_retry_at IS NOT NULL",
+            params![root_hash, at],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Makes every pending manifest due for a retry at `at`, including those
+    /// whose fetch was interrupted. Only safe while nothing is fetching.
+    pub fn schedule_all_manifest_retries(&self, at: i64) -> Result<usize> {
+        self.conn.execute("UPDATE chunk_manifests SET next_retry_at = ?1", [at])
     }
 
-    /// Gets crash groups aggregated by exception type.
-    pub fn get_groups(&self, limit: usize) -> Result<Vec<CrashGroup>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT
-                COALESCE(exception_type, 'Unknown') as exc_type,
-                COUNT(*) as count,
-                MIN(received_at) as first_seen,
-                MAX(received_at) as last_seen,
-                GROUP_CONCAT(DISTINCT app_version) as versions
-             FROM crashes
-             GROUP BY exc_type
-             ORDER BY count DESC
-             LIMIT ?1",
+    /// Gets the manifests due for a retry at `now` and marks them as being
+    /// retried, so they aren't handed out twice.
+    pub fn take_due_manifests(&self, now: i64) -> Result<Vec<PendingManifest>> {
+        let due = self.query_manifests("m.next_retry_at <= ?1", [now])?;
+        self.conn.execute(
+            "UPDATE chunk_manifests SET next_retry_at = NULL WHERE next_retry_at <= ?1",
+            [now],
         )?;
+        Ok(due)
+    }
 
-        let rows = stmt.query_map([limit], |row| {
-            let versions_str: Option<String> = row.get(4)?;
-            let app_versions = versions_str
-                .map(|s| s.split(',').map(String::from).collect())
-                .unwrap_or_default();
+    fn query_manifests<P: rusqlite::Params>(
+        &self,
+        condition: &str,
+        params: P,
+    ) -> Result<Vec<PendingManifest>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT m.event_id, m.sender_pubkey, m.created_at, m.received_at, m.manifest,
+                    (SELECT COUNT(*) FROM chunks c WHERE c.root_hash = m.root_hash),
+                    m.attempts, m.next_retry_at, m.last_error
+             FROM chunk_manifests m
+             WHERE {}
+             ORDER BY m.received_at, m.root_hash",
+            condition
+        ))?;
+        let rows = stmt.query_map(params, |row| {
+            let manifest: String = row.get(4)?;
+            Ok((
+                (row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?),
+                manifest,
+                row.get::<_, i64>(5)?,
+                (row.get(6)?, row.get(7)?, row.get(8)?),
+            ))
+        })?;
 
-            Ok(CrashGroup {
-                exception_type: row.get(0)?,
-                count: row.get(1)?,
-                first_seen: row.get(2)?,
-                last_seen: row.get(3)?,
-                app_versions,
+        let mut pending = Vec::new();
+        for row in rows {
+            let (
+                (event_id, sender_pubkey, created_at, received_at),
+                manifest,
+                chunks_fetched,
+                (attempts, next_retry_at, last_error),
+            ) = row?;
+            if let Ok(manifest) = serde_json::from_str(&manifest) {
+                pending.push(PendingManifest {
+                    event_id,
+                    sender_pubkey,
+                    created_at,
+                    received_at,
+                    manifest,
+                    chunks_fetched: chunks_fetched as usize,
+                    attempts,
+                    next_retry_at,
+                    last_error,
+                });
+            }
+        }
+        Ok(pending)
+    }
+
+    /// Forgets a manifest and its chunks, once the report is assembled or
+    /// given up on. Returns `false` if it wasn't pending.
+    pub fn delete_manifest(&self, root_hash: &str) -> Result<bool> {
+        self.conn.execute("DELETE FROM chunks WHERE root_hash = ?1", [root_hash])?;
+        let deleted = self
+            .conn
+            .execute("DELETE FROM chunk_manifests WHERE root_hash = ?1", [root_hash])?;
+        Ok(deleted > 0)
+    }
+
+    /// Gets the breadcrumbs sent with crash `crash_id`, oldest first.
+    pub fn get_breadcrumbs(&self, crash_id: i64) -> Result<Vec<Breadcrumb>> {
+        let breadcrumbs: Option<Option<String>> = self
+            .conn
+            .query_row(
+                "SELECT breadcrumbs FROM crashes WHERE id = ?1",
+                [crash_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(breadcrumbs
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
+
+    /// Lists the files received with crash `crash_id`.
+    pub fn get_attachments(&self, crash_id: i64) -> Result<Vec<AttachmentInfo>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, mime_type, LENGTH(data) FROM attachments
+             WHERE crash_id = ?1 ORDER BY name",
+        )?;
+        let rows = stmt.query_map([crash_id], |row| {
+            Ok(AttachmentInfo {
+                name: row.get(0)?,
+                mime_type: row.get(1)?,
+                size: row.get::<_, i64>(2)? as usize,
             })
         })?;
-
         rows.collect()
     }
 
-    /// Gets total crash count.
-    pub fn count(&self) -> Result<i64> {
+    /// Gets the file `name` received with crash `crash_id`.
+    pub fn get_attachment(&self, crash_id: i64, name: &str) -> Result<Option<Attachment>> {
         self.conn
-            .query_row("SELECT COUNT(*) FROM crashes", [], |row| row.get(0))
+            .query_row(
+                "SELECT name, mime_type, data FROM attachments WHERE crash_id = ?1 AND name = ?2",
+                params![crash_id, name],
+                |row| {
+                    let (name, mime_type): (String, String) = (row.get(0)?, row.get(1)?);
+                    Ok(Attachment::new(name, mime_type, row.get(2)?))
+                },
+            )
+            .optional()
     }
 
-    /// Deletes crashes older than the given timestamp.
-    pub fn delete_older_than(&self, timestamp: i64) -> Result<usize> {
-        self.conn.execute(
-            "DELETE FROM crashes WHERE received_at < ?1",
-            [timestamp],
-        )
+    /// Deletes a crash by ID. Returns `false` if it didn't exist.
+    pub fn delete_by_id(&self, id: i64) -> Result<bool> {
+        let deleted = self.conn.execute("DELETE FROM crashes WHERE id = ?1", [id])?;
+        Ok(deleted > 0)
     }
 
     /// Gets a crash by ID.
@@ -199,7 +1517,8 @@ impl CrashStorage {
         let mut stmt = self.conn.prepare(
             "SELECT id, event_id, sender_pubkey, received_at, created_at,
                     app_name, app_version, exception_type, message,
-                    stack_trace, raw_content, environment, release
+                    stack_trace, raw_content, environment, release,
+                    process_type, route, COALESCE(fingerprint, '')
              FROM crashes
              WHERE id = ?1",
         )?;
@@ -219,6 +1538,9 @@ impl CrashStorage {
                 raw_content: row.get(10)?,
                 environment: row.get(11)?,
                 release: row.get(12)?,
+                process_type: row.get(13)?,
+                route: row.get(14)?,
+                fingerprint: row.get(15)?,
             })
         })?;
 
@@ -226,11 +1548,49 @@ impl CrashStorage {
     }
 }
 
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
 /// Parses crash content to extract structured fields.
 /// Handles both JSON payloads (TypeScript SDK) and markdown (Android SDK).
+///
+/// Electron payloads may name the crashed process (`processType`) and the
+/// window's route (`route`, or `url` of a `window` object).
 pub fn parse_crash_content(content: &str) -> ParsedCrash {
     // Try JSON first
-    if let Ok(json) = serde_json::from_str::<serde_json::Value>(content) {
+    if let Ok(mut json) = serde_json::from_str::<serde_json::Value>(content) {
+        let string = |value: Option<&serde_json::Value>| {
+            value.and_then(|v| v.as_str()).map(String::from)
+        };
+        // None of the fields parsed here come from attachments or
+        // breadcrumbs, so skip decoding them; a malformed one then also
+        // can't fail the whole event
+        if let Some(object) = json.as_object_mut() {
+            object.remove("attachments");
+            object.remove("breadcrumbs");
+        }
+        // Versioned payloads deserialize natively; anything else (legacy
+        // SDKs, unsupported versions) falls back to field lookups below
+        if let Ok(event) = CrashEvent::from_value(json.clone()) {
+            let stack_trace = event.stack_trace();
+            let exception_type = event
+                .exception_type
+                .clone()
+                .or_else(|| extract_exception_type(Some(&event.message), stack_trace.as_deref()));
+            return ParsedCrash {
+                message: Some(event.message),
+                stack_trace,
+                exception_type,
+                environment: event.environment,
+                release: event.release,
+                app_name: None,
+                app_version: None,
+                process_type: string(json.get("processType").or_else(|| json.get("process_type"))),
+                route: string(json.get("route"))
+                    .or_else(|| string(json.get("window").and_then(|w| w.get("url")))),
+            };
+        }
         return ParsedCrash {
             message: json.get("message").and_then(|v| v.as_str()).map(String::from),
             stack_trace: json.get("stack").and_then(|v| v.as_str()).map(String::from),
@@ -242,6 +1602,9 @@ pub fn parse_crash_content(content: &str) -> ParsedCrash {
             release: json.get("release").and_then(|v| v.as_str()).map(String::from),
             app_name: None,
             app_version: None,
+            process_type: string(json.get("processType").or_else(|| json.get("process_type"))),
+            route: string(json.get("route"))
+                .or_else(|| string(json.get("window").and_then(|w| w.get("url")))),
         };
     }
 
@@ -253,11 +1616,9 @@ pub fn parse_crash_content(content: &str) -> ParsedCrash {
 
     for line in &lines {
         // Look for exception type in stack trace
-        if line.contains("Exception") || line.contains("Error") {
-            if exception_type.is_none() {
-                exception_type = extract_exception_name(line);
-                message = Some(line.to_string());
-            }
+        if (line.contains("Exception") || line.contains("Error")) && exception_type.is_none() {
+            exception_type = extract_exception_name(line);
+            message = Some(line.to_string());
         }
         // Look for version in header
         if line.contains('-') && (line.contains("RELEASE") || line.contains("DEBUG")) {
@@ -275,6 +1636,8 @@ pub fn parse_crash_content(content: &str) -> ParsedCrash {
         release: None,
         app_name: lines.first().map(|s| s.to_string()),
         app_version,
+        process_type: None,
+        route: None,
     }
 }
 
@@ -288,6 +1651,40 @@ pub struct ParsedCrash {
     pub release: Option<String>,
     pub app_name: Option<String>,
     pub app_version: Option<String>,
+    pub process_type: Option<String>,
+    pub route: Option<String>,
+}
+
+/// Takes the attachments out of a JSON crash payload, leaving their name,
+/// MIME type and size in its `attachments` list. Other content is returned
+/// unchanged.
+fn split_attachments(content: &str) -> (std::borrow::Cow<'_, str>, Vec<Attachment>) {
+    if !content.contains("\"attachments\"") {
+        return (content.into(), Vec::new());
+    }
+    let Ok(mut json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return (content.into(), Vec::new());
+    };
+    let Some(items) = json.get_mut("attachments").and_then(|a| a.as_array_mut()) else {
+        return (content.into(), Vec::new());
+    };
+
+    let mut attachments = Vec::new();
+    for item in items {
+        let Ok(attachment) = serde_json::from_value::<Attachment>(item.clone()) else {
+            continue;
+        };
+        *item = serde_json::json!(AttachmentInfo {
+            name: attachment.name.clone(),
+            mime_type: attachment.mime_type.clone(),
+            size: attachment.data.len(),
+        });
+        attachments.push(attachment);
+    }
+    if attachments.is_empty() {
+        return (content.into(), attachments);
+    }
+    (json.to_string().into(), attachments)
 }
 
 fn extract_exception_type(message: Option<&str>, stack: Option<&str>) -> Option<String> {
@@ -299,7 +1696,7 @@ fn extract_exception_type(message: Option<&str>, stack: Option<&str>) -> Option<
     }
     // Try message
     if let Some(msg) = message {
-        return extract_exception_name(msg).map(String::from);
+        return extract_exception_name(msg);
     }
     None
 }
@@ -314,7 +1711,7 @@ fn extract_exception_name(line: &str) -> Option<String> {
         let prefix = &line[..colon_pos];
         if prefix.contains('.') || prefix.ends_with("Exception") || prefix.ends_with("Error") {
             // Get just the class name
-            return Some(prefix.split('.').last().unwrap_or(prefix).to_string());
+            return Some(prefix.split('.').next_back().unwrap_or(prefix).to_string());
         }
     }
 
@@ -352,6 +1749,9 @@ mod tests {
             raw_content: "raw".to_string(),
             environment: None,
             release: None,
+            process_type: None,
+            route: None,
+            fingerprint: String::new(),
         };
 
         let id = storage.insert(&report).unwrap();
@@ -380,6 +1780,9 @@ mod tests {
             raw_content: "raw".to_string(),
             environment: None,
             release: None,
+            process_type: None,
+            route: None,
+            fingerprint: String::new(),
         };
 
         let id1 = storage.insert(&report).unwrap();
@@ -410,6 +1813,9 @@ mod tests {
                 raw_content: "raw".to_string(),
                 environment: None,
                 release: None,
+                process_type: None,
+                route: None,
+                fingerprint: String::new(),
             };
             storage.insert(&report).unwrap();
         }
@@ -418,6 +1824,665 @@ mod tests {
         assert_eq!(groups.len(), 1);
         assert_eq!(groups[0].exception_type, "NullPointerException");
         assert_eq!(groups[0].count, 5);
+        assert_eq!(
+            groups[0].fingerprint,
+            fingerprint(Some("NullPointerException"), None)
+        );
+        assert_eq!(storage.group_count(&groups[0].fingerprint).unwrap(), 5);
+        assert_eq!(storage.group_count("Unknown").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_expire() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let day = 24 * 60 * 60;
+        for (event_id, received_at) in [("old", 0), ("recent", 29 * day)] {
+            let report = CrashReport::from_content(
+                event_id.to_string(),
+                "sender".to_string(),
+                received_at,
+                received_at,
+                "boom".to_string(),
+            );
+            storage.insert(&report).unwrap();
+        }
+
+        assert_eq!(storage.expire(30, 30 * day).unwrap(), 0);
+        assert_eq!(storage.expire(30, 30 * day + 1).unwrap(), 1);
+        let remaining = storage.get_recent(10).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].event_id, "recent");
+    }
+
+    #[test]
+    fn test_forward_state() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let relay = "wss://relay.example";
+        storage.set_relay_last_seen(relay, 500).unwrap();
+        assert_eq!(storage.forward_last_seen(relay).unwrap(), None);
+        storage.set_forward_last_seen(relay, 200).unwrap();
+        storage.set_forward_last_seen(relay, 100).unwrap();
+        assert_eq!(storage.forward_last_seen(relay).unwrap(), Some(200));
+        assert_eq!(storage.relay_last_seen(relay).unwrap(), Some(500));
+
+        assert!(!storage.is_forwarded("wrap1").unwrap());
+        storage.set_forwarded("wrap1", 100).unwrap();
+        storage.set_forwarded("wrap1", 100).unwrap();
+        storage.set_forwarded("wrap2", 300).unwrap();
+        assert!(storage.is_forwarded("wrap1").unwrap());
+        assert_eq!(storage.prune_forwarded(200).unwrap(), 1);
+        assert!(!storage.is_forwarded("wrap1").unwrap());
+        assert!(storage.is_forwarded("wrap2").unwrap());
+    }
+
+    #[test]
+    fn test_relay_cursor() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let relay = "wss://relay.example";
+        assert_eq!(storage.relay_last_seen(relay).unwrap(), None);
+
+        storage.set_relay_last_seen(relay, 200).unwrap();
+        storage.set_relay_last_seen(relay, 100).unwrap();
+        assert_eq!(storage.relay_last_seen(relay).unwrap(), Some(200));
+        storage.set_relay_last_seen(relay, 300).unwrap();
+        assert_eq!(storage.relay_last_seen(relay).unwrap(), Some(300));
+        assert_eq!(storage.relay_last_seen("wss://other.example").unwrap(), None);
+    }
+
+    fn pending_manifest() -> PendingManifest {
+        PendingManifest {
+            event_id: "wrap".to_string(),
+            sender_pubkey: "sender".to_string(),
+            created_at: 100,
+            received_at: 200,
+            manifest: Manifest {
+                v: 1,
+                root_hash: "root".to_string(),
+                total_size: 6,
+                chunk_ids: vec!["c0".to_string(), "c1".to_string()],
+                relays: vec!["wss://relay.example".to_string()],
+                chunk_relays: Default::default(),
+                chunk_sizes: vec![],
+                chunk_hashes: vec![],
+                encoding: None,
+            },
+            chunks_fetched: 0,
+            attempts: 0,
+            next_retry_at: None,
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn test_chunk_cache() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let pending = pending_manifest();
+        storage.save_manifest(&pending).unwrap();
+        assert!(storage.save_chunk("root", 1, "déf").unwrap());
+        assert_eq!(storage.get_chunk_sizes("root").unwrap(), [(1, 4)]);
+        assert_eq!(storage.get_chunk("root", 0).unwrap(), None);
+        // Duplicates are ignored, conflicting copies reported
+        assert!(storage.save_chunk("root", 1, "déf").unwrap());
+        assert!(!storage.save_chunk("root", 1, "def").unwrap());
+        assert_eq!(storage.get_chunk("root", 1).unwrap().as_deref(), Some("déf"));
+        storage.delete_manifest("root").unwrap();
+        storage.save_manifest(&pending).unwrap();
+        storage.save_chunk("root", 1, "def").unwrap();
+
+        let stored = storage.get_pending_manifests().unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].event_id, "wrap");
+        assert_eq!(stored[0].manifest, pending.manifest);
+        assert_eq!(stored[0].chunks_fetched, 1);
+
+        // Saving the manifest again keeps the chunks fetched so far
+        storage.save_manifest(&pending).unwrap();
+        storage.save_chunk("root", 0, "abc").unwrap();
+        assert_eq!(
+            storage.get_chunks("root").unwrap(),
+            [(0, "abc".to_string()), (1, "def".to_string())]
+        );
+
+        // Failed fetches back off exponentially until retried
+        assert_eq!(storage.record_chunk_failure("root", "relay down", 1000).unwrap(), Some(1060));
+        assert!(storage.take_due_manifests(1059).unwrap().is_empty());
+        assert_eq!(storage.record_chunk_failure("root", "relay down", 1000).unwrap(), Some(1120));
+        let stored = storage.get_pending_manifest("root").unwrap().unwrap();
+        assert_eq!(stored.attempts, 2);
+        assert_eq!(stored.last_error.as_deref(), Some("relay down"));
+        assert_eq!(storage.take_due_manifests(1120).unwrap().len(), 1);
+        assert!(storage.take_due_manifests(2000).unwrap().is_empty());
+        // Taken manifests are being fetched, so can't be scheduled again
+        assert!(!storage.schedule_manifest_retry("root", 2000).unwrap());
+        assert_eq!(storage.record_chunk_failure("root", "relay down", 2000).unwrap(), Some(2240));
+        assert!(storage.schedule_manifest_retry("root", 2000).unwrap());
+        assert_eq!(storage.take_due_manifests(2000).unwrap().len(), 1);
+        assert_eq!(storage.schedule_all_manifest_retries(3000).unwrap(), 1);
+        assert_eq!(storage.take_due_manifests(3000).unwrap().len(), 1);
+        assert!(!storage.schedule_manifest_retry("other", 2000).unwrap());
+        assert_eq!(storage.record_chunk_failure("other", "relay down", 1000).unwrap(), None);
+
+        assert!(storage.delete_manifest("root").unwrap());
+        assert!(!storage.delete_manifest("root").unwrap());
+        assert!(storage.get_pending_manifests().unwrap().is_empty());
+        assert!(storage.get_chunks("root").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_partial_chunks_survive_restart_then_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bugstr.db");
+        {
+            let storage = CrashStorage::open(&path).unwrap();
+            storage.save_manifest(&pending_manifest()).unwrap();
+            // Interrupted mid-fetch, with one of two chunks stored
+            storage.save_chunk("root", 1, "def").unwrap();
+            assert!(storage.take_due_manifests(i64::MAX).unwrap().is_empty());
+        }
+
+        // On the next start the report is due again with its chunk kept
+        let storage = CrashStorage::open(&path).unwrap();
+        assert_eq!(storage.schedule_all_manifest_retries(1000).unwrap(), 1);
+        let due = storage.take_due_manifests(1000).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].chunks_fetched, 1);
+        assert_eq!(storage.get_chunk("root", 1).unwrap().as_deref(), Some("def"));
+
+        // Retried while younger than a week, then dropped with its chunks
+        let received_at = due[0].received_at;
+        let last_retry = received_at + CHUNK_EXPIRY_SECS - CHUNK_RETRY_BASE_SECS;
+        assert_eq!(
+            storage.record_chunk_failure("root", "relay down", last_retry).unwrap(),
+            Some(last_retry + CHUNK_RETRY_BASE_SECS)
+        );
+        assert_eq!(storage.record_chunk_failure("root", "relay down", last_retry).unwrap(), None);
+        assert!(storage.get_pending_manifest("root").unwrap().is_none());
+        assert!(storage.get_chunks("root").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_attachments() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let content = serde_json::json!({
+            "message": "boom",
+            "attachments": [
+                {"name": "app.log", "mime_type": "text/plain", "data": "c3RhcnRlZAo="},
+                {"name": "broken", "mime_type": "text/plain", "data": "not base64!"},
+            ],
+        })
+        .to_string();
+        let report = CrashReport::from_content("e1".into(), "s".into(), 0, 0, content);
+        let id = storage.insert(&report).unwrap().unwrap();
+
+        assert_eq!(
+            storage.get_attachments(id).unwrap(),
+            [AttachmentInfo {
+                name: "app.log".to_string(),
+                mime_type: "text/plain".to_string(),
+                size: 8,
+            }]
+        );
+        let log = storage.get_attachment(id, "app.log").unwrap().unwrap();
+        assert_eq!(log.data, b"started\n");
+        assert!(storage.get_attachment(id, "other").unwrap().is_none());
+
+        // The raw content keeps only what was attached
+        let stored = storage.get_by_id(id).unwrap().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&stored.raw_content).unwrap();
+        assert_eq!(
+            json["attachments"][0],
+            serde_json::json!({"name": "app.log", "mime_type": "text/plain", "size": 8})
+        );
+        assert_eq!(json["attachments"][1]["data"], "not base64!");
+
+        assert!(storage.delete_by_id(id).unwrap());
+        assert!(storage.get_attachments(id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_breadcrumbs() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let content = serde_json::json!({
+            "message": "boom",
+            "breadcrumbs": [{"timestamp": 1, "category": "ui", "message": "tap", "level": "error"}],
+        })
+        .to_string();
+        let report = CrashReport::from_content("e1".into(), "s".into(), 0, 0, content);
+        let id = storage.insert(&report).unwrap().unwrap();
+        let breadcrumbs = storage.get_breadcrumbs(id).unwrap();
+        assert_eq!(breadcrumbs.len(), 1);
+        assert_eq!(breadcrumbs[0].message, "tap");
+        assert_eq!(breadcrumbs[0].level, crate::breadcrumbs::Level::Error);
+
+        let report = CrashReport::from_content("e2".into(), "s".into(), 0, 0, "boom".into());
+        let id = storage.insert(&report).unwrap().unwrap();
+        assert!(storage.get_breadcrumbs(id).unwrap().is_empty());
+        assert!(storage.get_breadcrumbs(999).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_group_status() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+
+        let report = CrashReport {
+            id: 0,
+            event_id: "event".to_string(),
+            sender_pubkey: "pubkey".to_string(),
+            received_at: 1000,
+            created_at: 999,
+            app_name: None,
+            app_version: Some("2.0.0".to_string()),
+            exception_type: Some("NullPointerException".to_string()),
+            message: None,
+            stack_trace: None,
+            raw_content: "raw".to_string(),
+            environment: None,
+            release: None,
+            process_type: None,
+            route: None,
+            fingerprint: String::new(),
+        };
+        storage.insert(&report).unwrap();
+        let npe = fingerprint(Some("NullPointerException"), None);
+
+        assert_eq!(storage.get_groups(10).unwrap()[0].status, GroupStatus::Open);
+
+        assert!(storage
+            .set_group_status(&npe, GroupStatus::Resolved, Some("2.1.0"), 2000)
+            .unwrap());
+        let group = &storage.get_groups(10).unwrap()[0];
+        assert_eq!(group.status, GroupStatus::Resolved);
+        assert_eq!(group.resolved_in_release.as_deref(), Some("2.1.0"));
+        assert_eq!(
+            storage.get_group_status(&npe).unwrap(),
+            GroupStatus::Resolved
+        );
+        assert_eq!(storage.get_group_status("NoSuchError").unwrap(), GroupStatus::Open);
+
+        // Ignoring clears the fix release
+        storage
+            .set_group_status(&npe, GroupStatus::Ignored, Some("2.1.0"), 3000)
+            .unwrap();
+        let group = &storage.get_groups(10).unwrap()[0];
+        assert_eq!(group.status, GroupStatus::Ignored);
+        assert_eq!(group.resolved_in_release, None);
+
+        // Unknown groups are reported, not created
+        assert!(!storage
+            .set_group_status("NoSuchError", GroupStatus::Resolved, None, 4000)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_regression_detection() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let crash = |event_id: &str, exception: &str, version: Option<&str>, received_at| {
+            let report = CrashReport {
+                id: 0,
+                event_id: event_id.to_string(),
+                sender_pubkey: "pubkey".to_string(),
+                received_at,
+                created_at: received_at,
+                app_name: None,
+                app_version: version.map(String::from),
+                exception_type: Some(exception.to_string()),
+                message: None,
+                stack_trace: None,
+                raw_content: "raw".to_string(),
+                environment: None,
+                release: None,
+                process_type: None,
+                route: None,
+                fingerprint: String::new(),
+            };
+            storage.insert(&report).unwrap().unwrap()
+        };
+        let npe = fingerprint(Some("NullPointerException"), None);
+        let ise = fingerprint(Some("IllegalStateException"), None);
+
+        // Resolved in a release: older builds still crashing don't count
+        crash("npe1", "NullPointerException", Some("2.0.0"), 1000);
+        storage
+            .set_group_status(&npe, GroupStatus::Resolved, Some("2.1.0"), 2000)
+            .unwrap();
+        crash("npe2", "NullPointerException", Some("2.0.9"), 3000);
+        assert_eq!(storage.get_group_status(&npe).unwrap(), GroupStatus::Resolved);
+        let id = crash("npe3", "NullPointerException", Some("2.1.0"), 4000);
+        assert_eq!(storage.get_group_status(&npe).unwrap(), GroupStatus::Regressed);
+        assert_eq!(storage.regressed_by(&npe).unwrap(), Some(id));
+        crash("npe4", "NullPointerException", Some("2.2.0"), 5000);
+        assert_eq!(storage.regressed_by(&npe).unwrap(), Some(id));
+
+        // Resolved without a release: newer than anything seen before
+        crash("ise1", "IllegalStateException", Some("1.0.0"), 1000);
+        storage
+            .set_group_status(&ise, GroupStatus::Resolved, None, 2000)
+            .unwrap();
+        crash("ise2", "IllegalStateException", Some("1.0.0"), 3000);
+        assert_eq!(storage.get_group_status(&ise).unwrap(), GroupStatus::Resolved);
+        crash("ise3", "IllegalStateException", Some("1.0.1"), 4000);
+        assert_eq!(storage.get_group_status(&ise).unwrap(), GroupStatus::Regressed);
+
+        // Resolving again clears the regression
+        storage
+            .set_group_status(&ise, GroupStatus::Resolved, None, 5000)
+            .unwrap();
+        assert_eq!(storage.regressed_by(&ise).unwrap(), None);
+
+        let statuses = |status| {
+            storage
+                .get_groups_with_status(10, Some(status))
+                .unwrap()
+                .into_iter()
+                .map(|g| g.fingerprint)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(statuses(GroupStatus::Regressed), [npe.as_str()]);
+        assert_eq!(statuses(GroupStatus::Resolved), [ise.as_str()]);
+        assert!(statuses(GroupStatus::Open).is_empty());
+        let crashes = storage
+            .get_recent_with_status(10, Some(GroupStatus::Resolved))
+            .unwrap();
+        assert_eq!(crashes.len(), 3);
+        assert!(crashes.iter().all(|c| c.fingerprint == ise));
+    }
+
+    #[test]
+    fn test_symbolicated_stack() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let make = |event_id: &str, app_version: &str, stack: Option<&str>| CrashReport {
+            id: 0,
+            event_id: event_id.to_string(),
+            sender_pubkey: "pubkey".to_string(),
+            received_at: 1000,
+            created_at: 999,
+            app_name: Some("com.example.app".to_string()),
+            app_version: Some(app_version.to_string()),
+            exception_type: Some("Error".to_string()),
+            message: None,
+            stack_trace: stack.map(String::from),
+            raw_content: "raw".to_string(),
+            environment: None,
+            release: None,
+            process_type: None,
+            route: None,
+            fingerprint: String::new(),
+        };
+
+        let id = storage.insert(&make("a", "1.0.0", Some("at a.b(Unknown)"))).unwrap().unwrap();
+        storage.insert(&make("b", "2.0.0", Some("at c.d(Unknown)"))).unwrap();
+        storage.insert(&make("c", "1.0.0", None)).unwrap();
+
+        assert_eq!(storage.get_with_stack_trace(None, None).unwrap().len(), 2);
+        let filtered = storage
+            .get_with_stack_trace(Some("com.example.app"), Some("1.0.0"))
+            .unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].id, id);
+        assert!(storage.get_with_stack_trace(Some("other"), None).unwrap().is_empty());
+
+        assert_eq!(storage.get_symbolicated_stack(id).unwrap(), None);
+        assert!(storage.set_symbolicated_stack(id, Some("at Foo.bar(Foo.kt:1)")).unwrap());
+        assert_eq!(
+            storage.get_symbolicated_stack(id).unwrap().as_deref(),
+            Some("at Foo.bar(Foo.kt:1)")
+        );
+        assert!(!storage.set_symbolicated_stack(9999, None).unwrap());
+
+        assert!(storage.delete_by_id(id).unwrap());
+        assert!(!storage.delete_by_id(id).unwrap());
+        assert!(storage.get_by_id(id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_integrations() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let gitlab = IntegrationConfig::Gitlab {
+            url: "https://gitlab.example.com".to_string(),
+            project: "team/app".to_string(),
+            token: "secret".to_string(),
+            labels: vec!["crash".to_string()],
+        };
+
+        let all = storage.add_integration(None, &gitlab, 1000).unwrap();
+        let app = storage.add_integration(Some("MyApp"), &gitlab, 1001).unwrap();
+        storage
+            .add_integration(Some("OtherApp"), &gitlab, 1002)
+            .unwrap();
+        storage
+            .conn
+            .execute(
+                "INSERT INTO integrations (app_name, config, created_at)
+                 VALUES (NULL, '{\"kind\":\"future\"}', 1003)",
+                [],
+            )
+            .unwrap();
+
+        assert_eq!(storage.get_integrations().unwrap().len(), 3);
+        let ids = |app_name| {
+            storage
+                .integrations_for_app(app_name)
+                .unwrap()
+                .iter()
+                .map(|i| i.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(Some("MyApp")), [all, app]);
+        assert_eq!(ids(None), [all]);
+        assert_eq!(storage.get_integrations().unwrap()[1].config, gitlab);
+
+        assert!(storage.delete_integration(app).unwrap());
+        assert!(!storage.delete_integration(app).unwrap());
+        assert_eq!(ids(Some("MyApp")), [all]);
+    }
+
+    #[test]
+    fn test_ensure_webhook() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let url = "https://hooks.example.com/bugstr";
+        let id = storage.ensure_webhook(url, None, &[], 1000).unwrap();
+        assert_eq!(storage.ensure_webhook(url, None, &[], 2000).unwrap(), id);
+
+        // A new secret updates the webhook in place
+        let events = [AlertKind::NewCrash, AlertKind::NewGroup];
+        assert_eq!(storage.ensure_webhook(url, Some("s3cret"), &events, 3000).unwrap(), id);
+        let integrations = storage.get_integrations().unwrap();
+        assert_eq!(integrations.len(), 1);
+        assert_eq!(integrations[0].created_at, 1000);
+        assert_eq!(
+            integrations[0].config,
+            IntegrationConfig::Webhook {
+                url: url.to_string(),
+                secret: Some("s3cret".to_string()),
+                events: events.to_vec(),
+            }
+        );
+
+        let other = storage.ensure_webhook("https://other.example.com", None, &[], 4000).unwrap();
+        assert_ne!(other, id);
+    }
+
+    #[test]
+    fn test_deliveries() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let delivery = Delivery {
+            integration_id: 1,
+            alert_kind: AlertKind::NewGroup,
+            group_key: "NullPointerException".to_string(),
+            crash_id: Some(5),
+            error: None,
+            reference: Some("APP-12".to_string()),
+            created_at: 1000,
+        };
+        let failed = Delivery {
+            alert_kind: AlertKind::Threshold,
+            error: Some("HTTP 503: unavailable".to_string()),
+            reference: None,
+            created_at: 2000,
+            ..delivery.clone()
+        };
+        storage.record_delivery(&delivery).unwrap();
+        storage.record_delivery(&failed).unwrap();
+        storage
+            .conn
+            .execute(
+                "INSERT INTO notification_deliveries
+                    (integration_id, alert_kind, group_key, created_at)
+                 VALUES (1, 'future', 'X', 3000)",
+                [],
+            )
+            .unwrap();
+
+        assert_eq!(storage.get_deliveries(10).unwrap(), [failed, delivery.clone()]);
+        assert_eq!(storage.get_deliveries(2).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_issue_links() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let report = CrashReport::from_content(
+            "event".to_string(),
+            "sender".to_string(),
+            1000,
+            1001,
+            r#"{"stack": "java.lang.IllegalStateException: boom"}"#.to_string(),
+        );
+        storage.insert(&report).unwrap();
+        let ise = report.fingerprint;
+        let delivery = Delivery {
+            integration_id: 1,
+            alert_kind: AlertKind::NewGroup,
+            group_key: "NullPointerException".to_string(),
+            crash_id: Some(5),
+            error: None,
+            reference: Some("APP-12".to_string()),
+            created_at: 1000,
+        };
+        storage.record_delivery(&delivery).unwrap();
+        storage
+            .record_delivery(&Delivery {
+                group_key: ise.clone(),
+                reference: Some("APP-13".to_string()),
+                ..delivery.clone()
+            })
+            .unwrap();
+        storage
+            .record_delivery(&Delivery { integration_id: 2, ..delivery.clone() })
+            .unwrap();
+        storage
+            .set_group_status(&ise, GroupStatus::Resolved, None, 2000)
+            .unwrap();
+
+        let links = storage.issue_links(1).unwrap();
+        assert_eq!(
+            links,
+            [IssueLink {
+                integration_id: 1,
+                group_key: "NullPointerException".to_string(),
+                reference: "APP-12".to_string(),
+                status_updated_at: None,
+            }]
+        );
+
+        // Reopened groups report when they were reopened
+        storage
+            .set_group_status(&ise, GroupStatus::Open, None, 3000)
+            .unwrap();
+        let links = storage.issue_links(1).unwrap();
+        let reopened = links.iter().find(|link| link.reference == "APP-13").unwrap();
+        assert_eq!(reopened.status_updated_at, Some(3000));
+    }
+
+    #[test]
+    fn test_migrate_is_incremental() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("crashes.db");
+
+        let storage = CrashStorage::open_unmigrated(&path).unwrap();
+        assert_eq!(storage.schema_version().unwrap(), 0);
+        assert_eq!(storage.migrate().unwrap(), MIGRATIONS.len());
+        assert_eq!(storage.schema_version().unwrap(), SCHEMA_VERSION);
+
+        // Reopening applies nothing further
+        let storage = CrashStorage::open(&path).unwrap();
+        assert_eq!(storage.migrate().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_migrate_backfills_fingerprints() {
+        let storage = CrashStorage::open_unmigrated(":memory:").unwrap();
+        for (index, sql) in MIGRATIONS[..FINGERPRINT_MIGRATION - 1].iter().enumerate() {
+            storage
+                .conn
+                .execute_batch(&format!("{}\nPRAGMA user_version = {};", sql, index + 1))
+                .unwrap();
+        }
+        let stack = "java.lang.IllegalStateException: boom\n\tat com.example.Main.run(Main.java:3)";
+        storage
+            .conn
+            .execute_batch(&format!(
+                "INSERT INTO crashes (id, event_id, sender_pubkey, received_at, created_at,
+                    exception_type, stack_trace, raw_content)
+                 VALUES (1, 'event', 'sender', 1000, 1000, 'IllegalStateException', '{}', 'raw');
+                 INSERT INTO group_status (group_key, status, updated_at)
+                 VALUES ('IllegalStateException', 'resolved', 2000);
+                 INSERT INTO notification_deliveries
+                    (integration_id, alert_kind, group_key, crash_id, created_at)
+                 VALUES (1, 'new_group', 'IllegalStateException', 1, 1000);
+                 INSERT INTO rule_firings (rule_id, group_key, fired_at)
+                 VALUES (1, 'IllegalStateException', 1500), (2, 'GoneException', 1500);",
+                stack
+            ))
+            .unwrap();
+
+        assert_eq!(
+            storage.migrate().unwrap(),
+            MIGRATIONS.len() - (FINGERPRINT_MIGRATION - 1)
+        );
+        let key = fingerprint(Some("IllegalStateException"), Some(stack));
+        assert_eq!(storage.get_by_id(1).unwrap().unwrap().fingerprint, key);
+        assert_eq!(storage.get_group_status(&key).unwrap(), GroupStatus::Resolved);
+        assert_eq!(storage.get_groups(10).unwrap()[0].status, GroupStatus::Resolved);
+        assert_eq!(storage.get_deliveries(10).unwrap()[0].group_key, key);
+        assert_eq!(storage.rule_last_fired(1, &key).unwrap(), Some(1500));
+        assert_eq!(storage.rule_last_fired(2, "GoneException").unwrap(), None);
+
+        // Rows written without a fingerprint still load
+        storage.conn.execute("UPDATE crashes SET fingerprint = NULL", []).unwrap();
+        assert_eq!(storage.get_by_id(1).unwrap().unwrap().group_key(), key);
+        assert_eq!(storage.get_groups(10).unwrap()[0].fingerprint, "");
+    }
+
+    #[test]
+    fn test_backup_to() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let report = CrashReport {
+            id: 0,
+            event_id: "backed_up".to_string(),
+            sender_pubkey: "pubkey".to_string(),
+            received_at: 1000,
+            created_at: 999,
+            app_name: None,
+            app_version: None,
+            exception_type: None,
+            message: None,
+            stack_trace: None,
+            raw_content: "raw".to_string(),
+            environment: None,
+            release: None,
+            process_type: None,
+            route: None,
+            fingerprint: String::new(),
+        };
+        storage.insert(&report).unwrap();
+
+        let backup_path = dir.path().join("backup.db");
+        storage.backup_to(&backup_path).unwrap();
+
+        let restored = CrashStorage::open(&backup_path).unwrap();
+        assert_eq!(restored.count().unwrap(), 1);
+        assert_eq!(restored.schema_version().unwrap(), SCHEMA_VERSION);
     }
 
     #[test]
@@ -428,6 +2493,45 @@ mod tests {
         assert_eq!(parsed.message, Some("Something failed".to_string()));
         assert!(parsed.stack_trace.is_some());
         assert_eq!(parsed.environment, Some("production".to_string()));
+        assert_eq!(parsed.process_type, None);
+
+        let content = r#"{"message":"x","processType":"renderer","window":{"url":"app://./index.html#/settings"}}"#;
+        let parsed = parse_crash_content(content);
+        assert_eq!(parsed.process_type.as_deref(), Some("renderer"));
+        assert_eq!(parsed.route.as_deref(), Some("app://./index.html#/settings"));
+    }
+
+    #[test]
+    fn test_parse_crash_event() {
+        let mut event = CrashEvent::new("boom", "");
+        event.exception_type = Some("PanicError".to_string());
+        event.release = Some("app@1.0".to_string());
+        event.frames = vec![crate::schema::Frame {
+            function: Some("main".to_string()),
+            ..Default::default()
+        }];
+        let parsed = parse_crash_content(&serde_json::to_string(&event).unwrap());
+        assert_eq!(parsed.message.as_deref(), Some("boom"));
+        assert_eq!(parsed.stack_trace.as_deref(), Some("    at main"));
+        assert_eq!(parsed.exception_type.as_deref(), Some("PanicError"));
+        assert_eq!(parsed.release.as_deref(), Some("app@1.0"));
+
+        // Malformed attachments and breadcrumbs don't matter here
+        let content = r#"{"schema_version":1,"message":"boom","exception_type":"PanicError",
+            "attachments":[{"name":"a"}],"breadcrumbs":[1]}"#;
+        assert_eq!(parse_crash_content(content).exception_type.as_deref(), Some("PanicError"));
+
+        // Unsupported versions, older or newer, still get the legacy field lookups
+        for version in [0, crate::schema::CRASH_EVENT_VERSION + 1] {
+            let content = format!(
+                r#"{{"schema_version":{},"message":"old","stack":"s","exception_type":"X"}}"#,
+                version
+            );
+            let parsed = parse_crash_content(&content);
+            assert_eq!(parsed.message.as_deref(), Some("old"));
+            assert_eq!(parsed.stack_trace.as_deref(), Some("s"));
+            assert_ne!(parsed.exception_type.as_deref(), Some("X"));
+        }
     }
 
     #[test]
@@ -16,15 +16,62 @@
 //!
 //! The `:origStart:origEnd` suffix indicates the original source line range,
 //! which differs from the obfuscated line range when methods are inlined.
+//! Methods inlined from another class are written with their qualified name
+//! (`void com.example.Util.check():12 -> a`).
+//!
+//! # R8 Metadata
+//!
+//! R8 adds JSON metadata in comment lines. The ones used here:
+//!
+//! ```text
+//! # {"id":"com.android.tools.r8.mapping","version":"2.2"}
+//! com.example.Repo -> a.b:
+//! # {"id":"sourceFile","fileName":"Repo.kt"}
+//!     1:1:void access$load(com.example.Repo) -> c
+//!     # {"id":"com.android.tools.r8.synthesized"}
+//! ```
+//!
+//! `sourceFile` after a class line gives the file the class was compiled
+//! from, used instead of guessing `<Class>.java`. `com.android.tools.r8.synthesized`
+//! marks the preceding class (or member) as compiler-generated: accessors,
+//! lambda bridges and the like. Frames in synthesized code are hidden from
+//! the symbolicated stack, as R8's own retrace does. Other metadata is ignored.
+//!
+//! # Exception Chains
+//!
+//! Java traces nest causes (`Caused by: ...`), eliding frames shared with the
+//! enclosing exception as `... N more`. The chain is returned as
+//! [`SymbolicatedStack::exceptions`], outermost first, with deobfuscated
+//! exception classes and each exception's symbolicated frames. `Suppressed:`
+//! exceptions are left out.
+//!
+//! # Native Frames
+//!
+//! NDK frames from tombstones and logcat (`#00 pc 0001a2b4  /.../libfoo.so`)
+//! are resolved against unstripped `.so` files stored next to the mapping,
+//! either directly in the version directory or in per-ABI subdirectories:
+//!
+//! ```text
+//! android/<package>/<version>/
+//!   mapping.txt
+//!   arm64-v8a/libfoo.so
+//!   armeabi-v7a/libfoo.so
+//! ```
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use regex::Regex;
 
+use super::cache::{load_mapping, MappingCache};
+use super::debuginfod::Debuginfod;
+use super::native::DebugFileSlices;
 use super::{
-    MappingStore, SymbolicatedFrame, SymbolicatedStack, SymbolicationContext, SymbolicationError,
+    Confidence, ExceptionCause, MappingStore, PlatformSymbolicator, SymbolicatedFrame,
+    SymbolicatedStack, SymbolicationContext, SymbolicationError, SCHEMA_VERSION,
 };
 
 /// A single line range mapping entry.
@@ -41,6 +88,7 @@ struct LineRangeEntry {
     /// Original line range start
     orig_start: u32,
     /// Original line range end
+    #[allow(dead_code)]
     orig_end: u32,
     /// Original method name for this line range
     method_name: String,
@@ -62,13 +110,30 @@ struct ClassMapping {
     /// Field mappings (obfuscated -> original)
     #[allow(dead_code)]
     fields: HashMap<String, String>,
+    /// Source file name from R8 `sourceFile` metadata
+    source_file: Option<String>,
+    /// Whether R8 marked the whole class as synthesized
+    synthesized: bool,
+    /// Original names of methods R8 marked as synthesized
+    synthesized_methods: HashSet<String>,
 }
 
 /// Parsed ProGuard mapping file.
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct ProguardMapping {
     /// Class mappings (obfuscated name -> mapping)
     classes: HashMap<String, ClassMapping>,
+    /// Source file names from R8 metadata (original class name -> file name)
+    source_files: HashMap<String, String>,
+}
+
+/// Parse an R8 metadata comment (`# {"id":"...", ...}`).
+fn parse_metadata(line: &str) -> Option<serde_json::Value> {
+    let json = line.trim().strip_prefix('#')?.trim_start();
+    if !json.starts_with('{') {
+        return None;
+    }
+    serde_json::from_str(json).ok()
 }
 
 impl ProguardMapping {
@@ -78,9 +143,12 @@ impl ProguardMapping {
     /// - `startLine:endLine:returnType method(params) -> obfuscated`
     /// - `startLine:endLine:returnType method(params):origStart -> obfuscated`
     /// - `startLine:endLine:returnType method(params):origStart:origEnd -> obfuscated`
+    /// - R8 `sourceFile` and `com.android.tools.r8.synthesized` metadata
     fn parse<R: BufRead>(reader: R) -> Result<Self, SymbolicationError> {
         let mut classes = HashMap::new();
         let mut current_class: Option<ClassMapping> = None;
+        // Original name of the last method line, which metadata refers to
+        let mut last_method: Option<String> = None;
 
         // Regex patterns
         let class_re = Regex::new(r"^(\S+)\s+->\s+(\S+):$").unwrap();
@@ -103,6 +171,26 @@ impl ProguardMapping {
         for line in reader.lines() {
             let line = line.map_err(|e| SymbolicationError::ParseError(e.to_string()))?;
 
+            // R8 metadata applies to the class or member line before it
+            if let Some(metadata) = parse_metadata(&line) {
+                if let Some(ref mut class) = current_class {
+                    match metadata["id"].as_str() {
+                        Some("sourceFile") => {
+                            class.source_file =
+                                metadata["fileName"].as_str().map(str::to_string);
+                        }
+                        Some("com.android.tools.r8.synthesized") => match &last_method {
+                            Some(method) => {
+                                class.synthesized_methods.insert(method.clone());
+                            }
+                            None => class.synthesized = true,
+                        },
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+
             // Skip comments and empty lines
             if line.trim().is_empty() || line.trim().starts_with('#') {
                 continue;
@@ -121,7 +209,11 @@ impl ProguardMapping {
                     method_line_ranges: HashMap::new(),
                     methods_no_lines: HashMap::new(),
                     fields: HashMap::new(),
+                    source_file: None,
+                    synthesized: false,
+                    synthesized_methods: HashSet::new(),
                 });
+                last_method = None;
                 continue;
             }
 
@@ -144,6 +236,7 @@ impl ProguardMapping {
                         .unwrap_or(orig_start + (obf_end - obf_start));
                     let obfuscated_name = caps[8].to_string();
 
+                    last_method = Some(method_name.clone());
                     let entry = LineRangeEntry {
                         obf_start,
                         obf_end,
@@ -165,6 +258,7 @@ impl ProguardMapping {
                     let method_name = caps[2].to_string();
                     let _params = &caps[3];
                     let obfuscated_name = caps[4].to_string();
+                    last_method = Some(method_name.clone());
 
                     // Only store if we don't already have line range info for this method
                     if !class.method_line_ranges.contains_key(&obfuscated_name) {
@@ -179,6 +273,7 @@ impl ProguardMapping {
                     let _field_type = &caps[1];
                     let original_name = caps[2].to_string();
                     let obfuscated_name = caps[3].to_string();
+                    last_method = None;
 
                     class.fields.insert(obfuscated_name, original_name);
                 }
@@ -190,11 +285,37 @@ impl ProguardMapping {
             classes.insert(class.obfuscated.clone(), class);
         }
 
-        Ok(Self { classes })
+        let source_files = classes
+            .values()
+            .filter_map(|class| Some((class.original.clone(), class.source_file.clone()?)))
+            .collect();
+
+        Ok(Self {
+            classes,
+            source_files,
+        })
+    }
+
+    /// Source file of an original class: from R8 metadata, or guessed from
+    /// the class name. Nested classes share their outer class's file.
+    fn source_file(&self, original_class: &str) -> Option<String> {
+        let outer = original_class.split('$').next().unwrap_or(original_class);
+        self.source_files
+            .get(original_class)
+            .or_else(|| self.source_files.get(outer))
+            .cloned()
+            .or_else(|| outer.rsplit('.').next().map(|s| format!("{}.java", s)))
+    }
+
+    /// Whether R8 marked a method (by original name) of an obfuscated class
+    /// as synthesized.
+    fn is_synthesized(&self, class: &str, original_method: &str) -> bool {
+        self.classes.get(class).is_some_and(|c| {
+            c.synthesized || c.synthesized_methods.contains(original_method)
+        })
     }
 
     /// Deobfuscate a class name.
-    #[allow(dead_code)]
     fn deobfuscate_class(&self, obfuscated: &str) -> Option<&str> {
         self.classes.get(obfuscated).map(|c| c.original.as_str())
     }
@@ -219,6 +340,26 @@ impl ProguardMapping {
         None
     }
 
+    /// Whether [`deobfuscate_frame`](Self::deobfuscate_frame) resolves the
+    /// frame from the mapping rather than by a guess: the line falls in one of
+    /// the method's line ranges, or the method is mapped without lines.
+    fn resolves_exactly(&self, class: &str, method: &str, line: Option<u32>) -> bool {
+        let Some(class_mapping) = self.classes.get(class) else {
+            return false;
+        };
+        let in_range = line.is_some_and(|line| {
+            class_mapping
+                .method_line_ranges
+                .get(method)
+                .is_some_and(|entries| {
+                    entries
+                        .iter()
+                        .any(|entry| line >= entry.obf_start && line <= entry.obf_end)
+                })
+        });
+        in_range || class_mapping.methods_no_lines.contains_key(method)
+    }
+
     /// Deobfuscate a full stack frame.
     ///
     /// Returns (original_class, original_method, original_line).
@@ -271,12 +412,31 @@ impl ProguardMapping {
 /// Android stack trace symbolicator.
 pub struct AndroidSymbolicator<'a> {
     store: &'a MappingStore,
+    cache: Option<&'a MappingCache>,
+    debuginfod: Option<&'a Debuginfod>,
 }
 
 impl<'a> AndroidSymbolicator<'a> {
     /// Create a new Android symbolicator.
     pub fn new(store: &'a MappingStore) -> Self {
-        Self { store }
+        Self {
+            store,
+            cache: None,
+            debuginfod: None,
+        }
+    }
+
+    /// Reuse parsed mappings from `cache` instead of re-reading them.
+    pub fn with_cache(mut self, cache: &'a MappingCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Fetch native libraries by `BuildId` from `debuginfod` when they
+    /// aren't stored with the mapping.
+    pub fn with_debuginfod(mut self, debuginfod: &'a Debuginfod) -> Self {
+        self.debuginfod = Some(debuginfod);
+        self
     }
 
     /// Symbolicate an Android stack trace.
@@ -285,27 +445,71 @@ impl<'a> AndroidSymbolicator<'a> {
         stack_trace: &str,
         context: &SymbolicationContext,
     ) -> Result<SymbolicatedStack, SymbolicationError> {
+        let find_build_id = |build_id: &str| {
+            self.debuginfod?.debug_file(self.cache, &context.platform, build_id)
+        };
+
         // Load mapping file
-        let mapping_info = self
+        let mapping_info = self.store.get_with_fallback(
+            &context.platform,
+            context.app_id.as_deref().unwrap_or("unknown"),
+            context.version.as_deref().unwrap_or("unknown"),
+        );
+        let mapping_info = match (mapping_info, self.debuginfod) {
+            (Some(info), _) => info,
+            // Native frames can still be resolved by build ID
+            (None, Some(_)) => {
+                let mapping = ProguardMapping::default();
+                return Ok(self.symbolicate_with(stack_trace, &mapping, &[], |_| None, find_build_id));
+            }
+            (None, None) => {
+                return Err(SymbolicationError::MappingNotFound {
+                    platform: "android".to_string(),
+                    app_id: context.app_id.clone().unwrap_or_default(),
+                    version: context.version.clone().unwrap_or_default(),
+                })
+            }
+        };
+
+        // The mapping may be a native library when no ProGuard mapping was uploaded
+        let mapping = if is_native_lib(&mapping_info.path) {
+            Arc::new(ProguardMapping::default())
+        } else {
+            load_mapping(self.cache, &mapping_info, &mapping_info.path, |path| {
+                ProguardMapping::parse(MappingStore::open_file(path)?)
+            })?
+        };
+
+        let version_dir = self
             .store
-            .get_with_fallback(
-                &context.platform,
-                context.app_id.as_deref().unwrap_or("unknown"),
-                context.version.as_deref().unwrap_or("unknown"),
-            )
-            .ok_or_else(|| SymbolicationError::MappingNotFound {
-                platform: "android".to_string(),
-                app_id: context.app_id.clone().unwrap_or_default(),
-                version: context.version.clone().unwrap_or_default(),
-            })?;
-
-        let file = fs::File::open(&mapping_info.path)?;
-        let reader = BufReader::new(file);
-        let mapping = ProguardMapping::parse(reader)?;
+            .root()
+            .join(mapping_info.platform.as_str())
+            .join(&mapping_info.app_id)
+            .join(&mapping_info.version);
+        let native_libs = find_native_libs(&version_dir);
+
+        Ok(self.symbolicate_with(
+            stack_trace,
+            &mapping,
+            &native_libs,
+            |path| load_mapping(self.cache, &mapping_info, path, |p| Ok(DebugFileSlices::new(p))).ok(),
+            find_build_id,
+        ))
+    }
 
+    /// Symbolicate using a parsed mapping and native libraries, opened
+    /// through `open_lib`. Libraries that aren't stored are looked up by
+    /// their `BuildId` through `find_build_id`.
+    fn symbolicate_with(
+        &self,
+        stack_trace: &str,
+        mapping: &ProguardMapping,
+        native_libs: &[NativeLib],
+        mut open_lib: impl FnMut(&Path) -> Option<Arc<DebugFileSlices>>,
+        mut find_build_id: impl FnMut(&str) -> Option<Arc<DebugFileSlices>>,
+    ) -> SymbolicatedStack {
         // Parse and symbolicate each frame
         let mut frames = Vec::new();
-        let mut symbolicated_count = 0;
 
         // Regex for Android stack frames
         // Examples:
@@ -314,6 +518,15 @@ impl<'a> AndroidSymbolicator<'a> {
         let frame_re = Regex::new(
             r"^\s*at\s+([a-zA-Z0-9_.]+)\.([a-zA-Z0-9_<>]+)\(([^:)]+)?:?(\d+)?\)"
         ).unwrap();
+        // Native frames:
+        //   #00 pc 000000000001a2b4  /data/app/~~x/lib/arm64/libfoo.so (Java_a_b+20) (BuildId: ..)
+        let native_re = Regex::new(r"^\s*#(\d+)\s+pc\s+(?:0x)?([0-9a-fA-F]+)\s+(\S+)").unwrap();
+        let abi_re = Regex::new(r"ABI:\s*'([^']+)'").unwrap();
+        let build_id_re = Regex::new(r"\(BuildId:\s*([0-9a-fA-F]+)\)").unwrap();
+
+        let abi = abi_re.captures(stack_trace).map(|c| c[1].to_string());
+        let mut debug_files: HashMap<PathBuf, Option<Arc<DebugFileSlices>>> = HashMap::new();
+        let mut remote_files: HashMap<String, Option<Arc<DebugFileSlices>>> = HashMap::new();
 
         for line in stack_trace.lines() {
             let line = line.trim();
@@ -321,7 +534,62 @@ impl<'a> AndroidSymbolicator<'a> {
                 continue;
             }
 
-            if let Some(caps) = frame_re.captures(line) {
+            if let Some(caps) = native_re.captures(line) {
+                let index: u32 = caps[1].parse().unwrap_or(0);
+                let pc = u64::from_str_radix(&caps[2], 16).unwrap_or(0);
+                let library = caps[3].rsplit('/').next().unwrap_or(&caps[3]);
+
+                let (debug, confidence) =
+                    match select_native_lib(native_libs, library, abi.as_deref()) {
+                        Some(path) => (
+                            debug_files
+                                .entry(path.to_path_buf())
+                                .or_insert_with(|| open_lib(path))
+                                .as_ref(),
+                            Confidence::Exact,
+                        ),
+                        None => (
+                            build_id_re.captures(line).and_then(|caps| {
+                                remote_files
+                                    .entry(caps[1].to_string())
+                                    .or_insert_with(|| find_build_id(&caps[1]))
+                                    .as_ref()
+                            }),
+                            Confidence::BuildId,
+                        ),
+                    };
+                // pc is relative to the library's load address; caller
+                // frames hold return addresses
+                let locations = debug.and_then(|debug| {
+                    debug.with_slice(None, |debug| {
+                        let mut address = debug.address_for_offset(pc);
+                        if index > 0 {
+                            address = address.saturating_sub(1);
+                        }
+                        debug.lookup(address)
+                    })
+                });
+                let Some(locations) = locations else {
+                    frames.push(SymbolicatedFrame::raw(line.to_string()));
+                    continue;
+                };
+                if locations.iter().all(|l| l.function.is_none()) {
+                    frames.push(SymbolicatedFrame::raw(line.to_string()));
+                    continue;
+                }
+                for location in locations {
+                    frames.push(
+                        SymbolicatedFrame::symbolicated(
+                            line.to_string(),
+                            location.function.unwrap_or_else(|| "<unknown>".to_string()),
+                            location.file,
+                            location.line,
+                            location.column,
+                        )
+                        .with_confidence(confidence),
+                    );
+                }
+            } else if let Some(caps) = frame_re.captures(line) {
                 let class = &caps[1];
                 let method = &caps[2];
                 let _source = caps.get(3).map(|m| m.as_str());
@@ -330,20 +598,34 @@ impl<'a> AndroidSymbolicator<'a> {
                 if let Some((orig_class, orig_method, orig_line)) =
                     mapping.deobfuscate_frame(class, method, line_num)
                 {
-                    // Extract source file from original class name
-                    let source_file = orig_class
-                        .rsplit('.')
-                        .next()
-                        .map(|s| format!("{}.java", s));
-
-                    frames.push(SymbolicatedFrame::symbolicated(
-                        line.to_string(),
-                        format!("{}.{}", orig_class, orig_method),
-                        source_file,
-                        orig_line,
-                        None,
-                    ));
-                    symbolicated_count += 1;
+                    if mapping.is_synthesized(class, &orig_method) {
+                        continue;
+                    }
+
+                    // Methods inlined from another class carry its name
+                    let (orig_class, orig_method) = match orig_method.rsplit_once('.') {
+                        Some((inlined_class, method)) => {
+                            (inlined_class.to_string(), method.to_string())
+                        }
+                        None => (orig_class, orig_method),
+                    };
+                    let source_file = mapping.source_file(&orig_class);
+                    let confidence = if mapping.resolves_exactly(class, method, line_num) {
+                        Confidence::Exact
+                    } else {
+                        Confidence::Heuristic
+                    };
+
+                    frames.push(
+                        SymbolicatedFrame::symbolicated(
+                            line.to_string(),
+                            format!("{}.{}", orig_class, orig_method),
+                            source_file,
+                            orig_line,
+                            None,
+                        )
+                        .with_confidence(confidence),
+                    );
                 } else {
                     frames.push(SymbolicatedFrame::raw(line.to_string()));
                 }
@@ -352,18 +634,262 @@ impl<'a> AndroidSymbolicator<'a> {
             }
         }
 
-        Ok(SymbolicatedStack {
+        let frames = collapse_coroutine_frames(frames);
+        let exceptions = exception_chain(&frames, mapping);
+        let symbolicated_count = frames.iter().filter(|f| f.symbolicated).count();
+        let total_count = frames.len();
+
+        SymbolicatedStack {
+            schema_version: SCHEMA_VERSION,
             raw: stack_trace.to_string(),
             frames,
             symbolicated_count,
-            total_count: stack_trace.lines().filter(|l| !l.trim().is_empty()).count(),
-        })
+            total_count,
+            exceptions,
+        }
+    }
+}
+
+impl PlatformSymbolicator for AndroidSymbolicator<'_> {
+    fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        AndroidSymbolicator::symbolicate(self, stack_trace, context)
+    }
+}
+
+/// An unstripped native library found in a mapping directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NativeLib {
+    /// Library file name as it appears in stack frames (e.g. `libfoo.so`).
+    name: String,
+    /// ABI subdirectory the library was found in, if any.
+    abi: Option<String>,
+    path: PathBuf,
+}
+
+/// Class prefixes of Kotlin coroutine machinery (dispatchers, continuations,
+/// debug-probe boundaries).
+const COROUTINE_INTERNAL_PREFIXES: &[&str] = &["kotlin.coroutines.", "kotlinx.coroutines.", "_COROUTINE."];
+
+/// Split symbolicated frames into the exceptions of a `Caused by:` chain.
+///
+/// An exception starts at the first header line (`a.b.C: message`) and at
+/// each `Caused by:` line; its frames run until the next one. Frames of
+/// `Suppressed:` exceptions are skipped.
+fn exception_chain(
+    frames: &[SymbolicatedFrame],
+    mapping: &ProguardMapping,
+) -> Vec<ExceptionCause> {
+    let header_re =
+        Regex::new(r"^(Caused by:\s*)?([A-Za-z_$][\w$]*(?:\.[\w$]+)+)(?::\s*(.*))?$").unwrap();
+    let elided_re = Regex::new(r"^\.\.\.\s+(\d+)\s+more$").unwrap();
+
+    let mut chain: Vec<ExceptionCause> = Vec::new();
+    let mut suppressed = false;
+    for frame in frames {
+        if !frame.symbolicated {
+            if let Some(caps) = header_re.captures(&frame.raw) {
+                if chain.is_empty() || caps.get(1).is_some() {
+                    let class = &caps[2];
+                    chain.push(ExceptionCause {
+                        exception: mapping.deobfuscate_class(class).unwrap_or(class).to_string(),
+                        message: caps.get(3).map(|m| m.as_str().to_string()),
+                        frames: Vec::new(),
+                        elided: 0,
+                    });
+                    suppressed = false;
+                    continue;
+                }
+            }
+            if frame.raw.starts_with("Suppressed:") {
+                suppressed = true;
+                continue;
+            }
+        }
+        let Some(current) = chain.last_mut().filter(|_| !suppressed) else {
+            continue;
+        };
+        if let Some(caps) = elided_re.captures(&frame.raw) {
+            current.elided = caps[1].parse().unwrap_or(0);
+        } else if frame.symbolicated || frame.raw.starts_with("at ") || frame.raw.starts_with('#') {
+            current.frames.push(frame.clone());
+        }
+    }
+    chain
+}
+
+/// Make Kotlin coroutine traces readable.
+///
+/// Runs of coroutine machinery frames are collapsed into a single frame
+/// (the original lines are kept in its `raw` text), and compiler-generated
+/// state machine frames (`Repo$load$1.invokeSuspend`) are relabelled as the
+/// suspend function they belong to, marked `[resumed]`.
+fn collapse_coroutine_frames(frames: Vec<SymbolicatedFrame>) -> Vec<SymbolicatedFrame> {
+    let java_frame_re = Regex::new(r"^\s*at\s+([\w.$<>]+)\(").unwrap();
+    // "com.example.Repo$load$1.invokeSuspend" -> "com.example.Repo", "load"
+    let line_re = Regex::new(r":(\d+)\)").unwrap();
+    let suspend_re = Regex::new(r"^(.+?)\$([A-Za-z_][A-Za-z0-9_]*)\$\d+(?:\$\d+)*\.invokeSuspend$").unwrap();
+
+    let qualified_name = |frame: &SymbolicatedFrame| -> Option<String> {
+        match &frame.function {
+            Some(function) if frame.symbolicated => Some(function.clone()),
+            _ => java_frame_re
+                .captures(&frame.raw)
+                .map(|caps| caps[1].to_string()),
+        }
+    };
+
+    let mut result: Vec<SymbolicatedFrame> = Vec::with_capacity(frames.len());
+    let mut internal: Vec<SymbolicatedFrame> = Vec::new();
+
+    let flush = |internal: &mut Vec<SymbolicatedFrame>, result: &mut Vec<SymbolicatedFrame>| {
+        if internal.is_empty() {
+            return;
+        }
+        let raw = internal
+            .drain(..)
+            .map(|f| f.raw)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let count = raw.lines().count();
+        result.push(SymbolicatedFrame::symbolicated(
+            raw,
+            format!(
+                "<{} coroutine internal frame{}>",
+                count,
+                if count == 1 { "" } else { "s" }
+            ),
+            None,
+            None,
+            None,
+        ));
+    };
+
+    for mut frame in frames {
+        let name = qualified_name(&frame);
+
+        if name
+            .as_deref()
+            .is_some_and(|n| COROUTINE_INTERNAL_PREFIXES.iter().any(|p| n.starts_with(p)))
+        {
+            internal.push(frame);
+            continue;
+        }
+        flush(&mut internal, &mut result);
+
+        if let Some(caps) = name.as_deref().and_then(|n| suspend_re.captures(n)) {
+            let function = format!("{}.{} [resumed]", &caps[1], &caps[2]);
+            if frame.symbolicated {
+                frame.function = Some(function);
+                // "Repo$load$1.java" -> "Repo.java"
+                frame.file = frame.file.map(|file| match (file.find('$'), file.rfind('.')) {
+                    (Some(start), Some(ext)) if start < ext => {
+                        format!("{}{}", &file[..start], &file[ext..])
+                    }
+                    _ => file,
+                });
+            } else {
+                // Keep the line number from the raw frame
+                let line = line_re
+                    .captures(&frame.raw)
+                    .and_then(|c| c[1].parse().ok());
+                let file = caps[1]
+                    .rsplit('.')
+                    .next()
+                    .map(|class| format!("{}.kt", class));
+                frame = SymbolicatedFrame::symbolicated(frame.raw, function, file, line, None);
+            }
+        }
+        result.push(frame);
     }
+    flush(&mut internal, &mut result);
+
+    result
+}
+
+/// Whether a path looks like a native library or its split debug file.
+pub(crate) fn is_native_lib(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    name.ends_with(".so") || name.ends_with(".so.debug") || name.ends_with(".so.dbg")
+}
+
+/// Find native libraries in a version directory and its ABI subdirectories.
+fn find_native_libs(dir: &Path) -> Vec<NativeLib> {
+    let mut libs = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return libs;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let abi = entry.file_name().to_string_lossy().to_string();
+            for lib in fs::read_dir(&path).into_iter().flatten().flatten() {
+                let lib_path = lib.path();
+                if is_native_lib(&lib_path) {
+                    libs.push(NativeLib {
+                        name: library_name(&lib_path),
+                        abi: Some(abi.clone()),
+                        path: lib_path,
+                    });
+                }
+            }
+        } else if is_native_lib(&path) {
+            libs.push(NativeLib {
+                name: library_name(&path),
+                abi: None,
+                path,
+            });
+        }
+    }
+
+    libs.sort_by(|a, b| a.path.cmp(&b.path));
+    libs
+}
+
+/// Library name without a split debug suffix (`libfoo.so.debug` -> `libfoo.so`).
+fn library_name(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    name.trim_end_matches(".debug")
+        .trim_end_matches(".dbg")
+        .to_string()
+}
+
+/// Pick the library matching a frame, preferring the crash's ABI.
+fn select_native_lib<'l>(
+    libs: &'l [NativeLib],
+    library: &str,
+    abi: Option<&str>,
+) -> Option<&'l Path> {
+    // Tombstones report the short ABI name ("arm64"), directories use the
+    // full one ("arm64-v8a")
+    let abi_dir = abi.map(|abi| match abi {
+        "arm64" => "arm64-v8a",
+        "arm" => "armeabi-v7a",
+        other => other,
+    });
+
+    let mut candidates = libs.iter().filter(|lib| lib.name == library);
+    let first = candidates.clone().next();
+    candidates
+        .find(|lib| abi_dir.is_some() && lib.abi.as_deref() == abi_dir)
+        .or(first)
+        .map(|lib| lib.path.as_path())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::symbolication::DebugFile;
     use std::io::Cursor;
 
     #[test]
@@ -442,6 +968,56 @@ com.example.MyClass -> a.a:
         assert_eq!(line, Some(51));
     }
 
+    #[test]
+    fn test_r8_metadata() {
+        let mapping_content = r#"# compiler: R8
+# {"id":"com.android.tools.r8.mapping","version":"2.2"}
+com.example.Repo -> a.b:
+# {"id":"sourceFile","fileName":"Repo.kt"}
+    1:1:void load():20:20 -> a
+    2:2:void com.example.Util.check():7:7 -> a
+    2:2:void load():21:21 -> a
+    1:1:void access$load(com.example.Repo) -> c
+    # {"id":"com.android.tools.r8.synthesized"}
+com.example.Util -> a.c:
+# {"id":"sourceFile","fileName":"Util.kt"}
+com.example.Repo$$ExternalSyntheticLambda0 -> a.d:
+# {"id":"com.android.tools.r8.synthesized"}
+    void run() -> run
+"#;
+        let mapping = ProguardMapping::parse(Cursor::new(mapping_content)).unwrap();
+        assert!(mapping.is_synthesized("a.b", "access$load"));
+        assert!(!mapping.is_synthesized("a.b", "load"));
+        assert!(mapping.is_synthesized("a.d", "run"));
+        assert_eq!(
+            mapping.source_file("com.example.Repo$Inner").as_deref(),
+            Some("Repo.kt")
+        );
+        assert_eq!(
+            mapping.source_file("com.example.Other").as_deref(),
+            Some("Other.java")
+        );
+
+        let store = MappingStore::new("/nonexistent");
+        let sym = AndroidSymbolicator::new(&store);
+        let trace = "\
+java.lang.IllegalStateException: boom
+    at a.b.a(SourceFile:2)
+    at a.b.c(SourceFile:1)
+    at a.d.run(SourceFile:1)
+    at a.b.a(SourceFile:1)";
+        let result = sym.symbolicate_with(trace, &mapping, &[], |_| None, |_| None);
+        let frames: Vec<String> = result.frames.iter().map(|f| f.display()).collect();
+        assert_eq!(
+            frames,
+            [
+                "java.lang.IllegalStateException: boom",
+                "com.example.Util.check (Util.kt:7)",
+                "com.example.Repo.load (Repo.kt:20)",
+            ]
+        );
+    }
+
     #[test]
     fn test_overloaded_methods_different_line_ranges() {
         // Multiple methods with same obfuscated name but different line ranges
@@ -510,5 +1086,194 @@ com.example.MyClass -> a.a:
         assert_eq!(class, "com.example.MyClass");
         assert_eq!(method, "myMethod"); // Method name still resolved
         assert_eq!(line, Some(50)); // Line number preserved since no range matched
+
+        // The method is a guess, so the frame is only heuristic
+        assert!(!mapping.resolves_exactly("a.a", "a", Some(50)));
+        assert!(mapping.resolves_exactly("a.a", "a", Some(5)));
+    }
+
+    #[test]
+    fn test_select_native_lib_prefers_crash_abi() {
+        let libs = vec![
+            NativeLib {
+                name: "libfoo.so".to_string(),
+                abi: Some("arm64-v8a".to_string()),
+                path: PathBuf::from("arm64-v8a/libfoo.so"),
+            },
+            NativeLib {
+                name: "libfoo.so".to_string(),
+                abi: Some("x86_64".to_string()),
+                path: PathBuf::from("x86_64/libfoo.so"),
+            },
+        ];
+
+        assert_eq!(
+            select_native_lib(&libs, "libfoo.so", Some("x86_64")),
+            Some(Path::new("x86_64/libfoo.so"))
+        );
+        assert_eq!(
+            select_native_lib(&libs, "libfoo.so", Some("arm64")),
+            Some(Path::new("arm64-v8a/libfoo.so"))
+        );
+        assert_eq!(
+            select_native_lib(&libs, "libfoo.so", None),
+            Some(Path::new("arm64-v8a/libfoo.so"))
+        );
+        assert_eq!(select_native_lib(&libs, "libbar.so", None), None);
+        assert_eq!(library_name(Path::new("libfoo.so.debug")), "libfoo.so");
+    }
+
+    #[inline(never)]
+    fn ndk_lookup_marker() -> u32 {
+        std::hint::black_box(3)
+    }
+
+    #[test]
+    fn test_symbolicate_mixed_java_and_native_frames() {
+        // Use the test binary as an unstripped native library
+        assert_eq!(ndk_lookup_marker(), 3);
+        let exe = std::env::current_exe().unwrap();
+        let debug = DebugFile::open(&exe, None).unwrap();
+        let pc = debug.find_symbol("ndk_lookup_marker").unwrap() - debug.image_base();
+
+        let mapping = ProguardMapping::parse(Cursor::new(
+            "com.example.MyClass -> a.a:\n    void myMethod() -> a\n",
+        ))
+        .unwrap();
+        let libs = vec![NativeLib {
+            name: "libtest.so".to_string(),
+            abi: None,
+            path: exe,
+        }];
+        let trace = format!(
+            "ABI: 'arm64'\n\
+             #00 pc {:016x}  /data/app/~~abc/com.example-1/lib/arm64/libtest.so (BuildId: 1234)\n\
+             #01 pc 0000000000001000  /system/lib64/libc.so (abort+164)\n\
+             at a.a.a(Unknown Source)\n",
+            pc
+        );
+
+        let store = MappingStore::new("/nonexistent");
+        let result = AndroidSymbolicator::new(&store)
+            .symbolicate_with(&trace, &mapping, &libs, |p| Some(Arc::new(DebugFileSlices::new(p))), |_| None);
+
+        assert!(result.frames[1].symbolicated);
+        assert!(result.frames[1]
+            .function
+            .as_deref()
+            .unwrap()
+            .contains("ndk_lookup_marker"));
+        let libc = result
+            .frames
+            .iter()
+            .find(|f| f.raw.contains("libc.so"))
+            .unwrap();
+        assert!(!libc.symbolicated);
+        let java = result.frames.last().unwrap();
+        assert_eq!(java.function.as_deref(), Some("com.example.MyClass.myMethod"));
+    }
+
+    #[test]
+    fn test_symbolicate_native_frame_by_build_id() {
+        assert_eq!(ndk_lookup_marker(), 3);
+        let exe = std::env::current_exe().unwrap();
+        let debug = DebugFile::open(&exe, None).unwrap();
+        let pc = debug.find_symbol("ndk_lookup_marker").unwrap() - debug.image_base();
+
+        let trace = format!(
+            "#00 pc {:016x}  /data/app/~~abc/com.example-1/lib/arm64/libtest.so (BuildId: 0a1b2c3d)\n\
+             #01 pc 0000000000001000  /system/lib64/libc.so (abort+164) (BuildId: ffff)\n",
+            pc
+        );
+
+        let mut requested = Vec::new();
+        let store = MappingStore::new("/nonexistent");
+        let mapping = ProguardMapping::default();
+        let result = AndroidSymbolicator::new(&store).symbolicate_with(&trace, &mapping, &[], |_| None, |id| {
+            requested.push(id.to_string());
+            (id == "0a1b2c3d").then(|| Arc::new(DebugFileSlices::new(&exe)))
+        });
+
+        assert_eq!(requested, ["0a1b2c3d", "ffff"]);
+        assert!(result.frames[0]
+            .function
+            .as_deref()
+            .unwrap()
+            .contains("ndk_lookup_marker"));
+        assert!(!result.frames.last().unwrap().symbolicated);
+    }
+
+    #[test]
+    fn test_collapse_coroutine_frames() {
+        let mapping = ProguardMapping::parse(Cursor::new(
+            "com.example.Repo$load$1 -> a.b:\n    java.lang.Object invokeSuspend(java.lang.Object) -> c\n",
+        ))
+        .unwrap();
+        let trace = "\
+java.lang.IllegalStateException: boom
+    at com.example.Repo.fetch(Repo.kt:42)
+    at a.b.c(SourceFile:12)
+    at kotlin.coroutines.jvm.internal.BaseContinuationImpl.resumeWith(ContinuationImpl.kt:33)
+    at kotlinx.coroutines.DispatchedTask.run(DispatchedTask.kt:106)
+    at kotlinx.coroutines.scheduling.CoroutineScheduler$Worker.run(CoroutineScheduler.kt:684)
+    at com.example.Other$save$2.invokeSuspend(Other.kt:7)
+    at _COROUTINE._BOUNDARY._(CoroutineDebugging.kt)";
+
+        let store = MappingStore::new("/nonexistent");
+        let result = AndroidSymbolicator::new(&store).symbolicate_with(trace, &mapping, &[], |_| None, |_| None);
+        let displayed: Vec<String> = result.frames.iter().map(|f| f.display()).collect();
+
+        assert_eq!(
+            displayed,
+            vec![
+                "java.lang.IllegalStateException: boom".to_string(),
+                "at com.example.Repo.fetch(Repo.kt:42)".to_string(),
+                "com.example.Repo.load [resumed] (Repo.java:12)".to_string(),
+                "<3 coroutine internal frames>".to_string(),
+                "com.example.Other.save [resumed] (Other.kt:7)".to_string(),
+                "<1 coroutine internal frame>".to_string(),
+            ]
+        );
+        assert!(result.frames[3].raw.contains("DispatchedTask.run"));
+        assert_eq!(result.total_count, 6);
+    }
+
+    #[test]
+    fn test_caused_by_chain() {
+        let mapping = ProguardMapping::parse(Cursor::new(
+            "com.example.Repo -> a.b:\n    1:5:void load():10:14 -> c\n\
+             com.example.LoadException -> a.e:\n",
+        ))
+        .unwrap();
+        let trace = "\
+java.lang.RuntimeException: Unable to start activity
+\tat android.app.ActivityThread.performLaunchActivity(ActivityThread.java:3449)
+\tat android.os.Handler.dispatchMessage(Handler.java:106)
+Caused by: a.e: timeout
+\tat a.b.c(SourceFile:2)
+\tat android.os.Handler.dispatchMessage(Handler.java:106)
+\t... 1 more
+\tSuppressed: java.io.IOException: close failed
+\t\tat java.io.FileInputStream.close(FileInputStream.java:1)";
+
+        let store = MappingStore::new("/nonexistent");
+        let result = AndroidSymbolicator::new(&store).symbolicate_with(
+            trace,
+            &mapping,
+            &[],
+            |_| None,
+            |_| None,
+        );
+
+        assert_eq!(result.exceptions.len(), 2);
+        assert_eq!(result.exceptions[0].exception, "java.lang.RuntimeException");
+        assert_eq!(result.exceptions[0].frames.len(), 2);
+        let root = result.root_cause().unwrap();
+        assert_eq!(root.exception, "com.example.LoadException");
+        assert_eq!(root.message.as_deref(), Some("timeout"));
+        assert_eq!(root.elided, 1);
+        assert_eq!(root.frames.len(), 2);
+        assert_eq!(root.frames[0].display(), "com.example.Repo.load (Repo.java:11)");
+        assert_eq!(result.culprit(), Some(&root.frames[0]));
     }
 }
@@ -0,0 +1,248 @@
+//! In-memory cache of parsed mapping files.
+//!
+//! Parsing a large mapping (a 50MB ProGuard file, a dSYM, a PDB) takes
+//! seconds, so [`Symbolicator`](super::Symbolicator) keeps the most recently
+//! used parsed mappings in an LRU cache shared by all requests.
+//!
+//! Entries are keyed by platform, app, version, file path and modification
+//! time: re-uploading a mapping changes its mtime, so stale entries are never
+//! returned and simply age out.
+//!
+//! Hits and misses are counted per platform for
+//! [`Symbolicator::metrics`](super::Symbolicator::metrics).
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use lru::LruCache;
+
+use super::store::MappingInfo;
+use super::{Platform, SymbolicationError};
+
+/// Default number of parsed mappings kept in memory.
+pub const DEFAULT_CACHE_CAPACITY: usize = 32;
+
+/// Identifies one parsed mapping file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    platform: Platform,
+    app_id: String,
+    version: String,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+type CachedMapping = Arc<dyn Any + Send + Sync>;
+
+/// Lookups of one platform's mappings in a [`MappingCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups that found the parsed mapping in memory.
+    pub hits: u64,
+    /// Lookups that had to parse the mapping.
+    pub misses: u64,
+}
+
+/// Thread-safe LRU cache of parsed mappings.
+///
+/// Values are stored type-erased, so each platform caches its own parsed
+/// representation (ProGuard tables, source maps, DWARF contexts, ...).
+pub struct MappingCache {
+    entries: Mutex<LruCache<CacheKey, CachedMapping>>,
+    stats: Mutex<HashMap<Platform, CacheStats>>,
+}
+
+impl MappingCache {
+    /// Create a cache holding up to `capacity` parsed mappings (at least one).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of cached mappings.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hits and misses since the cache was created, by platform.
+    pub fn stats(&self) -> HashMap<Platform, CacheStats> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Drop all cached mappings.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Return the parsed mapping for `path`, loading it on a miss.
+    ///
+    /// `info` identifies the mapping's platform/app/version; `path` is the
+    /// file actually parsed (the mapping itself or a companion file such as
+    /// a dSYM in the same directory). The loader runs without holding the
+    /// lock, so concurrent misses may parse the same file twice.
+    pub(crate) fn get_or_load<T, F>(
+        &self,
+        info: &MappingInfo,
+        path: &Path,
+        load: F,
+    ) -> Result<Arc<T>, SymbolicationError>
+    where
+        T: Any + Send + Sync,
+        F: FnOnce(&Path) -> Result<T, SymbolicationError>,
+    {
+        let key = CacheKey {
+            platform: info.platform.clone(),
+            app_id: info.app_id.clone(),
+            version: info.version.clone(),
+            path: path.to_path_buf(),
+            modified: std::fs::metadata(path).and_then(|m| m.modified()).ok(),
+        };
+
+        let cached = self
+            .lock()
+            .get(&key)
+            .and_then(|cached| Arc::clone(cached).downcast::<T>().ok());
+        self.record(&info.platform, cached.is_some());
+        if let Some(value) = cached {
+            return Ok(value);
+        }
+
+        let value = Arc::new(load(path)?);
+        self.lock().put(key, value.clone());
+        Ok(value)
+    }
+
+    fn record(&self, platform: &Platform, hit: bool) {
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = stats.entry(platform.clone()).or_default();
+        if hit {
+            stats.hits += 1;
+        } else {
+            stats.misses += 1;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<CacheKey, CachedMapping>> {
+        // A panic while holding the lock can't leave the LRU inconsistent
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for MappingCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+/// Load through `cache` when one is configured, otherwise parse directly.
+pub(crate) fn load_mapping<T, F>(
+    cache: Option<&MappingCache>,
+    info: &MappingInfo,
+    path: &Path,
+    load: F,
+) -> Result<Arc<T>, SymbolicationError>
+where
+    T: Any + Send + Sync,
+    F: FnOnce(&Path) -> Result<T, SymbolicationError>,
+{
+    match cache {
+        Some(cache) => cache.get_or_load(info, path, load),
+        None => load(path).map(Arc::new),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::fs;
+
+    fn info(path: &Path) -> MappingInfo {
+        MappingInfo {
+            path: path.to_path_buf(),
+            files: vec![path.to_path_buf()],
+            platform: Platform::Android,
+            app_id: "com.test.app".to_string(),
+            version: "1.0.0".to_string(),
+            loaded_at: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_get_or_load_caches_until_modified() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mapping.txt");
+        fs::write(&path, "v1").unwrap();
+        let info = info(&path);
+
+        let cache = MappingCache::new(4);
+        let loads = Cell::new(0);
+        let load = |p: &Path| {
+            loads.set(loads.get() + 1);
+            Ok(fs::read_to_string(p)?)
+        };
+
+        assert_eq!(*cache.get_or_load(&info, &path, load).unwrap(), "v1");
+        assert_eq!(*cache.get_or_load(&info, &path, load).unwrap(), "v1");
+        assert_eq!(loads.get(), 1);
+        assert_eq!(
+            cache.stats()[&Platform::Android],
+            CacheStats { hits: 1, misses: 1 }
+        );
+
+        // A re-upload changes the mtime and invalidates the entry
+        let later = SystemTime::now() + std::time::Duration::from_secs(10);
+        fs::write(&path, "v2").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(*cache.get_or_load(&info, &path, load).unwrap(), "v2");
+        assert_eq!(loads.get(), 2);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = MappingCache::new(2);
+
+        for name in ["a", "b", "c"] {
+            let path = dir.path().join(name);
+            fs::write(&path, name).unwrap();
+            cache
+                .get_or_load(&info(&path), &path, |_| Ok(name.to_string()))
+                .unwrap();
+        }
+
+        assert_eq!(cache.len(), 2);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_load_errors_are_not_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mapping.txt");
+        let cache = MappingCache::new(2);
+
+        let result: Result<Arc<String>, _> = cache.get_or_load(&info(&path), &path, |_| {
+            Err(SymbolicationError::ParseError("bad".into()))
+        });
+        assert!(result.is_err());
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+//! debuginfod client.
+//!
+//! [debuginfod](https://sourceware.org/elfutils/Debuginfod.html) servers
+//! serve debug info by GNU build ID at `<server>/buildid/<id>/debuginfo`.
+//! When a native frame carries a build ID and no local debug file matches,
+//! [`Debuginfod`] queries the configured servers in order and keeps the
+//! result in an on-disk cache laid out like the elfutils client's
+//! (`<cache>/<id>/debuginfo`), so downloads are shared with gdb and friends.
+//!
+//! Configuration mirrors elfutils: `DEBUGINFOD_URLS` (space-separated server
+//! URLs) and `DEBUGINFOD_CACHE_PATH`.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use super::cache::{load_mapping, MappingCache};
+use super::native::DebugFileSlices;
+use super::remote::{http_get, DEFAULT_SYMBOL_SERVER_TIMEOUT};
+use super::store::MappingInfo;
+use super::{Platform, SymbolicationError};
+
+/// A set of debuginfod servers with a shared on-disk cache.
+pub struct Debuginfod {
+    servers: Vec<String>,
+    cache_dir: PathBuf,
+    timeout: Duration,
+    /// Build IDs no server had, so they are only requested once.
+    misses: Mutex<HashSet<String>>,
+}
+
+impl Debuginfod {
+    /// Create a client for `servers`, caching downloads in `cache_dir`.
+    pub fn new(servers: Vec<String>, cache_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            servers: servers
+                .into_iter()
+                .map(|s| s.trim_end_matches('/').to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            cache_dir: cache_dir.into(),
+            timeout: DEFAULT_SYMBOL_SERVER_TIMEOUT,
+            misses: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Create a client from `DEBUGINFOD_URLS` and `DEBUGINFOD_CACHE_PATH`.
+    ///
+    /// Returns `None` when no servers are configured.
+    pub fn from_env() -> Option<Self> {
+        let urls = std::env::var("DEBUGINFOD_URLS").ok()?;
+        let servers: Vec<String> = urls.split_whitespace().map(String::from).collect();
+        if servers.is_empty() {
+            return None;
+        }
+        Some(Self::new(servers, Self::default_cache_dir()?))
+    }
+
+    /// `$DEBUGINFOD_CACHE_PATH`, else `$XDG_CACHE_HOME/debuginfod_client`,
+    /// else `~/.cache/debuginfod_client`.
+    pub fn default_cache_dir() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("DEBUGINFOD_CACHE_PATH").filter(|v| !v.is_empty()) {
+            return Some(PathBuf::from(path));
+        }
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+        Some(base.join("debuginfod_client"))
+    }
+
+    /// Set the timeout for each download.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The configured server URLs.
+    pub fn servers(&self) -> &[String] {
+        &self.servers
+    }
+
+    /// The on-disk cache directory.
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    /// Find debug info for a build ID, downloading it if it isn't cached.
+    ///
+    /// Returns the cached file path, or `None` if no server has it.
+    ///
+    /// # Errors
+    ///
+    /// - [`SymbolicationError::InvalidPath`] - `build_id` is not hex
+    /// - [`SymbolicationError::RemoteError`] - A server failed with something other than 404
+    /// - [`SymbolicationError::IoError`] - The cache could not be written
+    pub fn find_debuginfo(&self, build_id: &str) -> Result<Option<PathBuf>, SymbolicationError> {
+        let build_id = normalize_build_id(build_id)?;
+        let path = self.cache_dir.join(&build_id).join("debuginfo");
+        if path.is_file() {
+            return Ok(Some(path));
+        }
+        if self.misses.lock().unwrap_or_else(PoisonError::into_inner).contains(&build_id) {
+            return Ok(None);
+        }
+
+        for server in &self.servers {
+            let url = format!("{}/buildid/{}/debuginfo", server, build_id);
+            let Some(content) = http_get(&url, self.timeout)? else {
+                continue;
+            };
+
+            // Write to a temporary file first so concurrent readers never
+            // see a partial download
+            let dir = path.parent().unwrap_or(&self.cache_dir);
+            fs::create_dir_all(dir)?;
+            let mut file = tempfile::NamedTempFile::new_in(dir)?;
+            file.write_all(&content)?;
+            file.persist(&path).map_err(|e| SymbolicationError::IoError(e.error))?;
+            return Ok(Some(path));
+        }
+
+        self.misses.lock().unwrap_or_else(PoisonError::into_inner).insert(build_id);
+        Ok(None)
+    }
+
+    /// Find and open debug info for a build ID, sharing parsed files
+    /// through `cache`. Lookup failures are treated as "not found".
+    pub(crate) fn debug_file(
+        &self,
+        cache: Option<&MappingCache>,
+        platform: &Platform,
+        build_id: &str,
+    ) -> Option<Arc<DebugFileSlices>> {
+        let path = self.find_debuginfo(build_id).ok()??;
+        let info = MappingInfo {
+            path: path.clone(),
+            files: vec![path.clone()],
+            platform: platform.clone(),
+            app_id: "debuginfod".to_string(),
+            version: build_id.to_string(),
+            loaded_at: SystemTime::now(),
+        };
+        load_mapping(cache, &info, &path, |p| Ok(DebugFileSlices::new(p))).ok()
+    }
+}
+
+/// Lowercase a hex build ID, rejecting anything else.
+fn normalize_build_id(build_id: &str) -> Result<String, SymbolicationError> {
+    let build_id = build_id.trim().to_ascii_lowercase();
+    if build_id.len() < 2 || !build_id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(SymbolicationError::InvalidPath(format!(
+            "Invalid build ID: {}",
+            build_id
+        )));
+    }
+    Ok(build_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_normalize_build_id() {
+        assert_eq!(normalize_build_id(" ABCdef01 ").unwrap(), "abcdef01");
+        assert!(normalize_build_id("../etc").is_err());
+        assert!(normalize_build_id("").is_err());
+    }
+
+    #[test]
+    fn test_find_debuginfo_downloads_once() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = format!("http://{}/", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 4096];
+            let n = stream.read(&mut request).unwrap();
+            let request = String::from_utf8_lossy(&request[..n]).to_string();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\n\x7fELF!")
+                .unwrap();
+            request
+        });
+
+        let cache = tempdir().unwrap();
+        let client = Debuginfod::new(vec![server], cache.path());
+
+        let path = client.find_debuginfo("ABCD1234").unwrap().expect("downloaded");
+        assert!(handle
+            .join()
+            .unwrap()
+            .starts_with("GET /buildid/abcd1234/debuginfo "));
+        assert_eq!(path, cache.path().join("abcd1234").join("debuginfo"));
+        assert_eq!(fs::read(&path).unwrap(), b"\x7fELF!");
+
+        // Served from the cache; the listener is gone
+        assert_eq!(client.find_debuginfo("abcd1234").unwrap(), Some(path));
+    }
+
+    #[test]
+    fn test_find_debuginfo_no_servers() {
+        let cache = tempdir().unwrap();
+        let client = Debuginfod::new(Vec::new(), cache.path());
+        assert_eq!(client.find_debuginfo("abcd").unwrap(), None);
+    }
+}
@@ -0,0 +1,86 @@
+//! Symbol demangling for native frames.
+//!
+//! Native debug info and symbol tables record linkage names, which are
+//! mangled for Rust (`_ZN...17h<hash>E`, v0 `_R...`) and C++ (Itanium ABI,
+//! `_ZN3foo3barEv`, used by Clang/GCC on Android, Apple and Linux). Rust
+//! is tried first: legacy Rust symbols are also valid Itanium names, but
+//! `rustc-demangle` renders them properly and drops the hash.
+
+use cpp_demangle::Symbol;
+
+/// Demangle a Rust or C++ symbol for display.
+///
+/// Names that aren't mangled are returned trimmed, with a trailing Rust
+/// `::h<hash>` removed (`std` prints it in full backtraces).
+///
+/// # Example
+///
+/// ```
+/// use bugstr::symbolication::demangle;
+///
+/// assert_eq!(demangle("_ZN7mycrate4main17h0123456789abcdefE"), "mycrate::main");
+/// assert_eq!(demangle("_ZN3foo3barEv"), "foo::bar()");
+/// assert_eq!(demangle("main"), "main");
+/// ```
+pub fn demangle(name: &str) -> String {
+    let name = name.trim();
+
+    if let Ok(demangled) = rustc_demangle::try_demangle(name) {
+        // The alternate format omits the hash
+        return format!("{:#}", demangled);
+    }
+
+    // Mach-O symbol tables prefix C symbols with an extra underscore
+    let itanium = if name.starts_with("__Z") { &name[1..] } else { name };
+    if itanium.starts_with("_Z") {
+        if let Some(demangled) = Symbol::new(itanium)
+            .ok()
+            .and_then(|symbol| symbol.demangle().ok())
+        {
+            return demangled;
+        }
+    }
+
+    strip_hash(name).to_string()
+}
+
+/// Remove a trailing `::h0123456789abcdef` hash.
+fn strip_hash(name: &str) -> &str {
+    match name.rsplit_once("::h") {
+        Some((path, hash)) if hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            path
+        }
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demangle_rust() {
+        assert_eq!(
+            demangle("_ZN7mycrate6module4func17h0123456789abcdefE"),
+            "mycrate::module::func"
+        );
+        assert_eq!(
+            demangle("std::rt::lang_start::h5f1a2b3c4d5e6f70"),
+            "std::rt::lang_start"
+        );
+        assert_eq!(demangle("  main  "), "main");
+        assert_eq!(demangle("<unknown>"), "<unknown>");
+    }
+
+    #[test]
+    fn test_demangle_cpp() {
+        assert_eq!(
+            demangle("_ZN6google8protobuf7Message9ParseFromEPKc"),
+            "google::protobuf::Message::ParseFrom(char const*)"
+        );
+        // Mach-O extra underscore
+        assert_eq!(demangle("__ZN3foo3barEi"), "foo::bar(int)");
+        // Not a valid mangling: returned unchanged
+        assert_eq!(demangle("_Zgarbage"), "_Zgarbage");
+    }
+}
@@ -1,129 +1,110 @@
 //! Flutter/Dart symbolication.
 //!
-//! Uses Flutter symbol files or the external `flutter symbolize` command
-//! to symbolicate Dart stack traces from release builds.
+//! Release builds made with `--split-debug-info` (usually together with
+//! `--obfuscate`) print non-symbolic stack traces:
+//!
+//! ```text
+//! *** *** *** *** *** *** *** *** *** *** *** *** *** *** *** ***
+//! pid: 5380, tid: 5405, name 1.ui
+//! os: android arch: arm64 comp: yes sim: no
+//! build_id: 'b6951c7c2bb5d8ef0d54a5a0b1d0b0b9'
+//! isolate_dso_base: 7ac4a85000, vm_dso_base: 7ac4a85000
+//! isolate_instructions: 7ac4b46000, vm_instructions: 7ac4b32000
+//!     #00 abs 0000007ac4c59a33 virt 00000000001d4a33 _kDartIsolateSnapshotInstructions+0x113a33
+//! ```
+//!
+//! The `app.<os>-<arch>.symbols` files written next to the build are ELF
+//! files with DWARF debug info, and `virt` is an address in that file, so
+//! frames are resolved with the same DWARF lookups as other native platforms
+//! instead of the `flutter symbolize` CLI.
+//! Frames without `virt` are resolved from their
+//! `_kDart*SnapshotInstructions+0x...` symbol and offset.
+//!
+//! Symbols for several targets can be stored in one version directory; the
+//! file matching the trace's `os:`/`arch:` header is preferred. Traces from
+//! debug and profile builds are already symbolic and are parsed as they are.
 
-use std::io::Write;
-use std::process::Command;
+use std::sync::Arc;
 
 use regex::Regex;
-use tempfile::NamedTempFile;
 
+use super::cache::{load_mapping, MappingCache};
+use super::native::DebugFileSlices;
 use super::{
-    MappingStore, SymbolicatedFrame, SymbolicatedStack, SymbolicationContext, SymbolicationError,
+    MappingStore, PlatformSymbolicator, SymbolicatedFrame, SymbolicatedStack, SymbolicationContext,
+    SymbolicationError, SCHEMA_VERSION,
 };
 
 /// Flutter stack trace symbolicator.
 pub struct FlutterSymbolicator<'a> {
     store: &'a MappingStore,
+    cache: Option<&'a MappingCache>,
 }
 
 impl<'a> FlutterSymbolicator<'a> {
     /// Create a new Flutter symbolicator.
     pub fn new(store: &'a MappingStore) -> Self {
-        Self { store }
+        Self { store, cache: None }
+    }
+
+    /// Reuse parsed symbol files from `cache` instead of re-reading them.
+    pub fn with_cache(mut self, cache: &'a MappingCache) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
     /// Symbolicate a Flutter stack trace.
     ///
-    /// Attempts to use the `flutter symbolize` command if available,
-    /// otherwise falls back to basic symbol file parsing.
+    /// Non-symbolic frames are resolved against the stored symbols file;
+    /// without one, only already-symbolic frames are parsed.
     pub fn symbolicate(
         &self,
         stack_trace: &str,
         context: &SymbolicationContext,
     ) -> Result<SymbolicatedStack, SymbolicationError> {
-        // Try to find symbols file
         let mapping_info = self.store.get_with_fallback(
             &context.platform,
             context.app_id.as_deref().unwrap_or("unknown"),
             context.version.as_deref().unwrap_or("unknown"),
         );
 
-        // Try flutter symbolize command first
-        if let Some(info) = &mapping_info {
-            if let Ok(result) = self.symbolicate_with_flutter_command(stack_trace, &info.path) {
-                return Ok(result);
+        let symbols = match &mapping_info {
+            Some(info) => {
+                // Prefer the symbols of the target the crash came from
+                let target_re = Regex::new(r"os:\s*(\S+)\s+arch:\s*(\S+)").unwrap();
+                let path = target_re
+                    .captures(stack_trace)
+                    .and_then(|caps| {
+                        let dir = info.path.parent()?;
+                        let name = format!("app.{}-{}.symbols", &caps[1], &caps[2]);
+                        let path = dir.join(name);
+                        path.exists().then_some(path)
+                    })
+                    .unwrap_or_else(|| info.path.clone());
+                Some(load_mapping(self.cache, info, &path, |p| {
+                    Ok(DebugFileSlices::new(p))
+                })?)
             }
-        }
+            None => None,
+        };
 
-        // Fall back to basic parsing
-        self.symbolicate_basic(stack_trace, mapping_info.map(|i| i.path.as_path()))
+        Ok(self.symbolicate_with(stack_trace, symbols))
     }
 
-    /// Use `flutter symbolize` command for symbolication.
-    fn symbolicate_with_flutter_command(
+    fn symbolicate_with(
         &self,
         stack_trace: &str,
-        symbols_path: &std::path::Path,
-    ) -> Result<SymbolicatedStack, SymbolicationError> {
-        // Write stack trace to a secure temp file
-        let mut temp_file = NamedTempFile::new()?;
-        temp_file.write_all(stack_trace.as_bytes())?;
-
-        // Run flutter symbolize
-        let output = Command::new("flutter")
-            .args([
-                "symbolize",
-                "-i",
-                temp_file.path().to_str().unwrap(),
-                "-d",
-                symbols_path.to_str().unwrap(),
-            ])
-            .output()
-            .map_err(|e| SymbolicationError::ToolError(format!("flutter symbolize failed: {}", e)))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(SymbolicationError::ToolError(format!(
-                "flutter symbolize failed: {}",
-                stderr
-            )));
-        }
-
-        let symbolicated = String::from_utf8_lossy(&output.stdout);
-
-        // Parse the symbolicated output
-        let frames: Vec<SymbolicatedFrame> = symbolicated
-            .lines()
-            .map(|line| {
-                // Check if line was symbolicated (contains source location)
-                if line.contains("(") && line.contains(".dart:") {
-                    SymbolicatedFrame {
-                        raw: line.to_string(),
-                        function: self.extract_function(line),
-                        file: self.extract_file(line),
-                        line: self.extract_line(line),
-                        column: None,
-                        symbolicated: true,
-                    }
-                } else {
-                    SymbolicatedFrame::raw(line.to_string())
-                }
-            })
-            .collect();
-
-        let symbolicated_count = frames.iter().filter(|f| f.symbolicated).count();
-
-        Ok(SymbolicatedStack {
-            raw: stack_trace.to_string(),
-            frames,
-            symbolicated_count,
-            total_count: stack_trace.lines().filter(|l| !l.trim().is_empty()).count(),
-        })
-    }
-
-    /// Basic symbolication without flutter command.
-    fn symbolicate_basic(
-        &self,
-        stack_trace: &str,
-        _symbols_path: Option<&std::path::Path>,
-    ) -> Result<SymbolicatedStack, SymbolicationError> {
-        // Regex for Dart stack frames
-        // Example: #0      MyClass.myMethod (package:myapp/src/my_class.dart:42:15)
-        let frame_re = Regex::new(
-            r"#(\d+)\s+(.+?)\s+\((.+?):(\d+)(?::(\d+))?\)"
-        ).unwrap();
+        symbols: Option<Arc<DebugFileSlices>>,
+    ) -> SymbolicatedStack {
+        // Non-symbolic frame (AOT release build):
+        //   #00 abs 0000007ac4c59a33 virt 00000000001d4a33 _kDartIsolateSnapshotInstructions+0x113a33
+        let native_re = Regex::new(
+            r"^#(\d+)\s+abs\s+([0-9a-fA-F]+)(?:\s+virt\s+([0-9a-fA-F]+))?(?:\s+(\w+)\+0x([0-9a-fA-F]+))?",
+        )
+        .unwrap();
+        // Symbolic frame (debug build, or already symbolized):
+        //   #0      MyClass.myMethod (package:myapp/src/my_class.dart:42:15)
+        let frame_re = Regex::new(r"#(\d+)\s+(.+?)\s+\((.+?):(\d+)(?::(\d+))?\)").unwrap();
 
         let mut frames = Vec::new();
 
@@ -133,50 +114,157 @@ impl<'a> FlutterSymbolicator<'a> {
                 continue;
             }
 
-            if let Some(caps) = frame_re.captures(line) {
-                let function = caps.get(2).map(|m| m.as_str().to_string());
+            if let Some(caps) = native_re.captures(line) {
+                let index: u32 = caps[1].parse().unwrap_or(0);
+                let virt = caps
+                    .get(3)
+                    .and_then(|m| u64::from_str_radix(m.as_str(), 16).ok());
+                let symbol_offset = caps.get(4).zip(caps.get(5)).and_then(|(symbol, offset)| {
+                    Some((
+                        symbol.as_str(),
+                        u64::from_str_radix(offset.as_str(), 16).ok()?,
+                    ))
+                });
+
+                let locations = symbols.as_ref().and_then(|symbols| {
+                    symbols.with_slice(None, |debug| {
+                        let mut address = match (virt, symbol_offset) {
+                            (Some(virt), _) => virt,
+                            (None, Some((symbol, offset))) => {
+                                debug.symbol_address(symbol)?.wrapping_add(offset)
+                            }
+                            (None, None) => return None,
+                        };
+                        // Caller frames hold return addresses
+                        if index > 0 {
+                            address = address.saturating_sub(1);
+                        }
+                        Some(debug.lookup(address))
+                    })?
+                });
+
+                match locations {
+                    Some(locations) if locations.iter().any(|l| l.function.is_some()) => {
+                        for location in locations {
+                            frames.push(SymbolicatedFrame::symbolicated(
+                                line.to_string(),
+                                location.function.unwrap_or_else(|| "<unknown>".to_string()),
+                                location.file,
+                                location.line,
+                                location.column,
+                            ));
+                        }
+                    }
+                    _ => frames.push(SymbolicatedFrame::raw(line.to_string())),
+                }
+            } else if let Some(caps) = frame_re.captures(line) {
+                let function = caps[2].to_string();
                 let file = caps.get(3).map(|m| m.as_str().to_string());
                 let line_num: Option<u32> = caps.get(4).and_then(|m| m.as_str().parse().ok());
                 let col: Option<u32> = caps.get(5).and_then(|m| m.as_str().parse().ok());
 
-                frames.push(SymbolicatedFrame {
-                    raw: line.to_string(),
+                // Already readable
+                frames.push(SymbolicatedFrame::symbolicated(
+                    line.to_string(),
                     function,
                     file,
-                    line: line_num,
-                    column: col,
-                    symbolicated: true, // Already readable in debug builds
-                });
+                    line_num,
+                    col,
+                ));
             } else {
                 frames.push(SymbolicatedFrame::raw(line.to_string()));
             }
         }
 
         let symbolicated_count = frames.iter().filter(|f| f.symbolicated).count();
+        let total_count = frames.len();
 
-        Ok(SymbolicatedStack {
+        SymbolicatedStack {
+            schema_version: SCHEMA_VERSION,
+            exceptions: Vec::new(),
             raw: stack_trace.to_string(),
             frames,
             symbolicated_count,
-            total_count: stack_trace.lines().filter(|l| !l.trim().is_empty()).count(),
-        })
+            total_count,
+        }
+    }
+}
+
+impl PlatformSymbolicator for FlutterSymbolicator<'_> {
+    fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        FlutterSymbolicator::symbolicate(self, stack_trace, context)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbolication::native::DebugFile;
 
-    fn extract_function(&self, line: &str) -> Option<String> {
-        // Extract function name from symbolicated line
-        let re = Regex::new(r"#\d+\s+(.+?)\s+\(").ok()?;
-        re.captures(line)?.get(1).map(|m| m.as_str().to_string())
+    #[inline(never)]
+    fn dart_lookup_marker() -> u32 {
+        std::hint::black_box(5)
     }
 
-    fn extract_file(&self, line: &str) -> Option<String> {
-        // Extract file path from symbolicated line
-        let re = Regex::new(r"\((.+\.dart):\d+").ok()?;
-        re.captures(line)?.get(1).map(|m| m.as_str().to_string())
+    #[test]
+    fn test_symbolicate_non_symbolic_frames() {
+        // Use the test binary as the ELF symbols file
+        assert_eq!(dart_lookup_marker(), 5);
+        let exe = std::env::current_exe().unwrap();
+        let debug = DebugFile::open(&exe, None).unwrap();
+        let virt = debug.find_symbol("dart_lookup_marker").unwrap();
+
+        let trace = format!(
+            "*** *** *** *** *** *** *** *** *** *** *** *** *** *** *** ***\n\
+             os: android arch: arm64 comp: yes sim: no\n\
+             #00 abs 0000007ac4c59a33 virt {:016x} _kDartIsolateSnapshotInstructions+0x113a33\n\
+             #01 abs 0000007ac4c59b00 _kDartUnknownInstructions+0x10\n\
+             #2      main (package:myapp/main.dart:12:5)",
+            virt
+        );
+
+        let store = MappingStore::new("/nonexistent");
+        let result = FlutterSymbolicator::new(&store)
+            .symbolicate_with(&trace, Some(Arc::new(DebugFileSlices::new(&exe))));
+
+        let frame = result
+            .frames
+            .iter()
+            .find(|f| f.raw.contains("virt"))
+            .unwrap();
+        assert!(frame.symbolicated);
+        assert!(frame
+            .function
+            .as_deref()
+            .unwrap()
+            .contains("dart_lookup_marker"));
+        let unresolved = result
+            .frames
+            .iter()
+            .find(|f| f.raw.contains("#01"))
+            .unwrap();
+        assert!(!unresolved.symbolicated);
+        let symbolic = result.frames.last().unwrap();
+        assert_eq!(symbolic.display(), "main (package:myapp/main.dart:12)");
     }
 
-    fn extract_line(&self, line: &str) -> Option<u32> {
-        // Extract line number from symbolicated line
-        let re = Regex::new(r"\.dart:(\d+)").ok()?;
-        re.captures(line)?.get(1)?.as_str().parse().ok()
+    #[test]
+    fn test_symbolic_trace_without_symbols() {
+        let store = MappingStore::new("/nonexistent");
+        let result = FlutterSymbolicator::new(&store).symbolicate_with(
+            "#0      MyClass.myMethod (package:myapp/src/my_class.dart:42:15)\n\
+             #1      abs 0000007ac4c59a33 virt 00000000001d4a33",
+            None,
+        );
+        assert_eq!(result.symbolicated_count, 1);
+        assert_eq!(
+            result.frames[0].file.as_deref(),
+            Some("package:myapp/src/my_class.dart")
+        );
+        assert_eq!(result.frames[0].line, Some(42));
     }
 }
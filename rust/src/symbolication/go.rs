@@ -1,48 +1,84 @@
 //! Go symbolication.
 //!
-//! Go binaries typically include symbol information by default.
-//! For stripped binaries, this module attempts to use external symbol files.
+//! Go tracebacks usually include source locations already, because every
+//! Go binary embeds its PC-to-line table. When a stack arrives without them
+//! (`?:0 +0x1a` locations, or bare `0x4a1b2c` PCs collected with
+//! `runtime.Callers`), frames are resolved against the `pclntab` of the
+//! binary stored with the mapping: `go.sym` or `go.bin` (the binary itself,
+//! stripped or not), or any `*.debug`/`*.exe` file in the version directory.
+
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use regex::Regex;
 
+use super::cache::{load_mapping, MappingCache};
+use super::gosym::{GoLocation, GoSymbolTable};
 use super::{
-    MappingStore, SymbolicatedFrame, SymbolicatedStack, SymbolicationContext, SymbolicationError,
+    Confidence, MappingStore, PlatformSymbolicator, SymbolicatedFrame, SymbolicatedStack,
+    SymbolicationContext, SymbolicationError, SCHEMA_VERSION,
 };
 
+/// File names a Go binary is stored under, in order of preference.
+const BINARY_FILE_NAMES: &[&str] = &["go.sym", "go.bin"];
+
 /// Go stack trace symbolicator.
 pub struct GoSymbolicator<'a> {
     store: &'a MappingStore,
+    cache: Option<&'a MappingCache>,
 }
 
 impl<'a> GoSymbolicator<'a> {
     /// Create a new Go symbolicator.
     pub fn new(store: &'a MappingStore) -> Self {
-        Self { store }
+        Self { store, cache: None }
+    }
+
+    /// Reuse parsed symbol tables from `cache` instead of re-reading them.
+    pub fn with_cache(mut self, cache: &'a MappingCache) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
     /// Symbolicate a Go stack trace.
     ///
-    /// Go stack traces typically already include source locations.
-    /// This method parses and formats them for display.
+    /// Located frames are parsed and formatted for display; unlocated ones
+    /// are resolved against the stored binary when there is one.
     pub fn symbolicate(
         &self,
         stack_trace: &str,
         context: &SymbolicationContext,
     ) -> Result<SymbolicatedStack, SymbolicationError> {
-        // Try to find symbol file (for stripped binaries)
-        let _mapping_info = self.store.get_with_fallback(
+        let mapping_info = self.store.get_with_fallback(
             &context.platform,
             context.app_id.as_deref().unwrap_or("unknown"),
             context.version.as_deref().unwrap_or("unknown"),
         );
 
-        self.parse_go_stack(stack_trace)
+        // Stacks with locations don't need the binary, so a missing or
+        // unreadable one isn't an error
+        let table = mapping_info.and_then(|info| {
+            let path = Self::find_binary(&info.path)?;
+            load_mapping(self.cache, &info, &path, GoSymbolTable::open).ok()
+        });
+
+        self.parse_go_stack(stack_trace, table)
+    }
+
+    /// The Go binary next to a mapping file.
+    fn find_binary(mapping_path: &std::path::Path) -> Option<PathBuf> {
+        let dir = mapping_path.parent()?;
+        BINARY_FILE_NAMES
+            .iter()
+            .find_map(|name| MappingStore::find_file(dir, name))
+            .or_else(|| MappingStore::find_with_extension(dir, &["debug", "exe"]))
     }
 
-    /// Parse a Go stack trace.
+    /// Parse a Go stack trace, resolving unlocated frames against `table`.
     fn parse_go_stack(
         &self,
         stack_trace: &str,
+        table: Option<Arc<GoSymbolTable>>,
     ) -> Result<SymbolicatedStack, SymbolicationError> {
         // Go stack trace format:
         // goroutine 1 [running]:
@@ -50,16 +86,32 @@ impl<'a> GoSymbolicator<'a> {
         //         /path/to/file.go:42 +0x1a
         // main.main()
         //         /path/to/main.go:10 +0x2b
+        //
+        // Without locations:
+        // main.main()
+        //         ?:0 +0x2b
+        // or one PC per line: "0x4a1b2c", "#1 0x4a1b2c", "pc=0x4a1b2c"
 
         // Matches function names including pointer receivers like main.(*Type).Method
         let func_re = Regex::new(r"^([a-zA-Z0-9_./]+(?:\(\*[^)]+\))?[a-zA-Z0-9_.]*)\(([^)]*)\)$").unwrap();
-        let location_re = Regex::new(r"^\s+(.+\.go):(\d+)\s+\+0x[0-9a-f]+$").unwrap();
+        // GOTRACEBACK=system appends " fp=0x... sp=0x... pc=0x..."
+        let location_re = Regex::new(r"^\s+(.+\.go):(\d+)\s+\+0x[0-9a-f]+(?:\s.*)?$").unwrap();
+        let unknown_location_re = Regex::new(r"^\s+\?:0\s+\+0x([0-9a-f]+)").unwrap();
+        let pc_re = Regex::new(r"^(?:#?\d+:?\s+)?(?:pc=)?0x([0-9a-fA-F]+)$").unwrap();
         let goroutine_re = Regex::new(r"^goroutine\s+\d+\s+\[.+\]:$").unwrap();
 
+        // Callers' PCs are return addresses, so look up the call instruction
+        let resolve = |pc: u64, index: usize| -> Option<GoLocation> {
+            let table = table.as_ref()?;
+            table.lookup(if index > 0 { pc.saturating_sub(1) } else { pc })
+        };
+
         let mut frames = Vec::new();
         let mut current_function: Option<String> = None;
         let mut current_args: Option<String> = None;
         let mut current_raw = String::new();
+        // Position of the current frame in its goroutine
+        let mut frame_index = 0;
 
         for line in stack_trace.lines() {
             let line_trimmed = line.trim();
@@ -67,6 +119,24 @@ impl<'a> GoSymbolicator<'a> {
             // Skip goroutine header
             if goroutine_re.is_match(line_trimmed) {
                 frames.push(SymbolicatedFrame::raw(line.to_string()));
+                frame_index = 0;
+                continue;
+            }
+
+            // Bare PC
+            if let Some(caps) = pc_re.captures(line_trimmed) {
+                let pc = u64::from_str_radix(&caps[1], 16).unwrap_or(0);
+                match resolve(pc, frame_index) {
+                    Some(location) => frames.push(SymbolicatedFrame::symbolicated(
+                        line.to_string(),
+                        location.function,
+                        location.file,
+                        location.line,
+                        None,
+                    )),
+                    None => frames.push(SymbolicatedFrame::raw(line.to_string())),
+                }
+                frame_index += 1;
                 continue;
             }
 
@@ -81,12 +151,39 @@ impl<'a> GoSymbolicator<'a> {
                         line: None,
                         column: None,
                         symbolicated: true,
+                        in_app: None,
+                        confidence: Some(Confidence::Exact),
                     });
                 }
 
                 current_function = Some(caps[1].to_string());
                 current_args = Some(caps[2].to_string());
                 current_raw = line.to_string();
+                frame_index += 1;
+                continue;
+            }
+
+            // Location unknown: resolve from the function's entry PC
+            if let Some(caps) = unknown_location_re.captures(line) {
+                if let Some(func) = current_function.take() {
+                    let offset = u64::from_str_radix(&caps[1], 16).unwrap_or(0);
+                    let location = table
+                        .as_ref()
+                        .and_then(|table| table.entry(&func))
+                        .and_then(|entry| resolve(entry + offset, frame_index - 1));
+                    current_args = None;
+                    frames.push(SymbolicatedFrame {
+                        raw: format!("{}\n{}", current_raw, line),
+                        function: Some(func),
+                        file: location.as_ref().and_then(|l| l.file.clone()),
+                        line: location.as_ref().and_then(|l| l.line),
+                        column: None,
+                        symbolicated: true,
+                        in_app: None,
+                        confidence: Some(Confidence::Exact),
+                    });
+                    current_raw.clear();
+                }
                 continue;
             }
 
@@ -113,6 +210,8 @@ impl<'a> GoSymbolicator<'a> {
                         line: line_num,
                         column: None,
                         symbolicated: true,
+                        in_app: None,
+                        confidence: Some(Confidence::Exact),
                     });
                     current_raw.clear();
                 }
@@ -134,6 +233,8 @@ impl<'a> GoSymbolicator<'a> {
                 line: None,
                 column: None,
                 symbolicated: true,
+                in_app: None,
+                confidence: Some(Confidence::Exact),
             });
         }
 
@@ -141,6 +242,8 @@ impl<'a> GoSymbolicator<'a> {
         let total_count = frames.len();
 
         Ok(SymbolicatedStack {
+            schema_version: SCHEMA_VERSION,
+            exceptions: Vec::new(),
             raw: stack_trace.to_string(),
             frames,
             symbolicated_count,
@@ -149,6 +252,16 @@ impl<'a> GoSymbolicator<'a> {
     }
 }
 
+impl PlatformSymbolicator for GoSymbolicator<'_> {
+    fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        GoSymbolicator::symbolicate(self, stack_trace, context)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,11 +277,57 @@ main.main()
 
         let store = MappingStore::new("/tmp");
         let sym = GoSymbolicator::new(&store);
-        let result = sym.parse_go_stack(stack).unwrap();
+        let result = sym.parse_go_stack(stack, None).unwrap();
 
         assert!(result.symbolicated_count >= 2);
     }
 
+    #[test]
+    fn test_resolve_unlocated_frames() {
+        use crate::symbolication::gosym::tests::{sample_pclntab, TEXT_START};
+        let table = GoSymbolTable::from_pclntab(sample_pclntab(), None, false).unwrap();
+        let stack = format!(
+            "goroutine 1 [running]:\n\
+             main.helper(0x1)\n\
+             \t?:0 +0x4\n\
+             main.main()\n\
+             \t?:0 +0x12\n\
+             0x{:x}",
+            TEXT_START + 0x10
+        );
+
+        let store = MappingStore::new("/tmp");
+        let result = GoSymbolicator::new(&store)
+            .parse_go_stack(&stack, Some(Arc::new(table)))
+            .unwrap();
+
+        let frames: Vec<String> = result.frames.iter().map(|f| f.display()).collect();
+        assert_eq!(
+            frames[1..],
+            [
+                "main.helper (/src/util.go:5)",
+                "main.main (/src/main.go:11)",
+                // Return address 0x10 is the call at line 10
+                "main.main (/src/main.go:10)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unlocated_frames_without_binary() {
+        let stack = "goroutine 1 [running]:\nmain.main()\n\t?:0 +0x2b\n0x4a1b2c";
+
+        let store = MappingStore::new("/tmp");
+        let result = GoSymbolicator::new(&store)
+            .parse_go_stack(stack, None)
+            .unwrap();
+
+        // Named frames are still readable; bare PCs stay raw
+        assert_eq!(result.frames[1].display(), "main.main");
+        assert!(result.frames[1].raw.contains("?:0"));
+        assert!(!result.frames[2].symbolicated);
+    }
+
     #[test]
     fn test_func_re_matches_pointer_receiver() {
         // Matches function names including pointer receivers like main.(*Type).Method
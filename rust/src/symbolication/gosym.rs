@@ -0,0 +1,433 @@
+//! Go symbol table (`pclntab`) reader.
+//!
+//! Every Go binary embeds a PC-to-line table that the runtime uses for its
+//! own tracebacks, and it survives `go build -ldflags=-s` and `strip`: ELF
+//! keeps it in `.gopclntab`, Mach-O in `__gopclntab`, and PE between the
+//! `runtime.pclntab` and `runtime.epclntab` symbols. This reads the table
+//! the way Go's `debug/gosym` does, for the formats written by Go 1.16 and
+//! later.
+//!
+//! Calls inlined into a function are reported at their own source position
+//! but under the name of the function they were inlined into.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use object::{Object, ObjectSection, ObjectSymbol};
+
+use super::{MappingStore, SymbolicationError};
+
+/// `pclntab` magic numbers by the Go version that introduced the layout.
+const MAGIC_GO116: u32 = 0xffff_fffa;
+const MAGIC_GO118: u32 = 0xffff_fff0;
+const MAGIC_GO120: u32 = 0xffff_fff1;
+
+/// A source location resolved from a Go symbol table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GoLocation {
+    /// Fully qualified function name (`main.(*Server).handle`).
+    pub function: String,
+    /// Source file path as recorded at build time.
+    pub file: Option<String>,
+    /// 1-based line number.
+    pub line: Option<u32>,
+}
+
+/// A parsed `pclntab`.
+pub(crate) struct GoSymbolTable {
+    data: Vec<u8>,
+    big_endian: bool,
+    /// Go 1.18 changed function entries to 32-bit offsets from `text_start`.
+    go118: bool,
+    ptr_size: usize,
+    quantum: u32,
+    text_start: u64,
+    nfunc: usize,
+    funcname_offset: usize,
+    cu_offset: usize,
+    filetab_offset: usize,
+    pctab_offset: usize,
+    functab_offset: usize,
+    /// Function name -> entry PC, for frames that only give an offset.
+    entries: HashMap<String, u64>,
+}
+
+impl GoSymbolTable {
+    /// Read the symbol table from a Go binary on disk.
+    pub(crate) fn open(path: &Path) -> Result<Self, SymbolicationError> {
+        Self::parse(&MappingStore::read_file(path)?)
+    }
+
+    /// Read the symbol table from a Go binary in memory.
+    pub(crate) fn parse(binary: &[u8]) -> Result<Self, SymbolicationError> {
+        let file = object::File::parse(binary)
+            .map_err(|e| SymbolicationError::ParseError(format!("Invalid object file: {}", e)))?;
+
+        let pclntab = match file
+            .section_by_name(".gopclntab")
+            .or_else(|| file.section_by_name("__gopclntab"))
+        {
+            Some(section) => section
+                .uncompressed_data()
+                .map_err(|e| SymbolicationError::ParseError(e.to_string()))?
+                .into_owned(),
+            None => pe_pclntab(&file).ok_or_else(|| {
+                SymbolicationError::ParseError("No Go symbol table (pclntab) found".into())
+            })?,
+        };
+        let text_start = file
+            .section_by_name(".text")
+            .or_else(|| file.section_by_name("__text"))
+            .map(|section| section.address());
+
+        Self::from_pclntab(pclntab, text_start, !file.is_little_endian())
+    }
+
+    /// Parse a raw `pclntab`. `text_start` is the address of the text
+    /// section, which Go 1.18+ entry offsets are relative to; the table's
+    /// own copy may be unrelocated.
+    pub(crate) fn from_pclntab(
+        data: Vec<u8>,
+        text_start: Option<u64>,
+        big_endian: bool,
+    ) -> Result<Self, SymbolicationError> {
+        let invalid = || SymbolicationError::ParseError("Truncated Go symbol table".into());
+        if data.len() < 8 {
+            return Err(invalid());
+        }
+
+        let mut table = Self {
+            data,
+            big_endian,
+            go118: false,
+            ptr_size: 0,
+            quantum: 0,
+            text_start: 0,
+            nfunc: 0,
+            funcname_offset: 0,
+            cu_offset: 0,
+            filetab_offset: 0,
+            pctab_offset: 0,
+            functab_offset: 0,
+            entries: HashMap::new(),
+        };
+        let magic = table.u32_at(0).ok_or_else(invalid)?;
+        table.go118 = match magic {
+            MAGIC_GO116 => false,
+            MAGIC_GO118 | MAGIC_GO120 => true,
+            _ => {
+                return Err(SymbolicationError::ParseError(format!(
+                    "Unsupported Go symbol table version {:#x} (Go 1.16 or later is needed)",
+                    magic
+                )))
+            }
+        };
+        table.quantum = table.data[6] as u32;
+        table.ptr_size = table.data[7] as usize;
+        if table.quantum == 0 || !matches!(table.ptr_size, 4 | 8) {
+            return Err(invalid());
+        }
+
+        // Header words after the 8-byte prefix
+        let word =
+            |table: &Self, n: usize| table.word_at(8 + n * table.ptr_size).ok_or_else(invalid);
+        table.nfunc = word(&table, 0)? as usize;
+        let mut next = 2;
+        if table.go118 {
+            let header_text_start = word(&table, next)?;
+            table.text_start = text_start.unwrap_or(header_text_start);
+            next += 1;
+        }
+        table.funcname_offset = word(&table, next)? as usize;
+        table.cu_offset = word(&table, next + 1)? as usize;
+        table.filetab_offset = word(&table, next + 2)? as usize;
+        table.pctab_offset = word(&table, next + 3)? as usize;
+        table.functab_offset = word(&table, next + 4)? as usize;
+
+        // The entry after the last function holds the end of the text
+        table.function_at(table.nfunc).ok_or_else(invalid)?;
+        for index in 0..table.nfunc {
+            let (entry, func) = table.function_at(index).ok_or_else(invalid)?;
+            if let Some(name) = table.function_name(func) {
+                table.entries.insert(name.to_string(), entry);
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// Entry PC of a function by name.
+    pub(crate) fn entry(&self, function: &str) -> Option<u64> {
+        self.entries.get(function).copied()
+    }
+
+    /// Resolve a PC to its function and source location.
+    pub(crate) fn lookup(&self, pc: u64) -> Option<GoLocation> {
+        // Find the last function starting at or before pc
+        let (mut low, mut high) = (0, self.nfunc);
+        while low < high {
+            let mid = (low + high) / 2;
+            if self.function_at(mid)?.0 <= pc {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        let index = low.checked_sub(1)?;
+        let (entry, func) = self.function_at(index)?;
+        let (end, _) = self.function_at(index + 1)?;
+        if pc >= end {
+            return None;
+        }
+
+        let function = self.function_name(func)?.to_string();
+        let line = self
+            .pcvalue(self.func_field(func, 6)?, entry, pc)
+            .and_then(|line| u32::try_from(line).ok());
+        let file = self
+            .pcvalue(self.func_field(func, 5)?, entry, pc)
+            .and_then(|index| {
+                let cu = self.func_field(func, 8)? as usize;
+                let offset =
+                    self.u32_at(self.cu_offset + (cu + usize::try_from(index).ok()?) * 4)?;
+                if offset == u32::MAX {
+                    return None;
+                }
+                self.c_str(self.filetab_offset + offset as usize)
+                    .map(str::to_string)
+            });
+
+        Some(GoLocation {
+            function,
+            file,
+            line,
+        })
+    }
+
+    /// Entry PC and `_func` offset of the `index`th function table entry.
+    fn function_at(&self, index: usize) -> Option<(u64, usize)> {
+        if self.go118 {
+            let at = self.functab_offset + index * 8;
+            let entry = self.text_start + self.u32_at(at)? as u64;
+            Some((entry, self.u32_at(at + 4)? as usize))
+        } else {
+            let at = self.functab_offset + index * 2 * self.ptr_size;
+            let entry = self.word_at(at)?;
+            Some((entry, self.word_at(at + self.ptr_size)? as usize))
+        }
+    }
+
+    /// Field `n` (1-based, after the entry) of the `_func` record at `func`.
+    /// 1 is the name offset, 5 the file table, 6 the line table, 8 the
+    /// compilation unit.
+    fn func_field(&self, func: usize, n: usize) -> Option<u32> {
+        let entry_size = if self.go118 { 4 } else { self.ptr_size };
+        self.u32_at(self.functab_offset + func + entry_size + (n - 1) * 4)
+    }
+
+    fn function_name(&self, func: usize) -> Option<&str> {
+        let offset = self.func_field(func, 1)? as usize;
+        self.c_str(self.funcname_offset + offset)
+    }
+
+    /// Value of the pc-value table at `offset` for `target` in the function
+    /// starting at `entry`.
+    fn pcvalue(&self, offset: u32, entry: u64, target: u64) -> Option<i32> {
+        if offset == 0 {
+            return None;
+        }
+        let mut position = self.pctab_offset + offset as usize;
+        let mut value: i32 = -1;
+        let mut pc = entry;
+        let mut first = true;
+        loop {
+            let delta = self.varint(&mut position)?;
+            if delta == 0 && !first {
+                return None;
+            }
+            first = false;
+            let delta = if delta & 1 != 0 {
+                !(delta >> 1) as i32
+            } else {
+                (delta >> 1) as i32
+            };
+            pc += self.varint(&mut position)? as u64 * self.quantum as u64;
+            value = value.wrapping_add(delta);
+            if target < pc {
+                return Some(value);
+            }
+        }
+    }
+
+    fn varint(&self, position: &mut usize) -> Option<u32> {
+        let mut value = 0u32;
+        let mut shift = 0;
+        loop {
+            let byte = *self.data.get(*position)?;
+            *position += 1;
+            value |= ((byte & 0x7f) as u32).checked_shl(shift)?;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+            shift += 7;
+        }
+    }
+
+    fn c_str(&self, offset: usize) -> Option<&str> {
+        let bytes = self.data.get(offset..)?;
+        let end = bytes.iter().position(|&b| b == 0)?;
+        std::str::from_utf8(&bytes[..end]).ok()
+    }
+
+    fn u32_at(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn word_at(&self, offset: usize) -> Option<u64> {
+        if self.ptr_size == 4 {
+            return self.u32_at(offset).map(u64::from);
+        }
+        let bytes: [u8; 8] = self.data.get(offset..offset + 8)?.try_into().ok()?;
+        Some(if self.big_endian {
+            u64::from_be_bytes(bytes)
+        } else {
+            u64::from_le_bytes(bytes)
+        })
+    }
+}
+
+/// PE binaries have no `pclntab` section; it is delimited by symbols.
+fn pe_pclntab(file: &object::File) -> Option<Vec<u8>> {
+    let symbol = |name: &str| file.symbols().find(|s| s.name() == Ok(name));
+    let start = symbol("runtime.pclntab")?;
+    let end = symbol("runtime.epclntab")?;
+    let section = file.section_by_index(start.section_index()?).ok()?;
+    let data = section.data().ok()?;
+    let from = usize::try_from(start.address().checked_sub(section.address())?).ok()?;
+    let to = usize::try_from(end.address().checked_sub(section.address())?).ok()?;
+    data.get(from..to).map(<[u8]>::to_vec)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const TEXT_START: u64 = 0x401000;
+
+    fn varint(out: &mut Vec<u8>, mut value: u32) {
+        while value >= 0x80 {
+            out.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    /// Encode a pc-value table from `(value, pc length)` runs.
+    fn pcvalue_table(out: &mut Vec<u8>, runs: &[(i32, u32)]) -> u32 {
+        let offset = out.len() as u32;
+        let mut previous = -1;
+        for &(value, length) in runs {
+            let delta = value - previous;
+            varint(
+                out,
+                if delta < 0 {
+                    (((-delta - 1) as u32) << 1) | 1
+                } else {
+                    (delta as u32) << 1
+                },
+            );
+            varint(out, length);
+            previous = value;
+        }
+        out.push(0);
+        offset
+    }
+
+    /// A Go 1.20 table with `main.main` (two lines in main.go) followed by
+    /// `main.helper` (util.go).
+    pub(crate) fn sample_pclntab() -> Vec<u8> {
+        let funcnames = b"main.main\0main.helper\0".to_vec();
+        let cutab: Vec<u8> = [0u32, 13].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let filetab = b"/src/main.go\0/src/util.go\0".to_vec();
+        // Offset 0 means "no table"
+        let mut pctab = vec![0];
+        let main_file = pcvalue_table(&mut pctab, &[(0, 0x40)]);
+        let main_line = pcvalue_table(&mut pctab, &[(10, 0x10), (11, 0x30)]);
+        let helper_file = pcvalue_table(&mut pctab, &[(1, 0x20)]);
+        let helper_line = pcvalue_table(&mut pctab, &[(5, 0x20)]);
+
+        let func = |entry_off: u32, name_off: u32, pcfile: u32, pcln: u32| -> Vec<u8> {
+            [entry_off, name_off, 0, 0, 0, pcfile, pcln, 0, 0, 0, 0]
+                .iter()
+                .flat_map(|v| v.to_le_bytes())
+                .collect()
+        };
+        let funcs_at = 3 * 8;
+        let mut functab = Vec::new();
+        for (entry_off, func_off) in [(0u32, funcs_at), (0x40, funcs_at + 44), (0x60, 0)] {
+            functab.extend(entry_off.to_le_bytes());
+            functab.extend((func_off as u32).to_le_bytes());
+        }
+        functab.extend(func(0, 0, main_file, main_line));
+        functab.extend(func(0x40, 10, helper_file, helper_line));
+
+        let header_len = 8 + 8 * 8;
+        let mut offsets = vec![header_len];
+        for table in [&funcnames, &cutab, &filetab, &pctab] {
+            offsets.push(offsets.last().unwrap() + table.len());
+        }
+
+        let mut data = MAGIC_GO120.to_le_bytes().to_vec();
+        data.extend([0, 0, 1, 8]);
+        for word in [2, 2, TEXT_START as usize]
+            .into_iter()
+            .chain(offsets.iter().copied())
+        {
+            data.extend((word as u64).to_le_bytes());
+        }
+        for table in [funcnames, cutab, filetab, pctab, functab] {
+            data.extend(table);
+        }
+        data
+    }
+
+    #[test]
+    fn test_lookup() {
+        let table = GoSymbolTable::from_pclntab(sample_pclntab(), None, false).unwrap();
+
+        let location = |pc| table.lookup(pc).unwrap();
+        assert_eq!(
+            location(TEXT_START + 0x12),
+            GoLocation {
+                function: "main.main".into(),
+                file: Some("/src/main.go".into()),
+                line: Some(11),
+            }
+        );
+        assert_eq!(location(TEXT_START).line, Some(10));
+        assert_eq!(location(TEXT_START + 0x45).function, "main.helper");
+        assert_eq!(
+            location(TEXT_START + 0x45).file.as_deref(),
+            Some("/src/util.go")
+        );
+        assert_eq!(table.lookup(TEXT_START + 0x60), None);
+        assert_eq!(table.lookup(TEXT_START - 1), None);
+        assert_eq!(table.entry("main.helper"), Some(TEXT_START + 0x40));
+    }
+
+    #[test]
+    fn test_rejects_old_and_garbage() {
+        let mut old = sample_pclntab();
+        old[..4].copy_from_slice(&0xffff_fffbu32.to_le_bytes());
+        assert!(matches!(
+            GoSymbolTable::from_pclntab(old, None, false),
+            Err(SymbolicationError::ParseError(_))
+        ));
+        assert!(GoSymbolTable::parse(b"not a binary").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! In-app frame classification rules.
+//!
+//! Symbolicators mark frames as application or library code when the
+//! mapping says so (a source map's `ignoreList`, a Python path mapping).
+//! [`InAppRules`] let each app configure this explicitly, by function or
+//! package prefix and by source path glob:
+//!
+//! ```toml
+//! [profiles.default.in_app."com.example.app"]
+//! include = ["com.example."]
+//! exclude = ["com.example.thirdparty."]
+//! paths = ["src/**"]
+//! exclude_paths = ["**/node_modules/**"]
+//! ```
+//!
+//! Exclusions win over inclusions. Frames no rule matches keep the
+//! symbolicator's classification; if there is none and the app has
+//! `include` or `paths` rules, they count as library code.
+
+use regex::Regex;
+use serde::Deserialize;
+
+use super::SymbolicatedFrame;
+
+/// Rules classifying the frames of one app as application or library code.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InAppRules {
+    /// Function or package prefixes of application code (`com.example.`).
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Function or package prefixes of library code.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Source path globs of application code (`src/**`).
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Source path globs of library code (`**/node_modules/**`).
+    #[serde(default)]
+    pub exclude_paths: Vec<String>,
+}
+
+impl InAppRules {
+    /// Whether `frame` is application code according to these rules, or
+    /// `None` if no rule matches.
+    ///
+    /// Prefixes are matched against the function name, or against the raw
+    /// text (without a leading `at `) of frames that weren't symbolicated.
+    pub fn classify(&self, frame: &SymbolicatedFrame) -> Option<bool> {
+        let function = frame
+            .function
+            .as_deref()
+            .unwrap_or_else(|| frame.raw.trim().trim_start_matches("at ").trim_start());
+        let file = frame.file.as_deref();
+
+        let has_prefix =
+            |prefixes: &[String]| prefixes.iter().any(|p| function.starts_with(p.as_str()));
+        let has_path =
+            |globs: &[String]| file.is_some_and(|file| globs.iter().any(|g| glob_matches(g, file)));
+
+        if has_prefix(&self.exclude) || has_path(&self.exclude_paths) {
+            Some(false)
+        } else if has_prefix(&self.include) || has_path(&self.paths) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    /// Set `in_app` on `frames` by these rules. Frames no rule matches keep
+    /// their value, and become library code if they have none and the rules
+    /// name application code.
+    pub fn apply(&self, frames: &mut [SymbolicatedFrame]) {
+        let names_app_code = !self.include.is_empty() || !self.paths.is_empty();
+        for frame in frames {
+            match self.classify(frame) {
+                Some(in_app) => frame.in_app = Some(in_app),
+                None if frame.in_app.is_none() && names_app_code => frame.in_app = Some(false),
+                None => {}
+            }
+        }
+    }
+}
+
+/// Match a path against a glob: `**` matches any number of directories, `*`
+/// anything within one path component, `?` one character. Backslashes in
+/// `path` are treated as separators.
+fn glob_matches(glob: &str, path: &str) -> bool {
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // "**/" also matches no directories at all
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+
+    Regex::new(&pattern).is_ok_and(|re| re.is_match(&path.replace('\\', "/")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_app_rules() {
+        let rules = InAppRules {
+            include: vec!["com.example.".to_string()],
+            exclude: vec!["com.example.thirdparty.".to_string()],
+            paths: vec!["src/**".to_string()],
+            exclude_paths: vec!["**/node_modules/**".to_string()],
+        };
+        let frame = |function: &str, file: &str| {
+            SymbolicatedFrame::symbolicated(
+                String::new(),
+                function.to_string(),
+                Some(file.to_string()),
+                Some(1),
+                None,
+            )
+        };
+        let mut frames = vec![
+            frame("com.example.Main.run", "Main.kt"),
+            frame("com.example.thirdparty.Lib.call", "Lib.java"),
+            frame("render", "src/ui/app.ts"),
+            frame("render", "src/node_modules/react/index.js"),
+            frame("java.lang.Thread.run", "Thread.java"),
+            SymbolicatedFrame::raw("at com.example.a.b(Unknown Source)".to_string()),
+        ];
+        frames[4].in_app = Some(true);
+
+        rules.apply(&mut frames);
+        let in_app: Vec<Option<bool>> = frames.iter().map(|f| f.in_app).collect();
+        assert_eq!(
+            in_app,
+            [
+                Some(true),
+                Some(false),
+                Some(true),
+                Some(false),
+                Some(true),
+                Some(true)
+            ]
+        );
+
+        assert!(glob_matches("**/node_modules/**", "node_modules/a.js"));
+        assert!(glob_matches("src/*.ts", "src\\app.ts"));
+        assert!(!glob_matches("src/*.ts", "src/ui/app.ts"));
+    }
+}
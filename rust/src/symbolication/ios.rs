@@ -0,0 +1,368 @@
+//! iOS/macOS symbolication using dSYM debug info.
+//!
+//! Parses Apple crash reports and resolves frame addresses against the
+//! DWARF files inside `.dSYM` bundles uploaded for the app version.
+//!
+//! # Crash Report Format
+//!
+//! ```text
+//! Thread 0 Crashed:
+//! 0   MyApp      0x0000000104a1c3d8 0x104a14000 + 33752
+//! 1   MyApp      0x0000000104a1b2f0 MyApp + 29424
+//! 2   UIKitCore  0x00000001a2b3c4d5 -[UIApplication sendEvent:] + 312
+//!
+//! Binary Images:
+//!        0x104a14000 -        0x104a2ffff MyApp arm64  <5a6b...> /var/containers/.../MyApp
+//! ```
+//!
+//! Apps are loaded at a random slide (ASLR), so each frame is converted to
+//! an offset from its image's load address (taken from the frame itself or
+//! the `Binary Images` section) and then to a file address in the dSYM.
+//!
+//! # Mapping Layout
+//!
+//! ```text
+//! ios/<bundle_id>/<version>/
+//!   MyApp.app.dSYM/Contents/Resources/DWARF/MyApp
+//!   MyFramework.framework.dSYM/Contents/Resources/DWARF/MyFramework
+//!   MyApp.dwarf                    # bare DWARF file named after the image
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use regex::Regex;
+
+use super::cache::{load_mapping, MappingCache};
+use super::native::DebugFileSlices;
+use super::{
+    MappingStore, PlatformSymbolicator, SymbolicatedFrame, SymbolicatedStack, SymbolicationContext,
+    SymbolicationError, SCHEMA_VERSION,
+};
+
+/// A loaded image from the `Binary Images` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BinaryImage {
+    load_address: u64,
+    arch: String,
+}
+
+/// iOS/macOS crash report symbolicator.
+pub struct IosSymbolicator<'a> {
+    store: &'a MappingStore,
+    cache: Option<&'a MappingCache>,
+}
+
+impl<'a> IosSymbolicator<'a> {
+    /// Create a new iOS symbolicator.
+    pub fn new(store: &'a MappingStore) -> Self {
+        Self { store, cache: None }
+    }
+
+    /// Reuse parsed mappings from `cache` instead of re-reading them.
+    pub fn with_cache(mut self, cache: &'a MappingCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Symbolicate an Apple crash report.
+    ///
+    /// Frames from images without a matching dSYM (system frameworks) are
+    /// kept as-is.
+    pub fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        let mapping_info = self
+            .store
+            .get_with_fallback(
+                &context.platform,
+                context.app_id.as_deref().unwrap_or("unknown"),
+                context.version.as_deref().unwrap_or("unknown"),
+            )
+            .ok_or_else(|| SymbolicationError::MappingNotFound {
+                platform: "ios".to_string(),
+                app_id: context.app_id.clone().unwrap_or_default(),
+                version: context.version.clone().unwrap_or_default(),
+            })?;
+
+        let debug_paths = match mapping_info.path.parent() {
+            Some(dir) => find_debug_files(dir),
+            None => HashMap::new(),
+        };
+
+        Ok(self.symbolicate_with(stack_trace, &debug_paths, |path| {
+            load_mapping(self.cache, &mapping_info, path, |p| Ok(DebugFileSlices::new(p))).ok()
+        }))
+    }
+
+    /// Symbolicate using debug files keyed by image name, opened through
+    /// `open_dsym`.
+    fn symbolicate_with(
+        &self,
+        stack_trace: &str,
+        debug_paths: &HashMap<String, PathBuf>,
+        mut open_dsym: impl FnMut(&Path) -> Option<Arc<DebugFileSlices>>,
+    ) -> SymbolicatedStack {
+        // Format: "0   MyApp   0x0000000104a1c3d8 0x104a14000 + 33752"
+        let frame_re = Regex::new(r"^\s*(\d+)\s+(\S+)\s+0x([0-9a-fA-F]+)\s+(.+?)\s*$").unwrap();
+        let load_offset_re = Regex::new(r"^0x([0-9a-fA-F]+) \+ (\d+)$").unwrap();
+        let symbol_offset_re = Regex::new(r"^(.+) \+ (\d+)$").unwrap();
+
+        let images = parse_binary_images(stack_trace);
+        let mut debug_files: HashMap<&str, Option<Arc<DebugFileSlices>>> = HashMap::new();
+        let mut frames = Vec::new();
+
+        for line in stack_trace.lines() {
+            if line.trim_start().starts_with("Binary Images:") {
+                break;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let Some(caps) = frame_re.captures(line) else {
+                frames.push(SymbolicatedFrame::raw(line.to_string()));
+                continue;
+            };
+            let index: u32 = caps[1].parse().unwrap_or(0);
+            let image = caps.get(2).unwrap().as_str();
+            let address = u64::from_str_radix(&caps[3], 16).unwrap_or(0);
+            let location = &caps[4];
+            let binary_image = images.get(image);
+
+            // Offset from the image's load address
+            let offset = if let Some(c) = load_offset_re.captures(location) {
+                c[2].parse::<u64>().ok()
+            } else if let Some(image) = binary_image {
+                address.checked_sub(image.load_address)
+            } else {
+                symbol_offset_re
+                    .captures(location)
+                    .filter(|c| &c[1] == image)
+                    .and_then(|c| c[2].parse::<u64>().ok())
+            };
+
+            let debug = match (offset, debug_paths.get(image)) {
+                (Some(_), Some(path)) => debug_files
+                    .entry(image)
+                    .or_insert_with(|| open_dsym(path))
+                    .as_ref(),
+                _ => None,
+            };
+            let (Some(offset), Some(debug)) = (offset, debug) else {
+                frames.push(SymbolicatedFrame::raw(line.to_string()));
+                continue;
+            };
+
+            // Frames above the crashing one hold return addresses; look up
+            // the call instruction instead
+            let arch = binary_image.map(|i| i.arch.as_str());
+            let locations = debug
+                .with_slice(arch, |debug| {
+                    let mut file_address = debug.address_for_offset(offset);
+                    if index > 0 {
+                        file_address = file_address.saturating_sub(1);
+                    }
+                    debug.lookup(file_address)
+                })
+                .unwrap_or_default();
+            if locations.is_empty() {
+                frames.push(SymbolicatedFrame::raw(line.to_string()));
+                continue;
+            }
+            for location in locations {
+                match location.function {
+                    Some(function) => frames.push(SymbolicatedFrame::symbolicated(
+                        line.to_string(),
+                        function,
+                        location.file,
+                        location.line,
+                        location.column,
+                    )),
+                    None => frames.push(SymbolicatedFrame::raw(line.to_string())),
+                }
+            }
+        }
+
+        let symbolicated_count = frames.iter().filter(|f| f.symbolicated).count();
+        let total_count = frames.len();
+
+        SymbolicatedStack {
+            schema_version: SCHEMA_VERSION,
+            exceptions: Vec::new(),
+            raw: stack_trace.to_string(),
+            frames,
+            symbolicated_count,
+            total_count,
+        }
+    }
+}
+
+impl PlatformSymbolicator for IosSymbolicator<'_> {
+    fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        IosSymbolicator::symbolicate(self, stack_trace, context)
+    }
+}
+
+/// Parse the `Binary Images` section into image name -> load address/arch.
+fn parse_binary_images(stack_trace: &str) -> HashMap<String, BinaryImage> {
+    // Format: "0x104a14000 - 0x104a2ffff +MyApp arm64  <uuid> /path/to/MyApp"
+    let image_re = Regex::new(
+        r"^\s*0x([0-9a-fA-F]+)\s*-\s*0x[0-9a-fA-F]+\s+\+?(\S+)\s+(\S+)\s+<[0-9a-fA-F-]+>",
+    )
+    .unwrap();
+
+    stack_trace
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("Binary Images:"))
+        .filter_map(|line| image_re.captures(line))
+        .filter_map(|caps| {
+            let load_address = u64::from_str_radix(&caps[1], 16).ok()?;
+            Some((
+                caps[2].to_string(),
+                BinaryImage {
+                    load_address,
+                    arch: caps[3].to_string(),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Find DWARF files in a version directory, keyed by image name.
+///
+/// Looks inside `*.dSYM/Contents/Resources/DWARF/` and at bare `*.dwarf`
+/// files (named after the image).
+fn find_debug_files(dir: &Path) -> HashMap<String, PathBuf> {
+    let mut files = HashMap::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if ext == "dsym" && path.is_dir() {
+            let dwarf_dir = path.join("Contents/Resources/DWARF");
+            for dwarf in fs::read_dir(dwarf_dir).into_iter().flatten().flatten() {
+                let name = dwarf.file_name().to_string_lossy().to_string();
+                files.insert(name, dwarf.path());
+            }
+        } else if ext == "dwarf" {
+            if let Some(stem) = path.file_stem() {
+                files.insert(stem.to_string_lossy().to_string(), path.clone());
+            }
+        }
+    }
+
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbolication::DebugFile;
+
+    const CRASH: &str = "\
+Exception Type:  EXC_CRASH (SIGABRT)
+
+Thread 0 Crashed:
+0   libsystem_kernel.dylib  0x00000001b3f1a1f4 __pthread_kill + 8
+1   MyApp                   0x0000000104a1c3d8 0x104a14000 + 33752
+2   MyApp                   0x0000000104a1b2f0 MyApp + 29424
+
+Binary Images:
+       0x104a14000 -        0x104a2ffff MyApp arm64  <5a6b7c8d9e0f11223344556677889900> /var/containers/Bundle/Application/MyApp.app/MyApp
+       0x1b3f15000 -        0x1b3f4cfff libsystem_kernel.dylib arm64e  <aabbccddeeff00112233445566778899> /usr/lib/system/libsystem_kernel.dylib
+";
+
+    #[test]
+    fn test_parse_binary_images() {
+        let images = parse_binary_images(CRASH);
+        assert_eq!(images.len(), 2);
+        assert_eq!(
+            images["MyApp"],
+            BinaryImage {
+                load_address: 0x104a14000,
+                arch: "arm64".to_string(),
+            }
+        );
+        assert_eq!(images["libsystem_kernel.dylib"].arch, "arm64e");
+    }
+
+    #[test]
+    fn test_frames_without_dsym_stay_raw() {
+        let store = MappingStore::new("/nonexistent");
+        let sym = IosSymbolicator::new(&store);
+        let result = sym.symbolicate_with(CRASH, &HashMap::new(), |_| None);
+
+        // Header lines and frames, but nothing from the Binary Images section
+        assert_eq!(result.total_count, 5);
+        assert_eq!(result.symbolicated_count, 0);
+        assert_eq!(
+            result.frames[2].raw,
+            "0   libsystem_kernel.dylib  0x00000001b3f1a1f4 __pthread_kill + 8"
+        );
+    }
+
+    #[test]
+    fn test_find_debug_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let dwarf_dir = dir.path().join("MyApp.app.dSYM/Contents/Resources/DWARF");
+        fs::create_dir_all(&dwarf_dir).unwrap();
+        fs::write(dwarf_dir.join("MyApp"), b"").unwrap();
+        fs::write(dir.path().join("MyFramework.dwarf"), b"").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"").unwrap();
+
+        let files = find_debug_files(dir.path());
+        assert_eq!(files.len(), 2);
+        assert_eq!(files["MyApp"], dwarf_dir.join("MyApp"));
+        assert!(files.contains_key("MyFramework"));
+    }
+
+    #[inline(never)]
+    fn ios_lookup_marker() -> u32 {
+        std::hint::black_box(7)
+    }
+
+    #[test]
+    fn test_symbolicate_slid_address() {
+        // Use the test binary as the "dSYM" for an image loaded at a slide
+        assert_eq!(ios_lookup_marker(), 7);
+        let exe = std::env::current_exe().unwrap();
+        let debug = DebugFile::open(&exe, None).unwrap();
+        let marker = debug.find_symbol("ios_lookup_marker").expect("marker symbol");
+        let offset = marker - debug.image_base();
+
+        // Frame 1 holds a return address, so point one byte past the marker
+        let load = 0x100000000u64;
+        let crash = format!(
+            "Thread 0 Crashed:\n\
+             1   TestApp   0x{:016x} 0x{:x} + {}\n",
+            load + offset + 1,
+            load,
+            offset + 1
+        );
+        let debug_paths = HashMap::from([("TestApp".to_string(), exe)]);
+
+        let store = MappingStore::new("/nonexistent");
+        let result = IosSymbolicator::new(&store)
+            .symbolicate_with(&crash, &debug_paths, |p| Some(Arc::new(DebugFileSlices::new(p))));
+
+        let frame = result.frames.last().unwrap();
+        assert!(frame.symbolicated);
+        assert!(frame.function.as_deref().unwrap().contains("ios_lookup_marker"));
+    }
+}
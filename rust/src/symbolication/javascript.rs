@@ -2,25 +2,100 @@
 //!
 //! Parses source map files (.map) and uses them to map minified
 //! JavaScript stack traces back to original source locations.
+//!
+//! Sources listed in the map's `ignoreList` (or the older
+//! `x_google_ignoreList`) are third-party code; their frames are marked as
+//! library frames. When the map carries scope information, a frame inside
+//! inlined code is expanded into one frame per original call (see
+//! [`scopes`](super::scopes)).
+//!
+//! Electron apps ship one source map per bundle (`main.js.map`,
+//! `preload.js.map`, `renderer.js.map`). Each frame is resolved with the map
+//! named after its bundle file, then with the map named after the crashed
+//! process (`renderer.js.map` for [`SymbolicationContext::process_type`]
+//! `renderer`), falling back to the version's primary map.
 
-use std::fs;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use regex::Regex;
+use serde::Deserialize;
 use sourcemap::SourceMap;
 
+use super::cache::{load_mapping, MappingCache};
+use super::scopes::Scopes;
 use super::{
-    MappingStore, SymbolicatedFrame, SymbolicatedStack, SymbolicationContext, SymbolicationError,
+    MappingStore, PlatformSymbolicator, SymbolicatedFrame, SymbolicatedStack, SymbolicationContext,
+    SymbolicationError, SCHEMA_VERSION,
 };
 
+/// Source map fields the `sourcemap` crate doesn't decode.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceMapExtensions {
+    #[serde(default)]
+    names: Vec<String>,
+    #[serde(rename = "x_google_ignoreList", default)]
+    x_google_ignore_list: Vec<u32>,
+    original_scopes: Option<Vec<Option<String>>>,
+    generated_ranges: Option<String>,
+}
+
+/// A parsed source map with its library sources and scopes.
+struct JsSourceMap {
+    map: SourceMap,
+    /// Indices of third-party sources.
+    ignored: HashSet<u32>,
+    scopes: Option<Scopes>,
+}
+
+impl JsSourceMap {
+    fn parse(content: &[u8]) -> Result<Self, SymbolicationError> {
+        let map = SourceMap::from_slice(content)
+            .map_err(|e| SymbolicationError::ParseError(e.to_string()))?;
+        let extensions: SourceMapExtensions = serde_json::from_slice(content)
+            .map_err(|e| SymbolicationError::ParseError(e.to_string()))?;
+
+        let mut ignored: HashSet<u32> = map.ignore_list().copied().collect();
+        ignored.extend(extensions.x_google_ignore_list);
+
+        let scopes = match (&extensions.original_scopes, &extensions.generated_ranges) {
+            (Some(original), Some(generated)) => {
+                Scopes::parse(&extensions.names, original, generated)
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            map,
+            ignored,
+            scopes,
+        })
+    }
+
+    /// Whether a source is application code. `None` if the map has no ignore list.
+    fn in_app(&self, source: u32) -> Option<bool> {
+        (!self.ignored.is_empty()).then(|| !self.ignored.contains(&source))
+    }
+}
+
 /// JavaScript stack trace symbolicator.
 pub struct JavaScriptSymbolicator<'a> {
     store: &'a MappingStore,
+    cache: Option<&'a MappingCache>,
 }
 
 impl<'a> JavaScriptSymbolicator<'a> {
     /// Create a new JavaScript symbolicator.
     pub fn new(store: &'a MappingStore) -> Self {
-        Self { store }
+        Self { store, cache: None }
+    }
+
+    /// Reuse parsed mappings from `cache` instead of re-reading them.
+    pub fn with_cache(mut self, cache: &'a MappingCache) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
     /// Symbolicate a JavaScript stack trace.
@@ -43,10 +118,41 @@ impl<'a> JavaScriptSymbolicator<'a> {
                 version: context.version.clone().unwrap_or_default(),
             })?;
 
-        let content = fs::read_to_string(&mapping_info.path)?;
-        let sourcemap = SourceMap::from_reader(content.as_bytes())
-            .map_err(|e| SymbolicationError::ParseError(e.to_string()))?;
+        let load = |path: &Path| {
+            load_mapping(self.cache, &mapping_info, path, |path| {
+                JsSourceMap::parse(&MappingStore::read_file(path)?)
+            })
+        };
+        let primary = load(&mapping_info.path)?;
+        let process = context
+            .process_type
+            .as_deref()
+            .and_then(|process| process_map_path(&mapping_info.files, process));
+
+        // Bundle maps are loaded on first use; unreadable ones fall back to
+        // the process's map, then the primary map
+        let mut loaded: HashMap<PathBuf, Option<Arc<JsSourceMap>>> = HashMap::new();
+        Ok(self.symbolicate_with(stack_trace, |bundle| {
+            let mut load_once = |path: &PathBuf| {
+                loaded
+                    .entry(path.clone())
+                    .or_insert_with(|| load(path).ok())
+                    .clone()
+            };
+            let sourcemap = bundle_map_path(&mapping_info.files, bundle)
+                .and_then(&mut load_once)
+                .or_else(|| process.and_then(&mut load_once));
+            Some(sourcemap.unwrap_or_else(|| primary.clone()))
+        }))
+    }
 
+    /// Symbolicate with the source map `source_map_for` returns for each
+    /// frame's bundle file.
+    fn symbolicate_with(
+        &self,
+        stack_trace: &str,
+        mut source_map_for: impl FnMut(&str) -> Option<Arc<JsSourceMap>>,
+    ) -> SymbolicatedStack {
         // Parse and symbolicate each frame
         let mut frames = Vec::new();
         let mut symbolicated_count = 0;
@@ -74,7 +180,7 @@ impl<'a> JavaScriptSymbolicator<'a> {
 
             if let Some(caps) = parsed {
                 let _function = caps.get(1).map(|m| m.as_str());
-                let _file = caps.get(2).map(|m| m.as_str());
+                let file = caps.get(2).map_or("", |m| m.as_str());
                 let line_num: u32 = caps
                     .get(3)
                     .and_then(|m| m.as_str().parse().ok())
@@ -88,24 +194,50 @@ impl<'a> JavaScriptSymbolicator<'a> {
                 let line_0 = if line_num > 0 { line_num - 1 } else { 0 };
                 let col_0 = if col_num > 0 { col_num - 1 } else { 0 };
 
-                if let Some(token) = sourcemap.lookup_token(line_0, col_0) {
-                    let orig_function = token.get_name().map(|s| s.to_string());
-                    let orig_file = token.get_source().map(|s| s.to_string());
-                    let orig_line = token.get_src_line();
-                    let orig_col = token.get_src_col();
-
-                    let function_name = orig_function
-                        .or_else(|| _function.map(|s| s.to_string()))
-                        .unwrap_or_else(|| "<anonymous>".to_string());
-
-                    frames.push(SymbolicatedFrame::symbolicated(
-                        line.to_string(),
-                        function_name,
-                        orig_file,
-                        Some(orig_line + 1), // Convert back to 1-based
-                        Some(orig_col + 1),
-                    ));
-                    symbolicated_count += 1;
+                let sourcemap = source_map_for(file);
+                let token = sourcemap.as_ref().and_then(|sourcemap| {
+                    Some((sourcemap, sourcemap.map.lookup_token(line_0, col_0)?))
+                });
+
+                if let Some((sourcemap, token)) = token {
+                    let inlined = match &sourcemap.scopes {
+                        Some(scopes) => scopes.frames_at(line_0, col_0),
+                        None => Vec::new(),
+                    };
+
+                    // The innermost frame is at the token; each inlined
+                    // function's caller is at its callsite
+                    let mut source = token.get_src_id();
+                    let mut orig_line = token.get_src_line();
+                    let mut orig_col = token.get_src_col();
+                    let mut name = inlined
+                        .first()
+                        .and_then(|f| f.function)
+                        .or(token.get_name())
+                        .or(_function);
+
+                    for index in 0..inlined.len().max(1) {
+                        let mut frame = SymbolicatedFrame::symbolicated(
+                            line.to_string(),
+                            name.unwrap_or("<anonymous>").to_string(),
+                            sourcemap.map.get_source(source).map(|s| s.to_string()),
+                            Some(orig_line + 1), // Convert back to 1-based
+                            Some(orig_col + 1),
+                        );
+                        if let Some(in_app) = sourcemap.in_app(source) {
+                            frame = frame.with_in_app(in_app);
+                        }
+                        frames.push(frame);
+                        symbolicated_count += 1;
+
+                        let Some(callsite) = inlined.get(index).and_then(|f| f.called_from) else {
+                            break;
+                        };
+                        source = callsite.source;
+                        orig_line = callsite.line;
+                        orig_col = callsite.column;
+                        name = inlined.get(index + 1).and_then(|f| f.function);
+                    }
                 } else {
                     frames.push(SymbolicatedFrame::raw(line.to_string()));
                 }
@@ -114,15 +246,59 @@ impl<'a> JavaScriptSymbolicator<'a> {
             }
         }
 
-        Ok(SymbolicatedStack {
+        let total_count = frames.len();
+
+        SymbolicatedStack {
+            schema_version: SCHEMA_VERSION,
+            exceptions: Vec::new(),
             raw: stack_trace.to_string(),
             frames,
             symbolicated_count,
-            total_count: stack_trace.lines().filter(|l| !l.trim().is_empty()).count(),
-        })
+            total_count,
+        }
     }
 }
 
+impl PlatformSymbolicator for JavaScriptSymbolicator<'_> {
+    fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        JavaScriptSymbolicator::symbolicate(self, stack_trace, context)
+    }
+}
+
+/// The source map for a bundle among a version's `files`: `<bundle>.map`,
+/// stored as-is or gzipped. Query strings and fragments in the bundle URL are
+/// ignored.
+/// The source map of an Electron process: a file named `<process>.*.map`
+/// (`renderer.js.map`, `renderer.bundle.js.map`), optionally gzipped.
+fn process_map_path<'f>(files: &'f [PathBuf], process: &str) -> Option<&'f PathBuf> {
+    let prefix = format!("{}.", process).to_lowercase();
+    files.iter().find(|file| {
+        let Some(file_name) = file.file_name() else {
+            return false;
+        };
+        let file_name = file_name.to_string_lossy().to_lowercase();
+        let file_name = file_name.strip_suffix(".gz").unwrap_or(&file_name);
+        file_name.starts_with(&prefix) && file_name.ends_with(".map")
+    })
+}
+
+fn bundle_map_path<'f>(files: &'f [PathBuf], bundle: &str) -> Option<&'f PathBuf> {
+    let path = bundle.split(['?', '#']).next().unwrap_or(bundle);
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let wanted = format!("{}.map", name).to_lowercase();
+    files.iter().find(|file| {
+        let Some(file_name) = file.file_name() else {
+            return false;
+        };
+        let file_name = file_name.to_string_lossy().to_lowercase();
+        file_name.strip_suffix(".gz").unwrap_or(&file_name) == wanted
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,4 +345,119 @@ mod tests {
         assert_eq!(caps.get(3).map(|m| m.as_str()), Some("1"));
         assert_eq!(caps.get(4).map(|m| m.as_str()), Some("2345"));
     }
+
+    #[test]
+    fn test_expand_inlined_frames_and_mark_libraries() {
+        use crate::symbolication::scopes::tests::encode;
+
+        // bundle.js line 1: columns 0-100 are `outer` (app.js), with `inner`
+        // inlined at columns 5-20; column 120 is in node_modules
+        let map = serde_json::json!({
+            "version": 3,
+            "sources": ["app.js", "node_modules/lib/index.js"],
+            "names": ["function", "outer", "inner"],
+            "mappings": encode(&[&[10, 0, 5, 2], &[110, 1, 10, 0]]),
+            "ignoreList": [1],
+            "originalScopes": [
+                encode(&[&[0, 0, 0, 1, 1], &[4, 0, 0, 1, 1], &[2, 1], &[4, 1]]),
+                null
+            ],
+            "generatedRanges": encode(&[&[0, 1, 0, 0], &[5, 3, 0, 1, 0, 2, 4], &[15], &[80]]),
+        });
+        let sourcemap = Arc::new(JsSourceMap::parse(map.to_string().as_bytes()).unwrap());
+
+        let store = MappingStore::new("/nonexistent");
+        let sym = JavaScriptSymbolicator::new(&store);
+        let trace = "\
+Error: boom
+    at a (bundle.js:1:11)
+    at b (bundle.js:1:121)";
+        let result = sym.symbolicate_with(trace, |_| Some(sourcemap.clone()));
+        let displayed: Vec<String> = result.frames.iter().map(|f| f.display()).collect();
+
+        assert_eq!(
+            displayed,
+            [
+                "Error: boom",
+                "inner (app.js:6)",
+                "outer (app.js:3)",
+                "b (node_modules/lib/index.js:16)",
+            ]
+        );
+        assert_eq!(result.frames[1].in_app, Some(true));
+        assert_eq!(result.frames[3].in_app, Some(false));
+        assert_eq!(result.total_count, 4);
+    }
+
+    #[test]
+    fn test_choose_source_map_by_bundle() {
+        use crate::symbolication::Platform;
+        use crate::symbolication::scopes::tests::encode;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = MappingStore::new(dir.path());
+        for (bundle, source) in [("main.js", "src/main.ts"), ("preload.js", "src/preload.ts")] {
+            let map = serde_json::json!({
+                "version": 3,
+                "sources": [source],
+                "names": [],
+                "mappings": encode(&[&[0, 0, 4, 0]]),
+            });
+            store
+                .save_mapping(
+                    Platform::Electron,
+                    "app",
+                    "1.0.0",
+                    &format!("{}.map", bundle),
+                    map.to_string().as_bytes(),
+                )
+                .unwrap();
+        }
+        assert_eq!(
+            store.get(&Platform::Electron, "app", "1.0.0").unwrap().files.len(),
+            2
+        );
+
+        let context = SymbolicationContext {
+            platform: Platform::Electron,
+            app_id: Some("app".to_string()),
+            version: Some("1.0.0".to_string()),
+            build_id: None,
+            process_type: None,
+        };
+        let trace = "\
+    at a (file:///app/resources/app.asar/preload.js?v=1:1:1)
+    at b (file:///app/resources/app.asar/main.js:1:1)";
+        let result = JavaScriptSymbolicator::new(&store)
+            .symbolicate(trace, &context)
+            .unwrap();
+
+        assert_eq!(result.frames[0].file.as_deref(), Some("src/preload.ts"));
+        assert_eq!(result.frames[1].file.as_deref(), Some("src/main.ts"));
+
+        // Bundles without their own map use the crashed process's map
+        let map = serde_json::json!({
+            "version": 3,
+            "sources": ["src/renderer.tsx"],
+            "names": [],
+            "mappings": encode(&[&[0, 0, 4, 0]]),
+        });
+        store
+            .save_mapping(
+                Platform::Electron,
+                "app",
+                "1.0.0",
+                "renderer.bundle.js.map",
+                map.to_string().as_bytes(),
+            )
+            .unwrap();
+        let context = SymbolicationContext {
+            process_type: Some("renderer".to_string()),
+            ..context
+        };
+        let result = JavaScriptSymbolicator::new(&store)
+            .symbolicate("    at c (app://./assets/index-3f2a.js:1:1)", &context)
+            .unwrap();
+        assert_eq!(result.frames[0].file.as_deref(), Some("src/renderer.tsx"));
+    }
 }
@@ -0,0 +1,248 @@
+//! Symbolication timing and hit-rate metrics.
+//!
+//! [`Symbolicator`](super::Symbolicator) records how long each platform takes
+//! to symbolicate a stack trace, how often it fails and how many frames it
+//! resolves, and [`MappingCache`](super::MappingCache) counts its hits and
+//! misses. [`Symbolicator::metrics`](super::Symbolicator::metrics) combines
+//! them into a [`SymbolicationMetrics`] snapshot, served by the dashboard's
+//! `GET /metrics` (Prometheus) and `GET /api/stats` (`bugstr stats`), so slow
+//! or failing mappings show up.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::{SymbolicatedStack, SymbolicationError};
+
+/// Upper bounds, in seconds, of the symbolication duration histogram.
+pub const DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Symbolication metrics since the symbolicator was created.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SymbolicationMetrics {
+    /// Metrics of each platform that was symbolicated, by platform name.
+    pub platforms: BTreeMap<String, PlatformMetrics>,
+}
+
+/// Symbolication metrics of one platform.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlatformMetrics {
+    /// Stack traces symbolicated, including failures.
+    pub requests: u64,
+    /// Stack traces that failed to symbolicate (missing or broken mapping).
+    pub errors: u64,
+    /// Frames in successfully symbolicated stack traces.
+    pub frames: u64,
+    /// Of those, frames resolved to a function or source location.
+    pub symbolicated_frames: u64,
+    /// Parsed mappings found in the mapping cache.
+    pub cache_hits: u64,
+    /// Mappings that had to be parsed.
+    pub cache_misses: u64,
+    /// Number of requests that took at most each of [`DURATION_BUCKETS`].
+    pub duration_buckets: Vec<u64>,
+    /// Total time spent symbolicating, in seconds.
+    pub duration_seconds: f64,
+}
+
+impl Default for PlatformMetrics {
+    fn default() -> Self {
+        Self {
+            requests: 0,
+            errors: 0,
+            frames: 0,
+            symbolicated_frames: 0,
+            cache_hits: 0,
+            cache_misses: 0,
+            duration_buckets: vec![0; DURATION_BUCKETS.len()],
+            duration_seconds: 0.0,
+        }
+    }
+}
+
+impl PlatformMetrics {
+    /// Percentage of frames resolved, or `None` before any frames.
+    pub fn resolution_rate(&self) -> Option<f64> {
+        percentage(self.symbolicated_frames, self.frames)
+    }
+
+    /// Percentage of mapping lookups served from the cache, or `None`
+    /// before any lookups.
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        percentage(self.cache_hits, self.cache_hits + self.cache_misses)
+    }
+
+    /// Mean time to symbolicate a stack trace, or `None` before any requests.
+    pub fn mean_duration(&self) -> Option<Duration> {
+        (self.requests > 0)
+            .then(|| Duration::from_secs_f64(self.duration_seconds / self.requests as f64))
+    }
+
+    /// Record one symbolication request.
+    pub(crate) fn observe(
+        &mut self,
+        elapsed: Duration,
+        result: &Result<SymbolicatedStack, SymbolicationError>,
+    ) {
+        let seconds = elapsed.as_secs_f64();
+        self.requests += 1;
+        self.duration_seconds += seconds;
+        for (count, bound) in self.duration_buckets.iter_mut().zip(DURATION_BUCKETS) {
+            if seconds <= *bound {
+                *count += 1;
+            }
+        }
+        match result {
+            Ok(stack) => {
+                self.frames += stack.total_count as u64;
+                self.symbolicated_frames += stack.symbolicated_count as u64;
+            }
+            Err(_) => self.errors += 1,
+        }
+    }
+}
+
+impl SymbolicationMetrics {
+    /// Render the metrics in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str(
+            "# HELP bugstr_symbolication_duration_seconds Time to symbolicate a stack trace.\n\
+             # TYPE bugstr_symbolication_duration_seconds histogram\n",
+        );
+        for (platform, metrics) in &self.platforms {
+            let platform = escape_label(platform);
+            for (count, bound) in metrics.duration_buckets.iter().zip(DURATION_BUCKETS) {
+                let _ = writeln!(
+                    out,
+                    "bugstr_symbolication_duration_seconds_bucket{{platform=\"{}\",le=\"{}\"}} {}",
+                    platform, bound, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "bugstr_symbolication_duration_seconds_bucket{{platform=\"{}\",le=\"+Inf\"}} {}\n\
+                 bugstr_symbolication_duration_seconds_sum{{platform=\"{}\"}} {}\n\
+                 bugstr_symbolication_duration_seconds_count{{platform=\"{}\"}} {}",
+                platform,
+                metrics.requests,
+                platform,
+                metrics.duration_seconds,
+                platform,
+                metrics.requests
+            );
+        }
+
+        let counters: [Counter; 5] = [
+            (
+                "bugstr_symbolication_errors_total",
+                "Stack traces that failed to symbolicate.",
+                |m| m.errors,
+            ),
+            (
+                "bugstr_symbolication_frames_total",
+                "Frames in symbolicated stack traces.",
+                |m| m.frames,
+            ),
+            (
+                "bugstr_symbolication_frames_resolved_total",
+                "Frames resolved to a function or source location.",
+                |m| m.symbolicated_frames,
+            ),
+            (
+                "bugstr_mapping_cache_hits_total",
+                "Parsed mappings found in the mapping cache.",
+                |m| m.cache_hits,
+            ),
+            (
+                "bugstr_mapping_cache_misses_total",
+                "Mappings parsed on a cache miss.",
+                |m| m.cache_misses,
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}\n# TYPE {} counter", name, help, name);
+            for (platform, metrics) in &self.platforms {
+                let _ = writeln!(
+                    out,
+                    "{}{{platform=\"{}\"}} {}",
+                    name,
+                    escape_label(platform),
+                    value(metrics)
+                );
+            }
+        }
+
+        out
+    }
+}
+
+/// Name, help text and value of a per-platform counter.
+type Counter = (&'static str, &'static str, fn(&PlatformMetrics) -> u64);
+
+fn percentage(part: u64, total: u64) -> Option<f64> {
+    (total > 0).then(|| part as f64 / total as f64 * 100.0)
+}
+
+/// Escape a Prometheus label value.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe_and_render() {
+        let stack = SymbolicatedStack {
+            schema_version: super::super::SCHEMA_VERSION,
+            raw: String::new(),
+            frames: Vec::new(),
+            symbolicated_count: 3,
+            total_count: 4,
+            exceptions: Vec::new(),
+        };
+        let mut android = PlatformMetrics::default();
+        android.observe(Duration::from_millis(20), &Ok(stack));
+        android.observe(
+            Duration::from_secs(2),
+            &Err(SymbolicationError::ParseError("bad".into())),
+        );
+        android.cache_hits = 1;
+        android.cache_misses = 3;
+
+        assert_eq!(android.requests, 2);
+        assert_eq!(android.errors, 1);
+        assert_eq!(android.resolution_rate(), Some(75.0));
+        assert_eq!(android.cache_hit_rate(), Some(25.0));
+        assert_eq!(android.mean_duration(), Some(Duration::from_millis(1010)));
+        assert_eq!(PlatformMetrics::default().resolution_rate(), None);
+
+        let metrics = SymbolicationMetrics {
+            platforms: BTreeMap::from([("android".to_string(), android)]),
+        };
+        let text = metrics.to_prometheus();
+        let lines: Vec<&str> = text.lines().collect();
+        for expected in [
+            "bugstr_symbolication_duration_seconds_bucket{platform=\"android\",le=\"0.01\"} 0",
+            "bugstr_symbolication_duration_seconds_bucket{platform=\"android\",le=\"0.025\"} 1",
+            "bugstr_symbolication_duration_seconds_bucket{platform=\"android\",le=\"2.5\"} 2",
+            "bugstr_symbolication_duration_seconds_bucket{platform=\"android\",le=\"+Inf\"} 2",
+            "bugstr_symbolication_duration_seconds_count{platform=\"android\"} 2",
+            "bugstr_symbolication_errors_total{platform=\"android\"} 1",
+            "bugstr_symbolication_frames_resolved_total{platform=\"android\"} 3",
+            "bugstr_mapping_cache_misses_total{platform=\"android\"} 3",
+        ] {
+            assert!(lines.contains(&expected), "missing {}", expected);
+        }
+    }
+}
@@ -7,11 +7,14 @@
 //!
 //! - **Android**: ProGuard/R8 mapping.txt files
 //! - **JavaScript/Electron**: Source map (.map) files
-//! - **Flutter/Dart**: Flutter symbol files or external `flutter symbolize`
-//! - **Rust**: Backtrace parsing (debug builds include source locations)
-//! - **Go**: Go symbol tables (usually embedded)
+//! - **Flutter/Dart**: `--split-debug-info` symbol files (ELF/DWARF)
+//! - **Rust**: Backtrace parsing, addr2line for stripped release builds
+//! - **Go**: Go symbol tables (`pclntab`) from the stored binary for unlocated frames
 //! - **Python**: Source file mapping for bundled apps
 //! - **React Native**: Hermes bytecode maps + JS source maps
+//! - **iOS/macOS**: dSYM bundles (DWARF), with ASLR slide handling
+//! - **Windows**: PDB files for native `module+offset` frames
+//! - **Unity**: IL2CPP `MethodMap.tsv` and `LineNumberMappings.json`
 //!
 //! # Example
 //!
@@ -26,6 +29,7 @@
 //!     app_id: Some("com.myapp".to_string()),
 //!     version: Some("1.0.0".to_string()),
 //!     build_id: None,
+//!     process_type: None,
 //! };
 //!
 //! let stack_trace = "...";
@@ -33,25 +37,71 @@
 //! ```
 
 mod android;
+mod cache;
+mod debuginfod;
+mod demangle;
 mod javascript;
 mod flutter;
 mod rust_sym;
 mod go;
+mod gosym;
+mod in_app;
+mod metrics;
 mod python;
 mod react_native;
+mod ios;
+mod windows;
+mod unity;
+mod native;
+mod normalize;
+mod remote;
+mod result_cache;
+mod scopes;
 mod store;
+mod validate;
+mod watch;
 
 pub use android::AndroidSymbolicator;
+pub use cache::{CacheStats, MappingCache, DEFAULT_CACHE_CAPACITY};
+pub use debuginfod::Debuginfod;
+pub use in_app::InAppRules;
+pub use metrics::{PlatformMetrics, SymbolicationMetrics, DURATION_BUCKETS};
+pub use demangle::demangle;
 pub use javascript::JavaScriptSymbolicator;
 pub use flutter::FlutterSymbolicator;
 pub use rust_sym::RustSymbolicator;
 pub use go::GoSymbolicator;
 pub use python::PythonSymbolicator;
 pub use react_native::ReactNativeSymbolicator;
-pub use store::MappingStore;
+pub use ios::IosSymbolicator;
+pub use windows::WindowsSymbolicator;
+pub use unity::UnitySymbolicator;
+pub use native::{DebugFile, NativeLocation};
+pub use normalize::{normalize_stack, NormalizedFrame};
+pub use remote::{SymbolServer, DEFAULT_SYMBOL_SERVER_TIMEOUT};
+pub use result_cache::ResultCache;
+pub use store::{DedupStats, MappingChange, MappingInfo, MappingKey, MappingStore, PrunePolicy};
+pub(crate) use store::compare_versions;
+pub use validate::{MappingWarning, MappingWarningKind, Severity};
+pub use watch::MappingWatcher;
 
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use result_cache::ResultKey;
+
+/// Version of the JSON form of [`SymbolicatedStack`] and
+/// [`SymbolicatedFrame`], stored as `schema_version`.
+///
+/// Bumped when a field is removed or changes meaning; added fields keep the
+/// version.
+pub const SCHEMA_VERSION: u32 = 1;
+
 /// Errors that can occur during symbolication.
 #[derive(Error, Debug)]
 pub enum SymbolicationError {
@@ -76,6 +126,9 @@ pub enum SymbolicationError {
 
     #[error("Invalid path component: {0}")]
     InvalidPath(String),
+
+    #[error("Symbol server error: {0}")]
+    RemoteError(String),
 }
 
 /// Platform identifier for crash reports.
@@ -88,11 +141,15 @@ pub enum Platform {
     Go,
     Python,
     ReactNative,
+    Ios,
+    Windows,
+    Unity,
     Unknown(String),
 }
 
 impl Platform {
     /// Parse platform from string.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "android" => Platform::Android,
@@ -102,6 +159,9 @@ impl Platform {
             "go" | "golang" => Platform::Go,
             "python" => Platform::Python,
             "react-native" | "reactnative" | "rn" => Platform::ReactNative,
+            "ios" | "apple" | "macos" => Platform::Ios,
+            "windows" | "win32" | "win" => Platform::Windows,
+            "unity" | "il2cpp" => Platform::Unity,
             other => Platform::Unknown(other.to_string()),
         }
     }
@@ -116,6 +176,9 @@ impl Platform {
             Platform::Go => "go",
             Platform::Python => "python",
             Platform::ReactNative => "react-native",
+            Platform::Ios => "ios",
+            Platform::Windows => "windows",
+            Platform::Unity => "unity",
             Platform::Unknown(s) => s,
         }
     }
@@ -136,6 +199,7 @@ impl Platform {
 ///   - iOS/Flutter: bundle ID (e.g., `"com.example.myapp"`)
 ///   - Electron: app name (e.g., `"my-desktop-app"`)
 ///   - Other: any unique identifier
+///
 ///   When `None`, defaults to `"unknown"` for mapping file lookup.
 ///
 /// * `version` - Optional semantic version string (e.g., `"1.2.3"`).
@@ -143,8 +207,13 @@ impl Platform {
 ///   `"unknown"`. If exact version not found, [`MappingStore::get_with_fallback`]
 ///   returns the newest available version using semver comparison.
 ///
-/// * `build_id` - Optional build identifier or commit hash.
-///   Currently unused but reserved for future build-specific mapping lookup.
+/// * `build_id` - Optional build identifier or commit hash. For Rust, a GNU
+///   build ID (hex) is used to fetch debug info from debuginfod when no debug
+///   file is stored.
+///
+/// * `process_type` - Optional Electron process type (`"main"`, `"renderer"`,
+///   `"gpu"`, ...). Frames whose bundle has no source map of its own are
+///   resolved with the map named after the process (`renderer.js.map`).
 ///
 /// # Example
 ///
@@ -156,6 +225,7 @@ impl Platform {
 ///     app_id: Some("com.myapp".to_string()),
 ///     version: Some("2.1.0".to_string()),
 ///     build_id: None,
+///     process_type: None,
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -168,8 +238,11 @@ pub struct SymbolicationContext {
     /// Application version (e.g., `"1.0.0"`).
     /// Falls back to newest available if exact match not found.
     pub version: Option<String>,
-    /// Build ID or commit hash. Reserved for future use.
+    /// Build ID or commit hash. Rust uses a GNU build ID for debuginfod lookups.
     pub build_id: Option<String>,
+    /// Electron process type (`main`, `renderer`, `gpu`, ...). Selects the
+    /// process's source map for frames whose bundle has none.
+    pub process_type: Option<String>,
 }
 
 /// A single stack frame with optional symbolication information.
@@ -200,6 +273,12 @@ pub struct SymbolicationContext {
 /// * `symbolicated` - `true` if this frame was successfully symbolicated,
 ///   `false` if it contains only raw/unparsed data.
 ///
+/// * `confidence` - How the frame was resolved (see [`Confidence`]); `None`
+///   if it wasn't.
+///
+/// * `in_app` - Whether the frame belongs to the application or to a library,
+///   when the mapping says so (e.g. a source map's `ignoreList`).
+///
 /// # Display Format
 ///
 /// The [`display()`](Self::display) method formats frames as:
@@ -227,7 +306,7 @@ pub struct SymbolicationContext {
 /// assert!(!raw_frame.symbolicated);
 /// assert_eq!(raw_frame.display(), "at a.b.c(Unknown:1)");
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SymbolicatedFrame {
     /// Original raw frame text as it appeared in the stack trace.
     pub raw: String,
@@ -241,6 +320,30 @@ pub struct SymbolicatedFrame {
     pub column: Option<u32>,
     /// Whether this frame was successfully symbolicated.
     pub symbolicated: bool,
+    /// Whether the frame is application code (`Some(true)`) or library
+    /// code (`Some(false)`); `None` when unknown.
+    pub in_app: Option<bool>,
+    /// How far the symbolicated location can be trusted; `None` for frames
+    /// that weren't symbolicated.
+    pub confidence: Option<Confidence>,
+}
+
+/// How a frame was resolved, and so how far it can be trusted.
+///
+/// Serialized in snake case (`"version_fallback"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    /// Resolved with the mapping stored for the crash's version.
+    Exact,
+    /// Resolved with debug info matched by build ID.
+    BuildId,
+    /// Resolved with the mapping of another version, because the crash's
+    /// version has none. Locations may be wrong if the code changed.
+    VersionFallback,
+    /// Resolved by a best guess, such as a ProGuard method whose line
+    /// ranges don't cover the frame's line.
+    Heuristic,
 }
 
 impl SymbolicatedFrame {
@@ -253,6 +356,8 @@ impl SymbolicatedFrame {
             line: None,
             column: None,
             symbolicated: false,
+            in_app: None,
+            confidence: None,
         }
     }
 
@@ -271,9 +376,25 @@ impl SymbolicatedFrame {
             line,
             column,
             symbolicated: true,
+            in_app: None,
+            confidence: Some(Confidence::Exact),
         }
     }
 
+    /// Mark the frame as application code (`true`) or library code (`false`).
+    pub fn with_in_app(mut self, in_app: bool) -> Self {
+        self.in_app = Some(in_app);
+        self
+    }
+
+    /// Set how the frame was resolved. Ignored for unsymbolicated frames.
+    pub fn with_confidence(mut self, confidence: Confidence) -> Self {
+        if self.symbolicated {
+            self.confidence = Some(confidence);
+        }
+        self
+    }
+
     /// Format the frame for display.
     pub fn display(&self) -> String {
         if self.symbolicated {
@@ -317,6 +438,18 @@ impl SymbolicatedFrame {
 ///   Note: This counts all non-empty lines, which may differ from `frames.len()`
 ///   depending on the platform-specific parser implementation.
 ///
+/// * `schema_version` - [`SCHEMA_VERSION`] of the result, so stored JSON can
+///   be re-loaded and checked.
+///
+/// * `exceptions` - The `Caused by:` chain as [`ExceptionCause`]s, for
+///   platforms that parse one (Android); see [`root_cause`](Self::root_cause).
+///
+/// # Serialization
+///
+/// Both types serialize to JSON with the field names above; the web API and
+/// `bugstr symbolicate --format json` return this form, and it can be
+/// deserialized back into a `SymbolicatedStack`.
+///
 /// # Example
 ///
 /// ```
@@ -332,8 +465,10 @@ impl SymbolicatedFrame {
 ///     println!("{}", result.display());
 /// }
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SymbolicatedStack {
+    /// Version of this structure's JSON form; see [`SCHEMA_VERSION`].
+    pub schema_version: u32,
     /// Original raw stack trace text as provided to the symbolicator.
     pub raw: String,
     /// Processed frames in stack trace order.
@@ -342,6 +477,24 @@ pub struct SymbolicatedStack {
     pub symbolicated_count: usize,
     /// Total count of non-empty lines in the original stack trace.
     pub total_count: usize,
+    /// Exceptions of a `Caused by:` chain, outermost first. Empty for traces
+    /// without one, and for platforms that don't parse chains.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exceptions: Vec<ExceptionCause>,
+}
+
+/// One exception of a chained stack trace (`Caused by: ...`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExceptionCause {
+    /// Exception class, deobfuscated when the mapping knows it.
+    pub exception: String,
+    /// Exception message, if any.
+    pub message: Option<String>,
+    /// The exception's own frames.
+    pub frames: Vec<SymbolicatedFrame>,
+    /// Number of frames elided as `... N more`; they are the last frames of
+    /// the enclosing exception.
+    pub elided: usize,
 }
 
 impl SymbolicatedStack {
@@ -382,12 +535,14 @@ impl SymbolicatedStack {
     /// # Example
     ///
     /// ```
-    /// # use bugstr::symbolication::{SymbolicatedStack, SymbolicatedFrame};
+    /// # use bugstr::symbolication::{SymbolicatedStack, SymbolicatedFrame, SCHEMA_VERSION};
     /// # let stack = SymbolicatedStack {
+    /// #     schema_version: SCHEMA_VERSION,
     /// #     raw: String::new(),
     /// #     frames: vec![],
     /// #     symbolicated_count: 8,
     /// #     total_count: 10,
+    /// #     exceptions: vec![],
     /// # };
     /// let pct = stack.percentage();
     /// assert!((pct - 80.0).abs() < 0.001);
@@ -399,6 +554,94 @@ impl SymbolicatedStack {
             (self.symbolicated_count as f64 / self.total_count as f64) * 100.0
         }
     }
+
+    /// The innermost exception of a `Caused by:` chain, which is usually the
+    /// one to group crashes by. `None` if the trace has no parsed chain.
+    pub fn root_cause(&self) -> Option<&ExceptionCause> {
+        self.exceptions.last()
+    }
+
+    /// The frame most likely responsible for the crash, for grouping: the
+    /// first application frame of the root cause (or of the whole stack),
+    /// falling back to its first symbolicated frame.
+    pub fn culprit(&self) -> Option<&SymbolicatedFrame> {
+        let frames = self.root_cause().map_or(&self.frames, |cause| &cause.frames);
+        frames
+            .iter()
+            .find(|f| f.in_app == Some(true))
+            .or_else(|| frames.iter().find(|f| f.symbolicated))
+    }
+}
+
+/// Symbolication for one platform.
+///
+/// Each built-in platform symbolicator ([`AndroidSymbolicator`],
+/// [`JavaScriptSymbolicator`], ...) implements this trait. Implement it for
+/// other runtimes and add it with [`Symbolicator::register`]; registered
+/// implementations take precedence over the built-in ones, and handle
+/// [`Platform::Unknown`] platforms by name. They own whatever they need, such
+/// as their own [`MappingStore`].
+///
+/// # Example
+///
+/// ```
+/// use bugstr::symbolication::{
+///     MappingStore, Platform, PlatformSymbolicator, SymbolicatedFrame, SymbolicatedStack,
+///     SymbolicationContext, SymbolicationError, Symbolicator, SCHEMA_VERSION,
+/// };
+///
+/// /// Frames of a proprietary runtime are `fn@file:line`.
+/// struct MyRuntime;
+///
+/// impl PlatformSymbolicator for MyRuntime {
+///     fn symbolicate(
+///         &self,
+///         stack_trace: &str,
+///         _context: &SymbolicationContext,
+///     ) -> Result<SymbolicatedStack, SymbolicationError> {
+///         let frames: Vec<SymbolicatedFrame> = stack_trace
+///             .lines()
+///             .map(|line| match line.split_once('@') {
+///                 Some((function, location)) => SymbolicatedFrame::symbolicated(
+///                     line.to_string(),
+///                     function.to_string(),
+///                     Some(location.to_string()),
+///                     None,
+///                     None,
+///                 ),
+///                 None => SymbolicatedFrame::raw(line.to_string()),
+///             })
+///             .collect();
+///         Ok(SymbolicatedStack {
+///             schema_version: SCHEMA_VERSION,
+///             raw: stack_trace.to_string(),
+///             symbolicated_count: frames.iter().filter(|f| f.symbolicated).count(),
+///             total_count: frames.len(),
+///             frames,
+///             exceptions: Vec::new(),
+///         })
+///     }
+/// }
+///
+/// let symbolicator = Symbolicator::new(MappingStore::new("/nonexistent"))
+///     .register(Platform::from_str("myruntime"), MyRuntime);
+/// let context = SymbolicationContext {
+///     platform: Platform::from_str("myruntime"),
+///     app_id: None,
+///     version: None,
+///     build_id: None,
+///     process_type: None,
+/// };
+/// let result = symbolicator.symbolicate("boom@main.my", &context).unwrap();
+/// assert_eq!(result.frames[0].display(), "boom (main.my)");
+/// ```
+pub trait PlatformSymbolicator: Send + Sync {
+    /// Symbolicate `stack_trace` from the crash described by `context`.
+    fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError>;
 }
 
 /// Main symbolicator that dispatches to platform-specific implementations.
@@ -412,17 +655,49 @@ impl SymbolicatedStack {
 ///
 /// - [`Platform::Android`] - Uses [`AndroidSymbolicator`] with ProGuard/R8 mapping.txt files
 /// - [`Platform::Electron`] - Uses [`JavaScriptSymbolicator`] with source map files
-/// - [`Platform::Flutter`] - Uses [`FlutterSymbolicator`] with Flutter symbol files
-/// - [`Platform::Rust`] - Uses [`RustSymbolicator`] for backtrace parsing
+/// - [`Platform::Flutter`] - Uses [`FlutterSymbolicator`] with Flutter `--split-debug-info` symbol files
+/// - [`Platform::Rust`] - Uses [`RustSymbolicator`] for backtrace parsing and addr2line
 /// - [`Platform::Go`] - Uses [`GoSymbolicator`] for goroutine stack parsing
 /// - [`Platform::Python`] - Uses [`PythonSymbolicator`] for Python traceback parsing
 /// - [`Platform::ReactNative`] - Uses [`ReactNativeSymbolicator`] with Hermes + JS source maps
+/// - [`Platform::Ios`] - Uses [`IosSymbolicator`] with dSYM debug info
+/// - [`Platform::Windows`] - Uses [`WindowsSymbolicator`] with PDB files
+/// - [`Platform::Unity`] - Uses [`UnitySymbolicator`] with IL2CPP mapping files
+///
+/// # Caching
+///
+/// Parsed mapping files are kept in a [`MappingCache`] (LRU, keyed by
+/// platform/app/version and file modification time), so repeated requests
+/// for the same app version skip re-reading and re-parsing the mapping.
+/// With [`with_result_cache`](Self::with_result_cache), whole results are
+/// also kept in SQLite by stack hash and mapping identity, so a repeated
+/// stack skips symbolication entirely until its mapping changes.
 ///
 /// # Thread Safety
 ///
-/// `Symbolicator` is `Send` but not `Sync`. For use in async contexts with multiple
-/// concurrent requests, wrap in `Arc<Symbolicator>` and use `spawn_blocking` for
-/// the CPU-bound symbolication work.
+/// `Symbolicator` is `Send + Sync`. For use in async contexts with multiple
+/// concurrent requests, wrap in `Arc<Symbolicator>` and use `spawn_blocking`
+/// for the CPU-bound symbolication work. The mapping store and cache are
+/// shared safely between requests, and [`store()`](Self::store) can be
+/// rescanned while requests are in flight.
+///
+/// # Symbol Server
+///
+/// With [`with_symbol_server`](Self::with_symbol_server), mappings missing
+/// from the store are downloaded and saved before symbolicating. See
+/// [`SymbolServer`]. With [`with_debuginfod`](Self::with_debuginfod), native
+/// debug info is looked up by build ID on debuginfod servers.
+///
+/// # In-App Rules
+///
+/// [`with_in_app_rules`](Self::with_in_app_rules) classifies each app's frames
+/// as application or library code by [`InAppRules`], on every platform.
+///
+/// # Metrics
+///
+/// Every call to [`symbolicate`](Self::symbolicate) is timed and counted per
+/// platform; [`metrics()`](Self::metrics) returns the totals along with the
+/// mapping cache's hit rates.
 ///
 /// # Example
 ///
@@ -430,7 +705,7 @@ impl SymbolicatedStack {
 /// use bugstr::symbolication::{Symbolicator, MappingStore, Platform, SymbolicationContext};
 ///
 /// // Create and scan mapping store
-/// let mut store = MappingStore::new("/path/to/mappings");
+/// let store = MappingStore::new("/path/to/mappings");
 /// store.scan()?;
 ///
 /// let symbolicator = Symbolicator::new(store);
@@ -440,6 +715,7 @@ impl SymbolicatedStack {
 ///     app_id: Some("com.example.app".to_string()),
 ///     version: Some("1.0.0".to_string()),
 ///     build_id: None,
+///     process_type: None,
 /// };
 ///
 /// let stack = "java.lang.NullPointerException\n\tat a.b.c(Unknown:1)";
@@ -450,6 +726,13 @@ impl SymbolicatedStack {
 /// ```
 pub struct Symbolicator {
     store: MappingStore,
+    cache: MappingCache,
+    symbol_server: Option<SymbolServer>,
+    debuginfod: Option<Debuginfod>,
+    in_app_rules: HashMap<String, InAppRules>,
+    registered: HashMap<Platform, Box<dyn PlatformSymbolicator>>,
+    metrics: Mutex<BTreeMap<String, PlatformMetrics>>,
+    result_cache: Option<ResultCache>,
 }
 
 impl Symbolicator {
@@ -464,12 +747,94 @@ impl Symbolicator {
     /// # Example
     ///
     /// ```rust,ignore
-    /// let mut store = MappingStore::new("/path/to/mappings");
+    /// let store = MappingStore::new("/path/to/mappings");
     /// store.scan()?;
     /// let symbolicator = Symbolicator::new(store);
     /// ```
     pub fn new(store: MappingStore) -> Self {
-        Self { store }
+        Self::with_cache_capacity(store, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Create a symbolicator that keeps up to `capacity` parsed mappings in
+    /// memory.
+    pub fn with_cache_capacity(store: MappingStore, capacity: usize) -> Self {
+        Self {
+            store,
+            cache: MappingCache::new(capacity),
+            symbol_server: None,
+            debuginfod: None,
+            in_app_rules: HashMap::new(),
+            registered: HashMap::new(),
+            metrics: Mutex::new(BTreeMap::new()),
+            result_cache: None,
+        }
+    }
+
+    /// Download mappings missing from the store from `server`.
+    pub fn with_symbol_server(mut self, server: SymbolServer) -> Self {
+        self.symbol_server = Some(server);
+        self
+    }
+
+    /// Look up native debug info by build ID on debuginfod servers when
+    /// it isn't in the store (Rust and Android NDK frames).
+    pub fn with_debuginfod(mut self, debuginfod: Debuginfod) -> Self {
+        self.debuginfod = Some(debuginfod);
+        self
+    }
+
+    /// Classify frames as application or library code by per-app rules,
+    /// keyed by app ID.
+    pub fn with_in_app_rules(mut self, rules: HashMap<String, InAppRules>) -> Self {
+        self.in_app_rules = rules;
+        self
+    }
+
+    /// Keep symbolication results in `cache`, keyed by stack and mapping.
+    ///
+    /// Only stacks resolved with a stored mapping are cached, and results
+    /// of [registered](Self::register) symbolicators never are. Cache
+    /// failures are ignored: the stack is symbolicated as if uncached.
+    pub fn with_result_cache(mut self, cache: ResultCache) -> Self {
+        self.result_cache = Some(cache);
+        self
+    }
+
+    /// Symbolicate `platform` with `symbolicator` instead of the built-in
+    /// implementation, or handle a [`Platform::Unknown`] platform by name.
+    pub fn register(
+        mut self,
+        platform: Platform,
+        symbolicator: impl PlatformSymbolicator + 'static,
+    ) -> Self {
+        self.registered.insert(platform, Box::new(symbolicator));
+        self
+    }
+
+    /// The mapping store.
+    pub fn store(&self) -> &MappingStore {
+        &self.store
+    }
+
+    /// The cache of parsed mapping files.
+    pub fn cache(&self) -> &MappingCache {
+        &self.cache
+    }
+
+    /// The persistent cache of symbolication results, if configured.
+    pub fn result_cache(&self) -> Option<&ResultCache> {
+        self.result_cache.as_ref()
+    }
+
+    /// Timing, error and hit-rate metrics of all symbolication so far.
+    pub fn metrics(&self) -> SymbolicationMetrics {
+        let mut platforms = self.metrics.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        for (platform, stats) in self.cache.stats() {
+            let metrics = platforms.entry(platform.as_str().to_string()).or_default();
+            metrics.cache_hits = stats.hits;
+            metrics.cache_misses = stats.misses;
+        }
+        SymbolicationMetrics { platforms }
     }
 
     /// Symbolicate a stack trace using platform-specific logic.
@@ -487,6 +852,9 @@ impl Symbolicator {
     ///   - Go: Goroutine stack with `goroutine N [status]:` header
     ///   - Python: Traceback with `File "...", line N` frames
     ///   - React Native: Mixed Hermes/JavaScript stack traces
+    ///   - iOS: Apple crash report with `Binary Images` section
+    ///   - Windows: `module+0xoffset` native frames
+    ///   - Unity: IL2CPP native frames naming generated C++ methods
     ///
     /// * `context` - [`SymbolicationContext`] providing platform, app ID, and version
     ///   for locating the correct mapping file.
@@ -504,8 +872,10 @@ impl Symbolicator {
     ///   - [`SymbolicationError::IoError`] - Failed to read mapping file from disk
     ///   - [`SymbolicationError::UnsupportedPlatform`] - Platform is `Unknown(...)`,
     ///     returned for unrecognized platform strings
-    ///   - [`SymbolicationError::ToolError`] - External tool (e.g., `flutter symbolize`)
-    ///     failed or is not available
+    ///   - [`SymbolicationError::ToolError`] - An external tool failed or is
+    ///     not available
+    ///   - [`SymbolicationError::RemoteError`] - The mapping is missing and the
+    ///     symbol server could not be reached
     ///
     /// # Example
     ///
@@ -524,36 +894,151 @@ impl Symbolicator {
         stack_trace: &str,
         context: &SymbolicationContext,
     ) -> Result<SymbolicatedStack, SymbolicationError> {
-        match &context.platform {
+        let start = Instant::now();
+        let remote_error = self.fetch_missing(context).err();
+
+        let result = self.symbolicate_cached(stack_trace, context);
+        let result = match (result, remote_error) {
+            // The server failure explains the missing mapping better
+            (Err(SymbolicationError::MappingNotFound { .. }), Some(e)) => Err(e),
+            (Ok(mut stack), _) => {
+                if self.uses_version_fallback(context) {
+                    for frame in &mut stack.frames {
+                        if frame.confidence == Some(Confidence::Exact) {
+                            frame.confidence = Some(Confidence::VersionFallback);
+                        }
+                    }
+                }
+                let rules = context.app_id.as_ref().and_then(|id| self.in_app_rules.get(id));
+                if let Some(rules) = rules {
+                    rules.apply(&mut stack.frames);
+                    for exception in &mut stack.exceptions {
+                        rules.apply(&mut exception.frames);
+                    }
+                }
+                Ok(stack)
+            }
+            (result, _) => result,
+        };
+
+        // Unsupported platform names come from clients; don't label by them
+        let label = match &context.platform {
+            Platform::Unknown(_) if !self.registered.contains_key(&context.platform) => "unknown",
+            platform => platform.as_str(),
+        };
+        self.metrics
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(label.to_string())
+            .or_default()
+            .observe(start.elapsed(), &result);
+        result
+    }
+
+    /// Whether symbolicating `context` uses another version's mapping.
+    fn uses_version_fallback(&self, context: &SymbolicationContext) -> bool {
+        let app_id = context.app_id.as_deref().unwrap_or("unknown");
+        let version = context.version.as_deref().unwrap_or("unknown");
+        self.store
+            .get_with_fallback(&context.platform, app_id, version)
+            .is_some_and(|info| info.version != version)
+    }
+
+    /// Download the exact mapping for `context` if the store lacks it.
+    fn fetch_missing(&self, context: &SymbolicationContext) -> Result<(), SymbolicationError> {
+        let (Some(server), Some(app_id), Some(version)) =
+            (&self.symbol_server, &context.app_id, &context.version)
+        else {
+            return Ok(());
+        };
+
+        if self.store.get(&context.platform, app_id, version).is_none() {
+            server.fetch(&self.store, &context.platform, app_id, version)?;
+        }
+        Ok(())
+    }
+
+    /// [`symbolicate_local`](Self::symbolicate_local) through the result
+    /// cache, if any.
+    fn symbolicate_cached(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        let cache = self
+            .result_cache
+            .as_ref()
+            .filter(|_| !self.registered.contains_key(&context.platform));
+        let key = cache.and_then(|_| {
+            let app_id = context.app_id.as_deref().unwrap_or("unknown");
+            let version = context.version.as_deref().unwrap_or("unknown");
+            let mapping = self
+                .store
+                .get_with_fallback(&context.platform, app_id, version)?;
+            ResultKey::new(stack_trace, context, &mapping)
+        });
+        let (Some(cache), Some(key)) = (cache, key) else {
+            return self.symbolicate_local(stack_trace, context);
+        };
+
+        if let Ok(Some(stack)) = cache.get(&key) {
+            return Ok(stack);
+        }
+        let result = self.symbolicate_local(stack_trace, context);
+        if let Ok(stack) = &result {
+            let _ = cache.put(&key, stack);
+        }
+        result
+    }
+
+    fn symbolicate_local(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        if let Some(registered) = self.registered.get(&context.platform) {
+            return registered.symbolicate(stack_trace, context);
+        }
+
+        let sym: Box<dyn PlatformSymbolicator + '_> = match &context.platform {
             Platform::Android => {
-                let sym = AndroidSymbolicator::new(&self.store);
-                sym.symbolicate(stack_trace, context)
+                let mut sym = AndroidSymbolicator::new(&self.store).with_cache(&self.cache);
+                if let Some(debuginfod) = &self.debuginfod {
+                    sym = sym.with_debuginfod(debuginfod);
+                }
+                Box::new(sym)
             }
             Platform::Electron => {
-                let sym = JavaScriptSymbolicator::new(&self.store);
-                sym.symbolicate(stack_trace, context)
+                Box::new(JavaScriptSymbolicator::new(&self.store).with_cache(&self.cache))
             }
             Platform::Flutter => {
-                let sym = FlutterSymbolicator::new(&self.store);
-                sym.symbolicate(stack_trace, context)
+                Box::new(FlutterSymbolicator::new(&self.store).with_cache(&self.cache))
             }
             Platform::Rust => {
-                let sym = RustSymbolicator::new(&self.store);
-                sym.symbolicate(stack_trace, context)
-            }
-            Platform::Go => {
-                let sym = GoSymbolicator::new(&self.store);
-                sym.symbolicate(stack_trace, context)
+                let mut sym = RustSymbolicator::new(&self.store).with_cache(&self.cache);
+                if let Some(debuginfod) = &self.debuginfod {
+                    sym = sym.with_debuginfod(debuginfod);
+                }
+                Box::new(sym)
             }
+            Platform::Go => Box::new(GoSymbolicator::new(&self.store).with_cache(&self.cache)),
             Platform::Python => {
-                let sym = PythonSymbolicator::new(&self.store);
-                sym.symbolicate(stack_trace, context)
+                Box::new(PythonSymbolicator::new(&self.store).with_cache(&self.cache))
             }
             Platform::ReactNative => {
-                let sym = ReactNativeSymbolicator::new(&self.store);
-                sym.symbolicate(stack_trace, context)
+                Box::new(ReactNativeSymbolicator::new(&self.store).with_cache(&self.cache))
             }
-            Platform::Unknown(p) => Err(SymbolicationError::UnsupportedPlatform(p.clone())),
-        }
+            Platform::Ios => Box::new(IosSymbolicator::new(&self.store).with_cache(&self.cache)),
+            Platform::Windows => {
+                Box::new(WindowsSymbolicator::new(&self.store).with_cache(&self.cache))
+            }
+            Platform::Unity => {
+                Box::new(UnitySymbolicator::new(&self.store).with_cache(&self.cache))
+            }
+            Platform::Unknown(p) => {
+                return Err(SymbolicationError::UnsupportedPlatform(p.clone()))
+            }
+        };
+        sym.symbolicate(stack_trace, context)
     }
 }
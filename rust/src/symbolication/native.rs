@@ -0,0 +1,316 @@
+//! Native debug info lookups.
+//!
+//! Resolves instruction addresses against DWARF debug info in Mach-O (dSYM),
+//! ELF and PE files, falling back to the symbol table when a binary has no
+//! line info. Shared by the platforms whose crashes contain raw native
+//! addresses (iOS, Android NDK, Rust, Flutter).
+//!
+//! Addresses passed to [`DebugFile::lookup`] are *file* addresses (the
+//! addresses in the binary on disk). Crash reports give runtime addresses
+//! or offsets from the image's load address; convert with
+//! [`DebugFile::address_for_offset`].
+
+use std::borrow::Cow;
+use std::fs;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use object::read::macho::{FatArch, MachOFatFile32, MachOFatFile64};
+use object::{Architecture, BinaryFormat, FileKind, Object, ObjectSection, ObjectSegment, ObjectSymbol};
+
+use super::demangle::demangle;
+use super::SymbolicationError;
+
+type Reader = gimli::EndianArcSlice<gimli::RunTimeEndian>;
+
+/// A source location resolved from debug info.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeLocation {
+    /// Demangled function name.
+    pub function: Option<String>,
+    /// Source file path.
+    pub file: Option<String>,
+    /// 1-based line number.
+    pub line: Option<u32>,
+    /// 1-based column number.
+    pub column: Option<u32>,
+}
+
+/// A parsed binary or debug companion file (dSYM DWARF, unstripped `.so`, ...).
+pub struct DebugFile {
+    context: Option<addr2line::Context<Reader>>,
+    /// Text symbols sorted by address.
+    symbols: Vec<(u64, String)>,
+    image_base: u64,
+}
+
+impl DebugFile {
+    /// Opens a debug file. For universal (fat) Mach-O files, `arch` selects
+    /// the slice (e.g. `"arm64"`, `"x86_64"`); defaults to arm64.
+    pub fn open(path: &Path, arch: Option<&str>) -> Result<Self, SymbolicationError> {
+        let data = fs::read(path)?;
+        Self::parse(&data, arch)
+    }
+
+    /// Parses a debug file from memory. See [`open`](Self::open).
+    pub fn parse(data: &[u8], arch: Option<&str>) -> Result<Self, SymbolicationError> {
+        let data = select_slice(data, arch)?;
+        let file = object::File::parse(data)
+            .map_err(|e| SymbolicationError::ParseError(format!("Invalid object file: {}", e)))?;
+
+        let endian = if file.is_little_endian() {
+            gimli::RunTimeEndian::Little
+        } else {
+            gimli::RunTimeEndian::Big
+        };
+        let load_section = |id: gimli::SectionId| -> Result<Reader, gimli::Error> {
+            let data = file
+                .section_by_name(id.name())
+                .and_then(|section| section.uncompressed_data().ok())
+                .unwrap_or(Cow::Borrowed(&[]));
+            Ok(gimli::EndianArcSlice::new(Arc::from(&*data), endian))
+        };
+
+        let has_dwarf = file.section_by_name(".debug_info").is_some()
+            || file.section_by_name("__debug_info").is_some();
+        let context = if has_dwarf {
+            let dwarf = gimli::Dwarf::load(load_section)
+                .map_err(|e| SymbolicationError::ParseError(format!("Invalid DWARF: {}", e)))?;
+            Some(
+                addr2line::Context::from_dwarf(dwarf)
+                    .map_err(|e| SymbolicationError::ParseError(format!("Invalid DWARF: {}", e)))?,
+            )
+        } else {
+            None
+        };
+
+        let mut symbols: Vec<(u64, String)> = file
+            .symbols()
+            .chain(file.dynamic_symbols())
+            .filter(|s| s.kind() == object::SymbolKind::Text && s.address() != 0)
+            .filter_map(|s| Some((s.address(), s.name().ok()?.to_string())))
+            .filter(|(_, name)| !name.is_empty())
+            .collect();
+        symbols.sort_by_key(|(addr, _)| *addr);
+        symbols.dedup_by_key(|(addr, _)| *addr);
+
+        Ok(Self {
+            context,
+            symbols,
+            image_base: image_base(&file),
+        })
+    }
+
+    /// Address the image expects to be loaded at (`__TEXT` vmaddr for
+    /// Mach-O, lowest segment address for ELF, image base for PE).
+    pub fn image_base(&self) -> u64 {
+        self.image_base
+    }
+
+    /// Converts an offset from the image's runtime load address into a file
+    /// address. This removes the ASLR slide: `runtime - load + image_base`.
+    pub fn address_for_offset(&self, offset: u64) -> u64 {
+        self.image_base.wrapping_add(offset)
+    }
+
+    /// Whether the file contains DWARF line info (not just symbols).
+    pub fn has_debug_info(&self) -> bool {
+        self.context.is_some()
+    }
+
+    /// Resolves a file address.
+    ///
+    /// Returns frames innermost first: inlined functions precede the
+    /// function they were inlined into. Empty when nothing matches.
+    pub fn lookup(&self, address: u64) -> Vec<NativeLocation> {
+        let mut locations = Vec::new();
+
+        if let Some(context) = &self.context {
+            if let Ok(mut frames) = context.find_frames(address).skip_all_loads() {
+                while let Ok(Some(frame)) = frames.next() {
+                    let function = frame
+                        .function
+                        .as_ref()
+                        .and_then(|f| f.raw_name().ok())
+                        .map(|name| demangle(&name));
+                    let (file, line, column) = match &frame.location {
+                        Some(loc) => (loc.file.map(String::from), loc.line, loc.column),
+                        None => (None, None, None),
+                    };
+                    locations.push(NativeLocation {
+                        function,
+                        file,
+                        line,
+                        column,
+                    });
+                }
+            }
+        }
+
+        // Symbol table fallback (stripped line info, or frames without a name)
+        let needs_symbol = locations.last().is_none_or(|loc| loc.function.is_none());
+        if needs_symbol {
+            if let Some(name) = self.symbol_for(address) {
+                match locations.last_mut() {
+                    Some(loc) => loc.function = Some(demangle(name)),
+                    None => locations.push(NativeLocation {
+                        function: Some(demangle(name)),
+                        file: None,
+                        line: None,
+                        column: None,
+                    }),
+                }
+            }
+        }
+
+        locations
+    }
+
+    /// Nearest text symbol at or below `address`.
+    fn symbol_for(&self, address: u64) -> Option<&str> {
+        let index = self.symbols.partition_point(|(addr, _)| *addr <= address);
+        index.checked_sub(1).map(|i| self.symbols[i].1.as_str())
+    }
+
+    /// Address of the text symbol named exactly `name`.
+    pub fn symbol_address(&self, name: &str) -> Option<u64> {
+        self.symbols
+            .iter()
+            .find(|(_, symbol)| symbol == name)
+            .map(|(addr, _)| *addr)
+    }
+
+    /// Address of the first text symbol whose name contains `name`.
+    #[cfg(test)]
+    pub(crate) fn find_symbol(&self, name: &str) -> Option<u64> {
+        self.symbols
+            .iter()
+            .find(|(_, symbol)| symbol.contains(name))
+            .map(|(addr, _)| *addr)
+    }
+}
+
+/// A debug file on disk whose architecture slices are parsed on first use.
+///
+/// This is the form cached in [`MappingCache`](super::MappingCache): the
+/// DWARF context parses units lazily, so lookups take a lock.
+pub(crate) struct DebugFileSlices {
+    path: PathBuf,
+    slices: Mutex<HashMap<Option<String>, Option<DebugFile>>>,
+}
+
+impl DebugFileSlices {
+    /// Create an entry for `path` without reading it yet.
+    pub(crate) fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            slices: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `f` against the slice for `arch`, parsing it if needed.
+    ///
+    /// Returns `None` when the file can't be parsed.
+    pub(crate) fn with_slice<R>(&self, arch: Option<&str>, f: impl FnOnce(&DebugFile) -> R) -> Option<R> {
+        let mut slices = self.slices.lock().unwrap_or_else(|e| e.into_inner());
+        slices
+            .entry(arch.map(String::from))
+            .or_insert_with(|| DebugFile::open(&self.path, arch).ok())
+            .as_ref()
+            .map(f)
+    }
+}
+
+/// Picks the slice of a universal Mach-O matching `arch` (arm64 by default).
+fn select_slice<'a>(data: &'a [u8], arch: Option<&str>) -> Result<&'a [u8], SymbolicationError> {
+    let wanted = arch.map(parse_arch).unwrap_or(Architecture::Aarch64);
+    let pick = |arches: Vec<(Architecture, Result<&'a [u8], object::Error>)>| {
+        let index = arches.iter().position(|(a, _)| *a == wanted).unwrap_or(0);
+        let (_, data) = arches
+            .into_iter()
+            .nth(index)
+            .ok_or_else(|| SymbolicationError::ParseError("Empty universal binary".into()))?;
+        data.map_err(|e| SymbolicationError::ParseError(format!("Invalid universal binary: {}", e)))
+    };
+
+    match FileKind::parse(data) {
+        Ok(FileKind::MachOFat32) => {
+            let fat = MachOFatFile32::parse(data)
+                .map_err(|e| SymbolicationError::ParseError(e.to_string()))?;
+            pick(fat.arches().iter().map(|a| (a.architecture(), a.data(data))).collect())
+        }
+        Ok(FileKind::MachOFat64) => {
+            let fat = MachOFatFile64::parse(data)
+                .map_err(|e| SymbolicationError::ParseError(e.to_string()))?;
+            pick(fat.arches().iter().map(|a| (a.architecture(), a.data(data))).collect())
+        }
+        _ => Ok(data),
+    }
+}
+
+/// Maps Apple/ELF architecture names to [`Architecture`].
+fn parse_arch(arch: &str) -> Architecture {
+    match arch {
+        "arm64" | "arm64e" | "aarch64" | "arm64-v8a" => Architecture::Aarch64,
+        "x86_64" | "amd64" => Architecture::X86_64,
+        "armv7" | "armv7s" | "armv7k" | "arm" | "armeabi-v7a" => Architecture::Arm,
+        "i386" | "x86" => Architecture::I386,
+        _ => Architecture::Unknown,
+    }
+}
+
+fn image_base(file: &object::File) -> u64 {
+    match file.format() {
+        BinaryFormat::MachO => file
+            .segments()
+            .find(|s| s.name().ok().flatten() == Some("__TEXT"))
+            .map(|s| s.address())
+            .unwrap_or(0),
+        BinaryFormat::Pe => file.relative_address_base(),
+        _ => file.segments().map(|s| s.address()).min().unwrap_or(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[inline(never)]
+    fn native_lookup_marker() -> u32 {
+        std::hint::black_box(42)
+    }
+
+    #[test]
+    fn test_lookup_own_debug_info() {
+        // The test binary itself is built with DWARF debug info
+        assert_eq!(native_lookup_marker(), 42);
+        let exe = std::env::current_exe().unwrap();
+        let debug = DebugFile::open(&exe, None).unwrap();
+
+        let address = debug.find_symbol("native_lookup_marker").expect("marker symbol");
+        let locations = debug.lookup(address);
+
+        let outer = locations.last().expect("location");
+        assert!(outer.function.as_deref().unwrap().contains("native_lookup_marker"));
+        if debug.has_debug_info() {
+            assert!(outer.file.as_deref().unwrap().ends_with("native.rs"));
+            assert!(outer.line.is_some());
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert!(matches!(
+            DebugFile::parse(b"not an object file", None),
+            Err(SymbolicationError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_arch() {
+        assert_eq!(parse_arch("arm64e"), Architecture::Aarch64);
+        assert_eq!(parse_arch("x86_64"), Architecture::X86_64);
+        assert_eq!(parse_arch("sparc"), Architecture::Unknown);
+    }
+}
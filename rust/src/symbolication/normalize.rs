@@ -0,0 +1,400 @@
+//! Stack normalization for fingerprinting.
+//!
+//! Two crashes from the same bug rarely have identical stack text: line
+//! numbers move between releases, addresses change with ASLR, compilers
+//! number lambdas and closures, and async runtimes add frames of their own.
+//! [`normalize_stack`] reduces a stack trace to what stays stable: each
+//! frame's function and module (file or library name, without directories,
+//! lines or columns), with runtime machinery frames dropped.
+//!
+//! Both raw traces and symbolicated ones ([`SymbolicatedStack::display`]
+//! output) are accepted, so a stack normalizes the same way before and after
+//! symbolication as long as the names match.
+//!
+//! [`SymbolicatedStack::display`]: super::SymbolicatedStack::display
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::{demangle, Platform};
+
+/// A stack frame reduced to the parts that are stable across builds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct NormalizedFrame {
+    /// Function name, without arguments, offsets, hashes or lambda indices.
+    pub function: String,
+    /// File or library the function is in, without directories, or `None`
+    /// when the trace doesn't say.
+    pub module: Option<String>,
+}
+
+/// Normalize a stack trace from `platform` for fingerprinting.
+///
+/// Lines that aren't frames (exception headers, `Caused by:`, thread
+/// names) are skipped, as are frames of runtime machinery: coroutine and
+/// reflection frames on the JVM, Node.js internals, `dart:async`, Rust panic
+/// handling and async executors, the Go scheduler and Python's `asyncio`.
+///
+/// # Example
+///
+/// ```
+/// use bugstr::symbolication::{normalize_stack, Platform};
+///
+/// let a = normalize_stack(&Platform::Android, "at com.example.Main$1.run(Main.java:12)");
+/// let b = normalize_stack(&Platform::Android, "at com.example.Main$2.run(Main.java:40)");
+/// assert_eq!(a, b);
+/// assert_eq!(a[0].function, "com.example.Main$.run");
+/// ```
+pub fn normalize_stack(platform: &Platform, stack: &str) -> Vec<NormalizedFrame> {
+    let parser = FrameParser::new();
+    let mut frames: Vec<NormalizedFrame> = Vec::new();
+    // Whether the previous frame was kept, for location lines that follow it
+    let mut last_kept = false;
+
+    for line in stack.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        // Rust and Go print the location on the line after the function
+        if matches!(platform, Platform::Rust | Platform::Go) {
+            if let Some(file) = parser.location(line) {
+                if last_kept {
+                    if let Some(frame) = frames.last_mut() {
+                        frame.module = Some(module_name(file));
+                    }
+                }
+                continue;
+            }
+        }
+
+        let Some((function, file)) = parser.frame(platform, line) else {
+            continue;
+        };
+        last_kept = !is_machinery(&function, file.as_deref());
+        if last_kept {
+            frames.push(NormalizedFrame {
+                function: parser.clean_function(&function),
+                module: file.as_deref().map(module_name),
+            });
+        }
+    }
+
+    frames
+}
+
+/// Frame patterns of all supported platforms.
+struct FrameParser {
+    java: Regex,
+    js: Regex,
+    firefox: Regex,
+    dart: Regex,
+    python: Regex,
+    android_native: Regex,
+    ios: Regex,
+    native: Regex,
+    rust: Regex,
+    go: Regex,
+    location: Regex,
+    symbolicated: Regex,
+    anonymous_index: Regex,
+    offset: Regex,
+}
+
+impl FrameParser {
+    fn new() -> Self {
+        Self {
+            // at com.example.Main.run(Main.java:12)
+            java: Regex::new(r"^at\s+([\w$.<>-]+)\(([^():]*)(?::\d+)?\)$").unwrap(),
+            // at async fn (file.js:1:2), at file.js:1:2
+            js: Regex::new(r"^at\s+(?:async\s+)?(?:(.+?)\s+\()?(.+?):\d+:\d+\)?$").unwrap(),
+            // fn@file.js:1:2
+            firefox: Regex::new(r"^(.*?)@(.+?):\d+:\d+$").unwrap(),
+            // #0      Foo.bar (package:app/foo.dart:1:2)
+            dart: Regex::new(r"^#\d+\s+(.+?)\s+\(([^()]+?)(?::\d+){0,2}\)$").unwrap(),
+            // File "app.py", line 3, in main
+            python: Regex::new(r#"^File "(.+?)", line \d+, in (.+)$"#).unwrap(),
+            // #00 pc 0001a2b4  /data/app/lib/arm64/libfoo.so (Java_a_b+20)
+            android_native: Regex::new(
+                r"^#\d+\s+pc\s+(?:0x)?[0-9a-fA-F]+\s+(\S+)(?:\s+\((.+?)(?:\+\d+)?\))?",
+            )
+            .unwrap(),
+            // 3   MyApp   0x0000000102a4b5c8 -[Foo bar] + 24
+            ios: Regex::new(r"^\d+\s+(\S+)\s+0x[0-9a-fA-F]+\s+(.+?)(?:\s+\+\s+\d+)?$").unwrap(),
+            // MyApp.exe!Foo::bar+0x1a, MyApp.exe+0x1a2b
+            native: Regex::new(r"^(\S+?\.(?:exe|dll|so|dylib))(?:!(\S+?))?(?:\+0x[0-9a-fA-F]+)?$")
+                .unwrap(),
+            // 3: 0x55d1c2a3b4c5 - mycrate::main::h0123456789abcdef
+            rust: Regex::new(r"^\d+:\s+(?:0x[0-9a-fA-F]+\s+-\s+)?(.+)$").unwrap(),
+            // main.(*T).run(0xc000010000, 0x1)
+            go: Regex::new(r"^([\w./*()$-]+?)\([^()]*\)$").unwrap(),
+            // at /src/main.rs:10:5 (Rust), /src/main.go:12 +0x1d (Go)
+            location: Regex::new(r"^(?:at\s+)?(\S+\.(?:rs|go)):\d+").unwrap(),
+            // com.example.Repo.load (Repo.kt:20), the symbolicated form
+            symbolicated: Regex::new(r"^(.+?)(?: \[resumed\])?(?: \(([^()\s]+?)(?::\d+)*\))?$")
+                .unwrap(),
+            // Main$1, lambda$onCreate$0, $$ExternalSyntheticLambda2, func1, {closure#0}
+            anonymous_index: Regex::new(
+                r"(\$\$ExternalSyntheticLambda|\$lambda[-$]?|lambda\$[\w]+\$|\.func|\{closure#|\$)\d+",
+            )
+            .unwrap(),
+            // + 24, +0x1a
+            offset: Regex::new(r"\s*\+\s*(?:0x[0-9a-fA-F]+|\d+)$").unwrap(),
+        }
+    }
+
+    /// File of a Rust or Go location line.
+    fn location<'l>(&self, line: &'l str) -> Option<&'l str> {
+        self.location
+            .captures(line)
+            .map(|caps| caps.get(1).unwrap().as_str())
+    }
+
+    /// Function and file of a frame line, or `None` if it isn't a frame.
+    fn frame(&self, platform: &Platform, line: &str) -> Option<(String, Option<String>)> {
+        let owned = |m: Option<regex::Match>| m.map(|m| m.as_str().to_string());
+
+        if let Some(caps) = self.java.captures(line) {
+            // "Unknown Source", "SourceFile" and "Native Method" name no file
+            let file = owned(caps.get(2)).filter(|f| f.contains('.'));
+            return Some((caps[1].to_string(), file));
+        }
+        if let Some(caps) = self
+            .js
+            .captures(line)
+            .or_else(|| self.firefox.captures(line))
+        {
+            let function = owned(caps.get(1))
+                .filter(|f| !f.is_empty())
+                .unwrap_or_else(|| "<anonymous>".to_string());
+            return Some((function, owned(caps.get(2))));
+        }
+        if let Some(caps) = self.dart.captures(line) {
+            return Some((caps[1].to_string(), owned(caps.get(2))));
+        }
+        if let Some(caps) = self.python.captures(line) {
+            return Some((caps[2].to_string(), owned(caps.get(1))));
+        }
+        if let Some(caps) = self.android_native.captures(line) {
+            let function = owned(caps.get(2)).unwrap_or_else(|| "<unknown>".to_string());
+            return Some((function, owned(caps.get(1))));
+        }
+        if let Some(caps) = self.ios.captures(line) {
+            return Some((caps[2].to_string(), owned(caps.get(1))));
+        }
+        if let Some(caps) = self.native.captures(line) {
+            let function = owned(caps.get(2)).unwrap_or_else(|| "<unknown>".to_string());
+            return Some((function, owned(caps.get(1))));
+        }
+        match platform {
+            Platform::Rust => {
+                if let Some(caps) = self.rust.captures(line) {
+                    return Some((caps[1].to_string(), None));
+                }
+            }
+            Platform::Go => {
+                if let Some(caps) = self.go.captures(line) {
+                    return Some((caps[1].to_string(), None));
+                }
+            }
+            _ => {}
+        }
+        // Frames as printed by SymbolicatedFrame::display
+        let caps = self.symbolicated.captures(line)?;
+        let function = &caps[1];
+        let is_name = !function.contains(char::is_whitespace) && !function.ends_with(':');
+        is_name.then(|| (function.to_string(), owned(caps.get(2))))
+    }
+
+    /// Strip what changes between builds from a function name.
+    fn clean_function(&self, function: &str) -> String {
+        let function = self.offset.replace(function.trim(), "");
+        if function.starts_with("0x") {
+            return "<unknown>".to_string();
+        }
+        let function = demangle(&function);
+        self.anonymous_index
+            .replace_all(&function, "$1")
+            .into_owned()
+    }
+}
+
+/// Whether a frame belongs to runtime machinery rather than to the code
+/// that crashed.
+fn is_machinery(function: &str, file: Option<&str>) -> bool {
+    const FUNCTION_PREFIXES: &[&str] = &[
+        // JVM coroutines and reflection
+        "kotlinx.coroutines.",
+        "kotlin.coroutines.",
+        "_COROUTINE.",
+        "java.lang.reflect.",
+        "sun.reflect.",
+        "jdk.internal.reflect.",
+        // Node.js promise jobs
+        "processTicksAndRejections",
+        // Rust panics and async executors
+        "std::panicking::",
+        "core::panicking::",
+        "std::rt::",
+        "std::sys::backtrace::",
+        "std::sys_common::backtrace::",
+        "rust_begin_unwind",
+        "__rust_",
+        "core::ops::function::",
+        "tokio::runtime::",
+        // Go scheduler and panics
+        "runtime.goexit",
+        "runtime.gopark",
+        "runtime.gopanic",
+        "runtime.sigpanic",
+        "runtime.main",
+    ];
+    const FILE_MARKERS: &[&str] = &[
+        "node:internal",
+        "internal/process/",
+        "dart:async",
+        "/asyncio/",
+        "\\asyncio\\",
+        "libdispatch.dylib",
+        "libsystem_pthread.dylib",
+    ];
+
+    FUNCTION_PREFIXES.iter().any(|p| function.starts_with(p))
+        || function.contains("core::future::future::Future>::poll")
+        || file.is_some_and(|file| FILE_MARKERS.iter().any(|m| file.contains(m)))
+}
+
+/// File or library name without directories, URL scheme or query string.
+/// Dart package URIs are kept whole, as they don't depend on the machine.
+fn module_name(file: &str) -> String {
+    if file.starts_with("package:") || file.starts_with("dart:") {
+        return file.to_string();
+    }
+    let file = file.split(['?', '#']).next().unwrap_or(file);
+    file.rsplit(['/', '\\']).next().unwrap_or(file).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(platform: Platform, stack: &str) -> Vec<(String, Option<String>)> {
+        normalize_stack(&platform, stack)
+            .into_iter()
+            .map(|f| (f.function, f.module))
+            .collect()
+    }
+
+    fn frame(function: &str, module: Option<&str>) -> (String, Option<String>) {
+        (function.to_string(), module.map(str::to_string))
+    }
+
+    #[test]
+    fn test_normalize_android() {
+        let stack = "\
+java.lang.IllegalStateException: boom
+\tat com.example.Repo.lambda$load$0(Repo.java:42)
+\tat com.example.Repo$$ExternalSyntheticLambda3.run(Unknown Source:2)
+\tat kotlinx.coroutines.DispatchedTask.run(DispatchedTask.kt:106)
+\tat java.lang.reflect.Method.invoke(Native Method)
+Caused by: java.io.IOException
+\tat com.example.Net$1.call(Net.kt:7)";
+
+        assert_eq!(
+            frames(Platform::Android, stack),
+            [
+                frame("com.example.Repo.lambda$load$", Some("Repo.java")),
+                frame("com.example.Repo$$ExternalSyntheticLambda.run", None),
+                frame("com.example.Net$.call", Some("Net.kt")),
+            ]
+        );
+        // The symbolicated display form normalizes the same way
+        assert_eq!(
+            frames(Platform::Android, "com.example.Net$2.call (Net.kt:90)"),
+            [frame("com.example.Net$.call", Some("Net.kt"))]
+        );
+    }
+
+    #[test]
+    fn test_normalize_javascript_and_dart() {
+        let stack = "\
+TypeError: x is undefined
+    at render (https://app.example/static/main.js?v=3:1:2345)
+    at async load (file:///app/resources/app.asar/renderer.js:10:5)
+    at processTicksAndRejections (node:internal/process/task_queues:95:5)
+handle@https://app.example/static/main.js:1:99";
+        assert_eq!(
+            frames(Platform::Electron, stack),
+            [
+                frame("render", Some("main.js")),
+                frame("load", Some("renderer.js")),
+                frame("handle", Some("main.js")),
+            ]
+        );
+
+        let stack = "\
+#0      Foo.bar (package:app/src/foo.dart:12:5)
+#1      _rootRunUnary (dart:async/zone.dart:1407:47)
+<asynchronous suspension>
+#2      main.<anonymous closure> (package:app/main.dart:3:1)";
+        assert_eq!(
+            frames(Platform::Flutter, stack),
+            [
+                frame("Foo.bar", Some("package:app/src/foo.dart")),
+                frame("main.<anonymous closure>", Some("package:app/main.dart")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_native_runtimes() {
+        let stack = "\
+thread 'main' panicked at src/main.rs:4:5:
+   0: std::panicking::begin_panic
+             at /rustc/library/std/src/panicking.rs:616:12
+   1:     0x55d1c2a3b4c5 - mycrate::run::{{closure}}::h0123456789abcdef
+             at /build/src/run.rs:10:5
+   2: mycrate::main";
+        assert_eq!(
+            frames(Platform::Rust, stack),
+            [
+                frame("mycrate::run::{{closure}}", Some("run.rs")),
+                frame("mycrate::main", None),
+            ]
+        );
+
+        let stack = "\
+goroutine 1 [running]:
+main.(*Server).handle.func1(0xc000010000, 0x1)
+\t/home/me/app/server.go:42 +0x1d
+runtime.goexit()
+\t/usr/local/go/src/runtime/asm_amd64.s:1571 +0x1";
+        assert_eq!(
+            frames(Platform::Go, stack),
+            [frame("main.(*Server).handle.func", Some("server.go"))]
+        );
+
+        let stack = "\
+Traceback (most recent call last):
+  File \"/usr/lib/python3.12/asyncio/events.py\", line 84, in _run
+  File \"/srv/app/handlers.py\", line 12, in handle
+ValueError: bad";
+        assert_eq!(
+            frames(Platform::Python, stack),
+            [frame("handle", Some("handlers.py"))]
+        );
+
+        let stack = "\
+0   libsystem_kernel.dylib        0x00000001a1b2c3d4 __pthread_kill + 8
+1   MyApp                         0x0000000102a4b5c8 -[Foo bar] + 24
+2   libdispatch.dylib             0x00000001a0000000 _dispatch_call_block_and_release + 32";
+        assert_eq!(
+            frames(Platform::Ios, stack),
+            [
+                frame("__pthread_kill", Some("libsystem_kernel.dylib")),
+                frame("-[Foo bar]", Some("MyApp")),
+            ]
+        );
+    }
+}
@@ -1,50 +1,136 @@
 //! Python symbolication.
 //!
-//! Python stack traces typically include source information.
-//! For bundled apps (PyInstaller, Nuitka), this module attempts to
-//! map back to original source files.
+//! Python stack traces typically include source information. Apps bundled
+//! with PyInstaller or Nuitka report paths inside the bundle or build
+//! machine (`/tmp/_MEI4f2a1c/myapp/cli.py`) instead of the repository, so
+//! a `mapping.json` (or `source-map.json`) maps bundled path prefixes to
+//! repository paths:
+//!
+//! ```json
+//! {
+//!   "paths": {
+//!     "/tmp/_MEI*/myapp/": "src/myapp/",
+//!     "C:/build/myapp/": "src/myapp/"
+//!   }
+//! }
+//! ```
+//!
+//! `*` matches within one path component, backslashes match `/`, and the
+//! longest matching prefix wins. Frames whose files are remapped are marked
+//! as application code; the rest (standard library, site-packages) as
+//! library code.
+
+use std::collections::BTreeMap;
+use std::path::Path;
 
 use regex::Regex;
+use serde::Deserialize;
 
+use super::cache::{load_mapping, MappingCache};
 use super::{
-    MappingStore, SymbolicatedFrame, SymbolicatedStack, SymbolicationContext, SymbolicationError,
+    Confidence, MappingStore, PlatformSymbolicator, SymbolicatedFrame, SymbolicatedStack,
+    SymbolicationContext, SymbolicationError, SCHEMA_VERSION,
 };
 
+/// Bundled path -> repository path prefixes from a Python mapping file.
+#[derive(Debug, Default)]
+pub(crate) struct PathMapping {
+    /// Compiled prefix patterns with their replacement, longest first.
+    prefixes: Vec<(Regex, String)>,
+}
+
+impl PathMapping {
+    fn load(path: &Path) -> Result<Self, SymbolicationError> {
+        Self::parse(&MappingStore::read_file(path)?)
+    }
+
+    pub(crate) fn parse(content: &[u8]) -> Result<Self, SymbolicationError> {
+        #[derive(Deserialize)]
+        struct MappingFile {
+            #[serde(default)]
+            paths: BTreeMap<String, String>,
+        }
+
+        let file: MappingFile = serde_json::from_slice(content).map_err(|e| {
+            SymbolicationError::ParseError(format!("Invalid Python path mapping: {}", e))
+        })?;
+
+        let mut prefixes: Vec<(String, String)> = file
+            .paths
+            .into_iter()
+            .map(|(from, to)| (from.replace('\\', "/"), to))
+            .collect();
+        prefixes.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+
+        let prefixes = prefixes
+            .into_iter()
+            .map(|(from, to)| {
+                let pattern = regex::escape(&from).replace(r"\*", r"[^/]*");
+                let re = Regex::new(&format!("^{}", pattern))
+                    .map_err(|e| SymbolicationError::ParseError(e.to_string()))?;
+                Ok((re, to))
+            })
+            .collect::<Result<_, SymbolicationError>>()?;
+        Ok(Self { prefixes })
+    }
+
+    /// Repository path for a bundled file, if one of the prefixes matches.
+    fn remap(&self, file: &str) -> Option<String> {
+        let file = file.replace('\\', "/");
+        self.prefixes.iter().find_map(|(re, to)| {
+            let matched = re.find(&file)?;
+            Some(format!("{}{}", to, &file[matched.end()..]))
+        })
+    }
+}
+
 /// Python stack trace symbolicator.
 pub struct PythonSymbolicator<'a> {
     store: &'a MappingStore,
+    cache: Option<&'a MappingCache>,
 }
 
 impl<'a> PythonSymbolicator<'a> {
     /// Create a new Python symbolicator.
     pub fn new(store: &'a MappingStore) -> Self {
-        Self { store }
+        Self { store, cache: None }
+    }
+
+    /// Reuse parsed path mappings from `cache` instead of re-reading them.
+    pub fn with_cache(mut self, cache: &'a MappingCache) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
     /// Symbolicate a Python stack trace.
     ///
     /// Python tracebacks already include source locations in most cases.
-    /// This method parses and formats them, and attempts to resolve
-    /// bundled app paths to original sources if a mapping is available.
+    /// This method parses and formats them, and resolves bundled app paths
+    /// to repository paths if a mapping is available.
     pub fn symbolicate(
         &self,
         stack_trace: &str,
         context: &SymbolicationContext,
     ) -> Result<SymbolicatedStack, SymbolicationError> {
-        // Try to find mapping file (for bundled apps)
-        let _mapping_info = self.store.get_with_fallback(
+        // Mapping file (for bundled apps)
+        let mapping_info = self.store.get_with_fallback(
             &context.platform,
             context.app_id.as_deref().unwrap_or("unknown"),
             context.version.as_deref().unwrap_or("unknown"),
         );
+        // Tracebacks are readable without the mapping, so an unusable one
+        // isn't an error (`bugstr mappings verify` reports it)
+        let mapping = mapping_info
+            .and_then(|info| load_mapping(self.cache, &info, &info.path, PathMapping::load).ok());
 
-        self.parse_python_traceback(stack_trace)
+        self.parse_python_traceback(stack_trace, mapping.as_deref())
     }
 
-    /// Parse a Python traceback.
+    /// Parse a Python traceback, remapping file paths with `mapping`.
     fn parse_python_traceback(
         &self,
         stack_trace: &str,
+        mapping: Option<&PathMapping>,
     ) -> Result<SymbolicatedStack, SymbolicationError> {
         // Python traceback format:
         // Traceback (most recent call last):
@@ -79,6 +165,8 @@ impl<'a> PythonSymbolicator<'a> {
                         line: current_line.take(),
                         column: None,
                         symbolicated: true,
+                        in_app: None,
+                        confidence: Some(Confidence::Exact),
                     });
                 }
 
@@ -108,6 +196,8 @@ impl<'a> PythonSymbolicator<'a> {
                         line: current_line.take(),
                         column: None,
                         symbolicated: true,
+                        in_app: None,
+                        confidence: Some(Confidence::Exact),
                     });
                     in_frame = false;
                 }
@@ -122,6 +212,8 @@ impl<'a> PythonSymbolicator<'a> {
                     line: None,
                     column: None,
                     symbolicated: true,
+                    in_app: None,
+                    confidence: Some(Confidence::Exact),
                 });
                 continue;
             }
@@ -136,6 +228,8 @@ impl<'a> PythonSymbolicator<'a> {
                         line: current_line.take(),
                         column: None,
                         symbolicated: true,
+                        in_app: None,
+                        confidence: Some(Confidence::Exact),
                     });
                     in_frame = false;
                     current_raw.clear();
@@ -153,13 +247,30 @@ impl<'a> PythonSymbolicator<'a> {
                 line: current_line,
                 column: None,
                 symbolicated: true,
+                in_app: None,
+                confidence: Some(Confidence::Exact),
             });
         }
 
+        if let Some(mapping) = mapping {
+            for frame in &mut frames {
+                let Some(file) = &frame.file else { continue };
+                match mapping.remap(file) {
+                    Some(remapped) => {
+                        frame.file = Some(remapped);
+                        frame.in_app = Some(true);
+                    }
+                    None => frame.in_app = Some(false),
+                }
+            }
+        }
+
         let symbolicated_count = frames.iter().filter(|f| f.symbolicated).count();
         let total_count = frames.len();
 
         Ok(SymbolicatedStack {
+            schema_version: SCHEMA_VERSION,
+            exceptions: Vec::new(),
             raw: stack_trace.to_string(),
             frames,
             symbolicated_count,
@@ -168,6 +279,16 @@ impl<'a> PythonSymbolicator<'a> {
     }
 }
 
+impl PlatformSymbolicator for PythonSymbolicator<'_> {
+    fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        PythonSymbolicator::symbolicate(self, stack_trace, context)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -183,8 +304,50 @@ ValueError: test error"#;
 
         let store = MappingStore::new("/tmp");
         let sym = PythonSymbolicator::new(&store);
-        let result = sym.parse_python_traceback(traceback).unwrap();
+        let result = sym.parse_python_traceback(traceback, None).unwrap();
 
         assert!(result.symbolicated_count >= 2);
     }
+
+    #[test]
+    fn test_remap_bundled_paths() {
+        let mapping = PathMapping::parse(
+            br#"{"paths": {
+                "/tmp/_MEI*/": "src/",
+                "/tmp/_MEI*/myapp/vendor/": "vendor/",
+                "C:\\build\\": "src/"
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            mapping.remap(r"C:\build\myapp\cli.py").as_deref(),
+            Some("src/myapp/cli.py")
+        );
+
+        let traceback = r#"Traceback (most recent call last):
+  File "/tmp/_MEI4f2a1c/myapp/cli.py", line 12, in main
+  File "/tmp/_MEI4f2a1c/myapp/vendor/six.py", line 3, in wrap
+  File "/usr/lib/python3.12/json/__init__.py", line 346, in loads
+ValueError: bad"#;
+
+        let store = MappingStore::new("/tmp");
+        let result = PythonSymbolicator::new(&store)
+            .parse_python_traceback(traceback, Some(&mapping))
+            .unwrap();
+
+        let files: Vec<(Option<&str>, Option<bool>)> = result
+            .frames
+            .iter()
+            .map(|f| (f.file.as_deref(), f.in_app))
+            .collect();
+        assert_eq!(
+            files[1..],
+            [
+                (Some("src/myapp/cli.py"), Some(true)),
+                (Some("vendor/six.py"), Some(true)),
+                (Some("/usr/lib/python3.12/json/__init__.py"), Some(false)),
+                (None, None),
+            ]
+        );
+    }
 }
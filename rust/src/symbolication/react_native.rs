@@ -2,25 +2,41 @@
 //!
 //! Handles both Hermes bytecode symbolication and JavaScript source maps
 //! for React Native applications.
+//!
+//! Hermes release builds report bytecode frames as
+//! `at fn (address at index.android.bundle:1:<offset>)`, where the column is
+//! a 0-based bytecode offset. These resolve against the composed source map
+//! that `react-native bundle` + `hermesc -output-source-map` produce (merged
+//! with `compose-source-maps.js`, as the React Native build does). Its
+//! `x_facebook_sources` function maps give the enclosing function's name,
+//! which Hermes frames often lack (`anonymous`).
 
-use std::fs;
 
 use regex::Regex;
-use sourcemap::SourceMap;
+use sourcemap::{DecodedMap, Token};
 
+use super::cache::{load_mapping, MappingCache};
 use super::{
-    MappingStore, SymbolicatedFrame, SymbolicatedStack, SymbolicationContext, SymbolicationError,
+    Confidence, MappingStore, PlatformSymbolicator, SymbolicatedFrame, SymbolicatedStack,
+    SymbolicationContext, SymbolicationError, SCHEMA_VERSION,
 };
 
 /// React Native stack trace symbolicator.
 pub struct ReactNativeSymbolicator<'a> {
     store: &'a MappingStore,
+    cache: Option<&'a MappingCache>,
 }
 
 impl<'a> ReactNativeSymbolicator<'a> {
     /// Create a new React Native symbolicator.
     pub fn new(store: &'a MappingStore) -> Self {
-        Self { store }
+        Self { store, cache: None }
+    }
+
+    /// Reuse parsed mappings from `cache` instead of re-reading them.
+    pub fn with_cache(mut self, cache: &'a MappingCache) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
     /// Symbolicate a React Native stack trace.
@@ -42,20 +58,28 @@ impl<'a> ReactNativeSymbolicator<'a> {
         );
 
         let sourcemap = if let Some(info) = mapping_info {
-            let content = fs::read_to_string(&info.path)?;
-            SourceMap::from_reader(content.as_bytes()).ok()
+            let loaded = load_mapping(self.cache, &info, &info.path, |path| {
+                DecodedMap::from_reader(MappingStore::open_file(path)?)
+                    .map_err(|e| SymbolicationError::ParseError(e.to_string()))
+            });
+            // An unparseable source map still leaves native frames to format
+            match loaded {
+                Ok(sourcemap) => Some(sourcemap),
+                Err(SymbolicationError::ParseError(_)) => None,
+                Err(e) => return Err(e),
+            }
         } else {
             None
         };
 
-        self.parse_react_native_stack(stack_trace, sourcemap.as_ref())
+        self.parse_react_native_stack(stack_trace, sourcemap.as_deref())
     }
 
     /// Parse a React Native stack trace.
     fn parse_react_native_stack(
         &self,
         stack_trace: &str,
-        sourcemap: Option<&SourceMap>,
+        sourcemap: Option<&DecodedMap>,
     ) -> Result<SymbolicatedStack, SymbolicationError> {
         // React Native stack frame formats:
         // JS: "    at myFunction (index.bundle:1:2345)"
@@ -65,7 +89,7 @@ impl<'a> ReactNativeSymbolicator<'a> {
 
         // Note: File paths can contain colons (URLs), so we match greedily
         let js_frame_re = Regex::new(
-            r"^\s*at\s+(?:(.+?)\s+)?\(?(?:address at\s+)?(.+):(\d+):(\d+)\)?"
+            r"^\s*at\s+(?:(.+?)\s+)?\(?(address at\s+)?(.+):(\d+):(\d+)\)?"
         ).unwrap();
         let native_android_re = Regex::new(
             r"^\s*at\s+([a-zA-Z0-9_.]+)\.([a-zA-Z0-9_<>]+)\(([^:]+):(\d+)\)"
@@ -86,27 +110,29 @@ impl<'a> ReactNativeSymbolicator<'a> {
             // Try JS/Hermes frame
             if let Some(caps) = js_frame_re.captures(line_trimmed) {
                 let function = caps.get(1).map(|m| m.as_str());
-                let file = caps.get(2).map(|m| m.as_str());
+                let bytecode = caps.get(2).is_some();
+                let file = caps.get(3).map(|m| m.as_str());
                 let line_num: u32 = caps
-                    .get(3)
+                    .get(4)
                     .and_then(|m| m.as_str().parse().ok())
                     .unwrap_or(0);
                 let col_num: u32 = caps
-                    .get(4)
+                    .get(5)
                     .and_then(|m| m.as_str().parse().ok())
                     .unwrap_or(0);
 
                 // Try to symbolicate with source map
                 if let Some(sm) = sourcemap {
-                    let line_0 = if line_num > 0 { line_num - 1 } else { 0 };
-                    let col_0 = if col_num > 0 { col_num - 1 } else { 0 };
+                    let line_0 = line_num.saturating_sub(1);
+                    // Bytecode offsets are already 0-based
+                    let col_0 = if bytecode { col_num } else { col_num.saturating_sub(1) };
 
                     if let Some(token) = sm.lookup_token(line_0, col_0) {
-                        let orig_function = token
-                            .get_name()
-                            .map(|s| s.to_string())
-                            .or_else(|| function.map(|s| s.to_string()))
-                            .unwrap_or_else(|| "<anonymous>".to_string());
+                        let orig_function = enclosing_function(sm, token)
+                            .or_else(|| token.get_name())
+                            .or(function.filter(|f| *f != "anonymous"))
+                            .unwrap_or("<anonymous>")
+                            .to_string();
                         let orig_file = token.get_source().map(|s| s.to_string());
                         let orig_line = token.get_src_line();
                         let orig_col = token.get_src_col();
@@ -131,6 +157,8 @@ impl<'a> ReactNativeSymbolicator<'a> {
                     line: Some(line_num),
                     column: Some(col_num),
                     symbolicated: false,
+                    in_app: None,
+                    confidence: None,
                 });
                 continue;
             }
@@ -149,6 +177,8 @@ impl<'a> ReactNativeSymbolicator<'a> {
                     line: line_num,
                     column: None,
                     symbolicated: true, // Native frames are usually not obfuscated
+                    in_app: None,
+                    confidence: Some(Confidence::Exact),
                 });
                 symbolicated_count += 1;
                 continue;
@@ -166,6 +196,8 @@ impl<'a> ReactNativeSymbolicator<'a> {
                     line: None,
                     column: None,
                     symbolicated: true,
+                    in_app: None,
+                    confidence: Some(Confidence::Exact),
                 });
                 symbolicated_count += 1;
                 continue;
@@ -176,6 +208,8 @@ impl<'a> ReactNativeSymbolicator<'a> {
         }
 
         Ok(SymbolicatedStack {
+            schema_version: SCHEMA_VERSION,
+            exceptions: Vec::new(),
             raw: stack_trace.to_string(),
             frames,
             symbolicated_count,
@@ -184,6 +218,24 @@ impl<'a> ReactNativeSymbolicator<'a> {
     }
 }
 
+impl PlatformSymbolicator for ReactNativeSymbolicator<'_> {
+    fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        ReactNativeSymbolicator::symbolicate(self, stack_trace, context)
+    }
+}
+
+/// Name of the function enclosing `token`, from Hermes/Metro function maps.
+fn enclosing_function<'a>(sourcemap: &'a DecodedMap, token: Token<'a>) -> Option<&'a str> {
+    match sourcemap {
+        DecodedMap::Hermes(hermes) => hermes.get_scope_for_token(token),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,30 +243,56 @@ mod tests {
     #[test]
     fn test_parse_js_frame() {
         let js_frame_re = Regex::new(
-            r"^\s*at\s+(?:(.+?)\s+)?\(?(?:address at\s+)?(.+):(\d+):(\d+)\)?"
+            r"^\s*at\s+(?:(.+?)\s+)?\(?(address at\s+)?(.+):(\d+):(\d+)\)?"
         ).unwrap();
 
         let frame = "    at myFunction (index.bundle:1:2345)";
         let caps = js_frame_re.captures(frame).unwrap();
 
         assert_eq!(caps.get(1).map(|m| m.as_str()), Some("myFunction"));
-        assert_eq!(caps.get(2).map(|m| m.as_str()), Some("index.bundle"));
-        assert_eq!(caps.get(3).map(|m| m.as_str()), Some("1"));
-        assert_eq!(caps.get(4).map(|m| m.as_str()), Some("2345"));
+        assert_eq!(caps.get(3).map(|m| m.as_str()), Some("index.bundle"));
+        assert_eq!(caps.get(4).map(|m| m.as_str()), Some("1"));
+        assert_eq!(caps.get(5).map(|m| m.as_str()), Some("2345"));
     }
 
     #[test]
     fn test_parse_js_frame_with_url() {
         let js_frame_re = Regex::new(
-            r"^\s*at\s+(?:(.+?)\s+)?\(?(?:address at\s+)?(.+):(\d+):(\d+)\)?"
+            r"^\s*at\s+(?:(.+?)\s+)?\(?(address at\s+)?(.+):(\d+):(\d+)\)?"
         ).unwrap();
 
         let frame = "    at myFunction (http://localhost:8081/index.bundle:1:2345)";
         let caps = js_frame_re.captures(frame).unwrap();
 
         assert_eq!(caps.get(1).map(|m| m.as_str()), Some("myFunction"));
-        assert_eq!(caps.get(2).map(|m| m.as_str()), Some("http://localhost:8081/index.bundle"));
-        assert_eq!(caps.get(3).map(|m| m.as_str()), Some("1"));
-        assert_eq!(caps.get(4).map(|m| m.as_str()), Some("2345"));
+        assert_eq!(caps.get(3).map(|m| m.as_str()), Some("http://localhost:8081/index.bundle"));
+        assert_eq!(caps.get(4).map(|m| m.as_str()), Some("1"));
+        assert_eq!(caps.get(5).map(|m| m.as_str()), Some("2345"));
+    }
+
+    #[test]
+    fn test_symbolicate_hermes_bytecode_frame() {
+        // Composed Hermes map: bytecode offset 10 maps to App.js:2:2, inside `crash`
+        let map = r#"{
+            "version": 3,
+            "sources": ["App.js"],
+            "names": [],
+            "mappings": "AAAA,UACC",
+            "x_facebook_sources": [[{"names": ["<global>", "crash"], "mappings": "AAA,ACC"}]]
+        }"#;
+        let sourcemap = DecodedMap::from_reader(map.as_bytes()).unwrap();
+        assert!(matches!(sourcemap, DecodedMap::Hermes(_)));
+
+        let store = MappingStore::new("/nonexistent");
+        let sym = ReactNativeSymbolicator::new(&store);
+        let trace = "\
+Error: boom
+    at anonymous (address at index.android.bundle:1:10)
+    at global (native)";
+        let result = sym.parse_react_native_stack(trace, Some(&sourcemap)).unwrap();
+
+        assert_eq!(result.symbolicated_count, 1);
+        assert_eq!(result.frames[1].display(), "crash (App.js:2)");
+        assert_eq!(result.frames[1].column, Some(2));
     }
 }
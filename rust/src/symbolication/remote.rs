@@ -0,0 +1,219 @@
+//! Remote symbol server.
+//!
+//! When a mapping is missing locally, [`SymbolServer`] downloads it from an
+//! HTTP server laid out like the local [`MappingStore`], then saves it with
+//! [`MappingStore::save_mapping`] so later crashes for the same release are
+//! symbolicated from disk. The server location is a URL template:
+//!
+//! ```text
+//! https://symbols.example.com/{platform}/{app}/{version}/{file}
+//! ```
+//!
+//! `{file}` is each of the platform's well-known mapping file names in turn
+//! (`mapping.txt`, `main.js.map`, ...); the first one the server returns is
+//! used. iOS and Windows debug files are named after the binary, so they are
+//! not fetched.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use super::{MappingStore, Platform, SymbolicationError};
+
+/// Default timeout for a single download.
+pub const DEFAULT_SYMBOL_SERVER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An HTTP source for mapping files that are missing locally.
+pub struct SymbolServer {
+    template: String,
+    timeout: Duration,
+    /// Releases the server didn't have, so they are only requested once.
+    misses: Mutex<HashSet<(Platform, String, String)>>,
+}
+
+impl SymbolServer {
+    /// Create a symbol server from a URL template with `{platform}`, `{app}`,
+    /// `{version}` and `{file}` placeholders.
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+            timeout: DEFAULT_SYMBOL_SERVER_TIMEOUT,
+            misses: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Set the timeout for each download.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The URL template.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Expand the URL template for one mapping file.
+    pub fn url_for(&self, platform: &Platform, app_id: &str, version: &str, file: &str) -> String {
+        self.template
+            .replace("{platform}", platform.as_str())
+            .replace("{app}", app_id)
+            .replace("{version}", version)
+            .replace("{file}", file)
+    }
+
+    /// Download the mapping for a release into `store`.
+    ///
+    /// Returns the saved path, or `None` if the server has no mapping for
+    /// the release. Misses are remembered for the lifetime of the server.
+    pub(crate) fn fetch(
+        &self,
+        store: &MappingStore,
+        platform: &Platform,
+        app_id: &str,
+        version: &str,
+    ) -> Result<Option<PathBuf>, SymbolicationError> {
+        MappingStore::validate_path_component(app_id, "app_id")?;
+        MappingStore::validate_path_component(version, "version")?;
+
+        let key = (platform.clone(), app_id.to_string(), version.to_string());
+        if self.misses.lock().unwrap_or_else(PoisonError::into_inner).contains(&key) {
+            return Ok(None);
+        }
+
+        for file in MappingStore::mapping_file_names(platform) {
+            let url = self.url_for(platform, app_id, version, file);
+            let Some(content) = http_get(&url, self.timeout)? else {
+                continue;
+            };
+            let path = store.save_mapping(platform.clone(), app_id, version, file, &content)?;
+            return Ok(Some(path));
+        }
+
+        self.misses.lock().unwrap_or_else(PoisonError::into_inner).insert(key);
+        Ok(None)
+    }
+}
+
+/// GET a URL with the blocking client. `None` on 404.
+pub(crate) fn http_get(url: &str, timeout: Duration) -> Result<Option<Vec<u8>>, SymbolicationError> {
+    let url = url.to_string();
+
+    // The blocking client owns a runtime and panics when used or dropped
+    // on an async thread, so the request always runs on its own thread
+    std::thread::spawn(move || -> Result<Option<Vec<u8>>, String> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let response = client.get(&url).send().map_err(|e| format!("{}: {}", url, e))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!("{}: HTTP {}", url, response.status()));
+        }
+        let body = response.bytes().map_err(|e| format!("{}: {}", url, e))?;
+        Ok(Some(body.to_vec()))
+    })
+    .join()
+    .unwrap_or_else(|_| Err("download thread panicked".to_string()))
+    .map_err(SymbolicationError::RemoteError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use tempfile::tempdir;
+
+    /// Serve `responses` (status, body) to consecutive connections,
+    /// returning the base URL and the request paths seen.
+    fn serve(responses: Vec<(u16, &'static str)>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut paths = Vec::new();
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 4096];
+                let n = stream.read(&mut request).unwrap();
+                let request = String::from_utf8_lossy(&request[..n]);
+                paths.push(request.split_whitespace().nth(1).unwrap_or("").to_string());
+                write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+            paths
+        });
+        (base, handle)
+    }
+
+    #[test]
+    fn test_url_for() {
+        let server = SymbolServer::new("https://s.example/{platform}/{app}/{version}/{file}");
+        assert_eq!(
+            server.url_for(&Platform::Android, "com.example", "1.0.0", "mapping.txt"),
+            "https://s.example/android/com.example/1.0.0/mapping.txt"
+        );
+    }
+
+    #[test]
+    fn test_fetch_saves_mapping() {
+        let dir = tempdir().unwrap();
+        let store = MappingStore::new(dir.path());
+        // mapping.txt is missing; proguard-mapping.txt is found
+        let (base, handle) = serve(vec![(404, ""), (200, "com.example.Foo -> a:\n")]);
+        let server = SymbolServer::new(format!("{}/{{platform}}/{{app}}/{{version}}/{{file}}", base));
+
+        let path = server
+            .fetch(&store, &Platform::Android, "com.example", "1.0.0")
+            .unwrap()
+            .expect("mapping downloaded");
+
+        assert_eq!(
+            handle.join().unwrap(),
+            [
+                "/android/com.example/1.0.0/mapping.txt",
+                "/android/com.example/1.0.0/proguard-mapping.txt"
+            ]
+        );
+        assert_eq!(MappingStore::read_file_to_string(&path).unwrap(), "com.example.Foo -> a:\n");
+        assert!(store.get(&Platform::Android, "com.example", "1.0.0").is_some());
+    }
+
+    #[test]
+    fn test_fetch_remembers_misses() {
+        let dir = tempdir().unwrap();
+        let store = MappingStore::new(dir.path());
+        let (base, handle) = serve(vec![(404, ""), (404, "")]);
+        let server = SymbolServer::new(format!("{}/{{platform}}/{{app}}/{{version}}/{{file}}", base));
+
+        assert!(server.fetch(&store, &Platform::Go, "app", "1.0").unwrap().is_none());
+        assert_eq!(handle.join().unwrap().len(), 2);
+        // Not requested again
+        assert!(server.fetch(&store, &Platform::Go, "app", "1.0").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_fetch_server_error() {
+        let dir = tempdir().unwrap();
+        let store = MappingStore::new(dir.path());
+        let (base, handle) = serve(vec![(500, "")]);
+        let server = SymbolServer::new(format!("{}/{{file}}", base));
+
+        assert!(matches!(
+            server.fetch(&store, &Platform::Python, "app", "1.0"),
+            Err(SymbolicationError::RemoteError(_))
+        ));
+        handle.join().unwrap();
+    }
+}
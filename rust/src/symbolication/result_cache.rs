@@ -0,0 +1,286 @@
+//! Persistent cache of symbolication results.
+//!
+//! The same stack trace arrives with hundreds of crashes. [`ResultCache`]
+//! stores each symbolicated stack in SQLite, keyed by a hash of the stack
+//! and its context and by the identity of the mapping that resolved it, so
+//! repeats skip mapping lookups and parsing entirely, across restarts.
+//!
+//! A mapping's identity covers the name, size and modification time of
+//! every file in its version directory (companion files such as dSYMs
+//! included). Re-uploading or pruning a mapping changes the identity, so
+//! stale results are never returned; they are deleted when the next result
+//! for that app version is stored.
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use sha2::{Digest, Sha256};
+
+use super::store::MappingInfo;
+use super::{SymbolicatedStack, SymbolicationContext, SCHEMA_VERSION};
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS symbolication_results (
+        stack_hash TEXT NOT NULL,
+        mapping_id TEXT NOT NULL,
+        platform TEXT NOT NULL,
+        app_id TEXT NOT NULL,
+        version TEXT NOT NULL,
+        result TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        PRIMARY KEY (stack_hash, mapping_id)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_symbolication_results_mapping
+        ON symbolication_results(platform, app_id, version);";
+
+/// SQLite-backed cache of symbolicated stacks.
+///
+/// Add it to a [`Symbolicator`](super::Symbolicator) with
+/// [`with_result_cache`](super::Symbolicator::with_result_cache). The table
+/// can live in its own file or in the crash database; it is created on open
+/// and safe to delete at any time.
+pub struct ResultCache {
+    conn: Mutex<Connection>,
+}
+
+/// Identifies one stack trace symbolicated with one mapping.
+pub(crate) struct ResultKey {
+    stack_hash: String,
+    mapping_id: String,
+    platform: String,
+    app_id: String,
+    version: String,
+}
+
+impl ResultCache {
+    /// Opens or creates a result cache in the SQLite database at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        Self::with_connection(conn)
+    }
+
+    /// Opens an in-memory result cache (useful for testing).
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(CREATE_TABLE)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Number of cached results.
+    pub fn len(&self) -> Result<usize> {
+        self.lock()
+            .query_row("SELECT COUNT(*) FROM symbolication_results", [], |row| {
+                row.get(0)
+            })
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Deletes all cached results.
+    pub fn clear(&self) -> Result<()> {
+        self.lock()
+            .execute("DELETE FROM symbolication_results", [])
+            .map(|_| ())
+    }
+
+    /// The cached result for `key`, if any. Rows that no longer parse (from
+    /// another schema version) count as misses.
+    pub(crate) fn get(&self, key: &ResultKey) -> Result<Option<SymbolicatedStack>> {
+        let json: Option<String> = self
+            .lock()
+            .query_row(
+                "SELECT result FROM symbolication_results
+                 WHERE stack_hash = ?1 AND mapping_id = ?2",
+                params![key.stack_hash, key.mapping_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    /// Stores `stack` under `key`, and deletes results for the same app
+    /// version made with a different mapping.
+    pub(crate) fn put(&self, key: &ResultKey, stack: &SymbolicatedStack) -> Result<()> {
+        let json = serde_json::to_string(stack)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        let mut conn = self.lock();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM symbolication_results
+             WHERE platform = ?1 AND app_id = ?2 AND version = ?3 AND mapping_id != ?4",
+            params![key.platform, key.app_id, key.version, key.mapping_id],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO symbolication_results
+                (stack_hash, mapping_id, platform, app_id, version, result, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                key.stack_hash,
+                key.mapping_id,
+                key.platform,
+                key.app_id,
+                key.version,
+                json,
+                created_at
+            ],
+        )?;
+        tx.commit()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl ResultKey {
+    /// Key of `stack_trace` symbolicated with `mapping`. Returns `None` when
+    /// the mapping's files can't be read.
+    pub(crate) fn new(
+        stack_trace: &str,
+        context: &SymbolicationContext,
+        mapping: &MappingInfo,
+    ) -> Option<Self> {
+        let app_id = context.app_id.as_deref().unwrap_or("unknown");
+        let version = context.version.as_deref().unwrap_or("unknown");
+
+        // A new bugstr release may symbolicate the same stack differently
+        let mut hasher = Sha256::new();
+        for part in [
+            env!("CARGO_PKG_VERSION"),
+            &SCHEMA_VERSION.to_string(),
+            context.platform.as_str(),
+            app_id,
+            version,
+            context.build_id.as_deref().unwrap_or(""),
+            context.process_type.as_deref().unwrap_or(""),
+            stack_trace,
+        ] {
+            hasher.update(part.len().to_le_bytes());
+            hasher.update(part);
+        }
+
+        Some(Self {
+            stack_hash: hex::encode(hasher.finalize()),
+            mapping_id: mapping_identity(mapping)?,
+            platform: context.platform.as_str().to_string(),
+            app_id: app_id.to_string(),
+            version: version.to_string(),
+        })
+    }
+}
+
+/// Hash of the mapping's version directory: the path, size and modification
+/// time of every file in it.
+fn mapping_identity(mapping: &MappingInfo) -> Option<String> {
+    let dir = mapping.path.parent()?;
+    let mut files = Vec::new();
+    collect_files(dir, &mut files).ok()?;
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(mapping.version.as_bytes());
+    for (path, len, modified) in files {
+        let relative = path.strip_prefix(dir).unwrap_or(&path);
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update(len.to_le_bytes());
+        hasher.update(modified.to_le_bytes());
+    }
+    Some(hex::encode(hasher.finalize()))
+}
+
+fn collect_files(
+    dir: &Path,
+    files: &mut Vec<(std::path::PathBuf, u64, u128)>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            collect_files(&entry.path(), files)?;
+        } else {
+            let modified = metadata
+                .modified()?
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or(0);
+            files.push((entry.path(), metadata.len(), modified));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbolication::{MappingStore, Platform, Symbolicator};
+
+    #[test]
+    fn test_results_cached_until_mapping_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let symbolicator = Symbolicator::new(MappingStore::new(dir.path()))
+            .with_result_cache(ResultCache::open_in_memory().unwrap());
+        let cache = symbolicator.result_cache().unwrap();
+        let save = |class: &str| {
+            symbolicator
+                .store()
+                .save_mapping(
+                    Platform::Android,
+                    "app",
+                    "1.0.0",
+                    "mapping.txt",
+                    format!("{} -> a:\n    void run() -> b\n", class).as_bytes(),
+                )
+                .unwrap();
+        };
+        save("com.example.Main");
+
+        let context = SymbolicationContext {
+            platform: Platform::Android,
+            app_id: Some("app".to_string()),
+            version: Some("1.0.0".to_string()),
+            build_id: None,
+            process_type: None,
+        };
+        let stack = "java.lang.RuntimeException\n\tat a.b(Unknown Source)";
+        let function = || {
+            symbolicator.symbolicate(stack, &context).unwrap().frames[1]
+                .function
+                .clone()
+        };
+
+        assert_eq!(function().as_deref(), Some("com.example.Main.run"));
+        assert_eq!(cache.len().unwrap(), 1);
+        assert_eq!(function().as_deref(), Some("com.example.Main.run"));
+        assert_eq!(cache.len().unwrap(), 1);
+
+        // A re-uploaded mapping replaces the stale result
+        save("com.example.Renamed");
+        symbolicator.cache().clear();
+        assert_eq!(function().as_deref(), Some("com.example.Renamed.run"));
+        assert_eq!(cache.len().unwrap(), 1);
+
+        // Stacks without a mapping aren't cached
+        let other = SymbolicationContext {
+            app_id: Some("other".to_string()),
+            ..context.clone()
+        };
+        assert!(symbolicator.symbolicate(stack, &other).is_err());
+        assert_eq!(cache.len().unwrap(), 1);
+    }
+}
@@ -1,30 +1,98 @@
 //! Rust symbolication using addr2line/DWARF debug info.
 //!
 //! Parses Rust stack traces and resolves addresses to source locations
-//! using debug symbols.
+//! using debug symbols. Mangled symbols from release builds (and C++ frames
+//! from FFI) are demangled, and the `::h<hash>` suffix is stripped.
+//!
+//! Frames without an `at file:line` location (stripped release builds print
+//! `<unknown>` or a bare symbol) are resolved with addr2line when the
+//! mapping store holds an unstripped binary or separate DWARF file for the
+//! release, expanding inlined calls into their own frames.
+//!
+//! Backtrace addresses are runtime addresses. For position-independent
+//! executables, include the load address in the report so the ASLR slide
+//! can be removed:
+//!
+//! ```text
+//! load address: 0x55d1c2a00000
+//! ```
+//!
+//! Without it, addresses are looked up as-is, which is correct for non-PIE
+//! binaries and for reporters that already subtract the load address.
+//!
+//! When no debug file is stored, the GNU build ID from the context or a
+//! `build id: <hex>` line in the report is used to request the debug info
+//! from debuginfod (see [`Debuginfod`]).
+
+use std::sync::Arc;
 
 use regex::Regex;
 
+use super::cache::{load_mapping, MappingCache};
+use super::debuginfod::Debuginfod;
+use super::demangle::demangle;
+use super::native::DebugFileSlices;
 use super::{
-    MappingStore, SymbolicatedFrame, SymbolicatedStack, SymbolicationContext, SymbolicationError,
+    Confidence, MappingStore, PlatformSymbolicator, SymbolicatedFrame, SymbolicatedStack,
+    SymbolicationContext, SymbolicationError, SCHEMA_VERSION,
 };
 
+/// Matches a `build id: <hex>` line added by the crash reporter.
+fn build_id_re() -> Regex {
+    Regex::new(r"(?i)^\s*build[ -]?id:\s*([0-9a-f]+)\s*$").unwrap()
+}
+
+/// The build ID from a `build id:` line, if any.
+fn build_id_line(stack_trace: &str) -> Option<String> {
+    let re = build_id_re();
+    stack_trace
+        .lines()
+        .find_map(|line| re.captures(line).map(|caps| caps[1].to_string()))
+}
+
+/// A frame header line awaiting its optional `at` location line.
+struct PendingFrame {
+    index: usize,
+    raw: String,
+    function: Option<String>,
+    address: Option<u64>,
+}
+
 /// Rust stack trace symbolicator.
 pub struct RustSymbolicator<'a> {
     store: &'a MappingStore,
+    cache: Option<&'a MappingCache>,
+    debuginfod: Option<&'a Debuginfod>,
 }
 
 impl<'a> RustSymbolicator<'a> {
     /// Create a new Rust symbolicator.
     pub fn new(store: &'a MappingStore) -> Self {
-        Self { store }
+        Self {
+            store,
+            cache: None,
+            debuginfod: None,
+        }
+    }
+
+    /// Reuse parsed debug files from `cache` instead of re-reading them.
+    pub fn with_cache(mut self, cache: &'a MappingCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Fetch debug info by build ID from `debuginfod` when none is stored.
+    pub fn with_debuginfod(mut self, debuginfod: &'a Debuginfod) -> Self {
+        self.debuginfod = Some(debuginfod);
+        self
     }
 
     /// Symbolicate a Rust stack trace.
     ///
     /// Rust stack traces from panics typically include source locations
-    /// in debug builds. For release builds with symbols stripped, this
-    /// attempts to use addr2line with debug symbols if available.
+    /// in debug builds. For release builds with symbols stripped, frame
+    /// addresses are resolved with addr2line against the stored debug
+    /// binary, if any.
     pub fn symbolicate(
         &self,
         stack_trace: &str,
@@ -37,90 +105,123 @@ impl<'a> RustSymbolicator<'a> {
             context.version.as_deref().unwrap_or("unknown"),
         );
 
-        // Rust backtraces already include source info in debug builds
-        // We just need to parse and format them nicely
-        self.parse_rust_backtrace(stack_trace, mapping_info.map(|i| i.path.as_path()))
+        // Debug files are parsed lazily, so a text mapping (symbols.txt)
+        // simply resolves nothing
+        let stored = mapping_info.and_then(|info| {
+            load_mapping(self.cache, &info, &info.path, |p| Ok(DebugFileSlices::new(p))).ok()
+        });
+        let debug = match stored {
+            Some(debug) => Some((debug, Confidence::Exact)),
+            None => context
+                .build_id
+                .clone()
+                .or_else(|| build_id_line(stack_trace))
+                .and_then(|build_id| {
+                    self.debuginfod?.debug_file(self.cache, &context.platform, &build_id)
+                })
+                .map(|debug| (debug, Confidence::BuildId)),
+        };
+
+        self.parse_rust_backtrace(stack_trace, debug)
     }
 
-    /// Parse a Rust backtrace.
+    /// Parse a Rust backtrace, resolving unlocated frames against `debug`
+    /// with the given confidence.
     fn parse_rust_backtrace(
         &self,
         stack_trace: &str,
-        _symbols_path: Option<&std::path::Path>,
+        debug: Option<(Arc<DebugFileSlices>, Confidence)>,
     ) -> Result<SymbolicatedStack, SymbolicationError> {
         // Regex patterns for Rust stack frames
         // Format 1: "   0: std::panicking::begin_panic"
         // Format 2: "   0:     0x7f1234567890 - std::panicking::begin_panic"
         // Format 3 (with location): "             at /path/to/file.rs:42:5"
-        let frame_num_re = Regex::new(r"^\s*(\d+):\s+(?:0x[0-9a-f]+\s+-\s+)?(.+)$").unwrap();
-        let location_re = Regex::new(r"^\s+at\s+(.+):(\d+)(?::(\d+))?$").unwrap();
+        // Format 4 (stripped): "   0:     0x7f1234567890 - <unknown>" or "   0: 0x7f1234567890"
+        let frame_num_re =
+            Regex::new(r"^\s*(\d+):\s+(?:0x([0-9a-f]+)\s+-\s+)?(.+)$").unwrap();
+        let address_only_re = Regex::new(r"^0x([0-9a-f]+)$").unwrap();
+        let location_re = Regex::new(r"^\s+at\s+(.+?):(\d+)(?::(\d+))?$").unwrap();
+        let load_address_re =
+            Regex::new(r"(?i)^\s*(?:load address|base address|image base):\s*0x([0-9a-f]+)\s*$")
+                .unwrap();
+
+        let load_address = stack_trace.lines().find_map(|line| {
+            load_address_re
+                .captures(line)
+                .and_then(|caps| u64::from_str_radix(&caps[1], 16).ok())
+        });
+
+        let build_id_line_re = build_id_re();
 
         let mut frames = Vec::new();
-        let mut current_function: Option<String> = None;
-        let mut current_raw: String = String::new();
+        let mut pending: Option<PendingFrame> = None;
 
         for line in stack_trace.lines() {
             // Check for frame number line
             if let Some(caps) = frame_num_re.captures(line) {
                 // Save previous frame if exists
-                if let Some(func) = current_function.take() {
-                    frames.push(SymbolicatedFrame {
-                        raw: current_raw.clone(),
-                        function: Some(func),
-                        file: None,
-                        line: None,
-                        column: None,
-                        symbolicated: true,
-                    });
+                if let Some(frame) = pending.take() {
+                    self.push_unlocated(&mut frames, frame, debug.as_ref(), load_address);
                 }
 
-                current_function = Some(caps[2].trim().to_string());
-                current_raw = line.to_string();
+                let name = caps[3].trim();
+                let (function, address) = match address_only_re.captures(name) {
+                    Some(addr) => (None, u64::from_str_radix(&addr[1], 16).ok()),
+                    None => (
+                        Some(demangle(name)).filter(|f| f != "<unknown>"),
+                        caps.get(2).and_then(|m| u64::from_str_radix(m.as_str(), 16).ok()),
+                    ),
+                };
+                pending = Some(PendingFrame {
+                    index: caps[1].parse().unwrap_or(0),
+                    raw: line.to_string(),
+                    function,
+                    address,
+                });
                 continue;
             }
 
             // Check for location line (belongs to current frame)
             if let Some(caps) = location_re.captures(line) {
-                if let Some(func) = current_function.take() {
+                if let Some(frame) = pending.take() {
                     let file = caps.get(1).map(|m| m.as_str().to_string());
                     let line_num: Option<u32> = caps.get(2).and_then(|m| m.as_str().parse().ok());
                     let col: Option<u32> = caps.get(3).and_then(|m| m.as_str().parse().ok());
 
                     frames.push(SymbolicatedFrame {
-                        raw: format!("{}\n{}", current_raw, line),
-                        function: Some(func),
+                        raw: format!("{}\n{}", frame.raw, line),
+                        function: Some(frame.function.unwrap_or_else(|| "<unknown>".to_string())),
                         file,
                         line: line_num,
                         column: col,
                         symbolicated: true,
+                        in_app: None,
+                        confidence: Some(Confidence::Exact),
                     });
-                    current_raw.clear();
                 }
                 continue;
             }
 
             // Other lines (thread info, etc.)
-            if !line.trim().is_empty() {
+            if !line.trim().is_empty()
+                && !load_address_re.is_match(line)
+                && !build_id_line_re.is_match(line)
+            {
                 frames.push(SymbolicatedFrame::raw(line.to_string()));
             }
         }
 
         // Don't forget last frame
-        if let Some(func) = current_function {
-            frames.push(SymbolicatedFrame {
-                raw: current_raw,
-                function: Some(func),
-                file: None,
-                line: None,
-                column: None,
-                symbolicated: true,
-            });
+        if let Some(frame) = pending {
+            self.push_unlocated(&mut frames, frame, debug.as_ref(), load_address);
         }
 
         let symbolicated_count = frames.iter().filter(|f| f.symbolicated).count();
         let total_count = frames.len();
 
         Ok(SymbolicatedStack {
+            schema_version: SCHEMA_VERSION,
+            exceptions: Vec::new(),
             raw: stack_trace.to_string(),
             frames,
             symbolicated_count,
@@ -128,7 +229,137 @@ impl<'a> RustSymbolicator<'a> {
         })
     }
 
-    // Note: addr2line integration for stripped binaries is available but requires
-    // additional setup. For most Rust applications, debug builds include full
-    // symbol information in the stack trace itself.
+    /// Push a frame that has no `at` line, resolving its address if possible.
+    fn push_unlocated(
+        &self,
+        frames: &mut Vec<SymbolicatedFrame>,
+        frame: PendingFrame,
+        debug: Option<&(Arc<DebugFileSlices>, Confidence)>,
+        load_address: Option<u64>,
+    ) {
+        let locations = match (frame.address, debug) {
+            (Some(address), Some((debug, _))) => debug
+                .with_slice(None, |debug| {
+                    let mut file_address = match load_address {
+                        Some(load) => debug.address_for_offset(address.wrapping_sub(load)),
+                        None => address,
+                    };
+                    // Frames above the first hold return addresses; look up
+                    // the call instruction instead
+                    if frame.index > 0 {
+                        file_address = file_address.saturating_sub(1);
+                    }
+                    debug.lookup(file_address)
+                })
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        if locations.is_empty() {
+            match frame.function {
+                Some(function) => frames.push(SymbolicatedFrame {
+                    raw: frame.raw,
+                    function: Some(function),
+                    file: None,
+                    line: None,
+                    column: None,
+                    symbolicated: true,
+                    in_app: None,
+                    confidence: Some(Confidence::Exact),
+                }),
+                None => frames.push(SymbolicatedFrame::raw(frame.raw)),
+            }
+            return;
+        }
+
+        for location in locations {
+            match location.function.or_else(|| frame.function.clone()) {
+                Some(function) => frames.push(
+                    SymbolicatedFrame::symbolicated(
+                        frame.raw.clone(),
+                        function,
+                        location.file,
+                        location.line,
+                        location.column,
+                    )
+                    .with_confidence(debug.map_or(Confidence::Exact, |(_, c)| *c)),
+                ),
+                None => frames.push(SymbolicatedFrame::raw(frame.raw.clone())),
+            }
+        }
+    }
+}
+
+impl PlatformSymbolicator for RustSymbolicator<'_> {
+    fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        RustSymbolicator::symbolicate(self, stack_trace, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbolication::DebugFile;
+
+    #[test]
+    fn test_parse_mangled_backtrace() {
+        let store = MappingStore::new("/nonexistent");
+        let sym = RustSymbolicator::new(&store);
+        let trace = "\
+   0:     0x55d1c2a3b4c5 - _ZN7mycrate4main17h0123456789abcdefE
+             at /src/main.rs:10:5
+   1:     0x55d1c2a3b4d6 - core::ops::function::FnOnce::call_once::h0f1e2d3c4b5a6978";
+
+        let result = sym.parse_rust_backtrace(trace, None).unwrap();
+        assert_eq!(result.frames[0].display(), "mycrate::main (/src/main.rs:10)");
+        assert_eq!(result.frames[1].display(), "core::ops::function::FnOnce::call_once");
+    }
+
+    #[inline(never)]
+    fn rust_lookup_marker() -> u32 {
+        std::hint::black_box(11)
+    }
+
+    #[test]
+    fn test_resolve_stripped_frames() {
+        // Use the test binary as the stored debug file
+        assert_eq!(rust_lookup_marker(), 11);
+        let exe = std::env::current_exe().unwrap();
+        let debug = DebugFile::open(&exe, None).unwrap();
+        let marker = debug.find_symbol("rust_lookup_marker").expect("marker symbol");
+        let offset = marker - debug.image_base();
+
+        // Frame 1 holds a return address, so point one byte past the marker
+        let load = 0x55d1c2a00000u64;
+        let trace = format!(
+            "load address: 0x{:x}\n   0: 0x{:x}\n   1:     0x{:x} - <unknown>\n",
+            load,
+            load + offset,
+            load + offset + 1
+        );
+
+        let store = MappingStore::new("/nonexistent");
+        let sym = RustSymbolicator::new(&store);
+        let result = sym
+            .parse_rust_backtrace(
+                &trace,
+                Some((Arc::new(DebugFileSlices::new(&exe)), Confidence::BuildId)),
+            )
+            .unwrap();
+
+        assert_eq!(result.symbolicated_count, result.total_count);
+        for frame in &result.frames {
+            assert!(frame.function.as_deref().unwrap().contains("rust_lookup_marker"));
+            assert_eq!(frame.confidence, Some(Confidence::BuildId));
+        }
+
+        // Without a debug file, address-only frames stay raw
+        let result = sym.parse_rust_backtrace(&trace, None).unwrap();
+        assert_eq!(result.total_count, 2);
+        assert_eq!(result.symbolicated_count, 0);
+    }
 }
@@ -0,0 +1,280 @@
+//! Source map scope information, for expanding inlined JavaScript frames.
+//!
+//! Minifiers that inline functions lose the inlined call in the stack: one
+//! generated frame stands for several original ones. The source map scopes
+//! proposal (ECMA-426, 2024 draft) records this with two extra fields:
+//!
+//! - `originalScopes`: per source, the function and block scopes of the
+//!   original code, as comma-separated VLQ items. A start item is
+//!   `line, column, kind, flags[, name], variables...` (line relative to the
+//!   previous item, name relative to the previous name, `flags & 1` = has
+//!   name); an end item is `line, column`.
+//! - `generatedRanges`: ranges of generated code, lines separated by `;` and
+//!   items by `,`. A start item is `column, flags[, definition][, callsite],
+//!   bindings...` where `flags & 1` = has definition (`source, scope`) and
+//!   `flags & 2` = has callsite (`source, line, column`), i.e. the range is an
+//!   inlined call. An end item is just `column`.
+//!
+//! Columns are relative to the previous item on the same generated line.
+//! Definition and callsite indices are relative to the previous definition
+//! or callsite, restarting from absolute values when an earlier field of the
+//! tuple changed.
+
+use sourcemap::vlq::parse_vlq_segment;
+
+/// Flag on an original scope start item: the scope has a name.
+const SCOPE_HAS_NAME: i64 = 0x1;
+/// Flag on a generated range start item: the range has a definition.
+const RANGE_HAS_DEFINITION: i64 = 0x1;
+/// Flag on a generated range start item: the range is an inlined call.
+const RANGE_HAS_CALLSITE: i64 = 0x2;
+
+/// An original source position (0-based).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Callsite {
+    pub source: u32,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// One original frame at a generated position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct InlineFrame<'a> {
+    /// Name of the original function.
+    pub function: Option<&'a str>,
+    /// Where this function was inlined into its caller, if it was. The
+    /// caller is the next frame.
+    pub called_from: Option<Callsite>,
+}
+
+#[derive(Debug)]
+struct GeneratedRange {
+    start: (u32, u32),
+    end: (u32, u32),
+    depth: usize,
+    /// `(source index, scope index)` into `Scopes::names`.
+    definition: Option<(u32, u32)>,
+    callsite: Option<Callsite>,
+}
+
+/// Decoded scopes of a source map.
+#[derive(Debug, Default)]
+pub(crate) struct Scopes {
+    /// Scope names, per source, in start order.
+    names: Vec<Vec<Option<String>>>,
+    ranges: Vec<GeneratedRange>,
+}
+
+impl Scopes {
+    /// Decode the scope fields. `None` if they are missing or malformed.
+    pub(crate) fn parse(
+        names: &[String],
+        original_scopes: &[Option<String>],
+        generated_ranges: &str,
+    ) -> Option<Self> {
+        let scope_names = original_scopes
+            .iter()
+            .map(|scopes| match scopes {
+                Some(scopes) => parse_original_scopes(names, scopes),
+                None => Some(Vec::new()),
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            names: scope_names,
+            ranges: parse_generated_ranges(generated_ranges)?,
+        })
+    }
+
+    /// Original frames at a generated position (0-based), innermost first.
+    ///
+    /// Returns one frame per enclosing range with a definition, stopping at
+    /// the first that wasn't inlined. Empty when no range covers the position.
+    pub(crate) fn frames_at(&self, line: u32, column: u32) -> Vec<InlineFrame<'_>> {
+        let mut containing: Vec<&GeneratedRange> = self
+            .ranges
+            .iter()
+            .filter(|r| r.start <= (line, column) && (line, column) < r.end)
+            .collect();
+        containing.sort_by_key(|r| std::cmp::Reverse(r.depth));
+
+        let mut frames = Vec::new();
+        for range in containing {
+            let Some((source, scope)) = range.definition else {
+                continue;
+            };
+            let function = self
+                .names
+                .get(source as usize)
+                .and_then(|scopes| scopes.get(scope as usize))
+                .and_then(|name| name.as_deref());
+            frames.push(InlineFrame {
+                function,
+                called_from: range.callsite,
+            });
+            if range.callsite.is_none() {
+                break;
+            }
+        }
+        frames
+    }
+}
+
+/// Names of the scopes in one source's `originalScopes` string, in start order.
+fn parse_original_scopes(names: &[String], encoded: &str) -> Option<Vec<Option<String>>> {
+    let mut scopes = Vec::new();
+    let mut name_index = 0i64;
+
+    for item in encoded.split(',').filter(|item| !item.is_empty()) {
+        let fields = parse_vlq_segment(item).ok()?;
+        // End items are `line, column`
+        if fields.len() <= 2 {
+            continue;
+        }
+        let flags = *fields.get(3)?;
+        let name = if flags & SCOPE_HAS_NAME != 0 {
+            name_index += *fields.get(4)?;
+            Some(names.get(usize::try_from(name_index).ok()?)?.clone())
+        } else {
+            None
+        };
+        scopes.push(name);
+    }
+
+    Some(scopes)
+}
+
+/// Decode `generatedRanges` into ranges with their nesting depth.
+fn parse_generated_ranges(encoded: &str) -> Option<Vec<GeneratedRange>> {
+    let mut ranges: Vec<GeneratedRange> = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    let mut definition = (0i64, 0i64);
+    let mut callsite = (0i64, 0i64, 0i64);
+
+    for (line, items) in encoded.split(';').enumerate() {
+        let line = u32::try_from(line).ok()?;
+        let mut column = 0i64;
+
+        for item in items.split(',').filter(|item| !item.is_empty()) {
+            let fields = parse_vlq_segment(item).ok()?;
+            column += *fields.first()?;
+            let position = (line, u32::try_from(column).ok()?);
+
+            // End items are just the column
+            if fields.len() == 1 {
+                let index = open.pop()?;
+                ranges[index].end = position;
+                continue;
+            }
+
+            let flags = fields[1];
+            let mut rest = fields[2..].iter().copied();
+
+            let range_definition = if flags & RANGE_HAS_DEFINITION != 0 {
+                let source_delta = rest.next()?;
+                let scope = rest.next()?;
+                definition = if source_delta == 0 {
+                    (definition.0, definition.1 + scope)
+                } else {
+                    (definition.0 + source_delta, scope)
+                };
+                Some((
+                    u32::try_from(definition.0).ok()?,
+                    u32::try_from(definition.1).ok()?,
+                ))
+            } else {
+                None
+            };
+
+            let range_callsite = if flags & RANGE_HAS_CALLSITE != 0 {
+                let source_delta = rest.next()?;
+                let line_value = rest.next()?;
+                let column_value = rest.next()?;
+                callsite = if source_delta != 0 {
+                    (callsite.0 + source_delta, line_value, column_value)
+                } else if line_value != 0 {
+                    (callsite.0, callsite.1 + line_value, column_value)
+                } else {
+                    (callsite.0, callsite.1, callsite.2 + column_value)
+                };
+                Some(Callsite {
+                    source: u32::try_from(callsite.0).ok()?,
+                    line: u32::try_from(callsite.1).ok()?,
+                    column: u32::try_from(callsite.2).ok()?,
+                })
+            } else {
+                None
+            };
+
+            open.push(ranges.len());
+            ranges.push(GeneratedRange {
+                start: position,
+                end: (u32::MAX, u32::MAX),
+                depth: open.len(),
+                definition: range_definition,
+                callsite: range_callsite,
+            });
+        }
+    }
+
+    Some(ranges)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use sourcemap::vlq::generate_vlq_segment;
+
+    /// Encode items as a comma-separated VLQ list.
+    pub(crate) fn encode(items: &[&[i64]]) -> String {
+        items
+            .iter()
+            .map(|item| generate_vlq_segment(item).unwrap())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    #[test]
+    fn test_inlined_frames() {
+        let names = vec![
+            "function".to_string(),
+            "outer".to_string(),
+            "inner".to_string(),
+        ];
+        // outer: lines 0-10, inner: lines 4-6
+        let original = encode(&[&[0, 0, 0, 1, 1], &[4, 0, 0, 1, 1], &[2, 1], &[4, 1]]);
+        // Generated line 0: outer over columns 0-100, inner inlined at
+        // columns 5-20, called from app.js line 2, column 4
+        let generated = encode(&[&[0, 1, 0, 0], &[5, 3, 0, 1, 0, 2, 4], &[15], &[80]]);
+
+        let scopes = Scopes::parse(&names, &[Some(original)], &generated).unwrap();
+
+        let frames = scopes.frames_at(0, 10);
+        assert_eq!(
+            frames,
+            [
+                InlineFrame {
+                    function: Some("inner"),
+                    called_from: Some(Callsite {
+                        source: 0,
+                        line: 2,
+                        column: 4
+                    }),
+                },
+                InlineFrame {
+                    function: Some("outer"),
+                    called_from: None,
+                },
+            ]
+        );
+        assert_eq!(scopes.frames_at(0, 30).len(), 1);
+        assert!(scopes.frames_at(1, 0).is_empty());
+    }
+
+    #[test]
+    fn test_malformed_scopes() {
+        assert!(Scopes::parse(&[], &[], "!!").is_none());
+        // End without start
+        assert!(Scopes::parse(&[], &[], "A").is_none());
+    }
+}
@@ -11,16 +11,22 @@
 //!   <platform>/           # e.g., "android", "electron", "flutter"
 //!     <app_id>/           # e.g., "com.example.app", "my-desktop-app"
 //!       <version>/        # e.g., "1.0.0", "2.1.3"
-//!         <mapping_file>  # e.g., "mapping.txt", "main.js.map"
+//!         <mapping_file>  # e.g., "mapping.txt.gz", "main.js.map.gz"
 //! ```
 //!
+//! Text mappings (ProGuard files, source maps, ...) are stored gzipped and
+//! decompressed on the fly when read; see [`MappingStore::open_file`].
+//! Saved files are deduplicated by content hash: each distinct file is kept
+//! once in `<root>/.cas/` and hard-linked into the version directories that
+//! use it.
+//!
 //! # Example
 //!
 //! ```rust,ignore
 //! use bugstr::symbolication::{MappingStore, Platform};
 //!
 //! // Create and scan store
-//! let mut store = MappingStore::new("./mappings");
+//! let store = MappingStore::new("./mappings");
 //! let count = store.scan()?;
 //! println!("Loaded {} mapping files", count);
 //!
@@ -41,10 +47,19 @@
 
 use std::collections::HashMap;
 use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::SystemTime;
 
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use semver::Version;
+use sha2::{Digest, Sha256};
 
+use super::android::is_native_lib;
+use super::validate::{self, MappingWarning};
 use super::{Platform, SymbolicationError};
 
 /// Key for looking up mapping files.
@@ -55,10 +70,60 @@ pub struct MappingKey {
     pub version: String,
 }
 
+/// Suffix of gzip-compressed mapping files.
+const GZIP_SUFFIX: &str = ".gz";
+
+/// Directory under the root holding one copy of each saved file, named by
+/// its SHA-256.
+const CAS_DIR: &str = ".cas";
+
+/// First bytes of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Which versions [`MappingStore::prune()`] removes.
+///
+/// A version is removed when it matches every criterion that is set; with
+/// none set, nothing is removed. Combining both keeps recent releases and
+/// a minimum history: `keep_latest: Some(5), older_than: Some(90 days ago)`
+/// removes versions older than 90 days except each app's newest five.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrunePolicy {
+    /// Keep the newest `n` versions of each app (by semantic version).
+    pub keep_latest: Option<usize>,
+    /// Only remove versions whose directory hasn't changed since this time.
+    pub older_than: Option<SystemTime>,
+}
+
+/// Result of [`MappingStore::dedup()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DedupStats {
+    /// Files examined.
+    pub files: usize,
+    /// Files replaced by a link to an identical file.
+    pub linked: usize,
+    /// Disk space freed, in bytes.
+    pub bytes_saved: u64,
+}
+
+/// A change to the store's index made by [`MappingStore::refresh()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MappingChange {
+    /// The mapping for a version was added or its file changed.
+    Updated(MappingKey),
+    /// A version no longer has a mapping file.
+    Removed(MappingKey),
+    /// The whole tree was rescanned, finding this many mappings.
+    Rescanned(usize),
+}
+
 /// Information about a loaded mapping file.
 #[derive(Debug, Clone)]
 pub struct MappingInfo {
     pub path: PathBuf,
+    /// Every mapping file of the version, `path` first. Electron apps have
+    /// one source map per bundle (`main.js.map`, `preload.js.map`, ...);
+    /// other platforms have just `path`.
+    pub files: Vec<PathBuf>,
     pub platform: Platform,
     pub app_id: String,
     pub version: String,
@@ -82,6 +147,7 @@ pub struct MappingInfo {
 ///     com.example.app/
 ///       1.0.0/
 ///         mapping.txt          # ProGuard/R8 mapping
+///         arm64-v8a/libfoo.so  # Unstripped NDK library (optional)
 ///       1.1.0/
 ///         mapping.txt
 ///   electron/
@@ -93,14 +159,46 @@ pub struct MappingInfo {
 ///     com.example.app/
 ///       1.0.0/
 ///         app.android-arm64.symbols
+///   ios/
+///     com.example.app/
+///       1.0.0/
+///         MyApp.app.dSYM/      # dSYM bundle(s) from the archive
+///   windows/
+///     my-desktop-app/
+///       1.0.0/
+///         MyApp.pdb            # PDB per module
 /// ```
 ///
+/// # Compression
+///
+/// [`save_mapping()`](Self::save_mapping) gzips text mappings (`.txt`,
+/// `.map`, `.json`, `.tsv`), which typically shrinks them by 80%, and stores
+/// them as `<filename>.gz`. Lookups find either form, so existing
+/// uncompressed files keep working. Read mapping files with
+/// [`open_file()`](Self::open_file) or [`read_file()`](Self::read_file),
+/// which decompress transparently. Native debug files (libraries, dSYMs,
+/// PDBs) are stored as-is since they are read in place.
+///
+/// # Deduplication
+///
+/// Apps often ship identical mappings across versions (a source map that
+/// didn't change, the same native library). `save_mapping()` stores each
+/// distinct file once under `<root>/.cas/<sha256>` and hard-links it into the
+/// version directory, so saving a duplicate costs no disk space and no
+/// write. Where hard links aren't supported a plain copy is written.
+/// [`dedup()`](Self::dedup) does the same for files already in the tree.
+/// Linked files must never be modified in place; `save_mapping()` always
+/// replaces the link.
+///
 /// # Thread Safety
 ///
-/// `MappingStore` is **not thread-safe**. For concurrent access, wrap in
-/// `Arc<Mutex<MappingStore>>` or use separate instances per thread.
-/// The [`scan()`](Self::scan) method clears and rebuilds the internal cache,
-/// so concurrent reads during a scan will produce inconsistent results.
+/// `MappingStore` is `Send + Sync`: all methods take `&self`, and the index
+/// of discovered mappings sits behind an internal `RwLock`, so lookups run
+/// concurrently and can be shared through an `Arc` without extra locking.
+/// [`scan()`](Self::scan) builds the new index before swapping it in, so
+/// lookups during a rescan see either the old or the new set of mappings,
+/// never a partial one. Lookups return owned [`MappingInfo`] values so no
+/// lock is held while a mapping is parsed.
 ///
 /// # Security
 ///
@@ -117,7 +215,7 @@ pub struct MappingInfo {
 /// use bugstr::symbolication::{MappingStore, Platform};
 ///
 /// // Create store pointing to mappings directory
-/// let mut store = MappingStore::new("./mappings");
+/// let store = MappingStore::new("./mappings");
 ///
 /// // Scan to discover existing mapping files
 /// let count = store.scan()?;
@@ -141,7 +239,7 @@ pub struct MappingStore {
     /// Root directory for mapping files.
     root: PathBuf,
     /// In-memory cache of discovered mapping files, keyed by platform/app/version.
-    mappings: HashMap<MappingKey, MappingInfo>,
+    mappings: RwLock<HashMap<MappingKey, MappingInfo>>,
 }
 
 impl MappingStore {
@@ -167,10 +265,18 @@ impl MappingStore {
     pub fn new<P: AsRef<Path>>(root: P) -> Self {
         Self {
             root: root.as_ref().to_path_buf(),
-            mappings: HashMap::new(),
+            mappings: RwLock::new(HashMap::new()),
         }
     }
 
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<MappingKey, MappingInfo>> {
+        self.mappings.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<MappingKey, MappingInfo>> {
+        self.mappings.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
     /// Get a reference to the root directory path.
     ///
     /// # Returns
@@ -187,7 +293,7 @@ impl MappingStore {
     ///
     /// # Side Effects
     ///
-    /// - **Clears** the internal mapping cache before scanning
+    /// - **Replaces** the internal mapping cache once the scan completes
     /// - **Creates** the root directory if it doesn't exist
     /// - Does **not** load file contents into memory (only paths are cached)
     ///
@@ -199,32 +305,37 @@ impl MappingStore {
     /// # Platform-Specific Files
     ///
     /// The scanner looks for these files by platform:
-    /// - **Android**: `mapping.txt`, `proguard-mapping.txt`, `r8-mapping.txt`
+    /// - **Android**: `mapping.txt`, `proguard-mapping.txt`, `r8-mapping.txt`, or
+    ///   unstripped `*.so` libraries (also in ABI subdirectories)
     /// - **Electron**: `main.js.map`, `index.js.map`, `bundle.js.map`
     /// - **Flutter**: `app.android-arm64.symbols`, `app.ios-arm64.symbols`, `app.symbols`
-    /// - **Rust**: `symbols.txt`, `debug.dwarf`
+    /// - **Rust**: `symbols.txt`, `debug.dwarf`, or any `*.debug`/`*.dwarf` file
+    ///   (unstripped binary or split DWARF)
     /// - **Go**: `symbols.txt`, `go.sym`
     /// - **Python**: `source-map.json`, `mapping.json`
     /// - **React Native**: `index.android.bundle.map`, `index.ios.bundle.map`, `main.jsbundle.map`
+    /// - **iOS**: any `*.dSYM` bundle or `*.dwarf` file
+    /// - **Windows**: any `*.pdb` file
+    /// - **Unity**: `MethodMap.tsv`, `LineNumberMappings.json`
     ///
     /// Falls back to any `.map`, `.txt`, or `.symbols` file if primary names not found.
     ///
     /// # Example
     ///
     /// ```rust,ignore
-    /// let mut store = MappingStore::new("./mappings");
+    /// let store = MappingStore::new("./mappings");
     /// match store.scan() {
     ///     Ok(0) => println!("No mapping files found"),
     ///     Ok(n) => println!("Loaded {} mapping files", n),
     ///     Err(e) => eprintln!("Scan failed: {}", e),
     /// }
     /// ```
-    pub fn scan(&mut self) -> Result<usize, SymbolicationError> {
-        self.mappings.clear();
-        let mut count = 0;
+    pub fn scan(&self) -> Result<usize, SymbolicationError> {
+        let mut mappings = HashMap::new();
 
         if !self.root.exists() {
             fs::create_dir_all(&self.root)?;
+            self.write().clear();
             return Ok(0);
         }
 
@@ -236,6 +347,10 @@ impl MappingStore {
             }
 
             let platform_name = platform_entry.file_name().to_string_lossy().to_string();
+            // Skip the content store and other hidden directories
+            if platform_name.starts_with('.') {
+                continue;
+            }
             let platform = Platform::from_str(&platform_name);
 
             // Scan app directories
@@ -266,6 +381,7 @@ impl MappingStore {
                         };
 
                         let info = MappingInfo {
+                            files: Self::mapping_files(&platform, &mapping_path),
                             path: mapping_path,
                             platform: platform.clone(),
                             app_id: app_id.clone(),
@@ -273,19 +389,83 @@ impl MappingStore {
                             loaded_at: std::time::SystemTime::now(),
                         };
 
-                        self.mappings.insert(key, info);
-                        count += 1;
+                        mappings.insert(key, info);
                     }
                 }
             }
         }
 
+        let count = mappings.len();
+        *self.write() = mappings;
         Ok(count)
     }
 
-    /// Find the primary mapping file for a platform in a directory.
-    fn find_mapping_file(&self, platform: &Platform, dir: &Path) -> Option<PathBuf> {
-        let candidates: &[&str] = match platform {
+    /// Re-index the version directory containing `path`.
+    ///
+    /// `path` may be a version directory or anything inside it (a mapping
+    /// file, a library in an ABI subdirectory, a file inside a dSYM bundle).
+    /// Only that platform/app/version is re-resolved, so this is cheap enough
+    /// to call for every filesystem event. Paths above the version level
+    /// (a new platform or app directory) fall back to a full
+    /// [`scan()`](Self::scan).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(change))` - The index entry that changed, or the rescan
+    /// * `Ok(None)` - `path` is outside the root or nothing changed
+    /// * `Err(SymbolicationError::IoError)` - A full rescan failed
+    pub fn refresh(&self, path: &Path) -> Result<Option<MappingChange>, SymbolicationError> {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return Ok(None);
+        };
+        if relative.to_string_lossy().starts_with('.') {
+            return Ok(None);
+        }
+        let components: Vec<String> = relative
+            .components()
+            .take(3)
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        let [platform, app_id, version] = components.as_slice() else {
+            return self.scan().map(|count| Some(MappingChange::Rescanned(count)));
+        };
+
+        let platform = Platform::from_str(platform);
+        let key = MappingKey {
+            platform: platform.clone(),
+            app_id: app_id.clone(),
+            version: version.clone(),
+        };
+        let version_path = self.root.join(relative.iter().take(3).collect::<PathBuf>());
+        let mapping_path = if version_path.is_dir() {
+            self.find_mapping_file(&platform, &version_path)
+        } else {
+            None
+        };
+
+        let mut mappings = self.write();
+        match mapping_path {
+            Some(path) => {
+                let info = MappingInfo {
+                    files: Self::mapping_files(&platform, &path),
+                    path,
+                    platform,
+                    app_id: app_id.clone(),
+                    version: version.clone(),
+                    loaded_at: std::time::SystemTime::now(),
+                };
+                mappings.insert(key.clone(), info);
+                Ok(Some(MappingChange::Updated(key)))
+            }
+            None => Ok(mappings.remove(&key).map(|_| MappingChange::Removed(key))),
+        }
+    }
+
+    /// Well-known primary mapping file names for a platform, in order of
+    /// preference. Empty for platforms whose debug files are named after
+    /// the binary (iOS dSYMs, Windows PDBs).
+    pub(crate) fn mapping_file_names(platform: &Platform) -> &'static [&'static str] {
+        match platform {
             Platform::Android => &["mapping.txt", "proguard-mapping.txt", "r8-mapping.txt"],
             Platform::Electron => &["main.js.map", "index.js.map", "bundle.js.map"],
             Platform::Flutter => &["app.android-arm64.symbols", "app.ios-arm64.symbols", "app.symbols"],
@@ -293,25 +473,57 @@ impl MappingStore {
             Platform::Go => &["symbols.txt", "go.sym"],
             Platform::Python => &["source-map.json", "mapping.json"],
             Platform::ReactNative => &["index.android.bundle.map", "index.ios.bundle.map", "main.jsbundle.map"],
-            Platform::Unknown(_) => &[],
-        };
+            Platform::Unity => &["MethodMap.tsv", "LineNumberMappings.json"],
+            Platform::Ios | Platform::Windows | Platform::Unknown(_) => &[],
+        }
+    }
+
+    /// Find the primary mapping file for a platform in a directory.
+    fn find_mapping_file(&self, platform: &Platform, dir: &Path) -> Option<PathBuf> {
+        match platform {
+            Platform::Ios => return Self::find_with_extension(dir, &["dsym", "dwarf"]),
+            Platform::Windows => return Self::find_with_extension(dir, &["pdb"]),
+            _ => {}
+        }
 
-        for candidate in candidates {
-            let path = dir.join(candidate);
-            if path.exists() {
+        for candidate in Self::mapping_file_names(platform) {
+            if let Some(path) = Self::find_file(dir, candidate) {
                 return Some(path);
             }
         }
 
+        // Rust release builds ship an unstripped binary or split DWARF
+        if *platform == Platform::Rust {
+            if let Some(debug) = Self::find_with_extension(dir, &["debug", "dwarf"]) {
+                return Some(debug);
+            }
+        }
+
+        // Go binaries carry their own symbol table
+        if *platform == Platform::Go {
+            let binary = Self::find_file(dir, "go.bin")
+                .or_else(|| Self::find_with_extension(dir, &["debug", "exe"]));
+            if binary.is_some() {
+                return binary;
+            }
+        }
+
+        // Android apps without ProGuard may only ship native libraries
+        if *platform == Platform::Android {
+            if let Some(lib) = Self::find_native_lib(dir) {
+                return Some(lib);
+            }
+        }
+
         // Also check for any .map or .txt files
         if let Ok(entries) = fs::read_dir(dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
-                if let Some(ext) = path.extension() {
-                    let ext = ext.to_string_lossy().to_lowercase();
-                    if ext == "map" || ext == "txt" || ext == "symbols" {
-                        return Some(path);
-                    }
+                let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase());
+                let name = name.as_deref().unwrap_or_default();
+                let name = name.strip_suffix(GZIP_SUFFIX).unwrap_or(name);
+                if [".map", ".txt", ".symbols"].iter().any(|ext| name.ends_with(ext)) {
+                    return Some(path);
                 }
             }
         }
@@ -319,6 +531,121 @@ impl MappingStore {
         None
     }
 
+    /// All mapping files of the version `primary` belongs to, `primary`
+    /// first: every source map for Electron, just `primary` otherwise.
+    fn mapping_files(platform: &Platform, primary: &Path) -> Vec<PathBuf> {
+        let mut files = vec![primary.to_path_buf()];
+        if *platform != Platform::Electron {
+            return files;
+        }
+        let Some(Ok(entries)) = primary.parent().map(fs::read_dir) else {
+            return files;
+        };
+        let mut maps: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase());
+                let name = name.as_deref().unwrap_or_default();
+                path != primary && name.strip_suffix(GZIP_SUFFIX).unwrap_or(name).ends_with(".map")
+            })
+            .collect();
+        maps.sort();
+        files.extend(maps);
+        files
+    }
+
+    /// Find `name` in `dir`, stored either as-is or gzipped.
+    pub(crate) fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+        let path = dir.join(name);
+        if path.exists() {
+            return Some(path);
+        }
+        let compressed = dir.join(format!("{}{}", name, GZIP_SUFFIX));
+        compressed.exists().then_some(compressed)
+    }
+
+    /// Whether a mapping file is stored compressed. Text formats compress
+    /// well; native debug files (including Flutter symbols) are read in
+    /// place.
+    fn is_compressible(filename: &str) -> bool {
+        let extension = Path::new(filename)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        matches!(extension.as_str(), "txt" | "map" | "json" | "tsv")
+    }
+
+    /// Open a mapping file for reading, decompressing it if it is gzipped.
+    ///
+    /// Compression is detected from the file's contents, so this works for
+    /// files saved by [`save_mapping()`](Self::save_mapping) and for plain
+    /// files dropped into the directory.
+    pub fn open_file(path: &Path) -> io::Result<Box<dyn BufRead + Send>> {
+        let mut reader = BufReader::new(fs::File::open(path)?);
+        if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+        } else {
+            Ok(Box::new(reader))
+        }
+    }
+
+    /// Read a whole mapping file, decompressing it if it is gzipped.
+    pub fn read_file(path: &Path) -> io::Result<Vec<u8>> {
+        let mut content = Vec::new();
+        Self::open_file(path)?.read_to_end(&mut content)?;
+        Ok(content)
+    }
+
+    /// Read a whole mapping file as UTF-8, decompressing it if it is gzipped.
+    pub fn read_file_to_string(path: &Path) -> io::Result<String> {
+        let mut content = String::new();
+        Self::open_file(path)?.read_to_string(&mut content)?;
+        Ok(content)
+    }
+
+    /// Find an unstripped native library in a directory or its ABI subdirectories.
+    fn find_native_lib(dir: &Path) -> Option<PathBuf> {
+        let mut found = Vec::new();
+        for entry in fs::read_dir(dir).ok()?.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                found.extend(
+                    fs::read_dir(&path)
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .map(|lib| lib.path())
+                        .filter(|lib| is_native_lib(lib)),
+                );
+            } else if is_native_lib(&path) {
+                found.push(path);
+            }
+        }
+        found.sort();
+        found.into_iter().next()
+    }
+
+    /// Find the first entry (file or bundle directory) with one of the given
+    /// extensions, compared case-insensitively.
+    pub(crate) fn find_with_extension(dir: &Path, extensions: &[&str]) -> Option<PathBuf> {
+        let mut found: Vec<PathBuf> = fs::read_dir(dir)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .map(|ext| {
+                        let ext = ext.to_string_lossy().to_lowercase();
+                        extensions.contains(&ext.as_str())
+                    })
+                    .unwrap_or(false)
+            })
+            .collect();
+        found.sort();
+        found.into_iter().next()
+    }
+
     /// Get mapping info for a specific platform/app/version combination.
     ///
     /// Performs an exact match lookup in the cache. Returns `None` if no mapping
@@ -333,7 +660,7 @@ impl MappingStore {
     ///
     /// # Returns
     ///
-    /// * `Some(&MappingInfo)` - Mapping found for exact match
+    /// * `Some(MappingInfo)` - Mapping found for exact match
     /// * `None` - No mapping for this exact combination
     ///
     /// # Example
@@ -343,13 +670,13 @@ impl MappingStore {
     ///     let content = std::fs::read_to_string(&info.path)?;
     /// }
     /// ```
-    pub fn get(&self, platform: &Platform, app_id: &str, version: &str) -> Option<&MappingInfo> {
+    pub fn get(&self, platform: &Platform, app_id: &str, version: &str) -> Option<MappingInfo> {
         let key = MappingKey {
             platform: platform.clone(),
             app_id: app_id.to_string(),
             version: version.to_string(),
         };
-        self.mappings.get(&key)
+        self.read().get(&key).cloned()
     }
 
     /// Get mapping info with version fallback.
@@ -369,7 +696,7 @@ impl MappingStore {
     ///
     /// # Returns
     ///
-    /// * `Some(&MappingInfo)` - Mapping found (exact or fallback)
+    /// * `Some(MappingInfo)` - Mapping found (exact or fallback)
     /// * `None` - No mappings exist for this app/platform at any version
     ///
     /// # Version Comparison
@@ -390,26 +717,18 @@ impl MappingStore {
         platform: &Platform,
         app_id: &str,
         version: &str,
-    ) -> Option<&MappingInfo> {
+    ) -> Option<MappingInfo> {
         // Try exact match first
         if let Some(info) = self.get(platform, app_id, version) {
             return Some(info);
         }
 
         // Try to find the newest version for this app using semantic versioning
-        self.mappings
+        self.read()
             .iter()
             .filter(|(k, _)| k.platform == *platform && k.app_id == app_id)
-            .max_by(|(a, _), (b, _)| {
-                // Parse as semver, fallback to lexicographic if parsing fails
-                match (Version::parse(&a.version), Version::parse(&b.version)) {
-                    (Ok(va), Ok(vb)) => va.cmp(&vb),
-                    (Ok(_), Err(_)) => std::cmp::Ordering::Greater, // Valid semver > invalid
-                    (Err(_), Ok(_)) => std::cmp::Ordering::Less,
-                    (Err(_), Err(_)) => a.version.cmp(&b.version), // Fallback to lexicographic
-                }
-            })
-            .map(|(_, v)| v)
+            .max_by(|(a, _), (b, _)| compare_versions(&a.version, &b.version))
+            .map(|(_, v)| v.clone())
     }
 
     /// Add a mapping file to the cache manually.
@@ -430,7 +749,7 @@ impl MappingStore {
     /// This method takes ownership of the `app_id` and `version` strings.
     /// If a mapping already exists for the same platform/app/version, it is replaced.
     pub fn add_mapping(
-        &mut self,
+        &self,
         platform: Platform,
         app_id: String,
         version: String,
@@ -443,6 +762,7 @@ impl MappingStore {
         };
 
         let info = MappingInfo {
+            files: vec![path.clone()],
             path,
             platform,
             app_id,
@@ -450,12 +770,12 @@ impl MappingStore {
             loaded_at: std::time::SystemTime::now(),
         };
 
-        self.mappings.insert(key, info);
+        self.write().insert(key, info);
     }
 
     /// List all loaded mappings.
     ///
-    /// Returns an iterator over all [`MappingInfo`] entries in the cache.
+    /// Returns a snapshot of all [`MappingInfo`] entries in the cache.
     /// Order is not guaranteed (depends on internal `HashMap` iteration).
     ///
     /// # Example
@@ -470,8 +790,8 @@ impl MappingStore {
     ///     );
     /// }
     /// ```
-    pub fn list(&self) -> impl Iterator<Item = &MappingInfo> {
-        self.mappings.values()
+    pub fn list(&self) -> Vec<MappingInfo> {
+        self.read().values().cloned().collect()
     }
 
     /// Get the expected filesystem path for a mapping file.
@@ -521,7 +841,7 @@ impl MappingStore {
     /// - Component contains `..` (parent directory reference)
     /// - Component contains `/` or `\` (path separators)
     /// - Component starts with `/` or `\` (absolute path attempt)
-    fn validate_path_component(component: &str, name: &str) -> Result<(), SymbolicationError> {
+    pub(crate) fn validate_path_component(component: &str, name: &str) -> Result<(), SymbolicationError> {
         if component.is_empty() {
             return Err(SymbolicationError::InvalidPath(format!(
                 "{} cannot be empty",
@@ -553,10 +873,36 @@ impl MappingStore {
         Ok(())
     }
 
+    /// Check a mapping file for problems before relying on it.
+    ///
+    /// Validates the file's format for `platform` (ProGuard/R8 grammar,
+    /// source map JSON and `sourcesContent`) and the name of the version
+    /// directory containing it. See [`MappingWarning`] for what's reported;
+    /// native debug files (dSYMs, PDBs, unstripped binaries) only get the
+    /// directory check.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(warnings)` - Problems found; empty if the mapping looks good
+    /// * `Err(SymbolicationError::IoError)` - The file could not be read
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// for warning in MappingStore::validate(&Platform::Android, &path)? {
+    ///     println!("{:?}: {}", warning.severity, warning.message);
+    /// }
+    /// ```
+    pub fn validate(platform: &Platform, path: &Path) -> Result<Vec<MappingWarning>, SymbolicationError> {
+        Ok(validate::validate_path(platform, path)?)
+    }
+
     /// Save a mapping file to the store with path validation.
     ///
     /// Writes the mapping file content to the appropriate location in the
-    /// directory hierarchy and adds it to the internal cache.
+    /// directory hierarchy and adds it to the internal cache. Text mappings
+    /// are gzipped and written as `<filename>.gz` (see the
+    /// [Compression](Self#compression) section).
     ///
     /// # Arguments
     ///
@@ -572,7 +918,7 @@ impl MappingStore {
     ///
     /// # Returns
     ///
-    /// * `Ok(PathBuf)` - Path where the file was written
+    /// * `Ok(PathBuf)` - Path where the file was written (with `.gz` if compressed)
     /// * `Err(SymbolicationError::InvalidPath)` - A path component failed validation
     /// * `Err(SymbolicationError::IoError)` - Failed to create directories or write file
     ///
@@ -606,7 +952,7 @@ impl MappingStore {
     /// store.save_mapping(Platform::Android, "app", "1.0", "/etc/passwd", b"")?; // Error
     /// ```
     pub fn save_mapping(
-        &mut self,
+        &self,
         platform: Platform,
         app_id: &str,
         version: &str,
@@ -619,27 +965,315 @@ impl MappingStore {
         Self::validate_path_component(version, "version")?;
         Self::validate_path_component(filename, "filename")?;
 
-        let path = self.mapping_path(&platform, app_id, version, filename);
+        let plain_path = self.mapping_path(&platform, app_id, version, filename);
+        let compressed_path = plain_path.with_file_name(format!("{}{}", filename, GZIP_SUFFIX));
 
         // Create parent directories
-        if let Some(parent) = path.parent() {
+        if let Some(parent) = plain_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        fs::write(&path, content)?;
+        // Already-compressed uploads are stored as they are
+        let path = if content.starts_with(&GZIP_MAGIC) || !Self::is_compressible(filename) {
+            self.store_deduplicated(&plain_path, content)?;
+            plain_path
+        } else {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(content)?;
+            self.store_deduplicated(&compressed_path, &encoder.finish()?)?;
+            // Don't leave an older uncompressed copy to shadow the new file
+            remove_if_exists(&plain_path)?;
+            compressed_path
+        };
 
-        // Add to cache
-        self.add_mapping(
+        // Add to cache, along with the version's other source maps
+        let key = MappingKey {
+            platform: platform.clone(),
+            app_id: app_id.to_string(),
+            version: version.to_string(),
+        };
+        let info = MappingInfo {
+            files: Self::mapping_files(&platform, &path),
+            path: path.clone(),
             platform,
-            app_id.to_string(),
-            version.to_string(),
-            path.clone(),
-        );
+            app_id: app_id.to_string(),
+            version: version.to_string(),
+            loaded_at: std::time::SystemTime::now(),
+        };
+        self.write().insert(key, info);
 
         Ok(path)
     }
 }
 
+impl MappingStore {
+    /// Write `content` to `path` as a link to its content-addressed copy.
+    fn store_deduplicated(&self, path: &Path, content: &[u8]) -> Result<(), SymbolicationError> {
+        let cas_dir = self.root.join(CAS_DIR);
+        fs::create_dir_all(&cas_dir)?;
+        let object = cas_dir.join(hex::encode(Sha256::digest(content)));
+
+        if !object.exists() {
+            // Never expose a partially written object to concurrent savers
+            let mut file = tempfile::NamedTempFile::new_in(&cas_dir)?;
+            file.write_all(content)?;
+            file.persist(&object).map_err(|e| SymbolicationError::IoError(e.error))?;
+        }
+
+        // Replace rather than overwrite: `path` may be linked to other versions
+        remove_if_exists(path)?;
+        if fs::hard_link(&object, path).is_err() {
+            fs::write(path, content)?;
+        }
+        Ok(())
+    }
+
+    /// Deduplicate the files already in the store.
+    ///
+    /// Every file under the version directories is hashed; the first copy
+    /// of each distinct content moves into `<root>/.cas/`, and identical
+    /// copies are replaced by hard links to it. Safe to run repeatedly.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DedupStats)` - How many files were linked and the space freed
+    /// * `Err(SymbolicationError::IoError)` - A file couldn't be read or linked
+    pub fn dedup(&self) -> Result<DedupStats, SymbolicationError> {
+        let cas_dir = self.root.join(CAS_DIR);
+        fs::create_dir_all(&cas_dir)?;
+
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.root)?.flatten() {
+            if !entry.file_name().to_string_lossy().starts_with('.') && entry.path().is_dir() {
+                collect_files(&entry.path(), &mut files)?;
+            }
+        }
+
+        let mut stats = DedupStats::default();
+        for path in files {
+            stats.files += 1;
+            let content = fs::read(&path)?;
+            let object = cas_dir.join(hex::encode(Sha256::digest(&content)));
+
+            if !object.exists() {
+                // First copy: it becomes the shared object
+                if fs::hard_link(&path, &object).is_err() {
+                    fs::write(&object, &content)?;
+                }
+                continue;
+            }
+            if is_same_file(&path, &object) {
+                continue;
+            }
+            if self.link_over(&object, &path).is_ok() {
+                stats.linked += 1;
+                stats.bytes_saved += content.len() as u64;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Remove the mappings of old versions.
+    ///
+    /// Deletes the whole version directory of each mapping selected by
+    /// `policy` (mapping file, native libraries, dSYMs) and drops it from the
+    /// index, then deletes content-store objects no version links to any
+    /// more. With `dry_run`, only reports what would be removed.
+    ///
+    /// Versions are grouped per platform and app, and ordered like
+    /// [`get_with_fallback()`](Self::get_with_fallback) orders them.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(removed)` - The mappings removed (or that would be), oldest first
+    /// * `Err(SymbolicationError::IoError)` - A directory couldn't be removed
+    pub fn prune(
+        &self,
+        policy: &PrunePolicy,
+        dry_run: bool,
+    ) -> Result<Vec<MappingInfo>, SymbolicationError> {
+        if policy.keep_latest.is_none() && policy.older_than.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let mut by_app: HashMap<(Platform, String), Vec<MappingInfo>> = HashMap::new();
+        for info in self.list() {
+            by_app
+                .entry((info.platform.clone(), info.app_id.clone()))
+                .or_default()
+                .push(info);
+        }
+
+        let mut removed = Vec::new();
+        for (_, mut versions) in by_app {
+            // Newest first
+            versions.sort_by(|a, b| compare_versions(&b.version, &a.version));
+            let keep = policy.keep_latest.unwrap_or(0);
+            for info in versions.into_iter().skip(keep) {
+                let dir = self.version_dir(&info);
+                if let Some(cutoff) = policy.older_than {
+                    let modified = fs::metadata(&dir).and_then(|m| m.modified());
+                    if !modified.is_ok_and(|modified| modified < cutoff) {
+                        continue;
+                    }
+                }
+                removed.push(info);
+            }
+        }
+        removed.sort_by(|a, b| {
+            (a.platform.as_str(), &a.app_id)
+                .cmp(&(b.platform.as_str(), &b.app_id))
+                .then_with(|| compare_versions(&a.version, &b.version))
+        });
+
+        if dry_run {
+            return Ok(removed);
+        }
+        for info in &removed {
+            self.remove_version(info)?;
+        }
+        self.collect_garbage()?;
+        Ok(removed)
+    }
+
+    /// Remove the mappings of one version.
+    ///
+    /// Deletes the version directory like [`prune()`](Self::prune) does and
+    /// drops it from the index.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(info))` - The mapping removed
+    /// * `Ok(None)` - No mapping is indexed for this version
+    /// * `Err(SymbolicationError::InvalidPath)` - A component is unsafe
+    /// * `Err(SymbolicationError::IoError)` - The directory couldn't be removed
+    pub fn remove(
+        &self,
+        platform: &Platform,
+        app_id: &str,
+        version: &str,
+    ) -> Result<Option<MappingInfo>, SymbolicationError> {
+        Self::validate_path_component(app_id, "app_id")?;
+        Self::validate_path_component(version, "version")?;
+
+        let key = MappingKey {
+            platform: platform.clone(),
+            app_id: app_id.to_string(),
+            version: version.to_string(),
+        };
+        let Some(info) = self.read().get(&key).cloned() else {
+            return Ok(None);
+        };
+        self.remove_version(&info)?;
+        self.collect_garbage()?;
+        Ok(Some(info))
+    }
+
+    /// Delete a mapping's version directory and drop it from the index.
+    fn remove_version(&self, info: &MappingInfo) -> Result<(), SymbolicationError> {
+        match fs::remove_dir_all(self.version_dir(info)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        self.write().remove(&MappingKey {
+            platform: info.platform.clone(),
+            app_id: info.app_id.clone(),
+            version: info.version.clone(),
+        });
+        Ok(())
+    }
+
+    /// Directory holding everything for a mapping's version.
+    fn version_dir(&self, info: &MappingInfo) -> PathBuf {
+        self.root
+            .join(info.platform.as_str())
+            .join(&info.app_id)
+            .join(&info.version)
+    }
+
+    /// Delete content-store objects no longer linked from any version.
+    #[cfg(unix)]
+    fn collect_garbage(&self) -> io::Result<()> {
+        use std::os::unix::fs::MetadataExt;
+        let Ok(entries) = fs::read_dir(self.root.join(CAS_DIR)) else {
+            return Ok(());
+        };
+        for entry in entries.flatten() {
+            if entry.metadata().is_ok_and(|m| m.is_file() && m.nlink() == 1) {
+                remove_if_exists(&entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Without link counts, objects are kept; they are only ever reused.
+    #[cfg(not(unix))]
+    fn collect_garbage(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Atomically replace `path` with a hard link to `object`.
+    fn link_over(&self, object: &Path, path: &Path) -> io::Result<()> {
+        let dir = path.parent().unwrap_or(&self.root);
+        let temp = tempfile::Builder::new().prefix(".link").tempfile_in(dir)?.into_temp_path();
+        // The placeholder only reserves a unique name
+        fs::remove_file(&temp)?;
+        fs::hard_link(object, &temp)?;
+        fs::rename(&temp, path)?;
+        // Already renamed away; nothing left to clean up
+        let _ = temp.keep();
+        Ok(())
+    }
+}
+
+/// Order version strings: semver where both parse, valid semver above
+/// anything else, and lexicographic otherwise.
+pub(crate) fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    match (Version::parse(a), Version::parse(b)) {
+        (Ok(va), Ok(vb)) => va.cmp(&vb),
+        (Ok(_), Err(_)) => std::cmp::Ordering::Greater, // Valid semver > invalid
+        (Err(_), Ok(_)) => std::cmp::Ordering::Less,
+        (Err(_), Err(_)) => a.cmp(b), // Fallback to lexicographic
+    }
+}
+
+/// Remove a file, treating "not found" as success.
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Recursively collect the regular files under `dir`.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(&entry.path(), files)?;
+        } else if file_type.is_file() {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Whether two paths are links to the same file.
+#[cfg(unix)]
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_same_file(_a: &Path, _b: &Path) -> bool {
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -655,13 +1289,189 @@ mod tests {
         fs::create_dir_all(&android_path).unwrap();
         fs::write(android_path.join("mapping.txt"), "# test mapping").unwrap();
 
-        let mut store = MappingStore::new(root);
+        let store = MappingStore::new(root);
         let count = store.scan().unwrap();
 
         assert_eq!(count, 1);
         assert!(store.get(&Platform::Android, "com.test.app", "1.0.0").is_some());
     }
 
+    #[test]
+    fn test_save_mapping_compresses_text() {
+        let dir = tempdir().unwrap();
+        let store = MappingStore::new(dir.path());
+        let content = "com.example.Foo -> a:\n".repeat(100);
+
+        let path = store
+            .save_mapping(Platform::Android, "com.test.app", "1.0.0", "mapping.txt", content.as_bytes())
+            .unwrap();
+
+        assert!(path.ends_with("android/com.test.app/1.0.0/mapping.txt.gz"));
+        assert!(fs::metadata(&path).unwrap().len() < content.len() as u64 / 4);
+        assert_eq!(MappingStore::read_file_to_string(&path).unwrap(), content);
+
+        // Found again by a fresh scan
+        let store = MappingStore::new(dir.path());
+        assert_eq!(store.scan().unwrap(), 1);
+        let info = store.get(&Platform::Android, "com.test.app", "1.0.0").unwrap();
+        assert_eq!(info.path, path);
+
+        // Uncompressed files are read as they are
+        let plain = dir.path().join("plain.txt");
+        fs::write(&plain, "plain").unwrap();
+        assert_eq!(MappingStore::read_file(&plain).unwrap(), b"plain");
+    }
+
+    #[test]
+    fn test_save_mapping_deduplicates() {
+        let dir = tempdir().unwrap();
+        let store = MappingStore::new(dir.path());
+        let content = b"{\"version\":3,\"sources\":[],\"names\":[],\"mappings\":\"\"}";
+
+        let first = store
+            .save_mapping(Platform::Electron, "app", "1.0.0", "main.js.map", content)
+            .unwrap();
+        let second = store
+            .save_mapping(Platform::Electron, "app", "1.0.1", "main.js.map", content)
+            .unwrap();
+
+        assert!(is_same_file(&first, &second) || cfg!(not(unix)));
+        assert_eq!(fs::read_dir(dir.path().join(CAS_DIR)).unwrap().count(), 1);
+
+        // Re-saving one version replaces its link instead of writing through it
+        store
+            .save_mapping(Platform::Electron, "app", "1.0.1", "main.js.map", b"{}")
+            .unwrap();
+        assert_eq!(MappingStore::read_file(&first).unwrap(), content);
+
+        // The content store is not a platform
+        assert_eq!(store.scan().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_dedup_existing_files() {
+        let dir = tempdir().unwrap();
+        for version in ["1.0.0", "1.0.1", "1.0.2"] {
+            let version_dir = dir.path().join("android/app").join(version);
+            fs::create_dir_all(&version_dir).unwrap();
+            fs::write(version_dir.join("mapping.txt"), "a -> b:\n").unwrap();
+        }
+        let store = MappingStore::new(dir.path());
+
+        let stats = store.dedup().unwrap();
+        assert_eq!(stats.files, 3);
+        if cfg!(unix) {
+            assert_eq!(stats.linked, 2);
+            assert_eq!(stats.bytes_saved, 16);
+        }
+        assert_eq!(
+            fs::read_to_string(dir.path().join("android/app/1.0.2/mapping.txt")).unwrap(),
+            "a -> b:\n"
+        );
+
+        // Nothing left to do
+        assert_eq!(store.dedup().unwrap().linked, 0);
+    }
+
+    #[test]
+    fn test_prune() {
+        let dir = tempdir().unwrap();
+        let store = MappingStore::new(dir.path());
+        for version in ["1.0.0", "1.2.0", "1.10.0", "2.0.0"] {
+            store
+                .save_mapping(Platform::Android, "app", version, "mapping.txt", version.as_bytes())
+                .unwrap();
+        }
+        store
+            .save_mapping(Platform::Android, "other", "0.1.0", "mapping.txt", b"other")
+            .unwrap();
+
+        let keep_two = PrunePolicy {
+            keep_latest: Some(2),
+            older_than: None,
+        };
+        let versions = |removed: Vec<MappingInfo>| -> Vec<String> {
+            removed.into_iter().map(|info| info.version).collect()
+        };
+
+        // Dry run leaves everything in place
+        assert_eq!(
+            versions(store.prune(&keep_two, true).unwrap()),
+            ["1.0.0", "1.2.0"]
+        );
+        assert!(store.get(&Platform::Android, "app", "1.0.0").is_some());
+
+        // Nothing is old enough
+        let old = PrunePolicy {
+            keep_latest: Some(2),
+            older_than: Some(SystemTime::now() - std::time::Duration::from_secs(3600)),
+        };
+        assert!(store.prune(&old, false).unwrap().is_empty());
+
+        assert_eq!(
+            versions(store.prune(&keep_two, false).unwrap()),
+            ["1.0.0", "1.2.0"]
+        );
+        assert!(store.get(&Platform::Android, "app", "1.0.0").is_none());
+        assert!(!dir.path().join("android/app/1.0.0").exists());
+        assert!(store.get(&Platform::Android, "app", "1.10.0").is_some());
+        assert!(store.get(&Platform::Android, "other", "0.1.0").is_some());
+        if cfg!(unix) {
+            assert_eq!(fs::read_dir(dir.path().join(CAS_DIR)).unwrap().count(), 3);
+        }
+
+        // No criteria, no pruning
+        assert!(store.prune(&PrunePolicy::default(), false).unwrap().is_empty());
+
+        let removed = store.remove(&Platform::Android, "other", "0.1.0").unwrap();
+        assert_eq!(removed.unwrap().app_id, "other");
+        assert!(!dir.path().join("android/other/0.1.0").exists());
+        assert!(store.remove(&Platform::Android, "other", "0.1.0").unwrap().is_none());
+        assert!(matches!(
+            store.remove(&Platform::Android, "..", "0.1.0"),
+            Err(SymbolicationError::InvalidPath(_))
+        ));
+        if cfg!(unix) {
+            assert_eq!(fs::read_dir(dir.path().join(CAS_DIR)).unwrap().count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_refresh_version_directory() {
+        let dir = tempdir().unwrap();
+        let store = MappingStore::new(dir.path());
+        assert_eq!(store.scan().unwrap(), 0);
+
+        let version_dir = dir.path().join("android/com.test.app/1.0.0");
+        fs::create_dir_all(&version_dir).unwrap();
+        fs::write(version_dir.join("mapping.txt"), "# test mapping").unwrap();
+
+        let key = MappingKey {
+            platform: Platform::Android,
+            app_id: "com.test.app".to_string(),
+            version: "1.0.0".to_string(),
+        };
+        assert_eq!(
+            store.refresh(&version_dir.join("mapping.txt")).unwrap(),
+            Some(MappingChange::Updated(key.clone()))
+        );
+        assert!(store.get(&Platform::Android, "com.test.app", "1.0.0").is_some());
+
+        fs::remove_dir_all(&version_dir).unwrap();
+        assert_eq!(
+            store.refresh(&version_dir.join("mapping.txt")).unwrap(),
+            Some(MappingChange::Removed(key))
+        );
+        assert!(store.get(&Platform::Android, "com.test.app", "1.0.0").is_none());
+
+        // Outside the root, or above the version level
+        assert_eq!(store.refresh(Path::new("/elsewhere/mapping.txt")).unwrap(), None);
+        assert_eq!(
+            store.refresh(&dir.path().join("android")).unwrap(),
+            Some(MappingChange::Rescanned(0))
+        );
+    }
+
     #[test]
     fn test_validate_path_component_rejects_parent_traversal() {
         assert!(MappingStore::validate_path_component("..", "test").is_err());
@@ -697,7 +1507,7 @@ mod tests {
     #[test]
     fn test_save_mapping_validates_paths() {
         let dir = tempdir().unwrap();
-        let mut store = MappingStore::new(dir.path());
+        let store = MappingStore::new(dir.path());
 
         // Valid save should succeed
         let result = store.save_mapping(
@@ -739,4 +1549,31 @@ mod tests {
         );
         assert!(matches!(result, Err(SymbolicationError::InvalidPath(_))));
     }
+
+    #[test]
+    fn test_concurrent_lookups_during_rescan() {
+        let dir = tempdir().unwrap();
+        let store = std::sync::Arc::new(MappingStore::new(dir.path()));
+        store
+            .save_mapping(Platform::Android, "com.test.app", "1.0.0", "mapping.txt", b"# test")
+            .unwrap();
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let store = std::sync::Arc::clone(&store);
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        // A rescan never exposes an empty index
+                        assert!(store.get(&Platform::Android, "com.test.app", "1.0.0").is_some());
+                    }
+                })
+            })
+            .collect();
+        for _ in 0..20 {
+            assert_eq!(store.scan().unwrap(), 1);
+        }
+        for reader in readers {
+            reader.join().unwrap();
+        }
+    }
 }
@@ -0,0 +1,283 @@
+//! Unity/IL2CPP symbolication.
+//!
+//! IL2CPP compiles C# to C++, so native crash frames name generated C++
+//! functions (`PlayerController_Update_m3A5B1C2D`) in generated files
+//! (`Bulk_Assembly-CSharp_0.cpp`). The IL2CPP build writes two mapping files
+//! under `Il2CppOutputProject/Source/il2cppOutput/Symbols/` that map them
+//! back to C#:
+//!
+//! ```text
+//! MethodMap.tsv               # <cpp method>\t<C# method>\t<assembly>
+//! LineNumberMappings.json     # {"<cpp file>": {"<C# file>": {"<cpp line>": <C# line>}}}
+//! ```
+//!
+//! Frames are resolved with whichever of the two is available.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use regex::Regex;
+
+use super::cache::{load_mapping, MappingCache};
+use super::{
+    MappingStore, PlatformSymbolicator, SymbolicatedFrame, SymbolicatedStack, SymbolicationContext,
+    SymbolicationError, SCHEMA_VERSION,
+};
+
+/// File name of the IL2CPP method map.
+const METHOD_MAP_FILE: &str = "MethodMap.tsv";
+/// File name of the IL2CPP line number mappings.
+const LINE_MAPPINGS_FILE: &str = "LineNumberMappings.json";
+
+/// Parsed IL2CPP mapping files.
+#[derive(Debug, Default)]
+struct Il2CppMappings {
+    /// Generated C++ method name -> C# method name.
+    methods: HashMap<String, String>,
+    /// C++ file name -> C++ line records `(cpp line, C# file, C# line)`
+    /// sorted by C++ line.
+    lines: HashMap<String, Vec<(u32, String, u32)>>,
+}
+
+impl Il2CppMappings {
+    /// Load the mapping files present in a directory.
+    fn load(dir: &Path) -> Result<Self, SymbolicationError> {
+        let mut mappings = Self::default();
+
+        if let Some(method_map) = MappingStore::find_file(dir, METHOD_MAP_FILE) {
+            mappings.parse_method_map(&MappingStore::read_file_to_string(&method_map)?);
+        }
+
+        if let Some(line_mappings) = MappingStore::find_file(dir, LINE_MAPPINGS_FILE) {
+            mappings.parse_line_mappings(&MappingStore::read_file_to_string(&line_mappings)?)?;
+        }
+
+        Ok(mappings)
+    }
+
+    /// Parse `MethodMap.tsv`: `<cpp method>\t<C# method>[\t<assembly>]`.
+    fn parse_method_map(&mut self, content: &str) {
+        for line in content.lines() {
+            let mut columns = line.split('\t');
+            if let (Some(cpp), Some(managed)) = (columns.next(), columns.next()) {
+                let (cpp, managed) = (cpp.trim(), managed.trim());
+                if !cpp.is_empty() && !managed.is_empty() {
+                    self.methods.insert(cpp.to_string(), managed.to_string());
+                }
+            }
+        }
+    }
+
+    /// Parse `LineNumberMappings.json`.
+    fn parse_line_mappings(&mut self, content: &str) -> Result<(), SymbolicationError> {
+        let parsed: HashMap<String, HashMap<String, HashMap<String, u32>>> =
+            serde_json::from_str(content).map_err(|e| {
+                SymbolicationError::ParseError(format!("Invalid {}: {}", LINE_MAPPINGS_FILE, e))
+            })?;
+
+        for (cpp_file, sources) in parsed {
+            let mut records: Vec<(u32, String, u32)> = sources
+                .into_iter()
+                .flat_map(|(cs_file, lines)| {
+                    lines.into_iter().filter_map(move |(cpp_line, cs_line)| {
+                        Some((cpp_line.parse().ok()?, cs_file.clone(), cs_line))
+                    })
+                })
+                .collect();
+            records.sort_by_key(|(cpp_line, _, _)| *cpp_line);
+            self.lines.insert(file_name(&cpp_file).to_string(), records);
+        }
+
+        Ok(())
+    }
+
+    /// Map a generated C++ location to the nearest preceding C# line.
+    fn map_line(&self, cpp_file: &str, cpp_line: u32) -> Option<(&str, u32)> {
+        let records = self.lines.get(file_name(cpp_file))?;
+        let index = records.partition_point(|(line, _, _)| *line <= cpp_line);
+        index
+            .checked_sub(1)
+            .map(|i| (records[i].1.as_str(), records[i].2))
+    }
+}
+
+/// Final path component, accepting both `/` and `\` separators.
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// Unity/IL2CPP stack trace symbolicator.
+pub struct UnitySymbolicator<'a> {
+    store: &'a MappingStore,
+    cache: Option<&'a MappingCache>,
+}
+
+impl<'a> UnitySymbolicator<'a> {
+    /// Create a new Unity symbolicator.
+    pub fn new(store: &'a MappingStore) -> Self {
+        Self { store, cache: None }
+    }
+
+    /// Reuse parsed mappings from `cache` instead of re-reading them.
+    pub fn with_cache(mut self, cache: &'a MappingCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Symbolicate a Unity IL2CPP stack trace.
+    pub fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        let mapping_info = self
+            .store
+            .get_with_fallback(
+                &context.platform,
+                context.app_id.as_deref().unwrap_or("unknown"),
+                context.version.as_deref().unwrap_or("unknown"),
+            )
+            .ok_or_else(|| SymbolicationError::MappingNotFound {
+                platform: "unity".to_string(),
+                app_id: context.app_id.clone().unwrap_or_default(),
+                version: context.version.clone().unwrap_or_default(),
+            })?;
+
+        // Both files come from the same IL2CPP build, so they are cached
+        // together under the primary mapping file
+        let mappings = load_mapping(self.cache, &mapping_info, &mapping_info.path, |path| {
+            match path.parent() {
+                Some(dir) => Il2CppMappings::load(dir),
+                None => Ok(Il2CppMappings::default()),
+            }
+        })?;
+
+        Ok(self.symbolicate_with(stack_trace, &mappings))
+    }
+
+    fn symbolicate_with(&self, stack_trace: &str, mappings: &Il2CppMappings) -> SymbolicatedStack {
+        // Generated method names end in "_m" + 8+ hex digits of a hash:
+        //   #00 pc 0012a3b4  /.../libil2cpp.so (PlayerController_Update_m3A5B1C2D+20)
+        //   3   UnityFramework  0x0000000104a1c3d8 PlayerController_Update_m3A5B1C2D + 20
+        let method_re = Regex::new(r"\b([A-Za-z_][A-Za-z0-9_]*_m[0-9A-F]{8,})\b").unwrap();
+        // Generated source location: "Bulk_Assembly-CSharp_0.cpp:1234"
+        let location_re = Regex::new(r"([A-Za-z0-9_\-./\\]+\.cpp):(\d+)").unwrap();
+
+        let mut frames = Vec::new();
+
+        for line in stack_trace.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let method = method_re
+                .captures(line)
+                .and_then(|caps| mappings.methods.get(&caps[1]));
+            let location = location_re.captures(line).and_then(|caps| {
+                let cpp_line = caps[2].parse().ok()?;
+                mappings.map_line(&caps[1], cpp_line)
+            });
+
+            match (method, location) {
+                (None, None) => frames.push(SymbolicatedFrame::raw(line.to_string())),
+                (method, location) => frames.push(SymbolicatedFrame::symbolicated(
+                    line.to_string(),
+                    method
+                        .cloned()
+                        .unwrap_or_else(|| "<unknown>".to_string()),
+                    location.map(|(file, _)| file.to_string()),
+                    location.map(|(_, line)| line),
+                    None,
+                )),
+            }
+        }
+
+        let symbolicated_count = frames.iter().filter(|f| f.symbolicated).count();
+        let total_count = frames.len();
+
+        SymbolicatedStack {
+            schema_version: SCHEMA_VERSION,
+            exceptions: Vec::new(),
+            raw: stack_trace.to_string(),
+            frames,
+            symbolicated_count,
+            total_count,
+        }
+    }
+}
+
+impl PlatformSymbolicator for UnitySymbolicator<'_> {
+    fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        UnitySymbolicator::symbolicate(self, stack_trace, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_mappings() -> Il2CppMappings {
+        let mut mappings = Il2CppMappings::default();
+        mappings.parse_method_map(
+            "PlayerController_Update_m3A5B1C2D\tPlayerController.Update()\tAssembly-CSharp\n\
+             Enemy_Die_m0F1E2D3C\tEnemy.Die()\tAssembly-CSharp\n",
+        );
+        mappings
+            .parse_line_mappings(
+                r#"{"C:\\Build\\il2cppOutput\\Bulk_Assembly-CSharp_0.cpp": {
+                    "Assets/Scripts/PlayerController.cs": {"1200": 40, "1210": 42},
+                    "Assets/Scripts/Enemy.cs": {"1500": 17}
+                }}"#,
+            )
+            .unwrap();
+        mappings
+    }
+
+    #[test]
+    fn test_map_line_uses_nearest_preceding_record() {
+        let mappings = sample_mappings();
+        assert_eq!(
+            mappings.map_line("Bulk_Assembly-CSharp_0.cpp", 1215),
+            Some(("Assets/Scripts/PlayerController.cs", 42))
+        );
+        assert_eq!(
+            mappings.map_line("Bulk_Assembly-CSharp_0.cpp", 1501),
+            Some(("Assets/Scripts/Enemy.cs", 17))
+        );
+        assert_eq!(mappings.map_line("Bulk_Assembly-CSharp_0.cpp", 10), None);
+        assert_eq!(mappings.map_line("Other.cpp", 1215), None);
+    }
+
+    #[test]
+    fn test_symbolicate_il2cpp_frames() {
+        let store = MappingStore::new("/nonexistent");
+        let sym = UnitySymbolicator::new(&store);
+        let trace = "\
+#00 pc 0012a3b4  /data/app/com.example/lib/arm64/libil2cpp.so (PlayerController_Update_m3A5B1C2D+20)
+#01 pc 0012b000  /data/app/com.example/lib/arm64/libil2cpp.so (Enemy_Die_m0F1E2D3C+8) (Bulk_Assembly-CSharp_0.cpp:1502)
+#02 pc 00054321  /data/app/com.example/lib/arm64/libunity.so";
+
+        let result = sym.symbolicate_with(trace, &sample_mappings());
+        assert_eq!(result.symbolicated_count, 2);
+        assert_eq!(result.frames[0].display(), "PlayerController.Update()");
+        assert_eq!(
+            result.frames[1].display(),
+            "Enemy.Die() (Assets/Scripts/Enemy.cs:17)"
+        );
+        assert!(!result.frames[2].symbolicated);
+    }
+
+    #[test]
+    fn test_invalid_line_mappings() {
+        let mut mappings = Il2CppMappings::default();
+        assert!(matches!(
+            mappings.parse_line_mappings("[1, 2]"),
+            Err(SymbolicationError::ParseError(_))
+        ));
+    }
+}
@@ -0,0 +1,419 @@
+//! Mapping file validation.
+//!
+//! A mapping that doesn't parse only shows up later as crashes that fail to
+//! symbolicate. [`MappingStore::validate`](super::MappingStore::validate)
+//! checks a file up front and reports [`MappingWarning`]s:
+//!
+//! - ProGuard/R8 files follow the `class -> obfuscated:` / indented member
+//!   grammar
+//! - Source maps are valid JSON, version 3, and decode
+//! - Source maps embed `sourcesContent` (needed to show source context)
+//! - Python path mappings have a `paths` object of prefixes
+//! - The version directory name matches what crash reports send
+//!
+//! Errors mean the file won't be used for symbolication; warnings mean it
+//! will, with reduced results.
+
+use std::path::Path;
+
+use regex::Regex;
+use semver::Version;
+use serde::Serialize;
+
+use super::android::is_native_lib;
+use super::python::PathMapping;
+use super::{MappingStore, Platform};
+
+/// ProGuard syntax errors reported individually before summarizing the rest.
+const MAX_SYNTAX_ERRORS: usize = 10;
+
+/// How serious a [`MappingWarning`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The mapping can't be used.
+    Error,
+    /// The mapping works, with reduced results.
+    Warning,
+}
+
+/// What a [`MappingWarning`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MappingWarningKind {
+    /// The file is empty.
+    Empty,
+    /// A ProGuard/R8 line doesn't follow the mapping grammar.
+    ProguardSyntax,
+    /// A source map isn't valid JSON or can't be decoded.
+    InvalidSourceMap,
+    /// A source map has no embedded `sourcesContent`.
+    MissingSourcesContent,
+    /// A Python path mapping isn't valid.
+    InvalidPathMapping,
+    /// The version directory name is unusual.
+    VersionName,
+}
+
+/// One problem found in a mapping file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MappingWarning {
+    pub severity: Severity,
+    pub kind: MappingWarningKind,
+    /// 1-based line in the file, for line-oriented formats.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl MappingWarning {
+    fn error(kind: MappingWarningKind, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            kind,
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    fn warning(kind: MappingWarningKind, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            kind,
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// Whether the mapping can't be used.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// Check the contents of a mapping file named `filename`.
+pub(crate) fn validate_content(
+    platform: &Platform,
+    filename: &str,
+    content: &[u8],
+) -> Vec<MappingWarning> {
+    if content.iter().all(u8::is_ascii_whitespace) {
+        return vec![MappingWarning::error(
+            MappingWarningKind::Empty,
+            "File is empty",
+        )];
+    }
+
+    let extension = Path::new(filename)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match platform {
+        Platform::Android if extension == "txt" => {
+            validate_proguard(&String::from_utf8_lossy(content))
+        }
+        Platform::Python if extension == "json" => validate_python_mapping(content),
+        Platform::Electron | Platform::ReactNative => validate_source_map(content),
+        _ if extension == "map" => validate_source_map(content),
+        _ => Vec::new(),
+    }
+}
+
+/// Check a version directory name.
+pub(crate) fn validate_version(version: &str) -> Vec<MappingWarning> {
+    if let Err(e) = MappingStore::validate_path_component(version, "version") {
+        return vec![MappingWarning::error(
+            MappingWarningKind::VersionName,
+            e.to_string(),
+        )];
+    }
+
+    let mut warnings = Vec::new();
+    if version.chars().any(char::is_whitespace) {
+        warnings.push(MappingWarning::warning(
+            MappingWarningKind::VersionName,
+            format!("Version '{}' contains whitespace", version),
+        ));
+    }
+    if let Some(stripped) = version.strip_prefix(['v', 'V']) {
+        if Version::parse(stripped).is_ok() {
+            warnings.push(MappingWarning::warning(
+                MappingWarningKind::VersionName,
+                format!(
+                    "Version '{}' has a 'v' prefix; crash reports usually send '{}'",
+                    version, stripped
+                ),
+            ));
+            return warnings;
+        }
+    }
+    if Version::parse(version).is_err() {
+        warnings.push(MappingWarning::warning(
+            MappingWarningKind::VersionName,
+            format!(
+                "Version '{}' is not semantic versioning; fallback to the newest version compares it as text",
+                version
+            ),
+        ));
+    }
+    warnings
+}
+
+/// Check the ProGuard/R8 mapping grammar line by line.
+fn validate_proguard(content: &str) -> Vec<MappingWarning> {
+    let class_re = Regex::new(r"^\S+\s+->\s+\S+:$").unwrap();
+    // Methods (with optional line ranges) and fields
+    let member_re =
+        Regex::new(r"^\s+(?:\d+:\d+:)?\S+\s+[^\s(]+(?:\([^)]*\)(?::\d+(?::\d+)?)?)?\s+->\s+\S+$")
+            .unwrap();
+
+    let mut warnings = Vec::new();
+    let mut errors = 0;
+    let mut in_class = false;
+    let mut classes = 0;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let problem = if class_re.is_match(line) {
+            in_class = true;
+            classes += 1;
+            None
+        } else if member_re.is_match(line) {
+            (!in_class).then_some("Member mapping before any class mapping")
+        } else {
+            Some("Not a class or member mapping")
+        };
+
+        if let Some(problem) = problem {
+            errors += 1;
+            if errors <= MAX_SYNTAX_ERRORS {
+                warnings.push(
+                    MappingWarning::error(
+                        MappingWarningKind::ProguardSyntax,
+                        format!("{}: {}", problem, trimmed),
+                    )
+                    .at_line(index + 1),
+                );
+            }
+        }
+    }
+
+    if errors > MAX_SYNTAX_ERRORS {
+        warnings.push(MappingWarning::error(
+            MappingWarningKind::ProguardSyntax,
+            format!("{} more invalid lines", errors - MAX_SYNTAX_ERRORS),
+        ));
+    }
+    if classes == 0 && errors == 0 {
+        warnings.push(MappingWarning::error(
+            MappingWarningKind::ProguardSyntax,
+            "No class mappings found",
+        ));
+    }
+    warnings
+}
+
+/// Check that a source map is valid JSON, decodes, and embeds its sources.
+fn validate_source_map(content: &[u8]) -> Vec<MappingWarning> {
+    let json: serde_json::Value = match serde_json::from_slice(content) {
+        Ok(json) => json,
+        Err(e) => {
+            return vec![MappingWarning::error(
+                MappingWarningKind::InvalidSourceMap,
+                format!("Invalid JSON: {}", e),
+            )
+            .at_line(e.line())]
+        }
+    };
+
+    let mut warnings = Vec::new();
+    if json.get("version").and_then(|v| v.as_u64()) != Some(3) {
+        warnings.push(MappingWarning::error(
+            MappingWarningKind::InvalidSourceMap,
+            "Source map version must be 3",
+        ));
+    }
+    if let Err(e) = sourcemap::DecodedMap::from_reader(content) {
+        warnings.push(MappingWarning::error(
+            MappingWarningKind::InvalidSourceMap,
+            format!("Source map can't be decoded: {}", e),
+        ));
+        return warnings;
+    }
+
+    // Index maps embed their sources per section
+    let maps: Vec<&serde_json::Value> = match json.get("sections").and_then(|s| s.as_array()) {
+        Some(sections) => sections.iter().filter_map(|s| s.get("map")).collect(),
+        None => vec![&json],
+    };
+    let has_content = maps.iter().any(|map| {
+        map.get("sourcesContent")
+            .and_then(|c| c.as_array())
+            .is_some_and(|contents| contents.iter().any(|c| c.is_string()))
+    });
+    if !has_content {
+        warnings.push(MappingWarning::warning(
+            MappingWarningKind::MissingSourcesContent,
+            "No sourcesContent; original source lines can't be shown",
+        ));
+    }
+    warnings
+}
+
+/// Check that a Python path mapping parses.
+fn validate_python_mapping(content: &[u8]) -> Vec<MappingWarning> {
+    match PathMapping::parse(content) {
+        Ok(_) => Vec::new(),
+        Err(e) => vec![MappingWarning::error(
+            MappingWarningKind::InvalidPathMapping,
+            e.to_string(),
+        )],
+    }
+}
+
+/// Check a mapping file or bundle on disk, including its version directory.
+pub(crate) fn validate_path(
+    platform: &Platform,
+    path: &Path,
+) -> std::io::Result<Vec<MappingWarning>> {
+    let mut warnings = Vec::new();
+    if let Some(version) = path.parent().and_then(|p| p.file_name()) {
+        warnings.extend(validate_version(&version.to_string_lossy()));
+    }
+
+    // dSYM bundles and native binaries are checked when they are opened
+    if path.is_dir() || is_native_lib(path) {
+        return Ok(warnings);
+    }
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if ["pdb", "dwarf", "debug", "symbols"].contains(&extension.as_str()) {
+        return Ok(warnings);
+    }
+
+    let content = MappingStore::read_file(path)?;
+    let filename = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    // Stored compressed as `<filename>.gz`
+    let filename = filename.strip_suffix(".gz").unwrap_or(&filename);
+    warnings.extend(validate_content(platform, filename, &content));
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(warnings: &[MappingWarning]) -> Vec<(Severity, MappingWarningKind, Option<usize>)> {
+        warnings
+            .iter()
+            .map(|w| (w.severity, w.kind, w.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_proguard() {
+        let valid = "\
+# compiler: R8
+com.example.MainActivity -> a.b:
+    int count -> a
+    1:5:void onCreate(android.os.Bundle):10:14 -> a
+    void helper() -> b
+";
+        assert!(validate_content(&Platform::Android, "mapping.txt", valid.as_bytes()).is_empty());
+
+        let invalid = "    void orphan() -> a\ncom.example.Foo -> a:\ngarbage\n";
+        assert_eq!(
+            kinds(&validate_content(
+                &Platform::Android,
+                "mapping.txt",
+                invalid.as_bytes()
+            )),
+            [
+                (Severity::Error, MappingWarningKind::ProguardSyntax, Some(1)),
+                (Severity::Error, MappingWarningKind::ProguardSyntax, Some(3)),
+            ]
+        );
+        assert_eq!(
+            kinds(&validate_content(
+                &Platform::Android,
+                "mapping.txt",
+                b"  \n"
+            )),
+            [(Severity::Error, MappingWarningKind::Empty, None)]
+        );
+    }
+
+    #[test]
+    fn test_validate_source_map() {
+        let with_content = br#"{"version":3,"sources":["a.js"],"sourcesContent":["x"],"names":[],"mappings":"AAAA"}"#;
+        assert!(validate_content(&Platform::Electron, "main.js.map", with_content).is_empty());
+
+        let without_content = br#"{"version":3,"sources":["a.js"],"names":[],"mappings":"AAAA"}"#;
+        assert_eq!(
+            kinds(&validate_content(
+                &Platform::Electron,
+                "main.js.map",
+                without_content
+            )),
+            [(
+                Severity::Warning,
+                MappingWarningKind::MissingSourcesContent,
+                None
+            )]
+        );
+
+        let warnings = validate_content(&Platform::Electron, "main.js.map", b"{\n\"version\": 3,");
+        assert_eq!(
+            kinds(&warnings),
+            [(
+                Severity::Error,
+                MappingWarningKind::InvalidSourceMap,
+                Some(2)
+            )]
+        );
+        assert!(warnings[0].is_error());
+    }
+
+    #[test]
+    fn test_validate_python_mapping() {
+        let valid = br#"{"paths": {"/tmp/_MEI*/": "src/"}}"#;
+        assert!(validate_content(&Platform::Python, "mapping.json", valid).is_empty());
+        assert_eq!(
+            kinds(&validate_content(
+                &Platform::Python,
+                "mapping.json",
+                br#"{"paths": ["src/"]}"#
+            )),
+            [(Severity::Error, MappingWarningKind::InvalidPathMapping, None)]
+        );
+    }
+
+    #[test]
+    fn test_validate_version() {
+        assert!(validate_version("1.2.0").is_empty());
+        assert_eq!(
+            kinds(&validate_version("v1.2.0")),
+            [(Severity::Warning, MappingWarningKind::VersionName, None)]
+        );
+        assert_eq!(
+            kinds(&validate_version("nightly")),
+            [(Severity::Warning, MappingWarningKind::VersionName, None)]
+        );
+        assert!(validate_version("..")[0].is_error());
+    }
+}
@@ -0,0 +1,178 @@
+//! Filesystem watcher for the mapping directory.
+//!
+//! A long-running `bugstr serve` scans the mapping directory once at start.
+//! [`MappingWatcher`] keeps the [`MappingStore`] index current afterwards:
+//! when CI drops a new mapping in (or replaces or deletes one), only the
+//! affected version directory is re-indexed with [`MappingStore::refresh`].
+//! Parsed mappings don't need invalidating here; the
+//! [`MappingCache`](super::MappingCache) already keys entries by modification
+//! time.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::{MappingChange, SymbolicationError, Symbolicator};
+
+/// How long a directory must be quiet before its events are applied, so
+/// files still being written are not indexed half-way.
+const SETTLE_DELAY: Duration = Duration::from_millis(500);
+
+/// Watches a symbolicator's mapping directory and keeps its store current.
+///
+/// Watching stops when the `MappingWatcher` is dropped.
+pub struct MappingWatcher {
+    watcher: Option<RecommendedWatcher>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MappingWatcher {
+    /// Start watching the mapping directory of `symbolicator`.
+    ///
+    /// `on_change` is called from the watcher thread for each index update,
+    /// or with the error if re-indexing failed.
+    ///
+    /// # Errors
+    ///
+    /// - [`SymbolicationError::IoError`] - The mapping directory doesn't exist
+    ///   or can't be watched
+    pub fn start<F>(
+        symbolicator: Arc<Symbolicator>,
+        mut on_change: F,
+    ) -> Result<Self, SymbolicationError>
+    where
+        F: FnMut(Result<MappingChange, SymbolicationError>) + Send + 'static,
+    {
+        // Events carry absolute paths; the store may have a relative root
+        let root = fs::canonicalize(symbolicator.store().root())?;
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(watch_error)?;
+
+        let thread = std::thread::spawn(move || {
+            let store = symbolicator.store();
+            let mut pending: BTreeSet<PathBuf> = BTreeSet::new();
+            loop {
+                let timeout = if pending.is_empty() {
+                    Duration::from_secs(3600)
+                } else {
+                    SETTLE_DELAY
+                };
+                match rx.recv_timeout(timeout) {
+                    Ok(Ok(event)) => {
+                        if matches!(
+                            event.kind,
+                            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                        ) {
+                            pending.extend(
+                                event
+                                    .paths
+                                    .iter()
+                                    .filter_map(|path| version_dir(&root, path))
+                                    .map(|relative| store.root().join(relative)),
+                            );
+                        }
+                    }
+                    Ok(Err(e)) => on_change(Err(watch_error(e))),
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        for path in std::mem::take(&mut pending) {
+                            match store.refresh(&path) {
+                                Ok(Some(change)) => on_change(Ok(change)),
+                                Ok(None) => {}
+                                Err(e) => on_change(Err(e)),
+                            }
+                        }
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+
+        Ok(Self {
+            watcher: Some(watcher),
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for MappingWatcher {
+    fn drop(&mut self) {
+        // Dropping the notify watcher closes the channel and ends the thread
+        self.watcher.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The part of `path` below `root`, truncated to the version directory
+/// (`<platform>/<app>/<version>`) so events inside one version collapse
+/// into a single refresh. `None` for paths outside `root`.
+fn version_dir(root: &Path, path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(relative.iter().take(3).collect())
+}
+
+fn watch_error(e: notify::Error) -> SymbolicationError {
+    SymbolicationError::IoError(std::io::Error::other(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbolication::{MappingStore, Platform};
+    use std::time::Instant;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_version_dir() {
+        let root = Path::new("/m");
+        assert_eq!(
+            version_dir(root, Path::new("/m/android/app/1.0/arm64-v8a/libfoo.so")),
+            Some(PathBuf::from("android/app/1.0"))
+        );
+        assert_eq!(
+            version_dir(root, Path::new("/m/android")),
+            Some(PathBuf::from("android"))
+        );
+        assert_eq!(version_dir(root, Path::new("/elsewhere/x")), None);
+    }
+
+    #[test]
+    fn test_picks_up_new_mapping() {
+        let dir = tempdir().unwrap();
+        let store = MappingStore::new(dir.path());
+        store.scan().unwrap();
+        let symbolicator = Arc::new(Symbolicator::new(store));
+
+        let (tx, rx) = mpsc::channel();
+        let _watcher = MappingWatcher::start(Arc::clone(&symbolicator), move |change| {
+            let _ = tx.send(change.unwrap());
+        })
+        .unwrap();
+
+        let version_dir = dir.path().join("android/com.test.app/1.0.0");
+        fs::create_dir_all(&version_dir).unwrap();
+        fs::write(version_dir.join("mapping.txt"), "# test mapping").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while symbolicator
+            .store()
+            .get(&Platform::Android, "com.test.app", "1.0.0")
+            .is_none()
+        {
+            assert!(Instant::now() < deadline, "mapping not picked up");
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(rx.recv_timeout(Duration::from_secs(1)).is_ok());
+    }
+}
@@ -0,0 +1,416 @@
+//! Windows symbolication using PDB files.
+//!
+//! Resolves `module+offset` frames from Windows native crashes (Win32 apps,
+//! Electron's main/renderer processes) against the PDB files uploaded for
+//! the app version.
+//!
+//! # Frame Formats
+//!
+//! ```text
+//! 0  MyApp.exe + 0x1a2b3
+//! 1  MyApp.dll+0x4c10
+//! MyApp!0x1a2b3
+//! ```
+//!
+//! Offsets are relative virtual addresses (RVAs) from the module's load
+//! address, so they are independent of ASLR.
+//!
+//! # Mapping Layout
+//!
+//! ```text
+//! windows/<app_id>/<version>/
+//!   MyApp.pdb          # matches MyApp.exe
+//!   electron.exe.pdb   # matches electron.exe
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use pdb::{FallibleIterator, SymbolData, PDB};
+use regex::Regex;
+
+use super::cache::{load_mapping, MappingCache};
+use super::native::NativeLocation;
+use super::{
+    MappingStore, PlatformSymbolicator, SymbolicatedFrame, SymbolicatedStack, SymbolicationContext,
+    SymbolicationError, SCHEMA_VERSION,
+};
+
+/// A procedure and its line table.
+#[derive(Debug, Clone)]
+struct Procedure {
+    rva: u32,
+    len: u32,
+    name: String,
+    /// Line records `(rva, file, line)` sorted by RVA.
+    lines: Vec<(u32, String, u32)>,
+}
+
+/// Procedures and public symbols loaded from a PDB.
+#[derive(Debug, Default)]
+struct PdbSymbols {
+    /// Procedures sorted by RVA.
+    procedures: Vec<Procedure>,
+    /// Public symbols `(rva, name)` sorted by RVA, used when a module has no
+    /// private symbols.
+    publics: Vec<(u32, String)>,
+}
+
+impl PdbSymbols {
+    /// Load all procedures, line records and public symbols from a PDB file.
+    fn open(path: &Path) -> Result<Self, SymbolicationError> {
+        let file = fs::File::open(path)?;
+        Self::load(file).map_err(|e| SymbolicationError::ParseError(format!("Invalid PDB: {}", e)))
+    }
+
+    fn load(file: fs::File) -> pdb::Result<Self> {
+        let mut pdb = PDB::open(file)?;
+        let address_map = pdb.address_map()?;
+        let string_table = pdb.string_table().ok();
+        let mut symbols = PdbSymbols::default();
+
+        let dbi = pdb.debug_information()?;
+        let mut modules = dbi.modules()?;
+        while let Some(module) = modules.next()? {
+            let Some(info) = pdb.module_info(&module)? else {
+                continue;
+            };
+            let program = info.line_program()?;
+            let mut module_symbols = info.symbols()?;
+
+            while let Some(symbol) = module_symbols.next()? {
+                let Ok(SymbolData::Procedure(proc)) = symbol.parse() else {
+                    continue;
+                };
+                let Some(rva) = proc.offset.to_rva(&address_map) else {
+                    continue;
+                };
+
+                let mut lines = Vec::new();
+                let mut line_iter = program.lines_for_symbol(proc.offset);
+                while let Some(line) = line_iter.next()? {
+                    let Some(line_rva) = line.offset.to_rva(&address_map) else {
+                        continue;
+                    };
+                    let file = program
+                        .get_file_info(line.file_index)
+                        .ok()
+                        .zip(string_table.as_ref())
+                        .and_then(|(info, strings)| info.name.to_string_lossy(strings).ok())
+                        .map(|name| name.into_owned())
+                        .unwrap_or_default();
+                    lines.push((line_rva.0, file, line.line_start));
+                }
+                lines.sort_by_key(|(rva, _, _)| *rva);
+
+                symbols.procedures.push(Procedure {
+                    rva: rva.0,
+                    len: proc.len,
+                    name: proc.name.to_string().into_owned(),
+                    lines,
+                });
+            }
+        }
+
+        let globals = pdb.global_symbols()?;
+        let mut iter = globals.iter();
+        while let Some(symbol) = iter.next()? {
+            if let Ok(SymbolData::Public(public)) = symbol.parse() {
+                if public.function {
+                    if let Some(rva) = public.offset.to_rva(&address_map) {
+                        symbols.publics.push((rva.0, public.name.to_string().into_owned()));
+                    }
+                }
+            }
+        }
+
+        symbols.procedures.sort_by_key(|p| p.rva);
+        symbols.publics.sort_by_key(|(rva, _)| *rva);
+        Ok(symbols)
+    }
+
+    /// Resolve an RVA to a procedure and source line.
+    fn lookup(&self, rva: u32) -> Option<NativeLocation> {
+        let index = self.procedures.partition_point(|p| p.rva <= rva);
+        let procedure = index
+            .checked_sub(1)
+            .map(|i| &self.procedures[i])
+            .filter(|p| rva < p.rva.saturating_add(p.len.max(1)));
+
+        if let Some(procedure) = procedure {
+            let line_index = procedure.lines.partition_point(|(line_rva, _, _)| *line_rva <= rva);
+            let line = line_index.checked_sub(1).map(|i| &procedure.lines[i]);
+            return Some(NativeLocation {
+                function: Some(procedure.name.clone()),
+                file: line.map(|(_, file, _)| file.clone()).filter(|f| !f.is_empty()),
+                line: line.map(|(_, _, line)| *line),
+                column: None,
+            });
+        }
+
+        let index = self.publics.partition_point(|(public_rva, _)| *public_rva <= rva);
+        index.checked_sub(1).map(|i| NativeLocation {
+            function: Some(self.publics[i].1.clone()),
+            file: None,
+            line: None,
+            column: None,
+        })
+    }
+}
+
+/// Windows native stack symbolicator.
+pub struct WindowsSymbolicator<'a> {
+    store: &'a MappingStore,
+    cache: Option<&'a MappingCache>,
+}
+
+impl<'a> WindowsSymbolicator<'a> {
+    /// Create a new Windows symbolicator.
+    pub fn new(store: &'a MappingStore) -> Self {
+        Self { store, cache: None }
+    }
+
+    /// Reuse parsed mappings from `cache` instead of re-reading them.
+    pub fn with_cache(mut self, cache: &'a MappingCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Symbolicate a Windows native stack trace.
+    ///
+    /// Frames from modules without a PDB (system DLLs) are kept as-is.
+    pub fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        let mapping_info = self
+            .store
+            .get_with_fallback(
+                &context.platform,
+                context.app_id.as_deref().unwrap_or("unknown"),
+                context.version.as_deref().unwrap_or("unknown"),
+            )
+            .ok_or_else(|| SymbolicationError::MappingNotFound {
+                platform: "windows".to_string(),
+                app_id: context.app_id.clone().unwrap_or_default(),
+                version: context.version.clone().unwrap_or_default(),
+            })?;
+
+        let pdb_paths = match mapping_info.path.parent() {
+            Some(dir) => find_pdbs(dir),
+            None => HashMap::new(),
+        };
+
+        let mut pdbs: HashMap<String, Option<Arc<PdbSymbols>>> = HashMap::new();
+        Ok(symbolicate_frames(stack_trace, |module, rva| {
+            let path = pdb_paths.get(module)?;
+            pdbs.entry(module.to_string())
+                .or_insert_with(|| load_mapping(self.cache, &mapping_info, path, PdbSymbols::open).ok())
+                .as_ref()?
+                .lookup(rva)
+        }))
+    }
+}
+
+impl PlatformSymbolicator for WindowsSymbolicator<'_> {
+    fn symbolicate(
+        &self,
+        stack_trace: &str,
+        context: &SymbolicationContext,
+    ) -> Result<SymbolicatedStack, SymbolicationError> {
+        WindowsSymbolicator::symbolicate(self, stack_trace, context)
+    }
+}
+
+/// Symbolicate frames with a `(module, rva)` resolver.
+fn symbolicate_frames<F>(stack_trace: &str, mut resolve: F) -> SymbolicatedStack
+where
+    F: FnMut(&str, u32) -> Option<NativeLocation>,
+{
+    // Format: "0  MyApp.exe + 0x1a2b3", "MyApp.dll+0x4c10", "MyApp!0x1a2b3"
+    let frame_re = Regex::new(
+        r"^\s*(?:#?\d+\s+)?([A-Za-z0-9_.\-]+?)(?:\.(?:exe|dll|node|sys))?\s*[+!]\s*0x([0-9a-fA-F]+)\s*$",
+    )
+    .unwrap();
+
+    let mut frames = Vec::new();
+    let mut native_index = 0;
+
+    for line in stack_trace.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some(caps) = frame_re.captures(line) else {
+            frames.push(SymbolicatedFrame::raw(line.to_string()));
+            continue;
+        };
+        let module = caps[1].to_lowercase();
+        let Ok(mut rva) = u32::from_str_radix(&caps[2], 16) else {
+            frames.push(SymbolicatedFrame::raw(line.to_string()));
+            continue;
+        };
+
+        // Caller frames hold return addresses; look up the call instruction
+        if native_index > 0 {
+            rva = rva.saturating_sub(1);
+        }
+        native_index += 1;
+
+        match resolve(&module, rva) {
+            Some(NativeLocation {
+                function: Some(function),
+                file,
+                line: line_num,
+                column,
+            }) => frames.push(SymbolicatedFrame::symbolicated(
+                line.to_string(),
+                function,
+                file,
+                line_num,
+                column,
+            )),
+            _ => frames.push(SymbolicatedFrame::raw(line.to_string())),
+        }
+    }
+
+    let symbolicated_count = frames.iter().filter(|f| f.symbolicated).count();
+    let total_count = frames.len();
+
+    SymbolicatedStack {
+        schema_version: SCHEMA_VERSION,
+        exceptions: Vec::new(),
+        raw: stack_trace.to_string(),
+        frames,
+        symbolicated_count,
+        total_count,
+    }
+}
+
+/// Find PDB files in a version directory, keyed by lowercase module name
+/// without extension (`MyApp.pdb` and `MyApp.exe.pdb` both map to `myapp`).
+fn find_pdbs(dir: &Path) -> HashMap<String, PathBuf> {
+    let mut pdbs = HashMap::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return pdbs;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_pdb = path
+            .extension()
+            .map(|e| e.eq_ignore_ascii_case("pdb"))
+            .unwrap_or(false);
+        if !is_pdb {
+            continue;
+        }
+        if let Some(stem) = path.file_stem() {
+            pdbs.insert(module_key(&stem.to_string_lossy()), path.clone());
+        }
+    }
+
+    pdbs
+}
+
+/// Lowercase module name without a binary extension.
+fn module_key(name: &str) -> String {
+    let name = name.to_lowercase();
+    for ext in [".exe", ".dll", ".node", ".sys"] {
+        if let Some(stem) = name.strip_suffix(ext) {
+            return stem.to_string();
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_symbols() -> PdbSymbols {
+        PdbSymbols {
+            procedures: vec![
+                Procedure {
+                    rva: 0x1000,
+                    len: 0x40,
+                    name: "MyApp::crash".to_string(),
+                    lines: vec![
+                        (0x1000, "C:\\src\\app.cpp".to_string(), 10),
+                        (0x1010, "C:\\src\\app.cpp".to_string(), 12),
+                    ],
+                },
+                Procedure {
+                    rva: 0x2000,
+                    len: 0x20,
+                    name: "main".to_string(),
+                    lines: vec![(0x2000, "C:\\src\\main.cpp".to_string(), 3)],
+                },
+            ],
+            publics: vec![(0x3000, "?exported@@YAXXZ".to_string())],
+        }
+    }
+
+    #[test]
+    fn test_lookup_procedures_and_lines() {
+        let symbols = sample_symbols();
+
+        let location = symbols.lookup(0x1014).unwrap();
+        assert_eq!(location.function.as_deref(), Some("MyApp::crash"));
+        assert_eq!(location.file.as_deref(), Some("C:\\src\\app.cpp"));
+        assert_eq!(location.line, Some(12));
+
+        // Past the end of a procedure falls back to public symbols
+        assert!(symbols.lookup(0x1050).is_none());
+        let public = symbols.lookup(0x3008).unwrap();
+        assert_eq!(public.function.as_deref(), Some("?exported@@YAXXZ"));
+        assert_eq!(public.line, None);
+    }
+
+    #[test]
+    fn test_symbolicate_frames() {
+        let symbols = sample_symbols();
+        let trace = "\
+Crash in thread 0
+0  MyApp.exe + 0x1014
+1  MyApp.exe+0x2005
+2  ntdll.dll + 0x9a1f4";
+
+        let result = symbolicate_frames(trace, |module, rva| {
+            (module == "myapp").then(|| symbols.lookup(rva)).flatten()
+        });
+
+        assert_eq!(result.total_count, 4);
+        assert_eq!(result.symbolicated_count, 2);
+        assert_eq!(result.frames[1].display(), "MyApp::crash (C:\\src\\app.cpp:12)");
+        assert_eq!(result.frames[2].display(), "main (C:\\src\\main.cpp:3)");
+        assert!(!result.frames[3].symbolicated);
+    }
+
+    #[test]
+    fn test_find_pdbs() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("MyApp.pdb"), b"").unwrap();
+        fs::write(dir.path().join("electron.exe.pdb"), b"").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"").unwrap();
+
+        let pdbs = find_pdbs(dir.path());
+        assert_eq!(pdbs.len(), 2);
+        assert!(pdbs.contains_key("myapp"));
+        assert!(pdbs.contains_key("electron"));
+    }
+
+    #[test]
+    fn test_open_rejects_invalid_pdb() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.pdb");
+        fs::write(&path, b"not a pdb").unwrap();
+        assert!(matches!(
+            PdbSymbols::open(&path),
+            Err(SymbolicationError::ParseError(_))
+        ));
+    }
+}
@@ -0,0 +1,370 @@
+//! Interactive terminal UI for crash triage.
+//!
+//! `bugstr tui` shows a scrollable crash list next to a detail pane with the
+//! (symbolicated, when available) stack trace, and keybindings to resolve,
+//! ignore, reopen or delete crashes without the web dashboard.
+
+use crate::storage::{CrashReport, CrashStorage, GroupStatus};
+use chrono::DateTime;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Number of crashes loaded into the list.
+const CRASH_LIMIT: usize = 500;
+
+/// Number of groups loaded for status lookups.
+const GROUP_LIMIT: usize = 10_000;
+
+/// Errors from the terminal UI.
+#[derive(Debug, Error)]
+pub enum TuiError {
+    #[error("Terminal error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Database error: {0}")]
+    Storage(#[from] rusqlite::Error),
+}
+
+/// Runs the terminal UI until the user quits.
+pub fn run(storage: &CrashStorage) -> Result<(), TuiError> {
+    let mut app = App::new(storage)?;
+    let mut terminal = ratatui::try_init()?;
+
+    let result = (|| -> Result<(), TuiError> {
+        loop {
+            terminal.draw(|frame| app.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && app.handle_key(key)? {
+                    return Ok(());
+                }
+            }
+        }
+    })();
+
+    ratatui::try_restore()?;
+    result
+}
+
+/// Exception type a crash is shown with.
+fn exception_type(crash: &CrashReport) -> &str {
+    crash.exception_type.as_deref().unwrap_or("Unknown")
+}
+
+fn format_time(secs: i64) -> String {
+    DateTime::from_timestamp(secs, 0)
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Terminal UI state.
+struct App<'a> {
+    storage: &'a CrashStorage,
+    crashes: Vec<CrashReport>,
+    statuses: HashMap<String, GroupStatus>,
+    list_state: ListState,
+    detail_scroll: u16,
+    /// Symbolicated stack of the selected crash, keyed by crash id.
+    detail_stack: Option<(i64, Option<String>)>,
+    confirm_delete: bool,
+    message: Option<String>,
+}
+
+impl<'a> App<'a> {
+    fn new(storage: &'a CrashStorage) -> rusqlite::Result<Self> {
+        let mut app = Self {
+            storage,
+            crashes: Vec::new(),
+            statuses: HashMap::new(),
+            list_state: ListState::default(),
+            detail_scroll: 0,
+            detail_stack: None,
+            confirm_delete: false,
+            message: None,
+        };
+        app.refresh()?;
+        Ok(app)
+    }
+
+    /// Reloads crashes and group statuses, keeping the selection in range.
+    fn refresh(&mut self) -> rusqlite::Result<()> {
+        self.crashes = self.storage.get_recent(CRASH_LIMIT)?;
+        self.statuses = self
+            .storage
+            .get_groups(GROUP_LIMIT)?
+            .into_iter()
+            .map(|g| (g.fingerprint, g.status))
+            .collect();
+        self.detail_stack = None;
+
+        let selected = match self.list_state.selected() {
+            _ if self.crashes.is_empty() => None,
+            Some(i) => Some(i.min(self.crashes.len() - 1)),
+            None => Some(0),
+        };
+        self.list_state.select(selected);
+        Ok(())
+    }
+
+    fn selected(&self) -> Option<&CrashReport> {
+        self.list_state.selected().and_then(|i| self.crashes.get(i))
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.crashes.is_empty() {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, self.crashes.len() as isize - 1);
+        self.list_state.select(Some(next as usize));
+        self.detail_scroll = 0;
+    }
+
+    fn set_status(&mut self, status: GroupStatus) -> rusqlite::Result<()> {
+        let Some(crash) = self.selected() else {
+            return Ok(());
+        };
+        let (group, exception) = (crash.fingerprint.clone(), exception_type(crash).to_string());
+        let now = chrono::Utc::now().timestamp();
+        self.storage.set_group_status(&group, status, None, now)?;
+        self.statuses.insert(group.clone(), status);
+        self.message = Some(format!("{} ({}) marked {}", exception, group, status.as_str()));
+        Ok(())
+    }
+
+    fn delete_selected(&mut self) -> rusqlite::Result<()> {
+        let Some(id) = self.selected().map(|c| c.id) else {
+            return Ok(());
+        };
+        self.storage.delete_by_id(id)?;
+        self.message = Some(format!("Deleted crash #{}", id));
+        self.refresh()
+    }
+
+    /// Handles a key press. Returns `true` when the user quits.
+    fn handle_key(&mut self, key: KeyEvent) -> rusqlite::Result<bool> {
+        if self.confirm_delete {
+            self.confirm_delete = false;
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.delete_selected()?;
+            } else {
+                self.message = Some("Delete cancelled".to_string());
+            }
+            return Ok(false);
+        }
+
+        self.message = None;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Home => self.move_selection(isize::MIN / 2),
+            KeyCode::End => self.move_selection(isize::MAX / 2),
+            KeyCode::PageDown => self.detail_scroll = self.detail_scroll.saturating_add(10),
+            KeyCode::PageUp => self.detail_scroll = self.detail_scroll.saturating_sub(10),
+            KeyCode::Char('r') => self.set_status(GroupStatus::Resolved)?,
+            KeyCode::Char('i') => self.set_status(GroupStatus::Ignored)?,
+            KeyCode::Char('o') => self.set_status(GroupStatus::Open)?,
+            KeyCode::Char('d') if self.selected().is_some() => {
+                self.confirm_delete = true;
+            }
+            KeyCode::Char('g') => {
+                self.refresh()?;
+                self.message = Some(format!("Loaded {} crashes", self.crashes.len()));
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn status_of(&self, crash: &CrashReport) -> GroupStatus {
+        self.statuses.get(&crash.fingerprint).copied().unwrap_or_default()
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(main);
+
+        let items: Vec<ListItem> = self
+            .crashes
+            .iter()
+            .map(|crash| {
+                let status = match self.status_of(crash) {
+                    GroupStatus::Open => Span::raw(""),
+                    GroupStatus::Resolved => Span::styled(" resolved", Style::new().fg(Color::Green)),
+                    GroupStatus::Ignored => Span::styled(" ignored", Style::new().fg(Color::DarkGray)),
+                    GroupStatus::Regressed => Span::styled(" regressed", Style::new().fg(Color::Red)),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("#{:<5} ", crash.id), Style::new().fg(Color::Cyan)),
+                    Span::styled(format_time(crash.received_at), Style::new().fg(Color::DarkGray)),
+                    Span::raw(" "),
+                    Span::styled(exception_type(crash).to_string(), Style::new().fg(Color::Red)),
+                    status,
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Crashes ({}) ", self.crashes.len())),
+            )
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▸ ");
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        let detail = self.detail_lines();
+        let paragraph = Paragraph::new(detail)
+            .block(Block::default().borders(Borders::ALL).title(" Detail "))
+            .wrap(Wrap { trim: false })
+            .scroll((self.detail_scroll, 0));
+        frame.render_widget(paragraph, detail_area);
+
+        let footer_text = if self.confirm_delete {
+            Line::styled("Delete this crash? (y/N)", Style::new().fg(Color::Yellow))
+        } else if let Some(message) = &self.message {
+            Line::raw(message.clone())
+        } else {
+            Line::styled(
+                "j/k move  PgUp/PgDn scroll  r resolve  i ignore  o reopen  d delete  g refresh  q quit",
+                Style::new().fg(Color::DarkGray),
+            )
+        };
+        frame.render_widget(Paragraph::new(footer_text), footer);
+    }
+
+    fn detail_lines(&mut self) -> Vec<Line<'static>> {
+        let Some(crash) = self.selected().cloned() else {
+            return vec![Line::raw("No crashes")];
+        };
+
+        if self.detail_stack.as_ref().map(|(id, _)| *id) != Some(crash.id) {
+            let stack = self.storage.get_symbolicated_stack(crash.id).ok().flatten();
+            self.detail_stack = Some((crash.id, stack));
+        }
+        let symbolicated = self.detail_stack.as_ref().and_then(|(_, s)| s.clone());
+
+        let label = Style::new().fg(Color::Cyan);
+        let mut lines = vec![Line::from(Span::styled(
+            exception_type(&crash).to_string(),
+            Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ))];
+        let mut field = |name: &str, value: Option<String>| {
+            if let Some(value) = value.filter(|v| !v.is_empty()) {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:<12}", name), label),
+                    Span::raw(value),
+                ]));
+            }
+        };
+        field("Crash ID", Some(crash.id.to_string()));
+        field("Status", Some(self.status_of(&crash).as_str().to_string()));
+        field("App", crash.app_name.clone());
+        field("Version", crash.app_version.clone());
+        field("Release", crash.release.clone());
+        field("Environment", crash.environment.clone());
+        field("Occurred", Some(format_time(crash.created_at)));
+        field("Received", Some(format_time(crash.received_at)));
+        field("Message", crash.message.clone());
+
+        let (title, stack) = match symbolicated {
+            Some(stack) => ("Stack trace (symbolicated)", Some(stack)),
+            None => ("Stack trace", crash.stack_trace.clone()),
+        };
+        if let Some(stack) = stack {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(title, label.add_modifier(Modifier::BOLD)));
+            lines.extend(stack.lines().map(|l| Line::raw(l.to_string())));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::KeyModifiers;
+    use ratatui::Terminal;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn storage_with_crashes() -> CrashStorage {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        for (i, exc) in ["NullPointerException", "TimeoutError"].iter().enumerate() {
+            storage
+                .insert(&CrashReport {
+                    id: 0,
+                    event_id: format!("event{}", i),
+                    sender_pubkey: "pubkey".to_string(),
+                    received_at: 1000 + i as i64,
+                    created_at: 999,
+                    app_name: None,
+                    app_version: None,
+                    exception_type: Some(exc.to_string()),
+                    message: Some(format!("{} happened", exc)),
+                    stack_trace: Some("at a.b(Unknown)".to_string()),
+                    raw_content: "raw".to_string(),
+                    environment: None,
+                    release: None,
+                    process_type: None,
+                    route: None,
+                    fingerprint: String::new(),
+                })
+                .unwrap();
+        }
+        storage
+    }
+
+    #[test]
+    fn test_triage_keys() {
+        let storage = storage_with_crashes();
+        let mut app = App::new(&storage).unwrap();
+
+        // Newest first
+        assert_eq!(exception_type(app.selected().unwrap()), "TimeoutError");
+        app.handle_key(key('j')).unwrap();
+        assert_eq!(exception_type(app.selected().unwrap()), "NullPointerException");
+        app.handle_key(key('j')).unwrap();
+        assert_eq!(app.list_state.selected(), Some(1));
+
+        app.handle_key(key('r')).unwrap();
+        let groups = storage.get_groups(10).unwrap();
+        let npe = groups.iter().find(|g| g.exception_type == "NullPointerException").unwrap();
+        assert_eq!(npe.status, GroupStatus::Resolved);
+
+        // Delete needs confirmation
+        app.handle_key(key('d')).unwrap();
+        app.handle_key(key('n')).unwrap();
+        assert_eq!(storage.count().unwrap(), 2);
+        app.handle_key(key('d')).unwrap();
+        app.handle_key(key('y')).unwrap();
+        assert_eq!(storage.count().unwrap(), 1);
+        assert_eq!(app.list_state.selected(), Some(0));
+
+        assert!(app.handle_key(key('q')).unwrap());
+    }
+
+    #[test]
+    fn test_draw() {
+        let storage = storage_with_crashes();
+        let mut app = App::new(&storage).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("Crashes (2)"));
+        assert!(text.contains("TimeoutError happened"));
+    }
+}
@@ -3,19 +3,28 @@
 //! Provides a REST API and serves an embedded static dashboard.
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, Request, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::{self, Next},
     response::{Html, IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post},
     Json, Router,
 };
 use rust_embed::Embed;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
 use tower_http::cors::CorsLayer;
 
-use crate::storage::{CrashGroup, CrashReport, CrashStorage};
-use crate::symbolication::{Platform, Symbolicator, SymbolicationContext};
+use crate::hooks::Hooks;
+use crate::notify::rules::{Rule, RuleCondition, DEFAULT_COOLDOWN_MINUTES};
+use crate::sentry;
+use crate::storage::{CrashGroup, CrashReport, CrashStorage, GroupStatus};
+use crate::symbolication::{
+    Platform, PrunePolicy, SymbolicatedStack, Symbolicator, SymbolicationContext,
+    SymbolicationError, SymbolicationMetrics,
+};
 
 /// Embedded static files for the dashboard.
 #[derive(Embed)]
@@ -26,8 +35,55 @@ struct Assets;
 pub struct AppState {
     pub storage: Mutex<CrashStorage>,
     pub symbolicator: Option<Arc<Symbolicator>>,
+    /// Reject requests that modify data (see [`reject_mutations`]).
+    pub read_only: bool,
+    /// Accept crashes from Sentry SDKs at `/api/ingest/sentry`.
+    pub sentry: Option<SentryIngest>,
+    /// Plugin hooks run by [`store_crash`](Self::store_crash) and on alerts.
+    pub hooks: Hooks,
+    /// Wakes the task retrying chunked reports after
+    /// `POST /api/chunks/{root_hash}/retry` makes one due.
+    pub chunk_retries: Option<mpsc::Sender<()>>,
+}
+
+impl AppState {
+    /// Runs the ingest hooks for a received crash and stores it. Returns
+    /// the stored report with its ID, or `None` for duplicates and reports
+    /// a hook dropped.
+    pub async fn store_crash(&self, mut report: CrashReport) -> rusqlite::Result<Option<CrashReport>> {
+        self.hooks.report_received(&report);
+        if !self.hooks.before_store(&mut report) {
+            return Ok(None);
+        }
+
+        let storage = self.storage.lock().await;
+        let Some(id) = storage.insert(&report)? else {
+            return Ok(None);
+        };
+        report.id = id;
+        report.fingerprint = report.group_key();
+        if storage.group_count(&report.fingerprint)? == 1 {
+            self.hooks.group_created(&report.fingerprint, &report);
+        }
+        Ok(Some(report))
+    }
 }
 
+/// Settings for the Sentry-compatible ingest endpoint.
+pub struct SentryIngest {
+    /// DSN public key that SDKs must authenticate with.
+    pub key: String,
+    /// Receives converted crashes for storage.
+    pub crashes: mpsc::Sender<CrashReport>,
+}
+
+/// Largest mapping file accepted by `POST /api/mappings`. Mappings of large
+/// apps (ProGuard maps, dSYMs, PDBs) are well beyond axum's 2 MB default.
+const MAX_MAPPING_UPLOAD: usize = 512 * 1024 * 1024;
+
+/// Endpoints that accept POST without modifying any data.
+const READ_ONLY_POST_ALLOWLIST: &[&str] = &["/api/symbolicate"];
+
 /// Creates the web server router.
 pub fn create_router(state: Arc<AppState>) -> Router {
     // CORS: Only allow same-origin requests by default.
@@ -35,24 +91,86 @@ pub fn create_router(state: Arc<AppState>) -> Router {
     // requests are not needed. This is more secure than allowing Any.
     let cors = CorsLayer::new();
 
-    Router::new()
+    let mut router = Router::new()
         // API routes
         .route("/api/crashes", get(get_crashes))
         .route("/api/crashes/{id}", get(get_crash))
+        .route("/api/crashes/{id}/breadcrumbs", get(get_breadcrumbs))
+        .route("/api/crashes/{id}/attachments", get(get_attachments))
+        .route("/api/crashes/{id}/attachments/{name}", get(get_attachment))
         .route("/api/groups", get(get_groups))
+        .route("/api/groups/{fingerprint}/resolve", post(resolve_group))
+        .route("/api/groups/{fingerprint}/ignore", post(ignore_group))
+        .route("/api/groups/{fingerprint}/reopen", post(reopen_group))
         .route("/api/stats", get(get_stats))
         .route("/api/symbolicate", post(symbolicate_stack))
+        .route(
+            "/api/mappings",
+            post(upload_mapping).layer(DefaultBodyLimit::max(MAX_MAPPING_UPLOAD)),
+        )
+        .route("/api/mappings/prune", post(prune_mappings))
+        .route("/api/rules", get(get_rules).post(add_rule))
+        .route("/api/rules/{id}", delete(delete_rule))
+        .route("/api/chunks", get(get_chunks))
+        .route("/api/chunks/{root_hash}/retry", post(retry_chunks))
+        .route("/metrics", get(get_metrics))
         // Static files and SPA fallback
         .route("/", get(index_handler))
         .route("/{*path}", get(static_handler))
-        .layer(cors)
-        .with_state(state)
+        .layer(middleware::from_fn_with_state(state.clone(), reject_mutations));
+
+    if state.sentry.is_some() {
+        // Bare endpoint, plus the paths SDKs derive from a DSN of
+        // http://<key>@<host>/api/ingest/sentry/<project>. Added after the
+        // read-only layer: ingestion, authenticated by the DSN key, goes on
+        // like relay ingestion when the dashboard is read-only.
+        router = router
+            .route("/api/ingest/sentry", post(sentry_ingest))
+            .route("/api/ingest/sentry/api/{project}/envelope/", post(sentry_ingest))
+            .route("/api/ingest/sentry/api/{project}/store/", post(sentry_ingest));
+    }
+
+    router.layer(cors).with_state(state)
+}
+
+/// In read-only mode, rejects every request that could modify data with
+/// `403 Forbidden`. Only GET/HEAD/OPTIONS and allowlisted POST endpoints
+/// pass, so new mutation endpoints are covered without opting in. Sentry
+/// ingestion is routed outside this layer and keeps storing crashes.
+async fn reject_mutations(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method();
+    let safe = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || (*method == Method::POST && READ_ONLY_POST_ALLOWLIST.contains(&request.uri().path()));
+
+    if state.read_only && !safe {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({ "error": "Dashboard is read-only" })),
+        )
+            .into_response();
+    }
+
+    next.run(request).await
 }
 
-/// GET /api/crashes - List recent crash reports
-async fn get_crashes(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+/// GET /api/crashes - List recent crash reports, optionally `?status=` of
+/// their group
+async fn get_crashes(
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<StatusFilter>,
+) -> impl IntoResponse {
+    let status = match filter.status() {
+        Ok(status) => status,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e }))).into_response()
+        }
+    };
     let storage = state.storage.lock().await;
-    match storage.get_recent(100) {
+    match storage.get_recent_with_status(100, status) {
         Ok(crashes) => Json(crashes.into_iter().map(CrashJson::from).collect::<Vec<_>>()).into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
@@ -71,24 +189,145 @@ async fn get_crash(
     }
 }
 
-/// GET /api/groups - Get crash groups by exception type
-async fn get_groups(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+/// GET /api/crashes/:id/breadcrumbs - Events recorded before a crash
+async fn get_breadcrumbs(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let storage = state.storage.lock().await;
+    match storage.get_breadcrumbs(id) {
+        Ok(breadcrumbs) => Json(breadcrumbs).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// GET /api/crashes/:id/attachments - List the files sent with a crash
+async fn get_attachments(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
     let storage = state.storage.lock().await;
-    match storage.get_groups(50) {
+    match storage.get_attachments(id) {
+        Ok(attachments) => Json(attachments).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// GET /api/crashes/:id/attachments/:name - Download a file sent with a crash
+async fn get_attachment(
+    State(state): State<Arc<AppState>>,
+    Path((id, name)): Path<(i64, String)>,
+) -> impl IntoResponse {
+    let storage = state.storage.lock().await;
+    match storage.get_attachment(id, &name) {
+        Ok(Some(attachment)) => {
+            // Always download: attachments come from crashing clients and
+            // must not render as pages on the dashboard's origin
+            let filename = attachment.name.replace(['"', '\\', '\r', '\n'], "_");
+            (
+                [
+                    (header::CONTENT_TYPE, attachment.mime_type),
+                    (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+                    (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+                ],
+                attachment.data,
+            )
+                .into_response()
+        }
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// GET /api/groups - Get crash groups by fingerprint, optionally `?status=`
+async fn get_groups(
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<StatusFilter>,
+) -> impl IntoResponse {
+    let status = match filter.status() {
+        Ok(status) => status,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e }))).into_response()
+        }
+    };
+    let storage = state.storage.lock().await;
+    match storage.get_groups_with_status(50, status) {
         Ok(groups) => Json(groups.into_iter().map(GroupJson::from).collect::<Vec<_>>()).into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
+/// POST /api/groups/:fingerprint/resolve - Mark a group resolved, optionally
+/// `in_release`
+async fn resolve_group(
+    State(state): State<Arc<AppState>>,
+    Path(fingerprint): Path<String>,
+    request: Option<Json<ResolveRequest>>,
+) -> impl IntoResponse {
+    let in_release = request.and_then(|Json(r)| r.in_release);
+    set_group_status(&state, &fingerprint, GroupStatus::Resolved, in_release.as_deref()).await
+}
+
+/// POST /api/groups/:fingerprint/ignore - Mark a group ignored
+async fn ignore_group(
+    State(state): State<Arc<AppState>>,
+    Path(fingerprint): Path<String>,
+) -> impl IntoResponse {
+    set_group_status(&state, &fingerprint, GroupStatus::Ignored, None).await
+}
+
+/// POST /api/groups/:fingerprint/reopen - Mark a group open again
+async fn reopen_group(
+    State(state): State<Arc<AppState>>,
+    Path(fingerprint): Path<String>,
+) -> impl IntoResponse {
+    set_group_status(&state, &fingerprint, GroupStatus::Open, None).await
+}
+
+async fn set_group_status(
+    state: &AppState,
+    fingerprint: &str,
+    status: GroupStatus,
+    in_release: Option<&str>,
+) -> Response {
+    let storage = state.storage.lock().await;
+    let now = chrono::Utc::now().timestamp();
+    match storage.set_group_status(fingerprint, status, in_release, now) {
+        Ok(true) => Json(serde_json::json!({
+            "fingerprint": fingerprint,
+            "status": status.as_str(),
+            "resolved_in_release": in_release.filter(|_| status == GroupStatus::Resolved),
+        }))
+        .into_response(),
+        Ok(false) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
 /// GET /api/stats - Get dashboard statistics
 async fn get_stats(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let storage = state.storage.lock().await;
     match storage.count() {
-        Ok(total) => Json(StatsJson { total_crashes: total }).into_response(),
+        Ok(total) => Json(StatsJson {
+            total_crashes: total,
+            read_only: state.read_only,
+            symbolication: state.symbolicator.as_ref().map(|s| s.metrics()),
+        })
+        .into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
+/// GET /metrics - Symbolication metrics in the Prometheus text format
+async fn get_metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let body = state
+        .symbolicator
+        .as_ref()
+        .map(|s| s.metrics().to_prometheus())
+        .unwrap_or_default();
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
 /// POST /api/symbolicate - Symbolicate a stack trace
 async fn symbolicate_stack(
     State(state): State<Arc<AppState>>,
@@ -109,6 +348,7 @@ async fn symbolicate_stack(
         app_id: request.app_id,
         version: request.version,
         build_id: request.build_id,
+        process_type: request.process_type,
     };
 
     // Clone Arc for move into spawn_blocking
@@ -122,21 +362,131 @@ async fn symbolicate_stack(
 
     match result {
         Ok(Ok(result)) => Json(SymbolicateResponse {
-            symbolicated_count: result.symbolicated_count,
-            total_count: result.total_count,
             percentage: result.percentage(),
             display: result.display(),
-            frames: result.frames.iter().map(|f| FrameJson {
-                raw: f.raw.clone(),
-                function: f.function.clone(),
-                file: f.file.clone(),
-                line: f.line,
-                column: f.column,
-                symbolicated: f.symbolicated,
+            stack: result,
+        }).into_response(),
+        Ok(Err(e)) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": e.to_string() }))
+        ).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("Task failed: {}", e) }))
+        ).into_response(),
+    }
+}
+
+/// POST /api/mappings/prune - Remove mappings of old versions
+async fn prune_mappings(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<PruneRequest>,
+) -> impl IntoResponse {
+    let Some(ref symbolicator) = state.symbolicator else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "error": "Symbolication not configured. Start server with --mappings option."
+            }))
+        ).into_response();
+    };
+    if request.keep_latest.is_none() && request.older_than_days.is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "Set keep_latest and/or older_than_days" }))
+        ).into_response();
+    }
+
+    let policy = PrunePolicy {
+        keep_latest: request.keep_latest,
+        older_than: request.older_than_days.map(|days| {
+            std::time::SystemTime::now()
+                - std::time::Duration::from_secs(days.saturating_mul(24 * 60 * 60))
+        }),
+    };
+    let dry_run = request.dry_run;
+    let symbolicator = Arc::clone(symbolicator);
+
+    // Removing directories is blocking I/O
+    let result = tokio::task::spawn_blocking(move || {
+        symbolicator.store().prune(&policy, dry_run)
+    }).await;
+
+    match result {
+        Ok(Ok(removed)) => Json(PruneResponse {
+            dry_run,
+            removed: removed.into_iter().map(|info| MappingJson {
+                platform: info.platform.as_str().to_string(),
+                app_id: info.app_id,
+                version: info.version,
             }).collect(),
         }).into_response(),
         Ok(Err(e)) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() }))
+        ).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("Task failed: {}", e) }))
+        ).into_response(),
+    }
+}
+
+/// POST /api/mappings - Upload a mapping file
+///
+/// The body is the file's content; `platform`, `app_id`, `version` and
+/// `filename` come from the query string. The version is re-indexed right
+/// away, so crashes can be symbolicated with it without a restart.
+async fn upload_mapping(
+    State(state): State<Arc<AppState>>,
+    Query(request): Query<UploadMappingRequest>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let Some(ref symbolicator) = state.symbolicator else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "error": "Symbolication not configured. Start server with --mappings option."
+            }))
+        ).into_response();
+    };
+    if body.is_empty() {
+        return (
             StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "Empty mapping file" }))
+        ).into_response();
+    }
+
+    let platform = Platform::from_str(&request.platform);
+    let symbolicator = Arc::clone(symbolicator);
+    let mapping = MappingJson {
+        platform: platform.as_str().to_string(),
+        app_id: request.app_id.clone(),
+        version: request.version.clone(),
+    };
+
+    // Writing and indexing the file is blocking I/O
+    let result = tokio::task::spawn_blocking(move || {
+        let store = symbolicator.store();
+        let path = store.save_mapping(
+            platform,
+            &request.app_id,
+            &request.version,
+            &request.filename,
+            &body,
+        )?;
+        store.refresh(&path)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(_)) => (StatusCode::CREATED, Json(mapping)).into_response(),
+        Ok(Err(e @ SymbolicationError::InvalidPath(_))) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": e.to_string() }))
+        ).into_response(),
+        Ok(Err(e)) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({ "error": e.to_string() }))
         ).into_response(),
         Err(e) => (
@@ -146,6 +496,144 @@ async fn symbolicate_stack(
     }
 }
 
+/// GET /api/rules - List alert rules
+async fn get_rules(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let storage = state.storage.lock().await;
+    match storage.get_rules() {
+        Ok(rules) => Json(rules).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// GET /api/chunks - List chunked reports whose chunks are still being fetched
+async fn get_chunks(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let storage = state.storage.lock().await;
+    match storage.get_pending_manifests() {
+        Ok(pending) => Json(pending).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// POST /api/chunks/:root_hash/retry - Fetch a chunked report's missing
+/// chunks now instead of waiting for its next retry
+async fn retry_chunks(
+    State(state): State<Arc<AppState>>,
+    Path(root_hash): Path<String>,
+) -> impl IntoResponse {
+    let storage = state.storage.lock().await;
+    match storage.schedule_manifest_retry(&root_hash, chrono::Utc::now().timestamp()) {
+        Ok(true) => {
+            if let Some(retries) = &state.chunk_retries {
+                let _ = retries.try_send(());
+            }
+            StatusCode::ACCEPTED.into_response()
+        }
+        // Not scheduled: either unknown or its chunks are being fetched
+        Ok(false) => match storage.get_pending_manifest(&root_hash) {
+            Ok(Some(_)) => (StatusCode::CONFLICT, "chunks are already being fetched").into_response(),
+            Ok(None) => StatusCode::NOT_FOUND.into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// POST /api/rules - Add an alert rule
+async fn add_rule(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<RuleRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = request.condition.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": e }))
+        ).into_response();
+    }
+
+    let mut rule = Rule {
+        id: 0,
+        name: request.name.unwrap_or_else(|| request.condition.describe()),
+        app_name: request.app_name,
+        condition: request.condition,
+        integration_ids: request.integration_ids,
+        cooldown_minutes: request.cooldown_minutes,
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    let storage = state.storage.lock().await;
+    match storage.add_rule(&rule) {
+        Ok(id) => {
+            rule.id = id;
+            (StatusCode::CREATED, Json(rule)).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// DELETE /api/rules/:id - Remove an alert rule
+async fn delete_rule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let storage = state.storage.lock().await;
+    match storage.delete_rule(id) {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// POST /api/ingest/sentry - Store crashes sent by Sentry SDKs
+///
+/// Accepts envelope and store payloads. The DSN key comes from the
+/// `X-Sentry-Auth` header or the `sentry_key` query parameter.
+async fn sentry_ingest(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let Some(ingest) = &state.sentry else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let error = |status: StatusCode, message: String| {
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    };
+
+    let key = headers
+        .get("x-sentry-auth")
+        .and_then(|v| v.to_str().ok())
+        .and_then(sentry::auth_key)
+        .or(query.get("sentry_key").map(String::as_str));
+    if key != Some(ingest.key.as_str()) {
+        return error(StatusCode::UNAUTHORIZED, "Invalid or missing sentry_key".to_string());
+    }
+
+    let encoding = headers.get(header::CONTENT_ENCODING).and_then(|v| v.to_str().ok());
+    let events = match sentry::decode_body(encoding, &body).and_then(|b| sentry::parse_events(&b)) {
+        Ok(events) => events,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e.to_string()),
+    };
+
+    let received_at = chrono::Utc::now().timestamp();
+    let mut ids = Vec::new();
+    for event in &events {
+        let Some(report) = sentry::crash_from_event(event, received_at) else {
+            continue;
+        };
+        if let Some(id) = event.get("event_id").and_then(|v| v.as_str()) {
+            ids.push(id.to_string());
+        }
+        if ingest.crashes.send(report).await.is_err() {
+            return error(StatusCode::SERVICE_UNAVAILABLE, "Not accepting crashes".to_string());
+        }
+    }
+
+    match ids.first() {
+        Some(id) => Json(serde_json::json!({ "id": id })).into_response(),
+        None => Json(serde_json::json!({})).into_response(),
+    }
+}
+
 /// Serve index.html
 async fn index_handler() -> impl IntoResponse {
     match Assets::get("index.html") {
@@ -191,6 +679,9 @@ struct CrashJson {
     raw_content: String,
     environment: Option<String>,
     release: Option<String>,
+    process_type: Option<String>,
+    route: Option<String>,
+    fingerprint: String,
 }
 
 impl From<CrashReport> for CrashJson {
@@ -209,27 +700,36 @@ impl From<CrashReport> for CrashJson {
             raw_content: r.raw_content,
             environment: r.environment,
             release: r.release,
+            process_type: r.process_type,
+            route: r.route,
+            fingerprint: r.fingerprint,
         }
     }
 }
 
 #[derive(serde::Serialize)]
 struct GroupJson {
+    fingerprint: String,
     exception_type: String,
     count: i64,
     first_seen: i64,
     last_seen: i64,
     app_versions: Vec<String>,
+    status: &'static str,
+    resolved_in_release: Option<String>,
 }
 
 impl From<CrashGroup> for GroupJson {
     fn from(g: CrashGroup) -> Self {
         Self {
+            fingerprint: g.fingerprint,
             exception_type: g.exception_type,
             count: g.count,
             first_seen: g.first_seen,
             last_seen: g.last_seen,
             app_versions: g.app_versions,
+            status: g.status.as_str(),
+            resolved_in_release: g.resolved_in_release,
         }
     }
 }
@@ -237,6 +737,9 @@ impl From<CrashGroup> for GroupJson {
 #[derive(serde::Serialize)]
 struct StatsJson {
     total_crashes: i64,
+    read_only: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbolication: Option<SymbolicationMetrics>,
 }
 
 // Symbolication request/response types
@@ -245,7 +748,7 @@ struct StatsJson {
 struct SymbolicateRequest {
     /// Stack trace to symbolicate
     stack_trace: String,
-    /// Platform: android, electron, flutter, rust, go, python, react-native
+    /// Platform: android, electron, flutter, rust, go, python, react-native, ios, windows, unity
     platform: String,
     /// Optional application ID
     app_id: Option<String>,
@@ -253,23 +756,640 @@ struct SymbolicateRequest {
     version: Option<String>,
     /// Optional build ID
     build_id: Option<String>,
+    /// Optional Electron process type (main, renderer, gpu, ...)
+    process_type: Option<String>,
 }
 
+/// The symbolicated stack, plus fields derived from it for display.
 #[derive(serde::Serialize)]
 struct SymbolicateResponse {
-    symbolicated_count: usize,
-    total_count: usize,
+    #[serde(flatten)]
+    stack: SymbolicatedStack,
     percentage: f64,
     display: String,
-    frames: Vec<FrameJson>,
+}
+
+// Mapping upload and retention request/response types
+
+#[derive(serde::Deserialize)]
+struct UploadMappingRequest {
+    /// Platform: android, electron, flutter, rust, go, python, react-native, ios, windows, unity
+    platform: String,
+    app_id: String,
+    version: String,
+    /// File name in the version directory, e.g. mapping.txt or main.js.map
+    filename: String,
+}
+
+#[derive(serde::Deserialize)]
+struct PruneRequest {
+    /// Keep the newest N versions of each app
+    keep_latest: Option<usize>,
+    /// Only remove versions whose mappings are older than this many days
+    older_than_days: Option<u64>,
+    /// Report what would be removed without removing it
+    #[serde(default)]
+    dry_run: bool,
 }
 
 #[derive(serde::Serialize)]
-struct FrameJson {
-    raw: String,
-    function: Option<String>,
-    file: Option<String>,
-    line: Option<u32>,
-    column: Option<u32>,
-    symbolicated: bool,
+struct PruneResponse {
+    dry_run: bool,
+    removed: Vec<MappingJson>,
+}
+
+// Group triage request types
+
+#[derive(serde::Deserialize)]
+struct StatusFilter {
+    /// open, resolved, ignored or regressed
+    status: Option<String>,
+}
+
+impl StatusFilter {
+    /// The requested status, or an error for an unknown one.
+    fn status(&self) -> Result<Option<GroupStatus>, String> {
+        match self.status.as_deref() {
+            None => Ok(None),
+            Some(name) => GroupStatus::parse(name)
+                .map(Some)
+                .ok_or_else(|| format!("Unknown status: {}", name)),
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ResolveRequest {
+    /// Release containing the fix
+    in_release: Option<String>,
+}
+
+// Alert rule request type
+
+#[derive(serde::Deserialize)]
+struct RuleRequest {
+    name: Option<String>,
+    /// App to watch; omitted for every app
+    app_name: Option<String>,
+    condition: RuleCondition,
+    /// Integrations to notify; empty for all of the app's integrations
+    #[serde(default)]
+    integration_ids: Vec<i64>,
+    #[serde(default = "default_cooldown")]
+    cooldown_minutes: i64,
+}
+
+fn default_cooldown() -> i64 {
+    DEFAULT_COOLDOWN_MINUTES
+}
+
+#[derive(serde::Serialize)]
+struct MappingJson {
+    platform: String,
+    app_id: String,
+    version: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbolication::Confidence;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    fn test_state(storage: CrashStorage) -> AppState {
+        AppState {
+            storage: Mutex::new(storage),
+            symbolicator: None,
+            read_only: false,
+            sentry: None,
+            hooks: Hooks::default(),
+            chunk_retries: None,
+        }
+    }
+
+    fn router(read_only: bool) -> Router {
+        create_router(Arc::new(AppState {
+            read_only,
+            ..test_state(CrashStorage::open_in_memory().unwrap())
+        }))
+    }
+
+    fn request(method: Method, uri: &str) -> axum::http::Request<Body> {
+        axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"platform":"android","stack_trace":""}"#))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_read_only_rejects_mutations() {
+        let app = router(true);
+
+        let res = app.clone().oneshot(request(Method::GET, "/api/stats")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = app.clone().oneshot(request(Method::DELETE, "/api/crashes/1")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = app.clone().oneshot(request(Method::POST, "/api/crashes")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = app.clone().oneshot(request(Method::POST, "/api/mappings")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        let res = app.clone().oneshot(request(Method::POST, "/api/mappings/prune")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        // Symbolication doesn't modify data; it fails only because no mappings are loaded
+        let res = app.oneshot(request(Method::POST, "/api/symbolicate")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_writable_passes_through() {
+        let res = router(false)
+            .oneshot(request(Method::DELETE, "/api/crashes/1"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn test_prune_mappings() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = crate::symbolication::MappingStore::new(dir.path());
+        for version in ["1.0.0", "2.0.0"] {
+            store
+                .save_mapping(Platform::Android, "app", version, "mapping.txt", b"a -> b:")
+                .unwrap();
+        }
+        let app = create_router(Arc::new(AppState {
+            symbolicator: Some(Arc::new(Symbolicator::new(store))),
+            ..test_state(CrashStorage::open_in_memory().unwrap())
+        }));
+        let prune = |body: &'static str| {
+            axum::http::Request::builder()
+                .method(Method::POST)
+                .uri("/api/mappings/prune")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let res = app.clone().oneshot(prune("{}")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = app.oneshot(prune(r#"{"keep_latest":1}"#)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["removed"][0]["version"], "1.0.0");
+        assert!(!dir.path().join("android/app/1.0.0").exists());
+    }
+
+    #[tokio::test]
+    async fn test_upload_mapping() {
+        let dir = tempfile::tempdir().unwrap();
+        let symbolicator = Arc::new(Symbolicator::new(crate::symbolication::MappingStore::new(
+            dir.path(),
+        )));
+        let app = create_router(Arc::new(AppState {
+            symbolicator: Some(Arc::clone(&symbolicator)),
+            ..test_state(CrashStorage::open_in_memory().unwrap())
+        }));
+        let upload = |query: &str, body: &'static [u8]| {
+            axum::http::Request::builder()
+                .method(Method::POST)
+                .uri(format!("/api/mappings?{}", query))
+                .header("content-type", "application/octet-stream")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(upload(
+                "platform=android&app_id=com.example&version=1.0.0&filename=mapping.txt",
+                b"com.example.Main -> a:\n",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["version"], "1.0.0");
+        let mappings = symbolicator.store().list();
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].app_id, "com.example");
+
+        let res = app
+            .clone()
+            .oneshot(upload(
+                "platform=android&app_id=..&version=1.0.0&filename=mapping.txt",
+                b"x",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let res = app
+            .clone()
+            .oneshot(upload(
+                "platform=android&app_id=com.example&version=1.0.1&filename=mapping.txt",
+                b"",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let res = app
+            .oneshot(upload("platform=android&app_id=com.example", b"x"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(symbolicator.store().list().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_store_crash_runs_hooks() {
+        struct Tag;
+        impl crate::hooks::Hook for Tag {
+            fn name(&self) -> &str {
+                "tag"
+            }
+            fn before_store(&self, report: &mut CrashReport) -> bool {
+                report.environment = Some("canary".to_string());
+                report.message.as_deref() != Some("ignore me")
+            }
+        }
+        let state = AppState {
+            hooks: Hooks::new().with_hook(Tag),
+            ..test_state(CrashStorage::open_in_memory().unwrap())
+        };
+        let report = |id: &str, message: &str| {
+            CrashReport::from_content(
+                id.to_string(),
+                "sender".to_string(),
+                1000,
+                1001,
+                serde_json::json!({ "message": message }).to_string(),
+            )
+        };
+
+        let stored = state.store_crash(report("a", "boom")).await.unwrap().unwrap();
+        assert_eq!(stored.environment.as_deref(), Some("canary"));
+        assert!(state.store_crash(report("a", "boom")).await.unwrap().is_none());
+        assert!(state.store_crash(report("b", "ignore me")).await.unwrap().is_none());
+        assert_eq!(state.storage.lock().await.count().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sentry_ingest() {
+        let (tx, mut rx) = mpsc::channel(10);
+        let app = create_router(Arc::new(AppState {
+            sentry: Some(SentryIngest {
+                key: "abc123".to_string(),
+                crashes: tx,
+            }),
+            ..test_state(CrashStorage::open_in_memory().unwrap())
+        }));
+        let envelope = concat!(
+            r#"{"event_id":"9ec79c33ec9942ab8353589fcb2e04dc"}"#,
+            "\n",
+            r#"{"type":"event"}"#,
+            "\n",
+            r#"{"event_id":"9ec79c33ec9942ab8353589fcb2e04dc","exception":{"values":[{"type":"ValueError","value":"bad"}]}}"#,
+            "\n"
+        );
+        let ingest = |uri: &str, auth: &str| {
+            axum::http::Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header("x-sentry-auth", auth)
+                .header("content-type", "application/x-sentry-envelope")
+                .body(Body::from(envelope))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(ingest("/api/ingest/sentry", "Sentry sentry_key=wrong"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = app
+            .oneshot(ingest(
+                "/api/ingest/sentry/api/1/envelope/",
+                "Sentry sentry_version=7, sentry_key=abc123",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["id"], "9ec79c33ec9942ab8353589fcb2e04dc");
+
+        let report = rx.recv().await.unwrap();
+        assert_eq!(report.exception_type.as_deref(), Some("ValueError"));
+        assert_eq!(report.message.as_deref(), Some("bad"));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_accepts_sentry_ingest() {
+        let (tx, mut rx) = mpsc::channel(10);
+        let app = create_router(Arc::new(AppState {
+            read_only: true,
+            sentry: Some(SentryIngest {
+                key: "abc123".to_string(),
+                crashes: tx,
+            }),
+            ..test_state(CrashStorage::open_in_memory().unwrap())
+        }));
+        let post = |uri: &str, body: &'static str| {
+            axum::http::Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header("x-sentry-auth", "Sentry sentry_key=abc123")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let event = r#"{"exception":{"values":[{"type":"ValueError","value":"bad"}]}}"#;
+        let res = app.clone().oneshot(post("/api/ingest/sentry/api/1/store/", event)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(rx.recv().await.unwrap().exception_type.as_deref(), Some("ValueError"));
+
+        let res = app.oneshot(post("/api/groups/ValueError/resolve", "")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_rules_api() {
+        let app = router(false);
+        let add = |body: &'static str| {
+            axum::http::Request::builder()
+                .method(Method::POST)
+                .uri("/api/rules")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(add(r#"{"condition": {"type": "group_rate", "count": 5, "window_minutes": 0}}"#))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = app
+            .clone()
+            .oneshot(add(r#"{"app_name": "app", "condition": {"type": "regression"}}"#))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let res = app.clone().oneshot(request(Method::GET, "/api/rules")).await.unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json[0]["name"], "regression");
+        assert_eq!(json[0]["cooldown_minutes"], DEFAULT_COOLDOWN_MINUTES);
+
+        let res = app.clone().oneshot(request(Method::DELETE, "/api/rules/1")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        let res = app.oneshot(request(Method::DELETE, "/api/rules/1")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_attachments_api() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let content = serde_json::json!({
+            "message": "boom",
+            "attachments": [{"name": "page.html", "mime_type": "text/html", "data": "PGgxPmhpPC9oMT4="}],
+        });
+        let report = CrashReport::from_content("e1".into(), "s".into(), 0, 0, content.to_string());
+        storage.insert(&report).unwrap();
+        let app = create_router(Arc::new(test_state(storage)));
+
+        let res = app
+            .clone()
+            .oneshot(request(Method::GET, "/api/crashes/1/attachments"))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{"name": "page.html", "mime_type": "text/html", "size": 11}])
+        );
+
+        let res = app
+            .clone()
+            .oneshot(request(Method::GET, "/api/crashes/1/attachments/page.html"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "text/html");
+        assert_eq!(
+            res.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"page.html\""
+        );
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"<h1>hi</h1>");
+
+        let res = app.oneshot(request(Method::GET, "/api/crashes/1/attachments/other")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_retry_chunks() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        storage
+            .save_manifest(&crate::storage::PendingManifest {
+                event_id: "wrap".to_string(),
+                sender_pubkey: "sender".to_string(),
+                created_at: 0,
+                received_at: 0,
+                manifest: crate::sender::Manifest {
+                    v: 1,
+                    root_hash: "root".to_string(),
+                    total_size: 3,
+                    chunk_ids: vec!["c0".to_string()],
+                    relays: vec![],
+                    chunk_relays: Default::default(),
+                    chunk_sizes: vec![],
+                    chunk_hashes: vec![],
+                    encoding: None,
+                },
+                chunks_fetched: 0,
+                attempts: 0,
+                next_retry_at: None,
+                last_error: None,
+            })
+            .unwrap();
+        storage.record_chunk_failure("root", "relay down", 0).unwrap();
+        let (tx, mut rx) = mpsc::channel(1);
+        let state = Arc::new(AppState {
+            chunk_retries: Some(tx),
+            ..test_state(storage)
+        });
+        let app = create_router(state.clone());
+
+        let res = app.clone().oneshot(request(Method::GET, "/api/chunks")).await.unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json[0]["manifest"]["root_hash"], "root");
+        assert_eq!(json[0]["attempts"], 1);
+
+        let res = app.clone().oneshot(request(Method::POST, "/api/chunks/root/retry")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert!(rx.try_recv().is_ok());
+        let now = chrono::Utc::now().timestamp();
+        assert_eq!(state.storage.lock().await.take_due_manifests(now).unwrap().len(), 1);
+
+        // Taken by the retry task, so already being fetched
+        let res = app.clone().oneshot(request(Method::POST, "/api/chunks/root/retry")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        let res = app.oneshot(request(Method::POST, "/api/chunks/other/retry")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_group_triage_api() {
+        let storage = CrashStorage::open_in_memory().unwrap();
+        let report = CrashReport::from_content(
+            "a".to_string(),
+            "sender".to_string(),
+            1000,
+            1001,
+            r#"{"stack": "java.lang.IllegalStateException: boom"}"#.to_string(),
+        );
+        storage.insert(&report).unwrap();
+        let app = create_router(Arc::new(test_state(storage)));
+        let post = |uri: String, body: &'static str| {
+            axum::http::Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let get = |uri: &str| {
+            let app = app.clone();
+            let uri = uri.to_string();
+            async move {
+                let res = app
+                    .oneshot(axum::http::Request::get(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = res.status();
+                let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+            }
+        };
+        let fingerprint = report.group_key();
+
+        let res = app
+            .clone()
+            .oneshot(post(
+                format!("/api/groups/{}/resolve", fingerprint),
+                r#"{"in_release": "2.1.0"}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let (_, groups) = get("/api/groups?status=resolved").await;
+        assert_eq!(groups[0]["fingerprint"], fingerprint);
+        assert_eq!(groups[0]["resolved_in_release"], "2.1.0");
+        let (_, crashes) = get("/api/crashes?status=open").await;
+        assert_eq!(crashes.as_array().unwrap().len(), 0);
+        let (status, _) = get("/api/groups?status=fixed").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Without a body, and for the other transitions
+        let ignore = axum::http::Request::post(format!("/api/groups/{}/ignore", fingerprint))
+            .body(Body::empty())
+            .unwrap();
+        let res = app.clone().oneshot(ignore).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let (_, groups) = get("/api/groups?status=ignored").await;
+        assert_eq!(groups.as_array().unwrap().len(), 1);
+        let res = app
+            .clone()
+            .oneshot(post(format!("/api/groups/{}/reopen", fingerprint), "{}"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let (_, crashes) = get("/api/crashes?status=open").await;
+        assert_eq!(crashes.as_array().unwrap().len(), 1);
+
+        let res = app
+            .oneshot(post("/api/groups/0000000000000000/resolve".to_string(), "{}"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_symbolicate_returns_stack_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = crate::symbolication::MappingStore::new(dir.path());
+        store
+            .save_mapping(
+                Platform::Android,
+                "app",
+                "1.0.0",
+                "mapping.txt",
+                b"com.example.Main -> a:\n    void run() -> b\n",
+            )
+            .unwrap();
+        let app = create_router(Arc::new(AppState {
+            symbolicator: Some(Arc::new(Symbolicator::new(store))),
+            ..test_state(CrashStorage::open_in_memory().unwrap())
+        }));
+        let symbolicate = |version: &str| {
+            let body = serde_json::json!({
+                "platform": "android",
+                "app_id": "app",
+                "version": version,
+                "stack_trace": "java.lang.RuntimeException\n\tat a.b(Unknown Source)",
+            });
+            axum::http::Request::builder()
+                .method(Method::POST)
+                .uri("/api/symbolicate")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let res = app.clone().oneshot(symbolicate("1.0.0")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let stack: SymbolicatedStack = serde_json::from_slice(&body).unwrap();
+        assert_eq!(stack.schema_version, crate::symbolication::SCHEMA_VERSION);
+        assert_eq!(stack.frames[1].function.as_deref(), Some("com.example.Main.run"));
+        assert_eq!(stack.frames[1].confidence, Some(Confidence::Exact));
+
+        // Another version's mapping resolves the frame, flagged as such
+        let res = app.clone().oneshot(symbolicate("1.1.0")).await.unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["frames"][1]["confidence"], "version_fallback");
+        assert_eq!(json["frames"][0]["confidence"], serde_json::Value::Null);
+
+        let res = app.clone().oneshot(request(Method::GET, "/metrics")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("bugstr_symbolication_duration_seconds_count{platform=\"android\"} 2"));
+        assert!(text.contains("bugstr_mapping_cache_hits_total{platform=\"android\"} 1"));
+
+        let res = app.oneshot(request(Method::GET, "/api/stats")).await.unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["symbolication"]["platforms"]["android"]["symbolicated_frames"], 2);
+    }
 }